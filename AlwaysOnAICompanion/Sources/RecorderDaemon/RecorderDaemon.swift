@@ -51,6 +51,12 @@ struct RecorderDaemon {
             exit(1)
         }
         
+        // Record scroll and trackpad gestures for the indexer's cursor tracker
+        let gestureMonitor = GestureEventMonitor(
+            logURL: config.storageURL.appendingPathComponent("gestures.jsonl")
+        )
+        gestureMonitor.start()
+        
         // Start health monitoring
         recoveryManager.startHealthMonitoring()
         
@@ -0,0 +1,170 @@
+import Foundation
+import Cocoa
+
+/// Appends native scroll-wheel and trackpad gesture samples to a JSON lines log
+/// that the keyframe indexer's cursor tracker reads (`cursor_config.gesture_log_path`)
+public class GestureEventMonitor {
+    private let logURL: URL
+    private let maxLogBytes: UInt64
+    private let queue = DispatchQueue(label: "com.alwayson.gesture-monitor")
+    private var monitor: Any?
+    private var fileHandle: FileHandle?
+
+    private let timestampFormatter: ISO8601DateFormatter = {
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        return formatter
+    }()
+
+    /// - Parameters:
+    ///   - logURL: File the samples are appended to
+    ///   - maxLogBytes: The log is truncated once it grows past this size; the indexer starts reading it from the top again
+    public init(logURL: URL, maxLogBytes: UInt64 = 10 * 1024 * 1024) {
+        self.logURL = logURL
+        self.maxLogBytes = maxLogBytes
+    }
+
+    deinit {
+        stop()
+    }
+
+    /// Start monitoring; needs the Accessibility permission to see other apps' events
+    public func start() {
+        guard monitor == nil else { return }
+
+        let mask: NSEvent.EventTypeMask = [.scrollWheel, .magnify, .rotate, .swipe]
+        monitor = NSEvent.addGlobalMonitorForEvents(matching: mask) { [weak self] event in
+            self?.record(event)
+        }
+
+        if monitor == nil {
+            print("Gesture monitor could not be installed; check the Accessibility permission")
+        } else {
+            print("Recording scroll and gesture samples to \(logURL.path)")
+        }
+    }
+
+    public func stop() {
+        if let monitor = monitor {
+            NSEvent.removeMonitor(monitor)
+            self.monitor = nil
+        }
+        queue.sync {
+            try? fileHandle?.close()
+            fileHandle = nil
+        }
+    }
+
+    private func record(_ event: NSEvent) {
+        guard var fields = GestureEventMonitor.sample(from: event) else { return }
+        fields["timestamp"] = timestampFormatter.string(from: Date())
+        guard let data = try? JSONSerialization.data(withJSONObject: fields, options: [.sortedKeys]) else { return }
+
+        queue.async { [weak self] in
+            self?.append(data + Data("\n".utf8))
+        }
+    }
+
+    private func append(_ line: Data) {
+        do {
+            let handle = try openLog()
+            if try handle.offset() > maxLogBytes {
+                try handle.truncate(atOffset: 0)
+            }
+            try handle.seekToEnd()
+            try handle.write(contentsOf: line)
+        } catch {
+            print("Failed to write gesture sample: \(error)")
+            fileHandle = nil
+        }
+    }
+
+    private func openLog() throws -> FileHandle {
+        if let handle = fileHandle {
+            return handle
+        }
+        let fileManager = FileManager.default
+        try fileManager.createDirectory(at: logURL.deletingLastPathComponent(), withIntermediateDirectories: true)
+        if !fileManager.fileExists(atPath: logURL.path) {
+            fileManager.createFile(atPath: logURL.path, contents: nil)
+        }
+        let handle = try FileHandle(forWritingTo: logURL)
+        try handle.seekToEnd()
+        fileHandle = handle
+        return handle
+    }
+
+    /// Fields of a sample in the indexer's `GestureSample` format, without the timestamp
+    static func sample(from event: NSEvent) -> [String: Any]? {
+        let kind: String
+        switch event.type {
+        case .scrollWheel: kind = "scroll"
+        case .magnify: kind = "magnify"
+        case .rotate: kind = "rotate"
+        case .swipe: kind = "swipe"
+        default: return nil
+        }
+
+        // Cocoa's origin is the bottom-left of the main display; the indexer uses top-left global coordinates
+        let location = NSEvent.mouseLocation
+        let mainHeight = NSScreen.screens.first?.frame.height ?? 0
+        // Swipes carry no phase, and only scrolls have momentum
+        let phase: NSEvent.Phase = event.type == .swipe ? [] : event.phase
+        let momentumPhase: NSEvent.Phase = event.type == .scrollWheel ? event.momentumPhase : []
+        var fields: [String: Any] = [
+            "kind": kind,
+            "x": Double(location.x),
+            "y": Double(mainHeight - location.y),
+            "phase": phaseName(phase),
+            "momentum_phase": phaseName(momentumPhase),
+            "modifiers": modifierNames(event.modifierFlags),
+        ]
+
+        if let screen = NSScreen.screens.first(where: { NSMouseInRect(location, $0.frame, false) }),
+           let displayID = screen.deviceDescription[NSDeviceDescriptionKey("NSScreenNumber")] as? CGDirectDisplayID {
+            fields["screen_id"] = Int32(bitPattern: displayID)
+        }
+
+        switch event.type {
+        case .scrollWheel:
+            fields["delta_x"] = Double(event.scrollingDeltaX)
+            fields["delta_y"] = Double(event.scrollingDeltaY)
+            fields["precise"] = event.hasPreciseScrollingDeltas
+        case .swipe:
+            fields["delta_x"] = Double(event.deltaX)
+            fields["delta_y"] = Double(event.deltaY)
+            fields["precise"] = true
+        case .magnify:
+            fields["magnification"] = Double(event.magnification)
+            fields["precise"] = true
+        case .rotate:
+            fields["rotation"] = Double(event.rotation)
+            fields["precise"] = true
+        default:
+            break
+        }
+
+        return fields
+    }
+
+    /// Name of an `NSEvent.Phase` as the indexer's `GesturePhase` spells it
+    static func phaseName(_ phase: NSEvent.Phase) -> String {
+        if phase.contains(.began) { return "began" }
+        if phase.contains(.changed) { return "changed" }
+        if phase.contains(.stationary) { return "stationary" }
+        if phase.contains(.ended) { return "ended" }
+        if phase.contains(.cancelled) { return "cancelled" }
+        if phase.contains(.mayBegin) { return "may_begin" }
+        return "none"
+    }
+
+    static func modifierNames(_ flags: NSEvent.ModifierFlags) -> [String] {
+        var names: [String] = []
+        if flags.contains(.command) { names.append("Command") }
+        if flags.contains(.option) { names.append("Option") }
+        if flags.contains(.control) { names.append("Control") }
+        if flags.contains(.shift) { names.append("Shift") }
+        if flags.contains(.function) { names.append("Function") }
+        return names
+    }
+}
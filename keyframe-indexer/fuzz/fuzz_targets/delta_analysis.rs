@@ -0,0 +1,74 @@
+//! Arbitrary OCR frames, malformed ROIs and hostile text included, through
+//! `DeltaAnalyzer`, with its events written to a scratch directory.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use chrono::{DateTime, Duration};
+use keyframe_indexer::{BoundingBox, DeltaAnalyzer, OCRResult};
+use libfuzzer_sys::fuzz_target;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+#[derive(Debug, Arbitrary)]
+struct Region {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    text: String,
+    confidence: f32,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Frame {
+    /// Time since the previous frame
+    gap_ms: u16,
+    regions: Vec<Region>,
+}
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap())
+}
+
+/// Emptied after every input, so flushed event files don't pile up
+fn scratch_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("delta_analysis_fuzz_{}", std::process::id()))
+}
+
+fuzz_target!(|frames: Vec<Frame>| {
+    let dir = scratch_dir();
+    let (ocr_dir, events_dir) = (dir.join("ocr"), dir.join("events"));
+    let Ok(mut analyzer) = DeltaAnalyzer::new(&ocr_dir.to_string_lossy(), &events_dir.to_string_lossy()) else {
+        return;
+    };
+    runtime().block_on(async {
+        let mut timestamp = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for (index, frame) in frames.iter().take(32).enumerate() {
+            timestamp += Duration::milliseconds(frame.gap_ms as i64);
+            let frame_id = format!("frame_{}", index);
+            let results: Vec<OCRResult> = frame
+                .regions
+                .iter()
+                .take(64)
+                .map(|region| OCRResult {
+                    frame_id: frame_id.clone(),
+                    roi: BoundingBox::new(region.x, region.y, region.width, region.height),
+                    text: region.text.clone(),
+                    language: "en-US".to_string(),
+                    confidence: region.confidence,
+                    processed_at: timestamp,
+                    processor: "fuzz".to_string(),
+                })
+                .collect();
+            let _ = analyzer.analyze_frame(&frame_id, results, timestamp).await;
+        }
+        analyzer.get_field_changes();
+        analyzer.get_current_field_states();
+        let _ = analyzer.finalize().await;
+    });
+    drop(analyzer);
+    let _ = std::fs::remove_dir_all(&dir);
+});
@@ -0,0 +1,34 @@
+//! Arbitrary bytes through the frame, OCR, event, correlation, audit,
+//! recovery and workflow file readers. A file the parquet crate accepts must
+//! never make our row decoding panic.
+
+#![no_main]
+
+use bytes::Bytes;
+use keyframe_indexer::correlation_parquet_writer::correlations_from_batch;
+use keyframe_indexer::error_recovery_analyzer::recovery_sequences_from_batch;
+use keyframe_indexer::event_parquet_writer::signed_events_from_batch;
+use keyframe_indexer::ocr_parquet_writer::ocr_results_from_batch;
+use keyframe_indexer::parquet_writer::frames_from_batch;
+use keyframe_indexer::query_audit::audit_entries_from_batch;
+use keyframe_indexer::workflow_chain::workflow_chains_from_batch;
+use libfuzzer_sys::fuzz_target;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(builder) = ParquetRecordBatchReaderBuilder::try_new(Bytes::copy_from_slice(data)) else {
+        return;
+    };
+    let Ok(reader) = builder.with_batch_size(256).build() else {
+        return;
+    };
+    for batch in reader.flatten() {
+        let _ = frames_from_batch(&batch);
+        let _ = ocr_results_from_batch(&batch);
+        let _ = signed_events_from_batch(&batch);
+        let _ = correlations_from_batch(&batch);
+        let _ = audit_entries_from_batch(&batch);
+        let _ = recovery_sequences_from_batch(&batch);
+        let _ = workflow_chains_from_batch(&batch);
+    }
+});
@@ -0,0 +1,45 @@
+/* C interface to the keyframe-indexer scene and event detectors.
+ *
+ * Build the static library with `make capi`. Calls take and return JSON;
+ * strings returned by the library are released with kfi_string_free. A NULL
+ * return means the call failed and kfi_last_error() describes why.
+ */
+#ifndef KEYFRAME_INDEXER_H
+#define KEYFRAME_INDEXER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define KFI_ABI_VERSION 1
+
+typedef struct KfiSceneDetector KfiSceneDetector;
+typedef struct KfiEventDetector KfiEventDetector;
+
+uint32_t kfi_abi_version(void);
+
+/* Message of the last failed call on this thread, or NULL; owned by the library */
+const char *kfi_last_error(void);
+
+void kfi_string_free(char *text);
+
+/* config_json: indexer config JSON, or NULL for defaults */
+KfiSceneDetector *kfi_scene_detector_new(const char *config_json);
+/* frame_json: {"frame_path": "...", "timestamp_ns": 0}; returns a scene change or "null" */
+char *kfi_scene_detector_process(KfiSceneDetector *detector, const char *frame_json);
+void kfi_scene_detector_free(KfiSceneDetector *detector);
+
+KfiEventDetector *kfi_event_detector_new(const char *config_json);
+/* frame_json: {"frame_id", "timestamp", "screen_width", "screen_height", "ocr_results"}; returns an event array */
+char *kfi_event_detector_process(KfiEventDetector *detector, const char *frame_json);
+/* Events held back until the recording ends; returns an event array */
+char *kfi_event_detector_finish(KfiEventDetector *detector);
+void kfi_event_detector_free(KfiEventDetector *detector);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,330 @@
+use crate::error::{IndexerError, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Kind of data access recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessOperation {
+    Query,
+    Export,
+    Decrypt,
+    Erase,
+}
+
+impl AccessOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Export => "export",
+            Self::Decrypt => "decrypt",
+            Self::Erase => "erase",
+        }
+    }
+}
+
+/// One access, chained to the entry before it by `prev_hash`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessAuditEntry {
+    /// Position in the log, from 0
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Who accessed the data: an API key id, or the user running a command
+    pub principal: String,
+    pub operation: AccessOperation,
+    /// What was accessed, e.g. the query or the exported table and file
+    pub target: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub prev_hash: String,
+    /// SHA-256 over this entry's fields and `prev_hash`
+    pub hash: String,
+}
+
+impl AccessAuditEntry {
+    fn compute_hash(&self) -> String {
+        let fields = (
+            self.seq,
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            &self.principal,
+            self.operation.as_str(),
+            &self.target,
+            self.success,
+            &self.error,
+            &self.prev_hash,
+        );
+        // A tuple serializes as a JSON array, so the encoding is unambiguous
+        let encoded = serde_json::to_vec(&fields).expect("audit fields serialize");
+        hex::encode(Sha256::digest(encoded))
+    }
+}
+
+/// Where a log stops verifying
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditBreak {
+    /// 1-based line of the first bad entry
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Outcome of checking a log's hash chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditVerification {
+    /// Entries verified before any break
+    pub entries: u64,
+    /// Hash of the last good entry; keep it elsewhere to detect a truncated log later
+    pub head: Option<String>,
+    pub broken: Option<AuditBreak>,
+}
+
+impl AuditVerification {
+    pub fn is_valid(&self) -> bool {
+        self.broken.is_none()
+    }
+}
+
+/// Append-only, hash-chained log of every query, export, decryption and
+/// erasure, one JSON object per line.
+///
+/// Editing, reordering or removing an entry breaks the chain from that entry
+/// on. Removing entries from the end can only be caught by comparing the head
+/// hash with one recorded earlier. Clones share the same file, and handles
+/// in other processes may append to it too.
+#[derive(Debug, Clone)]
+pub struct AccessAuditLog {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl AccessAuditLog {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self { path, lock: Arc::new(Mutex::new(())) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an access; `error` is `None` when it succeeded.
+    ///
+    /// The file stays exclusively locked from reading the previous hash until
+    /// the entry is written, so concurrent writers, in this process or others,
+    /// never fork the chain.
+    pub fn record(
+        &self,
+        principal: &str,
+        operation: AccessOperation,
+        target: &str,
+        error: Option<String>,
+    ) -> Result<AccessAuditEntry> {
+        let _guard = self.lock.lock().map_err(|_| IndexerError::Io(std::io::Error::other("Audit log lock poisoned")))?;
+        let mut file = OpenOptions::new().create(true).read(true).append(true).open(&self.path)?;
+        // Released when the file is closed
+        file.lock_exclusive()?;
+        // The tail is re-read under the lock, so other processes' entries are chained onto
+        let (seq, prev_hash) = match last_entry(&mut file)? {
+            Some(last) => (last.seq + 1, last.hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let mut entry = AccessAuditEntry {
+            seq,
+            timestamp: Utc::now(),
+            principal: principal.to_string(),
+            operation,
+            target: target.to_string(),
+            success: error.is_none(),
+            error,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        debug!("Audit: {} {} {}", entry.principal, operation.as_str(), entry.target);
+        Ok(entry)
+    }
+
+    /// Check every entry's hash and its link to the one before. With the head
+    /// hash of an earlier check, also check that no entry up to it was removed
+    /// from the end.
+    pub fn verify(&self, expected_head: Option<&str>) -> Result<AuditVerification> {
+        let mut verification = AuditVerification { entries: 0, head: None, broken: None };
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && expected_head.is_none() => return Ok(verification),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut prev_hash = GENESIS_HASH.to_string();
+        let mut head_seen = expected_head.is_none();
+        let mut lines = 0;
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            lines = index + 1;
+            let line = line?;
+            let broken = |reason: String| Some(AuditBreak { line: index + 1, reason });
+            let entry: AccessAuditEntry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    verification.broken = broken(format!("unreadable entry: {}", e));
+                    break;
+                }
+            };
+            if entry.seq != verification.entries {
+                verification.broken = broken(format!("expected entry {}, found {}", verification.entries, entry.seq));
+                break;
+            }
+            if entry.prev_hash != prev_hash {
+                verification.broken = broken("does not follow the entry before it".to_string());
+                break;
+            }
+            if entry.compute_hash() != entry.hash {
+                verification.broken = broken("entry was modified".to_string());
+                break;
+            }
+            head_seen |= expected_head == Some(entry.hash.as_str());
+            prev_hash = entry.hash;
+            verification.entries += 1;
+            verification.head = Some(prev_hash.clone());
+        }
+        if verification.broken.is_none() && !head_seen {
+            verification.broken = Some(AuditBreak {
+                line: lines + 1,
+                reason: "the expected head is missing; entries were removed from the end".to_string(),
+            });
+        }
+        Ok(verification)
+    }
+
+    /// Bind the log to the identity a component acts for
+    pub fn for_principal(&self, principal: &str) -> AccessAuditor {
+        AccessAuditor { log: self.clone(), principal: principal.to_string() }
+    }
+}
+
+/// An audit log together with the principal whose accesses it records
+#[derive(Debug, Clone)]
+pub struct AccessAuditor {
+    log: AccessAuditLog,
+    principal: String,
+}
+
+impl AccessAuditor {
+    pub fn record(&self, operation: AccessOperation, target: &str, error: Option<String>) -> Result<AccessAuditEntry> {
+        self.log.record(&self.principal, operation, target, error)
+    }
+
+    /// Record an operation's outcome and hand it back; an access that can't be
+    /// recorded fails
+    pub fn audited<T>(&self, operation: AccessOperation, target: &str, result: Result<T>) -> Result<T> {
+        self.record(operation, target, result.as_ref().err().map(|e| e.to_string()))?;
+        result
+    }
+
+    pub fn principal(&self) -> &str {
+        &self.principal
+    }
+}
+
+/// Last entry of a log, read from the end of the file
+fn last_entry(file: &mut File) -> Result<Option<AccessAuditEntry>> {
+    let len = file.metadata()?.len();
+    let mut window = 4096u64;
+    loop {
+        let start = len.saturating_sub(window);
+        file.seek(SeekFrom::Start(start))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        while tail.last().is_some_and(|byte| *byte == b'\n' || *byte == b'\r') {
+            tail.pop();
+        }
+        if tail.is_empty() {
+            return Ok(None);
+        }
+        match tail.iter().rposition(|byte| *byte == b'\n') {
+            Some(newline) => return Ok(Some(serde_json::from_slice(&tail[newline + 1..])?)),
+            None if start == 0 => return Ok(Some(serde_json::from_slice(&tail)?)),
+            None => window *= 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_verifies_and_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AccessAuditLog::open(dir.path().join("audit").join("access_audit.jsonl")).unwrap();
+        assert_eq!(log.verify(None).unwrap().entries, 0);
+
+        let first = log.record("alice", AccessOperation::Query, "SELECT * FROM ocr", None).unwrap();
+        assert_eq!((first.seq, first.prev_hash.as_str()), (0, GENESIS_HASH));
+        let auditor = log.for_principal("bob");
+        let failed: Result<()> = Err(IndexerError::AccessDenied("no".to_string()));
+        assert!(auditor.audited(AccessOperation::Decrypt, "ocr_1.parquet", failed).is_err());
+        // A second handle on the same file continues the chain
+        let third = AccessAuditLog::open(log.path()).unwrap().record("carol", AccessOperation::Erase, "apps Slack", None).unwrap();
+        assert_eq!(third.seq, 2);
+
+        let verification = log.verify(Some(&first.hash)).unwrap();
+        assert!(verification.is_valid());
+        assert_eq!((verification.entries, verification.head.as_deref()), (3, Some(third.hash.as_str())));
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        // Cutting entries off the end only shows against a recorded head
+        std::fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[1])).unwrap();
+        assert!(log.verify(None).unwrap().is_valid());
+        assert_eq!(log.verify(Some(&third.hash)).unwrap().broken.unwrap().line, 3);
+
+        std::fs::write(log.path(), content.replacen("\"bob\"", "\"mallory\"", 1)).unwrap();
+        let verification = log.verify(None).unwrap();
+        assert_eq!(verification.entries, 1);
+        assert_eq!(verification.broken.unwrap().line, 2);
+
+        // Dropping an entry breaks the link of the next one
+        std::fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(log.verify(None).unwrap().broken.unwrap().line, 2);
+    }
+
+    #[test]
+    fn test_separate_handles_keep_one_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access_audit.jsonl");
+        // Handles opened separately share no in-process lock, like writers in different processes
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let log = AccessAuditLog::open(&path).unwrap();
+                std::thread::spawn(move || {
+                    for query in 0..25 {
+                        log.record(&format!("writer-{}", writer), AccessOperation::Query, &format!("query {}", query), None).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let verification = AccessAuditLog::open(&path).unwrap().verify(None).unwrap();
+        assert!(verification.is_valid());
+        assert_eq!(verification.entries, 100);
+    }
+}
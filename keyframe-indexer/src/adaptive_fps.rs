@@ -0,0 +1,149 @@
+use crate::error::{IndexerError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bounds and thresholds for tuning the extraction rate to screen activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveFpsConfig {
+    pub enabled: bool,
+    pub min_fps: f32,
+    pub max_fps: f32,
+    /// Scene changes per keyframe interval at or above which the rate goes up
+    pub raise_at_density: f32,
+    /// Scene changes per keyframe interval at or below which a segment counts as idle
+    pub idle_at_density: f32,
+    /// Idle segments in a row before the rate goes down
+    pub idle_segments: u32,
+    /// Factor the rate is raised or lowered by per step
+    pub step: f32,
+}
+
+impl Default for AdaptiveFpsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_fps: 0.1,
+            max_fps: 4.0,
+            raise_at_density: 0.3,
+            idle_at_density: 0.02,
+            idle_segments: 2,
+            step: 2.0,
+        }
+    }
+}
+
+impl AdaptiveFpsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.min_fps > 0.0 && self.min_fps <= self.max_fps && self.max_fps <= 30.0) {
+            return Err(IndexerError::Config("adaptive_fps needs 0 < min_fps <= max_fps <= 30".to_string()));
+        }
+        if self.idle_at_density < 0.0 || self.raise_at_density <= self.idle_at_density {
+            return Err(IndexerError::Config(
+                "adaptive_fps needs 0 <= idle_at_density < raise_at_density".to_string(),
+            ));
+        }
+        if self.step <= 1.0 || self.idle_segments == 0 {
+            return Err(IndexerError::Config(
+                "adaptive_fps step must be above 1 and idle_segments positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DisplayRate {
+    fps: f32,
+    idle_streak: u32,
+}
+
+/// Picks each display's extraction rate from the scene-change density of its
+/// recent segments.
+///
+/// A busy segment raises the rate at once so activity is not missed; the rate
+/// only drops after `idle_segments` quiet segments in a row.
+#[derive(Debug, Clone)]
+pub struct AdaptiveFps {
+    config: AdaptiveFpsConfig,
+    initial_fps: f32,
+    displays: HashMap<i32, DisplayRate>,
+}
+
+impl AdaptiveFps {
+    /// Displays start at `initial_fps`, the configured extraction rate
+    pub fn new(config: AdaptiveFpsConfig, initial_fps: f32) -> Self {
+        let initial_fps = initial_fps.clamp(config.min_fps, config.max_fps);
+        Self { config, initial_fps, displays: HashMap::new() }
+    }
+
+    /// New bounds apply to the current rates at once
+    pub fn update_config(&mut self, config: AdaptiveFpsConfig, initial_fps: f32) {
+        for rate in self.displays.values_mut() {
+            rate.fps = rate.fps.clamp(config.min_fps, config.max_fps);
+        }
+        self.initial_fps = initial_fps.clamp(config.min_fps, config.max_fps);
+        self.config = config;
+    }
+
+    pub fn rate(&self, monitor_id: i32) -> f32 {
+        self.displays.get(&monitor_id).map_or(self.initial_fps, |rate| rate.fps)
+    }
+
+    /// Account for a processed segment; returns the display's new rate when it changed
+    pub fn observe(&mut self, monitor_id: i32, keyframes: usize, scene_changes: usize) -> Option<f32> {
+        let config = &self.config;
+        let rate = self
+            .displays
+            .entry(monitor_id)
+            .or_insert(DisplayRate { fps: self.initial_fps, idle_streak: 0 });
+        let density = scene_changes as f32 / keyframes.saturating_sub(1).max(1) as f32;
+
+        let previous = rate.fps;
+        if density >= config.raise_at_density {
+            rate.idle_streak = 0;
+            rate.fps = (rate.fps * config.step).min(config.max_fps);
+        } else if density <= config.idle_at_density {
+            rate.idle_streak += 1;
+            if rate.idle_streak >= config.idle_segments {
+                rate.idle_streak = 0;
+                rate.fps = (rate.fps / config.step).max(config.min_fps);
+            }
+        } else {
+            rate.idle_streak = 0;
+        }
+        (rate.fps != previous).then_some(rate.fps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_follows_scene_change_density() {
+        let config = AdaptiveFpsConfig { enabled: true, ..AdaptiveFpsConfig::default() };
+        let mut adaptive = AdaptiveFps::new(config, 1.0);
+
+        // Busy segments raise the rate at once, up to max_fps
+        assert_eq!(adaptive.observe(1, 61, 30), Some(2.0));
+        assert_eq!(adaptive.observe(1, 121, 60), Some(4.0));
+        assert_eq!(adaptive.observe(1, 241, 120), None);
+        // Other displays keep their own rate
+        assert_eq!(adaptive.rate(2), 1.0);
+
+        // One quiet segment is not enough; two in a row halve the rate
+        assert_eq!(adaptive.observe(1, 241, 0), None);
+        assert_eq!(adaptive.observe(1, 241, 0), Some(2.0));
+        // Moderate activity resets the idle streak
+        assert_eq!(adaptive.observe(1, 121, 0), None);
+        assert_eq!(adaptive.observe(1, 121, 12), None);
+        assert_eq!(adaptive.observe(1, 121, 0), None);
+        assert_eq!(adaptive.rate(1), 2.0);
+
+        for _ in 0..20 {
+            adaptive.observe(1, 10, 0);
+        }
+        assert_eq!(adaptive.rate(1), 0.1);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::display_timeline::TimelineEntry;
+use crate::embeddings::{FrameEmbedding, TextEmbedder};
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::form_model::FormRecord;
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Hex digits of the salted hash kept in a pseudonym
+const PSEUDONYM_DIGITS: usize = 10;
+
+/// Characters trimmed off the end of a matched URL, as they usually end the sentence
+const URL_TRAILING: &[char] = &['.', ',', ';', ':', ')', ']', '!', '?'];
+
+/// Settings for anonymized exports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnonymizerConfig {
+    /// Names replaced wherever they appear, e.g. login and display names.
+    /// Names in home directory paths are replaced without being listed.
+    pub usernames: Vec<String>,
+    /// Event metadata keys holding window titles, which are replaced whole
+    pub title_keys: Vec<String>,
+}
+
+impl Default for AnonymizerConfig {
+    fn default() -> Self {
+        let title_keys = [
+            "window_title", "win_title", "title", "current_window", "previous_window", "current_tab",
+            "previous_tab", "tab_title", "sap_screen_title", "notification_title",
+        ];
+        Self {
+            usernames: Vec::new(),
+            title_keys: title_keys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+}
+
+impl AnonymizerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.usernames.iter().any(|name| name.trim().chars().count() < 2) {
+            return Err(IndexerError::Config(
+                "anonymizer usernames must be at least 2 characters".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Replaces usernames, email addresses, window titles and URLs with stable
+/// pseudonyms such as `email-3fa9c1d20b`.
+///
+/// A pseudonym is a hash of the value salted per export session: the same
+/// value gets the same pseudonym in every dataset exported with the same salt,
+/// so rows still join up, while another salt gives unrelated pseudonyms.
+#[derive(Clone)]
+pub struct Anonymizer {
+    salt: String,
+    title_keys: Vec<String>,
+    /// URLs, email addresses, home directories and usernames as one pattern,
+    /// so text is replaced in a single pass and pseudonyms are never matched again
+    identities: Regex,
+}
+
+impl Anonymizer {
+    pub fn new(config: &AnonymizerConfig, salt: &str) -> Result<Self> {
+        config.validate()?;
+        if salt.is_empty() {
+            return Err(IndexerError::Config("anonymization salt must not be empty".to_string()));
+        }
+
+        let mut names: Vec<String> = config.usernames.iter().map(|name| regex::escape(name.trim())).collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+        // Earlier alternatives win at the same position, so an email address
+        // is replaced whole rather than the username it starts with
+        let mut pattern = String::from(concat!(
+            r#"(?P<url>(?i:\b(?:https?://|www\.)[^\s<>"']+))"#,
+            r"|(?P<email>[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,})",
+            r"|(?P<home>(?i:/Users/|/home/|[A-Z]:\\Users\\))(?P<home_user>[^/\\\s]+)",
+        ));
+        if !names.is_empty() {
+            pattern.push_str(&format!(r"|(?P<name>(?i:\b(?:{})\b))", names.join("|")));
+        }
+        let identities =
+            Regex::new(&pattern).map_err(|e| IndexerError::Config(format!("Invalid anonymizer pattern: {}", e)))?;
+
+        Ok(Self { salt: salt.to_string(), title_keys: config.title_keys.clone(), identities })
+    }
+
+    /// A fresh random salt for an export session
+    pub fn generate_salt() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+
+    /// Stable pseudonym of a value of some kind, e.g. "user" or "url"; case is ignored
+    pub fn pseudonym(&self, kind: &str, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [self.salt.as_str(), kind, &value.to_lowercase()] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{}-{}", kind, &hex::encode(hasher.finalize())[..PSEUDONYM_DIGITS])
+    }
+
+    /// Replace URLs, email addresses, home directory names and known usernames in free text
+    pub fn anonymize_text(&self, text: &str) -> String {
+        self.identities
+            .replace_all(text, |caps: &Captures| {
+                if let Some(url) = caps.name("url") {
+                    let url = url.as_str();
+                    let trimmed = url.trim_end_matches(URL_TRAILING);
+                    format!("{}{}", self.pseudonym("url", trimmed), &url[trimmed.len()..])
+                } else if let Some(email) = caps.name("email") {
+                    self.pseudonym("email", email.as_str())
+                } else if let Some(user) = caps.name("home_user") {
+                    format!("{}{}", &caps["home"], self.pseudonym("user", user.as_str()))
+                } else {
+                    self.pseudonym("user", &caps[0])
+                }
+            })
+            .into_owned()
+    }
+
+    /// Replace a whole window title, keeping empty titles empty
+    pub fn anonymize_title(&self, title: &str) -> String {
+        if title.trim().is_empty() {
+            return title.to_string();
+        }
+        self.pseudonym("title", title.trim())
+    }
+
+    /// Replace the title of an `App:Title` window navigation value, keeping
+    /// the app so app switches still line up
+    fn anonymize_window_value(&self, value: &str) -> String {
+        match value.split_once(':') {
+            Some((app, title)) => format!("{}:{}", app, self.anonymize_title(title)),
+            None => self.anonymize_title(value),
+        }
+    }
+
+    /// Replace a navigation event value: window changes hold `App:Title` and
+    /// tab changes the tab title, while app switches hold only app names
+    fn anonymize_value(&self, event_type: &EventType, target: &str, value: &str) -> String {
+        if *event_type != EventType::Navigation {
+            return self.anonymize_text(value);
+        }
+        if target.starts_with("window_") {
+            self.anonymize_window_value(value)
+        } else if target.starts_with("tab_") {
+            self.anonymize_title(value)
+        } else {
+            self.anonymize_text(value)
+        }
+    }
+
+    pub fn anonymize_events(&self, events: Vec<DetectedEvent>) -> Vec<DetectedEvent> {
+        events
+            .into_iter()
+            .map(|mut event| {
+                let (event_type, target) = (&event.event_type, &event.target);
+                event.value_from = event.value_from.map(|value| self.anonymize_value(event_type, target, &value));
+                event.value_to = event.value_to.map(|value| self.anonymize_value(event_type, target, &value));
+                event.target = self.anonymize_text(&event.target);
+                event.evidence_frames = event.evidence_frames.iter().map(|frame| self.anonymize_text(frame)).collect();
+                event.metadata = event
+                    .metadata
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = if self.title_keys.contains(&key) {
+                            self.anonymize_title(&value)
+                        } else {
+                            self.anonymize_text(&value)
+                        };
+                        (key, value)
+                    })
+                    .collect();
+                event
+            })
+            .collect()
+    }
+
+    pub fn anonymize_ocr(&self, results: Vec<OCRResult>) -> Vec<OCRResult> {
+        results
+            .into_iter()
+            .map(|mut result| {
+                result.frame_id = self.anonymize_text(&result.frame_id);
+                result.text = self.anonymize_text(&result.text);
+                result
+            })
+            .collect()
+    }
+
+    pub fn anonymize_frames(&self, frames: Vec<FrameMetadata>) -> Vec<FrameMetadata> {
+        frames
+            .into_iter()
+            .map(|mut frame| {
+                frame.path = self.anonymize_text(&frame.path);
+                frame.duplicate_of = frame.duplicate_of.map(|path| self.anonymize_text(&path));
+                frame.win_title = self.anonymize_title(&frame.win_title);
+                frame
+            })
+            .collect()
+    }
+
+    pub fn anonymize_timeline(&self, entries: Vec<TimelineEntry>) -> Vec<TimelineEntry> {
+        entries
+            .into_iter()
+            .map(|mut entry| {
+                entry.path = self.anonymize_text(&entry.path);
+                entry.win_title = self.anonymize_title(&entry.win_title);
+                entry
+            })
+            .collect()
+    }
+
+    /// Replace identities in submitted values; labels and buttons are the
+    /// form's own text and are kept
+    pub fn anonymize_form_records(&self, records: Vec<FormRecord>) -> Vec<FormRecord> {
+        records
+            .into_iter()
+            .map(|mut record| {
+                record.frame_id = self.anonymize_text(&record.frame_id);
+                for field in &mut record.fields {
+                    field.value = self.anonymize_text(&field.value);
+                }
+                record
+            })
+            .collect()
+    }
+
+    /// Replace identities in embedded text and embed the result again, as the
+    /// stored vectors were computed from the original text
+    pub fn anonymize_embeddings(
+        &self,
+        embeddings: Vec<FrameEmbedding>,
+        embedder: &dyn TextEmbedder,
+    ) -> Result<Vec<FrameEmbedding>> {
+        let texts: Vec<String> = embeddings.iter().map(|embedding| self.anonymize_text(&embedding.text)).collect();
+        let vectors = if texts.is_empty() { Vec::new() } else { embedder.embed(&texts)? };
+        Ok(embeddings
+            .into_iter()
+            .zip(texts.into_iter().zip(vectors))
+            .map(|(mut embedding, (text, vector))| {
+                embedding.frame_id = self.anonymize_text(&embedding.frame_id);
+                embedding.model = embedder.model().to_string();
+                embedding.text = text;
+                embedding.vector = vector;
+                embedding
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use crate::ocr_data::BoundingBox;
+    use chrono::Utc;
+
+    #[test]
+    fn test_pseudonyms_are_stable_per_salt_and_hide_identities() {
+        let config = AnonymizerConfig { usernames: vec!["Jane Doe".to_string()], ..AnonymizerConfig::default() };
+        let anonymizer = Anonymizer::new(&config, "session-1").unwrap();
+
+        let text = anonymizer.anonymize_text(
+            "Jane Doe <jane@example.com> opened https://example.com/inbox. Saved to /Users/jdoe/Desktop",
+        );
+        assert!(!text.contains("Jane") && !text.contains("jane@") && !text.contains("example.com/") && !text.contains("jdoe"));
+        assert!(text.starts_with(&anonymizer.pseudonym("user", "jane doe")));
+        assert!(text.contains(&format!("{}. Saved", anonymizer.pseudonym("url", "https://example.com/inbox"))));
+        assert!(text.ends_with(&format!("/Users/{}/Desktop", anonymizer.pseudonym("user", "jdoe"))));
+
+        // The same frame is the same pseudonym in every dataset
+        let frame_path = "/Users/jdoe/frames/frame_1.png";
+        let event = DetectedEvent {
+            id: "e1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::Navigation,
+            target: "window".to_string(),
+            value_from: None,
+            value_to: Some("Inbox - jane@example.com".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec![frame_path.to_string()],
+            metadata: [("window_title".to_string(), "Inbox - jane@example.com".to_string())].into(),
+        };
+        let ocr = OCRResult {
+            frame_id: frame_path.to_string(),
+            roi: BoundingBox { x: 0.0, y: 0.0, width: 1.0, height: 1.0 },
+            text: "From: jane@example.com".to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        };
+        let event = anonymizer.anonymize_events(vec![event]).remove(0);
+        let ocr = anonymizer.anonymize_ocr(vec![ocr]).remove(0);
+        assert_eq!(event.evidence_frames[0], ocr.frame_id);
+        assert_eq!(event.metadata["window_title"], anonymizer.anonymize_title("Inbox - jane@example.com"));
+        assert!(ocr.text.ends_with(&anonymizer.pseudonym("email", "jane@example.com")));
+
+        // Another session's salt gives unrelated pseudonyms
+        let other = Anonymizer::new(&config, "session-2").unwrap();
+        assert_ne!(other.pseudonym("user", "jane doe"), anonymizer.pseudonym("user", "jane doe"));
+    }
+    #[test]
+    fn test_navigation_titles_and_colliding_usernames() {
+        // Names that also occur in pseudonyms don't replace them again
+        let config = AnonymizerConfig { usernames: vec!["user".to_string(), "url".to_string()], ..AnonymizerConfig::default() };
+        let anonymizer = Anonymizer::new(&config, "session-1").unwrap();
+        let text = anonymizer.anonymize_text("user opened https://example.com as /home/url");
+        assert_eq!(
+            text,
+            format!(
+                "{} opened {} as /home/{}",
+                anonymizer.pseudonym("user", "user"),
+                anonymizer.pseudonym("url", "https://example.com"),
+                anonymizer.pseudonym("user", "url")
+            )
+        );
+
+        let event = DetectedEvent {
+            id: "e1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::Navigation,
+            target: "window_Mail_42".to_string(),
+            value_from: Some("Mail:Inbox - jane@example.com".to_string()),
+            value_to: Some("Mail:Re: Offer for Jane".to_string()),
+            confidence: 0.9,
+            evidence_frames: Vec::new(),
+            metadata: [
+                ("current_app".to_string(), "Mail".to_string()),
+                ("current_window".to_string(), "Re: Offer for Jane".to_string()),
+                ("previous_window".to_string(), "Inbox - jane@example.com".to_string()),
+            ]
+            .into(),
+        };
+        let event = anonymizer.anonymize_events(vec![event]).remove(0);
+        assert_eq!(event.value_to, Some(format!("Mail:{}", anonymizer.anonymize_title("Re: Offer for Jane"))));
+        assert_eq!(event.value_from, Some(format!("Mail:{}", anonymizer.anonymize_title("Inbox - jane@example.com"))));
+        assert_eq!(event.metadata["current_window"], anonymizer.anonymize_title("Re: Offer for Jane"));
+        assert_eq!(event.metadata["previous_window"], anonymizer.anonymize_title("Inbox - jane@example.com"));
+        assert_eq!(event.metadata["current_app"], "Mail");
+    }
+}
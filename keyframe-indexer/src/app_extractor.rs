@@ -0,0 +1,96 @@
+use crate::error::Result;
+use crate::event_detector::DetectedEvent;
+use crate::ide::{IdeConfig, IdeExtractor};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::sap_gui::{SapGuiConfig, SapGuiExtractor};
+use crate::terminal::{TerminalConfig, TerminalExtractor};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Application-specific extractors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppExtractorsConfig {
+    pub enabled: bool,
+    pub sap_gui: SapGuiConfig,
+    pub terminal: TerminalConfig,
+    pub ide: IdeConfig,
+}
+
+impl Default for AppExtractorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sap_gui: SapGuiConfig::default(),
+            terminal: TerminalConfig::default(),
+            ide: IdeConfig::default(),
+        }
+    }
+}
+
+/// A keyframe as seen by an app extractor
+pub struct AppFrame<'a> {
+    pub metadata: &'a FrameMetadata,
+    pub timestamp: DateTime<Utc>,
+    pub ocr_results: &'a [OCRResult],
+}
+
+/// Structured extraction for one application.
+///
+/// An extractor sees every keyframe of the apps it handles, in order, together
+/// with the generic events detected on it. It may add typed events and rewrite
+/// or drop generic ones it understands better.
+pub trait AppExtractor: Send {
+    fn name(&self) -> &str;
+
+    /// Whether frames of this app and window are handled by this extractor
+    fn handles(&self, frame: &FrameMetadata) -> bool;
+
+    /// Extract from one frame; `events` holds the frame's generic events
+    fn extract(&mut self, frame: &AppFrame<'_>, events: &mut Vec<DetectedEvent>) -> Result<()>;
+}
+
+/// Extractors by application; the first one handling a frame's app gets it
+#[derive(Default)]
+pub struct AppExtractorRegistry {
+    extractors: Vec<Box<dyn AppExtractor>>,
+}
+
+impl AppExtractorRegistry {
+    /// Registry with the built-in extractors enabled in `config`
+    pub fn from_config(config: &AppExtractorsConfig) -> Result<Self> {
+        let mut registry = Self::default();
+        if config.enabled && config.sap_gui.enabled {
+            registry.register(Box::new(SapGuiExtractor::new(config.sap_gui.clone())?));
+        }
+        if config.enabled && config.terminal.enabled {
+            registry.register(Box::new(TerminalExtractor::new(config.terminal.clone())?));
+        }
+        if config.enabled && config.ide.enabled {
+            registry.register(Box::new(IdeExtractor::new(config.ide.clone(), config.terminal.clone())?));
+        }
+        Ok(registry)
+    }
+
+    /// Add an extractor; it is consulted after those registered before it
+    pub fn register(&mut self, extractor: Box<dyn AppExtractor>) {
+        self.extractors.push(extractor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extractors.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.extractors.iter().map(|e| e.name()).collect()
+    }
+
+    /// Run the extractor for the frame's app, if any, over the frame's events
+    pub fn extract(&mut self, frame: &AppFrame<'_>, events: &mut Vec<DetectedEvent>) -> Result<()> {
+        match self.extractors.iter_mut().find(|e| e.handles(frame.metadata)) {
+            Some(extractor) => extractor.extract(frame, events),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use crate::error::{IndexerError, Result};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::debug;
+
+/// Speech detection on segment audio and meeting detection from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioAnalyzerConfig {
+    /// Off by default; decoding each segment's audio needs ffmpeg and takes time
+    pub enabled: bool,
+    /// Rate audio is resampled to before speech detection
+    pub sample_rate: u32,
+    /// Length of the windows speech is detected in
+    pub frame_ms: u32,
+    /// Quietest window level, in dBFS, that can count as speech
+    pub speech_threshold_db: f32,
+    /// How far above the segment's noise floor, in dB, speech must be
+    pub noise_margin_db: f32,
+    /// Shorter bursts of sound are ignored
+    pub min_speech_ms: u32,
+    /// Pauses in speech shorter than this are bridged
+    pub hangover_ms: u32,
+    /// Apps that hold calls, matched against the frontmost app
+    pub meeting_apps: Vec<String>,
+    /// Window title fragments of calls in other apps, e.g. browser tabs
+    pub meeting_titles: Vec<String>,
+    /// Share of a meeting app's time that must be speech for it to count as a meeting
+    pub min_speech_ratio: f32,
+    /// Meetings closer together than this are merged
+    pub merge_gap_secs: i64,
+    /// Drop the OCR text of keyframes captured during meetings, so no text
+    /// events are detected from them
+    pub exclude_from_detail: bool,
+}
+
+impl Default for AudioAnalyzerConfig {
+    fn default() -> Self {
+        let names = |list: &[&str]| -> Vec<String> { list.iter().map(|name| name.to_string()).collect() };
+        Self {
+            enabled: false,
+            sample_rate: 16_000,
+            frame_ms: 30,
+            speech_threshold_db: -50.0,
+            noise_margin_db: 10.0,
+            min_speech_ms: 300,
+            hangover_ms: 500,
+            meeting_apps: names(&["zoom.us", "Microsoft Teams", "Webex", "FaceTime", "Slack", "Discord", "Around"]),
+            meeting_titles: names(&["Google Meet", "meet.google.com", "Zoom Meeting", "Microsoft Teams", "Huddle"]),
+            min_speech_ratio: 0.2,
+            merge_gap_secs: 60,
+            exclude_from_detail: true,
+        }
+    }
+}
+
+impl AudioAnalyzerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_rate < 8_000 || self.frame_ms == 0 {
+            return Err(IndexerError::Config(
+                "audio_analyzer needs sample_rate >= 8000 and a positive frame_ms".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.min_speech_ratio) {
+            return Err(IndexerError::Config("audio_analyzer min_speech_ratio must be between 0 and 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Stretch of a segment's audio with speech, in milliseconds from its start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeechSpan {
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+/// A period the user was likely in a call; `end` is exclusive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingInterval {
+    pub segment_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Meeting app in front when the interval began
+    pub app: String,
+    /// Seconds of speech detected within the interval
+    pub speech_seconds: f64,
+}
+
+impl MeetingInterval {
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+/// Finds meetings in recorded segments: periods with a meeting app in front
+/// and enough speech on the audio track
+#[derive(Clone)]
+pub struct AudioAnalyzer {
+    config: AudioAnalyzerConfig,
+}
+
+impl AudioAnalyzer {
+    pub fn new(config: AudioAnalyzerConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &AudioAnalyzerConfig {
+        &self.config
+    }
+
+    /// Meetings in a segment; segments without an audio track have none
+    pub fn analyze_segment(
+        &self,
+        video_path: &Path,
+        segment_start: DateTime<Utc>,
+        frames: &[FrameMetadata],
+    ) -> Result<Vec<MeetingInterval>> {
+        let Some(samples) = decode_audio(video_path, self.config.sample_rate)? else {
+            debug!("No audio track in {}", video_path.display());
+            return Ok(Vec::new());
+        };
+        let speech = self.detect_speech(&samples);
+        debug!("Found {} speech spans in {}", speech.len(), video_path.display());
+        Ok(self.meeting_intervals(&speech, frames, segment_start))
+    }
+
+    /// Voice activity detection on mono samples.
+    ///
+    /// A window is voiced when its level clears both `speech_threshold_db` and
+    /// the segment's noise floor (its quietest tenth) by `noise_margin_db`, and
+    /// its zero-crossing rate is in the range of speech rather than hiss.
+    pub fn detect_speech(&self, samples: &[f32]) -> Vec<SpeechSpan> {
+        let window = (self.config.sample_rate as usize * self.config.frame_ms as usize / 1000).max(1);
+        let windows: Vec<(f32, f32)> = samples.chunks(window).map(|chunk| (level_db(chunk), zero_crossing_rate(chunk))).collect();
+        if windows.is_empty() {
+            return Vec::new();
+        }
+        let mut levels: Vec<f32> = windows.iter().map(|(level, _)| *level).collect();
+        levels.sort_by(|a, b| a.total_cmp(b));
+        let noise_floor = levels[levels.len() / 10];
+        let threshold = self.config.speech_threshold_db.max(noise_floor + self.config.noise_margin_db);
+
+        let frame_ms = self.config.frame_ms as i64;
+        let mut spans: Vec<SpeechSpan> = Vec::new();
+        for (index, (level, zcr)) in windows.iter().enumerate() {
+            if *level < threshold || !(0.01..=0.35).contains(zcr) {
+                continue;
+            }
+            let start_ms = index as i64 * frame_ms;
+            match spans.last_mut() {
+                Some(span) if start_ms - span.end_ms <= self.config.hangover_ms as i64 => span.end_ms = start_ms + frame_ms,
+                _ => spans.push(SpeechSpan { start_ms, end_ms: start_ms + frame_ms }),
+            }
+        }
+        spans.retain(|span| span.end_ms - span.start_ms >= self.config.min_speech_ms as i64);
+        spans
+    }
+
+    /// Meeting app periods of a segment with enough speech in them
+    pub fn meeting_intervals(
+        &self,
+        speech: &[SpeechSpan],
+        frames: &[FrameMetadata],
+        segment_start: DateTime<Utc>,
+    ) -> Vec<MeetingInterval> {
+        let mut frames: Vec<&FrameMetadata> = frames.iter().collect();
+        frames.sort_by_key(|f| f.ts_ns);
+
+        // Each frame stands for the time until the next one, the last for as long as the one before it
+        let mut periods: Vec<(i64, i64, &FrameMetadata)> = Vec::new();
+        for (index, &frame) in frames.iter().enumerate() {
+            if !self.is_meeting_frame(frame) {
+                continue;
+            }
+            let start_ms = frame.ts_ns / 1_000_000;
+            let end_ms = match (frames.get(index + 1), index.checked_sub(1)) {
+                (Some(next), _) => next.ts_ns / 1_000_000,
+                (None, Some(previous)) => 2 * start_ms - frames[previous].ts_ns / 1_000_000,
+                (None, None) => start_ms + 1000,
+            };
+            match periods.last_mut() {
+                Some(period) if period.1 == start_ms => period.1 = end_ms,
+                _ => periods.push((start_ms, end_ms, frame)),
+            }
+        }
+
+        let gap = self.config.merge_gap_secs * 1000;
+        let mut meetings: Vec<MeetingInterval> = Vec::new();
+        for (start_ms, end_ms, frame) in periods {
+            let speech_ms: i64 = speech
+                .iter()
+                .map(|span| (span.end_ms.min(end_ms) - span.start_ms.max(start_ms)).max(0))
+                .sum();
+            if end_ms <= start_ms || (speech_ms as f32) < self.config.min_speech_ratio * (end_ms - start_ms) as f32 {
+                continue;
+            }
+            let start = segment_start + Duration::milliseconds(start_ms);
+            let end = segment_start + Duration::milliseconds(end_ms);
+            match meetings.last_mut() {
+                Some(meeting) if (start - meeting.end).num_milliseconds() <= gap => {
+                    meeting.end = end;
+                    meeting.speech_seconds += speech_ms as f64 / 1000.0;
+                }
+                _ => meetings.push(MeetingInterval {
+                    segment_id: frame.segment_id.clone(),
+                    start,
+                    end,
+                    app: frame.app_name.clone(),
+                    speech_seconds: speech_ms as f64 / 1000.0,
+                }),
+            }
+        }
+        meetings
+    }
+
+    fn is_meeting_frame(&self, frame: &FrameMetadata) -> bool {
+        self.config.meeting_apps.iter().any(|app| app.eq_ignore_ascii_case(&frame.app_name))
+            || self
+                .config
+                .meeting_titles
+                .iter()
+                .any(|title| frame.win_title.to_lowercase().contains(&title.to_lowercase()))
+    }
+}
+
+/// Drop the OCR results of frames captured during `meetings`; returns how many frames lost theirs
+pub fn strip_meeting_ocr(
+    meetings: &[MeetingInterval],
+    frames: &[FrameMetadata],
+    frame_ocr: &mut HashMap<String, Vec<OCRResult>>,
+    segment_start: DateTime<Utc>,
+) -> usize {
+    let in_meeting: HashSet<&str> = frames
+        .iter()
+        .filter(|f| {
+            let time = segment_start + Duration::nanoseconds(f.ts_ns);
+            meetings.iter().any(|m| m.contains(time))
+        })
+        .map(|f| f.path.as_str())
+        .collect();
+    let before = frame_ocr.len();
+    frame_ocr.retain(|path, _| !in_meeting.contains(path.as_str()));
+    before - frame_ocr.len()
+}
+
+/// Meeting intervals from the `meetings_*.csv` files in `output_dir`
+pub fn load_meetings(output_dir: &Path) -> Result<Vec<MeetingInterval>> {
+    let mut meetings = Vec::new();
+    if !output_dir.exists() {
+        return Ok(meetings);
+    }
+    for entry in std::fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        let is_meetings = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with("meetings_") && name.ends_with(".csv"));
+        if !is_meetings {
+            continue;
+        }
+        let mut reader = csv::Reader::from_path(&path).map_err(std::io::Error::from)?;
+        for record in reader.deserialize() {
+            match record {
+                Ok(meeting) => meetings.push(meeting),
+                Err(e) => debug!("Skipping malformed meeting in {}: {}", path.display(), e),
+            }
+        }
+    }
+    meetings.sort_by_key(|m: &MeetingInterval| m.start);
+    Ok(meetings)
+}
+
+/// Level of a window in dBFS
+fn level_db(samples: &[f32]) -> f32 {
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32;
+    10.0 * mean_square.max(1e-10).log10()
+}
+
+/// Share of adjacent samples that change sign
+fn zero_crossing_rate(samples: &[f32]) -> f32 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let crossings = samples.windows(2).filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0)).count();
+    crossings as f32 / (samples.len() - 1) as f32
+}
+
+/// Mono samples of a file's audio track at `sample_rate`, if it has one
+#[cfg(feature = "ffmpeg")]
+fn decode_audio(video_path: &Path, sample_rate: u32) -> Result<Option<Vec<f32>>> {
+    use ffmpeg_next as ffmpeg;
+
+    let mut input = ffmpeg::format::input(&video_path)?;
+    let Some(stream) = input.streams().best(ffmpeg::media::Type::Audio) else {
+        return Ok(None);
+    };
+    let stream_index = stream.index();
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())?
+        .decoder()
+        .audio()?;
+    // Some codecs leave the layout unset and only give a channel count
+    let layout = match decoder.channel_layout() {
+        layout if layout.is_empty() => ffmpeg::ChannelLayout::default(decoder.channels() as i32),
+        layout => layout,
+    };
+    let mut resampler = ffmpeg::software::resampling::Context::get(
+        decoder.format(),
+        layout,
+        decoder.rate(),
+        ffmpeg::format::Sample::F32(ffmpeg::format::sample::Type::Packed),
+        ffmpeg::ChannelLayout::MONO,
+        sample_rate,
+    )?;
+
+    let mut samples = Vec::new();
+    let mut decoded = ffmpeg::frame::Audio::empty();
+    let mut resampled = ffmpeg::frame::Audio::empty();
+    for (stream, packet) in input.packets() {
+        if stream.index() != stream_index {
+            continue;
+        }
+        decoder.send_packet(&packet)?;
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            resampler.run(&decoded, &mut resampled)?;
+            samples.extend_from_slice(&resampled.plane::<f32>(0)[..resampled.samples()]);
+        }
+    }
+    decoder.send_eof()?;
+    while decoder.receive_frame(&mut decoded).is_ok() {
+        resampler.run(&decoded, &mut resampled)?;
+        samples.extend_from_slice(&resampled.plane::<f32>(0)[..resampled.samples()]);
+    }
+    Ok(Some(samples))
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn decode_audio(video_path: &Path, _sample_rate: u32) -> Result<Option<Vec<f32>>> {
+    debug!("Audio decoding needs the ffmpeg feature; skipping {}", video_path.display());
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn frame(second: i64, app_name: &str, win_title: &str) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns: second * 1_000_000_000,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: format!("frame_{}.png", second),
+            phash16: 0,
+            entropy: 0.0,
+            app_name: app_name.to_string(),
+            win_title: win_title.to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }
+    }
+
+    #[test]
+    fn test_detects_speech_and_meetings() {
+        let analyzer = AudioAnalyzer::new(AudioAnalyzerConfig::default()).unwrap();
+        let rate = analyzer.config().sample_rate as usize;
+        // 2s of quiet noise, 3s of a 200 Hz tone standing in for a voice, 2s of quiet
+        let mut samples: Vec<f32> = (0..rate * 2).map(|i| if i % 2 == 0 { 0.0005 } else { -0.0005 }).collect();
+        samples.extend((0..rate * 3).map(|i| 0.3 * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / rate as f32).sin()));
+        samples.extend(std::iter::repeat(0.0).take(rate * 2));
+
+        let speech = analyzer.detect_speech(&samples);
+        assert_eq!(speech.len(), 1);
+        assert!((speech[0].start_ms - 2000).abs() <= 30 && (speech[0].end_ms - 5000).abs() <= 30);
+
+        let frames = vec![
+            frame(0, "Xcode", "main.rs"),
+            frame(1, "Google Chrome", "Standup - Google Meet"),
+            frame(6, "Xcode", "main.rs"),
+            frame(7, "zoom.us", "Zoom Meeting"),
+            frame(9, "Xcode", "main.rs"),
+        ];
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        // Only the call with speech in it counts
+        let meetings = analyzer.meeting_intervals(&speech, &frames, start);
+        assert_eq!(meetings.len(), 1);
+        assert_eq!(meetings[0].app, "Google Chrome");
+        assert_eq!(meetings[0].start, start + Duration::seconds(1));
+        assert_eq!(meetings[0].end, start + Duration::seconds(6));
+
+        let mut frame_ocr: HashMap<String, Vec<OCRResult>> =
+            frames.iter().map(|f| (f.path.clone(), Vec::new())).collect();
+        assert_eq!(strip_meeting_ocr(&meetings, &frames, &mut frame_ocr, start), 1);
+        assert!(!frame_ocr.contains_key("frame_1.png") && frame_ocr.contains_key("frame_7.png"));
+    }
+}
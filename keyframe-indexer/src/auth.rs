@@ -0,0 +1,451 @@
+use crate::error::{IndexerError, Result};
+use crate::redaction::RedactionProfile;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Permission granted to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    ReadEvents,
+    ReadOcr,
+    ReadFrames,
+    Admin,
+}
+
+impl Scope {
+    /// Admin implies every other scope
+    pub fn is_granted_by(&self, granted: &[Scope]) -> bool {
+        granted.contains(self) || granted.contains(&Scope::Admin)
+    }
+}
+
+/// Static API key entry. Only the SHA-256 of the key is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Stable identifier used in logs and for rotation
+    pub id: String,
+    /// Hex-encoded SHA-256 of the secret key
+    pub key_sha256: String,
+    /// Scopes granted to the key
+    pub scopes: Vec<Scope>,
+    /// Redaction profile applied to this key's query results
+    pub profile: RedactionProfile,
+    /// Maximum requests per minute (0 disables limiting)
+    #[serde(default)]
+    pub rate_limit_per_minute: u32,
+    /// Key stops authenticating after this instant (set during rotation)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Revoked keys never authenticate
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+/// TLS material for network endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain
+    pub cert_path: PathBuf,
+    /// PEM private key
+    pub key_path: PathBuf,
+    /// Optional PEM CA bundle; when set clients must present a certificate
+    #[serde(default)]
+    pub client_ca_path: Option<PathBuf>,
+}
+
+/// Authentication configuration for network endpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Configured API keys
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// TLS settings; required before binding to a non-loopback address
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Allow plaintext on non-loopback addresses (not recommended)
+    #[serde(default)]
+    pub allow_insecure_remote: bool,
+}
+
+impl AuthConfig {
+    /// Refuse to expose endpoints beyond loopback without keys and TLS
+    pub fn check_bind_address(&self, addr: &SocketAddr) -> Result<()> {
+        if addr.ip().is_loopback() {
+            return Ok(());
+        }
+
+        if self.active_key_count() == 0 {
+            return Err(IndexerError::Config(format!(
+                "Refusing to bind {} without any active API keys",
+                addr
+            )));
+        }
+
+        if self.tls.is_none() && !self.allow_insecure_remote {
+            return Err(IndexerError::Config(format!(
+                "Refusing to bind {} without TLS; configure auth.tls or set allow_insecure_remote",
+                addr
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut seen_ids = std::collections::HashSet::new();
+        for key in &self.api_keys {
+            if !seen_ids.insert(key.id.as_str()) {
+                return Err(IndexerError::Config(format!("Duplicate API key id: {}", key.id)));
+            }
+            if key.key_sha256.len() != 64 || hex::decode(&key.key_sha256).is_err() {
+                return Err(IndexerError::Config(format!(
+                    "API key '{}' must have a 64-character hex key_sha256",
+                    key.id
+                )));
+            }
+            if key.scopes.is_empty() {
+                return Err(IndexerError::Config(format!("API key '{}' has no scopes", key.id)));
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            for path in [Some(&tls.cert_path), Some(&tls.key_path), tls.client_ca_path.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                if !path.exists() {
+                    return Err(IndexerError::Config(format!(
+                        "TLS file does not exist: {}",
+                        path.display()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn active_key_count(&self) -> usize {
+        let now = Utc::now();
+        self.api_keys
+            .iter()
+            .filter(|key| !key.revoked && !matches!(key.expires_at, Some(exp) if exp <= now))
+            .count()
+    }
+}
+
+/// Identity established for an authenticated request
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub key_id: String,
+    pub scopes: Vec<Scope>,
+    pub profile: RedactionProfile,
+}
+
+impl AuthContext {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        scope.is_granted_by(&self.scopes)
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Validates API keys, enforces scopes and per-key rate limits, and rotates keys
+pub struct Authenticator {
+    config: AuthConfig,
+    keys_by_hash: HashMap<String, usize>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl Authenticator {
+    pub fn new(config: AuthConfig) -> Result<Self> {
+        config.validate()?;
+        let keys_by_hash = Self::index_keys(&config);
+
+        info!("Authenticator initialized with {} API keys", config.api_keys.len());
+
+        Ok(Self {
+            config,
+            keys_by_hash,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Generate a new random API key, returning the secret and its hash
+    pub fn generate_key() -> (String, String) {
+        let secret = format!("kfi_{}", hex::encode(rand::random::<[u8; 24]>()));
+        let hash = hash_key(&secret);
+        (secret, hash)
+    }
+
+    /// Authenticate a presented key and require a scope
+    pub fn authenticate(&self, presented_key: &str, required_scope: Scope) -> Result<AuthContext> {
+        let key = self
+            .keys_by_hash
+            .get(&hash_key(presented_key))
+            .map(|&index| &self.config.api_keys[index])
+            .ok_or_else(|| IndexerError::AccessDenied("Invalid API key".to_string()))?;
+
+        if key.revoked {
+            return Err(IndexerError::AccessDenied(format!("API key '{}' has been revoked", key.id)));
+        }
+
+        if let Some(expires_at) = key.expires_at {
+            if expires_at <= Utc::now() {
+                return Err(IndexerError::AccessDenied(format!("API key '{}' has expired", key.id)));
+            }
+        }
+
+        if !required_scope.is_granted_by(&key.scopes) {
+            debug!("API key '{}' lacks scope {:?}", key.id, required_scope);
+            return Err(IndexerError::AccessDenied(format!(
+                "API key '{}' lacks scope {:?}",
+                key.id, required_scope
+            )));
+        }
+
+        self.check_rate_limit(key)?;
+
+        Ok(AuthContext {
+            key_id: key.id.clone(),
+            scopes: key.scopes.clone(),
+            profile: key.profile,
+        })
+    }
+
+    /// Replace a key with a freshly generated one.
+    ///
+    /// The previous key keeps working for `grace_period` so clients can switch
+    /// over; the new secret is returned and must be handed to the client.
+    pub fn rotate_key(&mut self, key_id: &str, grace_period: Duration) -> Result<String> {
+        let index = self
+            .config
+            .api_keys
+            .iter()
+            .position(|key| key.id == key_id && !key.revoked)
+            .ok_or_else(|| IndexerError::Config(format!("Unknown API key id: {}", key_id)))?;
+
+        let (secret, hash) = Self::generate_key();
+        let mut rotated = self.config.api_keys[index].clone();
+        rotated.key_sha256 = hash;
+        rotated.expires_at = None;
+
+        let previous = &mut self.config.api_keys[index];
+        previous.id = format!("{}@{}", key_id, &previous.key_sha256[..8]);
+        previous.expires_at = Some(Utc::now() + grace_period);
+
+        self.config.api_keys.push(rotated);
+        self.keys_by_hash = Self::index_keys(&self.config);
+
+        info!("Rotated API key '{}' with {}s grace period", key_id, grace_period.num_seconds());
+        Ok(secret)
+    }
+
+    /// Immediately revoke a key
+    pub fn revoke_key(&mut self, key_id: &str) -> Result<()> {
+        let key = self
+            .config
+            .api_keys
+            .iter_mut()
+            .find(|key| key.id == key_id)
+            .ok_or_else(|| IndexerError::Config(format!("Unknown API key id: {}", key_id)))?;
+
+        key.revoked = true;
+        warn!("Revoked API key '{}'", key_id);
+        Ok(())
+    }
+
+    /// Current configuration, including rotation state, for persisting
+    pub fn get_config(&self) -> &AuthConfig {
+        &self.config
+    }
+
+    // MARK: - Private Helper Methods
+
+    fn index_keys(config: &AuthConfig) -> HashMap<String, usize> {
+        config
+            .api_keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (key.key_sha256.to_ascii_lowercase(), index))
+            .collect()
+    }
+
+    fn check_rate_limit(&self, key: &ApiKeyConfig) -> Result<()> {
+        if key.rate_limit_per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = key.rate_limit_per_minute as f64;
+        let refill_per_sec = capacity / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .map_err(|_| IndexerError::Config("Rate limiter lock poisoned".to_string()))?;
+        let bucket = buckets.entry(key.id.clone()).or_insert(TokenBucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens < 1.0 {
+            return Err(IndexerError::RateLimited(format!(
+                "API key '{}' exceeded {} requests per minute",
+                key.id, key.rate_limit_per_minute
+            )));
+        }
+
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 of an API key secret
+pub fn hash_key(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Build a rustls server configuration from PEM files
+pub fn load_tls_server_config(tls: &TlsConfig) -> Result<Arc<rustls::ServerConfig>> {
+    let certs = load_certs(&tls.cert_path)?;
+
+    let mut key_reader = BufReader::new(File::open(&tls.key_path)?);
+    let key = rustls_pemfile::private_key(&mut key_reader)?
+        .ok_or_else(|| IndexerError::Config(format!("No private key found in {}", tls.key_path.display())))?;
+
+    let builder = rustls::ServerConfig::builder();
+    let builder = match &tls.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots
+                    .add(cert)
+                    .map_err(|e| IndexerError::Config(format!("Invalid client CA certificate: {}", e)))?;
+            }
+            let verifier = rustls::server::WebPkiClientVerifier::builder(Arc::new(roots))
+                .build()
+                .map_err(|e| IndexerError::Config(format!("Invalid client CA bundle: {}", e)))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let server_config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| IndexerError::Config(format!("Invalid TLS certificate or key: {}", e)))?;
+
+    Ok(Arc::new(server_config))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<std::result::Result<Vec<_>, _>>()?;
+
+    if certs.is_empty() {
+        return Err(IndexerError::Config(format!("No certificates found in {}", path.display())));
+    }
+
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_key(id: &str, scopes: Vec<Scope>, rate_limit: u32) -> (String, ApiKeyConfig) {
+        let (secret, hash) = Authenticator::generate_key();
+        let config = ApiKeyConfig {
+            id: id.to_string(),
+            key_sha256: hash,
+            scopes,
+            profile: RedactionProfile::Analyst,
+            rate_limit_per_minute: rate_limit,
+            expires_at: None,
+            revoked: false,
+        };
+        (secret, config)
+    }
+
+    #[test]
+    fn test_scope_enforcement() {
+        let (secret, key) = create_key("events-only", vec![Scope::ReadEvents], 0);
+        let (admin_secret, admin_key) = create_key("admin", vec![Scope::Admin], 0);
+        let auth = Authenticator::new(AuthConfig {
+            api_keys: vec![key, admin_key],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(auth.authenticate(&secret, Scope::ReadEvents).is_ok());
+        assert!(auth.authenticate(&secret, Scope::ReadOcr).is_err());
+        assert!(auth.authenticate(&admin_secret, Scope::ReadOcr).is_ok());
+        assert!(auth.authenticate("kfi_wrong", Scope::ReadEvents).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (secret, key) = create_key("limited", vec![Scope::ReadEvents], 2);
+        let auth = Authenticator::new(AuthConfig {
+            api_keys: vec![key],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(auth.authenticate(&secret, Scope::ReadEvents).is_ok());
+        assert!(auth.authenticate(&secret, Scope::ReadEvents).is_ok());
+        assert!(matches!(
+            auth.authenticate(&secret, Scope::ReadEvents),
+            Err(IndexerError::RateLimited(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_rotation_with_grace_period() {
+        let (old_secret, key) = create_key("client", vec![Scope::ReadEvents], 0);
+        let mut auth = Authenticator::new(AuthConfig {
+            api_keys: vec![key],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let new_secret = auth.rotate_key("client", Duration::minutes(5)).unwrap();
+        assert_eq!(auth.authenticate(&new_secret, Scope::ReadEvents).unwrap().key_id, "client");
+        assert!(auth.authenticate(&old_secret, Scope::ReadEvents).is_ok());
+
+        // Rotating again with no grace period cuts the previous key off immediately
+        let newest_secret = auth.rotate_key("client", Duration::zero()).unwrap();
+        assert!(auth.authenticate(&new_secret, Scope::ReadEvents).is_err());
+        assert!(auth.authenticate(&newest_secret, Scope::ReadEvents).is_ok());
+    }
+
+    #[test]
+    fn test_bind_address_requires_tls() {
+        let (_, key) = create_key("remote", vec![Scope::ReadEvents], 0);
+        let config = AuthConfig {
+            api_keys: vec![key],
+            ..Default::default()
+        };
+
+        assert!(config.check_bind_address(&"127.0.0.1:8080".parse().unwrap()).is_ok());
+        assert!(config.check_bind_address(&"0.0.0.0:8080".parse().unwrap()).is_err());
+        assert!(AuthConfig::default().check_bind_address(&"0.0.0.0:8080".parse().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::DetectedEvent;
+use crate::ocr_data::OCRResult;
+use crate::ocr_parquet_writer::OCRParquetWriter;
+use crate::shutdown::ShutdownSignal;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::fmt::{Display, Write as _};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Screenshot formats picked up by directory analysis
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "avif", "bmp", "tiff"];
+
+/// What a discovered file is analyzed as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchInputKind {
+    Segment,
+    Image,
+}
+
+/// Video segments and images under `dir` (recursively), sorted by path
+pub fn discover_inputs(dir: &Path, video_extensions: &[String]) -> Result<Vec<(PathBuf, BatchInputKind)>> {
+    let mut inputs = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            if let Some(kind) = classify_input(&path, video_extensions) {
+                inputs.push((path, kind));
+            }
+        }
+    }
+
+    inputs.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(inputs)
+}
+
+/// Whether a file is analyzed as a video segment or an image, by its extension
+pub fn classify_input(path: &Path, video_extensions: &[String]) -> Option<BatchInputKind> {
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase)?;
+    if video_extensions.iter().any(|v| v.eq_ignore_ascii_case(&extension)) {
+        Some(BatchInputKind::Segment)
+    } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(BatchInputKind::Image)
+    } else {
+        None
+    }
+}
+
+/// Stored OCR results for an image, keyed by its path or, failing that, its file name
+pub async fn stored_ocr_for_image(store: &OCRParquetWriter, path: &str) -> Result<Vec<OCRResult>> {
+    let results = store.query_by_frame_id(path).await?;
+    if !results.is_empty() {
+        return Ok(results);
+    }
+
+    match Path::new(path).file_stem().and_then(|s| s.to_str()) {
+        Some(stem) => store.query_by_frame_id(stem).await,
+        None => Ok(results),
+    }
+}
+
+/// Selection and resume settings for reprocessing an archive of recordings
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    /// Only files recorded at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only files recorded before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Progress file marking finished files; files listed there are skipped
+    pub checkpoint: Option<PathBuf>,
+    /// Keep the progress of an earlier run instead of starting over
+    pub resume: bool,
+    /// Stop after the file in progress once shutdown is requested
+    pub shutdown: Option<ShutdownSignal>,
+}
+
+impl BatchOptions {
+    /// Whether a file's recording time falls within `since`/`until`.
+    ///
+    /// Recordings are written as they happen, so the modification time is
+    /// when recording finished.
+    pub fn includes(&self, path: &Path) -> Result<bool> {
+        if self.since.is_none() && self.until.is_none() {
+            return Ok(true);
+        }
+        let recorded: DateTime<Utc> = std::fs::metadata(path)?.modified()?.into();
+        Ok(self.since.map_or(true, |since| recorded >= since) && self.until.map_or(true, |until| recorded < until))
+    }
+}
+
+/// Parse a `--since`/`--until` bound: an RFC 3339 timestamp or a date, taken as midnight UTC
+pub fn parse_time_bound(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| Utc.from_utc_datetime(&midnight))
+        .ok_or_else(|| IndexerError::Config(format!("Invalid time '{}': expected RFC 3339 or YYYY-MM-DD", value)))
+}
+
+/// Single append-only checkpoint record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckpointRecord {
+    path: PathBuf,
+    completed_at: DateTime<Utc>,
+}
+
+/// Files a batch run has finished, so an interrupted run can pick up where it stopped.
+///
+/// Records are appended as each file completes; files that failed are not
+/// recorded and are retried on resume.
+pub struct BatchCheckpoint {
+    path: PathBuf,
+    done: HashSet<PathBuf>,
+    file: File,
+}
+
+impl BatchCheckpoint {
+    /// Open a checkpoint file; without `resume` its earlier progress is discarded
+    pub fn open<P: AsRef<Path>>(path: P, resume: bool) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut done = HashSet::new();
+        if resume && path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // A run killed mid-write leaves a torn final line
+                match serde_json::from_str::<CheckpointRecord>(&line) {
+                    Ok(record) => {
+                        done.insert(record.path);
+                    }
+                    Err(e) => warn!("Skipping malformed checkpoint record: {}", e),
+                }
+            }
+            info!("Resuming batch from {} with {} files done", path.display(), done.len());
+        }
+
+        let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(&path)?;
+        // Rewrite what was kept so new records never follow a partial line
+        for done_path in &done {
+            let record = CheckpointRecord { path: done_path.clone(), completed_at: Utc::now() };
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        }
+        file.flush()?;
+
+        Ok(Self { path, done, file })
+    }
+
+    pub fn is_done(&self, path: &Path) -> bool {
+        self.done.contains(path)
+    }
+
+    /// Record a file as finished
+    pub fn mark_done(&mut self, path: &Path) -> Result<()> {
+        let record = CheckpointRecord { path: path.to_path_buf(), completed_at: Utc::now() };
+        writeln!(self.file, "{}", serde_json::to_string(&record)?)?;
+        self.file.sync_data()?;
+        self.done.insert(path.to_path_buf());
+        Ok(())
+    }
+
+    pub fn done_count(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// A file that could not be analyzed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Outcome of a one-off directory analysis
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub segments_processed: usize,
+    pub images_analyzed: usize,
+    pub keyframes: usize,
+    pub scene_changes: usize,
+    /// Applications whose OCR got worse than their own baseline
+    pub ocr_quality_regressions: usize,
+    pub events_by_type: BTreeMap<String, usize>,
+    pub failures: Vec<BatchFailure>,
+    /// Files outside the `since`/`until` window
+    #[serde(default)]
+    pub filtered_out: usize,
+    /// Files already finished by an earlier, resumed run
+    #[serde(default)]
+    pub already_done: usize,
+    /// Set when nothing was written, only estimated
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Bytes of keyframes, frame metadata and events a real run would write
+    #[serde(default)]
+    pub estimated_storage_bytes: u64,
+    /// Keyframes dropped by the privacy filter
+    #[serde(default)]
+    pub redacted_frames: usize,
+    /// Set when the run stopped early, on a shutdown request or with storage running out
+    #[serde(default)]
+    pub interrupted: bool,
+    pub elapsed_ms: u64,
+}
+
+impl BatchSummary {
+    pub fn record_events(&mut self, events: &[DetectedEvent]) {
+        for event in events {
+            *self.events_by_type.entry(format!("{:?}", event.event_type)).or_insert(0) += 1;
+        }
+    }
+
+    pub fn record_failure(&mut self, path: &Path, error: impl Display) {
+        self.failures.push(BatchFailure {
+            path: path.display().to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    pub fn total_events(&self) -> usize {
+        self.events_by_type.values().sum()
+    }
+
+    /// True when every discovered file was analyzed
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Plain-text report for terminals and CI logs
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "Segments processed: {}", self.segments_processed);
+        let _ = writeln!(text, "Images analyzed:    {}", self.images_analyzed);
+        let _ = writeln!(text, "Keyframes:          {}", self.keyframes);
+        let _ = writeln!(text, "Scene changes:      {}", self.scene_changes);
+        if self.ocr_quality_regressions > 0 {
+            let _ = writeln!(text, "OCR regressions:    {}", self.ocr_quality_regressions);
+        }
+        if self.redacted_frames > 0 {
+            let _ = writeln!(text, "Redacted keyframes: {}", self.redacted_frames);
+        }
+        let _ = writeln!(text, "Events:             {}", self.total_events());
+        for (event_type, count) in &self.events_by_type {
+            let _ = writeln!(text, "  {:<18}{}", event_type, count);
+        }
+        if self.filtered_out > 0 {
+            let _ = writeln!(text, "Outside time range: {}", self.filtered_out);
+        }
+        if self.already_done > 0 {
+            let _ = writeln!(text, "Already done:       {}", self.already_done);
+        }
+        let _ = writeln!(text, "Failures:           {}", self.failures.len());
+        for failure in &self.failures {
+            let _ = writeln!(text, "  {}: {}", failure.path, failure.error);
+        }
+        if self.dry_run {
+            let _ = writeln!(
+                text,
+                "Estimated storage:  {:.1} MiB (dry run, nothing written)",
+                self.estimated_storage_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+        if self.interrupted {
+            let _ = writeln!(text, "Interrupted:        resume with --resume");
+        }
+        let _ = writeln!(text, "Elapsed:            {} ms", self.elapsed_ms);
+        text
+    }
+}
+
+/// Rough bytes storing `events` takes, from their JSON size; Parquet
+/// compression usually makes the stored events smaller
+pub fn estimated_event_bytes(events: &[DetectedEvent]) -> u64 {
+    events
+        .iter()
+        .map(|event| serde_json::to_vec(event).map(|json| json.len() as u64).unwrap_or(0))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_inputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("run_2");
+        std::fs::create_dir_all(&nested).unwrap();
+        for path in [
+            temp_dir.path().join("b.mp4"),
+            temp_dir.path().join("a.PNG"),
+            temp_dir.path().join("notes.txt"),
+            nested.join("c.mov"),
+        ] {
+            std::fs::write(path, b"").unwrap();
+        }
+
+        let inputs = discover_inputs(temp_dir.path(), &["mp4".to_string(), "mov".to_string()]).unwrap();
+        let names: Vec<(String, BatchInputKind)> = inputs
+            .iter()
+            .map(|(path, kind)| (path.strip_prefix(temp_dir.path()).unwrap().display().to_string(), *kind))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                ("a.PNG".to_string(), BatchInputKind::Image),
+                ("b.mp4".to_string(), BatchInputKind::Segment),
+                (format!("run_2{}c.mov", std::path::MAIN_SEPARATOR), BatchInputKind::Segment),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_resume_and_time_bounds() {
+        let temp_dir = TempDir::new().unwrap();
+        let checkpoint_path = temp_dir.path().join("checkpoint.jsonl");
+        let first = temp_dir.path().join("a.mp4");
+        let second = temp_dir.path().join("b.mp4");
+
+        let mut checkpoint = BatchCheckpoint::open(&checkpoint_path, false).unwrap();
+        checkpoint.mark_done(&first).unwrap();
+        drop(checkpoint);
+        // Simulate a run killed while writing the next record
+        let mut file = OpenOptions::new().append(true).open(&checkpoint_path).unwrap();
+        write!(file, "{{\"path\":\"b.m").unwrap();
+        drop(file);
+
+        let resumed = BatchCheckpoint::open(&checkpoint_path, true).unwrap();
+        assert!(resumed.is_done(&first) && !resumed.is_done(&second));
+        drop(resumed);
+        assert_eq!(BatchCheckpoint::open(&checkpoint_path, false).unwrap().done_count(), 0);
+
+        let since = parse_time_bound("2024-03-05").unwrap();
+        assert_eq!(since, parse_time_bound("2024-03-05T00:00:00Z").unwrap());
+        assert!(parse_time_bound("last tuesday").is_err());
+
+        std::fs::write(&first, b"").unwrap();
+        let window = |since: &str, until: &str| BatchOptions {
+            since: Some(parse_time_bound(since).unwrap()),
+            until: Some(parse_time_bound(until).unwrap()),
+            ..BatchOptions::default()
+        };
+        assert!(window("2000-01-01", "2999-01-01").includes(&first).unwrap());
+        assert!(!window("2000-01-01", "2001-01-01").includes(&first).unwrap());
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Result};
+use arrow::array::{StringArray, TimestampNanosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use keyframe_indexer::file_index::{FileIndex, FileIndexCache, IndexFilter};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Rows per generated file, each with its own target
+const ROWS_PER_FILE: usize = 20;
+
+/// Write an archive of small indexed event files and time a target lookup
+/// scanning every file against one pruned by the sidecar indexes, cold and
+/// with the indexes cached.
+///
+/// Usage: bench_file_index <empty dir> [files]
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let Some(dir) = args.next().map(PathBuf::from) else {
+        bail!("usage: bench_file_index <empty dir> [files]");
+    };
+    let file_count: usize = args.next().map_or(Ok(10_000), |count| count.parse())?;
+    std::fs::create_dir_all(&dir)?;
+    if std::fs::read_dir(&dir)?.next().is_some() {
+        bail!("{} is not empty", dir.display());
+    }
+
+    let started = Instant::now();
+    let files = write_archive(&dir, file_count)?;
+    println!("Wrote {} files of {} rows in {:.1}s", files.len(), ROWS_PER_FILE, started.elapsed().as_secs_f64());
+
+    let target = format!("target_{}", file_count / 2);
+    let filter = IndexFilter::equals("target", &target);
+
+    let started = Instant::now();
+    let scanned = count_matches(&files, &target)?;
+    let scan_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let cache = FileIndexCache::default();
+    let started = Instant::now();
+    let kept = cache.prune(files.clone(), &filter);
+    let cold_matches = count_matches(&kept, &target)?;
+    let cold_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let started = Instant::now();
+    let kept = cache.prune(files.clone(), &filter);
+    let warm_matches = count_matches(&kept, &target)?;
+    let warm_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    if scanned != cold_matches || scanned != warm_matches {
+        bail!("pruning lost rows: {} scanned, {} cold, {} warm", scanned, cold_matches, warm_matches);
+    }
+    println!("{:<16} {:>10} {:>12} {:>10}", "lookup", "files", "ms", "speedup");
+    println!("{:<16} {:>10} {:>12.1} {:>9.1}x", "full scan", files.len(), scan_ms, 1.0);
+    println!("{:<16} {:>10} {:>12.1} {:>9.1}x", "pruned, cold", kept.len(), cold_ms, scan_ms / cold_ms);
+    println!("{:<16} {:>10} {:>12.1} {:>9.1}x", "pruned, cached", kept.len(), warm_ms, scan_ms / warm_ms);
+    Ok(())
+}
+
+fn write_archive(dir: &Path, file_count: usize) -> Result<Vec<PathBuf>> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+        Field::new("target", DataType::Utf8, false),
+    ]));
+    let mut files = Vec::with_capacity(file_count);
+    for i in 0..file_count {
+        let start_ns = (i * ROWS_PER_FILE) as i64 * 1_000_000_000;
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampNanosecondArray::from_iter_values(
+                    (0..ROWS_PER_FILE as i64).map(|row| start_ns + row * 1_000_000_000),
+                )),
+                Arc::new(StringArray::from_iter_values((0..ROWS_PER_FILE).map(|_| format!("target_{}", i)))),
+            ],
+        )?;
+        let file = dir.join(format!("events_{:06}.parquet", i));
+        let mut writer = ArrowWriter::try_new(File::create(&file)?, schema.clone(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        FileIndex::from_batch(&batch, "ts_ns", &["target"])?.write(&file)?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Rows of `files` with the given target, read the way an unindexed query would
+fn count_matches(files: &[PathBuf], target: &str) -> Result<usize> {
+    let mut matches = 0;
+    for file in files {
+        for batch in ParquetRecordBatchReaderBuilder::try_new(File::open(file)?)?.build()? {
+            let batch = batch?;
+            let targets = batch.column(1).as_any().downcast_ref::<StringArray>().expect("target column");
+            matches += targets.iter().flatten().filter(|value| *value == target).count();
+        }
+    }
+    Ok(matches)
+}
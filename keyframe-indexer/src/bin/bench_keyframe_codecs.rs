@@ -0,0 +1,50 @@
+use anyhow::{bail, Result};
+use keyframe_indexer::keyframe_codec::{self, KeyframeCodec};
+use std::collections::HashMap;
+
+/// Encode keyframes with every codec this build supports and compare file
+/// size and encode time against PNG.
+///
+/// Usage: bench_keyframe_codecs [--quality N] <keyframe>...
+fn main() -> Result<()> {
+    let mut quality = 80u8;
+    let mut paths = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--quality" {
+            quality = args.next().unwrap_or_default().parse()?;
+        } else {
+            paths.push(arg);
+        }
+    }
+    if paths.is_empty() {
+        bail!("usage: bench_keyframe_codecs [--quality N] <keyframe>...");
+    }
+
+    let mut totals: HashMap<KeyframeCodec, (usize, f64)> = HashMap::new();
+    for path in &paths {
+        let image = image::open(path)?;
+        for result in keyframe_codec::benchmark(&image, quality)? {
+            let total = totals.entry(result.codec).or_default();
+            total.0 += result.bytes;
+            total.1 += result.encode_ms;
+        }
+    }
+
+    let png_bytes = totals.get(&KeyframeCodec::Png).map_or(1, |total| total.0.max(1));
+    println!("{} keyframes, lossy quality {}", paths.len(), quality);
+    println!("{:<14} {:>12} {:>10} {:>14}", "codec", "avg KiB", "vs PNG", "avg encode ms");
+    for codec in KeyframeCodec::ALL {
+        let Some((bytes, ms)) = totals.get(&codec) else {
+            continue;
+        };
+        println!(
+            "{:<14} {:>12.1} {:>9.0}% {:>14.1}",
+            format!("{:?}", codec),
+            *bytes as f64 / paths.len() as f64 / 1024.0,
+            *bytes as f64 * 100.0 / png_bytes as f64,
+            ms / paths.len() as f64
+        );
+    }
+    Ok(())
+}
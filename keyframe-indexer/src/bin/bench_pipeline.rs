@@ -0,0 +1,407 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use keyframe_indexer::{
+    BatchOptions, BatchSummary, IndexerConfig, IndexerService, OCRParquetWriter, OCRResult, ScreenScript,
+    SyntheticRecording,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Run generated video segments through `IndexerService` and report each
+/// pipeline stage's throughput and the process's memory.
+///
+/// Usage: bench_pipeline [--frames N] [--width W] [--height H] [--segment-secs S] [--keep DIR] [--json]
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--frames" => options.frames = value().parse()?,
+            "--width" => options.width = value().parse()?,
+            "--height" => options.height = value().parse()?,
+            "--segment-secs" => options.segment_secs = value().parse()?,
+            "--keep" => options.keep = Some(PathBuf::from(value())),
+            "--json" => options.json = true,
+            _ => bail!("usage: bench_pipeline [--frames N] [--width W] [--height H] [--segment-secs S] [--keep DIR] [--json]"),
+        }
+    }
+    if options.frames == 0 || options.segment_secs == 0 {
+        bail!("--frames and --segment-secs must be positive");
+    }
+    if options.width < 320 || options.height < 200 || options.width % 2 != 0 || options.height % 2 != 0 {
+        bail!("frames must be at least 320x200, with an even width and height for H.264");
+    }
+
+    let times = SpanTimes::default();
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(times.clone()))?;
+
+    let root = options
+        .keep
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("bench_pipeline_{}", uuid::Uuid::new_v4())));
+    std::fs::create_dir_all(&root)?;
+    let root = root.canonicalize()?;
+    let report = run(&options, &root, &times).await;
+    if options.keep.is_none() {
+        std::fs::remove_dir_all(&root)?;
+    }
+    let report = report?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} frames at {}x{}: {} segments, {} keyframes, {} scene changes, {} OCR results, {} events, {} failures",
+            report.frames,
+            report.width,
+            report.height,
+            report.segments,
+            report.keyframes,
+            report.scene_changes,
+            report.ocr_results,
+            report.events,
+            report.failures
+        );
+        println!("{:<14} {:>8} {:>8} {:>10} {:>12}", "stage", "calls", "items", "seconds", "items/s");
+        for stage in &report.stages {
+            println!(
+                "{:<14} {:>8} {:>8} {:>10.3} {:>12.1}",
+                stage.stage, stage.calls, stage.items, stage.seconds, stage.items_per_second
+            );
+        }
+        let mib = |kib: Option<u64>| kib.map_or("-".to_string(), |kib| format!("{:.1} MiB", kib as f64 / 1024.0));
+        println!(
+            "total {:.3} s, RSS after processing {}, after detection {}, peak {}",
+            report.total_seconds,
+            mib(report.rss_after_process_kib),
+            mib(report.rss_after_detect_kib),
+            mib(report.peak_rss_kib)
+        );
+    }
+    Ok(())
+}
+
+struct Options {
+    frames: usize,
+    width: u32,
+    height: u32,
+    segment_secs: usize,
+    /// Keep the videos and outputs here instead of a temp directory that is removed
+    keep: Option<PathBuf>,
+    json: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { frames: 300, width: 1280, height: 800, segment_secs: 60, keep: None, json: false }
+    }
+}
+
+/// Results of one run, stable in shape for comparing runs across releases
+#[derive(Serialize)]
+struct BenchReport {
+    version: &'static str,
+    started_at: DateTime<Utc>,
+    frames: usize,
+    width: u32,
+    height: u32,
+    segments: usize,
+    keyframes: usize,
+    scene_changes: usize,
+    ocr_results: usize,
+    events: usize,
+    events_by_type: BTreeMap<String, usize>,
+    failures: usize,
+    stages: Vec<StageResult>,
+    total_seconds: f64,
+    /// Resident memory after the segments were processed, and after event detection
+    rss_after_process_kib: Option<u64>,
+    rss_after_detect_kib: Option<u64>,
+    /// Highest resident memory of the pipeline, where the platform reports it
+    peak_rss_kib: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct StageResult {
+    stage: &'static str,
+    /// Times the stage's span was entered and closed
+    calls: usize,
+    /// Keyframes, or images for `event_detect`, the stage went through
+    items: usize,
+    seconds: f64,
+    items_per_second: f64,
+}
+
+/// Pipeline spans reported as stages, in pipeline order
+const STAGES: [&str; 6] = ["segment", "extract", "scene_detect", "metadata", "write_outputs", "event_detect"];
+
+/// Frames in one pass of the scripted session: a window opens, a 20-character
+/// username is typed, an error dialog shows for three frames and goes, then
+/// another window opens, scrolls ten lines and asks to save
+const CYCLE_FRAMES: usize = 1 + 20 + 1 + 2 + 1 + 1 + 10 + 1 + 1;
+
+const USERNAME: &str = "jane.doe@example.com";
+
+async fn run(options: &Options, root: &Path, times: &SpanTimes) -> Result<BenchReport> {
+    let started_at = Utc::now();
+
+    // Rendering and encoding the session is not part of the pipeline
+    let videos = root.join("videos");
+    std::fs::create_dir_all(&videos)?;
+    let segments = options.frames.div_ceil(options.segment_secs);
+    let mut scripted_text: Vec<Vec<Vec<(String, keyframe_indexer::BoundingBox)>>> = Vec::new();
+    for segment in 0..segments {
+        let frames = options.segment_secs.min(options.frames - segment * options.segment_secs);
+        let recording = segment_recording(options, frames)?;
+        recording.write_video(&videos.join(format!("segment_{:04}.mp4", segment)))?;
+        scripted_text.push(recording.frames.into_iter().map(|frame| frame.text).collect());
+    }
+    reset_peak_memory();
+
+    // Keyframes are saved under `./frames` of the working directory
+    std::env::set_current_dir(root)?;
+    let config = IndexerConfig {
+        output_dir: root.join("output").to_string_lossy().to_string(),
+        extraction_fps: 1.0,
+        ..IndexerConfig::default()
+    };
+    let mut service = IndexerService::new(config)?;
+
+    // Extract, scene detection, metadata and output of every segment
+    times.take();
+    let started = Instant::now();
+    let processed = service.analyze_path(&videos, None, &BatchOptions::default()).await?;
+    let mut elapsed = started.elapsed();
+    let process_times = times.take();
+    let rss_after_process_kib = memory_kib().0;
+
+    // The OCR engine runs outside the indexer, so each keyframe's scripted
+    // text is stored as its OCR results before events are detected
+    let frames_root = root.join("frames");
+    let ocr_results = scripted_ocr(&frames_root, &scripted_text, started_at)?;
+    let mut ocr_store = OCRParquetWriter::new(&root.join("ocr").to_string_lossy())?;
+    ocr_store.write_ocr_results(&ocr_results).await?;
+    ocr_store.finalize().await?;
+
+    // Events from every keyframe with its OCR results
+    let started = Instant::now();
+    let detected = service.analyze_path(&frames_root, Some(&ocr_store), &BatchOptions::default()).await?;
+    elapsed += started.elapsed();
+    let detect_times = times.take();
+    let (rss_after_detect_kib, peak) = memory_kib();
+
+    let stages = STAGES
+        .iter()
+        .map(|&stage| {
+            // Segments also look for scrolling; only the detection pass is reported as `event_detect`
+            let (items, times) = match stage {
+                "segment" => (processed.segments_processed, &process_times),
+                "event_detect" => (detected.images_analyzed, &detect_times),
+                _ => (processed.keyframes, &process_times),
+            };
+            let (calls, busy) = times.get(stage).copied().unwrap_or_default();
+            let seconds = busy.as_secs_f64();
+            StageResult {
+                stage,
+                calls,
+                items,
+                seconds,
+                items_per_second: if seconds > 0.0 { items as f64 / seconds } else { 0.0 },
+            }
+        })
+        .collect();
+
+    let mut events_by_type = processed.events_by_type.clone();
+    for (event_type, count) in &detected.events_by_type {
+        *events_by_type.entry(event_type.clone()).or_default() += count;
+    }
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION"),
+        started_at,
+        frames: options.frames,
+        width: options.width,
+        height: options.height,
+        segments: processed.segments_processed,
+        keyframes: processed.keyframes,
+        scene_changes: processed.scene_changes,
+        ocr_results: ocr_results.len(),
+        events: events_by_type.values().sum(),
+        events_by_type,
+        failures: failures(&processed) + failures(&detected),
+        stages,
+        total_seconds: elapsed.as_secs_f64(),
+        rss_after_process_kib,
+        rss_after_detect_kib,
+        peak_rss_kib: [rss_after_process_kib, rss_after_detect_kib, peak].into_iter().flatten().max(),
+    })
+}
+
+fn failures(summary: &BatchSummary) -> usize {
+    for failure in &summary.failures {
+        eprintln!("{}", serde_json::to_string(failure).unwrap_or_default());
+    }
+    summary.failures.len()
+}
+
+/// One segment of the scripted session: signing in with an error dialog,
+/// then scrolling a spreadsheet, repeated for as long as the segment lasts
+fn segment_recording(options: &Options, frames: usize) -> Result<SyntheticRecording> {
+    let mut script = ScreenScript::new(options.width, options.height);
+    for _ in 0..frames.div_ceil(CYCLE_FRAMES) {
+        script = script
+            .open_window("Safari", "Sign in - Example Portal")
+            .type_into("Username", USERNAME)
+            .show_dialog("Sign-in failed", "Invalid password", true)
+            .idle(2)
+            .dismiss_dialog()
+            .open_window("Numbers", "Q3 Invoices.numbers")
+            .scroll(10)
+            .show_dialog("Save changes?", "Q3 Invoices has unsaved changes", false)
+            .dismiss_dialog();
+    }
+    let mut recording = script.render()?;
+    recording.frames.truncate(frames);
+    Ok(recording)
+}
+
+/// OCR results of every extracted keyframe, keyed by its path, from the text
+/// of the generated frame it was decoded from.
+///
+/// Keyframes are saved as `<segment_id>/frame_<segment_id>_<frame number>`,
+/// and segment ids start with the name of the video, `segment_<index>`.
+fn scripted_ocr(
+    frames_root: &Path,
+    scripted_text: &[Vec<Vec<(String, keyframe_indexer::BoundingBox)>>],
+    processed_at: DateTime<Utc>,
+) -> Result<Vec<OCRResult>> {
+    let mut results = Vec::new();
+    for segment_dir in std::fs::read_dir(frames_root)? {
+        let segment_dir = segment_dir?.path();
+        let segment_id = segment_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let Some(segment) = segment_id
+            .strip_prefix("segment_")
+            .and_then(|rest| rest.split('_').next())
+            .and_then(|index| index.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        for keyframe in std::fs::read_dir(&segment_dir)? {
+            let path = keyframe?.path();
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let Some(text) = stem
+                .rsplit('_')
+                .next()
+                .and_then(|frame| frame.parse::<usize>().ok())
+                .and_then(|frame| scripted_text.get(segment)?.get(frame))
+            else {
+                continue;
+            };
+            let frame_id = path.to_string_lossy().to_string();
+            results.extend(text.iter().map(|(text, roi)| OCRResult {
+                frame_id: frame_id.clone(),
+                roi: roi.clone(),
+                text: text.clone(),
+                language: "en-US".to_string(),
+                confidence: 0.95,
+                processed_at,
+                processor: "synthetic".to_string(),
+            }));
+        }
+    }
+    Ok(results)
+}
+
+/// Time spent inside each pipeline span, summed over every time it is
+/// entered, so awaiting I/O in other tasks is not counted
+#[derive(Clone, Default)]
+struct SpanTimes {
+    totals: Arc<Mutex<HashMap<&'static str, (usize, Duration)>>>,
+}
+
+/// When the span was last entered
+struct Entered(Option<Instant>);
+
+impl SpanTimes {
+    /// Calls and busy time of each span since the last take
+    fn take(&self) -> HashMap<&'static str, (usize, Duration)> {
+        self.totals.lock().map(|mut totals| std::mem::take(&mut *totals)).unwrap_or_default()
+    }
+}
+
+impl<S> Layer<S> for SpanTimes
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id).filter(|span| STAGES.contains(&span.name())) {
+            span.extensions_mut().insert(Entered(None));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(entered) = span.extensions_mut().get_mut::<Entered>() {
+                entered.0 = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(since) = span.extensions_mut().get_mut::<Entered>().and_then(|entered| entered.0.take()) else {
+            return;
+        };
+        if let Ok(mut totals) = self.totals.lock() {
+            totals.entry(span.name()).or_default().1 += since.elapsed();
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if span.extensions().get::<Entered>().is_some() {
+            if let Ok(mut totals) = self.totals.lock() {
+                totals.entry(span.name()).or_default().0 += 1;
+            }
+        }
+    }
+}
+
+/// Start the peak resident memory over, so generating the session doesn't count
+fn reset_peak_memory() {
+    // Linux only; elsewhere the peak is the highest resident size sampled
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Resident and peak resident memory of this process in KiB
+fn memory_kib() -> (Option<u64>, Option<u64>) {
+    if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+        let field = |name: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        };
+        return (field("VmRSS:"), field("VmHWM:"));
+    }
+    // No /proc on macOS; ps reports the resident size, and the peak is the highest seen
+    let rss = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &std::process::id().to_string()])
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|rss| rss.trim().parse().ok());
+    (rss, None)
+}
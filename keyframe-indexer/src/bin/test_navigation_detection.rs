@@ -69,6 +69,7 @@ fn create_test_configuration() -> NavigationIntegrationConfig {
         enable_focus_detection: true,
         min_detection_interval_ms: 100,
         min_confidence: 0.7,
+        ..NavigationDetectionConfig::default()
     };
     
     // Configure cursor tracking
@@ -80,6 +81,7 @@ fn create_test_configuration() -> NavigationIntegrationConfig {
         max_trail_gap_ms: 1000,
         min_confidence: 0.8,
         sampling_interval_ms: 100,
+        ..CursorTrackingConfig::default()
     };
     
     // Configure event correlation
@@ -90,6 +92,7 @@ fn create_test_configuration() -> NavigationIntegrationConfig {
         enable_temporal_correlation: true,
         enable_causal_correlation: true,
         spatial_correlation_radius: 50.0,
+        ..CorrelationConfig::default()
     };
     
     // Enable comprehensive logging for testing
@@ -1,4 +1,4 @@
-use keyframe_indexer::{OCRResult, OCRBatch, BoundingBox, OCRParquetWriter};
+use keyframe_indexer::{layout_compat, OCRResult, OCRBatch, BoundingBox, OCRParquetWriter};
 use chrono::Utc;
 use std::collections::HashMap;
 use tempfile::TempDir;
@@ -226,16 +226,14 @@ async fn run_performance_benchmark(storage_path: &str) -> Result<(), Box<dyn std
         println!("   🔍 Query time: {:?}", query_duration);
         
         // Check file size
-        let entries: Vec<_> = std::fs::read_dir(&benchmark_dir)?.collect();
+        let entries = layout_compat::parquet_files(&benchmark_dir)?;
         if let Some(entry) = entries.first() {
-            if let Ok(entry) = entry {
-                let metadata = std::fs::metadata(entry.path())?;
-                let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
-                println!("   💾 File size: {:.2} MB", size_mb);
-                
-                let compression_ratio = (size * 100) as f64 / metadata.len() as f64; // Rough estimate
-                println!("   🗜️  Compression efficiency: {:.2}x", compression_ratio);
-            }
+            let metadata = std::fs::metadata(entry)?;
+            let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
+            println!("   💾 File size: {:.2} MB", size_mb);
+            
+            let compression_ratio = (size * 100) as f64 / metadata.len() as f64; // Rough estimate
+            println!("   🗜️  Compression efficiency: {:.2}x", compression_ratio);
         }
         
         println!();
@@ -272,9 +270,9 @@ async fn test_compression_efficiency(storage_path: &str) -> Result<(), Box<dyn s
         let duration = start_time.elapsed();
         
         // Get file size
-        let entries: Vec<_> = std::fs::read_dir(&compression_dir)?.collect();
-        let file_size = if let Some(Ok(entry)) = entries.first() {
-            std::fs::metadata(entry.path())?.len()
+        let entries = layout_compat::parquet_files(&compression_dir)?;
+        let file_size = if let Some(entry) = entries.first() {
+            std::fs::metadata(entry)?.len()
         } else {
             0
         };
@@ -0,0 +1,271 @@
+use crate::error::{IndexerError, Result};
+use crate::navigation_detector::TabState;
+use crate::system_probe::SystemProbe;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Browsers whose active tab can be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Browser {
+    Safari,
+    Chrome,
+    Firefox,
+    Edge,
+    Arc,
+    Brave,
+}
+
+impl Browser {
+    pub const ALL: [Browser; 6] = [Browser::Safari, Browser::Chrome, Browser::Firefox, Browser::Edge, Browser::Arc, Browser::Brave];
+
+    /// Application name, as reported for the frontmost process
+    pub fn app_name(&self) -> &'static str {
+        match self {
+            Self::Safari => "Safari",
+            Self::Chrome => "Google Chrome",
+            Self::Firefox => "Firefox",
+            Self::Edge => "Microsoft Edge",
+            Self::Arc => "Arc",
+            Self::Brave => "Brave Browser",
+        }
+    }
+
+    pub fn from_app_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|b| b.app_name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// AppleScript returning `title|url|tab index`
+    fn script(&self) -> String {
+        match self {
+            Self::Safari => r#"
+                tell application "Safari"
+                    if (count of windows) = 0 then return "||0"
+                    set currentTab to current tab of front window
+                    return (name of currentTab) & "|" & (URL of currentTab) & "|" & (index of currentTab)
+                end tell
+            "#
+            .to_string(),
+            // Chromium browsers share Chrome's scripting dictionary
+            Self::Chrome | Self::Edge | Self::Brave => format!(
+                r#"
+                tell application "{}"
+                    if (count of windows) = 0 then return "||0"
+                    set currentTab to active tab of front window
+                    return (title of currentTab) & "|" & (URL of currentTab) & "|" & (active tab index of front window)
+                end tell
+            "#,
+                self.app_name()
+            ),
+            Self::Arc => r#"
+                tell application "Arc"
+                    if (count of windows) = 0 then return "||0"
+                    set currentTab to active tab of front window
+                    return (title of currentTab) & "|" & (URL of currentTab) & "|0"
+                end tell
+            "#
+            .to_string(),
+            // Firefox has no scripting dictionary; only the window title is available
+            Self::Firefox => r#"
+                tell application "System Events"
+                    tell (first application process whose frontmost is true)
+                        if (count of windows) = 0 then return "||0"
+                        return (name of front window) & "||0"
+                    end tell
+                end tell
+            "#
+            .to_string(),
+        }
+    }
+}
+
+/// Background browser polling settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserPollerConfig {
+    /// Poll in the background; otherwise the browser is queried on every frame
+    pub enabled: bool,
+    pub poll_interval_ms: u64,
+    /// A new tab state must hold this long before it is reported, so page loads
+    /// that retitle a tab several times count as one change
+    pub debounce_ms: u64,
+    pub browsers: Vec<Browser>,
+    /// Tab changes buffered for the detector; later ones are dropped when full
+    pub channel_capacity: usize,
+}
+
+impl Default for BrowserPollerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_ms: 500,
+            debounce_ms: 300,
+            browsers: Browser::ALL.to_vec(),
+            channel_capacity: 64,
+        }
+    }
+}
+
+/// Parse a `title|url|tab index` response; titles may contain `|`
+pub fn parse_tab_response(browser: Browser, response: &str) -> Result<TabState> {
+    let mut parts = response.trim().rsplitn(3, '|');
+    let (Some(index), Some(url), Some(title)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(IndexerError::navigation(format!("Invalid {} response", browser.app_name())));
+    };
+    Ok(TabState {
+        app_name: browser.app_name().to_string(),
+        tab_title: title.to_string(),
+        url: Some(url.to_string()).filter(|u| !u.is_empty() && u != "missing value"),
+        tab_index: index.parse().ok().filter(|i| *i > 0),
+        timestamp: Utc::now(),
+    })
+}
+
+/// Active tab of the frontmost app, when it is one of `browsers`.
+///
+/// Only the frontmost browser is asked, so browsers that are not running are
+/// never launched by the query.
+pub async fn query_frontmost_tab(probe: &SystemProbe, browsers: &[Browser]) -> Result<Option<TabState>> {
+    let frontmost = probe
+        .osascript(
+            "System Events",
+            r#"tell application "System Events" to return name of first application process whose frontmost is true"#,
+        )
+        .await?;
+    match Browser::from_app_name(&frontmost).filter(|b| browsers.contains(b)) {
+        Some(browser) => {
+            let response = probe.osascript(browser.app_name(), &browser.script()).await?;
+            parse_tab_response(browser, &response).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Reports a tab state once it has held for the debounce period
+#[derive(Debug)]
+pub struct TabDebouncer {
+    debounce: Duration,
+    published: Option<TabState>,
+    pending: Option<(TabState, Instant)>,
+}
+
+impl TabDebouncer {
+    pub fn new(debounce: Duration) -> Self {
+        Self { debounce, published: None, pending: None }
+    }
+
+    /// Feed one poll result; returns the tab state to report, if it is a settled change.
+    /// `None` (no browser in front) keeps the last reported tab, so returning to it is no change.
+    pub fn observe(&mut self, state: Option<TabState>, now: Instant) -> Option<TabState> {
+        let Some(state) = state else {
+            self.pending = None;
+            return None;
+        };
+        if self.published.as_ref().map_or(false, |p| p.same_tab(&state)) {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending.as_ref() {
+            Some((pending, since)) if pending.same_tab(&state) => *since,
+            _ => now,
+        };
+        if since == now {
+            self.pending = Some((state, now));
+        }
+        if now.duration_since(since) < self.debounce {
+            return None;
+        }
+        // Report the state as of when it first appeared
+        let (settled, _) = self.pending.take()?;
+        self.published = Some(settled.clone());
+        Some(settled)
+    }
+}
+
+/// Polls the frontmost browser on a background task and sends settled tab
+/// changes over a channel, so frame processing never waits on AppleScript.
+/// The task stops when the poller is dropped.
+pub struct BrowserPoller {
+    receiver: mpsc::Receiver<TabState>,
+    task: JoinHandle<()>,
+}
+
+impl BrowserPoller {
+    /// Start polling; must be called within a Tokio runtime
+    pub fn spawn(config: BrowserPollerConfig, probe: SystemProbe) -> Self {
+        let (sender, receiver) = mpsc::channel(config.channel_capacity.max(1));
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(config.poll_interval_ms.max(1)));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut debouncer = TabDebouncer::new(Duration::from_millis(config.debounce_ms));
+            loop {
+                interval.tick().await;
+                let polled = match query_frontmost_tab(&probe, &config.browsers).await {
+                    Ok(state) => state,
+                    Err(e) => {
+                        debug!("Browser poll failed: {}", e);
+                        continue;
+                    }
+                };
+                let Some(change) = debouncer.observe(polled, Instant::now()) else {
+                    continue;
+                };
+                match sender.try_send(change) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(_)) => warn!("Dropping browser tab change; detector is behind"),
+                    Err(mpsc::error::TrySendError::Closed(_)) => break,
+                }
+            }
+        });
+        Self { receiver, task }
+    }
+
+    /// Tab changes reported since the last call, oldest first
+    pub fn drain(&mut self) -> Vec<TabState> {
+        let mut changes = Vec::new();
+        while let Ok(change) = self.receiver.try_recv() {
+            changes.push(change);
+        }
+        changes
+    }
+}
+
+impl Drop for BrowserPoller {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounces_tab_changes_and_parses_responses() {
+        let tab = |title: &str| parse_tab_response(Browser::Brave, &format!("{}|https://example.com/{}|2", title, title)).unwrap();
+        assert_eq!(tab("a").app_name, "Brave Browser");
+        assert_eq!(tab("a").tab_index, Some(2));
+        let firefox = parse_tab_response(Browser::Firefox, "Docs | Project||0").unwrap();
+        assert_eq!(firefox.tab_title, "Docs | Project");
+        assert_eq!(firefox.url, None);
+        assert_eq!(Browser::from_app_name("firefox"), Some(Browser::Firefox));
+
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut debouncer = TabDebouncer::new(Duration::from_millis(300));
+        assert!(debouncer.observe(Some(tab("a")), at(0)).is_none());
+        assert_eq!(debouncer.observe(Some(tab("a")), at(500)).unwrap().tab_title, "a");
+        // A title that flickers during a page load is never reported
+        assert!(debouncer.observe(Some(tab("loading")), at(1000)).is_none());
+        assert!(debouncer.observe(Some(tab("b")), at(1100)).is_none());
+        assert_eq!(debouncer.observe(Some(tab("b")), at(1500)).unwrap().tab_title, "b");
+        // Leaving the browser and coming back to the same tab is no change
+        assert!(debouncer.observe(None, at(2000)).is_none());
+        assert!(debouncer.observe(Some(tab("b")), at(2500)).is_none());
+        assert!(debouncer.observe(Some(tab("b")), at(3000)).is_none());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::{IndexerError, Result};
+use crate::ocr_data::BoundingBox;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Sampling extra frames while a toast or snackbar is on screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstSamplingConfig {
+    pub enabled: bool,
+    /// Rate frames are kept at during a burst
+    pub burst_fps: f32,
+    /// How long a burst lasts after a transient region appears
+    pub burst_secs: f32,
+    /// Share of the width or height from each edge a region must reach into
+    pub edge_margin: f32,
+    /// Smallest and largest share of the frame a transient region may cover
+    pub min_area: f32,
+    pub max_area: f32,
+    /// Brightness difference (0-255) at which a thumbnail cell counts as changed
+    pub cell_threshold: u8,
+    /// Width of the thumbnail frames are compared at
+    pub grid_width: u32,
+}
+
+impl Default for BurstSamplingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            burst_fps: 5.0,
+            burst_secs: 3.0,
+            edge_margin: 0.15,
+            min_area: 0.001,
+            max_area: 0.08,
+            cell_threshold: 24,
+            grid_width: 64,
+        }
+    }
+}
+
+impl BurstSamplingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.burst_fps <= 0.0 || self.burst_fps > 30.0 || self.burst_secs <= 0.0 {
+            return Err(IndexerError::Config("burst_sampling needs 0 < burst_fps <= 30 and positive burst_secs".to_string()));
+        }
+        if !(0.0..=0.5).contains(&self.edge_margin) || !(0.0 < self.min_area && self.min_area < self.max_area && self.max_area <= 1.0) {
+            return Err(IndexerError::Config(
+                "burst_sampling needs edge_margin in [0, 0.5] and 0 < min_area < max_area <= 1".to_string(),
+            ));
+        }
+        if self.grid_width < 8 {
+            return Err(IndexerError::Config("burst_sampling grid_width must be at least 8".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Brightness thumbnail of a frame, compared cell by cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct LumaGrid {
+    pub cols: u32,
+    pub rows: u32,
+    pub cells: Vec<u8>,
+}
+
+impl LumaGrid {
+    pub fn from_rgb(image: &image::RgbImage, cols: u32) -> Self {
+        let cols = cols.min(image.width()).max(1);
+        let rows = ((image.height() as f32 * cols as f32 / image.width().max(1) as f32).round() as u32).max(1);
+        let luma = image::imageops::grayscale(image);
+        let thumbnail = image::imageops::thumbnail(&luma, cols, rows);
+        Self { cols, rows, cells: thumbnail.into_raw() }
+    }
+}
+
+/// A small region that appeared near an edge of the screen between two
+/// frames, e.g. a toast sliding in; `region` is in shares of the frame
+pub fn detect_transient(previous: &LumaGrid, current: &LumaGrid, config: &BurstSamplingConfig) -> Option<BoundingBox> {
+    if previous.cols != current.cols || previous.rows != current.rows {
+        return None;
+    }
+    let (cols, rows) = (current.cols as usize, current.rows as usize);
+    let total = (cols * rows) as f32;
+    let changed: Vec<bool> = previous
+        .cells
+        .iter()
+        .zip(&current.cells)
+        .map(|(a, b)| a.abs_diff(*b) > config.cell_threshold)
+        .collect();
+    // Most of the screen changing is an app switch or scroll, not a toast
+    let changed_count = changed.iter().filter(|c| **c).count() as f32;
+    if changed_count == 0.0 || changed_count / total > config.max_area * 2.0 {
+        return None;
+    }
+
+    let mut visited = vec![false; changed.len()];
+    let mut best: Option<(usize, BoundingBox)> = None;
+    for start in 0..changed.len() {
+        if !changed[start] || visited[start] {
+            continue;
+        }
+        // Bounds of the 4-connected changed cells around `start`
+        let (mut x0, mut y0, mut x1, mut y1) = (cols, rows, 0, 0);
+        let mut size = 0;
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(cell) = stack.pop() {
+            let (x, y) = (cell % cols, cell / cols);
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            size += 1;
+            let neighbours = [
+                (x > 0).then(|| cell - 1),
+                (x + 1 < cols).then(|| cell + 1),
+                (y > 0).then(|| cell - cols),
+                (y + 1 < rows).then(|| cell + cols),
+            ];
+            for next in neighbours.into_iter().flatten() {
+                if changed[next] && !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        let region = BoundingBox::new(
+            x0 as f32 / cols as f32,
+            y0 as f32 / rows as f32,
+            (x1 - x0 + 1) as f32 / cols as f32,
+            (y1 - y0 + 1) as f32 / rows as f32,
+        );
+        let area = region.width * region.height;
+        let margin = config.edge_margin;
+        let near_edge = region.x <= margin
+            || region.y <= margin
+            || region.x + region.width >= 1.0 - margin
+            || region.y + region.height >= 1.0 - margin;
+        if near_edge && (config.min_area..=config.max_area).contains(&area) && best.as_ref().map_or(true, |(s, _)| size > *s) {
+            best = Some((size, region));
+        }
+    }
+    best.map(|(_, region)| region)
+}
+
+/// Decides which frames between the regular samples to keep while a
+/// transient message is on screen.
+///
+/// Every kept frame is passed to `observe`; when a small region appears near
+/// an edge compared with the frame kept before, frames are kept at
+/// `burst_fps` for `burst_secs`.
+pub struct BurstSampler {
+    config: BurstSamplingConfig,
+    previous: Option<LumaGrid>,
+    /// Time of the last kept frame, in seconds
+    last_kept: Option<f64>,
+    burst_until: Option<f64>,
+    /// Bursts started and extra frames kept during them
+    pub bursts: usize,
+    pub extra_frames: usize,
+}
+
+impl BurstSampler {
+    pub fn new(config: BurstSamplingConfig) -> Self {
+        Self { config, previous: None, last_kept: None, burst_until: None, bursts: 0, extra_frames: 0 }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Whether a frame at `secs` that the regular rate skips is kept for a burst
+    pub fn wants_extra(&self, secs: f64) -> bool {
+        let in_burst = self.burst_until.map_or(false, |until| secs <= until);
+        // Some slack, as frames never arrive exactly on time
+        let due = self.last_kept.map_or(true, |last| secs - last >= 0.9 / self.config.burst_fps as f64);
+        self.config.enabled && in_burst && due
+    }
+
+    /// Note a kept frame; returns the region that started a burst, if one did
+    pub fn observe(&mut self, secs: f64, image: &image::RgbImage, extra: bool) -> Option<BoundingBox> {
+        if !self.config.enabled {
+            return None;
+        }
+        if extra {
+            self.extra_frames += 1;
+        }
+        self.last_kept = Some(secs);
+        let grid = LumaGrid::from_rgb(image, self.config.grid_width);
+        let in_burst = self.burst_until.map_or(false, |until| secs <= until);
+        let region = match &self.previous {
+            Some(previous) if !in_burst => detect_transient(previous, &grid, &self.config),
+            _ => None,
+        };
+        self.previous = Some(grid);
+        if let Some(region) = &region {
+            debug!(
+                "Transient region at ({:.2}, {:.2}) {:.2}x{:.2}; sampling at {} fps for {} s",
+                region.x, region.y, region.width, region.height, self.config.burst_fps, self.config.burst_secs
+            );
+            self.burst_until = Some(secs + self.config.burst_secs as f64);
+            self.bursts += 1;
+        }
+        region
+    }
+
+    /// Forget the previous frame, e.g. when a new recording starts
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.last_kept = None;
+        self.burst_until = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(toast: Option<(u32, u32, u32, u32)>) -> image::RgbImage {
+        image::RgbImage::from_fn(640, 360, |x, y| match toast {
+            Some((tx, ty, w, h)) if (tx..tx + w).contains(&x) && (ty..ty + h).contains(&y) => image::Rgb([240, 240, 240]),
+            _ => image::Rgb([30, 30, 40]),
+        })
+    }
+
+    #[test]
+    fn test_toast_near_edge_starts_burst() {
+        let config = BurstSamplingConfig { enabled: true, ..Default::default() };
+        let plain = LumaGrid::from_rgb(&screen(None), config.grid_width);
+        let toast = LumaGrid::from_rgb(&screen(Some((480, 300, 140, 40))), config.grid_width);
+        let centered = LumaGrid::from_rgb(&screen(Some((250, 160, 140, 40))), config.grid_width);
+        let region = detect_transient(&plain, &toast, &config).unwrap();
+        assert!(region.x > 0.7 && region.y > 0.8);
+        assert!(detect_transient(&plain, &centered, &config).is_none());
+        assert!(detect_transient(&plain, &plain, &config).is_none());
+
+        let mut sampler = BurstSampler::new(config);
+        assert!(sampler.observe(0.0, &screen(None), false).is_none());
+        assert!(!sampler.wants_extra(0.5));
+        assert!(sampler.observe(1.0, &screen(Some((480, 300, 140, 40))), false).is_some());
+        assert!(!sampler.wants_extra(1.1));
+        assert!(sampler.wants_extra(1.2));
+        sampler.observe(1.2, &screen(None), true);
+        assert!(!sampler.wants_extra(1.3));
+        assert!(sampler.wants_extra(1.4));
+        assert!(!sampler.wants_extra(4.5));
+        assert_eq!((sampler.bursts, sampler.extra_frames), (1, 1));
+    }
+}
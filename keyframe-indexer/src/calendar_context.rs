@@ -0,0 +1,822 @@
+use crate::error::{IndexerError, Result};
+use crate::metadata_collector::FrameMetadata;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Calendar enrichment settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Tag segments with concurrent meetings
+    pub enabled: bool,
+    /// ICS file to read; the companion app exports the EventKit calendar here
+    pub ics_path: Option<String>,
+}
+
+/// A meeting or appointment from the user's calendar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub location: Option<String>,
+    pub all_day: bool,
+}
+
+impl CalendarEvent {
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start <= end && start < self.end
+    }
+}
+
+/// Meeting concurrent with part of a video segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentCalendarTag {
+    pub segment_id: String,
+    pub meeting_uid: String,
+    pub meeting_title: String,
+    /// Overlap between the segment and the meeting
+    pub overlap_start: DateTime<Utc>,
+    pub overlap_end: DateTime<Utc>,
+    /// Keyframes of the segment captured during the meeting
+    pub frame_count: usize,
+}
+
+/// Keyframes of one app captured during a meeting a segment overlaps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingAppUsage {
+    pub segment_id: String,
+    pub meeting_uid: String,
+    pub app_name: String,
+    pub frame_count: usize,
+}
+
+/// Calendar events loaded from an ICS file, queried by time
+pub struct CalendarContext {
+    events: Vec<CalendarEvent>,
+    source_path: Option<PathBuf>,
+    source_modified: Option<SystemTime>,
+}
+
+impl CalendarContext {
+    pub fn from_events(mut events: Vec<CalendarEvent>) -> Self {
+        events.sort_by_key(|e| e.start);
+        Self {
+            events,
+            source_path: None,
+            source_modified: None,
+        }
+    }
+
+    /// Load events from an ICS file
+    pub fn load_ics<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| IndexerError::Config(format!("Failed to read calendar {}: {}", path.display(), e)))?;
+
+        let mut context = Self::from_events(parse_ics(&content));
+        context.source_path = Some(path.to_path_buf());
+        context.source_modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        info!("Loaded {} calendar events from {}", context.events.len(), path.display());
+        Ok(context)
+    }
+
+    /// Reload the ICS file if it changed on disk; returns true when reloaded
+    pub fn refresh_if_changed(&mut self) -> Result<bool> {
+        let Some(path) = self.source_path.clone() else {
+            return Ok(false);
+        };
+
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified.is_none() || modified == self.source_modified {
+            return Ok(false);
+        }
+
+        *self = Self::load_ics(&path)?;
+        Ok(true)
+    }
+
+    /// Events in progress at a point in time
+    pub fn events_at(&self, time: DateTime<Utc>) -> Vec<&CalendarEvent> {
+        self.events
+            .iter()
+            .take_while(|e| e.start <= time)
+            .filter(|e| time < e.end)
+            .collect()
+    }
+
+    /// The meeting the user is most likely in: timed events win over all-day ones,
+    /// and shorter events over longer ones
+    pub fn meeting_at(&self, time: DateTime<Utc>) -> Option<&CalendarEvent> {
+        self.events_at(time)
+            .into_iter()
+            .min_by_key(|e| (e.all_day, e.end - e.start))
+    }
+
+    /// Tag a segment with every timed meeting that overlaps it.
+    ///
+    /// Frame timestamps are offsets from `segment_start`.
+    pub fn tag_segment(
+        &self,
+        segment_id: &str,
+        segment_start: DateTime<Utc>,
+        frames: &[FrameMetadata],
+    ) -> Vec<SegmentCalendarTag> {
+        let frame_times: Vec<DateTime<Utc>> = frames
+            .iter()
+            .map(|f| segment_start + Duration::nanoseconds(f.ts_ns))
+            .collect();
+
+        let (Some(first), Some(last)) = (frame_times.iter().min(), frame_times.iter().max()) else {
+            return Vec::new();
+        };
+
+        self.events
+            .iter()
+            .filter(|e| !e.all_day && e.overlaps(*first, *last))
+            .map(|e| SegmentCalendarTag {
+                segment_id: segment_id.to_string(),
+                meeting_uid: e.uid.clone(),
+                meeting_title: e.title.clone(),
+                overlap_start: e.start.max(*first),
+                overlap_end: e.end.min(*last),
+                frame_count: frame_times.iter().filter(|t| e.start <= **t && **t < e.end).count(),
+            })
+            .filter(|tag| tag.frame_count > 0)
+            .collect()
+    }
+
+    /// Keyframe count per application during a meeting
+    pub fn app_usage_during(
+        &self,
+        meeting_uid: &str,
+        segment_start: DateTime<Utc>,
+        frames: &[FrameMetadata],
+    ) -> HashMap<String, usize> {
+        let mut usage = HashMap::new();
+        let Some(meeting) = self.events.iter().find(|e| e.uid == meeting_uid) else {
+            return usage;
+        };
+
+        for frame in frames {
+            let time = segment_start + Duration::nanoseconds(frame.ts_ns);
+            if meeting.start <= time && time < meeting.end {
+                *usage.entry(frame.app_name.clone()).or_insert(0) += 1;
+            }
+        }
+
+        usage
+    }
+
+    /// Apps used during each meeting the segment was tagged with, most used first
+    pub fn app_usage(
+        &self,
+        tags: &[SegmentCalendarTag],
+        segment_start: DateTime<Utc>,
+        frames: &[FrameMetadata],
+    ) -> Vec<MeetingAppUsage> {
+        let mut usage = Vec::new();
+        for tag in tags {
+            let mut apps: Vec<(String, usize)> = self.app_usage_during(&tag.meeting_uid, segment_start, frames).into_iter().collect();
+            apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            usage.extend(apps.into_iter().map(|(app_name, frame_count)| MeetingAppUsage {
+                segment_id: tag.segment_id.clone(),
+                meeting_uid: tag.meeting_uid.clone(),
+                app_name,
+                frame_count,
+            }));
+        }
+        usage
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+}
+
+/// Parse VEVENT entries from ICS content.
+///
+/// Supports UTC, floating (treated as local time), TZID and all-day dates,
+/// DTEND or DURATION, and skips cancelled events. Recurring events are
+/// expanded into one event per occurrence, less EXDATEs, with moved or
+/// cancelled occurrences (RECURRENCE-ID) applied; each occurrence's UID is
+/// the series UID followed by `/` and its UTC start.
+pub fn parse_ics(content: &str) -> Vec<CalendarEvent> {
+    let mut entries = Vec::new();
+    let mut current: Option<Properties> = None;
+
+    for line in unfold_lines(content) {
+        match line.as_str() {
+            "BEGIN:VEVENT" => current = Some(HashMap::new()),
+            "END:VEVENT" => entries.extend(current.take()),
+            _ => {
+                if let Some(properties) = current.as_mut() {
+                    if let Some((name, params, value)) = split_property(&line) {
+                        properties.entry(name).or_default().push((params, value));
+                    }
+                }
+            }
+        }
+    }
+
+    // Occurrences moved or cancelled by an entry of their own, by UID and original start
+    let mut overridden: HashSet<(String, DateTime<Utc>)> = HashSet::new();
+    let mut overrides = Vec::new();
+    let mut series = Vec::new();
+    for properties in entries {
+        let recurrence_id = first(&properties, "RECURRENCE-ID")
+            .and_then(|(params, value)| parse_ics_time(params, value))
+            .and_then(|time| time.to_utc());
+        let uid = first(&properties, "UID").map(|(_, uid)| unescape_text(uid));
+        match (recurrence_id, uid) {
+            (Some(original_start), Some(uid)) => {
+                overridden.insert((uid, original_start));
+                overrides.push((properties, original_start));
+            }
+            _ => series.push(properties),
+        }
+    }
+
+    let mut events = Vec::new();
+    for properties in &series {
+        match build_events(properties, None) {
+            Some(occurrences) => events.extend(occurrences.into_iter().filter(|(uid, event)| {
+                !uid.as_ref().is_some_and(|uid| overridden.contains(&(uid.clone(), event.start)))
+            }).map(|(_, event)| event)),
+            None => debug!("Skipping calendar entry without usable times"),
+        }
+    }
+    for (properties, original_start) in &overrides {
+        if let Some(occurrences) = build_events(properties, Some(*original_start)) {
+            events.extend(occurrences.into_iter().map(|(_, event)| event));
+        }
+    }
+
+    events
+}
+
+// MARK: - ICS Helpers
+
+/// Values of each property of a VEVENT, with their parameters, in file order
+type Properties = HashMap<String, Vec<(String, String)>>;
+
+/// Recurring events are expanded up to this many days after now
+const RECURRENCE_HORIZON_DAYS: i64 = 366;
+/// Occurrences expanded from one recurring event at most
+const MAX_OCCURRENCES: usize = 10_000;
+
+fn first<'a>(properties: &'a Properties, name: &str) -> Option<(&'a str, &'a str)> {
+    properties
+        .get(name)
+        .and_then(|values| values.first())
+        .map(|(params, value)| (params.as_str(), value.as_str()))
+}
+
+/// Join folded continuation lines (RFC 5545 section 3.1)
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(continuation) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+    lines
+}
+
+/// Split `NAME;PARAMS:VALUE` into upper-cased name, params and value.
+///
+/// Parameter values keep their case, since time zone IDs are case-sensitive.
+fn split_property(line: &str) -> Option<(String, String, String)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.to_ascii_uppercase(), params.to_string(), value.to_string()))
+}
+
+/// Value of one `;`-separated parameter, matched case-insensitively
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Build the event of a VEVENT, or one event per occurrence when it recurs,
+/// each with its occurrence UID when it belongs to a series.
+///
+/// `original_start` is the RECURRENCE-ID of an entry that replaces one
+/// occurrence of a series.
+fn build_events(properties: &Properties, original_start: Option<DateTime<Utc>>) -> Option<Vec<(Option<String>, CalendarEvent)>> {
+    let text = |name: &str| first(properties, name).map(|(_, value)| unescape_text(value));
+
+    if text("STATUS").map_or(false, |s| s.eq_ignore_ascii_case("CANCELLED")) {
+        return Some(Vec::new());
+    }
+
+    let (start_params, start_value) = first(properties, "DTSTART")?;
+    let dtstart = parse_ics_time(start_params, start_value)?;
+    let start = dtstart.to_utc()?;
+
+    let end = match first(properties, "DTEND") {
+        Some((params, value)) => parse_ics_time(params, value)?.to_utc()?,
+        None => match first(properties, "DURATION") {
+            Some((_, value)) => start + parse_duration(value)?,
+            None if dtstart.all_day => start + Duration::days(1),
+            None => start,
+        },
+    };
+    let length = (end - start).max(Duration::zero());
+
+    let title = text("SUMMARY").unwrap_or_else(|| "Untitled event".to_string());
+    let uid = text("UID").unwrap_or_else(|| format!("{}-{}", start.timestamp(), text("SUMMARY").unwrap_or_default()));
+    let location = text("LOCATION").filter(|l| !l.is_empty());
+    let event = |uid: String, start: DateTime<Utc>| CalendarEvent {
+        uid,
+        title: title.clone(),
+        start,
+        end: start + length,
+        location: location.clone(),
+        all_day: dtstart.all_day,
+    };
+
+    if let Some(original_start) = original_start {
+        return Some(vec![(Some(uid.clone()), event(occurrence_uid(&uid, original_start), start))]);
+    }
+
+    let Some(rule) = first(properties, "RRULE").and_then(|(_, value)| RecurrenceRule::parse(value)) else {
+        if properties.contains_key("RRULE") {
+            warn!("Unsupported recurrence rule on calendar event '{}'; only its first occurrence is matched", title);
+        }
+        return Some(vec![(None, event(uid, start))]);
+    };
+
+    let excluded: HashSet<DateTime<Utc>> = properties
+        .get("EXDATE")
+        .into_iter()
+        .flatten()
+        .flat_map(|(params, value)| value.split(',').filter_map(move |v| parse_ics_time(params, v)?.to_utc()))
+        .collect();
+    let horizon = Utc::now() + Duration::days(RECURRENCE_HORIZON_DAYS);
+    let occurrences = rule
+        .occurrences(&dtstart, horizon)
+        .into_iter()
+        .filter(|occurrence| !excluded.contains(occurrence))
+        .map(|occurrence| (Some(uid.clone()), event(occurrence_uid(&uid, occurrence), occurrence)))
+        .collect();
+    Some(occurrences)
+}
+
+fn occurrence_uid(uid: &str, start: DateTime<Utc>) -> String {
+    format!("{}/{}", uid, start.format("%Y%m%dT%H%M%SZ"))
+}
+
+/// Time zone a DATE or DATE-TIME value is given in
+#[derive(Debug, Clone, Copy)]
+enum IcsZone {
+    Utc,
+    /// Floating times and dates are taken as local time
+    Local,
+    Named(Tz),
+}
+
+/// A DATE or DATE-TIME value as written, before conversion to UTC
+#[derive(Debug, Clone, Copy)]
+struct IcsTime {
+    local: NaiveDateTime,
+    zone: IcsZone,
+    all_day: bool,
+}
+
+impl IcsTime {
+    fn to_utc(self) -> Option<DateTime<Utc>> {
+        self.zone.to_utc(self.local)
+    }
+}
+
+impl IcsZone {
+    /// Wall-clock times skipped by a DST change are moved an hour later
+    fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let convert = |time: NaiveDateTime| match self {
+            IcsZone::Utc => Some(Utc.from_utc_datetime(&time)),
+            IcsZone::Local => Local.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+            IcsZone::Named(tz) => tz.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+        };
+        convert(local).or_else(|| convert(local + Duration::hours(1)))
+    }
+}
+
+/// Parse a DATE or DATE-TIME value with the parameters of its property
+fn parse_ics_time(params: &str, value: &str) -> Option<IcsTime> {
+    let value = value.trim();
+
+    if param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime { local: date.and_hms_opt(0, 0, 0)?, zone: IcsZone::Local, all_day: true });
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let local = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(IcsTime { local, zone: IcsZone::Utc, all_day: false });
+    }
+
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = match param(params, "TZID") {
+        // Outlook writes Windows zone names, which are not in the IANA database
+        Some(tzid) => match tzid.parse::<Tz>() {
+            Ok(tz) => IcsZone::Named(tz),
+            Err(_) => {
+                debug!("Unknown calendar time zone '{}'; using local time", tzid);
+                IcsZone::Local
+            }
+        },
+        None => IcsZone::Local,
+    };
+    Some(IcsTime { local, zone, all_day: false })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// An RRULE; BYDAY is supported for weekly rules and, with an optional
+/// ordinal such as `-1FR`, for monthly ones
+#[derive(Debug, Clone)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Utc>>,
+    by_day: Vec<(Option<i32>, Weekday)>,
+}
+
+impl RecurrenceRule {
+    fn parse(value: &str) -> Option<Self> {
+        let mut rule = Self { frequency: Frequency::Daily, interval: 1, count: None, until: None, by_day: Vec::new() };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|&i| i > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => {
+                    let until = parse_ics_time("", value)?;
+                    // A date bound includes the whole day
+                    let end_of_day = if until.all_day { Duration::days(1) - Duration::seconds(1) } else { Duration::zero() };
+                    rule.until = Some(until.to_utc()? + end_of_day);
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let day = day.trim();
+                        if !day.is_ascii() {
+                            return None;
+                        }
+                        let split = day.len().checked_sub(2)?;
+                        let ordinal = match &day[..split] {
+                            "" => None,
+                            ordinal => Some(ordinal.trim_start_matches('+').parse().ok()?),
+                        };
+                        rule.by_day.push((ordinal, parse_weekday(&day[split..])?));
+                    }
+                }
+                "WKST" => {}
+                // BYMONTH, BYMONTHDAY, BYSETPOS and the like would change which days occur
+                _ => return None,
+            }
+        }
+        rule.frequency = frequency?;
+        let ordinals_allowed = rule.frequency == Frequency::Monthly;
+        if rule.by_day.iter().any(|(ordinal, _)| ordinal.is_some() && !ordinals_allowed)
+            || (!rule.by_day.is_empty() && matches!(rule.frequency, Frequency::Daily | Frequency::Yearly))
+        {
+            return None;
+        }
+        Some(rule)
+    }
+
+    /// UTC starts of the occurrences up to `horizon`, the first being DTSTART itself
+    fn occurrences(&self, dtstart: &IcsTime, horizon: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let limit = self.until.map_or(horizon, |until| until.min(horizon));
+        let max = self.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+        let start_date = dtstart.local.date();
+        let time = dtstart.local.time();
+
+        let mut occurrences = Vec::new();
+        for period in 0.. {
+            let Some(period_start) = self.period_start(start_date, period) else {
+                break;
+            };
+            match dtstart.zone.to_utc(period_start.and_time(time)) {
+                Some(period_time) if period_time <= limit => {}
+                _ => break,
+            }
+            for date in self.dates_in_period(start_date, period_start) {
+                if date < start_date {
+                    continue;
+                }
+                let Some(occurrence) = dtstart.zone.to_utc(date.and_time(time)) else {
+                    continue;
+                };
+                if occurrence > limit || occurrences.len() >= max {
+                    return occurrences;
+                }
+                occurrences.push(occurrence);
+            }
+        }
+        occurrences
+    }
+
+    /// First day of the `period`th interval after the one DTSTART is in
+    fn period_start(&self, start: NaiveDate, period: u32) -> Option<NaiveDate> {
+        let steps = period.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_signed(Duration::days(steps as i64)),
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                monday.checked_add_signed(Duration::weeks(steps as i64))
+            }
+            Frequency::Monthly => {
+                let months = start.month0() as i64 + steps as i64;
+                NaiveDate::from_ymd_opt(start.year() + (months / 12) as i32, (months % 12) as u32 + 1, 1)
+            }
+            Frequency::Yearly => NaiveDate::from_ymd_opt(start.year().checked_add(steps as i32)?, 1, 1),
+        }
+    }
+
+    /// Days of one interval that occur, in order; dates the month or year
+    /// lacks, such as the 31st or February 29th, are skipped
+    fn dates_in_period(&self, start: NaiveDate, period_start: NaiveDate) -> Vec<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => vec![period_start],
+            Frequency::Weekly if self.by_day.is_empty() => {
+                vec![period_start + Duration::days(start.weekday().num_days_from_monday() as i64)]
+            }
+            Frequency::Weekly => {
+                let mut dates: Vec<NaiveDate> = self
+                    .by_day
+                    .iter()
+                    .map(|(_, day)| period_start + Duration::days(day.num_days_from_monday() as i64))
+                    .collect();
+                dates.sort();
+                dates.dedup();
+                dates
+            }
+            Frequency::Monthly if self.by_day.is_empty() => {
+                NaiveDate::from_ymd_opt(period_start.year(), period_start.month(), start.day()).into_iter().collect()
+            }
+            Frequency::Monthly => {
+                let mut dates: Vec<NaiveDate> = self
+                    .by_day
+                    .iter()
+                    .flat_map(|&(ordinal, day)| weekdays_of_month(period_start, day, ordinal))
+                    .collect();
+                dates.sort();
+                dates.dedup();
+                dates
+            }
+            Frequency::Yearly => NaiveDate::from_ymd_opt(period_start.year(), start.month(), start.day()).into_iter().collect(),
+        }
+    }
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    Some(match value.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Every `day` of the month starting at `month_start`, or only the nth one;
+/// negative ordinals count from the end of the month
+fn weekdays_of_month(month_start: NaiveDate, day: Weekday, ordinal: Option<i32>) -> Vec<NaiveDate> {
+    let offset = (7 + day.num_days_from_monday() as i64 - month_start.weekday().num_days_from_monday() as i64) % 7;
+    let days: Vec<NaiveDate> = (0..5)
+        .map(|week| month_start + Duration::days(offset + week * 7))
+        .take_while(|date| date.month() == month_start.month())
+        .collect();
+    match ordinal {
+        None => days,
+        Some(n) if n > 0 => days.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) => days.len().checked_sub(n.unsigned_abs() as usize).and_then(|i| days.get(i)).copied().into_iter().collect(),
+    }
+}
+
+/// Parse an ICS duration such as `PT1H30M` or `P1D`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total = total + match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_ICS: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup-1\r\n\
+SUMMARY:Team standup\\, daily\r\n\
+DTSTART:20240305T090000Z\r\n\
+DTEND:20240305T091500Z\r\n\
+LOCATION:Room 4\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:review-1\r\n\
+SUMMARY:Quarterly review with a very long title that the calendar\r\n  \
+ folded\r\n\
+DTSTART:20240305T100000Z\r\n\
+DURATION:PT1H30M\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:cancelled-1\r\n\
+SUMMARY:Cancelled sync\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20240305T090000Z\r\n\
+DTEND:20240305T100000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn create_frame(ts_ns: i64, app_name: &str) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns,
+            monitor_id: 0,
+            segment_id: "segment_1".to_string(),
+            path: format!("/frames/{}.png", ts_ns),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: app_name.to_string(),
+            win_title: "Window".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let events = parse_ics(SAMPLE_ICS);
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0].title, "Team standup, daily");
+        assert_eq!(events[0].location.as_deref(), Some("Room 4"));
+        assert_eq!(events[1].title, "Quarterly review with a very long title that the calendar folded");
+        assert_eq!(events[1].end - events[1].start, Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_segment_tagging_and_app_usage() {
+        let context = CalendarContext::from_events(parse_ics(SAMPLE_ICS));
+        let segment_start = Utc.with_ymd_and_hms(2024, 3, 5, 9, 10, 0).unwrap();
+        let minute = 60 * 1_000_000_000;
+
+        // 09:10 to 09:20; the standup ends at 09:15
+        let frames: Vec<FrameMetadata> = (0..=10)
+            .map(|i| create_frame(i * minute, if i < 3 { "Zoom" } else { "Xcode" }))
+            .collect();
+
+        assert_eq!(context.meeting_at(segment_start).map(|e| e.uid.as_str()), Some("standup-1"));
+
+        let tags = context.tag_segment("segment_1", segment_start, &frames);
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].meeting_uid, "standup-1");
+        assert_eq!(tags[0].frame_count, 5);
+        assert_eq!(tags[0].overlap_end, Utc.with_ymd_and_hms(2024, 3, 5, 9, 15, 0).unwrap());
+
+        let usage = context.app_usage_during("standup-1", segment_start, &frames);
+        assert_eq!(usage.get("Zoom"), Some(&3));
+        assert_eq!(usage.get("Xcode"), Some(&2));
+
+        let rows = context.app_usage(&tags, segment_start, &frames);
+        let apps: Vec<(&str, usize)> = rows.iter().map(|r| (r.app_name.as_str(), r.frame_count)).collect();
+        assert_eq!(apps, vec![("Zoom", 3), ("Xcode", 2)]);
+    }
+
+    #[test]
+    fn test_recurring_events_are_expanded() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:sync\r\n\
+SUMMARY:Weekly sync\r\n\
+DTSTART;TZID=Europe/Berlin:20240304T093000\r\n\
+DTEND;TZID=Europe/Berlin:20240304T100000\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,TH;COUNT=5\r\n\
+EXDATE;TZID=Europe/Berlin:20240307T093000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:sync\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20240311T093000\r\n\
+SUMMARY:Weekly sync (moved)\r\n\
+DTSTART;TZID=Europe/Berlin:20240311T140000\r\n\
+DTEND;TZID=Europe/Berlin:20240311T143000\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let context = CalendarContext::from_events(parse_ics(ics));
+        let starts: Vec<DateTime<Utc>> = context.events.iter().map(|e| e.start).collect();
+
+        // Mon 4th, Thu 7th (excluded), Mon 11th (moved), Thu 14th, Mon 18th; Berlin is UTC+1 in March
+        assert_eq!(starts, vec![
+            Utc.with_ymd_and_hms(2024, 3, 4, 8, 30, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 11, 13, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 14, 8, 30, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 18, 8, 30, 0).unwrap(),
+        ]);
+        assert!(context.events.iter().all(|e| e.end - e.start == Duration::minutes(30)));
+        assert_eq!(context.events[1].title, "Weekly sync (moved)");
+        assert_eq!(context.events[1].uid, "sync/20240311T083000Z");
+        assert_eq!(context.events[2].uid, "sync/20240314T083000Z");
+    }
+
+    #[test]
+    fn test_monthly_rule_with_ordinal_weekday_follows_dst() {
+        let ics = "BEGIN:VEVENT\r\n\
+UID:review\r\n\
+DTSTART;TZID=America/New_York:20240126T150000\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20240430T000000Z\r\n\
+END:VEVENT\r\n";
+        let starts: Vec<DateTime<Utc>> = parse_ics(ics).iter().map(|e| e.start).collect();
+
+        // Last Friday of each month at 15:00 New York time, before and after DST starts on March 10th
+        assert_eq!(starts, vec![
+            Utc.with_ymd_and_hms(2024, 1, 26, 20, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 23, 20, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 29, 19, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 4, 26, 19, 0, 0).unwrap(),
+        ]);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::enum_codes::EnumCode;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::DetectedEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tracing::debug;
+
+/// Metadata key with the detector's own confidence, before calibration
+pub const RAW_CONFIDENCE_KEY: &str = "raw_confidence";
+
+/// Configuration for calibrating event confidence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    /// Apply the fitted curves to new events, when a calibration file exists
+    pub enabled: bool,
+    /// Where fitted curves are stored; defaults to `<output_dir>/calibration.json`
+    pub path: Option<String>,
+    /// Labeled events a detector needs before it gets a curve
+    pub min_samples: usize,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            min_samples: 20,
+        }
+    }
+}
+
+impl CalibrationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_samples < 2 {
+            return Err(IndexerError::Config("calibration min_samples must be at least 2".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Ground truth for one stored event: whether it really happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationLabel {
+    pub event_id: String,
+    /// `true` for a true positive, `false` for a false positive
+    pub correct: bool,
+}
+
+/// Read labels from a CSV file with `event_id` and `correct` columns; `correct`
+/// is `true`/`false`, `1`/`0`, `yes`/`no` or `tp`/`fp`
+pub fn load_labels(path: &Path) -> Result<Vec<CalibrationLabel>> {
+    let mut reader = csv::Reader::from_path(path).map_err(std::io::Error::from)?;
+    let headers = reader.headers().map_err(std::io::Error::from)?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| IndexerError::Config(format!("Label file has no '{}' column", name)))
+    };
+    let (id_column, correct_column) = (column("event_id")?, column("correct")?);
+
+    let mut labels = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(std::io::Error::from)?;
+        let value = record.get(correct_column).unwrap_or_default().trim().to_lowercase();
+        let correct = match value.as_str() {
+            "true" | "1" | "yes" | "tp" => true,
+            "false" | "0" | "no" | "fp" => false,
+            other => {
+                return Err(IndexerError::Config(format!("Label row {}: '{}' is not a true or false positive", index + 1, other)));
+            }
+        };
+        labels.push(CalibrationLabel {
+            event_id: record.get(id_column).unwrap_or_default().trim().to_string(),
+            correct,
+        });
+    }
+    Ok(labels)
+}
+
+/// Platt scaling curve: calibrated = 1 / (1 + exp(a * raw + b))
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlattCurve {
+    pub a: f64,
+    pub b: f64,
+    /// Labeled events the curve was fitted on
+    pub samples: usize,
+    /// Of those, the true positives
+    pub positives: usize,
+}
+
+impl PlattCurve {
+    /// Fit a curve to raw confidences and whether each was a true positive,
+    /// by Newton's method with backtracking (Lin, Lin & Weng's variant of Platt)
+    pub fn fit(samples: &[(f32, bool)]) -> Self {
+        let positives = samples.iter().filter(|(_, correct)| *correct).count();
+        let negatives = samples.len() - positives;
+        // Smoothed targets keep a perfectly separated set from diverging
+        let high = (positives as f64 + 1.0) / (positives as f64 + 2.0);
+        let low = 1.0 / (negatives as f64 + 2.0);
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|(score, correct)| (*score as f64, if *correct { high } else { low }))
+            .collect();
+
+        let objective = |a: f64, b: f64| {
+            points
+                .iter()
+                .map(|(score, target)| {
+                    let f = score * a + b;
+                    if f >= 0.0 {
+                        target * f + (-f).exp().ln_1p()
+                    } else {
+                        (target - 1.0) * f + f.exp().ln_1p()
+                    }
+                })
+                .sum::<f64>()
+        };
+
+        let mut a = 0.0;
+        let mut b = ((negatives as f64 + 1.0) / (positives as f64 + 1.0)).ln();
+        let mut value = objective(a, b);
+        for _ in 0..100 {
+            let (mut h11, mut h22, mut h21, mut g1, mut g2) = (1e-12, 1e-12, 0.0, 0.0, 0.0);
+            for (score, target) in &points {
+                let f = score * a + b;
+                let (p, q) = if f >= 0.0 {
+                    ((-f).exp() / (1.0 + (-f).exp()), 1.0 / (1.0 + (-f).exp()))
+                } else {
+                    (1.0 / (1.0 + f.exp()), f.exp() / (1.0 + f.exp()))
+                };
+                let d2 = p * q;
+                h11 += score * score * d2;
+                h22 += d2;
+                h21 += score * d2;
+                let d1 = target - p;
+                g1 += score * d1;
+                g2 += d1;
+            }
+            if g1.abs() < 1e-5 && g2.abs() < 1e-5 {
+                break;
+            }
+
+            let det = h11 * h22 - h21 * h21;
+            let da = -(h22 * g1 - h21 * g2) / det;
+            let db = -(-h21 * g1 + h11 * g2) / det;
+            let gd = g1 * da + g2 * db;
+            let mut step = 1.0;
+            while step >= 1e-10 {
+                let (new_a, new_b) = (a + step * da, b + step * db);
+                let new_value = objective(new_a, new_b);
+                if new_value < value + 1e-4 * step * gd {
+                    (a, b, value) = (new_a, new_b, new_value);
+                    break;
+                }
+                step /= 2.0;
+            }
+            if step < 1e-10 {
+                break;
+            }
+        }
+
+        Self { a, b, samples: samples.len(), positives }
+    }
+
+    pub fn apply(&self, confidence: f32) -> f32 {
+        (1.0 / (1.0 + (self.a * confidence as f64 + self.b).exp())) as f32
+    }
+}
+
+/// Per-detector calibration curves fitted on labeled events.
+///
+/// Detectors are told apart by event type. Detectors without a curve keep
+/// their confidence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub fitted_at: DateTime<Utc>,
+    pub curves: BTreeMap<String, PlattCurve>,
+    /// Detectors with labels but too few for a curve, with their label count
+    #[serde(default)]
+    pub skipped: BTreeMap<String, usize>,
+}
+
+impl Calibration {
+    /// Fit a curve per detector from stored events and their labels; labels
+    /// for events not found are ignored
+    pub fn fit(events: &[DetectedEvent], labels: &[CalibrationLabel], min_samples: usize) -> Self {
+        let by_id: HashMap<&str, &DetectedEvent> = events.iter().map(|event| (event.id.as_str(), event)).collect();
+        let mut samples: BTreeMap<String, Vec<(f32, bool)>> = BTreeMap::new();
+        for label in labels {
+            let Some(event) = by_id.get(label.event_id.as_str()) else {
+                debug!("No stored event {} for calibration label", label.event_id);
+                continue;
+            };
+            samples
+                .entry(Self::detector(event).to_string())
+                .or_default()
+                .push((Self::raw_confidence(event), label.correct));
+        }
+
+        let mut calibration = Self { fitted_at: Utc::now(), curves: BTreeMap::new(), skipped: BTreeMap::new() };
+        for (detector, samples) in samples {
+            if samples.len() < min_samples {
+                calibration.skipped.insert(detector, samples.len());
+            } else {
+                calibration.curves.insert(detector, PlattCurve::fit(&samples));
+            }
+        }
+        calibration
+    }
+
+    /// Calibrated confidence of a detector's raw score
+    pub fn calibrate(&self, detector: &str, confidence: f32) -> f32 {
+        match self.curves.get(detector) {
+            Some(curve) => curve.apply(confidence),
+            None => confidence,
+        }
+    }
+
+    /// Replace events' confidence with the calibrated one, keeping the
+    /// original under `raw_confidence`; calibrating twice changes nothing
+    pub fn apply(&self, events: &mut [DetectedEvent]) {
+        for event in events {
+            let Some(curve) = self.curves.get(Self::detector(event)) else {
+                continue;
+            };
+            let raw = Self::raw_confidence(event);
+            event.metadata.insert(RAW_CONFIDENCE_KEY.to_string(), raw.to_string());
+            event.confidence = curve.apply(raw);
+        }
+    }
+
+    /// Read a calibration file; `None` when there is none yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the calibration, replacing an earlier file whole
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    fn detector(event: &DetectedEvent) -> &'static str {
+        event.event_type.name()
+    }
+
+    fn raw_confidence(event: &DetectedEvent) -> f32 {
+        event
+            .metadata
+            .get(RAW_CONFIDENCE_KEY)
+            .and_then(|raw| raw.parse().ok())
+            .unwrap_or(event.confidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+
+    fn event(id: usize, event_type: EventType, confidence: f32) -> DetectedEvent {
+        DetectedEvent {
+            id: format!("event-{}", id),
+            timestamp: Utc::now(),
+            event_type,
+            target: "field".to_string(),
+            value_from: None,
+            value_to: None,
+            confidence,
+            evidence_frames: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_platt_curves_fit_per_detector_and_round_trip() {
+        // Errors reported at 0.9 are right 3 times in 4, at 0.6 once in 4
+        let mut events = Vec::new();
+        let mut labels = Vec::new();
+        for id in 0..40 {
+            let confidence = if id < 20 { 0.9 } else { 0.6 };
+            events.push(event(id, EventType::ErrorDisplay, confidence));
+            let correct = if id < 20 { id % 4 != 0 } else { id % 4 == 0 };
+            labels.push(CalibrationLabel { event_id: format!("event-{}", id), correct });
+        }
+        events.push(event(99, EventType::FieldChange, 0.7));
+        labels.push(CalibrationLabel { event_id: "event-99".to_string(), correct: true });
+        labels.push(CalibrationLabel { event_id: "missing".to_string(), correct: false });
+
+        let calibration = Calibration::fit(&events, &labels, 20);
+        assert_eq!(calibration.curves.len(), 1);
+        assert_eq!(calibration.skipped.get("field_change"), Some(&1));
+        let high = calibration.calibrate("error_display", 0.9);
+        let low = calibration.calibrate("error_display", 0.6);
+        assert!((high - 0.75).abs() < 0.05, "{}", high);
+        assert!((low - 0.25).abs() < 0.05, "{}", low);
+        assert_eq!(calibration.calibrate("field_change", 0.7), 0.7);
+
+        let mut sample = vec![event(0, EventType::ErrorDisplay, 0.9), event(1, EventType::FieldChange, 0.7)];
+        calibration.apply(&mut sample);
+        calibration.apply(&mut sample);
+        assert_eq!(sample[0].confidence, high);
+        assert_eq!(sample[0].metadata[RAW_CONFIDENCE_KEY], "0.9");
+        assert!(!sample[1].metadata.contains_key(RAW_CONFIDENCE_KEY));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calibration.json");
+        assert!(Calibration::load(&path).unwrap().is_none());
+        calibration.save(&path).unwrap();
+        assert_eq!(Calibration::load(&path).unwrap(), Some(calibration));
+    }
+}
@@ -0,0 +1,260 @@
+//! C ABI over scene and event detection, for apps that embed the detectors
+//! in-process. Every call takes and returns JSON strings; strings returned
+//! by the library are released with `kfi_string_free`. A null return means
+//! the call failed and `kfi_last_error` describes why.
+
+use crate::config::IndexerConfig;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::EventDetector;
+use crate::ocr_data::OCRResult;
+use crate::scene_detector::{FramePyramid, SceneDetector};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// Bumped whenever a function signature or JSON shape changes incompatibly
+pub const KFI_ABI_VERSION: u32 = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, turning errors and panics into a null result and a last error
+fn guarded<T>(f: impl FnOnce() -> Result<*mut T>) -> *mut T {
+    let message = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "panic in keyframe-indexer".to_string(),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message.replace('\0', " ")).ok());
+    ptr::null_mut()
+}
+
+/// Borrow a C string argument; null reads as an empty string
+unsafe fn input<'a>(text: *const c_char) -> Result<&'a str> {
+    if text.is_null() {
+        return Ok("");
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|_| IndexerError::Config("Input is not valid UTF-8".to_string()))
+}
+
+fn config(json: &str) -> Result<IndexerConfig> {
+    if json.trim().is_empty() {
+        return Ok(IndexerConfig::default());
+    }
+    IndexerConfig::from_json_str(json)
+}
+
+fn output(value: &impl serde::Serialize) -> Result<*mut c_char> {
+    let json = serde_json::to_string(value)?;
+    Ok(CString::new(json).map_err(|e| IndexerError::Config(e.to_string()))?.into_raw())
+}
+
+/// Scene detection over frames handed over one at a time
+pub struct KfiSceneDetector {
+    detector: SceneDetector,
+    previous: Option<FramePyramid>,
+    frame_index: usize,
+}
+
+#[derive(Deserialize)]
+struct SceneFrame {
+    frame_path: String,
+    timestamp_ns: i64,
+}
+
+/// Event detection over one frame's OCR results at a time
+pub struct KfiEventDetector {
+    detector: EventDetector,
+}
+
+#[derive(Deserialize)]
+struct EventFrame {
+    frame_id: String,
+    timestamp: DateTime<Utc>,
+    screen_width: f32,
+    screen_height: f32,
+    #[serde(default)]
+    ocr_results: Vec<OCRResult>,
+}
+
+#[no_mangle]
+pub extern "C" fn kfi_abi_version() -> u32 {
+    KFI_ABI_VERSION
+}
+
+/// Message of the last failed call on this thread, or null. Owned by the
+/// library and valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn kfi_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// # Safety
+/// `text` must be null or a string returned by this library, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Create a scene detector from an indexer config JSON (null or empty for
+/// defaults); only `scene_detection` is read.
+///
+/// # Safety
+/// `config_json` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_scene_detector_new(config_json: *const c_char) -> *mut KfiSceneDetector {
+    guarded(|| {
+        let detector = SceneDetector::new(config(input(config_json)?)?.scene_detection)?;
+        Ok(Box::into_raw(Box::new(KfiSceneDetector { detector, previous: None, frame_index: 0 })))
+    })
+}
+
+/// Compare a frame (`{"frame_path": ..., "timestamp_ns": ...}`) with the one
+/// before it; returns the scene change as JSON, or `null` JSON if none.
+///
+/// # Safety
+/// `detector` must come from `kfi_scene_detector_new` and not be used from
+/// two threads at once; `frame_json` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_scene_detector_process(
+    detector: *mut KfiSceneDetector,
+    frame_json: *const c_char,
+) -> *mut c_char {
+    guarded(|| {
+        let handle = detector.as_mut().ok_or_else(|| IndexerError::Config("Scene detector is null".to_string()))?;
+        let frame: SceneFrame = serde_json::from_str(input(frame_json)?)?;
+        let current = handle.detector.build_pyramid(&frame.frame_path)?;
+        let change = handle
+            .previous
+            .as_ref()
+            .and_then(|previous| handle.detector.compare_frames(handle.frame_index, frame.timestamp_ns, previous, &current));
+        handle.previous = Some(current);
+        handle.frame_index += 1;
+        output(&change)
+    })
+}
+
+/// # Safety
+/// `detector` must be null or come from `kfi_scene_detector_new`, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_scene_detector_free(detector: *mut KfiSceneDetector) {
+    if !detector.is_null() {
+        drop(Box::from_raw(detector));
+    }
+}
+
+/// Create an event detector from an indexer config JSON (null or empty for
+/// defaults); `event_detection` and `roi_scale` are read.
+///
+/// # Safety
+/// `config_json` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_event_detector_new(config_json: *const c_char) -> *mut KfiEventDetector {
+    guarded(|| {
+        let detector = EventDetector::with_config(config(input(config_json)?)?.event_detection_config())?;
+        Ok(Box::into_raw(Box::new(KfiEventDetector { detector })))
+    })
+}
+
+/// Detect events in one frame (`{"frame_id", "timestamp", "screen_width",
+/// "screen_height", "ocr_results": [...]}`); returns a JSON array of events.
+///
+/// # Safety
+/// `detector` must come from `kfi_event_detector_new` and not be used from
+/// two threads at once; `frame_json` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_event_detector_process(
+    detector: *mut KfiEventDetector,
+    frame_json: *const c_char,
+) -> *mut c_char {
+    guarded(|| {
+        let handle = detector.as_mut().ok_or_else(|| IndexerError::Config("Event detector is null".to_string()))?;
+        let frame: EventFrame = serde_json::from_str(input(frame_json)?)?;
+        let events = handle.detector.analyze_frame(
+            &frame.frame_id,
+            &frame.ocr_results,
+            frame.timestamp,
+            frame.screen_width,
+            frame.screen_height,
+        )?;
+        output(&events)
+    })
+}
+
+/// Events held back until the recording ends, such as open progress
+/// dialogs; returns a JSON array.
+///
+/// # Safety
+/// `detector` must come from `kfi_event_detector_new`.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_event_detector_finish(detector: *mut KfiEventDetector) -> *mut c_char {
+    guarded(|| {
+        let handle = detector.as_mut().ok_or_else(|| IndexerError::Config("Event detector is null".to_string()))?;
+        output(&handle.detector.finish())
+    })
+}
+
+/// # Safety
+/// `detector` must be null or come from `kfi_event_detector_new`, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn kfi_event_detector_free(detector: *mut KfiEventDetector) {
+    if !detector.is_null() {
+        drop(Box::from_raw(detector));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn take(text: *mut c_char) -> String {
+        assert!(!text.is_null(), "{:?}", CStr::from_ptr(kfi_last_error()));
+        let owned = CStr::from_ptr(text).to_str().unwrap().to_string();
+        kfi_string_free(text);
+        owned
+    }
+
+    #[test]
+    fn test_detectors_over_c_abi() {
+        let dir = tempfile::tempdir().unwrap();
+        let dark = dir.path().join("dark.png");
+        let light = dir.path().join("light.png");
+        image::RgbImage::from_fn(320, 200, |x, _| image::Rgb([(x % 40) as u8, 20, 20])).save(&dark).unwrap();
+        image::RgbImage::from_fn(320, 200, |_, y| image::Rgb([230, 240, 250 - (y % 50) as u8])).save(&light).unwrap();
+
+        unsafe {
+            let scenes = kfi_scene_detector_new(ptr::null());
+            let frame = |path: &std::path::Path, ts: i64| {
+                CString::new(format!(r#"{{"frame_path": "{}", "timestamp_ns": {}}}"#, path.display(), ts)).unwrap()
+            };
+            assert_eq!(take(kfi_scene_detector_process(scenes, frame(&dark, 0).as_ptr())), "null");
+            let change = take(kfi_scene_detector_process(scenes, frame(&light, 1_000).as_ptr()));
+            assert!(change.contains("\"timestamp_ns\":1000"));
+            kfi_scene_detector_free(scenes);
+
+            let empty = CString::new("{}").unwrap();
+            let events = kfi_event_detector_new(empty.as_ptr());
+            let frame = CString::new(
+                r#"{"frame_id": "f1", "timestamp": "2024-01-01T00:00:00Z", "screen_width": 1920, "screen_height": 1080}"#,
+            )
+            .unwrap();
+            assert!(take(kfi_event_detector_process(events, frame.as_ptr())).starts_with('['));
+            let invalid = CString::new("not json").unwrap();
+            assert!(kfi_event_detector_process(events, invalid.as_ptr()).is_null());
+            assert!(!kfi_last_error().is_null());
+            kfi_event_detector_free(events);
+
+            let bad_config = CString::new(r#"{"scene_detection": 5}"#).unwrap();
+            assert!(kfi_scene_detector_new(bad_config.as_ptr()).is_null());
+        }
+        assert_eq!(kfi_abi_version(), KFI_ABI_VERSION);
+    }
+}
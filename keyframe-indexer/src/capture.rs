@@ -0,0 +1,428 @@
+use crate::burst_sampling::{BurstSampler, BurstSamplingConfig};
+use crate::error::{IndexerError, Result};
+use crate::keyframe_codec::{self, KeyframeStorageConfig};
+use crate::keyframe_extractor::Keyframe;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Live screen capture settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Frames grabbed per second; unchanged screens deliver none
+    pub fps: f32,
+    /// Displays to capture at the same time, by CoreGraphics ID; the first
+    /// display when empty
+    pub display_ids: Vec<u32>,
+    /// Frames wider than this are scaled down when captured
+    pub max_width: Option<u32>,
+    /// Captured frames are processed in segments of this many seconds
+    pub segment_secs: u64,
+    pub show_cursor: bool,
+    /// Frames buffered while a segment is processed; later ones are dropped when full
+    pub channel_capacity: usize,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            fps: 1.0,
+            display_ids: Vec::new(),
+            max_width: None,
+            segment_secs: 60,
+            show_cursor: true,
+            channel_capacity: 16,
+        }
+    }
+}
+
+impl CaptureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.fps <= 0.0 || self.segment_secs == 0 || self.channel_capacity == 0 || self.max_width == Some(0) {
+            return Err(IndexerError::Config(
+                "capture fps, segment_secs, channel_capacity and max_width must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One screen image as delivered by the capture source, in BGRA rows
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub display_id: u32,
+    pub timestamp: DateTime<Utc>,
+    pub width: u32,
+    pub height: u32,
+    /// Rows may be padded past `width * 4` bytes
+    pub bytes_per_row: usize,
+    pub bgra: Vec<u8>,
+}
+
+impl CapturedFrame {
+    pub fn to_rgb(&self) -> Result<image::RgbImage> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if self.bytes_per_row < width * 4 || self.bgra.len() < self.bytes_per_row * height {
+            return Err(IndexerError::Image(image::ImageError::Parameter(
+                image::error::ParameterError::from_kind(image::error::ParameterErrorKind::DimensionMismatch),
+            )));
+        }
+        Ok(image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            let i = y as usize * self.bytes_per_row + x as usize * 4;
+            image::Rgb([self.bgra[i + 2], self.bgra[i + 1], self.bgra[i]])
+        }))
+    }
+}
+
+/// Something that grabs frames from one display and sends them over a channel
+pub trait FrameSource: Send {
+    /// Display the frames come from
+    fn display_id(&self) -> u32;
+
+    /// Start sending frames; frames that do not fit the channel are dropped
+    fn start(&mut self, frames: mpsc::Sender<CapturedFrame>) -> Result<()>;
+
+    fn stop(&mut self) -> Result<()>;
+}
+
+/// Frames of one display over one segment, saved as keyframes
+#[derive(Debug, Clone)]
+pub struct CapturedSegment {
+    pub segment_id: String,
+    /// Wall-clock time of the first frame; keyframe timestamps are offsets from it
+    pub start: DateTime<Utc>,
+    pub keyframes: Vec<Keyframe>,
+}
+
+/// Saves one display's captured frames as keyframes and cuts them into
+/// segments, the way the extractor does for a recorded video
+pub struct CaptureSegmenter {
+    segment_secs: u64,
+    /// Rate frames are kept at; faster frames are skipped
+    fps: f32,
+    capture_fps: f32,
+    display_id: u32,
+    /// Keyframes of each segment are saved under `<frames_root>/<segment_id>`
+    frames_root: PathBuf,
+    storage: KeyframeStorageConfig,
+    /// Keeps frames above `fps` while a toast or snackbar is on screen
+    burst: BurstSampler,
+    current: Option<CapturedSegment>,
+}
+
+impl CaptureSegmenter {
+    pub fn new(config: &CaptureConfig, display_id: u32, frames_root: impl Into<PathBuf>) -> Self {
+        Self {
+            segment_secs: config.segment_secs,
+            fps: config.fps,
+            capture_fps: config.fps,
+            display_id,
+            frames_root: frames_root.into(),
+            storage: KeyframeStorageConfig::default(),
+            burst: BurstSampler::new(BurstSamplingConfig::default()),
+            current: None,
+        }
+    }
+
+    /// Keep frames at most `fps` per second, e.g. as the adaptive rate or
+    /// storage level changes; never above the capture rate
+    pub fn set_rate(&mut self, fps: f32) {
+        self.fps = fps.min(self.capture_fps);
+    }
+
+    /// Codec and quality captured keyframes are saved with
+    pub fn set_storage(&mut self, storage: KeyframeStorageConfig) {
+        self.storage = storage;
+    }
+
+    /// Keep frames up to the capture rate for a while when a small region
+    /// appears near a screen edge
+    pub fn set_burst_sampling(&mut self, config: BurstSamplingConfig) {
+        self.burst = BurstSampler::new(config);
+    }
+
+    /// Save a frame unless it comes too soon after the last one kept; returns
+    /// the segment it closed, if any
+    pub fn push(&mut self, frame: &CapturedFrame) -> Result<Option<CapturedSegment>> {
+        let segment_length = chrono::Duration::seconds(self.segment_secs as i64);
+        let closed = match &self.current {
+            Some(segment) if frame.timestamp - segment.start >= segment_length => self.current.take(),
+            _ => None,
+        };
+
+        // The monitor part lets the metadata collector attribute frames to the display
+        let display_id = self.display_id;
+        let segment = self.current.get_or_insert_with(|| CapturedSegment {
+            segment_id: format!("capture_monitor{}_{}", display_id, frame.timestamp.format("%Y%m%d_%H%M%S")),
+            start: frame.timestamp,
+            keyframes: Vec::new(),
+        });
+        let secs = frame.timestamp.timestamp_millis() as f64 / 1000.0;
+        let mut extra = false;
+        if let Some(last) = segment.keyframes.last().filter(|_| self.fps < self.capture_fps) {
+            let since_last = frame.timestamp - segment.start - chrono::Duration::nanoseconds(last.timestamp_ns);
+            // Some slack, as frames never arrive exactly on time
+            if since_last.num_milliseconds() < (900.0 / self.fps) as i64 {
+                if !self.burst.wants_extra(secs) {
+                    return Ok(closed);
+                }
+                extra = true;
+            }
+        }
+        let frames_dir = self.frames_root.join(&segment.segment_id);
+        std::fs::create_dir_all(&frames_dir)?;
+        let frame_path = frames_dir.join(format!(
+            "frame_{}_{}.{}",
+            segment.segment_id,
+            segment.keyframes.len(),
+            self.storage.codec.extension()
+        ));
+        let rgb = frame.to_rgb()?;
+        self.burst.observe(secs, &rgb, extra);
+        keyframe_codec::save_keyframe(&image::DynamicImage::ImageRgb8(rgb), &frame_path, &self.storage)?;
+
+        segment.keyframes.push(Keyframe {
+            id: Uuid::new_v4(),
+            timestamp_ns: (frame.timestamp - segment.start).num_nanoseconds().unwrap_or(0),
+            segment_id: segment.segment_id.clone(),
+            frame_path: frame_path.to_string_lossy().to_string(),
+            width: frame.width,
+            height: frame.height,
+            format: "BGRA".to_string(),
+            source_fps: Some(self.capture_fps),
+            display_id: Some(self.display_id as i32),
+        });
+        Ok(closed)
+    }
+
+    /// The segment in progress, e.g. when capture stops
+    pub fn finish(&mut self) -> Option<CapturedSegment> {
+        self.current.take()
+    }
+}
+
+/// One ScreenCaptureKit source per configured display
+#[cfg(all(feature = "capture", target_os = "macos"))]
+pub fn screen_sources(config: &CaptureConfig) -> Result<Vec<Box<dyn FrameSource>>> {
+    let sources = screen_capture_kit::ScreenCaptureKitSource::for_displays(config)?;
+    Ok(sources.into_iter().map(|source| Box::new(source) as Box<dyn FrameSource>).collect())
+}
+
+#[cfg(not(all(feature = "capture", target_os = "macos")))]
+pub fn screen_sources(_config: &CaptureConfig) -> Result<Vec<Box<dyn FrameSource>>> {
+    Err(IndexerError::Capture(
+        "live capture needs macOS 13 or later and a build with the `capture` feature".to_string(),
+    ))
+}
+
+#[cfg(all(feature = "capture", target_os = "macos"))]
+mod screen_capture_kit {
+    use super::{CaptureConfig, CapturedFrame, FrameSource};
+    use crate::error::{IndexerError, Result};
+    use chrono::Utc;
+    use screencapturekit::output::{CMSampleBuffer, CMTime};
+    use screencapturekit::shareable_content::{SCDisplay, SCShareableContent};
+    use screencapturekit::stream::configuration::{pixel_format::PixelFormat, SCStreamConfiguration};
+    use screencapturekit::stream::content_filter::SCContentFilter;
+    use screencapturekit::stream::output_trait::SCStreamOutputTrait;
+    use screencapturekit::stream::output_type::SCStreamOutputType;
+    use screencapturekit::stream::SCStream;
+    use tokio::sync::mpsc;
+    use tracing::warn;
+
+    fn capture_error(e: impl std::fmt::Display) -> IndexerError {
+        IndexerError::Capture(e.to_string())
+    }
+
+    pub struct ScreenCaptureKitSource {
+        display_id: u32,
+        filter: SCContentFilter,
+        configuration: SCStreamConfiguration,
+        stream: Option<SCStream>,
+    }
+
+    impl ScreenCaptureKitSource {
+        /// Fails when screen recording permission has not been granted or a
+        /// configured display is not attached
+        pub fn for_displays(config: &CaptureConfig) -> Result<Vec<Self>> {
+            let displays = SCShareableContent::get().map_err(capture_error)?.displays();
+            if config.display_ids.is_empty() {
+                let first = displays.into_iter().next().ok_or_else(|| IndexerError::Capture("No display to capture".to_string()))?;
+                return Ok(vec![Self::new(config, first)?]);
+            }
+            config
+                .display_ids
+                .iter()
+                .map(|id| {
+                    let display = displays
+                        .iter()
+                        .find(|d| d.display_id() == *id)
+                        .cloned()
+                        .ok_or_else(|| IndexerError::Capture(format!("No display with ID {} to capture", id)))?;
+                    Self::new(config, display)
+                })
+                .collect()
+        }
+
+        fn new(config: &CaptureConfig, display: SCDisplay) -> Result<Self> {
+            let (mut width, mut height) = (display.width(), display.height());
+            if let Some(max_width) = config.max_width.filter(|max| width > *max) {
+                height = (height as u64 * max_width as u64 / width as u64).max(1) as u32;
+                width = max_width;
+            }
+            // Timescale in milliseconds so fractional rates keep their interval
+            let interval = CMTime { value: (1000.0 / config.fps) as i64, timescale: 1000, flags: 1, epoch: 0 };
+            let configuration = SCStreamConfiguration::new()
+                .set_width(width)
+                .and_then(|c| c.set_height(height))
+                .and_then(|c| c.set_pixel_format(PixelFormat::BGRA))
+                .and_then(|c| c.set_minimum_frame_interval(&interval))
+                .and_then(|c| c.set_shows_cursor(config.show_cursor))
+                .map_err(capture_error)?;
+            let filter = SCContentFilter::new().with_display_excluding_windows(&display, &[]);
+
+            Ok(Self { display_id: display.display_id(), filter, configuration, stream: None })
+        }
+    }
+
+    impl FrameSource for ScreenCaptureKitSource {
+        fn display_id(&self) -> u32 {
+            self.display_id
+        }
+
+        fn start(&mut self, frames: mpsc::Sender<CapturedFrame>) -> Result<()> {
+            let mut stream = SCStream::new(&self.filter, &self.configuration);
+            stream.add_output_handler(FrameHandler { display_id: self.display_id, frames }, SCStreamOutputType::Screen);
+            stream.start_capture().map_err(capture_error)?;
+            self.stream = Some(stream);
+            Ok(())
+        }
+
+        fn stop(&mut self) -> Result<()> {
+            match self.stream.take() {
+                Some(stream) => stream.stop_capture().map_err(capture_error),
+                None => Ok(()),
+            }
+        }
+    }
+
+    struct FrameHandler {
+        display_id: u32,
+        frames: mpsc::Sender<CapturedFrame>,
+    }
+
+    impl SCStreamOutputTrait for FrameHandler {
+        fn did_output_sample_buffer(&self, sample: CMSampleBuffer, _of_type: SCStreamOutputType) {
+            // Samples for an unchanged screen carry no image
+            let Ok(pixel_buffer) = sample.get_pixel_buffer() else {
+                return;
+            };
+            let Ok(pixels) = pixel_buffer.lock() else {
+                return;
+            };
+            let frame = CapturedFrame {
+                display_id: self.display_id,
+                timestamp: Utc::now(),
+                width: pixel_buffer.get_width(),
+                height: pixel_buffer.get_height(),
+                bytes_per_row: pixel_buffer.get_bytes_per_row() as usize,
+                bgra: pixels.as_slice().to_vec(),
+            };
+            if let Err(mpsc::error::TrySendError::Full(_)) = self.frames.try_send(frame) {
+                warn!("Dropping captured frame; processing is behind");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_captured_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CaptureConfig { segment_secs: 2, ..CaptureConfig::default() };
+        let mut segmenter = CaptureSegmenter::new(&config, 3, dir.path());
+        let start = DateTime::parse_from_rfc3339("2026-03-02T09:00:00Z").unwrap().with_timezone(&Utc);
+        // 2x1 frame padded to 12 bytes per row; first pixel pure blue, second pure red
+        let frame = |secs: i64| CapturedFrame {
+            display_id: 3,
+            timestamp: start + chrono::Duration::seconds(secs),
+            width: 2,
+            height: 1,
+            bytes_per_row: 12,
+            bgra: vec![255, 0, 0, 255, 0, 0, 255, 255, 0, 0, 0, 0],
+        };
+
+        assert!(segmenter.push(&frame(0)).unwrap().is_none());
+        assert!(segmenter.push(&frame(1)).unwrap().is_none());
+        let closed = segmenter.push(&frame(2)).unwrap().unwrap();
+        assert_eq!(closed.segment_id, "capture_monitor3_20260302_090000");
+        assert_eq!(closed.start, start);
+        let offsets: Vec<i64> = closed.keyframes.iter().map(|k| k.timestamp_ns).collect();
+        assert_eq!(offsets, vec![0, 1_000_000_000]);
+        assert_eq!(closed.keyframes[0].display_id, Some(3));
+
+        let saved = image::open(&closed.keyframes[1].frame_path).unwrap().to_rgb8();
+        assert_eq!(saved.get_pixel(0, 0).0, [0, 0, 255]);
+        assert_eq!(saved.get_pixel(1, 0).0, [255, 0, 0]);
+
+        let last = segmenter.finish().unwrap();
+        assert_eq!(last.keyframes.len(), 1);
+        assert_eq!(last.keyframes[0].timestamp_ns, 0);
+        assert!(segmenter.finish().is_none());
+
+        // At half the capture rate every other frame is skipped
+        segmenter.set_rate(0.5);
+        assert!(segmenter.push(&frame(10)).unwrap().is_none());
+        assert!(segmenter.push(&frame(11)).unwrap().is_none());
+        assert_eq!(segmenter.push(&frame(12)).unwrap().unwrap().keyframes.len(), 1);
+    }
+
+    #[test]
+    fn test_keeps_extra_frames_while_a_toast_is_on_screen() {
+        let start = DateTime::parse_from_rfc3339("2026-03-02T09:00:00Z").unwrap().with_timezone(&Utc);
+        // 64x40 gray screen at 10 fps; from 0.9 s a toast sits in the bottom-right corner
+        let frame = |tenths: i64| {
+            let mut bgra = vec![60; 64 * 40 * 4];
+            if tenths >= 9 {
+                for y in 32..38 {
+                    for x in 50..62 {
+                        let i = (y * 64 + x) * 4;
+                        bgra[i..i + 3].copy_from_slice(&[240, 240, 240]);
+                    }
+                }
+            }
+            CapturedFrame {
+                display_id: 1,
+                timestamp: start + chrono::Duration::milliseconds(tenths * 100),
+                width: 64,
+                height: 40,
+                bytes_per_row: 64 * 4,
+                bgra,
+            }
+        };
+        let offsets_kept = |burst_sampling: BurstSamplingConfig| {
+            let dir = tempfile::tempdir().unwrap();
+            let config = CaptureConfig { fps: 10.0, segment_secs: 60, ..CaptureConfig::default() };
+            let mut segmenter = CaptureSegmenter::new(&config, 1, dir.path());
+            segmenter.set_rate(1.0);
+            segmenter.set_burst_sampling(burst_sampling);
+            for tenths in 0..=30 {
+                assert!(segmenter.push(&frame(tenths)).unwrap().is_none());
+            }
+            let segment = segmenter.finish().unwrap();
+            segment.keyframes.iter().map(|k| k.timestamp_ns / 1_000_000).collect::<Vec<_>>()
+        };
+
+        // Frames up to 10% early count as due, so at 1 fps every ninth one is kept
+        assert_eq!(offsets_kept(BurstSamplingConfig::default()), [0, 900, 1800, 2700]);
+        // The toast starts a burst at 5 fps, after which the regular rate resumes
+        let burst = BurstSamplingConfig { enabled: true, burst_fps: 5.0, burst_secs: 0.95, ..BurstSamplingConfig::default() };
+        assert_eq!(offsets_kept(burst), [0, 900, 1100, 1300, 1500, 1700, 2600]);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::keyframe_extractor::Keyframe;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Resolution and frame rate a recorder produced a segment with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaptureProfile {
+    pub width: u32,
+    pub height: u32,
+    /// Source frame rate, when the decoder reports one
+    pub fps: Option<f32>,
+}
+
+impl CaptureProfile {
+    pub fn of_keyframe(keyframe: &Keyframe) -> Self {
+        Self {
+            width: keyframe.width,
+            height: keyframe.height,
+            fps: keyframe.source_fps,
+        }
+    }
+
+    pub fn resolution(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+}
+
+/// Settings for detecting recorder resolution and frame-rate changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureProfileConfig {
+    pub enabled: bool,
+    /// Resolution the configured pixel thresholds were tuned for
+    pub reference_width: u32,
+    pub reference_height: u32,
+    /// Frame-rate differences up to this many fps are jitter, not a change
+    pub fps_tolerance: f32,
+}
+
+impl Default for CaptureProfileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reference_width: 1920,
+            reference_height: 1080,
+            fps_tolerance: 0.5,
+        }
+    }
+}
+
+impl CaptureProfileConfig {
+    /// Factor for pixel thresholds (dialog sizes, movement distances) at a
+    /// capture resolution, from the ratio of its diagonal to the reference's
+    pub fn threshold_scale(&self, profile: &CaptureProfile) -> f32 {
+        if !self.enabled || self.reference_width == 0 || self.reference_height == 0 || profile.width == 0 || profile.height == 0 {
+            return 1.0;
+        }
+        let diagonal = |w: u32, h: u32| ((w as f32).powi(2) + (h as f32).powi(2)).sqrt();
+        diagonal(profile.width, profile.height) / diagonal(self.reference_width, self.reference_height)
+    }
+}
+
+/// A stream's capture profile differs from its previous segment's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureProfileChange {
+    pub stream_id: String,
+    pub previous: CaptureProfile,
+    pub current: CaptureProfile,
+}
+
+impl CaptureProfileChange {
+    pub fn resolution_changed(&self) -> bool {
+        self.previous.width != self.current.width || self.previous.height != self.current.height
+    }
+
+    /// Configuration-change event; `frame_id` is the first frame recorded with the new profile
+    pub fn to_event(&self, timestamp: DateTime<Utc>, frame_id: &str, threshold_scale: f32) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("stream_id".to_string(), self.stream_id.clone());
+        metadata.insert("threshold_scale".to_string(), format!("{:.3}", threshold_scale));
+        if let Some(fps) = self.previous.fps {
+            metadata.insert("fps_from".to_string(), format!("{:.2}", fps));
+        }
+        if let Some(fps) = self.current.fps {
+            metadata.insert("fps_to".to_string(), format!("{:.2}", fps));
+        }
+
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: EventType::CaptureChange,
+            target: format!("capture_stream_{}", self.stream_id),
+            value_from: Some(self.previous.resolution()),
+            value_to: Some(self.current.resolution()),
+            confidence: 1.0,
+            evidence_frames: vec![frame_id.to_string()],
+            metadata,
+        }
+    }
+}
+
+/// Remembers the capture profile of each stream (usually a monitor) and
+/// reports when the next segment was recorded differently
+pub struct CaptureProfileTracker {
+    config: CaptureProfileConfig,
+    profiles: HashMap<String, CaptureProfile>,
+}
+
+impl CaptureProfileTracker {
+    pub fn new(config: CaptureProfileConfig) -> Self {
+        Self {
+            config,
+            profiles: HashMap::new(),
+        }
+    }
+
+    pub fn update_config(&mut self, config: CaptureProfileConfig) {
+        self.config = config;
+    }
+
+    /// Record the profile of a stream's next segment; returns the change from the previous one
+    pub fn observe(&mut self, stream_id: &str, profile: CaptureProfile) -> Option<CaptureProfileChange> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let previous = self.profiles.insert(stream_id.to_string(), profile)?;
+        let fps_changed = match (previous.fps, profile.fps) {
+            (Some(before), Some(after)) => (before - after).abs() > self.config.fps_tolerance,
+            _ => false,
+        };
+        let resolution_changed = previous.width != profile.width || previous.height != profile.height;
+        if !resolution_changed && !fps_changed {
+            // Keep a known frame rate when a segment does not report one
+            if profile.fps.is_none() {
+                self.profiles.insert(stream_id.to_string(), previous);
+            }
+            return None;
+        }
+
+        warn!(
+            "Capture profile of stream {} changed from {} @ {:?} fps to {} @ {:?} fps",
+            stream_id, previous.resolution(), previous.fps, profile.resolution(), profile.fps
+        );
+        Some(CaptureProfileChange {
+            stream_id: stream_id.to_string(),
+            previous,
+            current: profile,
+        })
+    }
+
+    pub fn current(&self, stream_id: &str) -> Option<CaptureProfile> {
+        self.profiles.get(stream_id).copied()
+    }
+
+    pub fn threshold_scale(&self, profile: &CaptureProfile) -> f32 {
+        self.config.threshold_scale(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(width: u32, height: u32, fps: Option<f32>) -> CaptureProfile {
+        CaptureProfile { width, height, fps }
+    }
+
+    #[test]
+    fn test_changes_are_reported_and_thresholds_rescaled() {
+        let mut tracker = CaptureProfileTracker::new(CaptureProfileConfig::default());
+        assert!(tracker.observe("0", profile(1920, 1080, Some(30.0))).is_none());
+        // Jitter and unknown frame rates are not changes
+        assert!(tracker.observe("0", profile(1920, 1080, Some(29.97))).is_none());
+        assert!(tracker.observe("0", profile(1920, 1080, None)).is_none());
+        assert_eq!(tracker.current("0").and_then(|p| p.fps), Some(29.97));
+        // Other monitors are tracked separately
+        assert!(tracker.observe("1", profile(3840, 2160, Some(30.0))).is_none());
+
+        let change = tracker.observe("0", profile(3840, 2160, Some(15.0))).unwrap();
+        assert!(change.resolution_changed());
+        let scale = tracker.threshold_scale(&change.current);
+        assert!((scale - 2.0).abs() < 0.001);
+
+        let event = change.to_event(Utc::now(), "frame_0.png", scale);
+        assert_eq!(event.event_type, EventType::CaptureChange);
+        assert_eq!(event.value_from.as_deref(), Some("1920x1080"));
+        assert_eq!(event.value_to.as_deref(), Some("3840x2160"));
+        assert_eq!(event.metadata.get("fps_to").map(String::as_str), Some("15.00"));
+
+        let fps_only = tracker.observe("0", profile(3840, 2160, Some(60.0))).unwrap();
+        assert!(!fps_only.resolution_changed());
+
+        // Thresholds follow the resolution
+        let dialog = crate::error_modal_detector::ErrorModalDetectionConfig::default().scaled(scale);
+        assert_eq!(dialog.min_dialog_width, 400.0);
+        let cursor = crate::cursor_tracker::CursorTrackingConfig::default().scaled(scale);
+        assert!((cursor.min_movement_distance - 10.0).abs() < 0.001);
+    }
+}
@@ -1,6 +1,56 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tracing::warn;
 use crate::error::{IndexerError, Result};
+use crate::auth::AuthConfig;
+use crate::flight::FlightConfig;
+use crate::writer_memory::WriterMemoryConfig;
+use crate::logging::LoggingConfig;
+use crate::health::HealthConfig;
+use crate::file_watcher::FileWatcherConfig;
+use crate::parquet_tuning::ParquetTuningConfig;
+use crate::cursor_tracker::CursorTrackingConfig;
+use crate::event_correlator::CorrelationConfig;
+use crate::event_detector::EventDetectionConfig;
+use crate::navigation_detector::NavigationDetectionConfig;
+use crate::event_triggers::TriggerConfig;
+use crate::notifier::NotifierConfig;
+use crate::incremental_ocr::IncrementalOcrConfig;
+use crate::calendar_context::CalendarConfig;
+use crate::frame_dedup::FrameDedupConfig;
+use crate::focus_summary::FocusSummaryConfig;
+use crate::markdown_exporter::MarkdownExportConfig;
+use crate::scroll_detector::ScrollDetectionConfig;
+use crate::ocr_data::RoiScaleConfig;
+use crate::ocr_density::OcrQualityConfig;
+use crate::capture_profile::CaptureProfileConfig;
+use crate::suppression::{SuppressionList, SuppressionRule};
+use crate::template_matcher::TemplateMatchingConfig;
+use crate::privacy_filter::{PrivacyFilter, PrivacyFilterConfig};
+use crate::output_sink::OutputSinkKind;
+use crate::embeddings::EmbeddingsConfig;
+use crate::report::ReportConfig;
+use crate::workflow_miner::WorkflowMinerConfig;
+use crate::app_extractor::AppExtractorsConfig;
+use crate::sap_gui::SapGuiExtractor;
+use crate::terminal::TerminalExtractor;
+use crate::ide::IdeExtractor;
+use crate::system_probe::SystemProbeConfig;
+use crate::storage_monitor::StorageMonitorConfig;
+use crate::audio_analyzer::AudioAnalyzerConfig;
+use crate::capture::CaptureConfig;
+use crate::display_timeline::DisplayTimelineConfig;
+use crate::adaptive_fps::AdaptiveFpsConfig;
+use crate::burst_sampling::BurstSamplingConfig;
+use crate::keyframe_codec::KeyframeStorageConfig;
+use crate::screen_classifier::ScreenClassifierConfig;
+use crate::anonymizer::AnonymizerConfig;
+use crate::calibration::CalibrationConfig;
+use crate::plugin_tables::PluginTablesConfig;
+use crate::workflow_chain::WorkflowChainConfig;
+use crate::error_recovery_analyzer::ErrorRecoveryConfig;
+use crate::event_signing::EventSigner;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexerConfig {
@@ -9,6 +59,223 @@ pub struct IndexerConfig {
     pub scene_detection: SceneDetectionConfig,
     pub video_extensions: Vec<String>,
     pub max_concurrent_processing: usize,
+    /// Path of the pending-segment ledger; defaults to `<output_dir>/pending_segments.jsonl`
+    #[serde(default)]
+    pub pending_ledger_path: Option<String>,
+    /// Directory of failed segments and frames; defaults to `<output_dir>/dead_letter`
+    #[serde(default)]
+    pub dead_letter_dir: Option<String>,
+    /// Path of the hash-chained data access log; defaults to `<output_dir>/access_audit.jsonl`
+    #[serde(default)]
+    pub access_audit_path: Option<String>,
+    /// API keys and TLS settings for network endpoints
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Arrow Flight endpoint started by `serve-flight`
+    #[serde(default)]
+    pub flight: FlightConfig,
+    /// External commands run when detected events match a predicate
+    #[serde(default)]
+    pub triggers: Vec<TriggerConfig>,
+    /// Slack and Teams webhooks posted to when detected events match a predicate
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+    /// Byte limits of the Parquet writers' buffers and when they spill to disk
+    #[serde(default)]
+    pub writer_memory: WriterMemoryConfig,
+    /// How the Parquet writers size row groups and write batches
+    #[serde(default)]
+    pub parquet_tuning: ParquetTuningConfig,
+    /// Log level, format, per-stage rate limits and log file
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// `/healthz` and `/readyz` endpoints for service managers
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// How watched segments are settled, renamed files handled and missed ones rescanned
+    #[serde(default)]
+    pub file_watcher: FileWatcherConfig,
+    /// Re-OCR only regions reported as changed by scene detection
+    #[serde(default)]
+    pub incremental_ocr: IncrementalOcrConfig,
+    /// Tag segments with concurrent calendar meetings
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    /// Skip per-frame work for near-identical keyframes within a segment
+    #[serde(default)]
+    pub frame_dedup: FrameDedupConfig,
+    /// Daily focus summary notes and notifications
+    #[serde(default)]
+    pub focus_summary: FocusSummaryConfig,
+    /// Per-day markdown notes for knowledge bases such as Obsidian
+    #[serde(default)]
+    pub markdown_export: MarkdownExportConfig,
+    /// Scroll detection between consecutive keyframes
+    #[serde(default)]
+    pub scroll_detection: ScrollDetectionConfig,
+    /// Unit and per-display scale factors of OCR bounding boxes
+    #[serde(default)]
+    pub roi_scale: RoiScaleConfig,
+    /// Detect OCR quality regressions from per-frame density metrics
+    #[serde(default)]
+    pub ocr_quality: OcrQualityConfig,
+    /// Detect recorder resolution and frame-rate changes and rescale pixel thresholds
+    #[serde(default)]
+    pub capture_profile: CaptureProfileConfig,
+    /// Screenshots of known dialogs to recognize in keyframes
+    #[serde(default)]
+    pub template_matching: TemplateMatchingConfig,
+    /// Rules muting known noisy events from alerts and reports
+    #[serde(default)]
+    pub suppressions: Vec<SuppressionRule>,
+    /// Where rules managed through the control API are kept; defaults to `<output_dir>/suppressions.json`
+    #[serde(default)]
+    pub suppression_store_path: Option<String>,
+    /// Where OCR results written by the capture app are read from
+    #[serde(default)]
+    pub ocr: OcrConfig,
+    /// Field, error and modal detection on OCR text
+    #[serde(default)]
+    pub event_detection: EventDetectionConfig,
+    /// Cursor and gesture tracking, used with `navigation_tracking`
+    #[serde(default)]
+    pub cursor: CursorTrackingConfig,
+    /// Window, tab and focus change detection, used with `navigation_tracking`
+    #[serde(default)]
+    pub navigation: NavigationDetectionConfig,
+    /// Track window, tab and cursor changes live while capturing the screen
+    #[serde(default)]
+    pub navigation_tracking: bool,
+    /// Pairing of related events for live tracking, workflow chains and error recovery
+    #[serde(default)]
+    pub correlation: CorrelationConfig,
+    /// Encryption of Parquet files at rest
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// How long stored outputs are kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Apps and sites whose frames are dropped, leaving only redacted intervals
+    #[serde(default)]
+    pub privacy: PrivacyFilterConfig,
+    /// Formats frame metadata, OCR results and events are also written in
+    #[serde(default)]
+    pub output_sinks: Vec<OutputSinkKind>,
+    /// Text embeddings of each frame's OCR text, for similarity search
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+    /// Session report generation
+    #[serde(default)]
+    pub report: ReportConfig,
+    /// Mining repeated action sequences across sessions
+    #[serde(default)]
+    pub workflow_miner: WorkflowMinerConfig,
+    /// Structured extraction for specific applications such as SAP GUI
+    #[serde(default)]
+    pub app_extractors: AppExtractorsConfig,
+    /// Timeouts, retries and circuit breaking for AppleScript probes
+    #[serde(default)]
+    pub system_probe: SystemProbeConfig,
+    /// Throttling as disk space or the session's storage budget runs low
+    #[serde(default)]
+    pub storage_monitor: StorageMonitorConfig,
+    /// Meeting detection from segment audio
+    #[serde(default)]
+    pub audio_analyzer: AudioAnalyzerConfig,
+    /// Frame rate, display and segment length of `indexer capture`
+    #[serde(default)]
+    pub capture: CaptureConfig,
+    /// Merging of the displays' keyframes into one timeline
+    #[serde(default)]
+    pub display_timeline: DisplayTimelineConfig,
+    /// Extraction rate tuned to each display's activity, within bounds
+    #[serde(default)]
+    pub adaptive_fps: AdaptiveFpsConfig,
+    /// Extra frames sampled while a toast or snackbar is on screen
+    #[serde(default)]
+    pub burst_sampling: BurstSamplingConfig,
+    /// Image codec and quality keyframes are saved with
+    #[serde(default)]
+    pub keyframe_storage: KeyframeStorageConfig,
+    /// Labelling of keyframes as browser, IDE, terminal or video call
+    #[serde(default)]
+    pub screen_classifier: ScreenClassifierConfig,
+    /// Pseudonymization of usernames, emails, window titles and URLs in anonymized exports
+    #[serde(default)]
+    pub anonymizer: AnonymizerConfig,
+    /// Per-detector confidence curves fitted on labeled events
+    #[serde(default)]
+    pub calibration: CalibrationConfig,
+    /// Output tables declared by detector plugins
+    #[serde(default)]
+    pub plugin_tables: PluginTablesConfig,
+    /// Multi-step workflow chains stitched from correlated events
+    #[serde(default)]
+    pub workflow_chains: WorkflowChainConfig,
+    /// Following errors through to their resolution
+    #[serde(default)]
+    pub error_recovery: ErrorRecoveryConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrConfig {
+    /// OCR Parquet directory used when a command gets no `--ocr-dir`
+    pub results_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Read and write OCR Parquet files encrypted with the key in `ENCRYPTION_KEY`
+    pub enabled: bool,
+    /// Sign stored events with the Ed25519 key derived from `ENCRYPTION_KEY`
+    pub sign_events: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Delete expired keyframes and outputs once a day while watching or capturing
+    pub enabled: bool,
+    /// Days keyframe images are kept
+    pub keyframe_days: u32,
+    /// Days frame metadata, OCR results and events are kept
+    pub output_days: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keyframe_days: 30,
+            output_days: 365,
+        }
+    }
+}
+
+/// Prefix of environment variables overriding settings; `__` separates the
+/// parts of a setting's path, e.g. `INDEXER_SCENE_DETECTION__SSIM_THRESHOLD=0.9`
+pub const ENV_PREFIX: &str = "INDEXER_";
+
+/// A setting that fails validation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigIssue {
+    /// Dotted path of the setting, e.g. `scene_detection.ssim_threshold`
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { path: path.into(), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +283,75 @@ pub struct SceneDetectionConfig {
     pub ssim_threshold: f32,
     pub phash_distance_threshold: u32,
     pub entropy_threshold: f32,
+    /// Perceptual hash used for the pHash distance metric
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Derive thresholds from rolling frame-pair statistics instead of the static values
+    #[serde(default)]
+    pub auto_calibrate: bool,
+    /// Number of recent frame pairs kept for calibration
+    #[serde(default = "default_calibration_window")]
+    pub calibration_window: usize,
+    /// Minimum frame pairs observed before calibrated thresholds are used
+    #[serde(default = "default_calibration_min_samples")]
+    pub calibration_min_samples: usize,
+    /// Robust deviations from the median a pair must exceed to count as a change
+    #[serde(default = "default_calibration_sensitivity")]
+    pub calibration_sensitivity: f32,
+    /// Tiles per side of the block-wise diff grid used to locate changed regions
+    #[serde(default = "default_region_grid_size")]
+    pub region_grid_size: u32,
+    /// Mean absolute luma difference (0-1) a tile must exceed to count as changed
+    #[serde(default = "default_region_change_threshold")]
+    pub region_change_threshold: f32,
+    /// Maximum changed regions reported per scene change, most changed first
+    #[serde(default = "default_max_changed_regions")]
+    pub max_changed_regions: usize,
+}
+
+/// Perceptual hash algorithms available to scene detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// Average hash: pixels above the mean of an 8x8 thumbnail
+    #[default]
+    Average,
+    /// Difference hash: horizontal gradients of a 9x8 thumbnail; robust to brightness shifts
+    Difference,
+    /// Wavelet hash: Haar low-frequency band thresholded at its median; robust to small text edits
+    Wavelet,
+}
+
+fn default_calibration_window() -> usize {
+    500
+}
+
+fn default_calibration_min_samples() -> usize {
+    10
+}
+
+fn default_calibration_sensitivity() -> f32 {
+    3.0
+}
+
+fn default_region_grid_size() -> u32 {
+    8
+}
+
+fn default_region_change_threshold() -> f32 {
+    0.05
+}
+
+fn default_max_changed_regions() -> usize {
+    8
+}
+
+fn duplicate_trigger_name(triggers: &[TriggerConfig]) -> Option<&str> {
+    let mut seen = std::collections::HashSet::new();
+    triggers
+        .iter()
+        .map(|t| t.name.as_str())
+        .find(|name| !seen.insert(*name))
 }
 
 impl Default for IndexerConfig {
@@ -31,6 +367,58 @@ impl Default for IndexerConfig {
                 "mkv".to_string(),
             ],
             max_concurrent_processing: 4,
+            pending_ledger_path: None,
+            dead_letter_dir: None,
+            access_audit_path: None,
+            auth: AuthConfig::default(),
+            flight: FlightConfig::default(),
+            triggers: Vec::new(),
+            notifiers: Vec::new(),
+            writer_memory: WriterMemoryConfig::default(),
+            parquet_tuning: ParquetTuningConfig::default(),
+            logging: LoggingConfig::default(),
+            health: HealthConfig::default(),
+            file_watcher: FileWatcherConfig::default(),
+            incremental_ocr: IncrementalOcrConfig::default(),
+            calendar: CalendarConfig::default(),
+            frame_dedup: FrameDedupConfig::default(),
+            focus_summary: FocusSummaryConfig::default(),
+            markdown_export: MarkdownExportConfig::default(),
+            scroll_detection: ScrollDetectionConfig::default(),
+            roi_scale: RoiScaleConfig::default(),
+            ocr_quality: OcrQualityConfig::default(),
+            capture_profile: CaptureProfileConfig::default(),
+            template_matching: TemplateMatchingConfig::default(),
+            suppressions: Vec::new(),
+            suppression_store_path: None,
+            ocr: OcrConfig::default(),
+            event_detection: EventDetectionConfig::default(),
+            cursor: CursorTrackingConfig::default(),
+            navigation: NavigationDetectionConfig::default(),
+            navigation_tracking: false,
+            correlation: CorrelationConfig::default(),
+            encryption: EncryptionConfig::default(),
+            retention: RetentionConfig::default(),
+            privacy: PrivacyFilterConfig::default(),
+            output_sinks: Vec::new(),
+            embeddings: EmbeddingsConfig::default(),
+            report: ReportConfig::default(),
+            workflow_miner: WorkflowMinerConfig::default(),
+            app_extractors: AppExtractorsConfig::default(),
+            system_probe: SystemProbeConfig::default(),
+            storage_monitor: StorageMonitorConfig::default(),
+            audio_analyzer: AudioAnalyzerConfig::default(),
+            capture: CaptureConfig::default(),
+            display_timeline: DisplayTimelineConfig::default(),
+            adaptive_fps: AdaptiveFpsConfig::default(),
+            burst_sampling: BurstSamplingConfig::default(),
+            keyframe_storage: KeyframeStorageConfig::default(),
+            screen_classifier: ScreenClassifierConfig::default(),
+            anonymizer: AnonymizerConfig::default(),
+            calibration: CalibrationConfig::default(),
+            plugin_tables: PluginTablesConfig::default(),
+            workflow_chains: WorkflowChainConfig::default(),
+            error_recovery: ErrorRecoveryConfig::default(),
         }
     }
 }
@@ -41,6 +429,14 @@ impl Default for SceneDetectionConfig {
             ssim_threshold: 0.8,
             phash_distance_threshold: 10,
             entropy_threshold: 0.1,
+            hash_algorithm: HashAlgorithm::default(),
+            auto_calibrate: false,
+            calibration_window: default_calibration_window(),
+            calibration_min_samples: default_calibration_min_samples(),
+            calibration_sensitivity: default_calibration_sensitivity(),
+            region_grid_size: default_region_grid_size(),
+            region_change_threshold: default_region_change_threshold(),
+            max_changed_regions: default_max_changed_regions(),
         }
     }
 }
@@ -57,6 +453,79 @@ impl IndexerConfig {
         Ok(config)
     }
     
+    /// Load the config from its layers, later ones winning: built-in defaults,
+    /// the file at `path` when it exists, `INDEXER_*` environment variables,
+    /// then `section.setting=value` overrides from the command line
+    pub fn load(path: Option<&Path>, overrides: &[String]) -> Result<Self> {
+        let config = Self::from_layers(path, std::env::vars(), overrides)?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    /// Merge the config layers without validating the result.
+    ///
+    /// The file only needs the settings it changes. Override values are parsed
+    /// as JSON, falling back to a plain string, and must name an existing setting.
+    pub fn from_layers(
+        path: Option<&Path>,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[String],
+    ) -> Result<Self> {
+        let defaults = serde_json::to_value(Self::default())?;
+        let mut value = defaults.clone();
+        
+        if let Some(path) = path.filter(|path| path.exists()) {
+            let file = read_json(path)?;
+            for setting in unknown_settings(&defaults, &file) {
+                warn!("Ignoring unknown setting '{}' in {}", setting, path.display());
+            }
+            merge_json(&mut value, file);
+        }
+        
+        let mut env: Vec<(String, String)> = env.into_iter().filter(|(key, _)| key.starts_with(ENV_PREFIX)).collect();
+        env.sort();
+        for (key, raw) in env {
+            let setting = key[ENV_PREFIX.len()..].to_lowercase().replace("__", ".");
+            apply_override(&mut value, &defaults, &setting, &raw)
+                .map_err(|e| IndexerError::Config(format!("{}: {}", key, e)))?;
+        }
+        
+        for assignment in overrides {
+            let (setting, raw) = assignment.split_once('=').ok_or_else(|| {
+                IndexerError::Config(format!("Override '{}' must look like section.setting=value", assignment))
+            })?;
+            apply_override(&mut value, &defaults, setting.trim(), raw)
+                .map_err(|e| IndexerError::Config(format!("--set {}: {}", setting.trim(), e)))?;
+        }
+        
+        serde_json::from_value(value).map_err(|e| IndexerError::Config(format!("Invalid config: {}", e)))
+    }
+    
+    /// Defaults overlaid with the settings in a JSON document, e.g. one
+    /// handed over by an embedding app
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let mut value = serde_json::to_value(Self::default())?;
+        let layer = serde_json::from_str(json).map_err(|e| IndexerError::Config(format!("Failed to parse config: {}", e)))?;
+        merge_json(&mut value, layer);
+        let config: Self = serde_json::from_value(value).map_err(|e| IndexerError::Config(format!("Invalid config: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    /// Settings in the file at `path` that no config section defines
+    pub fn unknown_settings(path: &Path) -> Result<Vec<String>> {
+        let defaults = serde_json::to_value(Self::default())?;
+        Ok(unknown_settings(&defaults, &read_json(path)?))
+    }
+    
+    /// Event detection settings with the shared ROI scale applied
+    pub fn event_detection_config(&self) -> EventDetectionConfig {
+        EventDetectionConfig {
+            roi_scale: self.roi_scale.clone(),
+            ..self.event_detection.clone()
+        }
+    }
+    
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let content = serde_json::to_string_pretty(self)?;
         std::fs::write(path, content)
@@ -64,25 +533,465 @@ impl IndexerConfig {
         Ok(())
     }
     
+    /// Resolve the location of the pending-segment ledger
+    pub fn ledger_path(&self) -> PathBuf {
+        match &self.pending_ledger_path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.output_dir).join("pending_segments.jsonl"),
+        }
+    }
+    
+    /// Resolve the location of the dead-letter store
+    pub fn dead_letter_dir(&self) -> PathBuf {
+        match &self.dead_letter_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("dead_letter"),
+        }
+    }
+    
+    /// Resolve the location of the file watcher's rescan watermark
+    pub fn watcher_state_path(&self) -> PathBuf {
+        match &self.file_watcher.state_path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.output_dir).join("watcher_state.json"),
+        }
+    }
+    
+    /// Resolve the file a display's followed fields are kept in
+    pub fn field_identities_path(&self, monitor_id: i32) -> PathBuf {
+        let dir = match &self.event_detection.field_identity.state_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("field_identities"),
+        };
+        dir.join(format!("display_{}.json", monitor_id))
+    }
+    
+    /// Resolve the location of submitted form records
+    pub fn forms_dir(&self) -> PathBuf {
+        match &self.event_detection.form_model.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("forms"),
+        }
+    }
+    
+    /// Resolve the location of the fitted confidence calibration
+    pub fn calibration_path(&self) -> PathBuf {
+        match &self.calibration.path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.output_dir).join("calibration.json"),
+        }
+    }
+    
+    /// Resolve the root of the tables declared by detector plugins
+    pub fn plugin_tables_dir(&self) -> PathBuf {
+        match &self.plugin_tables.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("plugin_tables"),
+        }
+    }
+    
+    /// Resolve the location of events recorded by live navigation tracking
+    pub fn navigation_dir(&self) -> PathBuf {
+        Path::new(&self.output_dir).join("navigation")
+    }
+    
+    /// Resolve the OCR Parquet directory, `<output_dir>/ocr` when `ocr.results_dir` is unset
+    pub fn ocr_results_dir(&self) -> PathBuf {
+        match &self.ocr.results_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("ocr"),
+        }
+    }
+    
+    /// Resolve the location of stored correlations, shared by the service and live navigation tracking
+    pub fn correlations_dir(&self) -> PathBuf {
+        Path::new(&self.output_dir).join("correlations")
+    }
+    
+    /// Resolve the location of stored workflow chains
+    pub fn workflow_chains_dir(&self) -> PathBuf {
+        match &self.workflow_chains.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("workflow_chains"),
+        }
+    }
+    
+    /// Resolve the location of stored error recovery sequences
+    pub fn error_recovery_dir(&self) -> PathBuf {
+        match &self.error_recovery.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.output_dir).join("error_recovery"),
+        }
+    }
+    
+    /// Resolve the location of the data access audit log
+    pub fn access_audit_path(&self) -> PathBuf {
+        match &self.access_audit_path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.output_dir).join("access_audit.jsonl"),
+        }
+    }
+    
+    /// Progress file of `process` runs over an archive, used to resume them
+    pub fn batch_checkpoint_path(&self) -> PathBuf {
+        Path::new(&self.output_dir).join("batch_checkpoint.jsonl")
+    }
+    
+    /// Resolve the location of runtime-managed suppression rules
+    pub fn suppression_store_path(&self) -> PathBuf {
+        match &self.suppression_store_path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.output_dir).join("suppressions.json"),
+        }
+    }
+    
+    /// Suppression rules from the config merged with those managed at runtime
+    pub fn suppression_list(&self) -> Result<SuppressionList> {
+        SuppressionList::new(self.suppressions.clone())?.with_store(self.suppression_store_path())
+    }
+    
+    /// Signer for stored events, when `encryption.sign_events` is set
+    pub fn event_signer(&self) -> Result<Option<EventSigner>> {
+        self.encryption.sign_events.then(EventSigner::new).transpose()
+    }
+    
+    /// Check that files and directories the config points at exist.
+    ///
+    /// Used before applying a reloaded config, where a typo should not
+    /// silently disable a feature of the running service.
+    pub fn validate_paths(&self) -> Result<()> {
+        match self.path_issues().into_iter().next() {
+            Some(issue) => Err(IndexerError::Config(issue.to_string())),
+            None => Ok(()),
+        }
+    }
+    
+    /// Every file or directory setting whose target is missing
+    pub fn path_issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut check = |valid: bool, path: &str, target: &str, message: &str| {
+            if !valid {
+                issues.push(ConfigIssue::new(path, format!("{}: {}", message, target)));
+            }
+        };
+        
+        let output_dir = Path::new(&self.output_dir);
+        check(!output_dir.exists() || output_dir.is_dir(), "output_dir", &self.output_dir, "not a directory");
+        
+        if let Some(ics_path) = self.calendar.ics_path.as_ref().filter(|_| self.calendar.enabled) {
+            check(Path::new(ics_path).is_file(), "calendar.ics_path", ics_path, "does not exist");
+        }
+        
+        if self.markdown_export.enabled {
+            if let Some(template_path) = &self.markdown_export.template_path {
+                check(Path::new(template_path).is_file(), "markdown_export.template_path", template_path, "does not exist");
+            }
+            if let Some(frames_dir) = &self.markdown_export.frames_dir {
+                check(Path::new(frames_dir).is_dir(), "markdown_export.frames_dir", frames_dir, "not a directory");
+            }
+        }
+        
+        if let Some(results_dir) = &self.ocr.results_dir {
+            check(Path::new(results_dir).is_dir(), "ocr.results_dir", results_dir, "not a directory");
+        }
+        
+        for (i, manifest) in self.plugin_tables.manifests.iter().enumerate() {
+            let path = format!("plugin_tables.manifests[{}].path", i);
+            check(Path::new(&manifest.path).is_file(), &path, &manifest.path, "does not exist");
+        }
+        
+        issues
+    }
+    
     fn validate(&self) -> Result<()> {
-        if self.extraction_fps <= 0.0 || self.extraction_fps > 30.0 {
-            return Err(IndexerError::Config(
-                "extraction_fps must be between 0 and 30".to_string()
-            ));
+        match self.issues().into_iter().next() {
+            Some(issue) => Err(IndexerError::Config(issue.to_string())),
+            None => Ok(()),
+        }
+    }
+    
+    /// Every setting that fails validation
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut check = |valid: bool, path: &str, message: &str| {
+            if !valid {
+                issues.push(ConfigIssue::new(path, message));
+            }
+        };
+        
+        check(self.extraction_fps > 0.0 && self.extraction_fps <= 30.0, "extraction_fps", "must be between 0 and 30");
+        check(self.max_concurrent_processing > 0, "max_concurrent_processing", "must be greater than 0");
+        
+        let scene = &self.scene_detection;
+        check((0.0..=1.0).contains(&scene.ssim_threshold), "scene_detection.ssim_threshold", "must be between 0 and 1");
+        check(
+            !scene.auto_calibrate || scene.calibration_window > 0,
+            "scene_detection.calibration_window",
+            "must be greater than 0",
+        );
+        check(
+            (1..=64).contains(&scene.region_grid_size),
+            "scene_detection.region_grid_size",
+            "must be between 1 and 64",
+        );
+        
+        check(
+            (0.0..=1.0).contains(&self.incremental_ocr.max_changed_area_ratio),
+            "incremental_ocr.max_changed_area_ratio",
+            "must be between 0 and 1",
+        );
+        check(
+            (0.0..=100.0).contains(&self.incremental_ocr.sparse_text_coverage),
+            "incremental_ocr.sparse_text_coverage",
+            "must be a percentage between 0 and 100",
+        );
+        check(
+            !self.ocr_quality.enabled || self.ocr_quality.recent_frames > 0,
+            "ocr_quality.recent_frames",
+            "must be greater than 0",
+        );
+        check(
+            !self.calendar.enabled || self.calendar.ics_path.is_some(),
+            "calendar.ics_path",
+            "is required when calendar enrichment is enabled",
+        );
+        
+        let events = &self.event_detection;
+        for (path, value) in [
+            ("event_detection.min_ocr_confidence", events.min_ocr_confidence),
+            ("event_detection.min_iou_threshold", events.min_iou_threshold),
+            ("event_detection.min_text_similarity", events.min_text_similarity),
+            ("event_detection.min_event_confidence", events.min_event_confidence),
+            ("cursor.min_confidence", self.cursor.min_confidence),
+            ("navigation.min_confidence", self.navigation.min_confidence),
+            ("correlation.min_correlation_confidence", self.correlation.min_correlation_confidence),
+        ] {
+            check((0.0..=1.0).contains(&value), path, "must be between 0 and 1");
         }
+        check(events.max_cached_frames > 0, "event_detection.max_cached_frames", "must be greater than 0");
+        check(self.cursor.sampling_interval_ms > 0, "cursor.sampling_interval_ms", "must be greater than 0");
+        check(
+            self.correlation.max_correlation_window_ms > 0,
+            "correlation.max_correlation_window_ms",
+            "must be greater than 0",
+        );
         
-        if self.scene_detection.ssim_threshold < 0.0 || self.scene_detection.ssim_threshold > 1.0 {
-            return Err(IndexerError::Config(
-                "ssim_threshold must be between 0 and 1".to_string()
-            ));
+        check(
+            !self.encryption.enabled || std::env::var_os("ENCRYPTION_KEY").is_some(),
+            "encryption.enabled",
+            "needs the key in ENCRYPTION_KEY; files encrypted with a generated key cannot be read later",
+        );
+        check(
+            !self.encryption.sign_events || std::env::var_os("ENCRYPTION_KEY").is_some(),
+            "encryption.sign_events",
+            "needs the key in ENCRYPTION_KEY; signatures made with a generated key cannot be verified later",
+        );
+        if self.retention.enabled {
+            check(self.retention.keyframe_days > 0, "retention.keyframe_days", "must be greater than 0");
+            check(self.retention.output_days > 0, "retention.output_days", "must be greater than 0");
         }
         
-        if self.max_concurrent_processing == 0 {
-            return Err(IndexerError::Config(
-                "max_concurrent_processing must be greater than 0".to_string()
-            ));
+        let mut nested = |path: &str, result: Result<()>| {
+            if let Err(e) = result {
+                let message = match e {
+                    IndexerError::Config(message) => message,
+                    other => other.to_string(),
+                };
+                issues.push(ConfigIssue::new(path, message));
+            }
+        };
+        nested("auth", self.auth.validate());
+        nested("flight", self.flight.validate());
+        nested("writer_memory", self.writer_memory.validate());
+        nested("parquet_tuning", self.parquet_tuning.validate());
+        nested("logging", self.logging.validate());
+        nested("health", self.health.validate());
+        nested("file_watcher", self.file_watcher.validate());
+        nested("event_detection.dedup", self.event_detection.dedup.validate());
+        nested("event_detection.field_identity", self.event_detection.field_identity.validate());
+        nested("event_detection.form_model", self.event_detection.form_model.validate());
+        nested("event_detection.tables", self.event_detection.tables.validate());
+        nested("event_detection.progress", self.event_detection.progress.validate());
+        nested("template_matching", self.template_matching.validate());
+        if self.focus_summary.enabled {
+            nested("focus_summary", self.focus_summary.validate());
         }
+        if self.markdown_export.enabled {
+            nested("markdown_export", self.markdown_export.validate());
+        }
+        for (index, trigger) in self.triggers.iter().enumerate() {
+            nested(&format!("triggers[{}]", index), trigger.validate());
+        }
+        if let Some(name) = duplicate_trigger_name(&self.triggers) {
+            nested("triggers", Err(IndexerError::Config(format!("Duplicate trigger name '{}'", name))));
+        }
+        for (index, notifier) in self.notifiers.iter().enumerate() {
+            nested(&format!("notifiers[{}]", index), notifier.validate());
+        }
+        nested("suppressions", SuppressionList::new(self.suppressions.clone()).map(|_| ()));
+        nested("privacy", PrivacyFilter::new(self.privacy.clone()).map(|_| ()));
+        nested("embeddings", self.embeddings.validate());
+        nested("report", self.report.validate());
+        nested("workflow_miner", self.workflow_miner.validate());
+        nested("system_probe", self.system_probe.validate());
+        nested("storage_monitor", self.storage_monitor.validate());
+        nested("audio_analyzer", self.audio_analyzer.validate());
+        nested("capture", self.capture.validate());
+        nested("display_timeline", self.display_timeline.validate());
+        nested("adaptive_fps", self.adaptive_fps.validate());
+        nested("burst_sampling", self.burst_sampling.validate());
+        nested("keyframe_storage", self.keyframe_storage.validate());
+        nested("screen_classifier", self.screen_classifier.validate());
+        nested("anonymizer", self.anonymizer.validate());
+        nested("calibration", self.calibration.validate());
+        nested("app_extractors.sap_gui", SapGuiExtractor::new(self.app_extractors.sap_gui.clone()).map(|_| ()));
+        nested("app_extractors.terminal", TerminalExtractor::new(self.app_extractors.terminal.clone()).map(|_| ()));
+        nested("app_extractors.ide", IdeExtractor::new(self.app_extractors.ide.clone(), self.app_extractors.terminal.clone()).map(|_| ()));
         
-        Ok(())
+        issues
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| IndexerError::Config(format!("Failed to read config file: {}", e)))?;
+    serde_json::from_str(&content).map_err(|e| IndexerError::Config(format!("Failed to parse config: {}", e)))
+}
+
+/// Overlay `layer` on `base`, object by object
+fn merge_json(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Whether the keys below a default value are free-form map keys rather than
+/// fields; maps are empty by default, while sections always have fields
+fn is_map(defaults: Option<&Value>) -> bool {
+    defaults.map_or(true, |value| value.as_object().is_some_and(|object| object.is_empty()))
+}
+
+fn unknown_settings(defaults: &Value, layer: &Value) -> Vec<String> {
+    fn walk(defaults: Option<&Value>, layer: &Value, prefix: &str, unknown: &mut Vec<String>) {
+        let Value::Object(layer) = layer else {
+            return;
+        };
+        for (key, value) in layer {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            let default = defaults.and_then(|defaults| defaults.get(key));
+            if default.is_none() && !is_map(defaults) {
+                unknown.push(path);
+            } else {
+                walk(default, value, &path, unknown);
+            }
+        }
+    }
+    let mut unknown = Vec::new();
+    walk(Some(defaults), layer, "", &mut unknown);
+    unknown
+}
+
+/// Set the dotted `setting` in `config` to `raw`, checked against the type of its default
+fn apply_override(config: &mut Value, defaults: &Value, setting: &str, raw: &str) -> std::result::Result<(), String> {
+    let parts: Vec<&str> = setting.split('.').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err("invalid setting path".to_string());
+    }
+    
+    let mut default = Some(defaults);
+    let mut target = config;
+    for (i, part) in parts.iter().enumerate() {
+        let next_default = default.and_then(|value| value.get(*part));
+        if next_default.is_none() && !is_map(default) {
+            return Err(format!("unknown setting '{}'", parts[..=i].join(".")));
+        }
+        let Value::Object(object) = target else {
+            return Err(format!("'{}' is not a section", parts[..i].join(".")));
+        };
+        target = object.entry(part.to_string()).or_insert(Value::Null);
+        default = next_default;
+    }
+    
+    let parsed = serde_json::from_str::<Value>(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+    *target = match default {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        Some(Value::Bool(_)) if !parsed.is_boolean() => return Err(format!("expected true or false, got '{}'", raw)),
+        Some(Value::Number(_)) if !parsed.is_number() => return Err(format!("expected a number, got '{}'", raw)),
+        Some(Value::Array(_)) if !parsed.is_array() => return Err(format!("expected a JSON array, got '{}'", raw)),
+        Some(Value::Object(_)) if !parsed.is_object() => return Err(format!("expected a JSON object, got '{}'", raw)),
+        _ => parsed,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_layers_override_in_order_and_issues_name_their_setting() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"extraction_fps": 2.0, "scene_detection": {"ssim_threshold": 0.7}, "sceen_detection": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(IndexerConfig::unknown_settings(&path).unwrap(), vec!["sceen_detection"]);
+
+        let env = vec![
+            ("INDEXER_SCENE_DETECTION__SSIM_THRESHOLD".to_string(), "0.9".to_string()),
+            ("INDEXER_OUTPUT_DIR".to_string(), "/tmp/from-env".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        let overrides = vec!["output_dir=/tmp/from-cli".to_string(), "retention.enabled=true".to_string()];
+        let config = IndexerConfig::from_layers(Some(&path), env.clone(), &overrides).unwrap();
+        assert_eq!(config.extraction_fps, 2.0);
+        assert_eq!(config.scene_detection.ssim_threshold, 0.9);
+        assert_eq!(config.output_dir, "/tmp/from-cli");
+        assert!(config.retention.enabled);
+        // Unset settings keep their defaults
+        assert_eq!(config.max_concurrent_processing, 4);
+
+        assert!(IndexerConfig::from_layers(Some(&path), env.clone(), &["extraction_fps=fast".to_string()]).is_err());
+        assert!(IndexerConfig::from_layers(Some(&path), env.clone(), &["scene_detection.nope=1".to_string()]).is_err());
+        let scaled = IndexerConfig::from_layers(None, Vec::new(), &["roi_scale.display_scale_factors.2=2.0".to_string()]).unwrap();
+        assert_eq!(scaled.roi_scale.display_scale_factors.get(&2), Some(&2.0));
+
+        let invalid = IndexerConfig::from_layers(
+            Some(&path),
+            env,
+            &["cursor.min_confidence=1.5".to_string(), "retention.enabled=true".to_string(), "retention.output_days=0".to_string()],
+        )
+        .unwrap();
+        let paths: Vec<String> = invalid.issues().into_iter().map(|issue| issue.path).collect();
+        assert_eq!(paths, vec!["cursor.min_confidence", "retention.output_days"]);
+        assert!(invalid.validate().unwrap_err().to_string().contains("cursor.min_confidence"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_event_signing_switch() {
+        let unsigned = IndexerConfig::default();
+        assert!(!unsigned.encryption.sign_events);
+        assert!(unsigned.event_signer().unwrap().is_none());
+
+        let signed = IndexerConfig::from_layers(None, Vec::new(), &["encryption.sign_events=true".to_string()]).unwrap();
+        assert!(signed.encryption.sign_events);
+        assert!(signed.event_signer().unwrap().is_some());
+        // Without a stored key, signatures could not be verified after a restart
+        let flagged = signed.issues().into_iter().any(|issue| issue.path == "encryption.sign_events");
+        assert_eq!(flagged, std::env::var_os("ENCRYPTION_KEY").is_none());
+        assert!(IndexerConfig::from_layers(None, Vec::new(), &["encryption.sign_events=yes".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::config::IndexerConfig;
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Settings a running service cannot change; edits to them wait for a restart
+pub const RESTART_REQUIRED_SETTINGS: &[&str] = &[
+    "output_dir",
+    "pending_ledger_path",
+    "dead_letter_dir",
+    "suppression_store_path",
+    "max_concurrent_processing",
+    "auth",
+    "logging",
+    "health",
+    "file_watcher",
+    "plugin_tables",
+    "correlation",
+    "workflow_chains",
+    "error_recovery",
+    "navigation_tracking",
+    "writer_memory",
+];
+
+/// Watches the config file for edits.
+///
+/// The file's modification time is polled, as calendar files are, so editors
+/// that save by replacing the file are noticed too.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Command-line overrides, reapplied on top of every reload
+    overrides: Vec<String>,
+}
+
+impl ConfigWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        Self { path, modified, overrides: Vec::new() }
+    }
+
+    pub fn with_overrides(mut self, overrides: Vec<String>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The new config when the file changed since the last check.
+    ///
+    /// The file is layered like at startup, with environment and command-line
+    /// overrides, then validated, including the paths it points at. An invalid
+    /// edit is reported once; the next save is checked again.
+    pub fn check(&mut self) -> Result<Option<IndexerConfig>> {
+        let modified = modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+
+        let config = IndexerConfig::load(Some(&self.path), &self.overrides)?;
+        config.validate_paths()?;
+        Ok(Some(config))
+    }
+}
+
+/// Names of the settings in `RESTART_REQUIRED_SETTINGS` that differ between two configs
+pub fn restart_required_changes(current: &IndexerConfig, new: &IndexerConfig) -> Vec<&'static str> {
+    let (Ok(current), Ok(new)) = (serde_json::to_value(current), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    RESTART_REQUIRED_SETTINGS
+        .iter()
+        .copied()
+        .filter(|setting| current.get(setting) != new.get(setting))
+        .collect()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reports_valid_edits_and_rejects_invalid_ones() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let config = IndexerConfig::default();
+        config.to_file(&path).unwrap();
+
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(watcher.check().unwrap().is_none());
+
+        // Modification times can be coarse, so each edit sets its own
+        let touch = |secs: u64| {
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+        };
+
+        let mut edited = config.clone();
+        edited.extraction_fps = 2.0;
+        edited.output_dir = "./elsewhere".to_string();
+        edited.to_file(&path).unwrap();
+        touch(1_000);
+        let reloaded = watcher.check().unwrap().unwrap();
+        assert_eq!(reloaded.extraction_fps, 2.0);
+        assert_eq!(restart_required_changes(&config, &reloaded), vec!["output_dir"]);
+        assert!(watcher.check().unwrap().is_none());
+
+        let mut invalid = config.clone();
+        invalid.extraction_fps = 0.0;
+        invalid.to_file(&path).unwrap();
+        touch(2_000);
+        assert!(watcher.check().is_err());
+        assert!(watcher.check().unwrap().is_none());
+
+        let mut missing_calendar = config;
+        missing_calendar.calendar.enabled = true;
+        missing_calendar.calendar.ics_path = Some(temp_dir.path().join("missing.ics").to_string_lossy().into_owned());
+        missing_calendar.to_file(&path).unwrap();
+        touch(3_000);
+        assert!(watcher.check().is_err());
+    }
+}
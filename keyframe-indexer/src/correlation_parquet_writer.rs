@@ -0,0 +1,330 @@
+use crate::error::Result;
+use crate::event_correlator::{CorrelationEvidence, CorrelationResult, CorrelationType};
+use crate::layout_compat::{self, CompatibleTable};
+use arrow::array::{
+    Array, Float32Array, Int64Array, ListArray, ListBuilder, StringArray, StringBuilder,
+    TimestampNanosecondArray, TimestampNanosecondBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Stores correlation results so they outlive the correlator's in-memory window
+pub struct CorrelationParquetWriter {
+    output_dir: PathBuf,
+    schema: Arc<Schema>,
+    batch_size: usize,
+    current_batch: Vec<CorrelationResult>,
+    compression: Compression,
+    enable_dictionary_encoding: bool,
+}
+
+impl CorrelationParquetWriter {
+    pub fn new(output_dir: &str) -> Result<Self> {
+        let output_path = PathBuf::from(output_dir);
+        std::fs::create_dir_all(&output_path)?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("correlation_id", DataType::Utf8, false),
+            Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("correlation_type", DataType::Utf8, false),
+            Field::new("correlated_events", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
+            Field::new("confidence", DataType::Float32, false),
+            Field::new("temporal_proximity_ms", DataType::Int64, false),
+            Field::new("spatial_proximity", DataType::Float32, true),
+            Field::new("causal_strength", DataType::Float32, false),
+            Field::new("pattern_match", DataType::Utf8, true),
+            Field::new("clicked_button", DataType::Utf8, true),
+        ]));
+
+        Ok(Self {
+            output_dir: output_path,
+            schema,
+            batch_size: 1000,
+            current_batch: Vec::new(),
+            compression: Compression::SNAPPY,
+            enable_dictionary_encoding: true,
+        })
+    }
+
+    pub async fn write_correlations(&mut self, correlations: &[CorrelationResult]) -> Result<()> {
+        debug!("Writing {} correlations", correlations.len());
+        self.current_batch.extend_from_slice(correlations);
+        if self.current_batch.len() >= self.batch_size {
+            self.flush_batch().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered correlations to disk
+    pub async fn flush_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%f");
+        let file_path = self.output_dir.join(format!("correlations_{}.parquet", timestamp));
+
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        self.write_record_batch(&file_path, record_batch)?;
+
+        info!("Wrote {} correlations to {}", self.current_batch.len(), file_path.display());
+        self.current_batch.clear();
+        Ok(())
+    }
+
+    pub async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch().await
+    }
+
+    /// Correlations of one type, newest first
+    pub async fn query_by_type(&self, correlation_type: &CorrelationType) -> Result<Vec<CorrelationResult>> {
+        self.run_query(&format!(
+            "SELECT * FROM correlations WHERE correlation_type = '{}' ORDER BY ts_ns DESC",
+            correlation_type.as_str()
+        )).await
+    }
+
+    /// Correlations at or above a confidence, most confident first
+    pub async fn query_by_confidence(&self, min_confidence: f32) -> Result<Vec<CorrelationResult>> {
+        self.run_query(&format!(
+            "SELECT * FROM correlations WHERE confidence >= {} ORDER BY confidence DESC",
+            min_confidence
+        )).await
+    }
+
+    /// Correlations of one type at or above a confidence, newest first
+    pub async fn query_by_type_and_confidence(
+        &self,
+        correlation_type: &CorrelationType,
+        min_confidence: f32,
+    ) -> Result<Vec<CorrelationResult>> {
+        self.run_query(&format!(
+            "SELECT * FROM correlations WHERE correlation_type = '{}' AND confidence >= {} ORDER BY ts_ns DESC",
+            correlation_type.as_str(),
+            min_confidence
+        )).await
+    }
+
+    /// Correlations found within a time range, oldest first
+    pub async fn query_by_time_range(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<CorrelationResult>> {
+        self.run_query(&format!(
+            "SELECT * FROM correlations WHERE ts_ns >= {} AND ts_ns <= {} ORDER BY ts_ns ASC",
+            start_time.timestamp_nanos_opt().unwrap_or(0),
+            end_time.timestamp_nanos_opt().unwrap_or(0)
+        )).await
+    }
+
+    /// Register the stored correlations as the `correlations` table
+    pub async fn register_correlations(&self, ctx: &SessionContext) -> Result<()> {
+        CompatibleTable::new("correlations", self.schema.clone())
+            .register(ctx, &self.output_dir)
+            .await
+    }
+
+    // MARK: - Configuration Methods
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    pub fn set_dictionary_encoding(&mut self, enabled: bool) {
+        self.enable_dictionary_encoding = enabled;
+    }
+
+    pub fn get_schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn get_output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    // MARK: - Private Helper Methods
+
+    async fn run_query(&self, sql: &str) -> Result<Vec<CorrelationResult>> {
+        if layout_compat::parquet_files(&self.output_dir)?.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = SessionContext::new();
+        self.register_correlations(&ctx).await?;
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let mut correlations = Vec::new();
+        for batch in &batches {
+            correlations.extend(correlations_from_batch(batch)?);
+        }
+        Ok(correlations)
+    }
+
+    fn create_record_batch(&self, correlations: &[CorrelationResult]) -> Result<RecordBatch> {
+        let mut timestamp_builder = TimestampNanosecondBuilder::new();
+        let mut events_builder = ListBuilder::new(StringBuilder::new());
+        for correlation in correlations {
+            timestamp_builder.append_value(correlation.timestamp.timestamp_nanos_opt().unwrap_or(0));
+            for event_id in &correlation.correlated_events {
+                events_builder.values().append_value(event_id);
+            }
+            events_builder.append(true);
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(StringArray::from(correlations.iter().map(|c| c.correlation_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(timestamp_builder.finish()),
+                Arc::new(StringArray::from(correlations.iter().map(|c| c.correlation_type.as_str()).collect::<Vec<_>>())),
+                Arc::new(events_builder.finish()),
+                Arc::new(Float32Array::from(correlations.iter().map(|c| c.confidence).collect::<Vec<_>>())),
+                Arc::new(Int64Array::from(correlations.iter().map(|c| c.evidence.temporal_proximity).collect::<Vec<_>>())),
+                Arc::new(Float32Array::from(correlations.iter().map(|c| c.evidence.spatial_proximity).collect::<Vec<_>>())),
+                Arc::new(Float32Array::from(correlations.iter().map(|c| c.evidence.causal_strength).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(correlations.iter().map(|c| c.evidence.pattern_match.as_deref()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(correlations.iter().map(|c| c.evidence.clicked_button.as_deref()).collect::<Vec<_>>())),
+            ],
+        )?;
+
+        Ok(record_batch)
+    }
+
+    fn write_record_batch(&self, file_path: &Path, record_batch: RecordBatch) -> Result<()> {
+        let mut props_builder = WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_write_batch_size(1024)
+            .set_max_row_group_size(10000)
+            .set_created_by("AlwaysOnAI Event Correlator".to_string());
+
+        // Few distinct types and repeating event ids compress well as dictionaries
+        if self.enable_dictionary_encoding {
+            props_builder = props_builder
+                .set_dictionary_enabled(true)
+                .set_column_dictionary_enabled("correlation_type".into(), true)
+                .set_column_dictionary_enabled("pattern_match".into(), true);
+        }
+
+        let mut writer = ArrowWriter::try_new(File::create(file_path)?, self.schema.clone(), Some(props_builder.build()))?;
+        writer.write(&record_batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Correlations of a batch read back from a correlations file; rows of an
+/// unknown type are skipped and a batch with unexpected columns is an error
+pub fn correlations_from_batch(batch: &RecordBatch) -> Result<Vec<CorrelationResult>> {
+    let mut correlations = Vec::with_capacity(batch.num_rows());
+
+    let ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let timestamps = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let types = layout_compat::column::<StringArray>(batch, 2)?;
+    let events = layout_compat::column::<ListArray>(batch, 3)?;
+    let confidences = layout_compat::column::<Float32Array>(batch, 4)?;
+    let temporal = layout_compat::column::<Int64Array>(batch, 5)?;
+    let spatial = layout_compat::column::<Float32Array>(batch, 6)?;
+    let causal = layout_compat::column::<Float32Array>(batch, 7)?;
+    let patterns = layout_compat::column::<StringArray>(batch, 8)?;
+    let buttons = layout_compat::column::<StringArray>(batch, 9)?;
+
+    for i in 0..batch.num_rows() {
+        let Some(correlation_type) = CorrelationType::from_name(types.value(i)) else {
+            warn!("Skipping correlation {} of unknown type {}", ids.value(i), types.value(i));
+            continue;
+        };
+        let event_values = events.value(i);
+        let correlated_events = event_values
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .map(|values| values.iter().flatten().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        correlations.push(CorrelationResult {
+            correlation_id: ids.value(i).to_string(),
+            correlated_events,
+            correlation_type,
+            confidence: confidences.value(i),
+            evidence: CorrelationEvidence {
+                temporal_proximity: temporal.value(i),
+                spatial_proximity: (!spatial.is_null(i)).then(|| spatial.value(i)),
+                causal_strength: causal.value(i),
+                pattern_match: (!patterns.is_null(i)).then(|| patterns.value(i).to_string()),
+                clicked_button: (!buttons.is_null(i)).then(|| buttons.value(i).to_string()),
+            },
+            timestamp: DateTime::from_timestamp_nanos(timestamps.value(i)),
+        });
+    }
+
+    Ok(correlations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn correlation(id: &str, correlation_type: CorrelationType, confidence: f32, spatial: Option<f32>) -> CorrelationResult {
+        CorrelationResult {
+            correlation_id: id.to_string(),
+            correlated_events: vec![format!("{}_cause", id), format!("{}_effect", id)],
+            correlation_type,
+            confidence,
+            evidence: CorrelationEvidence {
+                temporal_proximity: 250,
+                spatial_proximity: spatial,
+                causal_strength: 0.8,
+                pattern_match: spatial.map(|_| "click_to_window".to_string()),
+                clicked_button: None,
+            },
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_correlations_survive_and_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = CorrelationParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        writer.set_batch_size(2);
+
+        let click = correlation("c1", CorrelationType::CursorToScreenChange, 0.9, Some(12.5));
+        let recovery = correlation("c2", CorrelationType::ErrorRecovery, 0.6, None);
+        let workflow = correlation("c3", CorrelationType::InteractionWorkflow, 0.75, None);
+        writer.write_correlations(&[click.clone(), recovery.clone()]).await.unwrap();
+        writer.write_correlations(&[workflow]).await.unwrap();
+        writer.finalize().await.unwrap();
+
+        // A fresh writer reads what the first one stored
+        let reader = CorrelationParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        let stored = reader.query_by_type(&CorrelationType::CursorToScreenChange).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].correlated_events, click.correlated_events);
+        assert_eq!(stored[0].evidence.spatial_proximity, Some(12.5));
+        assert_eq!(stored[0].evidence.pattern_match.as_deref(), Some("click_to_window"));
+
+        let confident = reader.query_by_confidence(0.7).await.unwrap();
+        assert_eq!(confident.iter().map(|c| c.correlation_id.as_str()).collect::<Vec<_>>(), vec!["c1", "c3"]);
+        assert!(reader.query_by_type_and_confidence(&CorrelationType::ErrorRecovery, 0.7).await.unwrap().is_empty());
+        assert_eq!(reader.query_by_type(&CorrelationType::ErrorRecovery).await.unwrap()[0].evidence.spatial_proximity, None);
+    }
+
+    #[test]
+    fn test_foreign_batch_is_an_error() {
+        let schema = Arc::new(Schema::new(vec![Field::new("ts_ns", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1, 2]))]).unwrap();
+        assert!(correlations_from_batch(&batch).is_err());
+    }
+}
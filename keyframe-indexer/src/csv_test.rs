@@ -16,6 +16,10 @@ fn create_test_metadata() -> Vec<FrameMetadata> {
             win_title: "Test Window".to_string(),
             width: 1920,
             height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         },
         FrameMetadata {
             ts_ns: 2000000000,
@@ -28,6 +32,10 @@ fn create_test_metadata() -> Vec<FrameMetadata> {
             win_title: "Another Window".to_string(),
             width: 2560,
             height: 1440,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         },
     ]
 }
@@ -106,6 +114,10 @@ async fn test_csv_performance() -> Result<()> {
             win_title: format!("Window_{}", i % 10),
             width: 1920 + (i % 4) as u32 * 320,
             height: 1080 + (i % 3) as u32 * 240,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         });
     }
     
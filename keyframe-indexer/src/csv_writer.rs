@@ -1,5 +1,13 @@
 use crate::error::{IndexerError, Result};
 use crate::metadata_collector::FrameMetadata;
+use crate::ocr_density::OcrDensity;
+use crate::calendar_context::{MeetingAppUsage, SegmentCalendarTag};
+use crate::privacy_filter::RedactedInterval;
+use crate::audio_analyzer::MeetingInterval;
+use crate::display_timeline::TimelineEntry;
+use crate::visual_features::{palette_from_text, palette_to_text, VisualFeatures};
+use crate::screen_classifier::ScreenType;
+use crate::writer_memory::{MemoryBudget, MemoryReservation};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -12,6 +20,8 @@ pub struct CsvWriter {
     current_file_path: Option<PathBuf>,
     batch_size: usize,
     current_batch: Vec<FrameMetadata>,
+    /// Estimated size of `current_batch`, held against the memory budget
+    buffered: MemoryReservation,
 }
 
 impl CsvWriter {
@@ -27,6 +37,7 @@ impl CsvWriter {
             current_file_path: None,
             batch_size: 1000, // Write in batches of 1000 records
             current_batch: Vec::new(),
+            buffered: MemoryBudget::default().reservation(),
         })
     }
     
@@ -35,15 +46,29 @@ impl CsvWriter {
         
         // Add to current batch
         self.current_batch.extend_from_slice(metadata);
-        
-        // Write batch if it's large enough
-        if self.current_batch.len() >= self.batch_size {
+        self.buffered.grow(metadata.iter().map(buffered_size).sum());
+        
+        // Write batch if it's large enough, or early when the budget is exceeded;
+        // CSV rows are not spilled
+        let budget = self.buffered.budget();
+        if self.current_batch.len() >= self.batch_size
+            || self.buffered.bytes() >= budget.flush_threshold_bytes()
+            || budget.is_exceeded()
+        {
             self.flush_batch().await?;
         }
         
         Ok(())
     }
     
+    /// Share a memory budget with other writers; records buffered so far
+    /// move over to it
+    pub fn set_memory_budget(&mut self, budget: MemoryBudget) {
+        let bytes = self.buffered.bytes();
+        self.buffered = budget.reservation();
+        self.buffered.grow(bytes);
+    }
+    
     pub async fn flush_batch(&mut self) -> Result<()> {
         if self.current_batch.is_empty() {
             return Ok(());
@@ -61,11 +86,19 @@ impl CsvWriter {
         
         // Clear current batch
         self.current_batch.clear();
+        self.buffered.clear();
         
         info!("Successfully wrote frame metadata to: {}", file_path.display());
         Ok(())
     }
     
+    /// Bytes a frame metadata CSV file holding `metadata` would take
+    pub fn estimated_size(metadata: &[FrameMetadata]) -> Result<u64> {
+        let mut buffer = Vec::new();
+        write_frame_rows(&mut buffer, metadata)?;
+        Ok(buffer.len() as u64)
+    }
+    
     async fn write_csv_batch(&self, file_path: &Path, metadata: &[FrameMetadata]) -> Result<()> {
         let mut file = OpenOptions::new()
             .create(true)
@@ -73,26 +106,7 @@ impl CsvWriter {
             .truncate(true)
             .open(file_path)?;
         
-        // Write CSV header
-        writeln!(file, "ts_ns,monitor_id,segment_id,path,phash16,entropy,app_name,win_title,width,height")?;
-        
-        // Write data rows
-        for record in metadata {
-            writeln!(
-                file,
-                "{},{},{},{},{},{},{},{},{},{}",
-                record.ts_ns,
-                record.monitor_id,
-                escape_csv_field(&record.segment_id),
-                escape_csv_field(&record.path),
-                record.phash16,
-                record.entropy,
-                escape_csv_field(&record.app_name),
-                escape_csv_field(&record.win_title),
-                record.width,
-                record.height
-            )?;
-        }
+        write_frame_rows(&mut file, metadata)?;
         
         file.flush()?;
         debug!("Successfully wrote CSV file: {}", file_path.display());
@@ -109,6 +123,162 @@ impl CsvWriter {
         Ok(())
     }
     
+    /// Append segment meeting tags to the day's calendar CSV
+    pub async fn write_calendar_tags(&self, tags: &[SegmentCalendarTag]) -> Result<PathBuf> {
+        let file_path = self.output_dir.join(format!("calendar_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !file_path.exists();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        
+        if is_new {
+            writeln!(file, "segment_id,meeting_uid,meeting_title,overlap_start,overlap_end,frame_count")?;
+        }
+        
+        for tag in tags {
+            writeln!(
+                file,
+                "{},{},{},{},{},{}",
+                escape_csv_field(&tag.segment_id),
+                escape_csv_field(&tag.meeting_uid),
+                escape_csv_field(&tag.meeting_title),
+                tag.overlap_start.to_rfc3339(),
+                tag.overlap_end.to_rfc3339(),
+                tag.frame_count
+            )?;
+        }
+        
+        file.flush()?;
+        debug!("Wrote {} calendar tags to {}", tags.len(), file_path.display());
+        Ok(file_path)
+    }
+    
+    /// Append the apps used during calendar meetings to the day's calendar apps CSV
+    pub async fn write_calendar_app_usage(&self, usage: &[MeetingAppUsage]) -> Result<PathBuf> {
+        let file_path = self.output_dir.join(format!("calendar_apps_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !file_path.exists();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        
+        if is_new {
+            writeln!(file, "segment_id,meeting_uid,app_name,frame_count")?;
+        }
+        
+        for row in usage {
+            writeln!(
+                file,
+                "{},{},{},{}",
+                escape_csv_field(&row.segment_id),
+                escape_csv_field(&row.meeting_uid),
+                escape_csv_field(&row.app_name),
+                row.frame_count
+            )?;
+        }
+        
+        file.flush()?;
+        debug!("Wrote {} calendar app usage rows to {}", usage.len(), file_path.display());
+        Ok(file_path)
+    }
+    
+    /// Append time ranges of frames dropped by the privacy filter to the day's redaction file
+    pub async fn write_redacted_intervals(&self, intervals: &[RedactedInterval]) -> Result<PathBuf> {
+        let file_path = self.output_dir.join(format!("redacted_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !file_path.exists();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        
+        if is_new {
+            writeln!(file, "segment_id,monitor_id,start,end,frame_count")?;
+        }
+        
+        for interval in intervals {
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                escape_csv_field(&interval.segment_id),
+                interval.monitor_id,
+                interval.start.to_rfc3339(),
+                interval.end.to_rfc3339(),
+                interval.frame_count
+            )?;
+        }
+        
+        file.flush()?;
+        debug!("Wrote {} redacted intervals to {}", intervals.len(), file_path.display());
+        Ok(file_path)
+    }
+    
+    /// Append meetings found from segment audio to the day's meetings file
+    pub async fn write_meeting_intervals(&self, meetings: &[MeetingInterval]) -> Result<PathBuf> {
+        let file_path = self.output_dir.join(format!("meetings_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !file_path.exists();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        
+        if is_new {
+            writeln!(file, "segment_id,start,end,app,speech_seconds")?;
+        }
+        
+        for meeting in meetings {
+            writeln!(
+                file,
+                "{},{},{},{},{:.1}",
+                escape_csv_field(&meeting.segment_id),
+                meeting.start.to_rfc3339(),
+                meeting.end.to_rfc3339(),
+                escape_csv_field(&meeting.app),
+                meeting.speech_seconds
+            )?;
+        }
+        
+        file.flush()?;
+        debug!("Wrote {} meetings to {}", meetings.len(), file_path.display());
+        Ok(file_path)
+    }
+    
+    /// Append entries of the merged cross-display timeline to the day's timeline file
+    pub async fn write_timeline(&self, entries: &[TimelineEntry]) -> Result<PathBuf> {
+        let file_path = self.output_dir.join(format!("timeline_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !file_path.exists();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        
+        if is_new {
+            writeln!(file, "timestamp,monitor_id,segment_id,path,app_name,win_title")?;
+        }
+        
+        for entry in entries {
+            writeln!(
+                file,
+                "{},{},{},{},{},{}",
+                entry.timestamp.to_rfc3339(),
+                entry.monitor_id,
+                escape_csv_field(&entry.segment_id),
+                escape_csv_field(&entry.path),
+                escape_csv_field(&entry.app_name),
+                escape_csv_field(&entry.win_title)
+            )?;
+        }
+        
+        file.flush()?;
+        debug!("Wrote {} timeline entries to {}", entries.len(), file_path.display());
+        Ok(file_path)
+    }
+    
     pub fn set_batch_size(&mut self, batch_size: usize) {
         self.batch_size = batch_size;
     }
@@ -135,7 +305,7 @@ impl CsvWriter {
             }
             
             let fields: Vec<&str> = line.split(',').collect();
-            if fields.len() != 10 {
+            if ![10, 11, 14, 17, 18].contains(&fields.len()) {
                 continue; // Skip malformed lines
             }
             
@@ -150,6 +320,27 @@ impl CsvWriter {
                 win_title: unescape_csv_field(fields[7]),
                 width: fields[8].parse().unwrap_or(0),
                 height: fields[9].parse().unwrap_or(0),
+                // Files written before deduplication have no duplicate_of column
+                duplicate_of: fields.get(10).map(|f| unescape_csv_field(f)).filter(|f| !f.is_empty()),
+                // Empty until the frame's OCR is known, absent in older files
+                ocr_density: match (fields.get(11), fields.get(12), fields.get(13)) {
+                    (Some(boxes), Some(coverage), Some(confidence)) if !boxes.is_empty() => Some(OcrDensity {
+                        box_count: boxes.parse().unwrap_or(0),
+                        text_coverage: coverage.parse().unwrap_or(0.0),
+                        mean_confidence: confidence.parse().unwrap_or(0.0),
+                    }),
+                    _ => None,
+                },
+                // Empty for frames without features, absent in older files
+                visual: match (fields.get(14), fields.get(15), fields.get(16)) {
+                    (Some(colors), Some(edges), Some(text)) if !edges.is_empty() => Some(VisualFeatures {
+                        dominant_colors: palette_from_text(colors),
+                        edge_density: edges.parse().unwrap_or(0.0),
+                        ui_text_density: text.parse().unwrap_or(0.0),
+                    }),
+                    _ => None,
+                },
+                screen_type: fields.get(17).and_then(|f| ScreenType::parse(f)),
             };
             
             metadata_records.push(metadata);
@@ -159,6 +350,49 @@ impl CsvWriter {
     }
 }
 
+/// Header and rows of a frame metadata CSV file
+/// Write the frame CSV header and a row per frame
+pub(crate) fn write_frame_rows<W: Write>(output: &mut W, metadata: &[FrameMetadata]) -> Result<()> {
+    writeln!(output, "ts_ns,monitor_id,segment_id,path,phash16,entropy,app_name,win_title,width,height,duplicate_of,ocr_box_count,ocr_text_coverage,ocr_mean_confidence,dominant_colors,edge_density,ui_text_density,screen_type")?;
+    
+    // Write data rows
+    for record in metadata {
+        writeln!(
+            output,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            record.ts_ns,
+            record.monitor_id,
+            escape_csv_field(&record.segment_id),
+            escape_csv_field(&record.path),
+            record.phash16,
+            record.entropy,
+            escape_csv_field(&record.app_name),
+            escape_csv_field(&record.win_title),
+            record.width,
+            record.height,
+            escape_csv_field(record.duplicate_of.as_deref().unwrap_or("")),
+            record.ocr_density.map(|d| d.box_count.to_string()).unwrap_or_default(),
+            record.ocr_density.map(|d| d.text_coverage.to_string()).unwrap_or_default(),
+            record.ocr_density.map(|d| d.mean_confidence.to_string()).unwrap_or_default(),
+            record.visual.as_ref().map(|v| palette_to_text(&v.dominant_colors)).unwrap_or_default(),
+            record.visual.as_ref().map(|v| v.edge_density.to_string()).unwrap_or_default(),
+            record.visual.as_ref().map(|v| v.ui_text_density.to_string()).unwrap_or_default(),
+            record.screen_type.map(|t| t.as_str()).unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Estimated bytes a buffered record holds
+fn buffered_size(record: &FrameMetadata) -> usize {
+    std::mem::size_of::<FrameMetadata>()
+        + record.segment_id.len()
+        + record.path.len()
+        + record.app_name.len()
+        + record.win_title.len()
+        + record.duplicate_of.as_ref().map_or(0, String::len)
+}
+
 fn escape_csv_field(field: &str) -> String {
     if field.contains(',') || field.contains('"') || field.contains('\n') {
         format!("\"{}\"", field.replace('"', "\"\""))
@@ -178,6 +412,7 @@ fn unescape_csv_field(field: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::visual_features::DominantColor;
     use tempfile::TempDir;
     use uuid::Uuid;
     
@@ -194,6 +429,10 @@ mod tests {
                 win_title: "Test Window".to_string(),
                 width: 1920,
                 height: 1080,
+                duplicate_of: None,
+                ocr_density: None,
+                visual: None,
+                screen_type: None,
             },
             FrameMetadata {
                 ts_ns: 2000000000,
@@ -206,6 +445,17 @@ mod tests {
                 win_title: "Another Window".to_string(),
                 width: 2560,
                 height: 1440,
+                duplicate_of: None,
+                ocr_density: None,
+                visual: Some(VisualFeatures {
+                    dominant_colors: vec![
+                        DominantColor { rgb: [30, 30, 30], share: 72.5 },
+                        DominantColor { rgb: [200, 200, 200], share: 27.5 },
+                    ],
+                    edge_density: 24.5,
+                    ui_text_density: 61.0,
+                }),
+                screen_type: Some(ScreenType::Terminal),
             },
         ]
     }
@@ -251,6 +501,8 @@ mod tests {
             assert_eq!(original.win_title, read.win_title);
             assert_eq!(original.width, read.width);
             assert_eq!(original.height, read.height);
+            assert_eq!(original.visual, read.visual);
+            assert_eq!(original.screen_type, read.screen_type);
         }
     }
     
@@ -278,6 +530,20 @@ mod tests {
         assert!(entries.len() >= 1); // At least one file should exist
     }
     
+    #[tokio::test]
+    async fn test_estimated_size_matches_written_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = CsvWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        
+        let test_metadata = create_test_metadata();
+        let estimated = CsvWriter::estimated_size(&test_metadata).unwrap();
+        writer.write_frame_metadata(&test_metadata).await.unwrap();
+        writer.flush_batch().await.unwrap();
+        
+        let entry = std::fs::read_dir(temp_dir.path()).unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.metadata().unwrap().len(), estimated);
+    }
+    
     #[test]
     fn test_csv_escaping() {
         assert_eq!(escape_csv_field("simple"), "simple");
@@ -1,9 +1,11 @@
+use crate::display_topology::DisplayTopology;
 use crate::error::{IndexerError, Result};
 use crate::event_detector::{DetectedEvent, EventType};
+use crate::system_probe::SystemProbe;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::{HashMap, VecDeque};
-use std::process::Command;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use tracing::{debug, info, warn, error};
 
 /// Cursor tracker for mouse movements and click events according to requirements 4.2 and 4.3
@@ -20,10 +22,19 @@ pub struct CursorTracker {
     last_position: Option<CursorPosition>,
     /// Movement trail analyzer
     trail_analyzer: MovementTrailAnalyzer,
+    /// Native scroll and gesture samples not yet turned into events
+    gesture_queue: VecDeque<GestureSample>,
+    /// Bytes of the gesture event log already read
+    gesture_log_offset: u64,
+    /// Display arrangement used to resolve screen_id and local coordinates
+    display_topology: Option<DisplayTopology>,
+    /// Runs the AppleScript probes with timeouts
+    probe: SystemProbe,
 }
 
 /// Configuration for cursor tracking behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CursorTrackingConfig {
     /// Enable cursor position tracking
     pub enable_position_tracking: bool,
@@ -39,6 +50,12 @@ pub struct CursorTrackingConfig {
     pub min_confidence: f32,
     /// Sampling interval for cursor position (milliseconds)
     pub sampling_interval_ms: u64,
+    /// Turn native scroll-wheel and trackpad gesture samples into events
+    pub enable_gesture_capture: bool,
+    /// JSONL log of gesture samples appended by the companion app's event monitor
+    pub gesture_log_path: Option<String>,
+    /// Samples further apart than this start a new gesture (milliseconds)
+    pub max_gesture_gap_ms: u64,
 }
 
 impl Default for CursorTrackingConfig {
@@ -51,6 +68,19 @@ impl Default for CursorTrackingConfig {
             max_trail_gap_ms: 1000,
             min_confidence: 0.8,
             sampling_interval_ms: 100,
+            enable_gesture_capture: true,
+            gesture_log_path: None,
+            max_gesture_gap_ms: 300,
+        }
+    }
+}
+
+impl CursorTrackingConfig {
+    /// Movement threshold scaled for a capture resolution `factor` times the reference
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            min_movement_distance: self.min_movement_distance * factor,
+            ..self.clone()
         }
     }
 }
@@ -103,6 +133,149 @@ pub enum KeyModifier {
     Function,
 }
 
+/// Native input gestures reported by the event monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureKind {
+    /// Scroll wheel or two-finger trackpad scroll
+    Scroll,
+    /// Pinch to zoom
+    Magnify,
+    Rotate,
+    Swipe,
+}
+
+/// Gesture and momentum phases, mirroring `NSEvent.Phase`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GesturePhase {
+    /// Not part of a phased gesture, e.g. a line-based scroll wheel
+    #[default]
+    None,
+    MayBegin,
+    Began,
+    Changed,
+    Stationary,
+    Ended,
+    Cancelled,
+}
+
+impl GesturePhase {
+    fn is_finished(&self) -> bool {
+        matches!(self, GesturePhase::Ended | GesturePhase::Cancelled)
+    }
+}
+
+/// One scroll or gesture event as captured from NSEvent/CGEvent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GestureSample {
+    pub kind: GestureKind,
+    pub timestamp: DateTime<Utc>,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub screen_id: Option<i32>,
+    /// `scrollingDeltaX`/`scrollingDeltaY`: points when `precise`, lines otherwise.
+    /// Positive `delta_y` moves content down, i.e. scrolls the view up.
+    #[serde(default)]
+    pub delta_x: f32,
+    #[serde(default)]
+    pub delta_y: f32,
+    /// `hasPreciseScrollingDeltas`: true for trackpads and Magic Mouse
+    #[serde(default)]
+    pub precise: bool,
+    #[serde(default)]
+    pub phase: GesturePhase,
+    #[serde(default)]
+    pub momentum_phase: GesturePhase,
+    /// Magnification delta of a pinch sample
+    #[serde(default)]
+    pub magnification: f32,
+    /// Rotation delta of a rotate sample, in degrees
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default)]
+    pub modifiers: Vec<KeyModifier>,
+}
+
+/// A complete gesture accumulated from its samples
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputGesture {
+    pub kind: GestureKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Pointer position at the end of the gesture
+    pub position: CursorPosition,
+    /// Horizontal view scroll; positive when scrolling right
+    pub scroll_dx: f32,
+    /// Vertical view scroll; positive when scrolling down
+    pub scroll_dy: f32,
+    pub precise: bool,
+    /// Part of the scroll was inertial (momentum after the fingers lifted)
+    pub momentum: bool,
+    /// Phase of the last sample with touch contact
+    pub final_phase: GesturePhase,
+    pub magnification: f32,
+    pub rotation: f32,
+    pub sample_count: usize,
+    pub modifiers: Vec<KeyModifier>,
+}
+
+impl InputGesture {
+    fn from_samples(samples: &[GestureSample]) -> Option<Self> {
+        let first = samples.first()?;
+        let last = samples.last()?;
+
+        Some(Self {
+            kind: first.kind,
+            start: first.timestamp,
+            end: last.timestamp,
+            position: CursorPosition {
+                x: last.x,
+                y: last.y,
+                timestamp: last.timestamp,
+                screen_id: last.screen_id,
+            },
+            // NSEvent deltas describe content motion, the opposite of the view scroll
+            scroll_dx: -samples.iter().map(|s| s.delta_x).sum::<f32>(),
+            scroll_dy: -samples.iter().map(|s| s.delta_y).sum::<f32>(),
+            precise: samples.iter().any(|s| s.precise),
+            momentum: samples.iter().any(|s| s.momentum_phase != GesturePhase::None),
+            final_phase: samples
+                .iter()
+                .rev()
+                .map(|s| s.phase)
+                .find(|p| *p != GesturePhase::None)
+                .unwrap_or_default(),
+            magnification: samples.iter().map(|s| s.magnification).sum(),
+            rotation: samples.iter().map(|s| s.rotation).sum(),
+            sample_count: samples.len(),
+            modifiers: first.modifiers.clone(),
+        })
+    }
+
+    pub fn duration_ms(&self) -> i64 {
+        self.end.signed_duration_since(self.start).num_milliseconds()
+    }
+
+    /// Dominant direction of a scroll or swipe
+    pub fn direction(&self) -> &'static str {
+        match self.kind {
+            GestureKind::Magnify => if self.magnification >= 0.0 { "in" } else { "out" },
+            GestureKind::Rotate => if self.rotation >= 0.0 { "counterclockwise" } else { "clockwise" },
+            GestureKind::Scroll | GestureKind::Swipe => {
+                if self.scroll_dx.abs() > self.scroll_dy.abs() {
+                    if self.scroll_dx > 0.0 { "right" } else { "left" }
+                } else if self.scroll_dy >= 0.0 {
+                    "down"
+                } else {
+                    "up"
+                }
+            }
+        }
+    }
+}
+
 /// Movement trail analysis results
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MovementTrail {
@@ -362,6 +535,40 @@ impl CursorTracker {
             max_history_size: 1000,
             last_position: None,
             trail_analyzer: MovementTrailAnalyzer::new(),
+            gesture_queue: VecDeque::new(),
+            gesture_log_offset: 0,
+            display_topology: None,
+            probe: SystemProbe::default(),
+        }
+    }
+    
+    /// Resolve cursor positions against this display arrangement
+    pub fn set_display_topology(&mut self, topology: DisplayTopology) {
+        self.display_topology = Some(topology);
+    }
+    
+    /// Share a probe, and its record of unresponsive apps, with other trackers
+    pub fn set_system_probe(&mut self, probe: SystemProbe) {
+        self.probe = probe;
+    }
+    
+    /// Queue a native scroll or gesture sample from an in-process event monitor
+    pub fn record_gesture_sample(&mut self, mut sample: GestureSample) {
+        if sample.screen_id.is_none() {
+            sample.screen_id = self.display_topology
+                .as_ref()
+                .and_then(|t| t.display_at(sample.x, sample.y))
+                .map(|d| d.id);
+        }
+        
+        // Samples from the log and the in-process monitor may interleave; keep them time-ordered
+        let index = self.gesture_queue
+            .iter()
+            .rposition(|s| s.timestamp <= sample.timestamp)
+            .map_or(0, |i| i + 1);
+        self.gesture_queue.insert(index, sample);
+        while self.gesture_queue.len() > self.max_history_size {
+            self.gesture_queue.pop_front();
         }
     }
     
@@ -392,6 +599,14 @@ impl CursorTracker {
             }
         }
         
+        // Turn native scroll and trackpad gestures into events
+        if self.config.enable_gesture_capture {
+            match self.capture_gesture_events(frame_id, timestamp) {
+                Ok(gesture_events) => events.extend(gesture_events),
+                Err(e) => warn!("Gesture capture failed: {}", e),
+            }
+        }
+        
         info!("Detected {} cursor events for frame {}", events.len(), frame_id);
         Ok(events)
     }
@@ -516,6 +731,110 @@ impl CursorTracker {
         Ok(events)
     }
     
+    /// Build events from gestures completed by `timestamp`; gestures still in progress stay queued
+    fn capture_gesture_events(&mut self, frame_id: &str, timestamp: DateTime<Utc>) -> Result<Vec<DetectedEvent>> {
+        self.read_gesture_log()?;
+        
+        let max_gap = chrono::Duration::milliseconds(self.config.max_gesture_gap_ms as i64);
+        let ready: Vec<GestureSample> = {
+            let count = self.gesture_queue.iter().take_while(|s| s.timestamp <= timestamp).count();
+            self.gesture_queue.drain(..count).collect()
+        };
+        
+        let mut groups = group_gesture_samples(ready, max_gap);
+        if let Some(open) = groups.last() {
+            let last = open.last().expect("gesture groups are never empty");
+            let finished = last.momentum_phase.is_finished()
+                || (last.phase.is_finished() && last.kind != GestureKind::Scroll);
+            // A scroll whose fingers lifted may still continue with momentum
+            if !finished && timestamp.signed_duration_since(last.timestamp) <= max_gap {
+                let open = groups.pop().unwrap_or_default();
+                for sample in open.into_iter().rev() {
+                    self.gesture_queue.push_front(sample);
+                }
+            }
+        }
+        
+        let mut events = Vec::new();
+        for gesture in groups.iter().filter_map(|samples| InputGesture::from_samples(samples)) {
+            if gesture.kind == GestureKind::Scroll {
+                self.click_history.push_back(ClickEvent {
+                    position: gesture.position.clone(),
+                    // Scroll wheels report as the middle button
+                    button: MouseButton::Middle,
+                    click_type: ClickType::Scroll,
+                    click_count: 1,
+                    modifiers: gesture.modifiers.clone(),
+                    confidence: 1.0,
+                });
+                while self.click_history.len() > self.max_history_size / 10 {
+                    self.click_history.pop_front();
+                }
+            }
+            
+            debug!("Captured {:?} gesture {} over {} samples", gesture.kind, gesture.direction(), gesture.sample_count);
+            events.push(DetectedEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                timestamp: gesture.end,
+                event_type: EventType::Navigation,
+                target: match gesture.kind {
+                    GestureKind::Scroll => "scroll",
+                    GestureKind::Magnify => "zoom",
+                    GestureKind::Rotate => "rotate",
+                    GestureKind::Swipe => "swipe",
+                }
+                .to_string(),
+                value_from: None,
+                value_to: Some(gesture.direction().to_string()),
+                // Native input is observed directly rather than inferred
+                confidence: 1.0,
+                evidence_frames: vec![frame_id.to_string()],
+                metadata: self.create_gesture_metadata(&gesture),
+            });
+        }
+        
+        Ok(events)
+    }
+    
+    /// Read samples appended to the gesture log since the last call
+    fn read_gesture_log(&mut self) -> Result<()> {
+        let Some(path) = self.config.gesture_log_path.as_deref() else {
+            return Ok(());
+        };
+        let mut file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(IndexerError::cursor(format!("Failed to open gesture log {}", path)).caused_by(e)),
+        };
+        
+        // The companion app truncates the log when it rotates it
+        let length = file.metadata()?.len();
+        if length < self.gesture_log_offset {
+            self.gesture_log_offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.gesture_log_offset))?;
+        
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            // Leave a partially written last line for the next read
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            self.gesture_log_offset += read as u64;
+            
+            match serde_json::from_str::<GestureSample>(line.trim()) {
+                Ok(sample) => self.record_gesture_sample(sample),
+                Err(e) if !line.trim().is_empty() => warn!("Skipping malformed gesture sample: {}", e),
+                Err(_) => {}
+            }
+        }
+        
+        Ok(())
+    }
+    
     /// Get current cursor position using macOS APIs
     async fn get_current_cursor_position(&self) -> Result<CursorPosition> {
         let script = r#"
@@ -527,36 +846,28 @@ impl CursorTracker {
             end tell
         "#;
         
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| IndexerError::CursorTracking(format!("Failed to get cursor position: {}", e)))?;
-        
-        if !output.status.success() {
-            return Err(IndexerError::CursorTracking(
-                format!("AppleScript failed: {}", String::from_utf8_lossy(&output.stderr))
-            ));
-        }
-        
-        let result = String::from_utf8_lossy(&output.stdout);
-        let coords: Vec<&str> = result.trim().split(',').collect();
+        let result = self.probe.osascript("System Events", script).await?;
+        let coords: Vec<&str> = result.split(',').collect();
         
         if coords.len() != 2 {
-            return Err(IndexerError::CursorTracking("Invalid cursor position response".to_string()));
+            return Err(IndexerError::cursor("Invalid cursor position response"));
         }
         
         let x = coords[0].parse::<f32>()
-            .map_err(|_| IndexerError::CursorTracking("Invalid X coordinate".to_string()))?;
+            .map_err(|_| IndexerError::cursor("Invalid X coordinate"))?;
         let y = coords[1].parse::<f32>()
-            .map_err(|_| IndexerError::CursorTracking("Invalid Y coordinate".to_string()))?;
+            .map_err(|_| IndexerError::cursor("Invalid Y coordinate"))?;
         
-        Ok(CursorPosition {
+        let mut position = CursorPosition {
             x,
             y,
             timestamp: Utc::now(),
-            screen_id: None, // Could be enhanced to detect which screen
-        })
+            screen_id: None,
+        };
+        if let Some(topology) = &self.display_topology {
+            topology.assign_screen(&mut position);
+        }
+        Ok(position)
     }
     
     /// Detect click patterns from position history
@@ -627,6 +938,11 @@ impl CursorTracker {
         if let Some(screen_id) = current.screen_id {
             metadata.insert("screen_id".to_string(), screen_id.to_string());
         }
+        if let Some(local) = self.display_topology.as_ref().and_then(|t| t.to_local(current.x, current.y)) {
+            metadata.insert("local_x".to_string(), local.x.to_string());
+            metadata.insert("local_y".to_string(), local.y.to_string());
+            metadata.insert("scale_factor".to_string(), local.scale_factor.to_string());
+        }
         
         metadata
     }
@@ -641,6 +957,10 @@ impl CursorTracker {
         metadata.insert("x".to_string(), click.position.x.to_string());
         metadata.insert("y".to_string(), click.position.y.to_string());
         
+        if let Some(screen_id) = click.position.screen_id {
+            metadata.insert("screen_id".to_string(), screen_id.to_string());
+        }
+        
         if !click.modifiers.is_empty() {
             let modifiers_str = click.modifiers.iter()
                 .map(|m| format!("{:?}", m))
@@ -652,6 +972,53 @@ impl CursorTracker {
         metadata
     }
     
+    /// Create metadata for scroll and gesture events
+    fn create_gesture_metadata(&self, gesture: &InputGesture) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        metadata.insert("event_type".to_string(), "input_gesture".to_string());
+        metadata.insert("gesture_kind".to_string(), format!("{:?}", gesture.kind).to_lowercase());
+        metadata.insert("input_source".to_string(), if gesture.precise { "trackpad" } else { "wheel" }.to_string());
+        metadata.insert("gesture_phase".to_string(), format!("{:?}", gesture.final_phase).to_lowercase());
+        metadata.insert("momentum".to_string(), gesture.momentum.to_string());
+        metadata.insert("sample_count".to_string(), gesture.sample_count.to_string());
+        metadata.insert("duration_ms".to_string(), gesture.duration_ms().to_string());
+        metadata.insert("x".to_string(), gesture.position.x.to_string());
+        metadata.insert("y".to_string(), gesture.position.y.to_string());
+        
+        match gesture.kind {
+            GestureKind::Scroll | GestureKind::Swipe => {
+                if gesture.kind == GestureKind::Scroll {
+                    // Same keys as keyframe scroll detection so both correlate alike
+                    metadata.insert("navigation_type".to_string(), "scroll".to_string());
+                }
+                metadata.insert("scroll_direction".to_string(), gesture.direction().to_string());
+                metadata.insert("scroll_dx".to_string(), format!("{:.1}", gesture.scroll_dx));
+                metadata.insert("scroll_dy".to_string(), format!("{:.1}", gesture.scroll_dy));
+                metadata.insert("scroll_unit".to_string(), if gesture.precise { "points" } else { "lines" }.to_string());
+            }
+            GestureKind::Magnify => {
+                metadata.insert("magnification".to_string(), format!("{:.3}", gesture.magnification));
+                metadata.insert("zoom_direction".to_string(), gesture.direction().to_string());
+            }
+            GestureKind::Rotate => {
+                metadata.insert("rotation_degrees".to_string(), format!("{:.1}", gesture.rotation));
+            }
+        }
+        
+        if let Some(screen_id) = gesture.position.screen_id {
+            metadata.insert("screen_id".to_string(), screen_id.to_string());
+        }
+        if !gesture.modifiers.is_empty() {
+            let modifiers_str = gesture.modifiers.iter()
+                .map(|m| format!("{:?}", m))
+                .collect::<Vec<_>>()
+                .join(",");
+            metadata.insert("modifiers".to_string(), modifiers_str);
+        }
+        
+        metadata
+    }
+    
     /// Create metadata for movement trail events
     fn create_trail_metadata(&self, trail: &MovementTrail) -> HashMap<String, String> {
         let mut metadata = HashMap::new();
@@ -688,6 +1055,7 @@ impl CursorTracker {
     pub fn clear_history(&mut self) {
         self.position_history.clear();
         self.click_history.clear();
+        self.gesture_queue.clear();
         self.last_position = None;
     }
     
@@ -697,6 +1065,31 @@ impl CursorTracker {
     }
 }
 
+/// Split time-ordered samples into gestures.
+///
+/// A gesture ends when the kind changes, samples are more than `max_gap` apart,
+/// momentum finishes, or a new touch begins after the fingers lifted.
+fn group_gesture_samples(samples: Vec<GestureSample>, max_gap: chrono::Duration) -> Vec<Vec<GestureSample>> {
+    let mut groups: Vec<Vec<GestureSample>> = Vec::new();
+    
+    for sample in samples {
+        let continues = groups.last().and_then(|g| g.last()).is_some_and(|previous| {
+            previous.kind == sample.kind
+                && sample.timestamp.signed_duration_since(previous.timestamp) <= max_gap
+                && !previous.momentum_phase.is_finished()
+                && !(previous.phase.is_finished() && sample.momentum_phase == GesturePhase::None)
+                && !(sample.phase == GesturePhase::Began && previous.phase != GesturePhase::MayBegin)
+        });
+        
+        match groups.last_mut() {
+            Some(group) if continues => group.push(sample),
+            _ => groups.push(vec![sample]),
+        }
+    }
+    
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -816,6 +1209,7 @@ mod tests {
             max_trail_gap_ms: 2000,
             min_confidence: 0.9,
             sampling_interval_ms: 200,
+            ..CursorTrackingConfig::default()
         };
         
         tracker.update_config(new_config.clone());
@@ -823,4 +1217,69 @@ mod tests {
         assert_eq!(tracker.config.min_movement_distance, 10.0);
         assert_eq!(tracker.config.sampling_interval_ms, 200);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_native_gesture_capture() {
+        let start = Utc::now();
+        let sample = |ms: i64, kind: GestureKind, delta_y: f32, phase: GesturePhase, momentum_phase: GesturePhase| GestureSample {
+            kind,
+            timestamp: start + chrono::Duration::milliseconds(ms),
+            x: 400.0,
+            y: 300.0,
+            screen_id: Some(1),
+            delta_x: 0.0,
+            delta_y,
+            precise: true,
+            phase,
+            momentum_phase,
+            magnification: if kind == GestureKind::Magnify { 0.1 } else { 0.0 },
+            rotation: 0.0,
+            modifiers: Vec::new(),
+        };
+        
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("gestures.jsonl");
+        let mut log = String::new();
+        for s in [
+            sample(0, GestureKind::Scroll, -10.0, GesturePhase::Began, GesturePhase::None),
+            sample(20, GestureKind::Scroll, -20.0, GesturePhase::Changed, GesturePhase::None),
+            sample(40, GestureKind::Scroll, 0.0, GesturePhase::Ended, GesturePhase::None),
+            sample(60, GestureKind::Scroll, -15.0, GesturePhase::None, GesturePhase::Began),
+            sample(80, GestureKind::Scroll, -5.0, GesturePhase::None, GesturePhase::Ended),
+            sample(400, GestureKind::Magnify, 0.0, GesturePhase::Began, GesturePhase::None),
+            sample(420, GestureKind::Magnify, 0.0, GesturePhase::Ended, GesturePhase::None),
+        ] {
+            log.push_str(&serde_json::to_string(&s).unwrap());
+            log.push('\n');
+        }
+        // A line still being written is left for the next read
+        log.push_str("{\"kind\":\"scroll\"");
+        std::fs::write(&log_path, log).unwrap();
+        
+        let mut tracker = CursorTracker::with_config(CursorTrackingConfig {
+            gesture_log_path: Some(log_path.to_str().unwrap().to_string()),
+            ..CursorTrackingConfig::default()
+        });
+        let mut wheel = sample(900, GestureKind::Scroll, 3.0, GesturePhase::None, GesturePhase::None);
+        wheel.precise = false;
+        tracker.record_gesture_sample(wheel);
+        
+        let events = tracker.capture_gesture_events("frame_1", start + chrono::Duration::milliseconds(1000)).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].target, "scroll");
+        assert_eq!(events[0].metadata.get("scroll_direction").map(String::as_str), Some("down"));
+        assert_eq!(events[0].metadata.get("scroll_dy").map(String::as_str), Some("50.0"));
+        assert_eq!(events[0].metadata.get("momentum").map(String::as_str), Some("true"));
+        assert_eq!(events[0].metadata.get("input_source").map(String::as_str), Some("trackpad"));
+        assert_eq!(events[1].target, "zoom");
+        assert_eq!(events[1].metadata.get("zoom_direction").map(String::as_str), Some("in"));
+        assert_eq!(tracker.get_click_history().back().unwrap().click_type, ClickType::Scroll);
+        
+        // The wheel tick is still within the gesture gap, so it is held until the next frame
+        assert_eq!(tracker.gesture_queue.len(), 1);
+        let events = tracker.capture_gesture_events("frame_2", start + chrono::Duration::milliseconds(1500)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].value_to.as_deref(), Some("up"));
+        assert_eq!(events[0].metadata.get("scroll_unit").map(String::as_str), Some("lines"));
+    }
+}
@@ -0,0 +1,319 @@
+use crate::error::{IndexerError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// File of the dead-letter records inside the dead-letter directory
+const DEAD_LETTER_FILE: &str = "failed.jsonl";
+
+/// What a failed item is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedItemKind {
+    /// Video segment file
+    Segment,
+    /// Video segment picked up by the watcher, retried the way the watcher processes it
+    WatchedSegment,
+    /// Directory of keyframes captured live
+    CapturedSegment,
+    /// Single keyframe or screenshot
+    Frame,
+}
+
+/// Pipeline stage an item failed in, taken from the error it failed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureStage {
+    Extract,
+    SceneDetection,
+    Metadata,
+    Ocr,
+    Events,
+    Output,
+    /// Errors that don't name their stage, such as I/O
+    Processing,
+}
+
+impl FailureStage {
+    pub fn of(error: &anyhow::Error) -> Self {
+        let Some(error) = error.chain().find_map(|e| e.downcast_ref::<IndexerError>()) else {
+            return Self::Processing;
+        };
+        match error.root() {
+            #[cfg(feature = "ffmpeg")]
+            IndexerError::FFmpeg(_) => Self::Extract,
+            IndexerError::CorruptedVideo(_) | IndexerError::UnsupportedFormat(_) | IndexerError::Extraction { .. } => {
+                Self::Extract
+            }
+            IndexerError::Image(_) | IndexerError::SceneDetection { .. } => Self::SceneDetection,
+            IndexerError::Metadata(_) | IndexerError::Probe(_) => Self::Metadata,
+            IndexerError::Ocr { .. } | IndexerError::InvalidOcr(_) => Self::Ocr,
+            IndexerError::EventStore { .. }
+            | IndexerError::EventCorrelation(_)
+            | IndexerError::Signing(_)
+            | IndexerError::Navigation { .. }
+            | IndexerError::Cursor { .. } => Self::Events,
+            IndexerError::Arrow(_)
+            | IndexerError::Parquet(_)
+            | IndexerError::DataFusion(_)
+            | IndexerError::Sqlite(_)
+            | IndexerError::DuckDb(_)
+            | IndexerError::Serde(_)
+            | IndexerError::Encryption { .. } => Self::Output,
+            _ => Self::Processing,
+        }
+    }
+}
+
+/// Output an item writes, recorded once written so a retry after a later
+/// failure doesn't write it twice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStep {
+    /// Frame metadata rows
+    FrameRows,
+    RedactedIntervals,
+    /// Frames written to the sink at this position in `output_sinks`
+    SinkFrames(usize),
+    SinkOcr(usize),
+    Embeddings,
+    Timeline,
+    CalendarTags,
+    MeetingIntervals,
+    /// Triggers, notifications and the snapshot of the item's events
+    EventsDispatched,
+    /// Workflow chains and error recoveries completed by the item's events
+    Correlations,
+    SinkEvents(usize),
+    Forms,
+}
+
+/// A segment or frame whose processing failed, kept for a later retry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub item: PathBuf,
+    pub kind: FailedItemKind,
+    pub stage: FailureStage,
+    /// Error of the latest failure
+    pub error: String,
+    /// `IndexerError::code` of the latest failure, or `internal`
+    pub code: String,
+    /// Failed retries after the first failure
+    pub retry_count: u32,
+    pub first_failed_at: DateTime<Utc>,
+    pub last_failed_at: DateTime<Utc>,
+    /// Outputs written before the latest failure, skipped by a retry
+    #[serde(default)]
+    pub committed: BTreeSet<OutputStep>,
+}
+
+/// Operation recorded in the dead-letter file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum DeadLetterRecord {
+    Failed(DeadLetter),
+    Resolved { item: PathBuf, recorded_at: DateTime<Utc> },
+}
+
+/// Durable list of failed segments and frames.
+///
+/// Like the segment ledger it is an append-only JSON lines file, replayed on
+/// open and compacted to the open entries on shutdown. A failure of an item
+/// already listed counts as a failed retry.
+pub struct DeadLetterStore {
+    path: PathBuf,
+    entries: HashMap<PathBuf, DeadLetter>,
+}
+
+impl DeadLetterStore {
+    /// Open (or create) the store in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        let path = dir.as_ref().join(DEAD_LETTER_FILE);
+
+        let mut entries = HashMap::new();
+        let mut needs_repair = false;
+
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for (line_no, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: DeadLetterRecord = match serde_json::from_str(&line) {
+                    Ok(record) => record,
+                    Err(e) => {
+                        warn!("Skipping malformed dead-letter record at line {}: {}", line_no + 1, e);
+                        needs_repair = true;
+                        continue;
+                    }
+                };
+
+                match record {
+                    DeadLetterRecord::Failed(entry) => {
+                        entries.insert(entry.item.clone(), entry);
+                    }
+                    DeadLetterRecord::Resolved { item, .. } => {
+                        entries.remove(&item);
+                    }
+                }
+            }
+        }
+
+        if !entries.is_empty() {
+            info!("{} failed items in {}; run `indexer retry-failed` to reprocess them", entries.len(), path.display());
+        }
+
+        let mut store = Self { path, entries };
+        if needs_repair {
+            store.compact()?;
+        }
+        Ok(store)
+    }
+
+    /// Record that `item` failed with `error` after writing the `committed` outputs
+    pub fn record_failure(
+        &mut self,
+        item: &Path,
+        kind: FailedItemKind,
+        error: &anyhow::Error,
+        committed: BTreeSet<OutputStep>,
+    ) -> Result<&DeadLetter> {
+        let now = Utc::now();
+        let code = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<IndexerError>())
+            .map_or("internal", IndexerError::code);
+        let entry = DeadLetter {
+            item: item.to_path_buf(),
+            kind,
+            stage: FailureStage::of(error),
+            error: format!("{:#}", error),
+            code: code.to_string(),
+            retry_count: self.entries.get(item).map_or(0, |previous| previous.retry_count + 1),
+            first_failed_at: self.entries.get(item).map_or(now, |previous| previous.first_failed_at),
+            last_failed_at: now,
+            committed,
+        };
+        self.append(&DeadLetterRecord::Failed(entry.clone()))?;
+        debug!("Dead-lettered {} at {:?} (retry {})", item.display(), entry.stage, entry.retry_count);
+        self.entries.insert(item.to_path_buf(), entry);
+        Ok(&self.entries[item])
+    }
+
+    /// Remove `item` once it has been processed
+    pub fn resolve(&mut self, item: &Path) -> Result<()> {
+        if self.entries.remove(item).is_none() {
+            return Ok(());
+        }
+        self.append(&DeadLetterRecord::Resolved { item: item.to_path_buf(), recorded_at: Utc::now() })
+    }
+
+    pub fn get(&self, item: &Path) -> Option<&DeadLetter> {
+        self.entries.get(item)
+    }
+
+    /// Failed items in the order they first failed
+    pub fn entries(&self) -> Vec<DeadLetter> {
+        let mut entries: Vec<DeadLetter> = self.entries.values().cloned().collect();
+        entries.sort_by(|a, b| a.first_failed_at.cmp(&b.first_failed_at).then_with(|| a.item.cmp(&b.item)));
+        entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Rewrite the file so it only contains the open entries
+    pub fn compact(&mut self) -> Result<()> {
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        {
+            let mut file = File::create(&temp_path)?;
+            for entry in self.entries() {
+                writeln!(file, "{}", serde_json::to_string(&DeadLetterRecord::Failed(entry))?)?;
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn append(&mut self, record: &DeadLetterRecord) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Outcome of `retry-failed`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetrySummary {
+    pub retried: usize,
+    pub recovered: usize,
+    pub still_failing: usize,
+    /// Items left alone after reaching the retry limit
+    pub skipped: usize,
+    /// Items whose files are gone; they are dropped from the store
+    pub missing: usize,
+}
+
+impl RetrySummary {
+    pub fn to_text(&self) -> String {
+        format!(
+            "Retried {} failed items: {} recovered, {} still failing, {} over the retry limit, {} missing\n",
+            self.retried, self.recovered, self.still_failing, self.skipped, self.missing
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_failures_and_retries_survive_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let segment = Path::new("/videos/a.mp4");
+        let frame = Path::new("/frames/b.png");
+        let corrupted = anyhow::Error::new(IndexerError::CorruptedVideo("truncated".to_string()).for_segment("a.mp4"));
+
+        {
+            let mut store = DeadLetterStore::open(temp_dir.path()).unwrap();
+            let entry = store.record_failure(segment, FailedItemKind::Segment, &corrupted, BTreeSet::new()).unwrap();
+            assert_eq!((entry.stage, entry.code.as_str(), entry.retry_count), (FailureStage::Extract, "corrupted_video", 0));
+            store.record_failure(segment, FailedItemKind::Segment, &corrupted, BTreeSet::new()).unwrap();
+            let written = BTreeSet::from([OutputStep::FrameRows, OutputStep::SinkFrames(0)]);
+            store.record_failure(frame, FailedItemKind::Frame, &anyhow::anyhow!("disk full"), written).unwrap();
+        }
+
+        let mut store = DeadLetterStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(segment).unwrap().retry_count, 1);
+        let unknown = store.get(frame).unwrap();
+        assert_eq!((unknown.stage, unknown.code.as_str()), (FailureStage::Processing, "internal"));
+        assert!(unknown.committed.contains(&OutputStep::SinkFrames(0)));
+        assert!(!unknown.committed.contains(&OutputStep::SinkOcr(0)));
+
+        store.resolve(segment).unwrap();
+        store.compact().unwrap();
+        let store = DeadLetterStore::open(temp_dir.path()).unwrap();
+        assert!(store.get(segment).is_none());
+        assert_eq!(store.len(), 1);
+    }
+}
@@ -331,6 +331,7 @@ impl DeltaAnalyzer {
             .iter()
             .map(|change| FieldChangeInfo {
                 field_id: change.field_id.clone(),
+                field_label: change.field_label.clone(),
                 value_from: change.value_from.clone(),
                 value_to: change.value_to.clone(),
                 timestamp: change.timestamp,
@@ -362,6 +363,14 @@ impl DeltaAnalyzer {
     
     /// Finalize the analyzer and close all resources
     pub async fn finalize(&mut self) -> Result<()> {
+        // Events still collecting repeats are stored before the writer closes
+        let held_events: Vec<DetectedEvent> = self.event_detector.finish()
+            .into_iter()
+            .filter(|e| e.confidence >= self.config.min_event_confidence)
+            .collect();
+        if !held_events.is_empty() {
+            self.event_writer.write_events(&held_events).await?;
+        }
         self.event_writer.finalize().await?;
         self.event_detector.clear_cache();
         info!("DeltaAnalyzer finalized");
@@ -382,6 +391,7 @@ struct TemporalContext {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldChangeInfo {
     pub field_id: String,
+    pub field_label: Option<String>,
     pub value_from: String,
     pub value_to: String,
     pub timestamp: DateTime<Utc>,
@@ -468,6 +478,7 @@ mod tests {
         // Check field changes
         let field_changes = analyzer.get_field_changes();
         assert!(!field_changes.is_empty());
+        assert_eq!(field_changes[0].field_label.as_deref(), Some("Username"));
     }
     
     #[tokio::test]
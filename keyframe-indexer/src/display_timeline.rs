@@ -0,0 +1,182 @@
+use crate::error::{IndexerError, Result};
+use crate::metadata_collector::FrameMetadata;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Merging of per-display streams into one timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayTimelineConfig {
+    pub enabled: bool,
+    /// A display this far behind the newest frame of any display is treated as
+    /// idle and not waited for; its late frames may then appear out of order
+    pub max_lag_secs: u64,
+}
+
+impl Default for DisplayTimelineConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_lag_secs: 120 }
+    }
+}
+
+impl DisplayTimelineConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_lag_secs == 0 {
+            return Err(IndexerError::Config("display_timeline max_lag_secs must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// One keyframe on the cross-display timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub timestamp: DateTime<Utc>,
+    pub monitor_id: i32,
+    pub segment_id: String,
+    pub path: String,
+    pub app_name: String,
+    pub win_title: String,
+}
+
+impl TimelineEntry {
+    pub fn from_frame(frame: &FrameMetadata, segment_start: DateTime<Utc>) -> Self {
+        Self {
+            timestamp: segment_start + chrono::Duration::nanoseconds(frame.ts_ns),
+            monitor_id: frame.monitor_id,
+            segment_id: frame.segment_id.clone(),
+            path: frame.path.clone(),
+            app_name: frame.app_name.clone(),
+            win_title: frame.win_title.clone(),
+        }
+    }
+}
+
+/// Merges the keyframes of each display's segment stream into one timeline
+/// ordered by wall-clock time.
+///
+/// Segments of different displays finish in any order, so entries are held
+/// back until every display still delivering has moved past them.
+pub struct DisplayTimeline {
+    max_lag: chrono::Duration,
+    pending: Vec<TimelineEntry>,
+    /// Time of the newest frame seen from each display
+    latest: HashMap<i32, DateTime<Utc>>,
+}
+
+impl DisplayTimeline {
+    pub fn new(config: &DisplayTimelineConfig) -> Self {
+        Self {
+            max_lag: chrono::Duration::seconds(config.max_lag_secs as i64),
+            pending: Vec::new(),
+            latest: HashMap::new(),
+        }
+    }
+
+    pub fn update_config(&mut self, config: &DisplayTimelineConfig) {
+        self.max_lag = chrono::Duration::seconds(config.max_lag_secs as i64);
+    }
+
+    /// Add a processed segment's keyframes; duplicates add nothing and are left out
+    pub fn push_segment(&mut self, frames: &[FrameMetadata], segment_start: DateTime<Utc>) {
+        for frame in frames {
+            let entry = TimelineEntry::from_frame(frame, segment_start);
+            let latest = self.latest.entry(entry.monitor_id).or_insert(entry.timestamp);
+            *latest = (*latest).max(entry.timestamp);
+            if !frame.is_duplicate() {
+                self.pending.push(entry);
+            }
+        }
+    }
+
+    /// Entries every active display has moved past, oldest first
+    pub fn drain_ready(&mut self) -> Vec<TimelineEntry> {
+        let Some(newest) = self.latest.values().max().copied() else {
+            return Vec::new();
+        };
+        let watermark = self
+            .latest
+            .values()
+            .filter(|latest| newest - **latest <= self.max_lag)
+            .min()
+            .copied()
+            .unwrap_or(newest);
+        self.sort_pending();
+        let ready = self.pending.partition_point(|entry| entry.timestamp <= watermark);
+        self.pending.drain(..ready).collect()
+    }
+
+    /// Every entry still held back, oldest first, e.g. when processing stops
+    pub fn finish(&mut self) -> Vec<TimelineEntry> {
+        self.sort_pending();
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Entries waiting for other displays to catch up
+    pub fn held_back(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Displays seen so far
+    pub fn displays(&self) -> usize {
+        self.latest.len()
+    }
+
+    fn sort_pending(&mut self) {
+        self.pending.sort_by(|a, b| (a.timestamp, a.monitor_id).cmp(&(b.timestamp, b.monitor_id)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(monitor_id: i32, offsets_secs: &[i64]) -> Vec<FrameMetadata> {
+        offsets_secs
+            .iter()
+            .map(|secs| FrameMetadata {
+                ts_ns: secs * 1_000_000_000,
+                monitor_id,
+                segment_id: format!("segment_monitor{}", monitor_id),
+                path: format!("/frames/{}_{}.png", monitor_id, secs),
+                phash16: 0,
+                entropy: 0.0,
+                app_name: "Terminal".to_string(),
+                win_title: String::new(),
+                width: 100,
+                height: 100,
+                duplicate_of: None,
+                ocr_density: None,
+                visual: None,
+                screen_type: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merges_displays_in_time_order() {
+        let start = DateTime::parse_from_rfc3339("2026-03-02T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut timeline = DisplayTimeline::new(&DisplayTimelineConfig { enabled: true, max_lag_secs: 120 });
+
+        timeline.push_segment(&frames(1, &[0, 20, 40]), start);
+        // Only one display seen so far, so everything is ready
+        assert_eq!(timeline.drain_ready().len(), 3);
+
+        timeline.push_segment(&frames(1, &[60, 80]), start);
+        // Display 2 is behind; display 1's later frames wait for it
+        timeline.push_segment(&frames(2, &[10, 30, 70]), start);
+        let merged: Vec<(i32, i64)> = timeline
+            .drain_ready()
+            .iter()
+            .map(|e| (e.monitor_id, (e.timestamp - start).num_seconds()))
+            .collect();
+        assert_eq!(merged, vec![(2, 10), (2, 30), (1, 60), (2, 70)]);
+        assert_eq!(timeline.displays(), 2);
+
+        // A display more than max_lag behind is not waited for
+        timeline.push_segment(&frames(1, &[400]), start);
+        assert_eq!(timeline.drain_ready().len(), 2);
+        assert!(timeline.finish().is_empty());
+    }
+}
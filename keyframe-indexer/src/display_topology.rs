@@ -0,0 +1,272 @@
+use crate::cursor_tracker::CursorPosition;
+use crate::error::Result;
+use crate::ocr_data::BoundingBox;
+use crate::system_probe::SystemProbe;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Lists `NSScreen.screens` as JSON; the first screen is the one with the menu bar
+const ENUMERATE_SCREENS_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+var screens = $.NSScreen.screens;
+var result = [];
+for (var i = 0; i < screens.count; i++) {
+    var screen = screens.objectAtIndex(i);
+    var frame = screen.frame;
+    result.push({
+        id: ObjC.unwrap(screen.deviceDescription.objectForKey('NSScreenNumber')),
+        name: ObjC.unwrap(screen.localizedName),
+        x: frame.origin.x,
+        y: frame.origin.y,
+        width: frame.size.width,
+        height: frame.size.height,
+        scale: screen.backingScaleFactor
+    });
+}
+JSON.stringify(result);
+"#;
+
+/// One attached display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    /// Display identifier, matching `screen_id` on cursor positions and events
+    pub id: i32,
+    pub name: String,
+    /// Frame in global points; origin at the top-left of the primary display, y down
+    pub bounds: BoundingBox,
+    /// Pixels per point (2.0 on Retina displays)
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+impl DisplayInfo {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.bounds.x
+            && y >= self.bounds.y
+            && x < self.bounds.x + self.bounds.width
+            && y < self.bounds.y + self.bounds.height
+    }
+}
+
+/// A global point resolved to the display it falls on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalPoint {
+    pub screen_id: i32,
+    /// Position relative to the display's top-left corner, in points
+    pub x: f32,
+    pub y: f32,
+    /// The same position in the display's pixels
+    pub pixel_x: f32,
+    pub pixel_y: f32,
+    pub scale_factor: f32,
+}
+
+/// Arrangement of the attached displays in the global coordinate space
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DisplayTopology {
+    pub displays: Vec<DisplayInfo>,
+}
+
+#[derive(Deserialize)]
+struct ScreenDescription {
+    id: i64,
+    #[serde(default)]
+    name: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    scale: f32,
+}
+
+impl DisplayTopology {
+    pub fn new(displays: Vec<DisplayInfo>) -> Self {
+        Self { displays }
+    }
+
+    /// A single display at the origin, for callers that only know the frame size
+    pub fn single(width: f32, height: f32) -> Self {
+        Self::new(vec![DisplayInfo {
+            id: 0,
+            name: "Display".to_string(),
+            bounds: BoundingBox::new(0.0, 0.0, width, height),
+            scale_factor: 1.0,
+            is_primary: true,
+        }])
+    }
+
+    /// Enumerate attached displays using macOS APIs
+    pub async fn enumerate(probe: &SystemProbe) -> Result<Self> {
+        let output = probe.jxa("AppKit", ENUMERATE_SCREENS_SCRIPT).await?;
+        let screens: Vec<ScreenDescription> = serde_json::from_str(&output)?;
+        let topology = Self::from_cocoa_frames(screens);
+        debug!("Enumerated {} displays", topology.displays.len());
+        Ok(topology)
+    }
+
+    /// Cocoa frames have their origin at the bottom-left of the primary display with y up
+    fn from_cocoa_frames(screens: Vec<ScreenDescription>) -> Self {
+        let primary_height = screens.first().map(|s| s.height).unwrap_or(0.0);
+        let displays = screens
+            .into_iter()
+            .enumerate()
+            .map(|(index, screen)| DisplayInfo {
+                id: screen.id as i32,
+                name: screen.name,
+                bounds: BoundingBox::new(
+                    screen.x,
+                    primary_height - (screen.y + screen.height),
+                    screen.width,
+                    screen.height,
+                ),
+                scale_factor: if screen.scale > 0.0 { screen.scale } else { 1.0 },
+                is_primary: index == 0,
+            })
+            .collect();
+        Self::new(displays)
+    }
+
+    pub fn is_multi_display(&self) -> bool {
+        self.displays.len() > 1
+    }
+
+    pub fn display(&self, screen_id: i32) -> Option<&DisplayInfo> {
+        self.displays.iter().find(|d| d.id == screen_id)
+    }
+
+    pub fn primary(&self) -> Option<&DisplayInfo> {
+        self.displays.iter().find(|d| d.is_primary).or_else(|| self.displays.first())
+    }
+
+    /// Display containing a global point
+    pub fn display_at(&self, x: f32, y: f32) -> Option<&DisplayInfo> {
+        self.displays.iter().find(|d| d.contains(x, y))
+    }
+
+    /// Bounding box of all displays in global points
+    pub fn desktop_bounds(&self) -> BoundingBox {
+        let Some(first) = self.displays.first() else {
+            return BoundingBox::new(0.0, 0.0, 0.0, 0.0);
+        };
+        let (mut min_x, mut min_y) = (first.bounds.x, first.bounds.y);
+        let (mut max_x, mut max_y) = (first.bounds.x + first.bounds.width, first.bounds.y + first.bounds.height);
+        for display in &self.displays[1..] {
+            min_x = min_x.min(display.bounds.x);
+            min_y = min_y.min(display.bounds.y);
+            max_x = max_x.max(display.bounds.x + display.bounds.width);
+            max_y = max_y.max(display.bounds.y + display.bounds.height);
+        }
+        BoundingBox::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Map a global point to display-local points and pixels
+    pub fn to_local(&self, x: f32, y: f32) -> Option<LocalPoint> {
+        let display = self.display_at(x, y)?;
+        let (local_x, local_y) = (x - display.bounds.x, y - display.bounds.y);
+        Some(LocalPoint {
+            screen_id: display.id,
+            x: local_x,
+            y: local_y,
+            pixel_x: local_x * display.scale_factor,
+            pixel_y: local_y * display.scale_factor,
+            scale_factor: display.scale_factor,
+        })
+    }
+
+    /// Map a display-local point back to global points
+    pub fn to_global(&self, screen_id: i32, x: f32, y: f32) -> Option<(f32, f32)> {
+        let display = self.display(screen_id)?;
+        Some((display.bounds.x + x, display.bounds.y + y))
+    }
+
+    /// Set `screen_id` on a cursor position reported in global points
+    pub fn assign_screen(&self, position: &mut CursorPosition) {
+        if let Some(display) = self.display_at(position.x, position.y) {
+            position.screen_id = Some(display.id);
+        }
+    }
+
+    /// Convert an OCR ROI in the pixels of a single display's capture to global points
+    pub fn roi_to_global(&self, screen_id: i32, roi: &BoundingBox) -> Option<BoundingBox> {
+        let display = self.display(screen_id)?;
+        let scale = display.scale_factor;
+        Some(BoundingBox::new(
+            display.bounds.x + roi.x / scale,
+            display.bounds.y + roi.y / scale,
+            roi.width / scale,
+            roi.height / scale,
+        ))
+    }
+
+    /// Locate a region of a frame that captures the whole desktop at `frame_width` x `frame_height`.
+    ///
+    /// Returns the display under the region's center and the region in that
+    /// display's local points.
+    pub fn locate_frame_region(&self, roi: &BoundingBox, frame_width: f32, frame_height: f32) -> Option<(&DisplayInfo, BoundingBox)> {
+        let desktop = self.desktop_bounds();
+        if frame_width <= 0.0 || frame_height <= 0.0 || desktop.width <= 0.0 || desktop.height <= 0.0 {
+            return None;
+        }
+
+        let (scale_x, scale_y) = (desktop.width / frame_width, desktop.height / frame_height);
+        let global = BoundingBox::new(
+            desktop.x + roi.x * scale_x,
+            desktop.y + roi.y * scale_y,
+            roi.width * scale_x,
+            roi.height * scale_y,
+        );
+        let display = self.display_at(global.x + global.width / 2.0, global.y + global.height / 2.0)?;
+
+        Some((
+            display,
+            BoundingBox::new(global.x - display.bounds.x, global.y - display.bounds.y, global.width, global.height),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1440x900 Retina laptop with a 1920x1080 monitor to its right, tops aligned
+    fn two_displays() -> DisplayTopology {
+        DisplayTopology::from_cocoa_frames(vec![
+            ScreenDescription { id: 1, name: "Built-in".to_string(), x: 0.0, y: 0.0, width: 1440.0, height: 900.0, scale: 2.0 },
+            ScreenDescription { id: 2, name: "External".to_string(), x: 1440.0, y: -180.0, width: 1920.0, height: 1080.0, scale: 1.0 },
+        ])
+    }
+
+    #[test]
+    fn test_global_to_local_mapping() {
+        let topology = two_displays();
+        assert!(topology.is_multi_display());
+        assert_eq!(topology.primary().unwrap().id, 1);
+        assert_eq!(topology.display(2).unwrap().bounds, BoundingBox::new(1440.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(topology.desktop_bounds(), BoundingBox::new(0.0, 0.0, 3360.0, 1080.0));
+
+        let local = topology.to_local(100.0, 50.0).unwrap();
+        assert_eq!((local.screen_id, local.pixel_x, local.pixel_y), (1, 200.0, 100.0));
+        let local = topology.to_local(1540.0, 1000.0).unwrap();
+        assert_eq!((local.screen_id, local.x, local.y), (2, 100.0, 1000.0));
+        assert_eq!(topology.to_global(2, 100.0, 1000.0), Some((1540.0, 1000.0)));
+        // Below the laptop's bottom edge there is no display
+        assert!(topology.to_local(100.0, 1000.0).is_none());
+
+        let mut position = CursorPosition { x: 2000.0, y: 400.0, timestamp: chrono::Utc::now(), screen_id: None };
+        topology.assign_screen(&mut position);
+        assert_eq!(position.screen_id, Some(2));
+
+        // A Retina capture ROI is halved into points
+        assert_eq!(
+            topology.roi_to_global(1, &BoundingBox::new(200.0, 100.0, 400.0, 60.0)),
+            Some(BoundingBox::new(100.0, 50.0, 200.0, 30.0))
+        );
+
+        // A dialog centered on the external display of a full-desktop capture
+        let (display, local) = topology
+            .locate_frame_region(&BoundingBox::new(2200.0, 440.0, 400.0, 200.0), 3360.0, 1080.0)
+            .unwrap();
+        assert_eq!(display.id, 2);
+        assert_eq!(local, BoundingBox::new(760.0, 440.0, 400.0, 200.0));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::enum_codes::{EnumCode, EnumCodeTable};
+use crate::error::{IndexerError, Result};
+use crate::error_modal_detector::{ErrorModalType, SeverityLevel};
+use crate::event_detector::EventType;
+use crate::layout_compat;
+use duckdb::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Output directories consolidated into a DuckDB database; missing ones are skipped
+#[derive(Debug, Clone, Default)]
+pub struct DuckDbSources {
+    /// Frame metadata, as `frames_*.parquet` or `frames_*.csv` files directly in the directory
+    pub frames_dir: Option<PathBuf>,
+    pub ocr_dir: Option<PathBuf>,
+    pub events_dir: Option<PathBuf>,
+}
+
+/// Rows loaded into each table of an exported database
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuckDbExportSummary {
+    pub path: PathBuf,
+    pub tables: Vec<(String, u64)>,
+    pub views: Vec<String>,
+}
+
+/// Load every stored output into a new DuckDB database at `path`.
+///
+/// Creates `frames`, `ocr` and `events` tables, with event, modal and severity
+/// codes stored as names, plus views over them:
+/// - `events_with_frames`: one row per event and evidence frame, with the frame's app, window and segment
+/// - `ocr_text`: OCR results with the app and window of their frame
+/// - `ocr_search(query)`: table macro returning `ocr_text` rows containing `query`, case-insensitively
+pub fn export_duckdb(sources: &DuckDbSources, path: &Path) -> Result<DuckDbExportSummary> {
+    if path.exists() {
+        return Err(IndexerError::Config(format!("{} already exists", path.display())));
+    }
+    let connection = Connection::open(path)?;
+    let mut summary = DuckDbExportSummary { path: path.to_path_buf(), ..DuckDbExportSummary::default() };
+
+    if let Some(dir) = &sources.frames_dir {
+        let parquet = frame_files(dir, "parquet")?;
+        let csv = frame_files(dir, "csv")?;
+        let mut selects = Vec::new();
+        if !parquet.is_empty() {
+            selects.push(format!("SELECT * FROM read_parquet({}, union_by_name = true)", file_list(&parquet)));
+        }
+        if !csv.is_empty() {
+            selects.push(format!("SELECT * FROM read_csv({}, header = true, union_by_name = true)", file_list(&csv)));
+        }
+        if !selects.is_empty() {
+            connection.execute_batch(&format!("CREATE TABLE frames AS {};", selects.join(" UNION ALL BY NAME ")))?;
+            summary.tables.push(("frames".to_string(), row_count(&connection, "frames")?));
+        }
+    }
+
+    if let Some(dir) = &sources.ocr_dir {
+        let files = layout_compat::parquet_files(dir)?;
+        if !files.is_empty() {
+            connection.execute_batch(&format!(
+                "CREATE TABLE ocr AS SELECT frame_id, text, language, confidence, roi.x AS x, roi.y AS y,
+                 roi.width AS width, roi.height AS height, processed_at, processor
+                 FROM read_parquet({}, union_by_name = true);",
+                file_list(&files)
+            ))?;
+            summary.tables.push(("ocr".to_string(), row_count(&connection, "ocr")?));
+        }
+    }
+
+    if let Some(dir) = &sources.events_dir {
+        let files = layout_compat::parquet_files(dir)?;
+        if !files.is_empty() {
+            // Codes written by other versions are named by the mapping stored with the files
+            let codes = EnumCodeTable::load(dir)?.unwrap_or_else(EnumCodeTable::current);
+            connection.execute_batch(&format!(
+                "CREATE TABLE events AS SELECT event_id, ts_ns AS timestamp, {} AS type, target, value_from, value_to,
+                 confidence, evidence_frames, metadata, {} AS modal_type, {} AS severity
+                 FROM read_parquet({}, union_by_name = true);",
+                codes.case_expression(EventType::DICTIONARY, "type_code"),
+                codes.case_expression(ErrorModalType::DICTIONARY, "modal_type_code"),
+                codes.case_expression(SeverityLevel::DICTIONARY, "severity_code"),
+                file_list(&files)
+            ))?;
+            summary.tables.push(("events".to_string(), row_count(&connection, "events")?));
+        }
+    }
+
+    let has = |table: &str| summary.tables.iter().any(|(name, _)| name == table);
+    let mut views = Vec::new();
+    if has("events") && has("frames") {
+        connection.execute_batch(
+            "CREATE VIEW events_with_frames AS
+             SELECT e.event_id, e.timestamp, e.type, e.target, e.value_from, e.value_to, e.confidence,
+                    e.modal_type, e.severity, e.frame_path, f.segment_id, f.app_name, f.win_title, f.ts_ns AS frame_ts_ns
+             FROM (SELECT *, UNNEST(evidence_frames) AS frame_path FROM events) e
+             LEFT JOIN frames f ON f.path = e.frame_path;",
+        )?;
+        views.push("events_with_frames".to_string());
+    }
+    if has("ocr") {
+        let view = if has("frames") {
+            "CREATE VIEW ocr_text AS
+             SELECT o.*, f.segment_id, f.app_name, f.win_title FROM ocr o LEFT JOIN frames f ON f.path = o.frame_id;"
+        } else {
+            "CREATE VIEW ocr_text AS SELECT * FROM ocr;"
+        };
+        connection.execute_batch(view)?;
+        connection.execute_batch(
+            "CREATE MACRO ocr_search(query) AS TABLE SELECT * FROM ocr_text WHERE text ILIKE '%' || query || '%';",
+        )?;
+        views.push("ocr_text".to_string());
+        views.push("ocr_search".to_string());
+    }
+    summary.views = views;
+
+    info!("Exported {} tables and {} views to {}", summary.tables.len(), summary.views.len(), path.display());
+    Ok(summary)
+}
+
+/// `frames_*` files with an extension directly in `dir`; the OCR, event and
+/// other tables kept under the output directory are not frames
+fn frame_files(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let is_frames = name.starts_with("frames_")
+                && !name.ends_with(".tmp.parquet")
+                && path.extension().and_then(|e| e.to_str()) == Some(extension);
+            if is_frames && path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// DuckDB list literal of file paths
+fn file_list(files: &[PathBuf]) -> String {
+    let quoted: Vec<String> = files
+        .iter()
+        .map(|file| format!("'{}'", file.to_string_lossy().replace('\'', "''")))
+        .collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+fn row_count(connection: &Connection, table: &str) -> Result<u64> {
+    let count: i64 = connection.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))?;
+    Ok(count as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quickstart::QuickstartDataset;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_export_quickstart_dataset_with_views() {
+        let temp_dir = TempDir::new().unwrap();
+        let dataset = QuickstartDataset::generate(&temp_dir.path().join("dataset")).await.unwrap();
+        let sources = DuckDbSources {
+            frames_dir: Some(dataset.frames_dir()),
+            ocr_dir: Some(dataset.ocr_dir()),
+            events_dir: Some(dataset.events_dir()),
+        };
+        let path = temp_dir.path().join("session.duckdb");
+
+        // Other tables kept in the output directory are not frames
+        let stray = dataset.frames_dir().join("events");
+        std::fs::create_dir_all(&stray).unwrap();
+        for file in layout_compat::parquet_files(&dataset.events_dir()).unwrap() {
+            std::fs::copy(&file, stray.join(file.file_name().unwrap())).unwrap();
+            std::fs::copy(&file, dataset.frames_dir().join("chains_0.parquet")).unwrap();
+        }
+
+        let summary = export_duckdb(&sources, &path).unwrap();
+        assert_eq!(summary.tables, vec![
+            ("frames".to_string(), dataset.frames as u64),
+            ("ocr".to_string(), dataset.ocr_results as u64),
+            ("events".to_string(), dataset.events as u64),
+        ]);
+        assert_eq!(summary.views, vec!["events_with_frames", "ocr_text", "ocr_search"]);
+
+        let connection = Connection::open(&path).unwrap();
+        let unnamed: i64 = connection
+            .query_row("SELECT COUNT(*) FROM events_with_frames WHERE type IS NULL OR app_name IS NULL", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(unnamed, 0);
+        let word: String = connection
+            .query_row("SELECT text FROM ocr LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        let word = word.split_whitespace().next().unwrap().to_lowercase();
+        let found: i64 = connection
+            .query_row("SELECT COUNT(*) FROM ocr_search(?)", [&word], |row| row.get(0))
+            .unwrap();
+        assert!(found > 0);
+
+        // An existing database is never overwritten
+        assert!(export_duckdb(&sources, &path).is_err());
+    }
+}
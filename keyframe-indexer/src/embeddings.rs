@@ -0,0 +1,622 @@
+use crate::error::{IndexerError, Result};
+use crate::layout_compat;
+use crate::ocr_data::OCRResult;
+use arrow::array::{Array, Float32Array, Float32Builder, ListArray, ListBuilder, StringArray, TimestampNanosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, TimeZone, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Indexes of up to this many frames are searched exhaustively
+const EXACT_SEARCH_LIMIT: usize = 2_000;
+
+/// Where text embeddings come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProvider {
+    /// A sentence-transformers model such as all-MiniLM-L6-v2 exported to ONNX; needs the `onnx` feature
+    #[default]
+    Onnx,
+    /// An OpenAI-compatible `/embeddings` endpoint
+    Remote,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub enabled: bool,
+    pub provider: EmbeddingProvider,
+    /// ONNX model file
+    pub model_path: Option<String>,
+    /// `tokenizer.json` of the ONNX model
+    pub tokenizer_path: Option<String>,
+    /// Remote endpoint, e.g. `https://api.openai.com/v1/embeddings`
+    pub api_url: Option<String>,
+    /// Model name sent to the remote endpoint
+    pub api_model: String,
+    /// Environment variable holding the remote endpoint's bearer token
+    pub api_key_env: String,
+    /// Seconds a request to the remote endpoint may take, connecting included
+    pub api_timeout_secs: u64,
+    /// Embedding Parquet directory; defaults to `<output_dir>/embeddings`
+    pub output_dir: Option<String>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: EmbeddingProvider::default(),
+            model_path: None,
+            tokenizer_path: None,
+            api_url: None,
+            api_model: "text-embedding-3-small".to_string(),
+            api_key_env: "EMBEDDINGS_API_KEY".to_string(),
+            api_timeout_secs: 30,
+            output_dir: None,
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        match self.provider {
+            EmbeddingProvider::Onnx if self.model_path.is_none() || self.tokenizer_path.is_none() => Err(
+                IndexerError::Config("ONNX embeddings need model_path and tokenizer_path".to_string()),
+            ),
+            EmbeddingProvider::Remote if self.api_url.is_none() => {
+                Err(IndexerError::Config("Remote embeddings need api_url".to_string()))
+            }
+            EmbeddingProvider::Remote if self.api_timeout_secs == 0 => {
+                Err(IndexerError::Config("api_timeout_secs must be positive".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn dir(&self, output_dir: &str) -> PathBuf {
+        match &self.output_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(output_dir).join("embeddings"),
+        }
+    }
+
+    /// Store embedding with the configured embedder, writing under `output_dir` unless set
+    pub fn store(&self, output_dir: &str) -> Result<EmbeddingStore> {
+        EmbeddingStore::new(self.dir(output_dir), self.embedder()?)
+    }
+
+    /// Build the configured embedder
+    pub fn embedder(&self) -> Result<Box<dyn TextEmbedder>> {
+        self.validate()?;
+        match self.provider {
+            EmbeddingProvider::Remote => Ok(Box::new(RemoteEmbedder::new(
+                self.api_url.clone().unwrap_or_default(),
+                self.api_model.clone(),
+                std::env::var(&self.api_key_env).ok(),
+                Duration::from_secs(self.api_timeout_secs),
+            ))),
+            #[cfg(feature = "onnx")]
+            EmbeddingProvider::Onnx => Ok(Box::new(onnx::OnnxEmbedder::load(
+                self.model_path.as_deref().unwrap_or_default(),
+                self.tokenizer_path.as_deref().unwrap_or_default(),
+            )?)),
+            #[cfg(not(feature = "onnx"))]
+            EmbeddingProvider::Onnx => Err(IndexerError::Config(
+                "ONNX embeddings need a build with the `onnx` feature".to_string(),
+            )),
+        }
+    }
+}
+
+/// Turns text into fixed-size vectors whose cosine similarity reflects meaning
+pub trait TextEmbedder: Send + Sync {
+    /// Model name stored with each embedding; vectors of different models are never compared
+    fn model(&self) -> &str;
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Embeds text through an OpenAI-compatible `/embeddings` endpoint
+pub struct RemoteEmbedder {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl RemoteEmbedder {
+    /// Requests taking longer than `timeout` fail instead of stalling the segment
+    pub fn new(url: String, model: String, api_key: Option<String>, timeout: Duration) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+        Self { url, model, api_key, agent }
+    }
+}
+
+impl TextEmbedder for RemoteEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        #[derive(Deserialize)]
+        struct Response {
+            data: Vec<Item>,
+        }
+        #[derive(Deserialize)]
+        struct Item {
+            index: usize,
+            embedding: Vec<f32>,
+        }
+
+        let mut request = self.agent.post(&self.url);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Response = request
+            .send_json(serde_json::json!({ "model": self.model, "input": texts }))
+            .map_err(|e| IndexerError::Embedding(format!("Request to {} failed: {}", self.url, e)))?
+            .into_json()?;
+
+        let mut vectors = vec![Vec::new(); texts.len()];
+        for item in response.data {
+            if let Some(vector) = vectors.get_mut(item.index) {
+                *vector = item.embedding;
+            }
+        }
+        if vectors.iter().any(Vec::is_empty) {
+            return Err(IndexerError::Embedding(format!("{} returned fewer embeddings than texts", self.url)));
+        }
+        Ok(vectors)
+    }
+}
+
+#[cfg(feature = "onnx")]
+mod onnx {
+    use super::TextEmbedder;
+    use crate::error::{IndexerError, Result};
+    use ndarray::{Array2, Axis};
+    use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+    /// Mean-pooled, normalized sentence embeddings from a local ONNX model
+    pub struct OnnxEmbedder {
+        model: String,
+        session: ort::Session,
+        tokenizer: Tokenizer,
+    }
+
+    impl OnnxEmbedder {
+        pub fn load(model_path: &str, tokenizer_path: &str) -> Result<Self> {
+            let error = |e: &dyn std::fmt::Display| IndexerError::Embedding(e.to_string());
+            let session = ort::Session::builder()
+                .and_then(|builder| builder.commit_from_file(model_path))
+                .map_err(|e| error(&e))?;
+            let mut tokenizer = Tokenizer::from_file(tokenizer_path).map_err(|e| error(&e))?;
+            tokenizer.with_padding(Some(PaddingParams::default()));
+            tokenizer
+                .with_truncation(Some(TruncationParams { max_length: 256, ..TruncationParams::default() }))
+                .map_err(|e| error(&e))?;
+            let model = std::path::Path::new(model_path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| model_path.to_string());
+            Ok(Self { model, session, tokenizer })
+        }
+    }
+
+    impl TextEmbedder for OnnxEmbedder {
+        fn model(&self) -> &str {
+            &self.model
+        }
+
+        fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let error = |e: &dyn std::fmt::Display| IndexerError::Embedding(e.to_string());
+            if texts.is_empty() {
+                return Ok(Vec::new());
+            }
+            let encodings = self.tokenizer.encode_batch(texts.to_vec(), true).map_err(|e| error(&e))?;
+            let length = encodings[0].get_ids().len();
+            let matrix = |values: &dyn Fn(&tokenizers::Encoding) -> Vec<i64>| {
+                let flat: Vec<i64> = encodings.iter().flat_map(values).collect();
+                Array2::from_shape_vec((encodings.len(), length), flat)
+            };
+            let ids = matrix(&|e| e.get_ids().iter().map(|&v| v as i64).collect()).map_err(|e| error(&e))?;
+            let mask = matrix(&|e| e.get_attention_mask().iter().map(|&v| v as i64).collect()).map_err(|e| error(&e))?;
+            let types = matrix(&|e| e.get_type_ids().iter().map(|&v| v as i64).collect()).map_err(|e| error(&e))?;
+
+            let inputs = ort::inputs![
+                "input_ids" => ids,
+                "attention_mask" => mask.clone(),
+                "token_type_ids" => types,
+            ]
+            .map_err(|e| error(&e))?;
+            let outputs = self.session.run(inputs).map_err(|e| error(&e))?;
+            let hidden = outputs[0].try_extract_tensor::<f32>().map_err(|e| error(&e))?;
+
+            // Mean over the real tokens of each text
+            let mut vectors = Vec::with_capacity(texts.len());
+            for (row, tokens) in hidden.axis_iter(Axis(0)).enumerate() {
+                let mut sum = vec![0.0f32; tokens.shape()[1]];
+                let mut count = 0.0f32;
+                for (position, token) in tokens.axis_iter(Axis(0)).enumerate() {
+                    if mask[[row, position]] == 1 {
+                        sum.iter_mut().zip(token.iter()).for_each(|(s, v)| *s += v);
+                        count += 1.0;
+                    }
+                }
+                sum.iter_mut().for_each(|s| *s /= count.max(1.0));
+                vectors.push(super::normalized(sum));
+            }
+            Ok(vectors)
+        }
+    }
+}
+
+/// Embedding of the OCR text of one frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameEmbedding {
+    pub frame_id: String,
+    pub model: String,
+    pub text: String,
+    pub embedded_at: DateTime<Utc>,
+    pub vector: Vec<f32>,
+}
+
+/// A search hit, most similar first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarFrame {
+    pub frame_id: String,
+    pub text: String,
+    /// Cosine similarity, 1.0 for identical meaning
+    pub score: f32,
+}
+
+/// Embeds OCR batches per frame and stores the vectors in Parquet files
+pub struct EmbeddingStore {
+    dir: PathBuf,
+    embedder: Box<dyn TextEmbedder>,
+    schema: Arc<Schema>,
+}
+
+impl EmbeddingStore {
+    pub fn new<P: AsRef<Path>>(dir: P, embedder: Box<dyn TextEmbedder>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("frame_id", DataType::Utf8, false),
+            Field::new("model", DataType::Utf8, false),
+            Field::new("text", DataType::Utf8, false),
+            Field::new("embedded_at", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("vector", DataType::List(Arc::new(Field::new("item", DataType::Float32, true))), false),
+        ]));
+        Ok(Self { dir, embedder, schema })
+    }
+
+    /// Embed the text of each frame in an OCR batch and store it, returning the number of frames embedded.
+    ///
+    /// A frame's results are joined in reading order, top to bottom and left to
+    /// right; frames without text are skipped.
+    pub fn embed_ocr_batch(&self, results: &[OCRResult]) -> Result<usize> {
+        let mut frames: BTreeMap<&str, Vec<&OCRResult>> = BTreeMap::new();
+        for result in results.iter().filter(|r| !r.text.trim().is_empty()) {
+            frames.entry(result.frame_id.as_str()).or_default().push(result);
+        }
+        if frames.is_empty() {
+            return Ok(0);
+        }
+
+        let (frame_ids, texts): (Vec<&str>, Vec<String>) = frames
+            .into_iter()
+            .map(|(frame_id, mut results)| {
+                results.sort_by(|a, b| a.roi.y.total_cmp(&b.roi.y).then(a.roi.x.total_cmp(&b.roi.x)));
+                let text: Vec<&str> = results.iter().map(|r| r.text.trim()).collect();
+                (frame_id, text.join(" "))
+            })
+            .unzip();
+        let vectors = self.embedder.embed(&texts)?;
+
+        let now = Utc::now();
+        let embeddings: Vec<FrameEmbedding> = frame_ids
+            .into_iter()
+            .zip(texts)
+            .zip(vectors)
+            .map(|((frame_id, text), vector)| FrameEmbedding {
+                frame_id: frame_id.to_string(),
+                model: self.embedder.model().to_string(),
+                text,
+                embedded_at: now,
+                vector,
+            })
+            .collect();
+        self.write(&embeddings)?;
+        Ok(embeddings.len())
+    }
+
+    fn write(&self, embeddings: &[FrameEmbedding]) -> Result<()> {
+        let mut vectors = ListBuilder::new(Float32Builder::new());
+        for embedding in embeddings {
+            vectors.values().append_slice(&embedding.vector);
+            vectors.append(true);
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), vec![
+            Arc::new(StringArray::from(embeddings.iter().map(|e| e.frame_id.as_str()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(embeddings.iter().map(|e| e.model.as_str()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(embeddings.iter().map(|e| e.text.as_str()).collect::<Vec<_>>())),
+            Arc::new(TimestampNanosecondArray::from(
+                embeddings.iter().map(|e| e.embedded_at.timestamp_nanos_opt().unwrap_or(0)).collect::<Vec<_>>(),
+            )),
+            Arc::new(vectors.finish()),
+        ])?;
+
+        let file_path = self.dir.join(format!("embeddings_{}.parquet", Utc::now().format("%Y%m%d_%H%M%S_%f")));
+        let mut writer = ArrowWriter::try_new(File::create(&file_path)?, self.schema.clone(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        debug!("Wrote {} embeddings to {}", embeddings.len(), file_path.display());
+        Ok(())
+    }
+
+    /// Every stored embedding of the current model; a frame embedded more than once keeps its latest vector
+    pub fn load(&self) -> Result<Vec<FrameEmbedding>> {
+        let mut latest: HashMap<String, FrameEmbedding> = HashMap::new();
+        for file in layout_compat::parquet_files(&self.dir)? {
+            for batch in ParquetRecordBatchReaderBuilder::try_new(File::open(&file)?)?.build()? {
+                let batch = batch?;
+                let column = |name: &str| {
+                    batch.column_by_name(name).ok_or_else(|| {
+                        IndexerError::Embedding(format!("{} has no {} column", file.display(), name))
+                    })
+                };
+                let frame_ids = column("frame_id")?.as_any().downcast_ref::<StringArray>();
+                let models = column("model")?.as_any().downcast_ref::<StringArray>();
+                let texts = column("text")?.as_any().downcast_ref::<StringArray>();
+                let times = column("embedded_at")?.as_any().downcast_ref::<TimestampNanosecondArray>();
+                let vectors = column("vector")?.as_any().downcast_ref::<ListArray>();
+                let (Some(frame_ids), Some(models), Some(texts), Some(times), Some(vectors)) =
+                    (frame_ids, models, texts, times, vectors)
+                else {
+                    return Err(IndexerError::Embedding(format!("Unexpected column types in {}", file.display())));
+                };
+
+                for row in 0..batch.num_rows() {
+                    if models.value(row) != self.embedder.model() {
+                        continue;
+                    }
+                    let values = vectors.value(row);
+                    let vector = values
+                        .as_any()
+                        .downcast_ref::<Float32Array>()
+                        .map(|v| v.values().to_vec())
+                        .unwrap_or_default();
+                    let embedding = FrameEmbedding {
+                        frame_id: frame_ids.value(row).to_string(),
+                        model: models.value(row).to_string(),
+                        text: texts.value(row).to_string(),
+                        embedded_at: Utc.timestamp_nanos(times.value(row)),
+                        vector,
+                    };
+                    match latest.get(&embedding.frame_id) {
+                        Some(existing) if existing.embedded_at > embedding.embedded_at => {}
+                        _ => {
+                            latest.insert(embedding.frame_id.clone(), embedding);
+                        }
+                    }
+                }
+            }
+        }
+        let mut embeddings: Vec<FrameEmbedding> = latest.into_values().collect();
+        embeddings.sort_by(|a, b| a.frame_id.cmp(&b.frame_id));
+        Ok(embeddings)
+    }
+
+    /// Search index over the stored embeddings
+    pub fn index(&self) -> Result<EmbeddingIndex> {
+        let index = EmbeddingIndex::build(self.load()?);
+        info!("Indexed {} frame embeddings from {}", index.len(), self.dir.display());
+        Ok(index)
+    }
+
+    /// Frames whose text means something close to `query`, e.g. "invoice approval"
+    pub fn search_text(&self, index: &EmbeddingIndex, query: &str, k: usize) -> Result<Vec<SimilarFrame>> {
+        let vector = self
+            .embedder
+            .embed(&[query.to_string()])?
+            .pop()
+            .ok_or_else(|| IndexerError::Embedding("No embedding returned for the query".to_string()))?;
+        Ok(index.search(&vector, k, None))
+    }
+}
+
+/// Approximate nearest-neighbour index over frame embeddings.
+///
+/// Vectors are bucketed by random-hyperplane hashes in several tables; a query
+/// is compared only with the vectors sharing a bucket in some table. Small
+/// indexes, and queries with too few candidates, are searched exhaustively.
+pub struct EmbeddingIndex {
+    embeddings: Vec<FrameEmbedding>,
+    /// `TABLES * BITS` hyperplanes through the origin
+    planes: Vec<Vec<f32>>,
+    tables: Vec<HashMap<u16, Vec<usize>>>,
+}
+
+impl EmbeddingIndex {
+    const TABLES: usize = 8;
+    const BITS: usize = 12;
+
+    pub fn build(embeddings: Vec<FrameEmbedding>) -> Self {
+        let dimensions = embeddings.first().map(|e| e.vector.len()).unwrap_or(0);
+        // Fixed seed, so the same embeddings always give the same buckets
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let planes: Vec<Vec<f32>> = (0..Self::TABLES * Self::BITS)
+            .map(|_| (0..dimensions).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect();
+
+        let mut index = Self { embeddings, planes, tables: vec![HashMap::new(); Self::TABLES] };
+        if index.embeddings.len() > EXACT_SEARCH_LIMIT {
+            for (position, embedding) in index.embeddings.iter().enumerate() {
+                for table in 0..Self::TABLES {
+                    let bucket = index.bucket(table, &embedding.vector);
+                    index.tables[table].entry(bucket).or_default().push(position);
+                }
+            }
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.embeddings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.embeddings.is_empty()
+    }
+
+    fn bucket(&self, table: usize, vector: &[f32]) -> u16 {
+        self.planes[table * Self::BITS..(table + 1) * Self::BITS]
+            .iter()
+            .enumerate()
+            .fold(0, |bucket, (bit, plane)| {
+                let side: f32 = plane.iter().zip(vector).map(|(p, v)| p * v).sum();
+                if side >= 0.0 { bucket | (1 << bit) } else { bucket }
+            })
+    }
+
+    /// The `k` embeddings most similar to `vector`, leaving out `exclude_frame`
+    pub fn search(&self, vector: &[f32], k: usize, exclude_frame: Option<&str>) -> Vec<SimilarFrame> {
+        let mut candidates: Vec<usize> = if self.embeddings.len() > EXACT_SEARCH_LIMIT {
+            let mut seen = HashSet::new();
+            for table in 0..Self::TABLES {
+                if let Some(bucket) = self.tables[table].get(&self.bucket(table, vector)) {
+                    seen.extend(bucket.iter().copied());
+                }
+            }
+            seen.into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        if candidates.len() < k.saturating_mul(4) {
+            candidates = (0..self.embeddings.len()).collect();
+        }
+
+        let mut hits: Vec<SimilarFrame> = candidates
+            .into_iter()
+            .map(|position| &self.embeddings[position])
+            .filter(|e| Some(e.frame_id.as_str()) != exclude_frame && e.vector.len() == vector.len())
+            .map(|e| SimilarFrame {
+                frame_id: e.frame_id.clone(),
+                text: e.text.clone(),
+                score: cosine_similarity(vector, &e.vector),
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        hits
+    }
+
+    /// Frames whose screen text is most similar to that of `frame_id`
+    pub fn similar_to_frame(&self, frame_id: &str, k: usize) -> Result<Vec<SimilarFrame>> {
+        let embedding = self
+            .embeddings
+            .iter()
+            .find(|e| e.frame_id == frame_id)
+            .ok_or_else(|| IndexerError::Embedding(format!("No embedding stored for frame {}", frame_id)))?;
+        Ok(self.search(&embedding.vector, k, Some(frame_id)))
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+#[cfg_attr(not(feature = "onnx"), allow(dead_code))]
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr_data::BoundingBox;
+    use tempfile::TempDir;
+
+    /// Counts of a few topic words, enough to tell the test screens apart
+    struct KeywordEmbedder;
+
+    impl TextEmbedder for KeywordEmbedder {
+        fn model(&self) -> &str {
+            "keywords"
+        }
+
+        fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let topics = ["invoice", "approve", "build", "test", "meeting"];
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    topics.iter().map(|topic| text.matches(topic).count() as f32).collect()
+                })
+                .collect())
+        }
+    }
+
+    fn ocr(frame_id: &str, text: &str, y: f32) -> OCRResult {
+        OCRResult {
+            frame_id: frame_id.to_string(),
+            roi: BoundingBox::new(10.0, y, 300.0, 20.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_embed_store_and_search_frames() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = EmbeddingStore::new(temp_dir.path(), Box::new(KeywordEmbedder)).unwrap();
+
+        let embedded = store
+            .embed_ocr_batch(&[
+                ocr("invoices.png", "Approve invoice", 200.0),
+                ocr("invoices.png", "Invoice #4711", 100.0),
+                ocr("approval.png", "Invoice approval pending: approve now", 100.0),
+                ocr("ci.png", "Build failed: 3 tests", 100.0),
+                ocr("blank.png", "   ", 100.0),
+            ])
+            .unwrap();
+        assert_eq!(embedded, 3);
+
+        let index = store.index().unwrap();
+        assert_eq!(index.len(), 3);
+        let invoices = index.embeddings.iter().find(|e| e.frame_id == "invoices.png").unwrap();
+        assert_eq!(invoices.text, "Invoice #4711 Approve invoice");
+
+        let similar = index.similar_to_frame("invoices.png", 2).unwrap();
+        assert_eq!(similar[0].frame_id, "approval.png");
+        assert!(similar.iter().all(|hit| hit.frame_id != "invoices.png"));
+
+        let hits = store.search_text(&index, "when did I approve an invoice", 1).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_ne!(hits[0].frame_id, "ci.png");
+        assert!(index.similar_to_frame("missing.png", 1).is_err());
+    }
+}
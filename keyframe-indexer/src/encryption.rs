@@ -3,14 +3,17 @@ use aes_gcm::{
     Aes256Gcm, Key, Nonce,
 };
 use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 
 /// Encryption manager for Parquet files and other data
-/// Uses AES-256-GCM for authenticated encryption
+/// Uses AES-256-GCM for authenticated encryption and Ed25519 for signatures
 pub struct EncryptionManager {
     cipher: Aes256Gcm,
+    signing_key: SigningKey,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,14 +28,16 @@ impl EncryptionManager {
     pub fn new() -> Result<Self> {
         let key = Self::get_or_create_key()?;
         let cipher = Aes256Gcm::new(&key);
-        Ok(Self { cipher })
+        let signing_key = Self::derive_signing_key(key.as_slice());
+        Ok(Self { cipher, signing_key })
     }
 
     /// Creates a new encryption manager with a specific key
     pub fn with_key(key_bytes: &[u8; 32]) -> Self {
         let key = Key::<Aes256Gcm>::from_slice(key_bytes);
         let cipher = Aes256Gcm::new(key);
-        Self { cipher }
+        let signing_key = Self::derive_signing_key(key_bytes);
+        Self { cipher, signing_key }
     }
 
     /// Encrypts data using AES-256-GCM
@@ -107,6 +112,35 @@ impl EncryptionManager {
         Ok(())
     }
 
+    /// Signs a message with the Ed25519 key derived from the encryption key
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(message).to_bytes().to_vec()
+    }
+
+    /// Verifies a signature produced by this manager
+    pub fn verify_signature(&self, message: &[u8], signature: &[u8]) -> Result<bool> {
+        Self::verify_with_public_key(&self.verifying_key_bytes(), message, signature)
+    }
+
+    /// Public half of the signing key, for distribution to downstream verifiers
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    /// Verifies a signature using only the public key
+    pub fn verify_with_public_key(
+        public_key: &[u8; 32],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        let verifying_key = VerifyingKey::from_bytes(public_key)
+            .map_err(|e| anyhow::anyhow!("Invalid public key: {}", e))?;
+        let signature = Signature::from_slice(signature)
+            .map_err(|e| anyhow::anyhow!("Invalid signature: {}", e))?;
+
+        Ok(verifying_key.verify(message, &signature).is_ok())
+    }
+
     /// Securely deletes a file by overwriting it with random data
     pub fn secure_delete<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
         let path = file_path.as_ref();
@@ -127,6 +161,15 @@ impl EncryptionManager {
         Ok(())
     }
 
+    /// Derives a dedicated signing key so the AES key is never used directly for signatures
+    fn derive_signing_key(key_bytes: &[u8]) -> SigningKey {
+        let mut hasher = Sha256::new();
+        hasher.update(b"keyframe-indexer/event-signing/v1");
+        hasher.update(key_bytes);
+        let seed: [u8; 32] = hasher.finalize().into();
+        SigningKey::from_bytes(&seed)
+    }
+
     /// Gets or creates an encryption key
     /// In production, this should integrate with the Swift keychain manager
     fn get_or_create_key() -> Result<Key<Aes256Gcm>> {
@@ -234,6 +277,28 @@ mod tests {
         assert_eq!(original_data, decrypted_content.as_slice());
     }
 
+    #[test]
+    fn test_sign_and_verify() {
+        let manager = EncryptionManager::with_key(&[7u8; 32]);
+        let message = b"event payload";
+
+        let signature = manager.sign(message);
+        assert_eq!(signature.len(), 64);
+        assert!(manager.verify_signature(message, &signature).unwrap());
+        assert!(!manager.verify_signature(b"tampered payload", &signature).unwrap());
+
+        // Signing keys are deterministic per encryption key
+        let same_key = EncryptionManager::with_key(&[7u8; 32]);
+        let other_key = EncryptionManager::with_key(&[8u8; 32]);
+        assert_eq!(manager.verifying_key_bytes(), same_key.verifying_key_bytes());
+        assert!(!EncryptionManager::verify_with_public_key(
+            &other_key.verifying_key_bytes(),
+            message,
+            &signature
+        )
+        .unwrap());
+    }
+
     #[test]
     fn test_secure_parquet_writer() {
         let key = [0u8; 32]; // Test key
@@ -0,0 +1,231 @@
+use crate::error::{IndexerError, Result};
+use crate::error_modal_detector::{ErrorModalType, SeverityLevel};
+use crate::event_detector::EventType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use tracing::info;
+
+/// Version of the code assignments below; bump it whenever a variant is added
+pub const ENUM_CODE_VERSION: u32 = 11;
+/// Mapping table persisted in every output directory that stores enum codes
+pub const ENUM_CODES_FILE: &str = "_enum_codes.json";
+/// Parquet key-value metadata entry recording the code version a file was written with
+pub const ENUM_CODE_VERSION_KEY: &str = "enum_code_version";
+
+/// Enum stored as a stable integer code.
+///
+/// Codes are append-only: a code is never reassigned or reused, so files
+/// written by older versions keep their meaning.
+pub trait EnumCode: Sized + Clone + PartialEq + 'static {
+    /// Name of this enum's dictionary in the mapping table
+    const DICTIONARY: &'static str;
+    /// Every variant with its code and canonical name
+    const VARIANTS: &'static [(Self, i16, &'static str)];
+
+    fn code(&self) -> i16 {
+        Self::VARIANTS.iter().find(|(v, _, _)| v == self).map(|(_, code, _)| *code).unwrap_or(-1)
+    }
+
+    fn name(&self) -> &'static str {
+        Self::VARIANTS.iter().find(|(v, _, _)| v == self).map(|(_, _, name)| *name).unwrap_or("unknown")
+    }
+
+    fn from_code(code: i16) -> Option<Self> {
+        Self::VARIANTS.iter().find(|(_, c, _)| *c == code).map(|(v, _, _)| v.clone())
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::VARIANTS.iter().find(|(_, _, n)| *n == name).map(|(v, _, _)| v.clone())
+    }
+}
+
+impl EnumCode for EventType {
+    const DICTIONARY: &'static str = "event_type";
+    const VARIANTS: &'static [(Self, i16, &'static str)] = &[
+        (EventType::FieldChange, 0, "field_change"),
+        (EventType::FormSubmission, 1, "form_submission"),
+        (EventType::ModalAppearance, 2, "modal_appearance"),
+        (EventType::ErrorDisplay, 3, "error_display"),
+        (EventType::Navigation, 4, "navigation"),
+        (EventType::DataEntry, 5, "data_entry"),
+        (EventType::TriggerExecution, 6, "trigger_execution"),
+        (EventType::ClipboardPaste, 7, "clipboard_paste"),
+        (EventType::CaptureChange, 8, "capture_change"),
+        (EventType::TransactionStart, 9, "transaction_start"),
+        (EventType::TCodeEntered, 10, "tcode_entered"),
+        (EventType::StorageThrottle, 11, "storage_throttle"),
+        (EventType::TableChange, 12, "table_change"),
+        (EventType::UrlDisplay, 13, "url_display"),
+        (EventType::EmailDisplay, 14, "email_display"),
+        (EventType::FilePathDisplay, 15, "file_path_display"),
+        (EventType::CommandExecuted, 16, "command_executed"),
+        (EventType::FileOpened, 17, "file_opened"),
+        (EventType::CodeDiagnostic, 18, "code_diagnostic"),
+        (EventType::BuildFailure, 19, "build_failure"),
+        (EventType::ProgressCompleted, 20, "progress_completed"),
+        (EventType::ModalDismissal, 21, "modal_dismissal"),
+    ];
+}
+
+impl EnumCode for ErrorModalType {
+    const DICTIONARY: &'static str = "error_modal_type";
+    const VARIANTS: &'static [(Self, i16, &'static str)] = &[
+        (ErrorModalType::SystemError, 0, "system_error"),
+        (ErrorModalType::ApplicationError, 1, "application_error"),
+        (ErrorModalType::NetworkError, 2, "network_error"),
+        (ErrorModalType::AuthError, 3, "auth_error"),
+        (ErrorModalType::ValidationError, 4, "validation_error"),
+        (ErrorModalType::Warning, 5, "warning"),
+        (ErrorModalType::ConfirmationDialog, 6, "confirmation_dialog"),
+        (ErrorModalType::InfoDialog, 7, "info_dialog"),
+        (ErrorModalType::AlertDialog, 8, "alert_dialog"),
+        (ErrorModalType::FileDialog, 9, "file_dialog"),
+        (ErrorModalType::SettingsDialog, 10, "settings_dialog"),
+        (ErrorModalType::ProgressDialog, 11, "progress_dialog"),
+        (ErrorModalType::CustomDialog, 12, "custom_dialog"),
+        (ErrorModalType::SystemNotification, 13, "system_notification"),
+    ];
+}
+
+impl EnumCode for SeverityLevel {
+    const DICTIONARY: &'static str = "severity";
+    // Ordered so that `severity_code <= 1` selects critical and high
+    const VARIANTS: &'static [(Self, i16, &'static str)] = &[
+        (SeverityLevel::Critical, 0, "critical"),
+        (SeverityLevel::High, 1, "high"),
+        (SeverityLevel::Medium, 2, "medium"),
+        (SeverityLevel::Low, 3, "low"),
+        (SeverityLevel::Info, 4, "info"),
+    ];
+}
+
+/// Versioned code-to-name mapping for every coded enum
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumCodeTable {
+    pub version: u32,
+    pub dictionaries: BTreeMap<String, BTreeMap<i16, String>>,
+}
+
+impl EnumCodeTable {
+    /// Mapping of this build
+    pub fn current() -> Self {
+        let mut dictionaries = BTreeMap::new();
+        dictionaries.insert(EventType::DICTIONARY.to_string(), Self::dictionary::<EventType>());
+        dictionaries.insert(ErrorModalType::DICTIONARY.to_string(), Self::dictionary::<ErrorModalType>());
+        dictionaries.insert(SeverityLevel::DICTIONARY.to_string(), Self::dictionary::<SeverityLevel>());
+        Self { version: ENUM_CODE_VERSION, dictionaries }
+    }
+
+    fn dictionary<T: EnumCode>() -> BTreeMap<i16, String> {
+        T::VARIANTS.iter().map(|(_, code, name)| (*code, name.to_string())).collect()
+    }
+
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(ENUM_CODES_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// Write the current mapping to `dir`, merged with any mapping already there.
+    ///
+    /// Codes stored by other versions are kept so their data stays readable; a
+    /// code that now means something else is an error.
+    pub fn persist(dir: &Path) -> Result<Self> {
+        let mut table = Self::current();
+
+        if let Some(stored) = Self::load(dir)? {
+            for (dictionary, codes) in &stored.dictionaries {
+                let merged = table.dictionaries.entry(dictionary.clone()).or_default();
+                for (code, name) in codes {
+                    match merged.get(code) {
+                        Some(current) if current != name => {
+                            return Err(IndexerError::Config(format!(
+                                "Code {} of {} is stored as {} but this build maps it to {}",
+                                code, dictionary, name, current
+                            )));
+                        }
+                        Some(_) => {}
+                        None => {
+                            merged.insert(*code, name.clone());
+                        }
+                    }
+                }
+            }
+            table.version = table.version.max(stored.version);
+            if table == stored {
+                return Ok(table);
+            }
+        }
+
+        std::fs::write(dir.join(ENUM_CODES_FILE), serde_json::to_string_pretty(&table)?)?;
+        info!("Wrote enum code mapping version {} to {}", table.version, dir.display());
+        Ok(table)
+    }
+
+    pub fn name(&self, dictionary: &str, code: i16) -> Option<&str> {
+        self.dictionaries.get(dictionary)?.get(&code).map(String::as_str)
+    }
+
+    /// SQL expression mapping the codes in `column` back to names, for string views
+    pub fn case_expression(&self, dictionary: &str, column: &str) -> String {
+        let mut sql = format!("CASE {}", column);
+        for (code, name) in self.dictionaries.get(dictionary).into_iter().flatten() {
+            let _ = write!(sql, " WHEN {} THEN '{}'", code, name.replace('\'', "''"));
+        }
+        sql.push_str(" ELSE NULL END");
+        sql
+    }
+
+    /// SQL expression mapping the names in `column` to codes, for files written before codes
+    pub fn code_expression(&self, dictionary: &str, column: &str) -> String {
+        let mut sql = format!("CASE {}", column);
+        for (code, name) in self.dictionaries.get(dictionary).into_iter().flatten() {
+            let _ = write!(sql, " WHEN '{}' THEN {}", name.replace('\'', "''"), code);
+        }
+        sql.push_str(" ELSE NULL END");
+        sql
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_codes_roundtrip_and_persist() {
+        for (variant, code, name) in EventType::VARIANTS {
+            assert_eq!(variant.code(), *code);
+            assert_eq!(EventType::from_code(*code).as_ref(), Some(variant));
+            assert_eq!(EventType::from_name(name).as_ref(), Some(variant));
+        }
+        // Names match the Display strings used elsewhere
+        for (variant, _, name) in ErrorModalType::VARIANTS {
+            assert_eq!(&variant.to_string(), name);
+        }
+        assert_eq!(SeverityLevel::High.code(), 1);
+
+        let temp_dir = TempDir::new().unwrap();
+        let table = EnumCodeTable::persist(temp_dir.path()).unwrap();
+        assert_eq!(EnumCodeTable::load(temp_dir.path()).unwrap(), Some(table.clone()));
+        assert_eq!(table.name("event_type", 3), Some("error_display"));
+        assert!(table.case_expression("severity", "severity_code").contains("WHEN 0 THEN 'critical'"));
+
+        // Codes written by a newer build survive; a reassigned code is rejected
+        let mut newer = table.clone();
+        newer.version += 1;
+        newer.dictionaries.get_mut("event_type").unwrap().insert(99, "future_type".to_string());
+        std::fs::write(temp_dir.path().join(ENUM_CODES_FILE), serde_json::to_string(&newer).unwrap()).unwrap();
+        let merged = EnumCodeTable::persist(temp_dir.path()).unwrap();
+        assert_eq!(merged.name("event_type", 99), Some("future_type"));
+        assert_eq!(merged.version, newer.version);
+
+        newer.dictionaries.get_mut("event_type").unwrap().insert(0, "renamed".to_string());
+        std::fs::write(temp_dir.path().join(ENUM_CODES_FILE), serde_json::to_string(&newer).unwrap()).unwrap();
+        assert!(EnumCodeTable::persist(temp_dir.path()).is_err());
+    }
+}
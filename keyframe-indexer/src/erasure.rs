@@ -0,0 +1,1370 @@
+use crate::access_audit::{AccessAuditor, AccessOperation};
+use crate::error::{IndexerError, Result};
+use crate::layout_compat;
+use crate::file_index::FileIndex;
+use crate::stats_cache::StatsManifest;
+use arrow::array::{Array, ArrayRef, BooleanArray, ListArray, StringArray, TimestampNanosecondArray, UInt32Array};
+use arrow::buffer::OffsetBuffer;
+use arrow::compute::{filter_record_batch, take};
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, TimeZone, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Text left in redacted fields
+pub const REDACTED: &str = "[erased]";
+
+/// Audit log of erasures, one JSON object per line, in the output directory
+pub const AUDIT_LOG_FILE: &str = "erasure_audit.jsonl";
+
+/// Event metadata keys naming an app
+const EVENT_APP_KEYS: &[&str] = &["current_app", "to_app", "from_app", "app_name"];
+
+/// Extensions of files holding outputs, for finding stores the eraser does not cover
+const DATA_EXTENSIONS: &[&str] = &["parquet", "csv", "jsonl", "db"];
+
+/// How matching data is erased
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasureMode {
+    /// Remove matching rows and keyframe images
+    #[default]
+    Delete,
+    /// Keep rows so counts and timing survive, but blank their text and black out keyframes
+    Redact,
+}
+
+/// What a data subject asked to have erased: frames in a time range, of some
+/// apps or showing a URL, and everything derived from them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ErasureRequest {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// App names, matched case-insensitively
+    pub apps: Vec<String>,
+    /// Text matched case-insensitively in window titles and event values, e.g. a domain
+    pub url: Option<String>,
+    pub mode: ErasureMode,
+    /// Who asked for the erasure, for the audit log
+    pub requested_by: String,
+}
+
+impl ErasureRequest {
+    pub fn validate(&self) -> Result<()> {
+        if !self.has_time() && !self.has_content() {
+            return Err(IndexerError::Config("erasure needs a time range, an app or a URL".to_string()));
+        }
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since >= until {
+                return Err(IndexerError::Config("erasure since must be before until".to_string()));
+            }
+        }
+        if self.apps.iter().chain(&self.url).any(|value| value.trim().is_empty()) {
+            return Err(IndexerError::Config("erasure apps and url must not be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Human-readable filter for the audit log
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(since) = self.since {
+            parts.push(format!("since {}", since.to_rfc3339()));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("until {}", until.to_rfc3339()));
+        }
+        if !self.apps.is_empty() {
+            parts.push(format!("apps {}", self.apps.join(", ")));
+        }
+        if let Some(url) = &self.url {
+            parts.push(format!("url {}", url));
+        }
+        parts.join("; ")
+    }
+
+    fn has_time(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    fn has_content(&self) -> bool {
+        !self.apps.is_empty() || self.url.is_some()
+    }
+
+    /// Whether a time is in range; unknown times only match requests without one
+    fn in_range(&self, time: Option<DateTime<Utc>>) -> bool {
+        if !self.has_time() {
+            return true;
+        }
+        time.is_some_and(|time| {
+            self.since.map_or(true, |since| time >= since) && self.until.map_or(true, |until| time < until)
+        })
+    }
+
+    /// Whether a time span overlaps the range; unknown spans only match requests without one
+    fn overlaps(&self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> bool {
+        if !self.has_time() {
+            return true;
+        }
+        let (Some(start), Some(end)) = (start, end) else {
+            return false;
+        };
+        self.since.map_or(true, |since| end > since) && self.until.map_or(true, |until| start < until)
+    }
+
+    fn matches_app(&self, app: &str) -> bool {
+        let app = app.trim().to_lowercase();
+        self.apps.iter().any(|wanted| wanted.trim().to_lowercase() == app)
+    }
+
+    fn matches_url(&self, text: &str) -> bool {
+        self.url
+            .as_deref()
+            .is_some_and(|url| text.to_lowercase().contains(&url.trim().to_lowercase()))
+    }
+
+    fn matches_frame(&self, time: Option<DateTime<Utc>>, app: &str, title: &str) -> bool {
+        self.in_range(time) && (!self.has_content() || self.matches_app(app) || self.matches_url(title))
+    }
+}
+
+/// What an erasure removed or redacted; also its audit log entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErasureReport {
+    pub erasure_id: String,
+    pub requested_at: DateTime<Utc>,
+    pub requested_by: String,
+    pub filter: String,
+    pub mode: ErasureMode,
+    /// Nothing was changed; the counts are what would have been erased
+    pub dry_run: bool,
+    /// Distinct keyframes matched
+    pub frames: usize,
+    pub keyframe_files: usize,
+    /// Frame metadata and timeline rows
+    pub frame_rows: usize,
+    pub ocr_rows: usize,
+    pub events: usize,
+    #[serde(default)]
+    pub embeddings: usize,
+    #[serde(default)]
+    pub form_records: usize,
+    /// Meetings and calendar rows
+    #[serde(default)]
+    pub meeting_rows: usize,
+    /// Copies of frames, OCR results and events in the JSONL and SQLite sinks
+    #[serde(default)]
+    pub sink_rows: usize,
+    pub files_rewritten: Vec<String>,
+    /// Data files left without rows and removed
+    pub files_deleted: Vec<String>,
+    /// Stores in the output directory the eraser does not cover, left as they were
+    #[serde(default)]
+    pub not_erased: Vec<String>,
+}
+
+/// Data tables an erasure rewrites
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Table {
+    Frames,
+    Ocr,
+    Events,
+    Embeddings,
+    Forms,
+}
+
+impl Table {
+    /// String columns blanked in redact mode, and their replacement
+    fn redactions(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        match self {
+            Self::Frames => &[("app_name", Some(REDACTED)), ("win_title", Some(REDACTED))],
+            Self::Ocr => &[("text", Some(REDACTED))],
+            // Redacted events no longer match their signature
+            Self::Events => &[
+                ("target", Some(REDACTED)),
+                ("value_from", Some(REDACTED)),
+                ("value_to", Some(REDACTED)),
+                ("metadata", Some("{}")),
+                ("signature", None),
+            ],
+            Self::Embeddings => &[("text", Some(REDACTED))],
+            Self::Forms => &[("submitted_with", Some(REDACTED)), ("fields", Some("[]")), ("buttons", Some("[]"))],
+        }
+    }
+
+    /// List columns emptied in redact mode; a kept vector would still carry the text's meaning
+    fn cleared_lists(&self) -> &'static [&'static str] {
+        match self {
+            Self::Embeddings => &["vector"],
+            Self::Forms => &["labels", "field_values"],
+            _ => &[],
+        }
+    }
+}
+
+/// CSV outputs an erasure rewrites
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvTable {
+    Frames,
+    Timeline,
+    Meetings,
+    CalendarTags,
+    CalendarApps,
+}
+
+impl CsvTable {
+    /// Positions of the columns blanked in redact mode
+    fn redact(&self) -> &'static [usize] {
+        match self {
+            Self::Frames => &[6, 7],
+            Self::Timeline => &[4, 5],
+            Self::Meetings => &[3],
+            Self::CalendarTags | Self::CalendarApps => &[2],
+        }
+    }
+}
+
+/// A frame as recorded in one of the frame tables
+struct FrameRow {
+    path: String,
+    app_name: String,
+    win_title: String,
+    time: Option<DateTime<Utc>>,
+}
+
+/// Keyframes being erased, by path; bare file names also match
+#[derive(Debug, Default)]
+struct ErasedFrames {
+    paths: HashSet<String>,
+    names: HashSet<String>,
+}
+
+impl ErasedFrames {
+    fn insert(&mut self, path: &str) {
+        if let Some(name) = Path::new(path).file_name().and_then(|name| name.to_str()) {
+            self.names.insert(name.to_string());
+        }
+        self.paths.insert(path.to_string());
+    }
+
+    fn contains(&self, frame_id: &str) -> bool {
+        self.paths.contains(frame_id) || (!frame_id.contains(['/', '\\']) && self.names.contains(frame_id))
+    }
+}
+
+/// Rows erased from one file
+#[derive(Debug, Clone, Copy, Default)]
+struct FileErasure {
+    rows: usize,
+    emptied: bool,
+}
+
+/// Finds and erases everything recorded about matching frames: keyframe
+/// images, frame metadata and timeline rows, OCR results, events, embeddings,
+/// form records, meetings and calendar rows, and the JSONL and SQLite copies
+pub struct Eraser {
+    output_dir: PathBuf,
+    ocr_dir: PathBuf,
+    events_dir: PathBuf,
+    embeddings_dir: PathBuf,
+    forms_dir: PathBuf,
+    access_auditor: Option<AccessAuditor>,
+}
+
+impl Eraser {
+    pub fn new(output_dir: impl Into<PathBuf>, ocr_dir: impl Into<PathBuf>, events_dir: impl Into<PathBuf>) -> Self {
+        let output_dir = output_dir.into();
+        Self {
+            embeddings_dir: output_dir.join("embeddings"),
+            forms_dir: output_dir.join("forms"),
+            output_dir,
+            ocr_dir: ocr_dir.into(),
+            events_dir: events_dir.into(),
+            access_auditor: None,
+        }
+    }
+
+    /// Erase embeddings from `embeddings.output_dir` rather than `<output_dir>/embeddings`
+    pub fn with_embeddings_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.embeddings_dir = dir.into();
+        self
+    }
+
+    /// Erase form records from a configured forms directory rather than `<output_dir>/forms`
+    pub fn with_forms_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.forms_dir = dir.into();
+        self
+    }
+
+    /// Also record every erasure, failed ones included, in a data access log
+    pub fn with_access_auditor(mut self, auditor: AccessAuditor) -> Self {
+        self.access_auditor = Some(auditor);
+        self
+    }
+
+    /// Erase what `request` matches and append the outcome to the audit log.
+    ///
+    /// Every Parquet file is read before any is changed, so an unreadable
+    /// (e.g. encrypted) file fails the erasure without leaving it half done.
+    pub fn erase(&self, request: &ErasureRequest, dry_run: bool) -> Result<ErasureReport> {
+        let result = self.erase_matching(request, dry_run);
+        match &self.access_auditor {
+            Some(auditor) => {
+                let target = format!("{}{}", request.describe(), if dry_run { " (dry run)" } else { "" });
+                auditor.audited(AccessOperation::Erase, &target, result)
+            }
+            None => result,
+        }
+    }
+
+    fn erase_matching(&self, request: &ErasureRequest, dry_run: bool) -> Result<ErasureReport> {
+        request.validate()?;
+        let frames = self.matching_frames(request)?;
+        let meetings = self.meeting_times()?;
+        let scope = Scope { request, frames, meetings };
+
+        let frames_parquet = self.output_files("frames_", "parquet")?;
+        let tables = [
+            (&self.ocr_dir, Table::Ocr),
+            (&self.events_dir, Table::Events),
+            (&self.embeddings_dir, Table::Embeddings),
+            (&self.forms_dir, Table::Forms),
+        ];
+        let mut parquet: Vec<(PathBuf, Table)> = frames_parquet.into_iter().map(|file| (file, Table::Frames)).collect();
+        for (dir, table) in tables {
+            parquet.extend(layout_compat::parquet_files(dir)?.into_iter().map(|file| (file, table)));
+        }
+
+        let mut planned = Vec::new();
+        for (file, table) in parquet {
+            let erasure = scope.erase_parquet(&file, table, false)?;
+            if erasure.rows > 0 {
+                planned.push((file, table));
+            }
+        }
+
+        let mut report = ErasureReport {
+            erasure_id: uuid::Uuid::new_v4().to_string(),
+            requested_at: Utc::now(),
+            requested_by: request.requested_by.clone(),
+            filter: request.describe(),
+            mode: request.mode,
+            dry_run,
+            frames: scope.frames.paths.len(),
+            keyframe_files: 0,
+            frame_rows: 0,
+            ocr_rows: 0,
+            events: 0,
+            embeddings: 0,
+            form_records: 0,
+            meeting_rows: 0,
+            sink_rows: 0,
+            files_rewritten: Vec::new(),
+            files_deleted: Vec::new(),
+            not_erased: Vec::new(),
+        };
+
+        for (file, table) in planned {
+            let erasure = scope.erase_parquet(&file, table, !dry_run)?;
+            let stats_dir = match table {
+                Table::Ocr => Some(&self.ocr_dir),
+                Table::Events => Some(&self.events_dir),
+                Table::Frames | Table::Embeddings | Table::Forms => None,
+            };
+            if let Some(dir) = stats_dir.filter(|_| !dry_run) {
+                if let Err(e) = StatsManifest::invalidate(dir, &file) {
+                    warn!("Failed to update statistics manifest in {}: {}", dir.display(), e);
+                }
+            }
+            match table {
+                Table::Frames => report.frame_rows += erasure.rows,
+                Table::Ocr => report.ocr_rows += erasure.rows,
+                Table::Events => report.events += erasure.rows,
+                Table::Embeddings => report.embeddings += erasure.rows,
+                Table::Forms => report.form_records += erasure.rows,
+            }
+            record_file(&mut report, &file, erasure, dry_run);
+        }
+
+        let calendar_files = self.output_files("calendar_", "csv")?;
+        let (calendar_apps, calendar_tags): (Vec<_>, Vec<_>) =
+            calendar_files.into_iter().partition(|file| file_name(file).starts_with("calendar_apps_"));
+        let csv_files = self
+            .output_files("frames_", "csv")?
+            .into_iter()
+            .map(|file| (file, CsvTable::Frames))
+            .chain(self.output_files("timeline_", "csv")?.into_iter().map(|file| (file, CsvTable::Timeline)))
+            .chain(self.output_files("meetings_", "csv")?.into_iter().map(|file| (file, CsvTable::Meetings)))
+            .chain(calendar_tags.into_iter().map(|file| (file, CsvTable::CalendarTags)))
+            .chain(calendar_apps.into_iter().map(|file| (file, CsvTable::CalendarApps)));
+        for (file, table) in csv_files {
+            let erasure = scope.erase_csv(&file, table, !dry_run)?;
+            match table {
+                CsvTable::Frames | CsvTable::Timeline => report.frame_rows += erasure.rows,
+                CsvTable::Meetings | CsvTable::CalendarTags | CsvTable::CalendarApps => report.meeting_rows += erasure.rows,
+            }
+            if erasure.rows > 0 {
+                record_file(&mut report, &file, erasure, dry_run);
+            }
+        }
+
+        let jsonl_files = [("frames_", Table::Frames), ("ocr_", Table::Ocr), ("events_", Table::Events)]
+            .into_iter()
+            .map(|(prefix, table)| Ok((files_in(&self.jsonl_dir(), prefix, "jsonl")?, table)))
+            .collect::<Result<Vec<_>>>()?;
+        for (files, table) in jsonl_files {
+            for file in files {
+                let erasure = scope.erase_jsonl(&file, table, !dry_run)?;
+                report.sink_rows += erasure.rows;
+                if erasure.rows > 0 {
+                    record_file(&mut report, &file, erasure, dry_run);
+                }
+            }
+        }
+        for file in files_in(&self.sqlite_dir(), "", "db")? {
+            let erasure = scope.erase_sqlite(&file, !dry_run)?;
+            report.sink_rows += erasure.rows;
+            if erasure.rows > 0 {
+                record_file(&mut report, &file, erasure, dry_run);
+            }
+        }
+
+        for path in &scope.frames.paths {
+            let path = Path::new(path);
+            if !path.is_file() {
+                continue;
+            }
+            report.keyframe_files += 1;
+            if dry_run {
+                continue;
+            }
+            match request.mode {
+                ErasureMode::Delete => std::fs::remove_file(path)?,
+                ErasureMode::Redact => {
+                    let (width, height) = image::image_dimensions(path)?;
+                    image::RgbImage::new(width, height).save(path)?;
+                }
+            }
+        }
+
+        report.not_erased = self.uncovered_stores()?;
+        if !report.not_erased.is_empty() {
+            warn!(
+                "Erasure {} does not cover {}; erase matching data there by hand",
+                report.erasure_id,
+                report.not_erased.join(", ")
+            );
+        }
+
+        self.append_audit(&report)?;
+        info!(
+            "Erasure {} ({}): {} frames, {} keyframe files, {} frame rows, {} OCR rows, {} events, {} embeddings, {} form records, {} meeting rows, {} sink rows{}",
+            report.erasure_id,
+            report.filter,
+            report.frames,
+            report.keyframe_files,
+            report.frame_rows,
+            report.ocr_rows,
+            report.events,
+            report.embeddings,
+            report.form_records,
+            report.meeting_rows,
+            report.sink_rows,
+            if dry_run { " (dry run)" } else { "" }
+        );
+        Ok(report)
+    }
+
+    /// Earlier erasures, oldest first
+    pub fn audit_log(&self) -> Result<Vec<ErasureReport>> {
+        let path = self.output_dir.join(AUDIT_LOG_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    fn append_audit(&self, report: &ErasureReport) -> Result<()> {
+        std::fs::create_dir_all(&self.output_dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.output_dir.join(AUDIT_LOG_FILE))?;
+        writeln!(file, "{}", serde_json::to_string(report)?)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Keyframes matching the request in any frame table. Frame metadata only
+    /// has times relative to its segment, so absolute times come from the
+    /// timeline, else from the keyframe file's modification time.
+    fn matching_frames(&self, request: &ErasureRequest) -> Result<ErasedFrames> {
+        let mut rows = Vec::new();
+        for file in self.output_files("timeline_", "csv")? {
+            for fields in read_csv_rows(&file)? {
+                if let [timestamp, _, _, path, app_name, win_title, ..] = fields.as_slice() {
+                    rows.push(FrameRow {
+                        path: path.clone(),
+                        app_name: app_name.clone(),
+                        win_title: win_title.clone(),
+                        time: parse_time(timestamp),
+                    });
+                }
+            }
+        }
+        for file in self.output_files("frames_", "csv")? {
+            for fields in read_csv_rows(&file)? {
+                if let [_, _, _, path, _, _, app_name, win_title, ..] = fields.as_slice() {
+                    rows.push(FrameRow { path: path.clone(), app_name: app_name.clone(), win_title: win_title.clone(), time: None });
+                }
+            }
+        }
+        for file in self.output_files("frames_", "parquet")? {
+            for batch in read_parquet(&file)?.1 {
+                let (Some(paths), Some(apps), Some(titles)) =
+                    (strings(&batch, "path"), strings(&batch, "app_name"), strings(&batch, "win_title"))
+                else {
+                    continue;
+                };
+                for row in 0..batch.num_rows() {
+                    rows.push(FrameRow {
+                        path: paths.value(row).to_string(),
+                        app_name: apps.value(row).to_string(),
+                        win_title: titles.value(row).to_string(),
+                        time: None,
+                    });
+                }
+            }
+        }
+
+        let timeline: HashMap<String, DateTime<Utc>> =
+            rows.iter().filter_map(|row| Some((row.path.clone(), row.time?))).collect();
+        let mut frames = ErasedFrames::default();
+        for row in &rows {
+            let time = row.time.or_else(|| timeline.get(&row.path).copied()).or_else(|| {
+                if request.has_time() {
+                    modified_time(Path::new(&row.path))
+                } else {
+                    None
+                }
+            });
+            if request.matches_frame(time, &row.app_name, &row.win_title) {
+                frames.insert(&row.path);
+            }
+        }
+        Ok(frames)
+    }
+
+    /// Time spans of calendar meetings by segment and meeting, for the
+    /// calendar app rows that carry none
+    fn meeting_times(&self) -> Result<HashMap<(String, String), MeetingSpan>> {
+        let mut times = HashMap::new();
+        for file in self.output_files("calendar_", "csv")? {
+            if file_name(&file).starts_with("calendar_apps_") {
+                continue;
+            }
+            for fields in read_csv_rows(&file)? {
+                if let [segment_id, meeting_uid, _, start, end, ..] = fields.as_slice() {
+                    times.insert((segment_id.clone(), meeting_uid.clone()), (parse_time(start), parse_time(end)));
+                }
+            }
+        }
+        Ok(times)
+    }
+
+    /// Files directly in the output directory named `<prefix>*.<extension>`
+    fn output_files(&self, prefix: &str, extension: &str) -> Result<Vec<PathBuf>> {
+        files_in(&self.output_dir, prefix, extension)
+    }
+
+    fn jsonl_dir(&self) -> PathBuf {
+        self.output_dir.join("jsonl")
+    }
+
+    fn sqlite_dir(&self) -> PathBuf {
+        self.output_dir.join("sqlite")
+    }
+
+    /// Data files and directories in the output directory that no erasure step reads
+    fn uncovered_stores(&self) -> Result<Vec<String>> {
+        let mut stores = Vec::new();
+        if !self.output_dir.is_dir() {
+            return Ok(stores);
+        }
+        let covered = [&self.ocr_dir, &self.events_dir, &self.embeddings_dir, &self.forms_dir, &self.jsonl_dir(), &self.sqlite_dir()]
+            .map(|dir| dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()));
+        for entry in std::fs::read_dir(&self.output_dir)? {
+            let path = entry?.path();
+            let uncovered = if path.is_dir() {
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                !covered.contains(&canonical) && holds_data(&path)?
+            } else {
+                let name = file_name(&path);
+                // Redacted intervals only hold times, and the audit log must survive
+                let known = ["frames_", "timeline_", "meetings_", "calendar_", "redacted_"]
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+                    || name == AUDIT_LOG_FILE;
+                !known && is_data_file(&path)
+            };
+            if uncovered {
+                stores.push(path.display().to_string());
+            }
+        }
+        stores.sort();
+        Ok(stores)
+    }
+}
+
+/// Start and end of a calendar meeting in a segment
+type MeetingSpan = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// A request with the keyframes it matched
+struct Scope<'a> {
+    request: &'a ErasureRequest,
+    frames: ErasedFrames,
+    meetings: HashMap<(String, String), MeetingSpan>,
+}
+
+impl Scope<'_> {
+    /// Count the matching rows of a Parquet file and, when `apply`, rewrite it
+    /// without them (or with them redacted)
+    fn erase_parquet(&self, path: &Path, table: Table, apply: bool) -> Result<FileErasure> {
+        let (schema, batches, properties) = read_parquet_with_properties(path)?;
+        let mut erasure = FileErasure::default();
+        let mut erased = Vec::with_capacity(batches.len());
+        for batch in &batches {
+            let matched = self.matched_rows(table, batch)?;
+            erasure.rows += matched.iter().filter(|matched| **matched).count();
+            if apply {
+                erased.push(erase_rows(batch, &matched, self.request.mode, table)?);
+            }
+        }
+        if !apply || erasure.rows == 0 {
+            return Ok(erasure);
+        }
+
+        FileIndex::remove(path)?;
+        if erased.iter().all(|batch| batch.num_rows() == 0) {
+            std::fs::remove_file(path)?;
+            erasure.emptied = true;
+        } else {
+            write_parquet(path, schema, properties, &erased)?;
+        }
+        Ok(erasure)
+    }
+
+    fn matched_rows(&self, table: Table, batch: &RecordBatch) -> Result<Vec<bool>> {
+        let frame_column = match table {
+            Table::Frames => "path",
+            Table::Ocr | Table::Embeddings | Table::Forms => "frame_id",
+            Table::Events => return Ok(self.matched_events(batch)),
+        };
+        // Other tables sharing the directory have nothing to erase
+        let Some(ids) = strings(batch, frame_column) else {
+            return Ok(vec![false; batch.num_rows()]);
+        };
+        Ok(ids.iter().map(|id| id.is_some_and(|id| self.frames.contains(id))).collect())
+    }
+
+    fn matched_events(&self, batch: &RecordBatch) -> Vec<bool> {
+        let times = batch
+            .column_by_name("ts_ns")
+            .and_then(|column| column.as_any().downcast_ref::<TimestampNanosecondArray>());
+        let evidence = batch
+            .column_by_name("evidence_frames")
+            .and_then(|column| column.as_any().downcast_ref::<ListArray>());
+        let metadata = strings(batch, "metadata");
+        let texts = [strings(batch, "target"), strings(batch, "value_from"), strings(batch, "value_to"), metadata];
+
+        (0..batch.num_rows())
+            .map(|row| {
+                let cited: Vec<String> = evidence
+                    .filter(|list| !list.is_null(row))
+                    .and_then(|list| {
+                        let frames = list.value(row);
+                        let frames = frames.as_any().downcast_ref::<StringArray>()?;
+                        Some(frames.iter().flatten().map(str::to_string).collect())
+                    })
+                    .unwrap_or_default();
+                let time = times.filter(|times| !times.is_null(row)).map(|times| Utc.timestamp_nanos(times.value(row)));
+                let apps: HashMap<String, String> = serde_json::from_str(value(metadata, row)).unwrap_or_default();
+                let texts = texts.map(|column| value(column, row));
+                self.event_matches(time, &cited, &apps, &texts)
+            })
+            .collect()
+    }
+
+    /// Events citing an erased frame, or in range and naming a matching app or URL
+    fn event_matches(&self, time: Option<DateTime<Utc>>, evidence: &[String], apps: &HashMap<String, String>, texts: &[&str]) -> bool {
+        if evidence.iter().any(|frame| self.frames.contains(frame)) {
+            return true;
+        }
+        if !self.request.in_range(time) {
+            return false;
+        }
+        if !self.request.has_content() {
+            return true;
+        }
+        EVENT_APP_KEYS.iter().filter_map(|key| apps.get(*key)).any(|app| self.request.matches_app(app))
+            || texts.iter().any(|text| self.request.matches_url(text))
+    }
+
+    /// Rows of a CSV output about an erased keyframe, or about a matching
+    /// meeting: in range and of a matching app or title
+    fn csv_row_matches(&self, table: CsvTable, fields: &[String]) -> bool {
+        let field = |index: usize| fields.get(index).map_or("", String::as_str);
+        let request = self.request;
+        match table {
+            CsvTable::Frames | CsvTable::Timeline => self.frames.contains(field(3)),
+            CsvTable::Meetings => {
+                request.overlaps(parse_time(field(1)), parse_time(field(2)))
+                    && (!request.has_content() || request.matches_app(field(3)))
+            }
+            CsvTable::CalendarTags => {
+                request.overlaps(parse_time(field(3)), parse_time(field(4)))
+                    && (!request.has_content() || request.matches_url(field(2)))
+            }
+            CsvTable::CalendarApps => {
+                let (start, end) =
+                    self.meetings.get(&(field(0).to_string(), field(1).to_string())).copied().unwrap_or((None, None));
+                request.overlaps(start, end) && (!request.has_content() || request.matches_app(field(2)))
+            }
+        }
+    }
+
+    /// Count the matching rows of a CSV output and, when `apply`, rewrite the
+    /// file without them (or with them redacted)
+    fn erase_csv(&self, path: &Path, table: CsvTable, apply: bool) -> Result<FileErasure> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path).map_err(std::io::Error::from)?;
+        let header = reader.headers().map_err(std::io::Error::from)?.clone();
+
+        let mut erasure = FileErasure::default();
+        let mut kept = Vec::new();
+        for record in reader.records() {
+            let mut fields: Vec<String> = record.map_err(std::io::Error::from)?.iter().map(str::to_string).collect();
+            if !self.csv_row_matches(table, &fields) {
+                kept.push(fields);
+                continue;
+            }
+            erasure.rows += 1;
+            if self.request.mode == ErasureMode::Redact {
+                for index in table.redact() {
+                    if let Some(field) = fields.get_mut(*index) {
+                        *field = REDACTED.to_string();
+                    }
+                }
+                kept.push(fields);
+            }
+        }
+        if !apply || erasure.rows == 0 {
+            return Ok(erasure);
+        }
+
+        if kept.is_empty() {
+            std::fs::remove_file(path)?;
+            erasure.emptied = true;
+        } else {
+            let temp = path.with_extension("tmp.csv");
+            let mut writer = csv::WriterBuilder::new().flexible(true).from_path(&temp).map_err(std::io::Error::from)?;
+            writer.write_record(&header).map_err(std::io::Error::from)?;
+            for fields in &kept {
+                writer.write_record(fields).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            std::fs::rename(&temp, path)?;
+        }
+        Ok(erasure)
+    }
+
+    /// Whether a row of the JSONL sink, in the shape of `export --format jsonl`, is erased
+    fn json_row_matches(&self, table: Table, row: &Map<String, Value>) -> bool {
+        let text = |name: &str| row.get(name).and_then(Value::as_str).unwrap_or_default();
+        match table {
+            Table::Frames => self.frames.contains(text("path")),
+            Table::Ocr => self.frames.contains(text("frame_id")),
+            Table::Events => {
+                let evidence: Vec<String> = row
+                    .get("evidence_frames")
+                    .and_then(|frames| serde_json::from_value(frames.clone()).ok())
+                    .unwrap_or_default();
+                let apps: HashMap<String, String> = row
+                    .get("metadata")
+                    .and_then(|metadata| serde_json::from_value(metadata.clone()).ok())
+                    .unwrap_or_default();
+                let metadata = row.get("metadata").map(Value::to_string).unwrap_or_default();
+                let texts = [text("target"), text("value_from"), text("value_to"), metadata.as_str()];
+                self.event_matches(parse_time(text("timestamp")), &evidence, &apps, &texts)
+            }
+            Table::Embeddings | Table::Forms => false,
+        }
+    }
+
+    /// Count the matching rows of a JSONL sink file and, when `apply`,
+    /// rewrite it without them (or with them redacted)
+    fn erase_jsonl(&self, path: &Path, table: Table, apply: bool) -> Result<FileErasure> {
+        let content = std::fs::read_to_string(path)?;
+        let mut erasure = FileErasure::default();
+        let mut kept = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let mut row: Map<String, Value> = serde_json::from_str(line)?;
+            if !self.json_row_matches(table, &row) {
+                kept.push(line.to_string());
+                continue;
+            }
+            erasure.rows += 1;
+            if self.request.mode == ErasureMode::Redact {
+                for (name, replacement) in table.redactions() {
+                    let redacted = match (replacement, row.get(*name)) {
+                        (_, None) => continue,
+                        (None, _) => Value::Null,
+                        (Some(_), Some(Value::Object(_))) => Value::Object(Map::new()),
+                        (Some(text), _) => Value::String(text.to_string()),
+                    };
+                    row.insert(name.to_string(), redacted);
+                }
+                kept.push(serde_json::to_string(&row)?);
+            }
+        }
+        if !apply || erasure.rows == 0 {
+            return Ok(erasure);
+        }
+
+        if kept.is_empty() {
+            std::fs::remove_file(path)?;
+            erasure.emptied = true;
+        } else {
+            let temp = path.with_extension("tmp.jsonl");
+            std::fs::write(&temp, kept.join("\n") + "\n")?;
+            std::fs::rename(&temp, path)?;
+        }
+        Ok(erasure)
+    }
+
+    /// Count the matching frames, OCR results and events of a SQLite sink
+    /// database and, when `apply`, delete (or redact) them in one transaction
+    fn erase_sqlite(&self, path: &Path, apply: bool) -> Result<FileErasure> {
+        let mut connection = Connection::open(path)?;
+        let transaction = connection.transaction()?;
+
+        let frames: Vec<i64> = {
+            let mut select = transaction.prepare("SELECT id, path FROM frames")?;
+            let rows = select
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            rows.into_iter().filter(|(_, path)| self.frames.contains(path)).map(|(id, _)| id).collect()
+        };
+        let ocr: Vec<(i64, String)> = {
+            let mut select = transaction.prepare("SELECT id, frame_id, text FROM ocr")?;
+            let rows = select
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .filter(|(_, frame_id, _)| self.frames.contains(frame_id))
+                .map(|(id, _, text)| (id, text))
+                .collect()
+        };
+        let events: Vec<String> = {
+            let mut select = transaction
+                .prepare("SELECT id, timestamp, target, value_from, value_to, evidence_frames, metadata FROM events")?;
+            let rows = select.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .filter(|(_, timestamp, target, value_from, value_to, evidence, metadata)| {
+                    let evidence: Vec<String> = evidence.split(';').filter(|frame| !frame.is_empty()).map(str::to_string).collect();
+                    let apps: HashMap<String, String> = serde_json::from_str(metadata).unwrap_or_default();
+                    let texts = [target.as_str(), value_from.as_deref().unwrap_or_default(), value_to.as_deref().unwrap_or_default(), metadata.as_str()];
+                    self.event_matches(parse_time(timestamp), &evidence, &apps, &texts)
+                })
+                .map(|(id, ..)| id)
+                .collect()
+        };
+
+        let erasure = FileErasure { rows: frames.len() + ocr.len() + events.len(), emptied: false };
+        if !apply || erasure.rows == 0 {
+            return Ok(erasure);
+        }
+
+        // The full-text index has external content, so its entries are removed by their old text
+        let mut unindex = transaction.prepare("INSERT INTO ocr_fts (ocr_fts, rowid, text) VALUES ('delete', ?1, ?2)")?;
+        for (id, text) in &ocr {
+            unindex.execute(params![id, text])?;
+        }
+        drop(unindex);
+        match self.request.mode {
+            ErasureMode::Delete => {
+                for id in &frames {
+                    transaction.execute("DELETE FROM frames WHERE id = ?1", params![id])?;
+                }
+                for (id, _) in &ocr {
+                    transaction.execute("DELETE FROM ocr WHERE id = ?1", params![id])?;
+                }
+                for id in &events {
+                    transaction.execute("DELETE FROM events WHERE id = ?1", params![id])?;
+                }
+            }
+            ErasureMode::Redact => {
+                for id in &frames {
+                    transaction.execute("UPDATE frames SET app_name = ?2, win_title = ?2 WHERE id = ?1", params![id, REDACTED])?;
+                }
+                for (id, _) in &ocr {
+                    transaction.execute("UPDATE ocr SET text = ?2 WHERE id = ?1", params![id, REDACTED])?;
+                    transaction.execute("INSERT INTO ocr_fts (rowid, text) VALUES (?1, ?2)", params![id, REDACTED])?;
+                }
+                for id in &events {
+                    transaction.execute(
+                        "UPDATE events SET target = ?2, value_from = ?2, value_to = ?2, metadata = '{}' WHERE id = ?1",
+                        params![id, REDACTED],
+                    )?;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(erasure)
+    }
+}
+
+fn record_file(report: &mut ErasureReport, path: &Path, erasure: FileErasure, dry_run: bool) {
+    let path = path.display().to_string();
+    if dry_run || !erasure.emptied {
+        report.files_rewritten.push(path);
+    } else {
+        report.files_deleted.push(path);
+    }
+}
+
+/// Drop the matched rows, or blank their text columns in redact mode
+fn erase_rows(batch: &RecordBatch, matched: &[bool], mode: ErasureMode, table: Table) -> Result<RecordBatch> {
+    match mode {
+        ErasureMode::Delete => {
+            let keep: BooleanArray = matched.iter().map(|matched| Some(!matched)).collect();
+            Ok(filter_record_batch(batch, &keep)?)
+        }
+        ErasureMode::Redact => {
+            let schema = batch.schema();
+            let columns = schema
+                .fields()
+                .iter()
+                .zip(batch.columns())
+                .map(|(field, column)| match table.redactions().iter().find(|(name, _)| name == field.name()) {
+                    Some((_, replacement)) => redact_strings(column, matched, *replacement),
+                    None if table.cleared_lists().contains(&field.name().as_str()) => clear_lists(column, matched),
+                    None => Ok(column.clone()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(RecordBatch::try_new(schema, columns)?)
+        }
+    }
+}
+
+fn redact_strings(column: &ArrayRef, matched: &[bool], replacement: Option<&str>) -> Result<ArrayRef> {
+    let strings = column
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or_else(|| IndexerError::event_store("Redacted column is not a string column"))?;
+    let redacted: StringArray = strings
+        .iter()
+        .zip(matched)
+        .map(|(value, matched)| if *matched { replacement } else { value })
+        .collect();
+    Ok(Arc::new(redacted))
+}
+
+/// Empty the lists of the matched rows, keeping the rest
+fn clear_lists(column: &ArrayRef, matched: &[bool]) -> Result<ArrayRef> {
+    let lists = column
+        .as_any()
+        .downcast_ref::<ListArray>()
+        .ok_or_else(|| IndexerError::event_store("Cleared column is not a list column"))?;
+    let DataType::List(item) = lists.data_type() else {
+        return Err(IndexerError::event_store("Cleared column is not a list column"));
+    };
+
+    let value_offsets = lists.value_offsets();
+    let mut kept = Vec::new();
+    let mut offsets = vec![0i32];
+    for (row, matched) in matched.iter().enumerate() {
+        if !matched {
+            kept.extend(value_offsets[row] as u32..value_offsets[row + 1] as u32);
+        }
+        offsets.push(kept.len() as i32);
+    }
+    let values = take(lists.values(), &UInt32Array::from(kept), None)?;
+    let cleared = ListArray::try_new(item.clone(), OffsetBuffer::new(offsets.into()), values, lists.nulls().cloned())?;
+    Ok(Arc::new(cleared))
+}
+
+/// Writer settings of an existing file, so a rewrite keeps its provenance
+struct SourceProperties {
+    created_by: Option<String>,
+    key_values: Option<Vec<KeyValue>>,
+}
+
+fn read_parquet(path: &Path) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let (schema, batches, _) = read_parquet_with_properties(path)?;
+    Ok((schema, batches))
+}
+
+fn read_parquet_with_properties(path: &Path) -> Result<(SchemaRef, Vec<RecordBatch>, SourceProperties)> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let schema = builder.schema().clone();
+    let file_metadata = builder.metadata().file_metadata();
+    let properties = SourceProperties {
+        created_by: file_metadata.created_by().map(str::to_string),
+        // The writer stores its own Arrow schema again
+        key_values: file_metadata
+            .key_value_metadata()
+            .map(|key_values| key_values.iter().filter(|kv| kv.key != "ARROW:schema").cloned().collect()),
+    };
+    let batches = builder.build()?.collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok((schema, batches, properties))
+}
+
+/// Replace a Parquet file through a temporary file, so readers never see a partial one
+fn write_parquet(path: &Path, schema: SchemaRef, properties: SourceProperties, batches: &[RecordBatch]) -> Result<()> {
+    let mut builder = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(properties.key_values);
+    if let Some(created_by) = properties.created_by {
+        builder = builder.set_created_by(created_by);
+    }
+
+    let temp = path.with_extension("tmp.parquet");
+    let mut writer = ArrowWriter::try_new(File::create(&temp)?, schema, Some(builder.build()))?;
+    for batch in batches.iter().filter(|batch| batch.num_rows() > 0) {
+        writer.write(batch)?;
+    }
+    writer.close()?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+fn strings<'a>(batch: &'a RecordBatch, name: &str) -> Option<&'a StringArray> {
+    batch.column_by_name(name)?.as_any().downcast_ref::<StringArray>()
+}
+
+fn value(column: Option<&StringArray>, row: usize) -> &str {
+    column.filter(|column| !column.is_null(row)).map_or("", |column| column.value(row))
+}
+
+fn modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.into())
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|time| time.with_timezone(&Utc))
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().and_then(|name| name.to_str()).unwrap_or_default()
+}
+
+/// Files directly in `dir` named `<prefix>*.<extension>`
+fn files_in(dir: &Path, prefix: &str, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = file_name(&path);
+        if name.starts_with(prefix) && !name.contains(".tmp.") && path.extension().is_some_and(|e| e == extension) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_data_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|extension| DATA_EXTENSIONS.contains(&extension))
+}
+
+/// Whether a directory holds output data files at any depth; keyframe directories hold only images
+fn holds_data(dir: &Path) -> Result<bool> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let holds = if path.is_dir() { holds_data(&path)? } else { is_data_file(&path) };
+        if holds {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Data rows of a CSV file, with quoted fields unescaped
+fn read_csv_rows(path: &Path) -> Result<Vec<Vec<String>>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path).map_err(|e| {
+        warn!("Failed to read {}: {}", path.display(), e);
+        std::io::Error::from(e)
+    })?;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        rows.push(record.map_err(std::io::Error::from)?.iter().map(str::to_string).collect());
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::{DetectedEvent, EventType};
+    use crate::event_parquet_writer::EventParquetWriter;
+    use crate::ocr_data::{BoundingBox, OCRResult};
+    use crate::embeddings::{EmbeddingStore, TextEmbedder};
+    use crate::form_model::{FormRecord, FormRecordParquetWriter};
+    use crate::ocr_parquet_writer::OCRParquetWriter;
+    use crate::output_sink::{JsonlSink, OutputSink};
+    use crate::sqlite_sink::SqliteSink;
+
+    /// Text length as a one-dimensional vector
+    struct LengthEmbedder;
+
+    impl TextEmbedder for LengthEmbedder {
+        fn model(&self) -> &str {
+            "length"
+        }
+
+        fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|text| vec![text.len() as f32]).collect())
+        }
+    }
+
+    fn ocr(frame_id: &str, text: &str) -> OCRResult {
+        OCRResult {
+            frame_id: frame_id.to_string(),
+            roi: BoundingBox { x: 0.0, y: 0.0, width: 10.0, height: 10.0 },
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    fn event(id: &str, timestamp: DateTime<Utc>, frame: &str, app: &str) -> DetectedEvent {
+        DetectedEvent {
+            id: id.to_string(),
+            timestamp,
+            event_type: EventType::Navigation,
+            target: "window".to_string(),
+            value_from: None,
+            value_to: Some(app.to_string()),
+            confidence: 0.9,
+            evidence_frames: vec![frame.to_string()],
+            metadata: [("current_app".to_string(), app.to_string())].into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_erases_an_app_everywhere_and_audits_it() {
+        let root = tempfile::tempdir().unwrap();
+        let output_dir = root.path().join("output");
+        let (ocr_dir, events_dir) = (output_dir.join("ocr"), output_dir.join("events"));
+        std::fs::create_dir_all(&output_dir).unwrap();
+
+        let slack = output_dir.join("frame_1.png").display().to_string();
+        let code = output_dir.join("frame_2.png").display().to_string();
+        for path in [&slack, &code] {
+            image::RgbImage::from_pixel(8, 8, image::Rgb([255, 255, 255])).save(path).unwrap();
+        }
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        std::fs::write(
+            output_dir.join("timeline_20240305.csv"),
+            format!(
+                "timestamp,monitor_id,segment_id,path,app_name,win_title\n{},0,s1,{},Slack,\"general, team\"\n{},0,s1,{},Code,main.rs\n",
+                start.to_rfc3339(),
+                slack,
+                (start + chrono::Duration::seconds(5)).to_rfc3339(),
+                code
+            ),
+        )
+        .unwrap();
+
+        let mut ocr_writer = OCRParquetWriter::new(ocr_dir.to_str().unwrap()).unwrap();
+        ocr_writer.write_ocr_results(&[ocr(&slack, "lunch?"), ocr(&code, "fn main()")]).await.unwrap();
+        ocr_writer.finalize().await.unwrap();
+        let mut event_writer = EventParquetWriter::new(events_dir.to_str().unwrap()).unwrap();
+        event_writer
+            .write_events(&[event("e1", start, &slack, "Slack"), event("e2", start + chrono::Duration::seconds(5), &code, "Code")])
+            .await
+            .unwrap();
+        event_writer.flush_batch().await.unwrap();
+
+        let eraser = Eraser::new(&output_dir, &ocr_dir, &events_dir);
+        let request = ErasureRequest { apps: vec!["slack".to_string()], requested_by: "dpo".to_string(), ..ErasureRequest::default() };
+
+        // A dry run counts without changing anything
+        let planned = eraser.erase(&request, true).unwrap();
+        assert_eq!((planned.frames, planned.keyframe_files, planned.frame_rows, planned.ocr_rows, planned.events), (1, 1, 1, 1, 1));
+        assert!(Path::new(&slack).exists());
+
+        let report = eraser.erase(&request, false).unwrap();
+        assert_eq!((report.ocr_rows, report.events), (1, 1));
+        assert!(!Path::new(&slack).exists() && Path::new(&code).exists());
+        let timeline = std::fs::read_to_string(output_dir.join("timeline_20240305.csv")).unwrap();
+        assert!(!timeline.contains("Slack") && timeline.contains("main.rs"));
+
+        let remaining_ocr = OCRParquetWriter::new(ocr_dir.to_str().unwrap()).unwrap().query_by_confidence(0.0).await.unwrap();
+        assert_eq!(remaining_ocr.iter().map(|r| r.frame_id.as_str()).collect::<Vec<_>>(), vec![code.as_str()]);
+        let remaining_events = EventParquetWriter::new(events_dir.to_str().unwrap()).unwrap().query_by_confidence(0.0).await.unwrap();
+        assert_eq!(remaining_events.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["e2"]);
+
+        // Nothing left to erase, and both runs are in the audit log
+        assert_eq!(eraser.erase(&request, false).unwrap().frames, 0);
+        let audit = eraser.audit_log().unwrap();
+        assert_eq!(audit.len(), 3);
+        assert!(audit[0].dry_run && audit[1].filter == "apps slack" && audit[1].requested_by == "dpo");
+
+        assert!(ErasureRequest::default().validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_erases_derived_stores_and_sink_copies() {
+        let root = tempfile::tempdir().unwrap();
+        let output_dir = root.path().join("output");
+        let (ocr_dir, events_dir) = (output_dir.join("ocr"), output_dir.join("events"));
+        std::fs::create_dir_all(&output_dir).unwrap();
+
+        let slack = output_dir.join("frame_1.png").display().to_string();
+        let code = output_dir.join("frame_2.png").display().to_string();
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let later = start + chrono::Duration::minutes(30);
+        let write = |name: &str, content: String| std::fs::write(output_dir.join(name), content).unwrap();
+        write(
+            "timeline_20240305.csv",
+            format!(
+                "timestamp,monitor_id,segment_id,path,app_name,win_title\n{},0,s1,{},Slack,general\n{},0,s1,{},Code,main.rs\n",
+                start.to_rfc3339(),
+                slack,
+                later.to_rfc3339(),
+                code
+            ),
+        );
+        write(
+            "meetings_20240305.csv",
+            format!(
+                "segment_id,start,end,app,speech_seconds\ns1,{},{},Slack,120.0\ns1,{},{},Zoom,600.0\n",
+                start.to_rfc3339(),
+                (start + chrono::Duration::minutes(5)).to_rfc3339(),
+                later.to_rfc3339(),
+                (later + chrono::Duration::minutes(20)).to_rfc3339()
+            ),
+        );
+        write(
+            "calendar_20240305.csv",
+            format!(
+                "segment_id,meeting_uid,meeting_title,overlap_start,overlap_end,frame_count\ns1,m1,\"Standup, team\",{},{},2\n",
+                start.to_rfc3339(),
+                later.to_rfc3339()
+            ),
+        );
+        write("calendar_apps_20240305.csv", "segment_id,meeting_uid,app_name,frame_count\ns1,m1,Slack,1\ns1,m1,Code,1\n".to_string());
+        // Stores the eraser does not know are reported, not silently kept
+        std::fs::create_dir_all(output_dir.join("plugin_tables")).unwrap();
+        std::fs::write(output_dir.join("plugin_tables").join("tickets.parquet"), b"PAR1").unwrap();
+
+        let results = [ocr(&slack, "lunch today"), ocr(&code, "fn main")];
+        let events = [event("e1", start, &slack, "Slack"), event("e2", later, &code, "Code")];
+        let embeddings = EmbeddingStore::new(output_dir.join("embeddings"), Box::new(LengthEmbedder)).unwrap();
+        embeddings.embed_ocr_batch(&results).unwrap();
+        let mut forms = FormRecordParquetWriter::new(output_dir.join("forms").to_str().unwrap()).unwrap();
+        let record = FormRecord {
+            record_id: "r1".to_string(),
+            timestamp: start,
+            frame_id: slack.clone(),
+            submission_event_id: "e1".to_string(),
+            submitted_with: Some("Send".to_string()),
+            fields: Vec::new(),
+            buttons: Vec::new(),
+        };
+        forms.write_records(&[record]).await.unwrap();
+        forms.finalize().await.unwrap();
+        let mut jsonl = JsonlSink::new(output_dir.join("jsonl")).unwrap();
+        jsonl.write_ocr(&results).unwrap();
+        jsonl.write_events(&events).unwrap();
+        let mut sqlite = SqliteSink::open(output_dir.join("sqlite").join("session.db")).unwrap();
+        sqlite.write_ocr(&results).unwrap();
+        sqlite.write_events(&events).unwrap();
+        drop(sqlite);
+
+        let eraser = Eraser::new(&output_dir, &ocr_dir, &events_dir);
+        let request = ErasureRequest {
+            since: Some(start),
+            until: Some(start + chrono::Duration::minutes(10)),
+            apps: vec!["slack".to_string()],
+            ..ErasureRequest::default()
+        };
+        let report = eraser.erase(&request, false).unwrap();
+        assert_eq!((report.frames, report.embeddings, report.form_records), (1, 1, 1));
+        // The Slack meeting and the Slack row of the calendar meeting
+        assert_eq!(report.meeting_rows, 2);
+        // OCR result and event, each in JSONL and SQLite
+        assert_eq!(report.sink_rows, 4);
+        assert_eq!(report.not_erased, vec![output_dir.join("plugin_tables").display().to_string()]);
+
+        let remaining: Vec<String> = embeddings.load().unwrap().into_iter().map(|e| e.frame_id).collect();
+        assert_eq!(remaining, vec![code.clone()]);
+        assert!(!output_dir.join("forms").read_dir().unwrap().any(|entry| is_data_file(&entry.unwrap().path())));
+        let meetings = std::fs::read_to_string(output_dir.join("meetings_20240305.csv")).unwrap();
+        assert!(!meetings.contains("Slack") && meetings.contains("Zoom"));
+        let calendar = std::fs::read_to_string(output_dir.join("calendar_20240305.csv")).unwrap();
+        assert!(calendar.contains("\"Standup, team\""));
+        let calendar_apps = std::fs::read_to_string(output_dir.join("calendar_apps_20240305.csv")).unwrap();
+        assert!(!calendar_apps.contains("Slack") && calendar_apps.contains("Code"));
+        let jsonl_files = files_in(&output_dir.join("jsonl"), "", "jsonl").unwrap();
+        assert!(jsonl_files.iter().all(|file| !std::fs::read_to_string(file).unwrap().contains("lunch")));
+
+        let connection = Connection::open(output_dir.join("sqlite").join("session.db")).unwrap();
+        let matches = |term: &str| -> i64 {
+            connection
+                .query_row("SELECT COUNT(*) FROM ocr_fts WHERE ocr_fts MATCH ?1", [term], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!((matches("lunch"), matches("main")), (0, 1));
+        let events: Vec<String> = connection
+            .prepare("SELECT id FROM events")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|id| id.unwrap())
+            .collect();
+        assert_eq!(events, vec!["e2"]);
+
+        // Redacted embeddings keep their row but lose their text and vector
+        let redact = ErasureRequest { apps: vec!["code".to_string()], mode: ErasureMode::Redact, ..ErasureRequest::default() };
+        assert_eq!(eraser.erase(&redact, false).unwrap().embeddings, 1);
+        let redacted = embeddings.load().unwrap();
+        assert_eq!((redacted[0].text.as_str(), redacted[0].vector.len()), (REDACTED, 0));
+        assert_eq!(matches("main"), 0);
+    }
+}
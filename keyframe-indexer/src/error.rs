@@ -1,7 +1,30 @@
+use std::fmt;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, IndexerError>;
 
+/// Underlying cause kept by the subsystem variants
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where in the recording an error happened
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Video segment or captured segment being processed
+    pub segment: Option<String>,
+    /// Frame being processed, usually its path
+    pub frame_id: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [("segment", &self.segment), ("frame", &self.frame_id)]
+            .into_iter()
+            .filter_map(|(label, value)| value.as_ref().map(|value| format!("{} {}", label, value)))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum IndexerError {
     #[cfg(feature = "ffmpeg")]
@@ -23,6 +46,15 @@ pub enum IndexerError {
     #[error("DataFusion error: {0}")]
     DataFusion(#[from] datafusion::error::DataFusionError),
     
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    
+    #[error("DuckDB error: {0}")]
+    DuckDb(#[from] duckdb::Error),
+    
+    #[error("Embedding error: {0}")]
+    Embedding(String),
+    
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
     
@@ -38,15 +70,223 @@ pub enum IndexerError {
     #[error("Configuration error: {0}")]
     Config(String),
     
+    #[error("Invalid OCR data: {0}")]
+    InvalidOcr(String),
+    
     #[error("Metadata collection error: {0}")]
     Metadata(String),
     
-    #[error("Navigation detection error: {0}")]
-    Navigation(String),
+    #[error("Keyframe extraction failed: {message}")]
+    Extraction { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("Scene detection failed: {message}")]
+    SceneDetection { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("OCR failed: {message}")]
+    Ocr { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("Event store error: {message}")]
+    EventStore { message: String, #[source] source: Option<BoxError> },
     
-    #[error("Cursor tracking error: {0}")]
-    CursorTracking(String),
+    #[error("Encryption error: {message}")]
+    Encryption { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("Navigation detection error: {message}")]
+    Navigation { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("Cursor tracking error: {message}")]
+    Cursor { message: String, #[source] source: Option<BoxError> },
+    
+    #[error("System probe error: {0}")]
+    Probe(String),
+    
+    #[error("Screen capture error: {0}")]
+    Capture(String),
     
     #[error("Event correlation error: {0}")]
     EventCorrelation(String),
-}
\ No newline at end of file
+    
+    #[error("Event signing error: {0}")]
+    Signing(String),
+    
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+    
+    #[error("Rate limit exceeded: {0}")]
+    RateLimited(String),
+    
+    /// Any error, with the segment and frame it happened in
+    #[error("{source} ({context})")]
+    Context { context: ErrorContext, #[source] source: Box<IndexerError> },
+}
+
+impl IndexerError {
+    pub fn extraction(message: impl Into<String>) -> Self {
+        Self::Extraction { message: message.into(), source: None }
+    }
+    
+    pub fn scene_detection(message: impl Into<String>) -> Self {
+        Self::SceneDetection { message: message.into(), source: None }
+    }
+    
+    pub fn ocr(message: impl Into<String>) -> Self {
+        Self::Ocr { message: message.into(), source: None }
+    }
+    
+    pub fn event_store(message: impl Into<String>) -> Self {
+        Self::EventStore { message: message.into(), source: None }
+    }
+    
+    pub fn encryption(message: impl Into<String>) -> Self {
+        Self::Encryption { message: message.into(), source: None }
+    }
+    
+    pub fn navigation(message: impl Into<String>) -> Self {
+        Self::Navigation { message: message.into(), source: None }
+    }
+    
+    pub fn cursor(message: impl Into<String>) -> Self {
+        Self::Cursor { message: message.into(), source: None }
+    }
+    
+    /// Attach the underlying error of a subsystem error; other variants
+    /// already carry theirs and are returned unchanged
+    pub fn caused_by(mut self, cause: impl Into<BoxError>) -> Self {
+        if let Self::Context { context, source } = self {
+            return Self::Context { context, source: Box::new((*source).caused_by(cause)) };
+        }
+        match &mut self {
+            Self::Extraction { source, .. }
+            | Self::SceneDetection { source, .. }
+            | Self::Ocr { source, .. }
+            | Self::EventStore { source, .. }
+            | Self::Encryption { source, .. }
+            | Self::Navigation { source, .. }
+            | Self::Cursor { source, .. } => *source = Some(cause.into()),
+            _ => {}
+        }
+        self
+    }
+    
+    /// Record the segment the error happened in
+    pub fn for_segment(self, segment: impl fmt::Display) -> Self {
+        self.with_context(|context| context.segment = Some(segment.to_string()))
+    }
+    
+    /// Record the frame the error happened in
+    pub fn for_frame(self, frame_id: impl fmt::Display) -> Self {
+        self.with_context(|context| context.frame_id = Some(frame_id.to_string()))
+    }
+    
+    fn with_context(self, update: impl FnOnce(&mut ErrorContext)) -> Self {
+        match self {
+            Self::Context { mut context, source } => {
+                update(&mut context);
+                Self::Context { context, source }
+            }
+            other => {
+                let mut context = ErrorContext::default();
+                update(&mut context);
+                Self::Context { context, source: Box::new(other) }
+            }
+        }
+    }
+    
+    /// Segment and frame the error happened in, when recorded
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+    
+    /// The error without its context
+    pub fn root(&self) -> &IndexerError {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+    
+    /// Stable identifier for API responses; codes are never renamed
+    pub fn code(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "ffmpeg")]
+            Self::FFmpeg(_) => "extraction",
+            Self::Io(_) => "io",
+            Self::Image(_) => "image",
+            Self::Arrow(_) | Self::Parquet(_) | Self::DataFusion(_) | Self::Sqlite(_) | Self::DuckDb(_) => "storage",
+            Self::Embedding(_) => "embedding",
+            Self::Serde(_) => "serialization",
+            Self::Notify(_) => "file_watcher",
+            Self::CorruptedVideo(_) => "corrupted_video",
+            Self::UnsupportedFormat(_) => "unsupported_format",
+            Self::Config(_) => "config",
+            Self::InvalidOcr(_) => "invalid_ocr",
+            Self::Metadata(_) => "metadata",
+            Self::Extraction { .. } => "extraction",
+            Self::SceneDetection { .. } => "scene_detection",
+            Self::Ocr { .. } => "ocr",
+            Self::EventStore { .. } => "event_store",
+            Self::Encryption { .. } => "encryption",
+            Self::Navigation { .. } => "navigation",
+            Self::Cursor { .. } => "cursor",
+            Self::Probe(_) => "probe",
+            Self::Capture(_) => "capture",
+            Self::EventCorrelation(_) => "event_correlation",
+            Self::Signing(_) => "signing",
+            Self::AccessDenied(_) => "access_denied",
+            Self::RateLimited(_) => "rate_limited",
+            Self::Context { source, .. } => source.code(),
+        }
+    }
+}
+
+/// Add the segment or frame to any error of a result
+pub trait ResultExt<T> {
+    fn for_segment(self, segment: impl fmt::Display) -> Result<T>;
+    fn for_frame(self, frame_id: impl fmt::Display) -> Result<T>;
+}
+
+impl<T, E: Into<IndexerError>> ResultExt<T> for std::result::Result<T, E> {
+    fn for_segment(self, segment: impl fmt::Display) -> Result<T> {
+        self.map_err(|e| e.into().for_segment(segment))
+    }
+    
+    fn for_frame(self, frame_id: impl fmt::Display) -> Result<T> {
+        self.map_err(|e| e.into().for_frame(frame_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+    
+    #[test]
+    fn test_context_and_codes() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "frame.png missing");
+        let error = IndexerError::scene_detection("Failed to load frame")
+            .caused_by(io)
+            .for_segment("seg_0001.mp4")
+            .for_frame("frame_42");
+        
+        assert_eq!(error.code(), "scene_detection");
+        assert_eq!(
+            error.context(),
+            Some(&ErrorContext { segment: Some("seg_0001.mp4".to_string()), frame_id: Some("frame_42".to_string()) })
+        );
+        assert_eq!(
+            error.to_string(),
+            "Scene detection failed: Failed to load frame (segment seg_0001.mp4, frame frame_42)"
+        );
+        // The chain leads from the context through the subsystem error to its cause
+        let root = error.source().unwrap();
+        assert_eq!(root.source().unwrap().to_string(), "frame.png missing");
+        
+        let result: Result<()> = Err::<(), _>(std::io::Error::other("disk full")).for_frame("frame_7");
+        let error = result.unwrap_err();
+        assert_eq!((error.code(), error.root().code()), ("io", "io"));
+        assert_eq!(error.to_string(), "IO error: disk full (frame frame_7)");
+    }
+}
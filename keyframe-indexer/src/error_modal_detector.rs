@@ -1,4 +1,6 @@
+use crate::display_topology::DisplayTopology;
 use crate::error::{IndexerError, Result};
+use crate::notification_detector::{NotificationBanner, NotificationDetectionConfig, NotificationDetector};
 use crate::ocr_data::{OCRResult, BoundingBox};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
@@ -16,6 +18,10 @@ pub struct ErrorModalDetector {
     system_alert_patterns: Vec<CompiledPattern>,
     /// Layout analysis for dialog detection
     layout_analyzer: DialogLayoutAnalyzer,
+    /// Notification Center banners, which dialog layout analysis does not recognize
+    notification_detector: NotificationDetector,
+    /// Display arrangement of full-desktop captures; layouts are judged per display
+    display_topology: Option<DisplayTopology>,
 }
 
 /// Configuration for error and modal detection behavior
@@ -35,6 +41,12 @@ pub struct ErrorModalDetectionConfig {
     /// Maximum dialog size (to avoid detecting full-screen content)
     pub max_dialog_width_ratio: f32,
     pub max_dialog_height_ratio: f32,
+    /// Events whose ROIs overlap or lie within this many pixels are merged
+    pub merge_gap_px: f32,
+    /// Events further apart in time than this (ms) are never merged
+    pub merge_window_ms: i64,
+    /// Notification Center banner detection
+    pub notifications: NotificationDetectionConfig,
 }
 
 impl Default for ErrorModalDetectionConfig {
@@ -48,6 +60,21 @@ impl Default for ErrorModalDetectionConfig {
             min_dialog_height: 100.0,
             max_dialog_width_ratio: 0.8,
             max_dialog_height_ratio: 0.8,
+            merge_gap_px: 0.0,
+            merge_window_ms: 1000,
+            notifications: NotificationDetectionConfig::default(),
+        }
+    }
+}
+
+impl ErrorModalDetectionConfig {
+    /// Pixel thresholds scaled for a capture resolution `factor` times the reference
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            min_dialog_width: self.min_dialog_width * factor,
+            min_dialog_height: self.min_dialog_height * factor,
+            notifications: self.notifications.scaled(factor),
+            ..self.clone()
         }
     }
 }
@@ -90,6 +117,8 @@ pub enum ErrorModalType {
     ProgressDialog,
     /// Custom application dialogs
     CustomDialog,
+    /// Notification Center banners and menu-bar alerts
+    SystemNotification,
 }
 
 /// Detected error or modal with detailed classification
@@ -147,6 +176,20 @@ impl std::fmt::Display for ErrorModalType {
             ErrorModalType::SettingsDialog => write!(f, "settings_dialog"),
             ErrorModalType::ProgressDialog => write!(f, "progress_dialog"),
             ErrorModalType::CustomDialog => write!(f, "custom_dialog"),
+            ErrorModalType::SystemNotification => write!(f, "system_notification"),
+        }
+    }
+}
+
+impl SeverityLevel {
+    /// Higher is more severe
+    fn rank(&self) -> u8 {
+        match self {
+            SeverityLevel::Critical => 4,
+            SeverityLevel::High => 3,
+            SeverityLevel::Medium => 2,
+            SeverityLevel::Low => 1,
+            SeverityLevel::Info => 0,
         }
     }
 }
@@ -191,6 +234,48 @@ pub struct LayoutAnalysis {
     pub is_centered: bool,
     /// Confidence in layout analysis
     pub layout_confidence: f32,
+    /// Display the region is on, when the display topology is known
+    #[serde(default)]
+    pub screen_id: Option<i32>,
+}
+
+/// Labels that are almost always buttons, so a row with one of them is a button row
+const BUTTON_LABELS: &[&str] = &[
+    "ok", "cancel", "retry", "try again", "close", "yes", "no", "save", "don't save",
+    "delete", "allow", "don't allow", "continue", "dismiss", "ignore", "abort", "apply",
+    "done", "open", "quit", "report", "skip", "later", "not now", "replace", "stop", "reload",
+];
+
+/// A button in a dialog's button row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DialogButton {
+    pub label: String,
+    pub roi: BoundingBox,
+}
+
+impl DialogButton {
+    /// Event metadata key holding a dialog's buttons as JSON
+    pub const METADATA_KEY: &'static str = "dialog_buttons";
+    
+    /// Buttons recorded in an event's metadata
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Vec<Self> {
+        metadata
+            .get(Self::METADATA_KEY)
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+    
+    /// Button under a click position
+    pub fn at(buttons: &[DialogButton], x: f32, y: f32) -> Option<&DialogButton> {
+        buttons.iter().find(|b| {
+            b.roi.contains_point(x, y)
+        })
+    }
+    
+    fn is_known_label(text: &str) -> bool {
+        let normalized = text.trim().trim_end_matches(['.', '…']).to_lowercase().replace('’', "'");
+        BUTTON_LABELS.contains(&normalized.as_str())
+    }
 }
 
 /// Analyzes layout patterns for dialog detection
@@ -210,6 +295,7 @@ impl ErrorModalDetector {
         let modal_patterns = Self::compile_modal_patterns()?;
         let system_alert_patterns = Self::compile_system_alert_patterns()?;
         let layout_analyzer = DialogLayoutAnalyzer::new(config.clone());
+        let notification_detector = NotificationDetector::new(config.notifications.clone())?;
         
         Ok(Self {
             config,
@@ -217,9 +303,42 @@ impl ErrorModalDetector {
             modal_patterns,
             system_alert_patterns,
             layout_analyzer,
+            notification_detector,
+            display_topology: None,
         })
     }
     
+    /// Judge dialog layout relative to the display a region is on rather than the whole frame
+    pub fn set_display_topology(&mut self, topology: DisplayTopology) {
+        self.display_topology = Some(topology);
+    }
+    
+    /// Re-derive dialog size limits for a capture resolution `factor` times the reference
+    pub fn set_resolution_scale(&mut self, factor: f32) {
+        let scaled = self.config.scaled(factor);
+        if let Ok(detector) = NotificationDetector::new(scaled.notifications.clone()) {
+            self.notification_detector = detector;
+        }
+        self.layout_analyzer = DialogLayoutAnalyzer::new(scaled);
+    }
+    
+    /// Layout analysis of a region, against its own display on multi-display captures
+    fn analyze_region_layout(&self, roi: &BoundingBox, screen_width: f32, screen_height: f32) -> LayoutAnalysis {
+        let located = self.display_topology
+            .as_ref()
+            .filter(|t| t.is_multi_display())
+            .and_then(|t| t.locate_frame_region(roi, screen_width, screen_height));
+        
+        match located {
+            Some((display, local_roi)) => {
+                let mut analysis = self.layout_analyzer.analyze_layout(&local_roi, display.bounds.width, display.bounds.height);
+                analysis.screen_id = Some(display.id);
+                analysis
+            }
+            None => self.layout_analyzer.analyze_layout(roi, screen_width, screen_height),
+        }
+    }
+    
     /// Analyze OCR results from a frame and detect errors and modals
     pub fn detect_errors_and_modals(
         &self,
@@ -244,6 +363,22 @@ impl ErrorModalDetector {
         
         let mut detected_events = Vec::new();
         
+        // Notification banners are taken out first so their lines are not read as dialogs
+        let banners = self.notification_detector.detect(&high_confidence_results, screen_width, screen_height);
+        let mut in_banner = vec![false; high_confidence_results.len()];
+        for banner in banners {
+            for &index in &banner.members {
+                in_banner[index] = true;
+            }
+            detected_events.push(self.notification_event(banner, frame_id, timestamp, screen_width, screen_height));
+        }
+        let high_confidence_results: Vec<&OCRResult> = high_confidence_results
+            .into_iter()
+            .zip(in_banner)
+            .filter(|(_, in_banner)| !in_banner)
+            .map(|(result, _)| result)
+            .collect();
+        
         // Detect individual error messages and modals
         for result in &high_confidence_results {
             if let Some(event) = self.analyze_text_for_errors_modals(
@@ -412,7 +547,7 @@ impl ErrorModalDetector {
         
         // Perform layout analysis if enabled
         let layout_analysis = if self.config.enable_layout_detection {
-            Some(self.layout_analyzer.analyze_layout(
+            Some(self.analyze_region_layout(
                 &ocr_result.roi,
                 screen_width,
                 screen_height,
@@ -428,6 +563,9 @@ impl ErrorModalDetector {
         metadata.insert("screen_width".to_string(), screen_width.to_string());
         metadata.insert("screen_height".to_string(), screen_height.to_string());
         metadata.insert("pattern_count".to_string(), pattern_matches.len().to_string());
+        if let Some(screen_id) = layout_analysis.as_ref().and_then(|l| l.screen_id) {
+            metadata.insert("screen_id".to_string(), screen_id.to_string());
+        }
         
         let event = ErrorModalEvent {
             id: uuid::Uuid::new_v4().to_string(),
@@ -447,6 +585,61 @@ impl ErrorModalDetector {
         Ok(Some(event))
     }
     
+    /// Event for a Notification Center banner, attributed to the app that posted it
+    fn notification_event(
+        &self,
+        banner: NotificationBanner,
+        frame_id: &str,
+        timestamp: DateTime<Utc>,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> ErrorModalEvent {
+        let message = if banner.body.is_empty() {
+            banner.title.clone()
+        } else {
+            format!("{} {}", banner.title, banner.body)
+        };
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("detection_method".to_string(), "notification_banner".to_string());
+        metadata.insert("screen_width".to_string(), screen_width.to_string());
+        metadata.insert("screen_height".to_string(), screen_height.to_string());
+        metadata.insert("notification_title".to_string(), banner.title.clone());
+        if !banner.body.is_empty() {
+            metadata.insert("notification_body".to_string(), banner.body.clone());
+        }
+        if let Some(app_name) = &banner.app_name {
+            metadata.insert("app_name".to_string(), app_name.clone());
+        }
+        if let Some(shown_time) = &banner.shown_time {
+            metadata.insert("shown_time".to_string(), shown_time.clone());
+        }
+        
+        // Position and size alone are weaker evidence than a named app and a time label
+        let mut confidence: f32 = 0.7;
+        if banner.app_name.is_some() {
+            confidence += 0.15;
+        }
+        if banner.shown_time.is_some() {
+            confidence += 0.1;
+        }
+        
+        ErrorModalEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: ErrorModalType::SystemNotification,
+            severity: self.determine_severity_by_content(&message),
+            title: banner.title,
+            message,
+            confidence: confidence.min(1.0),
+            frame_id: frame_id.to_string(),
+            roi: banner.roi,
+            metadata,
+            pattern_matches: Vec::new(),
+            layout_analysis: None,
+        }
+    }
+    
     /// Detect dialog layouts based on spatial arrangement of OCR results
     fn detect_dialog_layouts(
         &self,
@@ -470,7 +663,7 @@ impl ErrorModalDetector {
             let group_bbox = self.calculate_group_bounding_box(&group);
             
             // Check if this looks like a dialog layout
-            let layout_analysis = self.layout_analyzer.analyze_layout(
+            let layout_analysis = self.analyze_region_layout(
                 &group_bbox,
                 screen_width,
                 screen_height,
@@ -492,6 +685,15 @@ impl ErrorModalDetector {
                 metadata.insert("detection_method".to_string(), "layout_analysis".to_string());
                 metadata.insert("screen_width".to_string(), screen_width.to_string());
                 metadata.insert("screen_height".to_string(), screen_height.to_string());
+                if let Some(screen_id) = layout_analysis.screen_id {
+                    metadata.insert("screen_id".to_string(), screen_id.to_string());
+                }
+                
+                // Available actions, so a click can be matched to the button it hit
+                let buttons = self.layout_analyzer.find_buttons(&group_bbox, &group);
+                if !buttons.is_empty() {
+                    metadata.insert(DialogButton::METADATA_KEY.to_string(), serde_json::to_string(&buttons)?);
+                }
                 
                 let event = ErrorModalEvent {
                     id: uuid::Uuid::new_v4().to_string(),
@@ -534,10 +736,7 @@ impl ErrorModalDetector {
                     continue;
                 }
                 
-                let distance = self.calculate_spatial_distance(
-                    &ocr_results[i].roi,
-                    &ocr_results[j].roi,
-                );
+                let distance = ocr_results[i].roi.center_distance(&ocr_results[j].roi);
                 
                 // If close enough, add to group
                 if distance < 100.0 { // Configurable threshold
@@ -552,48 +751,10 @@ impl ErrorModalDetector {
         groups
     }
     
-    /// Calculate spatial distance between two bounding boxes
-    fn calculate_spatial_distance(&self, bbox1: &BoundingBox, bbox2: &BoundingBox) -> f32 {
-        let center1_x = bbox1.x + bbox1.width / 2.0;
-        let center1_y = bbox1.y + bbox1.height / 2.0;
-        let center2_x = bbox2.x + bbox2.width / 2.0;
-        let center2_y = bbox2.y + bbox2.height / 2.0;
-        
-        let dx = center1_x - center2_x;
-        let dy = center1_y - center2_y;
-        
-        (dx * dx + dy * dy).sqrt()
-    }
-    
     /// Calculate bounding box that encompasses a group of OCR results
     fn calculate_group_bounding_box(&self, group: &[&OCRResult]) -> BoundingBox {
-        if group.is_empty() {
-            return BoundingBox {
-                x: 0.0,
-                y: 0.0,
-                width: 0.0,
-                height: 0.0,
-            };
-        }
-        
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        let mut max_x = f32::MIN;
-        let mut max_y = f32::MIN;
-        
-        for result in group {
-            min_x = min_x.min(result.roi.x);
-            min_y = min_y.min(result.roi.y);
-            max_x = max_x.max(result.roi.x + result.roi.width);
-            max_y = max_y.max(result.roi.y + result.roi.height);
-        }
-        
-        BoundingBox {
-            x: min_x,
-            y: min_y,
-            width: max_x - min_x,
-            height: max_y - min_y,
-        }
+        BoundingBox::enclosing(group.iter().map(|result| &result.roi))
+            .unwrap_or_else(|| BoundingBox::new(0.0, 0.0, 0.0, 0.0))
     }
     
     /// Classify dialog type based on content analysis
@@ -667,8 +828,8 @@ impl ErrorModalDetector {
         }
         
         // Fallback to first 50 characters
-        if text.len() > 50 {
-            format!("{}...", &text[..47])
+        if text.chars().count() > 50 {
+            format!("{}...", text.chars().take(47).collect::<String>())
         } else {
             text.to_string()
         }
@@ -676,9 +837,87 @@ impl ErrorModalDetector {
     
     /// Group related elements that might belong to the same dialog
     fn group_related_elements(&self, events: Vec<ErrorModalEvent>) -> Result<Vec<ErrorModalEvent>> {
-        // For now, return events as-is
-        // In a more sophisticated implementation, we would merge overlapping or nearby events
-        Ok(events)
+        // A dialog yields one event per matching line plus one for its layout;
+        // cluster events with overlapping ROIs and close timestamps
+        let mut clusters: Vec<Vec<ErrorModalEvent>> = Vec::new();
+        
+        for event in events {
+            let (related, unrelated): (Vec<_>, Vec<_>) = clusters
+                .into_iter()
+                .partition(|cluster| cluster.iter().any(|member| self.are_related(member, &event)));
+            clusters = unrelated;
+            
+            // The event may bridge several clusters
+            let mut merged: Vec<ErrorModalEvent> = related.into_iter().flatten().collect();
+            merged.push(event);
+            clusters.push(merged);
+        }
+        
+        Ok(clusters.into_iter().map(Self::merge_cluster).collect())
+    }
+    
+    /// Whether two events show the same dialog
+    fn are_related(&self, a: &ErrorModalEvent, b: &ErrorModalEvent) -> bool {
+        if (a.timestamp - b.timestamp).num_milliseconds().abs() > self.config.merge_window_ms {
+            return false;
+        }
+        
+        let gap = self.config.merge_gap_px;
+        a.roi.expand(gap).intersects(&b.roi)
+    }
+    
+    /// Combine a cluster into one event led by its most severe, then most confident, member
+    fn merge_cluster(mut cluster: Vec<ErrorModalEvent>) -> ErrorModalEvent {
+        if cluster.len() == 1 {
+            return cluster.remove(0);
+        }
+        
+        cluster.sort_by(|a, b| {
+            b.severity.rank().cmp(&a.severity.rank())
+                .then_with(|| b.confidence.total_cmp(&a.confidence))
+        });
+        let count = cluster.len();
+        let mut members = cluster.into_iter();
+        let mut merged = members.next().expect("cluster is not empty");
+        let others: Vec<ErrorModalEvent> = members.collect();
+        
+        // Messages in reading order; lines already part of a combined layout message are dropped
+        let mut messages: Vec<(BoundingBox, String)> = std::iter::once((merged.roi.clone(), merged.message.clone()))
+            .chain(others.iter().map(|e| (e.roi.clone(), e.message.clone())))
+            .collect();
+        messages.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+        let mut kept: Vec<(BoundingBox, String)> = Vec::new();
+        for (roi, message) in messages {
+            if !kept.iter().any(|(_, k)| k.contains(message.as_str())) {
+                kept.push((roi, message));
+            }
+        }
+        kept.sort_by(|a, b| a.0.y.total_cmp(&b.0.y).then_with(|| a.0.x.total_cmp(&b.0.x)));
+        merged.message = kept.into_iter().map(|(_, m)| m).collect::<Vec<_>>().join("\n");
+        
+        for other in others {
+            merged.timestamp = merged.timestamp.min(other.timestamp);
+            merged.confidence = merged.confidence.max(other.confidence);
+            merged.roi = merged.roi.union(&other.roi);
+            for (key, value) in other.metadata {
+                merged.metadata.entry(key).or_insert(value);
+            }
+            for pattern in other.pattern_matches {
+                let known = merged.pattern_matches.iter().any(|p| {
+                    p.pattern_type == pattern.pattern_type && p.matched_text == pattern.matched_text
+                });
+                if !known {
+                    merged.pattern_matches.push(pattern);
+                }
+            }
+            if merged.layout_analysis.is_none() {
+                merged.layout_analysis = other.layout_analysis;
+            }
+        }
+        merged.metadata.insert("merged_event_count".to_string(), count.to_string());
+        
+        debug!("Merged {} overlapping error/modal events into {}", count, merged.id);
+        merged
     }
     
     /// Compile error detection patterns
@@ -829,8 +1068,7 @@ impl DialogLayoutAnalyzer {
             && dialog_height <= screen_height * self.config.max_dialog_height_ratio;
         
         // Check if dialog is centered
-        let center_x = roi.x + roi.width / 2.0;
-        let center_y = roi.y + roi.height / 2.0;
+        let (center_x, center_y) = roi.center();
         let screen_center_x = screen_width / 2.0;
         let screen_center_y = screen_height / 2.0;
         
@@ -861,8 +1099,8 @@ impl DialogLayoutAnalyzer {
         // Position check (not at screen edges)
         let margin = 50.0;
         if roi.x > margin && roi.y > margin 
-            && roi.x + roi.width < screen_width - margin
-            && roi.y + roi.height < screen_height - margin {
+            && roi.right() < screen_width - margin
+            && roi.bottom() < screen_height - margin {
             confidence += 0.1;
         }
         
@@ -876,7 +1114,58 @@ impl DialogLayoutAnalyzer {
             center_y_ratio,
             is_centered,
             layout_confidence: confidence,
+            screen_id: None,
+        }
+    }
+    
+    /// Button row of a dialog: the lowest row of short, horizontally aligned
+    /// labels in the bottom part of the dialog, left to right
+    fn find_buttons(&self, dialog: &BoundingBox, ocr_results: &[&OCRResult]) -> Vec<DialogButton> {
+        let bottom_band = dialog.y + dialog.height * 0.6;
+        let mut candidates: Vec<&OCRResult> = ocr_results
+            .iter()
+            .copied()
+            .filter(|r| {
+                let (center_x, center_y) = r.roi.center();
+                let text = r.text.trim();
+                center_y >= bottom_band
+                    && dialog.contains_point(center_x, center_y)
+                    && !text.is_empty()
+                    && text.chars().count() <= 20
+                    && text.split_whitespace().count() <= 3
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.roi.center().1.total_cmp(&b.roi.center().1));
+        
+        // Tokens whose vertical centers are within half a line of each other share a row
+        let mut rows: Vec<Vec<&OCRResult>> = Vec::new();
+        for candidate in candidates {
+            let center_y = candidate.roi.center().1;
+            let joins_last = rows.last().is_some_and(|row: &Vec<&OCRResult>| {
+                let last = row[row.len() - 1];
+                let tolerance = last.roi.height.max(candidate.roi.height) / 2.0;
+                (center_y - last.roi.center().1).abs() <= tolerance
+            });
+            match rows.last_mut() {
+                Some(row) if joins_last => row.push(candidate),
+                _ => rows.push(vec![candidate]),
+            }
         }
+        
+        let Some(mut row) = rows
+            .into_iter()
+            .rev()
+            .find(|row| row.len() >= 2 || row.iter().any(|r| DialogButton::is_known_label(&r.text)))
+        else {
+            return Vec::new();
+        };
+        row.sort_by(|a, b| a.roi.x.total_cmp(&b.roi.x));
+        row.into_iter()
+            .map(|r| DialogButton {
+                label: r.text.trim().to_string(),
+                roi: r.roi.clone(),
+            })
+            .collect()
     }
 }
 
@@ -970,4 +1259,81 @@ mod tests {
             SeverityLevel::Info
         );
     }
+    
+    #[test]
+    fn test_button_row_detection() {
+        let analyzer = DialogLayoutAnalyzer::new(ErrorModalDetectionConfig::default());
+        let line = |text: &str, x: f32, y: f32, width: f32| OCRResult {
+            frame_id: "frame_1".to_string(),
+            roi: BoundingBox::new(x, y, width, 28.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "test".to_string(),
+        };
+        let dialog = BoundingBox::new(300.0, 200.0, 400.0, 200.0);
+        let results = vec![
+            line("Connection failed", 320.0, 210.0, 300.0),
+            line("The server could not be reached", 320.0, 260.0, 360.0),
+            line("Retry", 600.0, 362.0, 80.0),
+            line("Cancel", 500.0, 360.0, 80.0),
+        ];
+        let refs: Vec<&OCRResult> = results.iter().collect();
+        
+        let buttons = analyzer.find_buttons(&dialog, &refs);
+        let labels: Vec<&str> = buttons.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["Cancel", "Retry"]);
+        
+        // A lone known label is a button row; lone other text is not
+        assert_eq!(analyzer.find_buttons(&dialog, &refs[..1]).len(), 0);
+        assert_eq!(analyzer.find_buttons(&dialog, &[refs[0], refs[2]]).len(), 1);
+        
+        let mut metadata = HashMap::new();
+        metadata.insert(DialogButton::METADATA_KEY.to_string(), serde_json::to_string(&buttons).unwrap());
+        let stored = DialogButton::from_metadata(&metadata);
+        assert_eq!(DialogButton::at(&stored, 640.0, 375.0).map(|b| b.label.as_str()), Some("Retry"));
+        assert!(DialogButton::at(&stored, 400.0, 375.0).is_none());
+    }
+    
+    #[test]
+    fn test_overlapping_events_are_merged() {
+        let detector = ErrorModalDetector::new().unwrap();
+        let timestamp = Utc::now();
+        let event = |message: &str, event_type: ErrorModalType, severity: SeverityLevel, roi: BoundingBox, confidence: f32| ErrorModalEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type,
+            severity,
+            title: message.to_string(),
+            message: message.to_string(),
+            confidence,
+            frame_id: "frame_1".to_string(),
+            roi,
+            metadata: HashMap::new(),
+            pattern_matches: Vec::new(),
+            layout_analysis: None,
+        };
+        
+        let events = vec![
+            // Dialog layout covering both lines, plus one event per line
+            event("Fatal error Connection lost", ErrorModalType::ApplicationError, SeverityLevel::High, BoundingBox::new(300.0, 200.0, 400.0, 200.0), 0.7),
+            event("Fatal error", ErrorModalType::SystemError, SeverityLevel::Critical, BoundingBox::new(320.0, 220.0, 200.0, 30.0), 0.9),
+            event("Connection lost", ErrorModalType::NetworkError, SeverityLevel::High, BoundingBox::new(320.0, 300.0, 250.0, 30.0), 0.8),
+            // Unrelated toast elsewhere on screen
+            event("Warning: Low battery", ErrorModalType::Warning, SeverityLevel::Medium, BoundingBox::new(10.0, 10.0, 150.0, 20.0), 0.75),
+        ];
+        
+        let grouped = detector.group_related_elements(events).unwrap();
+        assert_eq!(grouped.len(), 2);
+        
+        let dialog = grouped.iter().find(|e| e.metadata.contains_key("merged_event_count")).unwrap();
+        assert_eq!(dialog.metadata["merged_event_count"], "3");
+        assert_eq!(dialog.severity, SeverityLevel::Critical);
+        assert_eq!(dialog.event_type, ErrorModalType::SystemError);
+        assert_eq!(dialog.title, "Fatal error");
+        assert_eq!(dialog.message, "Fatal error Connection lost");
+        assert_eq!(dialog.confidence, 0.9);
+        assert_eq!(dialog.roi, BoundingBox::new(300.0, 200.0, 400.0, 200.0));
+    }
 }
\ No newline at end of file
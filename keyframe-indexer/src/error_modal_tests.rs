@@ -715,4 +715,34 @@ mod tests {
         // Should detect with high OCR confidence
         assert!(!events.is_empty(), "Should detect with high OCR confidence");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_notification_banner_detection() {
+        let detector = ErrorModalDetector::new().unwrap();
+        let timestamp = Utc::now();
+        let ocr_results = vec![
+            create_ocr_result("SLACK", BoundingBox::new(2200.0, 90.0, 120.0, 24.0), 0.9),
+            create_ocr_result("now", BoundingBox::new(2780.0, 90.0, 50.0, 24.0), 0.9),
+            create_ocr_result("Deploy failed", BoundingBox::new(2200.0, 122.0, 260.0, 28.0), 0.95),
+            create_ocr_result("Pipeline #42 failed on main", BoundingBox::new(2200.0, 156.0, 480.0, 26.0), 0.9),
+        ];
+        
+        let events = detector.detect_errors_and_modals(
+            "test_frame",
+            &ocr_results,
+            timestamp,
+            2880.0,
+            1800.0,
+        ).unwrap();
+        
+        // The banner's lines are not also reported as error messages
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.event_type, ErrorModalType::SystemNotification);
+        assert_eq!(event.metadata.get("app_name").map(String::as_str), Some("Slack"));
+        assert_eq!(event.metadata.get("shown_time").map(String::as_str), Some("now"));
+        assert_eq!(event.title, "Deploy failed");
+        assert_eq!(event.severity, SeverityLevel::High);
+        assert!(event.confidence >= 0.9);
+    }
+}
@@ -0,0 +1,654 @@
+use crate::error::Result;
+use crate::event_correlator::{CorrelationEvent, CorrelationEventType, CorrelationResult, CorrelationType};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::layout_compat::{self, CompatibleTable};
+use crate::modal_lifetime;
+use arrow::array::{
+    Array, BooleanArray, Int64Array, ListBuilder, StringArray, StringBuilder, TimestampNanosecondArray,
+    TimestampNanosecondBuilder, UInt32Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Duration, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Configuration for following errors to their resolution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ErrorRecoveryConfig {
+    /// Follow errors in the events the service publishes
+    pub enabled: bool,
+    /// Directory of the recovery Parquet files; defaults to `<output_dir>/error_recovery`
+    pub dir: Option<String>,
+    /// Actions later than this after an error (ms) no longer count towards it
+    pub max_recovery_ms: i64,
+    /// The same error showing again within this time (ms) is a recurrence
+    pub recurrence_window_ms: i64,
+}
+
+impl Default for ErrorRecoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            max_recovery_ms: 60_000,
+            recurrence_window_ms: 300_000,
+        }
+    }
+}
+
+/// What the user did after an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryActionKind {
+    /// Submitted again
+    Retry,
+    /// Clicked the error away
+    Dismiss,
+    /// Changed a field value
+    ValueCorrection,
+    /// Left for another page, tab or window
+    Navigation,
+}
+
+impl RecoveryActionKind {
+    fn of(event_type: &EventType) -> Option<Self> {
+        match event_type {
+            EventType::FormSubmission => Some(Self::Retry),
+            EventType::FieldChange | EventType::ClipboardPaste | EventType::DataEntry => Some(Self::ValueCorrection),
+            EventType::Navigation => Some(Self::Navigation),
+            _ => None,
+        }
+    }
+
+    /// What clicking a dialog button does: retry buttons retry, anything else dismisses
+    pub fn of_button(label: &str) -> Self {
+        let label = label.trim().to_lowercase();
+        if label == "retry" || label == "try again" {
+            Self::Retry
+        } else {
+            Self::Dismiss
+        }
+    }
+
+    /// Whether the action ends the error; value corrections lead up to a retry
+    pub fn resolves(&self) -> bool {
+        !matches!(self, Self::ValueCorrection)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Retry => "retry",
+            Self::Dismiss => "dismiss",
+            Self::ValueCorrection => "value_correction",
+            Self::Navigation => "navigation",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "retry" => Some(Self::Retry),
+            "dismiss" => Some(Self::Dismiss),
+            "value_correction" => Some(Self::ValueCorrection),
+            "navigation" => Some(Self::Navigation),
+            _ => None,
+        }
+    }
+}
+
+/// One user action following an error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryAction {
+    pub event_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: RecoveryActionKind,
+    pub target: String,
+}
+
+/// An error and what the user did about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorRecoverySequence {
+    pub recovery_id: String,
+    pub error_event_id: String,
+    pub error_at: DateTime<Utc>,
+    /// Normalized error text; equal signatures mean the same error
+    pub error_signature: String,
+    pub error_message: String,
+    /// Error type and severity from the error/modal detector, when known
+    pub error_type: Option<String>,
+    pub severity: Option<String>,
+    pub frame_id: String,
+    /// Actions up to and including the resolving one, in time order
+    pub actions: Vec<RecoveryAction>,
+    /// Action that ended the error; `None` if the user never resolved it
+    pub resolution: Option<RecoveryActionKind>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// The same error showed again within the recurrence window
+    pub recurred: bool,
+    /// Earlier occurrences of the same error, each within the recurrence window of the next
+    pub prior_occurrences: u32,
+}
+
+impl ErrorRecoverySequence {
+    pub fn time_to_recover_ms(&self) -> Option<i64> {
+        self.resolved_at.map(|at| (at - self.error_at).num_milliseconds())
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.resolution.is_some()
+    }
+
+    /// Text identifying an error across frames: detector type plus the message
+    /// with digits and whitespace runs normalized
+    pub fn signature_of(event: &DetectedEvent) -> String {
+        // Dismissal events carry the text of the dialog that went away in `value_from`
+        let message = event.value_to.as_deref().or(event.value_from.as_deref()).unwrap_or(&event.target);
+        let mut normalized = String::with_capacity(message.len());
+        for word in message.split_whitespace() {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            normalized.extend(word.chars().map(|c| if c.is_ascii_digit() { '#' } else { c.to_ascii_lowercase() }));
+        }
+        match event.metadata.get("error_modal_type") {
+            Some(error_type) => format!("{}:{}", error_type, normalized),
+            None => normalized,
+        }
+    }
+
+    /// End of the period in which the user could still act on the error
+    fn settled_at(&self, config: &ErrorRecoveryConfig) -> DateTime<Utc> {
+        self.resolved_at.unwrap_or(self.error_at + Duration::milliseconds(config.max_recovery_ms))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Occurrences {
+    count: u32,
+    last_seen: DateTime<Utc>,
+}
+
+/// Follows each detected error through the user's next actions: value
+/// corrections, then a retry, dismissal or navigation that resolves it.
+///
+/// A sequence is emitted once the same error could no longer recur within
+/// `recurrence_window_ms`, so `recurred` is final when it is written. Events
+/// must be added in time order.
+pub struct ErrorRecoveryAnalyzer {
+    config: ErrorRecoveryConfig,
+    pending: Vec<ErrorRecoverySequence>,
+    occurrences: HashMap<String, Occurrences>,
+}
+
+impl ErrorRecoveryAnalyzer {
+    pub fn new(config: ErrorRecoveryConfig) -> Self {
+        Self {
+            config,
+            pending: Vec::new(),
+            occurrences: HashMap::new(),
+        }
+    }
+
+    /// Add detected events, e.g. a segment's events from the event detector
+    pub fn add_events(&mut self, events: &[DetectedEvent]) {
+        let mut ordered: Vec<&DetectedEvent> = events.iter().collect();
+        ordered.sort_by_key(|e| e.timestamp);
+
+        for event in ordered {
+            if modal_lifetime::dismissed_type(event) == Some(EventType::ErrorDisplay) {
+                self.add_dismissal(event);
+            } else if event.event_type == EventType::ErrorDisplay {
+                self.add_error(event);
+            } else if let Some(kind) = RecoveryActionKind::of(&event.event_type) {
+                self.add_action(RecoveryAction {
+                    event_id: event.id.clone(),
+                    timestamp: event.timestamp,
+                    kind,
+                    target: event.target.clone(),
+                });
+            }
+        }
+    }
+
+    /// Add the correlator's error-recovery correlations; a click correlated with
+    /// a pending error dismisses it, or retries when it hit a retry button
+    pub fn add_correlations<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a CorrelationEvent>,
+        correlations: &[CorrelationResult],
+    ) {
+        let known: HashMap<&str, &CorrelationEvent> = events.into_iter().map(|e| (e.id.as_str(), e)).collect();
+
+        for correlation in correlations.iter().filter(|c| c.correlation_type == CorrelationType::ErrorRecovery) {
+            let Some(index) = self.pending.iter().position(|s| {
+                !s.is_resolved() && correlation.correlated_events.contains(&s.error_event_id)
+            }) else {
+                continue;
+            };
+            let click = correlation
+                .correlated_events
+                .iter()
+                .filter_map(|id| known.get(id.as_str()))
+                .find(|e| e.event_type == CorrelationEventType::CursorClick);
+            if let Some(click) = click {
+                let button = correlation.evidence.clicked_button.as_deref();
+                let action = RecoveryAction {
+                    event_id: click.id.clone(),
+                    timestamp: click.timestamp,
+                    kind: button.map_or(RecoveryActionKind::Dismiss, RecoveryActionKind::of_button),
+                    target: button.map_or_else(|| click.frame_id.clone(), str::to_string),
+                };
+                Self::record(&mut self.pending[index], action, &self.config);
+            }
+        }
+    }
+
+    /// Emit sequences that can no longer change as of `now`
+    pub fn take_completed(&mut self, now: DateTime<Utc>) -> Vec<ErrorRecoverySequence> {
+        let window = Duration::milliseconds(self.config.recurrence_window_ms);
+        let config = self.config.clone();
+        let (done, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|s| s.recurred || s.settled_at(&config) + window < now);
+        self.pending = pending;
+        self.occurrences.retain(|_, o| o.last_seen + window >= now);
+        done
+    }
+
+    /// Emit every pending sequence, e.g. at the end of a session
+    pub fn flush(&mut self) -> Vec<ErrorRecoverySequence> {
+        self.occurrences.clear();
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Errors still being followed
+    pub fn pending_errors(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn add_error(&mut self, event: &DetectedEvent) {
+        let signature = ErrorRecoverySequence::signature_of(event);
+        let window = Duration::milliseconds(self.config.recurrence_window_ms);
+
+        // The detector reports a visible error on every frame; until the user
+        // resolves it, those are the same error
+        let still_showing = self.pending.iter().any(|s| {
+            s.error_signature == signature
+                && !s.is_resolved()
+                && event.timestamp <= s.settled_at(&self.config)
+        });
+        if still_showing {
+            return;
+        }
+
+        for sequence in self.pending.iter_mut().filter(|s| s.error_signature == signature && !s.recurred) {
+            if event.timestamp <= sequence.settled_at(&self.config) + window {
+                sequence.recurred = true;
+                debug!("Error '{}' recurred after {:?}", signature, sequence.resolution.map(|r| r.as_str()));
+            }
+        }
+
+        let prior_occurrences = match self.occurrences.get(&signature) {
+            Some(o) if event.timestamp <= o.last_seen + window => o.count,
+            _ => 0,
+        };
+        self.occurrences.insert(signature.clone(), Occurrences {
+            count: prior_occurrences + 1,
+            last_seen: event.timestamp,
+        });
+
+        self.pending.push(ErrorRecoverySequence {
+            recovery_id: uuid::Uuid::new_v4().to_string(),
+            error_event_id: event.id.clone(),
+            error_at: event.timestamp,
+            error_signature: signature,
+            error_message: event.value_to.clone().unwrap_or_else(|| event.target.clone()),
+            error_type: event.metadata.get("error_modal_type").cloned(),
+            severity: event.metadata.get("severity").cloned(),
+            frame_id: event.evidence_frames.first().cloned().unwrap_or_default(),
+            actions: Vec::new(),
+            resolution: None,
+            resolved_at: None,
+            recurred: false,
+            prior_occurrences,
+        });
+    }
+
+    /// The error's dialog went away without a recognized action
+    fn add_dismissal(&mut self, event: &DetectedEvent) {
+        let signature = ErrorRecoverySequence::signature_of(event);
+        let action = RecoveryAction {
+            event_id: event.id.clone(),
+            timestamp: event.timestamp,
+            kind: RecoveryActionKind::Dismiss,
+            target: event.target.clone(),
+        };
+        for sequence in self.pending.iter_mut().filter(|s| s.error_signature == signature) {
+            Self::record(sequence, action.clone(), &self.config);
+        }
+    }
+
+    fn add_action(&mut self, action: RecoveryAction) {
+        for sequence in &mut self.pending {
+            Self::record(sequence, action.clone(), &self.config);
+        }
+    }
+
+    fn record(sequence: &mut ErrorRecoverySequence, action: RecoveryAction, config: &ErrorRecoveryConfig) {
+        if sequence.is_resolved()
+            || action.timestamp < sequence.error_at
+            || action.timestamp > sequence.settled_at(config)
+            || sequence.actions.iter().any(|a| a.event_id == action.event_id)
+        {
+            return;
+        }
+
+        if action.kind.resolves() {
+            sequence.resolution = Some(action.kind);
+            sequence.resolved_at = Some(action.timestamp);
+        }
+        sequence.actions.push(action);
+    }
+}
+
+/// Parquet output for error recovery sequences, one row per error
+pub struct ErrorRecoveryParquetWriter {
+    output_dir: PathBuf,
+    schema: Arc<Schema>,
+    batch_size: usize,
+    current_batch: Vec<ErrorRecoverySequence>,
+}
+
+impl ErrorRecoveryParquetWriter {
+    pub fn new(output_dir: &str) -> Result<Self> {
+        let output_path = PathBuf::from(output_dir);
+        std::fs::create_dir_all(&output_path)?;
+
+        let string_list = || DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("recovery_id", DataType::Utf8, false),
+            Field::new("error_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("error_event_id", DataType::Utf8, false),
+            Field::new("error_signature", DataType::Utf8, false),
+            Field::new("error_message", DataType::Utf8, false),
+            Field::new("error_type", DataType::Utf8, true),
+            Field::new("severity", DataType::Utf8, true),
+            Field::new("frame_id", DataType::Utf8, false),
+            Field::new("action_kinds", string_list(), false),
+            Field::new("resolution", DataType::Utf8, true),
+            Field::new("resolved_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), true),
+            Field::new("time_to_recover_ms", DataType::Int64, true),
+            Field::new("recurred", DataType::Boolean, false),
+            Field::new("prior_occurrences", DataType::UInt32, false),
+            Field::new("actions", DataType::Utf8, false), // JSON-encoded actions
+        ]));
+
+        Ok(Self {
+            output_dir: output_path,
+            schema,
+            batch_size: 200,
+            current_batch: Vec::new(),
+        })
+    }
+
+    pub async fn write_sequences(&mut self, sequences: &[ErrorRecoverySequence]) -> Result<()> {
+        self.current_batch.extend_from_slice(sequences);
+        if self.current_batch.len() >= self.batch_size {
+            self.flush_batch().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered sequences to disk
+    pub async fn flush_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%f");
+        let file_path = self.output_dir.join(format!("error_recoveries_{}.parquet", timestamp));
+
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        let mut writer = ArrowWriter::try_new(File::create(&file_path)?, self.schema.clone(), Some(props))?;
+        writer.write(&record_batch)?;
+        writer.close()?;
+
+        info!("Wrote {} error recoveries to {}", self.current_batch.len(), file_path.display());
+        self.current_batch.clear();
+        Ok(())
+    }
+
+    pub async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch().await
+    }
+
+    /// Errors the user never resolved, oldest first
+    pub async fn query_unresolved(&self) -> Result<Vec<ErrorRecoverySequence>> {
+        self.run_query("SELECT * FROM error_recoveries WHERE resolution IS NULL ORDER BY error_ns ASC").await
+    }
+
+    /// Errors that showed again after the user acted on them, oldest first
+    pub async fn query_recurring(&self) -> Result<Vec<ErrorRecoverySequence>> {
+        self.run_query("SELECT * FROM error_recoveries WHERE recurred ORDER BY error_ns ASC").await
+    }
+
+    /// Every occurrence of one error, oldest first
+    pub async fn query_by_signature(&self, signature: &str) -> Result<Vec<ErrorRecoverySequence>> {
+        self.run_query(&format!(
+            "SELECT * FROM error_recoveries WHERE error_signature = '{}' ORDER BY error_ns ASC",
+            signature.replace('\'', "''")
+        )).await
+    }
+
+    /// Errors shown within a time range, oldest first
+    pub async fn query_by_time_range(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<ErrorRecoverySequence>> {
+        self.run_query(&format!(
+            "SELECT * FROM error_recoveries WHERE error_ns >= {} AND error_ns <= {} ORDER BY error_ns ASC",
+            start_time.timestamp_nanos_opt().unwrap_or(0),
+            end_time.timestamp_nanos_opt().unwrap_or(0)
+        )).await
+    }
+
+    /// Register the stored sequences as the `error_recoveries` table
+    pub async fn register_recoveries(&self, ctx: &SessionContext) -> Result<()> {
+        CompatibleTable::new("error_recoveries", self.schema.clone())
+            .register(ctx, &self.output_dir)
+            .await
+    }
+
+    pub fn get_output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    // MARK: - Private Helper Methods
+
+    async fn run_query(&self, sql: &str) -> Result<Vec<ErrorRecoverySequence>> {
+        if layout_compat::parquet_files(&self.output_dir)?.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = SessionContext::new();
+        self.register_recoveries(&ctx).await?;
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let mut sequences = Vec::new();
+        for batch in &batches {
+            sequences.extend(recovery_sequences_from_batch(batch)?);
+        }
+        Ok(sequences)
+    }
+
+    fn create_record_batch(&self, sequences: &[ErrorRecoverySequence]) -> Result<RecordBatch> {
+        let mut error_builder = TimestampNanosecondBuilder::new();
+        let mut resolved_builder = TimestampNanosecondBuilder::new();
+        let mut kinds_builder = ListBuilder::new(StringBuilder::new());
+        let mut actions_json = Vec::with_capacity(sequences.len());
+
+        for sequence in sequences {
+            error_builder.append_value(sequence.error_at.timestamp_nanos_opt().unwrap_or(0));
+            resolved_builder.append_option(sequence.resolved_at.and_then(|at| at.timestamp_nanos_opt()));
+            for action in &sequence.actions {
+                kinds_builder.values().append_value(action.kind.as_str());
+            }
+            kinds_builder.append(true);
+            actions_json.push(serde_json::to_string(&sequence.actions)?);
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.recovery_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(error_builder.finish()),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.error_event_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.error_signature.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.error_message.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.error_type.as_deref()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.severity.as_deref()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.frame_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(kinds_builder.finish()),
+                Arc::new(StringArray::from(sequences.iter().map(|s| s.resolution.map(|r| r.as_str())).collect::<Vec<_>>())),
+                Arc::new(resolved_builder.finish()),
+                Arc::new(Int64Array::from(sequences.iter().map(|s| s.time_to_recover_ms()).collect::<Vec<_>>())),
+                Arc::new(BooleanArray::from(sequences.iter().map(|s| s.recurred).collect::<Vec<_>>())),
+                Arc::new(UInt32Array::from(sequences.iter().map(|s| s.prior_occurrences).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(actions_json)),
+            ],
+        )?;
+
+        Ok(record_batch)
+    }
+}
+
+/// Recovery sequences of a batch read back from a recoveries file; a batch
+/// with unexpected columns is an error rather than a panic
+pub fn recovery_sequences_from_batch(batch: &RecordBatch) -> Result<Vec<ErrorRecoverySequence>> {
+    let mut sequences = Vec::with_capacity(batch.num_rows());
+
+    let ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let error_times = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let error_event_ids = layout_compat::column::<StringArray>(batch, 2)?;
+    let signatures = layout_compat::column::<StringArray>(batch, 3)?;
+    let messages = layout_compat::column::<StringArray>(batch, 4)?;
+    let error_types = layout_compat::column::<StringArray>(batch, 5)?;
+    let severities = layout_compat::column::<StringArray>(batch, 6)?;
+    let frame_ids = layout_compat::column::<StringArray>(batch, 7)?;
+    let resolutions = layout_compat::column::<StringArray>(batch, 9)?;
+    let resolved_times = layout_compat::column::<TimestampNanosecondArray>(batch, 10)?;
+    let recurred = layout_compat::column::<BooleanArray>(batch, 12)?;
+    let prior = layout_compat::column::<UInt32Array>(batch, 13)?;
+    let actions = layout_compat::column::<StringArray>(batch, 14)?;
+
+    let optional = |array: &StringArray, i: usize| (!array.is_null(i)).then(|| array.value(i).to_string());
+    for i in 0..batch.num_rows() {
+        let sequence_actions = serde_json::from_str(actions.value(i)).unwrap_or_else(|e| {
+            warn!("Failed to parse actions of error recovery {}: {}", ids.value(i), e);
+            Vec::new()
+        });
+
+        sequences.push(ErrorRecoverySequence {
+            recovery_id: ids.value(i).to_string(),
+            error_event_id: error_event_ids.value(i).to_string(),
+            error_at: DateTime::from_timestamp_nanos(error_times.value(i)),
+            error_signature: signatures.value(i).to_string(),
+            error_message: messages.value(i).to_string(),
+            error_type: optional(error_types, i),
+            severity: optional(severities, i),
+            frame_id: frame_ids.value(i).to_string(),
+            actions: sequence_actions,
+            resolution: optional(resolutions, i).and_then(|r| RecoveryActionKind::from_name(&r)),
+            resolved_at: (!resolved_times.is_null(i)).then(|| DateTime::from_timestamp_nanos(resolved_times.value(i))),
+            recurred: recurred.value(i),
+            prior_occurrences: prior.value(i),
+        });
+    }
+
+    Ok(sequences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn detected(id: &str, event_type: EventType, value: Option<&str>, at: DateTime<Utc>) -> DetectedEvent {
+        DetectedEvent {
+            id: id.to_string(),
+            timestamp: at,
+            event_type,
+            target: id.to_string(),
+            value_from: None,
+            value_to: value.map(str::to_string),
+            confidence: 0.9,
+            evidence_frames: vec![format!("frame_{}", id)],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_followed_to_resolution_and_stored() {
+        let start = Utc::now();
+        let at = |ms: i64| start + Duration::milliseconds(ms);
+
+        let mut analyzer = ErrorRecoveryAnalyzer::new(ErrorRecoveryConfig::default());
+        analyzer.add_events(&[
+            detected("error_1", EventType::ErrorDisplay, Some("Invalid code 4411"), at(0)),
+            // Same error on the next frame
+            detected("error_1b", EventType::ErrorDisplay, Some("Invalid code 4411"), at(500)),
+            detected("code", EventType::FieldChange, Some("4412"), at(2_000)),
+            detected("submit", EventType::FormSubmission, None, at(3_000)),
+            // The retry failed with the same error
+            detected("error_2", EventType::ErrorDisplay, Some("Invalid code 4412"), at(3_500)),
+            detected("away", EventType::Navigation, None, at(9_000)),
+        ]);
+        assert_eq!(analyzer.pending_errors(), 2);
+
+        // The recurred error is final; the second could still recur
+        let first = analyzer.take_completed(at(10_000));
+        assert_eq!(first.len(), 1);
+        let first = &first[0];
+        assert_eq!(first.error_event_id, "error_1");
+        assert_eq!(first.error_signature, "invalid code ####");
+        assert_eq!(
+            first.actions.iter().map(|a| a.kind).collect::<Vec<_>>(),
+            vec![RecoveryActionKind::ValueCorrection, RecoveryActionKind::Retry]
+        );
+        assert_eq!(first.resolution, Some(RecoveryActionKind::Retry));
+        assert_eq!(first.time_to_recover_ms(), Some(3_000));
+        assert!(first.recurred);
+
+        let second = analyzer.take_completed(at(400_000));
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].resolution, Some(RecoveryActionKind::Navigation));
+        assert_eq!(second[0].prior_occurrences, 1);
+        assert!(!second[0].recurred);
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = ErrorRecoveryParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        writer.write_sequences(&[first.clone(), second[0].clone()]).await.unwrap();
+        writer.finalize().await.unwrap();
+
+        assert_eq!(writer.query_recurring().await.unwrap(), vec![first.clone()]);
+        assert_eq!(writer.query_by_signature("invalid code ####").await.unwrap().len(), 2);
+        assert!(writer.query_unresolved().await.unwrap().is_empty());
+    }
+}
@@ -2,12 +2,21 @@ use crate::error::{IndexerError, Result};
 use crate::event_detector::{DetectedEvent, EventType};
 use crate::cursor_tracker::{CursorPosition, ClickEvent, MovementTrail};
 use crate::navigation_detector::{WindowState, TabState, FocusEvent};
-use crate::ocr_data::OCRResult;
+use crate::error_modal_detector::DialogButton;
+use crate::ocr_data::{BoundingBox, OCRResult};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{hash_map, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
+/// Format version of pattern library files
+const PATTERN_LIBRARY_VERSION: u32 = 1;
+/// Learned patterns seen at least this often are cited as correlation evidence
+const MIN_PATTERN_OCCURRENCES: u32 = 10;
+/// Timing samples kept per pattern
+const MAX_PATTERN_TIMINGS: usize = 100;
+
 /// Event correlator that links cursor actions with screen changes according to requirement 4.6
 pub struct EventCorrelator {
     /// Configuration for event correlation
@@ -18,10 +27,14 @@ pub struct EventCorrelator {
     correlation_patterns: HashMap<String, CorrelationPattern>,
     /// Maximum buffer size to maintain
     max_buffer_size: usize,
+    /// Event sets and correlation types already reported, with when they were;
+    /// events stay in the buffer across analyses, so each set is reported once
+    reported: HashMap<(Vec<String>, &'static str), DateTime<Utc>>,
 }
 
 /// Configuration for event correlation behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CorrelationConfig {
     /// Maximum time window for correlating events (milliseconds)
     pub max_correlation_window_ms: i64,
@@ -35,6 +48,8 @@ pub struct CorrelationConfig {
     pub enable_causal_correlation: bool,
     /// Spatial correlation radius (pixels)
     pub spatial_correlation_radius: f32,
+    /// JSON file learned patterns are loaded from on start and saved to on shutdown
+    pub pattern_library_path: Option<PathBuf>,
 }
 
 impl Default for CorrelationConfig {
@@ -46,6 +61,7 @@ impl Default for CorrelationConfig {
             enable_temporal_correlation: true,
             enable_causal_correlation: true,
             spatial_correlation_radius: 50.0,
+            pattern_library_path: None,
         }
     }
 }
@@ -71,9 +87,13 @@ pub enum CorrelationEventType {
     TabChange,
     FocusChange,
     FieldChange,
+    /// Form submitted or button pressed
+    FormSubmission,
     ScreenChange,
     ErrorDisplay,
     ModalAppearance,
+    /// Content scrolled between keyframes
+    Scroll,
 }
 
 /// Spatial information for correlation
@@ -86,6 +106,13 @@ pub struct SpatialInfo {
     pub screen_id: Option<i32>,
 }
 
+impl SpatialInfo {
+    /// Region of the event; a point when its size is unknown
+    pub fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(self.x, self.y, self.width.unwrap_or(0.0), self.height.unwrap_or(0.0))
+    }
+}
+
 /// Correlation pattern learned from historical data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrelationPattern {
@@ -97,6 +124,41 @@ pub struct CorrelationPattern {
     pub occurrence_count: u32,
 }
 
+impl CorrelationPattern {
+    /// Whether a time gap lies within the gaps this pattern was seen with
+    fn matches_timing(&self, temporal_proximity: i64) -> bool {
+        match (self.typical_timing.iter().min(), self.typical_timing.iter().max()) {
+            (Some(min), Some(max)) => (*min..=*max).contains(&temporal_proximity),
+            _ => false,
+        }
+    }
+
+    /// Fold another observation history of the same pattern into this one
+    fn merge(&mut self, other: CorrelationPattern) {
+        let total = self.occurrence_count + other.occurrence_count;
+        if total > 0 {
+            self.confidence = (self.confidence * self.occurrence_count as f32
+                + other.confidence * other.occurrence_count as f32) / total as f32;
+        }
+        self.occurrence_count = total;
+
+        // Older timings first so the most recent ones are kept
+        let mut timings = other.typical_timing;
+        timings.append(&mut self.typical_timing);
+        let excess = timings.len().saturating_sub(MAX_PATTERN_TIMINGS);
+        timings.drain(..excess);
+        self.typical_timing = timings;
+    }
+}
+
+/// Learned patterns as stored in the pattern library file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatternLibrary {
+    version: u32,
+    saved_at: DateTime<Utc>,
+    patterns: Vec<CorrelationPattern>,
+}
+
 /// Spatial relationship between correlated events
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SpatialRelationship {
@@ -127,6 +189,29 @@ pub enum CorrelationType {
     ErrorRecovery,          // Error followed by recovery actions
 }
 
+impl CorrelationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorrelationType::CursorToScreenChange => "cursor_to_screen_change",
+            CorrelationType::ScreenToCursorResponse => "screen_to_cursor_response",
+            CorrelationType::NavigationSequence => "navigation_sequence",
+            CorrelationType::InteractionWorkflow => "interaction_workflow",
+            CorrelationType::ErrorRecovery => "error_recovery",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cursor_to_screen_change" => Some(CorrelationType::CursorToScreenChange),
+            "screen_to_cursor_response" => Some(CorrelationType::ScreenToCursorResponse),
+            "navigation_sequence" => Some(CorrelationType::NavigationSequence),
+            "interaction_workflow" => Some(CorrelationType::InteractionWorkflow),
+            "error_recovery" => Some(CorrelationType::ErrorRecovery),
+            _ => None,
+        }
+    }
+}
+
 /// Evidence supporting the correlation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrelationEvidence {
@@ -134,6 +219,9 @@ pub struct CorrelationEvidence {
     pub spatial_proximity: Option<f32>, // Distance in pixels
     pub causal_strength: f32,       // Strength of causal relationship
     pub pattern_match: Option<String>, // Matching known pattern ID
+    /// Label of the dialog button a click landed on, for error recovery correlations
+    #[serde(default)]
+    pub clicked_button: Option<String>,
 }
 
 impl EventCorrelator {
@@ -144,12 +232,78 @@ impl EventCorrelator {
     
     /// Create a new event correlator with custom configuration
     pub fn with_config(config: CorrelationConfig) -> Self {
-        Self {
+        let mut correlator = Self {
             config,
             event_buffer: VecDeque::new(),
             correlation_patterns: HashMap::new(),
             max_buffer_size: 1000,
+            reported: HashMap::new(),
+        };
+        
+        // A missing or unreadable library only means starting without learned patterns
+        if let Some(path) = correlator.config.pattern_library_path.clone().filter(|p| p.exists()) {
+            if let Err(e) = correlator.load_patterns(&path) {
+                warn!("Failed to load correlation patterns from {}: {}", path.display(), e);
+            }
+        }
+        correlator
+    }
+    
+    /// Merge learned patterns from a library file; returns how many were read
+    pub fn load_patterns(&mut self, path: &Path) -> Result<usize> {
+        let library: PatternLibrary = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if library.version > PATTERN_LIBRARY_VERSION {
+            return Err(IndexerError::EventCorrelation(format!(
+                "Pattern library version {} is newer than supported version {}",
+                library.version, PATTERN_LIBRARY_VERSION
+            )));
+        }
+        
+        let count = library.patterns.len();
+        for pattern in library.patterns {
+            match self.correlation_patterns.get_mut(&pattern.pattern_id) {
+                Some(existing) => existing.merge(pattern),
+                None => {
+                    self.correlation_patterns.insert(pattern.pattern_id.clone(), pattern);
+                }
+            }
         }
+        info!("Loaded {} correlation patterns from {}", count, path.display());
+        Ok(count)
+    }
+    
+    /// Write learned patterns to a library file, replacing it atomically
+    pub fn save_patterns(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        let mut patterns: Vec<CorrelationPattern> = self.correlation_patterns.values().cloned().collect();
+        patterns.sort_by(|a, b| a.pattern_id.cmp(&b.pattern_id));
+        let library = PatternLibrary {
+            version: PATTERN_LIBRARY_VERSION,
+            saved_at: Utc::now(),
+            patterns,
+        };
+        
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&library)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        debug!("Saved {} correlation patterns to {}", library.patterns.len(), path.display());
+        Ok(())
+    }
+    
+    /// Save learned patterns to the configured library; returns whether one is configured
+    pub fn persist_patterns(&self) -> Result<bool> {
+        match &self.config.pattern_library_path {
+            Some(path) => self.save_patterns(path).map(|_| true),
+            None => Ok(false),
+        }
+    }
+    
+    /// Learned pattern of a correlation type, if any
+    pub fn get_pattern(&self, correlation_type: &CorrelationType) -> Option<&CorrelationPattern> {
+        self.correlation_patterns.get(&format!("{:?}", correlation_type))
     }
     
     /// Add cursor event for correlation analysis
@@ -279,7 +433,13 @@ impl EventCorrelator {
     pub fn add_detected_event(&mut self, detected_event: &DetectedEvent) {
         let event_type = match detected_event.event_type {
             EventType::FieldChange => CorrelationEventType::FieldChange,
+            EventType::FormSubmission => CorrelationEventType::FormSubmission,
+            EventType::Navigation if detected_event.metadata.get("navigation_type").map(String::as_str) == Some("scroll") => {
+                CorrelationEventType::Scroll
+            }
             EventType::Navigation => CorrelationEventType::ScreenChange,
+            // A dialog going away changes the screen rather than showing something
+            EventType::ModalDismissal => CorrelationEventType::ScreenChange,
             EventType::ErrorDisplay => CorrelationEventType::ErrorDisplay,
             EventType::ModalAppearance => CorrelationEventType::ModalAppearance,
             _ => CorrelationEventType::ScreenChange,
@@ -302,6 +462,7 @@ impl EventCorrelator {
     }
     
     /// Analyze correlations between recent events
+    #[tracing::instrument(name = "correlate", skip_all, fields(at = %current_timestamp))]
     pub fn analyze_correlations(&mut self, current_timestamp: DateTime<Utc>) -> Result<Vec<CorrelationResult>> {
         debug!("Analyzing correlations for {} events", self.event_buffer.len());
         
@@ -327,6 +488,39 @@ impl EventCorrelator {
             correlations.extend(self.analyze_causal_correlations(current_timestamp)?);
         }
         
+        // Pairs found by an earlier analysis are neither reported nor learned from again
+        correlations.retain(|correlation| {
+            let mut events = correlation.correlated_events.clone();
+            events.sort();
+            match self.reported.entry((events, correlation.correlation_type.as_str())) {
+                hash_map::Entry::Occupied(_) => false,
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(current_timestamp);
+                    true
+                }
+            }
+        });
+        
+        // Cite established patterns whose timing the correlation fits
+        for correlation in &mut correlations {
+            if correlation.evidence.pattern_match.is_some() {
+                continue;
+            }
+            let pattern_key = format!("{:?}", correlation.correlation_type);
+            let matches = self.correlation_patterns.get(&pattern_key).is_some_and(|pattern| {
+                pattern.occurrence_count >= MIN_PATTERN_OCCURRENCES
+                    && pattern.matches_timing(correlation.evidence.temporal_proximity)
+            });
+            if matches {
+                correlation.evidence.pattern_match = Some(pattern_key);
+            }
+        }
+        
+        // Name the dialog button behind clicks that answered an error or modal
+        for correlation in correlations.iter_mut().filter(|c| c.correlation_type == CorrelationType::ErrorRecovery) {
+            correlation.evidence.clicked_button = self.clicked_button(&correlation.correlated_events);
+        }
+        
         // Update correlation patterns based on findings
         self.update_correlation_patterns(&correlations);
         
@@ -371,6 +565,13 @@ impl EventCorrelator {
                 let event2 = events[j];
                 
                 if let (Some(spatial1), Some(spatial2)) = (&event1.spatial_info, &event2.spatial_info) {
+                    // Nearby coordinates on different displays are not related
+                    if let (Some(screen1), Some(screen2)) = (spatial1.screen_id, spatial2.screen_id) {
+                        if screen1 != screen2 {
+                            continue;
+                        }
+                    }
+                    
                     let distance = self.calculate_spatial_distance(spatial1, spatial2);
                     
                     if distance <= self.config.spatial_correlation_radius {
@@ -414,6 +615,10 @@ impl EventCorrelator {
             (CorrelationEventType::CursorClick, CorrelationEventType::ScreenChange) => CorrelationType::CursorToScreenChange,
             (CorrelationEventType::CursorClick, CorrelationEventType::WindowChange) => CorrelationType::CursorToScreenChange,
             (CorrelationEventType::CursorClick, CorrelationEventType::TabChange) => CorrelationType::CursorToScreenChange,
+            // Scroll-wheel or scrollbar activity shows up as cursor events around the scroll
+            (CorrelationEventType::CursorMovement, CorrelationEventType::Scroll) => CorrelationType::CursorToScreenChange,
+            (CorrelationEventType::Scroll, CorrelationEventType::CursorMovement) => CorrelationType::CursorToScreenChange,
+            (CorrelationEventType::CursorClick, CorrelationEventType::Scroll) => CorrelationType::CursorToScreenChange,
             (CorrelationEventType::WindowChange, CorrelationEventType::CursorMovement) => CorrelationType::ScreenToCursorResponse,
             (CorrelationEventType::ErrorDisplay, CorrelationEventType::CursorClick) => CorrelationType::ErrorRecovery,
             _ => return None,
@@ -434,6 +639,7 @@ impl EventCorrelator {
                 spatial_proximity: None,
                 causal_strength: 0.7, // Default causal strength for temporal correlations
                 pattern_match: None,
+                clicked_button: None,
             },
             timestamp: Utc::now(),
         })
@@ -463,6 +669,7 @@ impl EventCorrelator {
                 spatial_proximity: Some(distance),
                 causal_strength: 0.8, // Higher causal strength for spatial correlations
                 pattern_match: None,
+                clicked_button: None,
             },
             timestamp: Utc::now(),
         })
@@ -475,8 +682,18 @@ impl EventCorrelator {
             (CorrelationEventType::CursorClick, CorrelationEventType::WindowChange) => (CorrelationType::CursorToScreenChange, 0.9),
             (CorrelationEventType::CursorClick, CorrelationEventType::TabChange) => (CorrelationType::CursorToScreenChange, 0.85),
             (CorrelationEventType::CursorClick, CorrelationEventType::FieldChange) => (CorrelationType::CursorToScreenChange, 0.8),
+            (CorrelationEventType::CursorMovement, CorrelationEventType::Scroll) => (CorrelationType::CursorToScreenChange, 0.7),
             (CorrelationEventType::ErrorDisplay, CorrelationEventType::CursorMovement) => (CorrelationType::ErrorRecovery, 0.7),
             (CorrelationEventType::ModalAppearance, CorrelationEventType::CursorClick) => (CorrelationType::ErrorRecovery, 0.75),
+            // Form workflow: fill in fields, submit, then see the result
+            (CorrelationEventType::CursorClick, CorrelationEventType::FormSubmission) => (CorrelationType::CursorToScreenChange, 0.85),
+            (CorrelationEventType::FieldChange, CorrelationEventType::FieldChange) => (CorrelationType::InteractionWorkflow, 0.6),
+            (CorrelationEventType::FieldChange, CorrelationEventType::FormSubmission) => (CorrelationType::InteractionWorkflow, 0.75),
+            (CorrelationEventType::FormSubmission, CorrelationEventType::ScreenChange)
+            | (CorrelationEventType::FormSubmission, CorrelationEventType::WindowChange)
+            | (CorrelationEventType::FormSubmission, CorrelationEventType::TabChange)
+            | (CorrelationEventType::FormSubmission, CorrelationEventType::ErrorDisplay)
+            | (CorrelationEventType::FormSubmission, CorrelationEventType::ModalAppearance) => (CorrelationType::InteractionWorkflow, 0.8),
             _ => return None,
         };
         
@@ -501,11 +718,30 @@ impl EventCorrelator {
                 spatial_proximity: None,
                 causal_strength,
                 pattern_match: None,
+                clicked_button: None,
             },
             timestamp: Utc::now(),
         })
     }
     
+    /// Label of the dialog button a correlated click hit, if the dialog's buttons are known
+    fn clicked_button(&self, event_ids: &[String]) -> Option<String> {
+        let events: Vec<&CorrelationEvent> = self.event_buffer
+            .iter()
+            .filter(|e| event_ids.contains(&e.id))
+            .collect();
+        let click = events.iter().find(|e| e.event_type == CorrelationEventType::CursorClick)?;
+        let position = click.spatial_info.as_ref()?;
+        
+        events
+            .iter()
+            .filter(|e| matches!(e.event_type, CorrelationEventType::ErrorDisplay | CorrelationEventType::ModalAppearance))
+            .find_map(|dialog| {
+                DialogButton::at(&DialogButton::from_metadata(&dialog.metadata), position.x, position.y)
+                    .map(|button| button.label.clone())
+            })
+    }
+    
     /// Add event to buffer and maintain size
     fn add_event(&mut self, event: CorrelationEvent) {
         self.event_buffer.push_back(event);
@@ -527,6 +763,8 @@ impl EventCorrelator {
                 break;
             }
         }
+        // Events reported before the cutoff have left the buffer and cannot pair again
+        self.reported.retain(|_, reported_at| *reported_at >= cutoff_time);
     }
     
     /// Calculate spatial distance between two spatial info objects
@@ -548,7 +786,7 @@ impl EventCorrelator {
             y,
             width,
             height,
-            screen_id: None,
+            screen_id: metadata.get("screen_id").and_then(|id| id.parse().ok()),
         })
     }
     
@@ -577,7 +815,7 @@ impl EventCorrelator {
             pattern.typical_timing.push(correlation.evidence.temporal_proximity);
             
             // Keep only recent timing data
-            if pattern.typical_timing.len() > 100 {
+            if pattern.typical_timing.len() > MAX_PATTERN_TIMINGS {
                 pattern.typical_timing.remove(0);
             }
         }
@@ -599,6 +837,7 @@ impl EventCorrelator {
     pub fn clear_data(&mut self) {
         self.event_buffer.clear();
         self.correlation_patterns.clear();
+        self.reported.clear();
     }
     
     /// Update configuration
@@ -642,6 +881,16 @@ mod tests {
         assert_eq!(distance, 5.0); // 3-4-5 triangle
     }
     
+    #[test]
+    fn test_spatial_distance_ignores_box_sizes() {
+        let correlator = EventCorrelator::new();
+        let spatial1 = SpatialInfo { x: 0.0, y: 0.0, width: Some(100.0), height: Some(20.0), screen_id: None };
+        let spatial2 = SpatialInfo { x: 30.0, y: 40.0, width: Some(10.0), height: Some(10.0), screen_id: None };
+        
+        // Measured between top-left corners, as before the BoundingBox refactor
+        assert_eq!(correlator.calculate_spatial_distance(&spatial1, &spatial2), 50.0);
+    }
+    
     #[test]
     fn test_event_buffer_management() {
         let mut correlator = EventCorrelator::new();
@@ -730,4 +979,72 @@ mod tests {
         assert_eq!(correlator.event_buffer.len(), 1);
         assert_eq!(correlator.event_buffer[0].id, "recent_event");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_scroll_pairs_with_cursor_activity() {
+        let now = Utc::now();
+        let mut correlator = EventCorrelator::new();
+        correlator.add_cursor_event(&CursorPosition { x: 400.0, y: 300.0, timestamp: now, screen_id: Some(1) }, "frame_1");
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("navigation_type".to_string(), "scroll".to_string());
+        let scroll = DetectedEvent {
+            id: "scroll_event".to_string(),
+            timestamp: now + Duration::milliseconds(150),
+            event_type: EventType::Navigation,
+            target: "scroll".to_string(),
+            value_from: None,
+            value_to: Some("down 120px".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1".to_string(), "frame_2".to_string()],
+            metadata,
+        };
+        correlator.add_detected_event(&scroll);
+        assert_eq!(correlator.event_buffer[1].event_type, CorrelationEventType::Scroll);
+        
+        let correlations = correlator.analyze_correlations(now + Duration::milliseconds(200)).unwrap();
+        assert!(correlations.iter().any(|c| {
+            c.correlation_type == CorrelationType::CursorToScreenChange
+                && c.correlated_events.contains(&"scroll_event".to_string())
+        }));
+        
+        // The events are still buffered, but the pair is not reported or counted twice
+        let statistics = correlator.get_correlation_statistics();
+        assert!(correlator.analyze_correlations(now + Duration::milliseconds(300)).unwrap().is_empty());
+        assert_eq!(correlator.get_correlation_statistics(), statistics);
+    }
+    
+    #[test]
+    fn test_pattern_library_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("patterns").join("library.json");
+        let config = CorrelationConfig {
+            pattern_library_path: Some(path.clone()),
+            ..CorrelationConfig::default()
+        };
+        
+        let mut correlator = EventCorrelator::with_config(config.clone());
+        let key = format!("{:?}", CorrelationType::NavigationSequence);
+        correlator.correlation_patterns.insert(key.clone(), CorrelationPattern {
+            pattern_id: key.clone(),
+            event_sequence: Vec::new(),
+            typical_timing: vec![120, 300],
+            spatial_relationship: None,
+            confidence: 0.8,
+            occurrence_count: 12,
+        });
+        assert!(correlator.persist_patterns().unwrap());
+        assert!(!EventCorrelator::new().persist_patterns().unwrap());
+        
+        // A new correlator picks the library up on start
+        let mut restored = EventCorrelator::with_config(config);
+        let pattern = restored.get_pattern(&CorrelationType::NavigationSequence).unwrap();
+        assert_eq!(pattern.occurrence_count, 12);
+        assert!(pattern.matches_timing(200));
+        assert!(!pattern.matches_timing(500));
+        
+        // Loading again merges counts instead of replacing them
+        assert_eq!(restored.load_patterns(&path).unwrap(), 1);
+        assert_eq!(restored.get_pattern(&CorrelationType::NavigationSequence).unwrap().occurrence_count, 24);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{levenshtein_distance, DetectedEvent};
+use crate::modal_lifetime::MODAL_PHASE_KEY;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Metadata key with the number of detections collapsed into an event
+pub const OCCURRENCE_COUNT_KEY: &str = "occurrence_count";
+/// Metadata key with the RFC 3339 time of the last collapsed detection
+pub const LAST_SEEN_KEY: &str = "last_seen";
+
+/// Configuration for collapsing repeated events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventDedupConfig {
+    /// Off by default, as collapsed events are only released once their window has passed
+    pub enabled: bool,
+    /// Longest gap (ms) between two detections of the same event
+    pub window_ms: i64,
+    /// Minimum similarity (0-1) of the old and new values for detections to be the same event
+    pub min_value_similarity: f32,
+}
+
+impl Default for EventDedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_ms: 3000,
+            min_value_similarity: 0.9,
+        }
+    }
+}
+
+impl EventDedupConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.window_ms <= 0 {
+            return Err(IndexerError::Config("event dedup window_ms must be greater than 0".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.min_value_similarity) {
+            return Err(IndexerError::Config("event dedup min_value_similarity must be between 0 and 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// An event whose repeats are still being collected
+#[derive(Debug, Clone)]
+struct PendingEvent {
+    event: DetectedEvent,
+    last_seen: DateTime<Utc>,
+    last_frame: Option<String>,
+    occurrences: u32,
+}
+
+/// Collapses near-identical events from consecutive frames into one.
+///
+/// Detections are the same event when their type, target and dialog phase
+/// match, their old and new values are similar, and they are at most
+/// `window_ms` apart. The collapsed event keeps the first detection's id, time
+/// and old value, takes the latest new value and the highest confidence, and
+/// cites the first and last evidence frames. It is released once a frame
+/// arrives after its window, or on `flush`. Frames must be processed in time
+/// order.
+pub struct EventDeduplicator {
+    config: EventDedupConfig,
+    pending: Vec<PendingEvent>,
+}
+
+impl EventDeduplicator {
+    pub fn new(config: EventDedupConfig) -> Self {
+        Self {
+            config,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add a frame's events, returning the collapsed events whose window
+    /// closed before `timestamp`; events pass through when disabled
+    pub fn process(&mut self, timestamp: DateTime<Utc>, events: Vec<DetectedEvent>) -> Vec<DetectedEvent> {
+        if !self.config.enabled {
+            return events;
+        }
+
+        let window = chrono::Duration::milliseconds(self.config.window_ms);
+        let (expired, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| timestamp - pending.last_seen > window);
+        self.pending = pending;
+
+        for event in events {
+            let matched = self.pending.iter().position(|pending| self.is_repeat(&pending.event, &event));
+            match matched {
+                Some(index) => {
+                    let pending = &mut self.pending[index];
+                    pending.occurrences += 1;
+                    pending.last_seen = pending.last_seen.max(event.timestamp);
+                    pending.event.confidence = pending.event.confidence.max(event.confidence);
+                    if event.value_to.is_some() {
+                        pending.event.value_to = event.value_to;
+                    }
+                    if let Some(frame) = event.evidence_frames.last() {
+                        pending.last_frame = Some(frame.clone());
+                    }
+                }
+                None => self.pending.push(PendingEvent {
+                    last_seen: event.timestamp,
+                    last_frame: None,
+                    occurrences: 1,
+                    event,
+                }),
+            }
+        }
+
+        let mut released: Vec<DetectedEvent> = expired.into_iter().map(Self::collapse).collect();
+        released.sort_by_key(|event| event.timestamp);
+        released
+    }
+
+    /// Release every event still collecting repeats, e.g. at the end of a segment
+    pub fn flush(&mut self) -> Vec<DetectedEvent> {
+        let mut released: Vec<DetectedEvent> = self.pending.drain(..).map(Self::collapse).collect();
+        released.sort_by_key(|event| event.timestamp);
+        released
+    }
+
+    /// Events still collecting repeats
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn is_repeat(&self, first: &DetectedEvent, event: &DetectedEvent) -> bool {
+        first.event_type == event.event_type
+            && first.target == event.target
+            && first.metadata.get(MODAL_PHASE_KEY) == event.metadata.get(MODAL_PHASE_KEY)
+            && value_similarity(&first.value_from, &event.value_from) >= self.config.min_value_similarity
+            && value_similarity(&first.value_to, &event.value_to) >= self.config.min_value_similarity
+    }
+
+    fn collapse(pending: PendingEvent) -> DetectedEvent {
+        let mut event = pending.event;
+        if pending.occurrences > 1 {
+            debug!("Collapsed {} repeats of {:?} on {}", pending.occurrences, event.event_type, event.target);
+            if let Some(last_frame) = pending.last_frame {
+                event.evidence_frames.truncate(1);
+                if event.evidence_frames.first() != Some(&last_frame) {
+                    event.evidence_frames.push(last_frame);
+                }
+            }
+        }
+        event.metadata.insert(OCCURRENCE_COUNT_KEY.to_string(), pending.occurrences.to_string());
+        event
+            .metadata
+            .insert(LAST_SEEN_KEY.to_string(), pending.last_seen.to_rfc3339_opts(SecondsFormat::Millis, true));
+        event
+    }
+}
+
+fn value_similarity(a: &Option<String>, b: &Option<String>) -> f32 {
+    match (a, b) {
+        (None, None) => 1.0,
+        (Some(a), Some(b)) => {
+            let max_len = a.chars().count().max(b.chars().count());
+            if max_len == 0 {
+                return 1.0;
+            }
+            1.0 - levenshtein_distance(a, b) as f32 / max_len as f32
+        }
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use chrono::Duration;
+    use std::collections::HashMap;
+
+    fn error(text: &str, frame: &str, at: DateTime<Utc>) -> DetectedEvent {
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: at,
+            event_type: EventType::ErrorDisplay,
+            target: "error_text".to_string(),
+            value_from: None,
+            value_to: Some(text.to_string()),
+            confidence: 0.8,
+            evidence_frames: vec![frame.to_string()],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_repeats_collapse_into_one_event_per_window() {
+        let mut dedup = EventDeduplicator::new(EventDedupConfig { enabled: true, ..EventDedupConfig::default() });
+        let start = Utc::now();
+
+        assert!(dedup.process(start, vec![error("Invalid password", "f1", start)]).is_empty());
+        let at = start + Duration::seconds(1);
+        assert!(dedup.process(at, vec![error("Invalid password.", "f2", at), error("Network unreachable", "f2", at)]).is_empty());
+        let at = start + Duration::seconds(2);
+        assert!(dedup.process(at, vec![error("Invalid password", "f3", at)]).is_empty());
+        assert_eq!(dedup.pending_count(), 2);
+
+        // The password error was last seen at 2 s, the network error at 1 s
+        let released = dedup.process(start + Duration::milliseconds(4500), Vec::new());
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].value_to.as_deref(), Some("Network unreachable"));
+        assert_eq!(released[0].metadata[OCCURRENCE_COUNT_KEY], "1");
+
+        let released = dedup.flush();
+        assert_eq!(released.len(), 1);
+        let collapsed = &released[0];
+        assert_eq!(collapsed.timestamp, start);
+        assert_eq!(collapsed.metadata[OCCURRENCE_COUNT_KEY], "3");
+        assert_eq!(collapsed.evidence_frames, vec!["f1".to_string(), "f3".to_string()]);
+        assert_eq!(dedup.pending_count(), 0);
+
+        // Disabled, every detection passes straight through
+        let mut dedup = EventDeduplicator::new(EventDedupConfig::default());
+        assert_eq!(dedup.process(start, vec![error("a", "f1", start), error("a", "f1", start)]).len(), 2);
+    }
+}
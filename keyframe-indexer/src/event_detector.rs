@@ -1,25 +1,59 @@
+use crate::display_topology::DisplayTopology;
 use crate::error::{IndexerError, Result};
-use crate::ocr_data::{OCRResult, BoundingBox};
+use crate::ocr_data::{valid_results, OCRResult, BoundingBox, RoiNormalizer, RoiScaleConfig};
 use crate::error_modal_detector::{ErrorModalDetector, ErrorModalEvent, ErrorModalType};
+use crate::event_dedup::{EventDedupConfig, EventDeduplicator};
+use crate::field_identity::{FieldIdentity, FieldIdentityConfig, FieldIdentityTracker};
+use crate::form_model::{FormModelBuilder, FormModelConfig, FormRecord};
+use crate::table_extractor::{TableExtractorConfig, TableTracker};
+use crate::link_detector::{LinkDetector, LinkDetectorConfig};
+use crate::modal_lifetime::{ModalLifetimeConfig, ModalLifetimeTracker};
+use crate::progress_tracker::{ProgressTracker, ProgressTrackingConfig};
+use crate::template_matcher::TemplateMatcher;
+use crate::text_diff::{ChangeKind, DiffGranularity, TextDiff};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 /// Event detection engine for identifying field changes and interactions
 pub struct EventDetector {
     /// Configuration for event detection
     config: EventDetectionConfig,
-    /// Previous frame OCR results for delta analysis
-    previous_frame_cache: HashMap<String, Vec<OCRResult>>,
+    /// Previous frame OCR results for delta analysis, ordered by frame timestamp
+    previous_frame_cache: BTreeMap<(DateTime<Utc>, String), Vec<OCRResult>>,
     /// Field tracking for maintaining state across frames
     field_tracker: FieldTracker,
     /// Specialized error and modal detector
     error_modal_detector: ErrorModalDetector,
+    /// Recent paste shortcuts reported by keyboard tracking
+    paste_shortcuts: VecDeque<DateTime<Utc>>,
+    /// Brings incoming OCR ROIs into keyframe pixels
+    roi_normalizer: RoiNormalizer,
+    /// Display the analyzed frames were captured from, when known
+    capture_screen: Option<i32>,
+    /// Follows dialogs across frames so each is reported when it appears and when it goes
+    modal_tracker: ModalLifetimeTracker,
+    /// Screenshots of known dialogs matched against each frame's image
+    template_matcher: Option<Arc<TemplateMatcher>>,
+    /// Collapses repeats of an event in consecutive frames
+    deduplicator: EventDeduplicator,
+    /// Persistent field ids that survive windows moving
+    field_identities: FieldIdentityTracker,
+    /// Form on screen, recorded at each submission
+    forms: FormModelBuilder,
+    /// Tables on screen, reported when they appear or change
+    tables: TableTracker,
+    /// URLs, email addresses and file paths read from the screen
+    links: LinkDetector,
+    /// Progress dialogs followed until they finish or are cancelled
+    progress: ProgressTracker,
 }
 
 /// Configuration for event detection behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EventDetectionConfig {
     /// Minimum confidence threshold for considering OCR results
     pub min_ocr_confidence: f32,
@@ -27,10 +61,39 @@ pub struct EventDetectionConfig {
     pub min_iou_threshold: f32,
     /// Minimum text similarity threshold for field matching
     pub min_text_similarity: f32,
-    /// Maximum time gap between frames for delta analysis (seconds)
+    /// Maximum time gap between frames for delta analysis (seconds); older
+    /// cached frames fall out of the lookback window
     pub max_frame_gap_seconds: f64,
+    /// Maximum number of frames kept for delta analysis
+    pub max_cached_frames: usize,
     /// Minimum confidence for event detection
     pub min_event_confidence: f32,
+    /// Unit used when diffing old and new field values
+    pub diff_granularity: DiffGranularity,
+    /// Minimum characters inserted in one frame delta to count as a paste
+    pub paste_min_chars: usize,
+    /// Insertion rates above this (characters per second) are faster than typing
+    pub max_typing_chars_per_second: f64,
+    /// How close a paste shortcut must be to a field change to attribute it (ms)
+    pub paste_shortcut_window_ms: i64,
+    /// Unit and scale factors of incoming OCR ROIs; set from the top-level
+    /// `roi_scale` section when loaded from the config file
+    #[serde(skip)]
+    pub roi_scale: RoiScaleConfig,
+    /// Matching of dialogs across frames for appear/dismiss events
+    pub modal_lifetime: ModalLifetimeConfig,
+    /// Collapsing of repeated events across consecutive frames
+    pub dedup: EventDedupConfig,
+    /// Following fields across frames when the layout shifts
+    pub field_identity: FieldIdentityConfig,
+    /// Recording submitted forms
+    pub form_model: FormModelConfig,
+    /// Finding tables in the OCR layout
+    pub tables: TableExtractorConfig,
+    /// Reporting URLs, email addresses and file paths in OCR text
+    pub links: LinkDetectorConfig,
+    /// Following progress dialogs to completion
+    pub progress: ProgressTrackingConfig,
 }
 
 impl Default for EventDetectionConfig {
@@ -40,7 +103,20 @@ impl Default for EventDetectionConfig {
             min_iou_threshold: 0.3,
             min_text_similarity: 0.8,
             max_frame_gap_seconds: 10.0,
+            max_cached_frames: 10,
             min_event_confidence: 0.6,
+            diff_granularity: DiffGranularity::Character,
+            paste_min_chars: 20,
+            max_typing_chars_per_second: 12.0,
+            paste_shortcut_window_ms: 1500,
+            roi_scale: RoiScaleConfig::default(),
+            modal_lifetime: ModalLifetimeConfig::default(),
+            dedup: EventDedupConfig::default(),
+            field_identity: FieldIdentityConfig::default(),
+            form_model: FormModelConfig::default(),
+            tables: TableExtractorConfig::default(),
+            links: LinkDetectorConfig::default(),
+            progress: ProgressTrackingConfig::default(),
         }
     }
 }
@@ -74,6 +150,8 @@ pub struct FieldState {
 pub struct FieldChange {
     /// Field identifier
     pub field_id: String,
+    /// Label shown next to the field, e.g. `Amount`
+    pub field_label: Option<String>,
     /// Previous value
     pub value_from: String,
     /// New value
@@ -82,6 +160,8 @@ pub struct FieldChange {
     pub timestamp: DateTime<Utc>,
     /// Confidence in change detection
     pub confidence: f32,
+    /// How the value changed
+    pub diff: TextDiff,
 }
 
 /// Detected event types according to requirements 4.1 and 4.5
@@ -99,6 +179,38 @@ pub enum EventType {
     Navigation,
     /// Data entry completion
     DataEntry,
+    /// Outcome of an external command run by an event trigger
+    TriggerExecution,
+    /// Field change where text was pasted rather than typed
+    ClipboardPaste,
+    /// Recorder resolution or frame rate changed between segments
+    CaptureChange,
+    /// A business application transaction was opened, e.g. an SAP GUI transaction
+    TransactionStart,
+    /// A transaction code was typed into a command field
+    TCodeEntered,
+    /// Processing was cut back or paused to save disk space
+    StorageThrottle,
+    /// A table appeared on screen or its rows or cells changed
+    TableChange,
+    /// A URL appeared in on-screen text, e.g. an address bar or a document
+    UrlDisplay,
+    /// An email address appeared in on-screen text
+    EmailDisplay,
+    /// A file path appeared in on-screen text
+    FilePathDisplay,
+    /// A command was run in a terminal window
+    CommandExecuted,
+    /// A different file became active in a code editor
+    FileOpened,
+    /// A compiler or linter error or warning appeared in a code editor
+    CodeDiagnostic,
+    /// A build or test run failed after recent code edits
+    BuildFailure,
+    /// A progress dialog went away, finished or cancelled
+    ProgressCompleted,
+    /// An error message or modal dialog went away
+    ModalDismissal,
 }
 
 /// Detected event with evidence and confidence scoring
@@ -124,6 +236,18 @@ pub struct DetectedEvent {
     pub metadata: HashMap<String, String>,
 }
 
+impl DetectedEvent {
+    /// Label of the field the event happened in, when one was found
+    pub fn field_label(&self) -> Option<&str> {
+        self.metadata.get("field_label").map(String::as_str)
+    }
+    
+    /// What the event happened to, for people: the field label, else the target
+    pub fn subject(&self) -> &str {
+        self.field_label().unwrap_or(&self.target)
+    }
+}
+
 impl EventDetector {
     /// Create a new event detector with default configuration
     pub fn new() -> Result<Self> {
@@ -133,22 +257,87 @@ impl EventDetector {
     /// Create a new event detector with custom configuration
     pub fn with_config(config: EventDetectionConfig) -> Result<Self> {
         let error_modal_detector = ErrorModalDetector::new()?;
+        let roi_normalizer = RoiNormalizer::new(config.roi_scale.clone());
+        let modal_tracker = ModalLifetimeTracker::new(config.modal_lifetime.clone());
+        let deduplicator = EventDeduplicator::new(config.dedup.clone());
+        let field_identities = FieldIdentityTracker::new(config.field_identity.clone());
+        let forms = FormModelBuilder::new(config.form_model.clone());
+        let tables = TableTracker::new(config.tables.clone());
+        let links = LinkDetector::new(config.links.clone())?;
+        let progress = ProgressTracker::new(config.progress.clone())?;
         
         Ok(Self {
             config,
-            previous_frame_cache: HashMap::new(),
+            previous_frame_cache: BTreeMap::new(),
             field_tracker: FieldTracker {
                 fields: HashMap::new(),
                 change_history: Vec::new(),
             },
             error_modal_detector,
+            paste_shortcuts: VecDeque::new(),
+            roi_normalizer,
+            capture_screen: None,
+            modal_tracker,
+            template_matcher: None,
+            deduplicator,
+            field_identities,
+            forms,
+            tables,
+            links,
+            progress,
         })
     }
     
+    /// Judge dialog layouts per display when frames capture several displays
+    pub fn set_display_topology(&mut self, topology: DisplayTopology) {
+        self.roi_normalizer = RoiNormalizer::new(self.config.roi_scale.clone()).with_topology(&topology);
+        self.error_modal_detector.set_display_topology(topology);
+    }
+    
+    /// Re-derive pixel thresholds after the capture resolution changed
+    pub fn set_resolution_scale(&mut self, factor: f32) {
+        self.error_modal_detector.set_resolution_scale(factor);
+    }
+    
+    /// Match each analyzed frame against known dialog screenshots; frame ids
+    /// must then be image paths
+    pub fn set_template_matcher(&mut self, matcher: Arc<TemplateMatcher>) {
+        self.template_matcher = Some(matcher);
+    }
+    
+    /// Display subsequent frames are captured from, selecting its ROI scale factor
+    pub fn set_capture_screen(&mut self, screen_id: Option<i32>) {
+        self.capture_screen = screen_id;
+    }
+    
+    /// App and window title of subsequent frames; fields are identified within their window
+    pub fn set_window(&mut self, app: &str, title: &str) {
+        self.field_identities.set_window(app, title);
+    }
+    
+    /// Fields followed so far, to keep their identities across runs
+    pub fn field_identities(&self) -> &FieldIdentityTracker {
+        &self.field_identities
+    }
+    
+    /// Continue following fields of an earlier run
+    pub fn set_field_identities(&mut self, tracker: FieldIdentityTracker) {
+        self.field_identities = tracker;
+    }
+    
     /// Analyze OCR results from a frame and detect events
     pub fn analyze_frame(&mut self, frame_id: &str, ocr_results: &[OCRResult], timestamp: DateTime<Utc>, screen_width: f32, screen_height: f32) -> Result<Vec<DetectedEvent>> {
         debug!("Analyzing frame {} with {} OCR results", frame_id, ocr_results.len());
         
+        // Malformed ROIs and confidences never reach the geometry below
+        let valid = valid_results(ocr_results);
+        // Compare regions in keyframe pixels whatever unit the OCR reported
+        let normalized = self.roi_normalizer.normalize(&valid, self.capture_screen, screen_width, screen_height);
+        let ocr_results: &[OCRResult] = &normalized;
+        
+        // Known dialogs are recognized from pixels, whatever OCR made of them
+        let template_events = self.match_templates(frame_id, timestamp);
+        
         // Filter OCR results by confidence threshold
         let high_confidence_results: Vec<&OCRResult> = ocr_results
             .iter()
@@ -157,20 +346,37 @@ impl EventDetector {
         
         if high_confidence_results.is_empty() {
             debug!("No high-confidence OCR results in frame {}", frame_id);
-            return Ok(Vec::new());
+            // Dialogs shown in earlier frames are gone
+            let mut template_events: Vec<DetectedEvent> = template_events
+                .into_iter()
+                .map(|event| self.convert_error_modal_to_detected_event(event))
+                .collect();
+            if self.config.progress.enabled {
+                let completed = self.progress.track_frame(frame_id, timestamp, &template_events);
+                template_events.extend(completed);
+            }
+            let events = self.modal_tracker.track_frame(frame_id, timestamp, template_events);
+            return Ok(self.deduplicator.process(timestamp, events));
         }
         
+        // Fields keep their ids when the window moves or the layout reflows
+        let identities = self.field_identities.assign(&high_confidence_results, timestamp);
+        
         let mut detected_events = Vec::new();
         
         // Check if we have previous frame data for delta analysis
-        let previous_results = self.get_previous_frame_results(frame_id).cloned();
-        if let Some(previous_results) = previous_results {
+        let previous_frame = self
+            .get_previous_frame(timestamp)
+            .map(|(previous_ts, results)| (timestamp - previous_ts, results.clone()));
+        if let Some((frame_gap, previous_results)) = previous_frame {
             // Perform delta analysis between current and previous frame
             let delta_events = self.perform_delta_analysis(
                 frame_id,
                 &high_confidence_results,
+                &identities,
                 &previous_results,
                 timestamp,
+                frame_gap,
             )?;
             detected_events.extend(delta_events);
         }
@@ -192,17 +398,61 @@ impl EventDetector {
             screen_height,
         )?;
         
-        // Convert ErrorModalEvents to DetectedEvents
+        // Convert ErrorModalEvents to DetectedEvents; a template match replaces
+        // the text-based detection of the same dialog
         for error_modal_event in error_modal_events {
-            let detected_event = self.convert_error_modal_to_detected_event(error_modal_event);
+            let matched_by_template = template_events.iter().any(|template_event| {
+                template_event.roi.iou(&error_modal_event.roi) >= self.config.min_iou_threshold
+            });
+            if !matched_by_template {
+                let detected_event = self.convert_error_modal_to_detected_event(error_modal_event);
+                detected_events.push(detected_event);
+            }
+        }
+        for template_event in template_events {
+            let detected_event = self.convert_error_modal_to_detected_event(template_event);
             detected_events.push(detected_event);
         }
         
+        // Spreadsheets and grids are reported as whole tables and their changes
+        if self.config.tables.enabled {
+            let snapshots = self.tables.process_frame(frame_id, timestamp, &high_confidence_results);
+            detected_events.extend(
+                snapshots
+                    .iter()
+                    .map(|snapshot| snapshot.to_event())
+                    .filter(|event| event.confidence >= self.config.min_event_confidence),
+            );
+        }
+        
+        // Links are read from the pixels, whatever the browser's scripting allows
+        if self.config.links.enabled {
+            detected_events.extend(self.links.detect(frame_id, timestamp, &high_confidence_results));
+        }
+        
+        // Submissions snapshot the form as filled in so far
+        if self.config.form_model.enabled {
+            self.forms.observe(&high_confidence_results, &identities);
+            self.forms.record_submissions(frame_id, timestamp, &mut detected_events);
+        }
+        
         // Update field tracker with current frame data
-        self.update_field_tracker(frame_id, &high_confidence_results, timestamp)?;
+        self.update_field_tracker(frame_id, &high_confidence_results, &identities, timestamp)?;
         
         // Cache current frame results for next comparison
-        self.cache_frame_results(frame_id, high_confidence_results.into_iter().cloned().collect());
+        self.cache_frame_results(frame_id, timestamp, high_confidence_results.into_iter().cloned().collect());
+        
+        // Progress dialogs are followed frame by frame, before repeats of them are collapsed
+        if self.config.progress.enabled {
+            let completed = self.progress.track_frame(frame_id, timestamp, &detected_events);
+            detected_events.extend(completed);
+        }
+        
+        // A dialog that stays up is reported when it appears and when it goes, not on every frame
+        let detected_events = self.modal_tracker.track_frame(frame_id, timestamp, detected_events);
+        
+        // With dedup enabled, repeats are held back and released collapsed once their window passes
+        let detected_events = self.deduplicator.process(timestamp, detected_events);
         
         info!("Detected {} events in frame {}", detected_events.len(), frame_id);
         Ok(detected_events)
@@ -213,8 +463,10 @@ impl EventDetector {
         &mut self,
         frame_id: &str,
         current_results: &[&OCRResult],
+        identities: &[FieldIdentity],
         previous_results: &[OCRResult],
         timestamp: DateTime<Utc>,
+        frame_gap: chrono::Duration,
     ) -> Result<Vec<DetectedEvent>> {
         let mut events = Vec::new();
         
@@ -236,8 +488,10 @@ impl EventDetector {
                 let change_event = self.create_field_change_event(
                     frame_id,
                     current,
+                    &identities[current_idx],
                     previous,
                     timestamp,
+                    frame_gap,
                 )?;
                 
                 if change_event.confidence >= self.config.min_event_confidence {
@@ -257,12 +511,12 @@ impl EventDetector {
                     id: uuid::Uuid::new_v4().to_string(),
                     timestamp,
                     event_type: EventType::DataEntry,
-                    target: self.generate_field_id(&new_region.roi),
+                    target: identities[region_idx].id.clone(),
                     value_from: None,
                     value_to: Some(new_region.text.clone()),
                     confidence: new_region.confidence * 0.8, // Slightly lower confidence for new elements
                     evidence_frames: vec![frame_id.to_string()],
-                    metadata: self.create_metadata(new_region),
+                    metadata: self.create_field_metadata(new_region, &identities[region_idx]),
                 };
                 
                 if event.confidence >= self.config.min_event_confidence {
@@ -341,8 +595,10 @@ impl EventDetector {
         &self,
         frame_id: &str,
         current: &OCRResult,
+        field: &FieldIdentity,
         previous: &OCRResult,
         timestamp: DateTime<Utc>,
+        frame_gap: chrono::Duration,
     ) -> Result<DetectedEvent> {
         // Calculate confidence based on multiple factors
         let text_similarity = self.calculate_text_similarity(&current.text, &previous.text);
@@ -356,21 +612,72 @@ impl EventDetector {
             (1.0 - text_similarity) * 0.3 // Higher confidence for more different text
         ).min(1.0);
         
-        let field_id = self.generate_field_id(&current.roi);
+        // Record insertions/deletions so consumers can tell typing from pasting
+        let mut metadata = self.create_field_metadata(current, field);
+        let diff = TextDiff::compute(&previous.text, &current.text, self.config.diff_granularity);
+        diff.write_metadata(&mut metadata);
+        
+        let event_type = match self.paste_evidence(&diff, timestamp, frame_gap) {
+            Some(evidence) => {
+                metadata.insert("paste_evidence".to_string(), evidence.to_string());
+                EventType::ClipboardPaste
+            }
+            None => EventType::FieldChange,
+        };
         
         Ok(DetectedEvent {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp,
-            event_type: EventType::FieldChange,
-            target: field_id,
+            event_type,
+            target: field.id.clone(),
             value_from: Some(previous.text.clone()),
             value_to: Some(current.text.clone()),
             confidence,
             evidence_frames: vec![frame_id.to_string()],
-            metadata: self.create_metadata(current),
+            metadata,
         })
     }
     
+    /// Record a paste shortcut (Cmd+V) observed by keyboard tracking
+    pub fn record_paste_shortcut(&mut self, timestamp: DateTime<Utc>) {
+        self.paste_shortcuts.push_back(timestamp);
+        
+        let cutoff = timestamp - self.lookback_window() - self.paste_shortcut_window();
+        while self.paste_shortcuts.front().map_or(false, |t| *t < cutoff) {
+            self.paste_shortcuts.pop_front();
+        }
+    }
+    
+    /// Why a field change looks like a paste, if it does.
+    ///
+    /// A paste shortcut between the previous frame and this one is decisive;
+    /// otherwise one contiguous block of text must appear faster than anyone types.
+    fn paste_evidence(&self, diff: &TextDiff, timestamp: DateTime<Utc>, frame_gap: chrono::Duration) -> Option<&'static str> {
+        if diff.inserted_chars == 0 {
+            return None;
+        }
+        
+        let window = self.paste_shortcut_window();
+        let since = timestamp - frame_gap.max(window);
+        if self.paste_shortcuts.iter().any(|t| *t >= since && *t <= timestamp + window) {
+            return Some("shortcut");
+        }
+        
+        let contiguous = matches!(
+            diff.kind,
+            ChangeKind::Append | ChangeKind::Prepend | ChangeKind::Insert | ChangeKind::Replace | ChangeKind::ReplaceAll
+        );
+        let seconds = frame_gap.num_milliseconds() as f64 / 1000.0;
+        let too_fast_to_type = seconds <= 0.0
+            || diff.inserted_chars as f64 / seconds > self.config.max_typing_chars_per_second;
+        
+        (contiguous && diff.inserted_chars >= self.config.paste_min_chars && too_fast_to_type).then_some("size")
+    }
+    
+    fn paste_shortcut_window(&self) -> chrono::Duration {
+        chrono::Duration::milliseconds(self.config.paste_shortcut_window_ms)
+    }
+    
     /// Create spatial index for efficient region matching
     fn create_spatial_index<'a>(&self, results: &[&'a OCRResult]) -> Vec<(usize, &'a BoundingBox)> {
         results.iter()
@@ -433,16 +740,6 @@ impl EventDetector {
         new_regions
     }
     
-    /// Generate a unique field identifier based on spatial location
-    fn generate_field_id(&self, roi: &BoundingBox) -> String {
-        format!("field_{}_{}_{}_{}", 
-            (roi.x as i32), 
-            (roi.y as i32), 
-            (roi.width as i32), 
-            (roi.height as i32)
-        )
-    }
-    
     /// Calculate text similarity between two strings
     pub fn calculate_text_similarity(&self, text1: &str, text2: &str) -> f32 {
         if text1 == text2 {
@@ -518,10 +815,16 @@ impl EventDetector {
         let mut metadata = HashMap::new();
         metadata.insert("language".to_string(), ocr_result.language.clone());
         metadata.insert("processor".to_string(), ocr_result.processor.clone());
-        metadata.insert("roi_x".to_string(), ocr_result.roi.x.to_string());
-        metadata.insert("roi_y".to_string(), ocr_result.roi.y.to_string());
-        metadata.insert("roi_width".to_string(), ocr_result.roi.width.to_string());
-        metadata.insert("roi_height".to_string(), ocr_result.roi.height.to_string());
+        ocr_result.roi.insert_metadata(&mut metadata);
+        metadata
+    }
+    
+    /// Metadata for an event in a field, with the field's label when known
+    fn create_field_metadata(&self, ocr_result: &OCRResult, field: &FieldIdentity) -> HashMap<String, String> {
+        let mut metadata = self.create_metadata(ocr_result);
+        if let Some(label) = &field.label {
+            metadata.insert("field_label".to_string(), label.clone());
+        }
         metadata
     }
     
@@ -530,10 +833,11 @@ impl EventDetector {
         &mut self,
         frame_id: &str,
         ocr_results: &[&OCRResult],
+        identities: &[FieldIdentity],
         timestamp: DateTime<Utc>,
     ) -> Result<()> {
-        for result in ocr_results {
-            let field_id = self.generate_field_id(&result.roi);
+        for (result, identity) in ocr_results.iter().zip(identities) {
+            let field_id = identity.id.clone();
             
             // Check if this field has changed
             if let Some(previous_state) = self.field_tracker.fields.get(&field_id) {
@@ -541,10 +845,12 @@ impl EventDetector {
                     // Record the change
                     let change = FieldChange {
                         field_id: field_id.clone(),
+                        field_label: identity.label.clone(),
                         value_from: previous_state.value.clone(),
                         value_to: result.text.clone(),
                         timestamp,
                         confidence: result.confidence,
+                        diff: TextDiff::compute(&previous_state.value, &result.text, self.config.diff_granularity),
                     };
                     self.field_tracker.change_history.push(change);
                 }
@@ -566,25 +872,42 @@ impl EventDetector {
     }
     
     /// Cache frame results for delta analysis
-    fn cache_frame_results(&mut self, frame_id: &str, results: Vec<OCRResult>) {
+    fn cache_frame_results(&mut self, frame_id: &str, timestamp: DateTime<Utc>, results: Vec<OCRResult>) {
+        self.previous_frame_cache.insert((timestamp, frame_id.to_string()), results);
+        
+        // Drop frames outside the lookback window of the newest cached frame
+        if let Some((newest, _)) = self.previous_frame_cache.keys().next_back().cloned() {
+            let cutoff = newest - self.lookback_window();
+            self.previous_frame_cache.retain(|(ts, _), _| *ts >= cutoff);
+        }
+        
         // Keep only recent frames to manage memory
-        const MAX_CACHED_FRAMES: usize = 10;
+        while self.previous_frame_cache.len() > self.config.max_cached_frames.max(1) {
+            self.previous_frame_cache.pop_first();
+        }
+    }
+    
+    /// Timestamp and results of the chronologically previous frame, if it is within
+    /// the lookback window.
+    ///
+    /// Frames may arrive out of order, so this is the latest cached frame strictly
+    /// before `timestamp` rather than the most recently analyzed one.
+    fn get_previous_frame(&self, timestamp: DateTime<Utc>) -> Option<(DateTime<Utc>, &Vec<OCRResult>)> {
+        let ((previous_ts, _), results) = self
+            .previous_frame_cache
+            .range(..(timestamp, String::new()))
+            .next_back()?;
         
-        if self.previous_frame_cache.len() >= MAX_CACHED_FRAMES {
-            // Remove oldest frame (simple FIFO for now)
-            if let Some(oldest_key) = self.previous_frame_cache.keys().next().cloned() {
-                self.previous_frame_cache.remove(&oldest_key);
-            }
+        if timestamp - *previous_ts > self.lookback_window() {
+            debug!("Previous frame at {} is outside the lookback window", previous_ts);
+            return None;
         }
         
-        self.previous_frame_cache.insert(frame_id.to_string(), results);
+        Some((*previous_ts, results))
     }
     
-    /// Get previous frame results for comparison
-    fn get_previous_frame_results(&self, current_frame_id: &str) -> Option<&Vec<OCRResult>> {
-        // For now, just get any previous frame
-        // In a more sophisticated implementation, we'd find the chronologically previous frame
-        self.previous_frame_cache.values().next()
+    fn lookback_window(&self) -> chrono::Duration {
+        chrono::Duration::milliseconds((self.config.max_frame_gap_seconds * 1000.0) as i64)
     }
     
     /// Get field change history
@@ -597,10 +920,45 @@ impl EventDetector {
         &self.field_tracker.fields
     }
     
+    /// Release events still collecting repeats, make the record of the form
+    /// on screen final and report the progress dialogs still followed; call
+    /// when the recording stops, not between segments
+    pub fn finish(&mut self) -> Vec<DetectedEvent> {
+        self.forms.finish();
+        let mut events = self.deduplicator.flush();
+        events.extend(self.progress.finish());
+        events
+    }
+    
+    /// Forms recorded at submissions whose form has left the screen
+    pub fn take_form_records(&mut self) -> Vec<FormRecord> {
+        self.forms.take_records()
+    }
+    
     /// Clear cached data to free memory
     pub fn clear_cache(&mut self) {
         self.previous_frame_cache.clear();
         self.field_tracker.change_history.clear();
+        self.modal_tracker.clear();
+        self.field_identities.clear();
+        self.tables.clear();
+        self.links.clear();
+        self.progress.clear();
+    }
+    
+    /// Dialog templates found in a frame's image; a frame that cannot be read
+    /// is still analyzed from its OCR text
+    fn match_templates(&self, frame_id: &str, timestamp: DateTime<Utc>) -> Vec<ErrorModalEvent> {
+        let Some(matcher) = &self.template_matcher else {
+            return Vec::new();
+        };
+        match matcher.match_frame(frame_id, timestamp) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Template matching failed for frame {}: {}", frame_id, e);
+                Vec::new()
+            }
+        }
     }
     
     /// Convert ErrorModalEvent to DetectedEvent
@@ -613,9 +971,15 @@ impl EventDetector {
             ErrorModalType::ConfirmationDialog | ErrorModalType::InfoDialog | 
             ErrorModalType::AlertDialog | ErrorModalType::FileDialog | 
             ErrorModalType::SettingsDialog | ErrorModalType::ProgressDialog | 
-            ErrorModalType::CustomDialog => EventType::ModalAppearance,
+            ErrorModalType::CustomDialog | ErrorModalType::SystemNotification => EventType::ModalAppearance,
         };
         
+        // Stored as code columns by the event writer
+        let mut metadata = error_modal_event.metadata;
+        metadata.insert("error_modal_type".to_string(), error_modal_event.event_type.to_string());
+        metadata.insert("severity".to_string(), error_modal_event.severity.to_string());
+        error_modal_event.roi.insert_metadata(&mut metadata);
+        
         DetectedEvent {
             id: error_modal_event.id,
             timestamp: error_modal_event.timestamp,
@@ -625,13 +989,13 @@ impl EventDetector {
             value_to: Some(error_modal_event.message),
             confidence: error_modal_event.confidence,
             evidence_frames: vec![error_modal_event.frame_id],
-            metadata: error_modal_event.metadata,
+            metadata,
         }
     }
 }
 
 /// Calculate Levenshtein distance between two strings
-fn levenshtein_distance(s1: &str, s2: &str) -> usize {
+pub(crate) fn levenshtein_distance(s1: &str, s2: &str) -> usize {
     let len1 = s1.chars().count();
     let len2 = s2.chars().count();
     
@@ -719,4 +1083,97 @@ mod tests {
         assert!(detector.is_form_submission("Sign up"));
         assert!(!detector.is_form_submission("Regular button"));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_previous_frame_is_chronological() {
+        let mut detector = EventDetector::with_config(EventDetectionConfig {
+            max_frame_gap_seconds: 5.0,
+            ..EventDetectionConfig::default()
+        }).unwrap();
+        let start = Utc::now();
+        let at = |seconds: i64| start + chrono::Duration::seconds(seconds);
+        let result = |text: &str| vec![OCRResult {
+            frame_id: text.to_string(),
+            roi: BoundingBox::new(0.0, 0.0, 100.0, 20.0),
+            text: text.to_string(),
+            language: "en".to_string(),
+            confidence: 0.9,
+            processed_at: start,
+            processor: "vision".to_string(),
+        }];
+        
+        // Frames cached out of order
+        detector.cache_frame_results("frame_3", at(3), result("third"));
+        detector.cache_frame_results("frame_1", at(1), result("first"));
+        detector.cache_frame_results("frame_2", at(2), result("second"));
+        
+        assert_eq!(detector.get_previous_frame(at(3)).unwrap().1[0].text, "second");
+        assert_eq!(detector.get_previous_frame(at(2)).unwrap().1[0].text, "first");
+        assert!(detector.get_previous_frame(at(1)).is_none());
+        
+        // Beyond the lookback window there is nothing to compare against
+        assert!(detector.get_previous_frame(at(10)).is_none());
+        
+        // Frames older than the window behind the newest frame are evicted
+        detector.cache_frame_results("frame_9", at(9), result("ninth"));
+        assert_eq!(detector.previous_frame_cache.len(), 1);
+    }
+    
+    #[test]
+    fn test_clipboard_paste_detection() {
+        let mut detector = EventDetector::new().unwrap();
+        let now = Utc::now();
+        let second = chrono::Duration::seconds(1);
+        let field = |text: &str| OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(10.0, 10.0, 300.0, 24.0),
+            text: text.to_string(),
+            language: "en".to_string(),
+            confidence: 0.95,
+            processed_at: now,
+            processor: "vision".to_string(),
+        };
+        let identity = FieldIdentity { id: "order_field".to_string(), label: None };
+        
+        // A few characters per second is typing
+        let typed = detector.create_field_change_event("frame_2", &field("Order 1"), &identity, &field("Order "), now, second).unwrap();
+        assert_eq!(typed.event_type, EventType::FieldChange);
+        assert_eq!(typed.metadata.get("diff_kind").map(String::as_str), Some("append"));
+        
+        // A long block appearing within one second is a paste
+        let pasted = detector
+            .create_field_change_event("frame_2", &field("Ship to 1 Infinite Loop, Cupertino CA"), &identity, &field("Ship to "), now, second)
+            .unwrap();
+        assert_eq!(pasted.event_type, EventType::ClipboardPaste);
+        assert_eq!(pasted.metadata.get("paste_evidence").map(String::as_str), Some("size"));
+        
+        // The same block over a minute could have been typed
+        let slow = detector
+            .create_field_change_event("frame_2", &field("Ship to 1 Infinite Loop, Cupertino CA"), &identity, &field("Ship to "), now, second * 60)
+            .unwrap();
+        assert_eq!(slow.event_type, EventType::FieldChange);
+        
+        // A paste shortcut makes even short insertions a paste
+        detector.record_paste_shortcut(now - chrono::Duration::milliseconds(300));
+        let shortcut = detector.create_field_change_event("frame_2", &field("Order 12"), &identity, &field("Order 1"), now, second).unwrap();
+        assert_eq!(shortcut.event_type, EventType::ClipboardPaste);
+        assert_eq!(shortcut.metadata.get("paste_evidence").map(String::as_str), Some("shortcut"));
+    }
+    
+    proptest::proptest! {
+        #![proptest_config(proptest::test_runner::Config::with_cases(64))]
+        #[test]
+        fn prop_hostile_ocr_never_panics(
+            frames in proptest::collection::vec(crate::ocr_data::strategies::frame("frame"), 1..6)
+        ) {
+            let mut detector = EventDetector::new().unwrap();
+            let start = Utc::now();
+            for (index, results) in frames.iter().enumerate() {
+                let timestamp = start + chrono::Duration::seconds(index as i64);
+                // Errors are fine; only a panic fails
+                let _ = detector.analyze_frame(&format!("frame_{}", index), results, timestamp, 1920.0, 1080.0);
+            }
+            detector.finish();
+        }
+    }
+}
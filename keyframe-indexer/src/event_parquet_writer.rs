@@ -1,13 +1,23 @@
+use crate::enum_codes::{EnumCode, EnumCodeTable, ENUM_CODE_VERSION, ENUM_CODE_VERSION_KEY};
 use crate::error::{IndexerError, Result};
+use crate::error_modal_detector::{ErrorModalType, SeverityLevel};
 use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_signing::{EventSigner, SignedEvent};
+use crate::file_index::{FileIndex, FileIndexBuilder, FileIndexCache, IndexFilter};
+use crate::layout_compat::{self, CompatibleTable};
+use crate::stats_cache::{FileStats, StatsManifest};
+use crate::query_stream::{self, RowStream};
+use crate::parquet_tuning::{ParquetSizing, ParquetTuningConfig, RowGroupAutotuner};
+use crate::writer_memory::{MemoryBudget, MemoryReservation, SpillBuffer};
 use arrow::array::{
-    Array, Float32Array, StringArray, TimestampNanosecondArray, ListArray, 
+    Array, Float32Array, Int16Array, StringArray, TimestampNanosecondArray, ListArray, 
     StringBuilder, TimestampNanosecondBuilder
 };
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::ArrowWriter;
 use parquet::file::properties::WriterProperties;
+use parquet::file::metadata::KeyValue;
 use parquet::basic::Compression;
 use std::fs::File;
 use std::path::{Path, PathBuf};
@@ -15,16 +25,39 @@ use std::sync::Arc;
 use tracing::{debug, info, error, warn};
 use chrono::{DateTime, Utc};
 use datafusion::prelude::*;
+use futures::TryStreamExt;
 use std::collections::HashMap;
 
+/// Columns counted by value in the statistics manifest; `type` holds names
+/// in files from before type codes
+const EVENT_STAT_COLUMNS: &[&str] = &["type_code", "type"];
+
+/// Parquet sizes used until rows have been measured, or always without
+/// autotuning; events use smaller row groups
+const DEFAULT_SIZING: ParquetSizing = ParquetSizing { max_row_group_size: 10000, write_batch_size: 1024 };
+
+/// Columns given bloom filters in each file's sidecar index
+const EVENT_INDEX_COLUMNS: &[&str] = &["target", "type_code"];
+
 /// Event Parquet writer for storing detected events according to design specification
 pub struct EventParquetWriter {
     output_dir: PathBuf,
     schema: Arc<Schema>,
     batch_size: usize,
     current_batch: Vec<DetectedEvent>,
+    /// Estimated size of `current_batch`, held against the memory budget
+    buffered: MemoryReservation,
+    /// Events moved to disk while the budget was exceeded
+    spill: SpillBuffer,
+    /// Row group and write batch sizes, tuned to the row width
+    sizing: RowGroupAutotuner,
     compression: Compression,
     enable_dictionary_encoding: bool,
+    signer: Option<EventSigner>,
+    /// Code mapping persisted alongside the data, used for the string view
+    enum_codes: EnumCodeTable,
+    /// Sidecar indexes read so far, used to skip files
+    indexes: FileIndexCache,
 }
 
 impl EventParquetWriter {
@@ -34,45 +67,105 @@ impl EventParquetWriter {
         // Create output directory if it doesn't exist
         std::fs::create_dir_all(&output_path)?;
         
+        // Record which code means which type before writing any coded data
+        let enum_codes = EnumCodeTable::persist(&output_path)?;
+        
         // Define schema for events according to design specification:
-        // events.parquet with type, target, value_from, value_to, confidence, evidence_frames
+        // events.parquet with type, target, value_from, value_to, confidence, evidence_frames.
+        // Enums are stored as integer codes; the `events` view maps them back to names.
         let schema = Arc::new(Schema::new(vec![
             Field::new("event_id", DataType::Utf8, false),
             Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
-            Field::new("type", DataType::Utf8, false),
+            Field::new("type_code", DataType::Int16, false),
             Field::new("target", DataType::Utf8, false),
             Field::new("value_from", DataType::Utf8, true), // Nullable
             Field::new("value_to", DataType::Utf8, true),   // Nullable
             Field::new("confidence", DataType::Float32, false),
             Field::new("evidence_frames", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
             Field::new("metadata", DataType::Utf8, true), // JSON-encoded metadata
+            Field::new("signature", DataType::Utf8, true), // Hex Ed25519 signature when signing is enabled
+            Field::new("modal_type_code", DataType::Int16, true), // Error/modal events only
+            Field::new("severity_code", DataType::Int16, true),   // Error/modal events only
         ]));
         
         Ok(Self {
+            spill: SpillBuffer::new(&output_path),
             output_dir: output_path,
             schema,
             batch_size: 1000, // Smaller batch size for events
             current_batch: Vec::new(),
+            buffered: MemoryBudget::default().reservation(),
+            sizing: RowGroupAutotuner::new(ParquetTuningConfig::default(), DEFAULT_SIZING),
             compression: Compression::SNAPPY,
             enable_dictionary_encoding: true,
+            signer: None,
+            enum_codes,
+            indexes: FileIndexCache::default(),
         })
     }
     
+    /// Sign every written event with the given signer
+    pub fn enable_signing(&mut self, signer: EventSigner) {
+        info!("Event signing enabled with public key {}", signer.public_key_hex());
+        self.signer = Some(signer);
+    }
+    
+    /// Disable event signing
+    pub fn disable_signing(&mut self) {
+        self.signer = None;
+    }
+    
+    /// Check if event signing is enabled
+    pub fn is_signing_enabled(&self) -> bool {
+        self.signer.is_some()
+    }
+    
     /// Write detected events to Parquet format
     pub async fn write_events(&mut self, events: &[DetectedEvent]) -> Result<()> {
         debug!("Writing {} events", events.len());
         
         // Add to current batch
         self.current_batch.extend_from_slice(events);
+        self.buffered.grow(events.iter().map(buffered_size).sum());
         
-        // Write batch if it's large enough
-        if self.current_batch.len() >= self.batch_size {
+        // Write batch if it's large enough, by rows or bytes
+        let rows = self.current_batch.len() + self.spill.rows();
+        let bytes = self.buffered.bytes() + self.spill.bytes();
+        if rows >= self.batch_size || bytes >= self.buffered.budget().flush_threshold_bytes() {
             self.flush_batch().await?;
+        } else if self.buffered.budget().is_exceeded() {
+            self.spill_batch()?;
         }
         
         Ok(())
     }
     
+    /// Share a memory budget with other writers; events buffered so far
+    /// move to the new budget
+    pub fn set_memory_budget(&mut self, budget: MemoryBudget) {
+        let bytes = self.buffered.bytes();
+        self.buffered = budget.reservation();
+        self.buffered.grow(bytes);
+    }
+    
+    /// Choose how row group and write batch sizes are picked
+    pub fn set_parquet_tuning(&mut self, config: ParquetTuningConfig) {
+        self.sizing = RowGroupAutotuner::new(config, DEFAULT_SIZING);
+    }
+    
+    /// Move the buffered events to a spill file until the next flush
+    fn spill_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        self.sizing.observe(&record_batch);
+        self.spill.spill(&record_batch, self.buffered.bytes())?;
+        self.current_batch.clear();
+        self.buffered.clear();
+        Ok(())
+    }
+    
     /// Write a single event
     pub async fn write_event(&mut self, event: &DetectedEvent) -> Result<()> {
         self.write_events(&[event.clone()]).await
@@ -80,11 +173,11 @@ impl EventParquetWriter {
     
     /// Flush current batch to disk
     pub async fn flush_batch(&mut self) -> Result<()> {
-        if self.current_batch.is_empty() {
+        if self.current_batch.is_empty() && self.spill.is_empty() {
             return Ok(());
         }
         
-        info!("Flushing event batch of {} records", self.current_batch.len());
+        info!("Flushing event batch of {} records", self.current_batch.len() + self.spill.rows());
         
         // Generate filename with timestamp for partitioning
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
@@ -92,13 +185,23 @@ impl EventParquetWriter {
         let file_path = self.output_dir.join(filename);
         
         // Create record batch from current data
-        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let record_batch = match self.current_batch.is_empty() {
+            true => None,
+            false => Some(self.create_record_batch(&self.current_batch)?),
+        };
         
-        // Write to Parquet file with optimized settings
-        self.write_record_batch(&file_path, record_batch).await?;
+        // Write to Parquet file with optimized settings, after any spilled events
+        let (stats, index) = self.write_record_batch(&file_path, record_batch).await?;
+        if let Err(e) = StatsManifest::record(&self.output_dir, &file_path, stats) {
+            warn!("Failed to record statistics of {}: {}", file_path.display(), e);
+        }
+        if let Err(e) = index.write(&file_path) {
+            warn!("Failed to write index of {}: {}", file_path.display(), e);
+        }
         
         // Clear current batch
         self.current_batch.clear();
+        self.buffered.clear();
         
         info!("Successfully wrote event data to: {}", file_path.display());
         Ok(())
@@ -120,8 +223,16 @@ impl EventParquetWriter {
         }
         let timestamp_array = timestamp_builder.finish();
         
-        let type_array = StringArray::from(
-            events.iter().map(|e| self.event_type_to_string(&e.event_type)).collect::<Vec<_>>()
+        let type_array = Int16Array::from(
+            events.iter().map(|e| e.event_type.code()).collect::<Vec<_>>()
+        );
+        
+        // Error/modal classification moves out of the metadata JSON into coded columns
+        let modal_type_array = Int16Array::from(
+            events.iter().map(|e| coded_metadata::<ErrorModalType>(&e.metadata)).collect::<Vec<_>>()
+        );
+        let severity_array = Int16Array::from(
+            events.iter().map(|e| coded_metadata::<SeverityLevel>(&e.metadata)).collect::<Vec<_>>()
         );
         
         let target_array = StringArray::from(
@@ -154,14 +265,24 @@ impl EventParquetWriter {
         // Serialize metadata as JSON
         let metadata_array = StringArray::from(
             events.iter().map(|e| {
-                if e.metadata.is_empty() {
+                let metadata: HashMap<&String, &String> = e.metadata
+                    .iter()
+                    .filter(|(key, value)| !is_coded_metadata(key, value))
+                    .collect();
+                if metadata.is_empty() {
                     None
                 } else {
-                    Some(serde_json::to_string(&e.metadata).unwrap_or_default())
+                    Some(serde_json::to_string(&metadata).unwrap_or_default())
                 }
             }).collect::<Vec<_>>()
         );
         
+        let signature_array = StringArray::from(
+            events.iter().map(|e| {
+                self.signer.as_ref().map(|signer| signer.sign_event(e)).transpose()
+            }).collect::<Result<Vec<_>>>()?
+        );
+        
         // Create record batch
         let record_batch = RecordBatch::try_new(
             self.schema.clone(),
@@ -175,6 +296,9 @@ impl EventParquetWriter {
                 Arc::new(confidence_array),
                 Arc::new(evidence_frames_array),
                 Arc::new(metadata_array),
+                Arc::new(signature_array),
+                Arc::new(modal_type_array),
+                Arc::new(severity_array),
             ],
         )?;
         
@@ -182,23 +306,37 @@ impl EventParquetWriter {
         Ok(record_batch)
     }
     
-    /// Write record batch to Parquet file with optimized settings
-    async fn write_record_batch(&self, file_path: &Path, record_batch: RecordBatch) -> Result<()> {
+    /// Write the spilled batches and then `record_batch` to a Parquet file with
+    /// optimized settings; returns the file's statistics and index
+    async fn write_record_batch(
+        &mut self,
+        file_path: &Path,
+        record_batch: Option<RecordBatch>,
+    ) -> Result<(FileStats, FileIndex)> {
         let file = File::create(file_path)?;
         
+        if let Some(record_batch) = &record_batch {
+            self.sizing.observe(record_batch);
+        }
+        let sizing = self.sizing.sizing();
+        
         // Configure writer properties for optimal compression and performance
         let mut props_builder = WriterProperties::builder()
             .set_compression(self.compression)
-            .set_write_batch_size(1024)
-            .set_max_row_group_size(10000) // Smaller row groups for events
-            .set_created_by("AlwaysOnAI Event Detector".to_string());
+            .set_write_batch_size(sizing.write_batch_size)
+            .set_max_row_group_size(sizing.max_row_group_size)
+            .set_created_by("AlwaysOnAI Event Detector".to_string())
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                ENUM_CODE_VERSION_KEY.to_string(),
+                ENUM_CODE_VERSION.to_string(),
+            )]));
         
         // Enable dictionary encoding for string columns
         if self.enable_dictionary_encoding {
             props_builder = props_builder
                 .set_dictionary_enabled(true)
                 .set_column_dictionary_enabled("event_id".into(), true)
-                .set_column_dictionary_enabled("type".into(), true)
+                .set_column_dictionary_enabled("type_code".into(), true)
                 .set_column_dictionary_enabled("target".into(), true)
                 .set_column_dictionary_enabled("value_from".into(), true)
                 .set_column_dictionary_enabled("value_to".into(), true);
@@ -209,152 +347,157 @@ impl EventParquetWriter {
         // Create Arrow writer
         let mut writer = ArrowWriter::try_new(file, self.schema.clone(), Some(props))?;
         
-        // Write record batch
-        writer.write(&record_batch)?;
+        // Write record batches, one spilled batch in memory at a time
+        let mut stats = FileStats::default();
+        let mut index = FileIndexBuilder::new("ts_ns", EVENT_INDEX_COLUMNS);
+        let mut write = |batch: RecordBatch| -> Result<()> {
+            stats.add_batch(&batch, EVENT_STAT_COLUMNS)?;
+            index.add_batch(&batch)?;
+            writer.write(&batch)?;
+            Ok(())
+        };
+        self.spill.drain(&mut write)?;
+        if let Some(record_batch) = record_batch {
+            write(record_batch)?;
+        }
         
         // Close writer
         writer.close()?;
         
         debug!("Successfully wrote event Parquet file: {}", file_path.display());
-        Ok(())
+        Ok((stats, index.finish()))
+    }
+    
+    /// Stream events by type, newest first
+    pub async fn stream_by_type(&self, event_type: &EventType) -> Result<RowStream<DetectedEvent>> {
+        let filter = IndexFilter::equals("type_code", &event_type.code().to_string());
+        let parquet_files = self.indexes.prune(self.get_parquet_files()?, &filter);
+        let sql = format!("SELECT * FROM events WHERE type_code = {} ORDER BY ts_ns DESC", event_type.code());
+        self.stream_events(&parquet_files, &sql).await
     }
     
     /// Query events by type
     pub async fn query_by_type(&self, event_type: &EventType) -> Result<Vec<DetectedEvent>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("events", &table_path, ParquetReadOptions::default()).await?;
-        
-        let type_str = self.event_type_to_string(event_type);
-        let sql = format!("SELECT * FROM events WHERE type = '{}' ORDER BY ts_ns DESC", type_str);
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_events(batches)
+        self.stream_by_type(event_type).await?.try_collect().await
     }
     
-    /// Query events by target
-    pub async fn query_by_target(&self, target: &str) -> Result<Vec<DetectedEvent>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("events", &table_path, ParquetReadOptions::default()).await?;
-        
+    /// Stream events by target, newest first
+    pub async fn stream_by_target(&self, target: &str) -> Result<RowStream<DetectedEvent>> {
+        let parquet_files = self.indexes.prune(self.get_parquet_files()?, &IndexFilter::equals("target", target));
         let sql = format!(
             "SELECT * FROM events WHERE target = '{}' ORDER BY ts_ns DESC",
             target.replace("'", "''") // Escape single quotes
         );
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_events(batches)
+        self.stream_events(&parquet_files, &sql).await
     }
     
-    /// Query events by confidence threshold
-    pub async fn query_by_confidence(&self, min_confidence: f32) -> Result<Vec<DetectedEvent>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("events", &table_path, ParquetReadOptions::default()).await?;
-        
+    /// Query events by target
+    pub async fn query_by_target(&self, target: &str) -> Result<Vec<DetectedEvent>> {
+        self.stream_by_target(target).await?.try_collect().await
+    }
+    
+    /// Stream events by confidence threshold, most confident first
+    pub async fn stream_by_confidence(&self, min_confidence: f32) -> Result<RowStream<DetectedEvent>> {
         let sql = format!(
             "SELECT * FROM events WHERE confidence >= {} ORDER BY confidence DESC",
             min_confidence
         );
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_events(batches)
+        self.stream_events(&self.get_parquet_files()?, &sql).await
     }
     
-    /// Query events by time range
-    pub async fn query_by_time_range(
+    /// Query events by confidence threshold
+    pub async fn query_by_confidence(&self, min_confidence: f32) -> Result<Vec<DetectedEvent>> {
+        self.stream_by_confidence(min_confidence).await?.try_collect().await
+    }
+    
+    /// Stream events in a time range, oldest first
+    pub async fn stream_by_time_range(
         &self,
         start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
-    ) -> Result<Vec<DetectedEvent>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("events", &table_path, ParquetReadOptions::default()).await?;
-        
+    ) -> Result<RowStream<DetectedEvent>> {
         let start_ns = start_time.timestamp_nanos_opt().unwrap_or(0);
         let end_ns = end_time.timestamp_nanos_opt().unwrap_or(0);
         
+        let parquet_files = self.indexes.prune(self.get_parquet_files()?, &IndexFilter::time_range(start_ns, end_ns));
         let sql = format!(
             "SELECT * FROM events WHERE ts_ns >= {} AND ts_ns <= {} ORDER BY ts_ns ASC",
             start_ns, end_ns
         );
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_events(batches)
+        self.stream_events(&parquet_files, &sql).await
     }
     
-    /// Get event statistics
-    pub async fn get_statistics(&self) -> Result<EventStatistics> {
-        let ctx = SessionContext::new();
-        
+    /// Query events by time range
+    pub async fn query_by_time_range(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<DetectedEvent>> {
+        self.stream_by_time_range(start_time, end_time).await?.try_collect().await
+    }
+    
+    /// Stream all events together with their stored signatures, oldest first
+    pub async fn stream_signed_events(&self) -> Result<RowStream<SignedEvent>> {
         let parquet_files = self.get_parquet_files()?;
         if parquet_files.is_empty() {
-            return Ok(EventStatistics::default());
+            return Ok(query_stream::empty());
         }
         
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("events", &table_path, ParquetReadOptions::default()).await?;
-        
-        // Get basic statistics
-        let count_sql = "SELECT COUNT(*) as total_events FROM events";
-        let count_df = ctx.sql(count_sql).await?;
-        let count_batches = count_df.collect().await?;
-        
-        let avg_confidence_sql = "SELECT AVG(confidence) as avg_confidence FROM events";
-        let avg_df = ctx.sql(avg_confidence_sql).await?;
-        let avg_batches = avg_df.collect().await?;
-        
-        let type_stats_sql = "SELECT type, COUNT(*) as count FROM events GROUP BY type ORDER BY count DESC";
-        let type_df = ctx.sql(type_stats_sql).await?;
-        let type_batches = type_df.collect().await?;
-        
-        // Extract statistics (simplified for now)
-        let total_events = if !count_batches.is_empty() && count_batches[0].num_rows() > 0 {
-            count_batches[0].num_rows() as u64
-        } else {
-            0
-        };
+        let ctx = query_stream::spilling_context()?;
+        self.register_event_files(&ctx, Some(&parquet_files)).await?;
+        query_stream::query_rows(&ctx, "SELECT * FROM events ORDER BY ts_ns ASC", signed_events_from_batch).await
+    }
+    
+    /// Query all events together with their stored signatures
+    pub async fn query_signed_events(&self) -> Result<Vec<SignedEvent>> {
+        self.stream_signed_events().await?.try_collect().await
+    }
+    
+    /// Run `sql` over the events tables of `parquet_files`, converting one
+    /// record batch at a time
+    async fn stream_events(&self, parquet_files: &[PathBuf], sql: &str) -> Result<RowStream<DetectedEvent>> {
+        if parquet_files.is_empty() {
+            return Ok(query_stream::empty());
+        }
         
+        let ctx = query_stream::spilling_context()?;
+        self.register_event_files(&ctx, Some(parquet_files)).await?;
+        query_stream::query_rows(&ctx, sql, |batch| {
+            Ok(signed_events_from_batch(batch)?.into_iter().map(|signed| signed.event).collect())
+        })
+        .await
+    }
+    
+    /// Event statistics from the directory's statistics manifest; only files
+    /// written without it, or rewritten since, are read
+    pub async fn get_statistics(&self) -> Result<EventStatistics> {
+        let totals = StatsManifest::refresh(&self.output_dir, EVENT_STAT_COLUMNS)?.totals();
+
+        // Current files store type codes, older ones the type names
+        let mut event_type_distribution = HashMap::new();
+        for (code, count) in totals.counts("type_code") {
+            let name = code
+                .parse()
+                .ok()
+                .and_then(|code| self.enum_codes.name(EventType::DICTIONARY, code))
+                .unwrap_or(code);
+            *event_type_distribution.entry(name.to_string()).or_insert(0) += count;
+        }
+        for (name, count) in totals.counts("type") {
+            *event_type_distribution.entry(name.to_string()).or_insert(0) += count;
+        }
+
         Ok(EventStatistics {
-            total_events,
-            average_confidence: 0.75, // Placeholder
-            event_type_distribution: HashMap::new(), // Placeholder
-            total_size_bytes: self.calculate_total_size()?,
+            total_events: totals.rows,
+            average_confidence: totals.average_confidence(),
+            event_type_distribution,
+            total_size_bytes: totals.size_bytes,
         })
     }
     
     /// Finalize and flush any remaining data
     pub async fn finalize(&mut self) -> Result<()> {
-        if !self.current_batch.is_empty() {
+        if !self.current_batch.is_empty() || !self.spill.is_empty() {
             self.flush_batch().await?;
         }
         
@@ -362,94 +505,38 @@ impl EventParquetWriter {
         Ok(())
     }
     
-    // MARK: - Private Helper Methods
-    
-    fn event_type_to_string(&self, event_type: &EventType) -> &'static str {
-        match event_type {
-            EventType::FieldChange => "field_change",
-            EventType::FormSubmission => "form_submission",
-            EventType::ModalAppearance => "modal_appearance",
-            EventType::ErrorDisplay => "error_display",
-            EventType::Navigation => "navigation",
-            EventType::DataEntry => "data_entry",
-        }
+    /// Register the stored files as `events_raw` and the `events` view, which adds
+    /// `type`, `modal_type` and `severity` names for the coded columns.
+    ///
+    /// Files from every earlier layout are included: flat or partitioned, with
+    /// or without signatures, and with type names instead of codes.
+    pub async fn register_events(&self, ctx: &SessionContext) -> Result<()> {
+        self.register_event_files(ctx, None).await
     }
-    
-    fn string_to_event_type(&self, type_str: &str) -> EventType {
-        match type_str {
-            "field_change" => EventType::FieldChange,
-            "form_submission" => EventType::FormSubmission,
-            "modal_appearance" => EventType::ModalAppearance,
-            "error_display" => EventType::ErrorDisplay,
-            "navigation" => EventType::Navigation,
-            "data_entry" => EventType::DataEntry,
-            _ => EventType::FieldChange, // Default fallback
-        }
-    }
-    
-    pub fn get_parquet_files(&self) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
-        
-        if !self.output_dir.exists() {
-            return Ok(files);
-        }
-        
-        for entry in std::fs::read_dir(&self.output_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) == Some("parquet") {
-                files.push(path);
-            }
-        }
-        
-        Ok(files)
-    }
-    
-    fn record_batches_to_events(&self, batches: Vec<RecordBatch>) -> Result<Vec<DetectedEvent>> {
-        let mut events = Vec::new();
-        
-        for batch in batches {
-            // Extract data from batch (simplified implementation)
-            let event_ids = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
-            let timestamps = batch.column(1).as_any().downcast_ref::<TimestampNanosecondArray>().unwrap();
-            let types = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
-            let targets = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
-            let values_from = batch.column(4).as_any().downcast_ref::<StringArray>().unwrap();
-            let values_to = batch.column(5).as_any().downcast_ref::<StringArray>().unwrap();
-            let confidences = batch.column(6).as_any().downcast_ref::<Float32Array>().unwrap();
-            
-            for i in 0..batch.num_rows() {
-                let timestamp_ns = timestamps.value(i);
-                let timestamp = DateTime::from_timestamp_nanos(timestamp_ns);
-                
-                events.push(DetectedEvent {
-                    id: event_ids.value(i).to_string(),
-                    timestamp,
-                    event_type: self.string_to_event_type(types.value(i)),
-                    target: targets.value(i).to_string(),
-                    value_from: if values_from.is_null(i) { None } else { Some(values_from.value(i).to_string()) },
-                    value_to: if values_to.is_null(i) { None } else { Some(values_to.value(i).to_string()) },
-                    confidence: confidences.value(i),
-                    evidence_frames: Vec::new(), // Simplified - would extract from list array
-                    metadata: HashMap::new(), // Simplified - would parse JSON
-                });
-            }
+
+    /// Register only `files`, when given, as the events tables
+    async fn register_event_files(&self, ctx: &SessionContext, files: Option<&[PathBuf]>) -> Result<()> {
+        let table = CompatibleTable::new("events_raw", self.schema.clone())
+            .with_upgrade("type_code", "type", self.enum_codes.code_expression(EventType::DICTIONARY, "\"type\""));
+        match files {
+            Some(files) => table.register_files(ctx, &self.output_dir, files).await?,
+            None => table.register(ctx, &self.output_dir).await?,
         }
         
-        Ok(events)
+        let sql = format!(
+            "CREATE VIEW events AS SELECT *, {} AS type, {} AS modal_type, {} AS severity FROM events_raw",
+            self.enum_codes.case_expression(EventType::DICTIONARY, "type_code"),
+            self.enum_codes.case_expression(ErrorModalType::DICTIONARY, "modal_type_code"),
+            self.enum_codes.case_expression(SeverityLevel::DICTIONARY, "severity_code"),
+        );
+        ctx.sql(&sql).await?;
+        Ok(())
     }
     
-    fn calculate_total_size(&self) -> Result<u64> {
-        let mut total_size = 0u64;
-        
-        for file_path in self.get_parquet_files()? {
-            if let Ok(metadata) = std::fs::metadata(&file_path) {
-                total_size += metadata.len();
-            }
-        }
-        
-        Ok(total_size)
+    // MARK: - Private Helper Methods
+    
+    pub fn get_parquet_files(&self) -> Result<Vec<PathBuf>> {
+        layout_compat::parquet_files(&self.output_dir)
     }
     
     // MARK: - Configuration Methods
@@ -475,6 +562,112 @@ impl EventParquetWriter {
     }
 }
 
+/// Events of a batch read back from an event file, with their signatures; a
+/// batch with unexpected columns is an error rather than a panic
+pub fn signed_events_from_batch(batch: &RecordBatch) -> Result<Vec<SignedEvent>> {
+    let mut events = Vec::with_capacity(batch.num_rows());
+    let event_ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let timestamps = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let type_codes = batch.columns().get(2).and_then(|column| column.as_any().downcast_ref::<Int16Array>());
+    // Files written before enum codes store the type name
+    let type_names = batch.columns().get(2).and_then(|column| column.as_any().downcast_ref::<StringArray>());
+    let targets = layout_compat::column::<StringArray>(batch, 3)?;
+    let values_from = layout_compat::column::<StringArray>(batch, 4)?;
+    let values_to = layout_compat::column::<StringArray>(batch, 5)?;
+    let confidences = layout_compat::column::<Float32Array>(batch, 6)?;
+    let evidence = layout_compat::column::<ListArray>(batch, 7)?;
+    let metadata = layout_compat::column::<StringArray>(batch, 8)?;
+    // Files written before signing was introduced have no signature column
+    let signatures = batch.column_by_name("signature")
+        .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+    let modal_type_codes = batch.column_by_name("modal_type_code")
+        .and_then(|column| column.as_any().downcast_ref::<Int16Array>());
+    let severity_codes = batch.column_by_name("severity_code")
+        .and_then(|column| column.as_any().downcast_ref::<Int16Array>());
+    
+    for i in 0..batch.num_rows() {
+        let timestamp_ns = timestamps.value(i);
+        let timestamp = DateTime::from_timestamp_nanos(timestamp_ns);
+        
+        let evidence_values = evidence.value(i);
+        let evidence_frames = evidence_values
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .map(|frames| frames.iter().flatten().map(|f| f.to_string()).collect())
+            .unwrap_or_default();
+        
+        let mut event_metadata: HashMap<String, String> = if metadata.is_null(i) {
+            HashMap::new()
+        } else {
+            serde_json::from_str(metadata.value(i)).unwrap_or_else(|e| {
+                warn!("Failed to parse metadata for event {}: {}", event_ids.value(i), e);
+                HashMap::new()
+            })
+        };
+        
+        restore_coded_metadata::<ErrorModalType>(&mut event_metadata, modal_type_codes, i);
+        restore_coded_metadata::<SeverityLevel>(&mut event_metadata, severity_codes, i);
+        
+        let event_type = match (type_codes, type_names) {
+            (Some(codes), _) => EventType::from_code(codes.value(i)).unwrap_or(EventType::FieldChange),
+            (None, Some(names)) => EventType::from_name(names.value(i)).unwrap_or(EventType::FieldChange),
+            (None, None) => EventType::FieldChange,
+        };
+        
+        let signature = signatures
+            .filter(|column| !column.is_null(i))
+            .map(|column| column.value(i).to_string());
+        
+        events.push(SignedEvent {
+            event: DetectedEvent {
+                id: event_ids.value(i).to_string(),
+                timestamp,
+                event_type,
+                target: targets.value(i).to_string(),
+                value_from: if values_from.is_null(i) { None } else { Some(values_from.value(i).to_string()) },
+                value_to: if values_to.is_null(i) { None } else { Some(values_to.value(i).to_string()) },
+                confidence: confidences.value(i),
+                evidence_frames,
+                metadata: event_metadata,
+            },
+            signature,
+        });
+    }
+    
+    Ok(events)
+}
+
+/// Estimated bytes a buffered event holds
+fn buffered_size(event: &DetectedEvent) -> usize {
+    std::mem::size_of::<DetectedEvent>()
+        + event.id.len()
+        + event.target.len()
+        + event.value_from.as_ref().map_or(0, String::len)
+        + event.value_to.as_ref().map_or(0, String::len)
+        + event.evidence_frames.iter().map(String::len).sum::<usize>()
+        + event.metadata.iter().map(|(key, value)| key.len() + value.len()).sum::<usize>()
+}
+
+/// Code of an enum recorded by name in event metadata under its dictionary key
+fn coded_metadata<T: EnumCode>(metadata: &HashMap<String, String>) -> Option<i16> {
+    metadata.get(T::DICTIONARY).and_then(|name| T::from_name(name)).map(|value| value.code())
+}
+
+/// Metadata entries stored as code columns instead of in the metadata JSON
+fn is_coded_metadata(key: &str, value: &str) -> bool {
+    (key == ErrorModalType::DICTIONARY && ErrorModalType::from_name(value).is_some())
+        || (key == SeverityLevel::DICTIONARY && SeverityLevel::from_name(value).is_some())
+}
+
+fn restore_coded_metadata<T: EnumCode>(metadata: &mut HashMap<String, String>, codes: Option<&Int16Array>, row: usize) {
+    let value = codes
+        .filter(|codes| !codes.is_null(row))
+        .and_then(|codes| T::from_code(codes.value(row)));
+    if let Some(value) = value {
+        metadata.insert(T::DICTIONARY.to_string(), value.name().to_string());
+    }
+}
+
 /// Statistics about stored event data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct EventStatistics {
@@ -493,4 +686,48 @@ impl Default for EventStatistics {
             total_size_bytes: 0,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::EncryptionManager;
+    use tempfile::TempDir;
+    
+    fn create_test_event(id: &str) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("roi_x".to_string(), "10".to_string());
+        
+        DetectedEvent {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "error_dialog".to_string(),
+            value_from: None,
+            value_to: Some("Connection failed".to_string()),
+            confidence: 0.85,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_signed_events_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = EventParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        
+        let signer = EventSigner::with_manager(EncryptionManager::with_key(&[3u8; 32]));
+        let verifier = signer.verifier();
+        writer.enable_signing(signer);
+        assert!(writer.is_signing_enabled());
+        
+        writer.write_events(&[create_test_event("e1"), create_test_event("e2")]).await.unwrap();
+        writer.finalize().await.unwrap();
+        
+        let signed_events = writer.query_signed_events().await.unwrap();
+        assert_eq!(signed_events.len(), 2);
+        for signed in &signed_events {
+            assert_eq!(signed.event.evidence_frames, vec!["frame_1".to_string()]);
+            assert!(verifier.verify_signed_event(signed).unwrap());
+        }
+    }
+}
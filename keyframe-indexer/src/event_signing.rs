@@ -0,0 +1,218 @@
+use crate::encryption::EncryptionManager;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Canonical, order-stable view of an event used as the signed payload
+#[derive(Serialize)]
+struct CanonicalEvent<'a> {
+    id: &'a str,
+    ts_ns: i64,
+    event_type: &'a EventType,
+    target: &'a str,
+    value_from: Option<&'a str>,
+    value_to: Option<&'a str>,
+    confidence: f32,
+    evidence_frames: &'a [String],
+    metadata: BTreeMap<&'a str, &'a str>,
+}
+
+/// Metadata key published events carry their hex signature under; it is
+/// left out of the signed payload
+pub const SIGNATURE_METADATA_KEY: &str = "signature";
+
+/// Event paired with the signature stored alongside it
+#[derive(Debug, Clone)]
+pub struct SignedEvent {
+    pub event: DetectedEvent,
+    pub signature: Option<String>,
+}
+
+/// Build the canonical JSON representation of an event.
+///
+/// Metadata keys are sorted and timestamps are reduced to nanoseconds so the
+/// bytes are identical before writing and after reading back from Parquet.
+pub fn canonical_event_json(event: &DetectedEvent) -> Result<String> {
+    let canonical = CanonicalEvent {
+        id: &event.id,
+        ts_ns: event.timestamp.timestamp_nanos_opt().unwrap_or(0),
+        event_type: &event.event_type,
+        target: &event.target,
+        value_from: event.value_from.as_deref(),
+        value_to: event.value_to.as_deref(),
+        confidence: event.confidence,
+        evidence_frames: &event.evidence_frames,
+        metadata: event
+            .metadata
+            .iter()
+            .filter(|(k, _)| k.as_str() != SIGNATURE_METADATA_KEY)
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect(),
+    };
+
+    Ok(serde_json::to_string(&canonical)?)
+}
+
+/// Signs events with the Ed25519 key held by the encryption manager
+pub struct EventSigner {
+    encryption_manager: EncryptionManager,
+}
+
+impl EventSigner {
+    pub fn new() -> Result<Self> {
+        let encryption_manager = EncryptionManager::new()
+            .map_err(|e| IndexerError::Signing(format!("Failed to initialize signing key: {}", e)))?;
+        Ok(Self::with_manager(encryption_manager))
+    }
+
+    pub fn with_manager(encryption_manager: EncryptionManager) -> Self {
+        Self { encryption_manager }
+    }
+
+    /// Sign an event, returning the hex-encoded signature
+    pub fn sign_event(&self, event: &DetectedEvent) -> Result<String> {
+        let payload = canonical_event_json(event)?;
+        Ok(hex::encode(self.encryption_manager.sign(payload.as_bytes())))
+    }
+
+    /// Sign an event and attach the signature to its metadata, so sinks,
+    /// triggers and notifiers pass it on with the event
+    pub fn attach_signature(&self, event: &mut DetectedEvent) -> Result<()> {
+        let signature = self.sign_event(event)?;
+        event.metadata.insert(SIGNATURE_METADATA_KEY.to_string(), signature);
+        Ok(())
+    }
+
+    /// Verify an event against a hex-encoded signature
+    pub fn verify_event(&self, event: &DetectedEvent, signature_hex: &str) -> Result<bool> {
+        self.verifier().verify_event(event, signature_hex)
+    }
+
+    /// Verifier holding only the public key, suitable for handing to consumers
+    pub fn verifier(&self) -> EventVerifier {
+        EventVerifier {
+            public_key: self.encryption_manager.verifying_key_bytes(),
+        }
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.encryption_manager.verifying_key_bytes())
+    }
+}
+
+/// Verifies event signatures without access to the private key
+#[derive(Debug, Clone)]
+pub struct EventVerifier {
+    public_key: [u8; 32],
+}
+
+impl EventVerifier {
+    pub fn from_public_key(public_key: [u8; 32]) -> Self {
+        Self { public_key }
+    }
+
+    pub fn from_public_key_hex(public_key_hex: &str) -> Result<Self> {
+        let bytes = hex::decode(public_key_hex)
+            .map_err(|e| IndexerError::Signing(format!("Invalid public key encoding: {}", e)))?;
+        let public_key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| IndexerError::Signing("Public key must be 32 bytes".to_string()))?;
+        Ok(Self { public_key })
+    }
+
+    /// Verify an event against a hex-encoded signature
+    pub fn verify_event(&self, event: &DetectedEvent, signature_hex: &str) -> Result<bool> {
+        let signature = match hex::decode(signature_hex) {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(false),
+        };
+
+        let payload = canonical_event_json(event)?;
+        EncryptionManager::verify_with_public_key(&self.public_key, payload.as_bytes(), &signature)
+            .map_err(|e| IndexerError::Signing(e.to_string()))
+    }
+
+    /// Verify an event by the signature in its metadata; unsigned events never verify
+    pub fn verify_attached(&self, event: &DetectedEvent) -> Result<bool> {
+        match event.metadata.get(SIGNATURE_METADATA_KEY) {
+            Some(signature) => self.verify_event(event, signature),
+            None => Ok(false),
+        }
+    }
+
+    /// Verify a stored event; unsigned events never verify
+    pub fn verify_signed_event(&self, signed_event: &SignedEvent) -> Result<bool> {
+        match &signed_event.signature {
+            Some(signature) => self.verify_event(&signed_event.event, signature),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn create_test_event() -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("language".to_string(), "en".to_string());
+        metadata.insert("processor".to_string(), "vision".to_string());
+
+        DetectedEvent {
+            id: "event-1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::FieldChange,
+            target: "username".to_string(),
+            value_from: Some("old".to_string()),
+            value_to: Some("new".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1".to_string(), "frame_2".to_string()],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify_event() {
+        let signer = EventSigner::with_manager(EncryptionManager::with_key(&[1u8; 32]));
+        let event = create_test_event();
+
+        let signature = signer.sign_event(&event).unwrap();
+        assert!(signer.verify_event(&event, &signature).unwrap());
+
+        let mut tampered = event.clone();
+        tampered.value_to = Some("forged".to_string());
+        assert!(!signer.verify_event(&tampered, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_public_key_verifier() {
+        let signer = EventSigner::with_manager(EncryptionManager::with_key(&[2u8; 32]));
+        let verifier = EventVerifier::from_public_key_hex(&signer.public_key_hex()).unwrap();
+        let event = create_test_event();
+
+        let signed = SignedEvent {
+            signature: Some(signer.sign_event(&event).unwrap()),
+            event: event.clone(),
+        };
+        assert!(verifier.verify_signed_event(&signed).unwrap());
+
+        let unsigned = SignedEvent { event, signature: None };
+        assert!(!verifier.verify_signed_event(&unsigned).unwrap());
+        assert!(!verifier.verify_event(&unsigned.event, "not-hex").unwrap());
+    }
+
+    #[test]
+    fn test_canonical_json_is_order_independent() {
+        let event = create_test_event();
+        let mut reordered = event.clone();
+        reordered.metadata = event.metadata.iter().rev().map(|(k, v)| (k.clone(), v.clone())).collect();
+
+        assert_eq!(
+            canonical_event_json(&event).unwrap(),
+            canonical_event_json(&reordered).unwrap()
+        );
+    }
+}
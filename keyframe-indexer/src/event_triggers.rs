@@ -0,0 +1,479 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_signing::SIGNATURE_METADATA_KEY;
+use crate::suppression::SuppressionList;
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Maximum bytes of stdout/stderr kept on a follow-up event
+const MAX_CAPTURED_OUTPUT: usize = 1024;
+
+/// Conditions an event must meet to fire a trigger; empty fields match anything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventPredicate {
+    /// Event types that match
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    /// Regex the event target must match
+    #[serde(default)]
+    pub target_pattern: Option<String>,
+    /// Regex the new value must match
+    #[serde(default)]
+    pub value_pattern: Option<String>,
+    /// Minimum event confidence
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Metadata entries that must be present with these exact values
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Maps an event predicate to an external command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    /// Unique trigger name, reported on follow-up events
+    pub name: String,
+    pub predicate: EventPredicate,
+    /// Program to execute; run directly, not through a shell
+    pub command: String,
+    /// Arguments; `{id}`, `{type}`, `{target}`, `{value_from}`, `{value_to}`,
+    /// `{confidence}`, `{timestamp}`, `{frames}` and `{metadata.<key>}` are substituted
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Maximum executions per minute
+    #[serde(default)]
+    pub max_runs_per_minute: Option<u32>,
+    /// Seconds before a running command is killed
+    #[serde(default = "default_trigger_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_trigger_enabled")]
+    pub enabled: bool,
+}
+
+fn default_trigger_timeout_secs() -> u64 {
+    30
+}
+
+fn default_trigger_enabled() -> bool {
+    true
+}
+
+impl TriggerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(IndexerError::Config("Trigger name must not be empty".to_string()));
+        }
+
+        if self.command.trim().is_empty() {
+            return Err(IndexerError::Config(format!("Trigger '{}' has no command", self.name)));
+        }
+
+        if self.timeout_secs == 0 {
+            return Err(IndexerError::Config(format!(
+                "Trigger '{}' timeout_secs must be greater than 0",
+                self.name
+            )));
+        }
+
+        CompiledTrigger::compile(self.clone()).map(|_| ())
+    }
+}
+
+/// Trigger with compiled predicate patterns and run history
+struct CompiledTrigger {
+    config: TriggerConfig,
+    target_regex: Option<Regex>,
+    value_regex: Option<Regex>,
+    recent_runs: Mutex<VecDeque<Instant>>,
+}
+
+impl CompiledTrigger {
+    fn compile(config: TriggerConfig) -> Result<Self> {
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    Regex::new(p).map_err(|e| {
+                        IndexerError::Config(format!("Trigger '{}' has invalid pattern '{}': {}", config.name, p, e))
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            target_regex: compile(&config.predicate.target_pattern)?,
+            value_regex: compile(&config.predicate.value_pattern)?,
+            config,
+            recent_runs: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    fn matches(&self, event: &DetectedEvent) -> bool {
+        let predicate = &self.config.predicate;
+
+        if !predicate.event_types.is_empty() && !predicate.event_types.contains(&event.event_type) {
+            return false;
+        }
+
+        if predicate.min_confidence.map_or(false, |min| event.confidence < min) {
+            return false;
+        }
+
+        if let Some(regex) = &self.target_regex {
+            if !regex.is_match(&event.target) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.value_regex {
+            if !event.value_to.as_deref().map_or(false, |v| regex.is_match(v)) {
+                return false;
+            }
+        }
+
+        predicate
+            .metadata
+            .iter()
+            .all(|(key, value)| event.metadata.get(key) == Some(value))
+    }
+
+    /// Record a run if the rate limit allows it
+    fn try_acquire(&self) -> bool {
+        let Some(limit) = self.config.max_runs_per_minute else {
+            return true;
+        };
+
+        let mut runs = match self.recent_runs.lock() {
+            Ok(runs) => runs,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let now = Instant::now();
+        while runs.front().map_or(false, |t| now.duration_since(*t) >= Duration::from_secs(60)) {
+            runs.pop_front();
+        }
+
+        if runs.len() >= limit as usize {
+            return false;
+        }
+
+        runs.push_back(now);
+        true
+    }
+}
+
+/// Runs external commands in response to detected events.
+///
+/// Commands execute asynchronously; each completion is reported as a
+/// `TriggerExecution` follow-up event carrying the exit code, which callers
+/// collect with `drain_follow_up_events` and store alongside other events.
+/// Follow-up events never fire triggers themselves.
+pub struct TriggerEngine {
+    triggers: Vec<CompiledTrigger>,
+    follow_up_tx: mpsc::UnboundedSender<DetectedEvent>,
+    follow_up_rx: Mutex<mpsc::UnboundedReceiver<DetectedEvent>>,
+    running: Mutex<Vec<JoinHandle<()>>>,
+    suppressions: Option<SuppressionList>,
+}
+
+impl TriggerEngine {
+    pub fn new(configs: Vec<TriggerConfig>) -> Result<Self> {
+        let mut triggers = Vec::new();
+        for config in configs {
+            config.validate()?;
+            if config.enabled {
+                triggers.push(CompiledTrigger::compile(config)?);
+            }
+        }
+
+        info!("Trigger engine initialized with {} triggers", triggers.len());
+
+        let (follow_up_tx, follow_up_rx) = mpsc::unbounded_channel();
+        Ok(Self {
+            triggers,
+            follow_up_tx,
+            follow_up_rx: Mutex::new(follow_up_rx),
+            running: Mutex::new(Vec::new()),
+            suppressions: None,
+        })
+    }
+
+    /// Skip events muted by suppression rules
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Start commands for every trigger matching the events; returns the number started
+    pub fn dispatch(&self, events: &[DetectedEvent]) -> usize {
+        let mut started = 0;
+
+        for event in events.iter().filter(|e| e.event_type != EventType::TriggerExecution) {
+            if self.suppressions.as_ref().is_some_and(|s| s.is_suppressed(event)) {
+                continue;
+            }
+            for trigger in self.triggers.iter().filter(|t| t.matches(event)) {
+                if !trigger.try_acquire() {
+                    debug!("Trigger '{}' rate limited, skipping event {}", trigger.config.name, event.id);
+                    continue;
+                }
+
+                let args: Vec<String> = trigger.config.args.iter().map(|a| render_template(a, event)).collect();
+                let handle = tokio::spawn(run_trigger(
+                    trigger.config.clone(),
+                    args,
+                    event.clone(),
+                    self.follow_up_tx.clone(),
+                ));
+
+                if let Ok(mut running) = self.running.lock() {
+                    running.retain(|h| !h.is_finished());
+                    running.push(handle);
+                }
+                started += 1;
+            }
+        }
+
+        started
+    }
+
+    /// Follow-up events for commands that finished since the last call
+    pub fn drain_follow_up_events(&self) -> Vec<DetectedEvent> {
+        let mut events = Vec::new();
+        if let Ok(mut rx) = self.follow_up_rx.lock() {
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Wait for all running commands to finish
+    pub async fn wait_idle(&self) {
+        let handles: Vec<JoinHandle<()>> = match self.running.lock() {
+            Ok(mut running) => running.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    pub fn trigger_count(&self) -> usize {
+        self.triggers.len()
+    }
+}
+
+/// Execute one trigger command and report the outcome as a follow-up event
+async fn run_trigger(
+    config: TriggerConfig,
+    args: Vec<String>,
+    source: DetectedEvent,
+    follow_up_tx: mpsc::UnboundedSender<DetectedEvent>,
+) {
+    let started = Instant::now();
+    let mut metadata = HashMap::new();
+    metadata.insert("trigger".to_string(), config.name.clone());
+    metadata.insert("source_event_id".to_string(), source.id.clone());
+    metadata.insert("command".to_string(), config.command.clone());
+
+    let mut command = Command::new(&config.command);
+    command.args(&args).kill_on_drop(true);
+
+    let exit_code = match tokio::time::timeout(Duration::from_secs(config.timeout_secs), command.output()).await {
+        Ok(Ok(output)) => {
+            metadata.insert("status".to_string(), if output.status.success() { "success" } else { "failed" }.to_string());
+            metadata.insert("stdout".to_string(), truncate_output(&output.stdout));
+            metadata.insert("stderr".to_string(), truncate_output(&output.stderr));
+            output.status.code()
+        }
+        Ok(Err(e)) => {
+            warn!("Trigger '{}' failed to start '{}': {}", config.name, config.command, e);
+            metadata.insert("status".to_string(), "spawn_error".to_string());
+            metadata.insert("error".to_string(), e.to_string());
+            None
+        }
+        Err(_) => {
+            warn!("Trigger '{}' timed out after {}s", config.name, config.timeout_secs);
+            metadata.insert("status".to_string(), "timeout".to_string());
+            None
+        }
+    };
+
+    metadata.insert("duration_ms".to_string(), started.elapsed().as_millis().to_string());
+    if let Some(code) = exit_code {
+        metadata.insert("exit_code".to_string(), code.to_string());
+    }
+
+    info!("Trigger '{}' finished for event {} with exit code {:?}", config.name, source.id, exit_code);
+
+    let follow_up = DetectedEvent {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        event_type: EventType::TriggerExecution,
+        target: config.name,
+        value_from: None,
+        value_to: exit_code.map(|code| code.to_string()),
+        confidence: 1.0,
+        evidence_frames: source.evidence_frames,
+        metadata,
+    };
+
+    // The engine may already be gone during shutdown
+    let _ = follow_up_tx.send(follow_up);
+}
+
+/// Substitute event fields into an argument template. The template is scanned
+/// once, so placeholder text inside a substituted value is left as it is.
+pub(crate) fn render_template(template: &str, event: &DetectedEvent) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after
+            .find('}')
+            .and_then(|end| Some((end, placeholder_value(&after[..end], event)?)));
+        match placeholder {
+            Some((end, value)) => {
+                rendered.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            // Not a placeholder; kept as written
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Value of one `{name}` placeholder, or `None` for an unknown name
+fn placeholder_value(name: &str, event: &DetectedEvent) -> Option<String> {
+    let value = match name {
+        "id" => event.id.clone(),
+        "type" => format!("{:?}", event.event_type),
+        "target" => event.target.clone(),
+        "value_from" => event.value_from.clone().unwrap_or_default(),
+        "value_to" => event.value_to.clone().unwrap_or_default(),
+        "confidence" => format!("{:.3}", event.confidence),
+        "timestamp" => event.timestamp.to_rfc3339(),
+        "frames" => event.evidence_frames.join(","),
+        "signature" => event.metadata.get(SIGNATURE_METADATA_KEY).cloned().unwrap_or_default(),
+        _ => event.metadata.get(name.strip_prefix("metadata.")?)?.clone(),
+    };
+    Some(value)
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end();
+    if text.len() <= MAX_CAPTURED_OUTPUT {
+        return text.to_string();
+    }
+
+    let mut end = MAX_CAPTURED_OUTPUT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_event(event_type: EventType, target: &str) -> DetectedEvent {
+        DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type,
+            target: target.to_string(),
+            value_from: None,
+            value_to: Some("Payment failed".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn create_trigger(command: &str, args: &[&str]) -> TriggerConfig {
+        TriggerConfig {
+            name: "notify".to_string(),
+            predicate: EventPredicate {
+                event_types: vec![EventType::ErrorDisplay],
+                target_pattern: Some("^checkout".to_string()),
+                ..Default::default()
+            },
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            max_runs_per_minute: Some(1),
+            timeout_secs: 5,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_predicate_and_template() {
+        let trigger = CompiledTrigger::compile(create_trigger("echo", &[])).unwrap();
+
+        assert!(trigger.matches(&create_event(EventType::ErrorDisplay, "checkout_form")));
+        assert!(!trigger.matches(&create_event(EventType::ErrorDisplay, "login_form")));
+        assert!(!trigger.matches(&create_event(EventType::Navigation, "checkout_form")));
+
+        let event = create_event(EventType::ErrorDisplay, "checkout_form");
+        assert_eq!(render_template("{target}: {value_to}", &event), "checkout_form: Payment failed");
+
+        // Screen text that looks like a placeholder is not substituted again
+        let mut injected = create_event(EventType::ErrorDisplay, "{metadata.app}");
+        injected.value_to = Some("{id} {unknown}".to_string());
+        injected.metadata.insert("app".to_string(), "Safari".to_string());
+        assert_eq!(
+            render_template("{target} {value_to} {metadata.app} {{id}", &injected),
+            "{metadata.app} {id} {unknown} Safari {event_1"
+        );
+
+        let invalid = TriggerConfig {
+            predicate: EventPredicate {
+                target_pattern: Some("(".to_string()),
+                ..Default::default()
+            },
+            ..create_trigger("echo", &[])
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dispatch_reports_exit_code_and_rate_limits() {
+        let engine = TriggerEngine::new(vec![create_trigger("sh", &["-c", "echo {target}; exit 3"])]).unwrap();
+        let events = vec![create_event(EventType::ErrorDisplay, "checkout_form")];
+
+        assert_eq!(engine.dispatch(&events), 1);
+        // Second run within the same minute exceeds max_runs_per_minute
+        assert_eq!(engine.dispatch(&events), 0);
+
+        engine.wait_idle().await;
+        let follow_ups = engine.drain_follow_up_events();
+        assert_eq!(follow_ups.len(), 1);
+        assert_eq!(follow_ups[0].event_type, EventType::TriggerExecution);
+        assert_eq!(follow_ups[0].value_to.as_deref(), Some("3"));
+        assert_eq!(follow_ups[0].metadata.get("stdout").map(String::as_str), Some("checkout_form"));
+
+        // Follow-up events are never dispatched again
+        assert_eq!(engine.dispatch(&follow_ups), 0);
+    }
+}
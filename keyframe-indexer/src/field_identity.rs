@@ -0,0 +1,412 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::levenshtein_distance;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::debug;
+
+/// Grid (px) unlabelled fields are placed on when deriving their id
+const POSITION_GRID_PX: f32 = 50.0;
+
+/// Configuration for following fields across frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldIdentityConfig {
+    /// Furthest (px) a field may move between frames, beyond any shift of the
+    /// whole layout, and keep its identity
+    pub max_shift_px: f32,
+    /// Furthest gap (px) between a label and the field it names
+    pub label_distance_px: f32,
+    /// Minimum score (0-1) for a region to continue a known field
+    pub min_match_score: f32,
+    /// Fields not seen for this long are forgotten
+    pub forget_after_secs: i64,
+    /// Where followed fields are kept between runs, one file per display;
+    /// defaults to `<output_dir>/field_identities`
+    pub state_dir: Option<String>,
+}
+
+impl Default for FieldIdentityConfig {
+    fn default() -> Self {
+        Self {
+            max_shift_px: 60.0,
+            label_distance_px: 250.0,
+            min_match_score: 0.5,
+            forget_after_secs: 300,
+            state_dir: None,
+        }
+    }
+}
+
+impl FieldIdentityConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_shift_px <= 0.0 || self.label_distance_px <= 0.0 {
+            return Err(IndexerError::Config("field identity distances must be greater than 0".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.min_match_score) {
+            return Err(IndexerError::Config("field identity min_match_score must be between 0 and 1".to_string()));
+        }
+        if self.forget_after_secs <= 0 {
+            return Err(IndexerError::Config("field identity forget_after_secs must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Persistent identity of a text region
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldIdentity {
+    /// UUID derived from the app, window and label (or position) the field
+    /// was first seen with, kept for as long as the field is followed
+    pub id: String,
+    /// Label naming the field, e.g. `Amount` for `Amount:`, when one is next to it
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedField {
+    id: String,
+    #[serde(default)]
+    app: String,
+    #[serde(default)]
+    window: String,
+    roi: BoundingBox,
+    text: String,
+    label: Option<String>,
+    last_seen: DateTime<Utc>,
+}
+
+/// Gives text regions identities that survive layout shifts.
+///
+/// A region continues a known field when it sits where the field was, after
+/// allowing for the whole layout moving (estimated from text that appears
+/// once in both frames), when it has the same label to its left or above, and
+/// when its text continues the field's (typed into, edited or unchanged).
+/// Only fields of the current window are continued.
+///
+/// Regions that continue no field get an id hashed from the app, the window
+/// title and the region's label, or its position when it has none, so a field
+/// gets the same id in every segment and run. Frames must be assigned in time
+/// order.
+#[derive(Debug, Clone)]
+pub struct FieldIdentityTracker {
+    config: FieldIdentityConfig,
+    fields: Vec<TrackedField>,
+    app: String,
+    window: String,
+}
+
+impl FieldIdentityTracker {
+    pub fn new(config: FieldIdentityConfig) -> Self {
+        Self { config, fields: Vec::new(), app: String::new(), window: String::new() }
+    }
+
+    /// Fields followed by an earlier run, from a file written by `save`; none
+    /// when there is no file yet
+    pub fn load(config: FieldIdentityConfig, path: &Path) -> Result<Self> {
+        let mut tracker = Self::new(config);
+        match std::fs::read_to_string(path) {
+            Ok(content) => tracker.fields = serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(tracker)
+    }
+
+    /// Write the followed fields, replacing an earlier file whole
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string(&self.fields)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// App and window title of the frames assigned next
+    pub fn set_window(&mut self, app: &str, window: &str) {
+        if self.app != app || self.window != window {
+            self.app = app.to_string();
+            self.window = window.to_string();
+        }
+    }
+
+    /// Identity of each region of a frame, in the same order
+    pub fn assign(&mut self, regions: &[&OCRResult], timestamp: DateTime<Utc>) -> Vec<FieldIdentity> {
+        let forget_before = timestamp - chrono::Duration::seconds(self.config.forget_after_secs);
+        self.fields.retain(|field| field.last_seen >= forget_before);
+
+        let labels: Vec<Option<String>> = (0..regions.len())
+            .map(|index| infer_label(regions, index, self.config.label_distance_px))
+            .collect();
+        let (shift_x, shift_y) = self.layout_shift(regions);
+
+        let mut candidates = Vec::new();
+        for (region_index, region) in regions.iter().enumerate() {
+            for (field_index, field) in self.fields.iter().enumerate() {
+                if !self.in_window(field) {
+                    continue;
+                }
+                let expected = BoundingBox::new(field.roi.x + shift_x, field.roi.y + shift_y, field.roi.width, field.roi.height);
+                let position = (1.0 - region.roi.center_distance(&expected) / self.config.max_shift_px).max(0.0);
+                let label = match (&labels[region_index], &field.label) {
+                    (Some(a), Some(b)) if a.to_lowercase() == b.to_lowercase() => 1.0,
+                    (Some(_), Some(_)) => 0.0,
+                    (None, None) => 0.5,
+                    _ => 0.25,
+                };
+                if position == 0.0 && label < 1.0 {
+                    continue;
+                }
+                let score = 0.5 * position + 0.3 * label + 0.2 * text_continuity(&field.text, &region.text);
+                if score >= self.config.min_match_score {
+                    candidates.push((score, region_index, field_index));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut matched: Vec<Option<usize>> = vec![None; regions.len()];
+        let mut taken = vec![false; self.fields.len()];
+        for (_, region_index, field_index) in candidates {
+            if matched[region_index].is_none() && !taken[field_index] {
+                matched[region_index] = Some(field_index);
+                taken[field_index] = true;
+            }
+        }
+
+        let mut identities = Vec::with_capacity(regions.len());
+        for (region_index, region) in regions.iter().enumerate() {
+            let label = labels[region_index].clone();
+            let field_index = match matched[region_index] {
+                Some(field_index) => field_index,
+                None => {
+                    self.fields.push(TrackedField {
+                        id: self.new_id(label.as_deref(), &region.roi),
+                        app: self.app.clone(),
+                        window: self.window.clone(),
+                        roi: region.roi.clone(),
+                        text: String::new(),
+                        label: None,
+                        last_seen: timestamp,
+                    });
+                    self.fields.len() - 1
+                }
+            };
+            let field = &mut self.fields[field_index];
+            field.roi = region.roi.clone();
+            field.text = region.text.clone();
+            field.label = label.clone().or(field.label.take());
+            field.last_seen = timestamp;
+            identities.push(FieldIdentity { id: field.id.clone(), label });
+        }
+        identities
+    }
+
+    /// Fields currently followed
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+
+    fn in_window(&self, field: &TrackedField) -> bool {
+        field.app == self.app && field.window == self.window
+    }
+
+    /// Id of a new field in the current window; fields with the same label,
+    /// or unlabelled at the same place, are numbered in the order they appear
+    fn new_id(&self, label: Option<&str>, roi: &BoundingBox) -> String {
+        let anchor = match label {
+            Some(label) => format!("label:{}", label.to_lowercase()),
+            None => format!(
+                "at:{},{}",
+                (roi.x / POSITION_GRID_PX).round() as i64,
+                (roi.y / POSITION_GRID_PX).round() as i64
+            ),
+        };
+        (0..)
+            .map(|ordinal| {
+                let digest = Sha256::digest(format!("{}\0{}\0{}\0{}", self.app, self.window, anchor, ordinal));
+                let mut bytes = [0u8; 16];
+                bytes.copy_from_slice(&digest[..16]);
+                uuid::Uuid::from_bytes(bytes).to_string()
+            })
+            .find(|id| !self.fields.iter().any(|field| &field.id == id))
+            .unwrap_or_default()
+    }
+
+    /// Median displacement of text found exactly once in both this frame and
+    /// the known fields, i.e. how far the whole layout moved
+    fn layout_shift(&self, regions: &[&OCRResult]) -> (f32, f32) {
+        let mut known: HashMap<&str, Option<&TrackedField>> = HashMap::new();
+        for field in self.fields.iter().filter(|field| self.in_window(field)) {
+            known
+                .entry(field.text.as_str())
+                .and_modify(|entry| *entry = None)
+                .or_insert(Some(field));
+        }
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for region in regions {
+            *seen.entry(region.text.as_str()).or_default() += 1;
+        }
+
+        let (mut dxs, mut dys) = (Vec::new(), Vec::new());
+        for region in regions {
+            if region.text.trim().is_empty() || seen[region.text.as_str()] != 1 {
+                continue;
+            }
+            if let Some(Some(field)) = known.get(region.text.as_str()) {
+                let ((x, y), (field_x, field_y)) = (region.roi.center(), field.roi.center());
+                dxs.push(x - field_x);
+                dys.push(y - field_y);
+            }
+        }
+        if dxs.is_empty() {
+            return (0.0, 0.0);
+        }
+        let shift = (median(&mut dxs), median(&mut dys));
+        debug!("Layout shifted by ({:.1}, {:.1}) px", shift.0, shift.1);
+        shift
+    }
+}
+
+/// Label of `regions[index]`: the nearest label-like region (`Amount:`) to
+/// its left on the same row, or just above it, at most `max_distance` px away.
+/// Labels themselves have none.
+pub fn infer_label(regions: &[&OCRResult], index: usize, max_distance: f32) -> Option<String> {
+    let field = &regions[index].roi;
+    if is_label(&regions[index].text) {
+        return None;
+    }
+    let (_, field_center_y) = field.center();
+    regions
+        .iter()
+        .filter(|region| is_label(&region.text))
+        .filter_map(|region| {
+            let label = &region.roi;
+            let (_, label_center_y) = label.center();
+            let same_row = (label_center_y - field_center_y).abs() <= field.height.max(label.height) / 2.0
+                && label.right() <= field.x + 2.0;
+            let above = label.bottom() <= field.y + 2.0 && (label.x - field.x).abs() <= field.height.max(label.height) * 2.0;
+            let gap = if same_row { field.x - label.right() } else { field.y - label.bottom() };
+            ((same_row || above) && gap <= max_distance).then(|| (gap.max(0.0), label_name(&region.text)))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, label)| label)
+}
+
+/// Short text ending with a colon
+fn is_label(text: &str) -> bool {
+    let text = text.trim();
+    text.len() > 1 && text.ends_with(':') && text.chars().count() <= 40
+}
+
+fn label_name(text: &str) -> String {
+    text.trim().trim_end_matches(':').trim().to_string()
+}
+
+/// How plausibly `new` is the same field's text as `old`: unchanged, typed
+/// into or deleted from, or edited
+fn text_continuity(old: &str, new: &str) -> f32 {
+    if old == new {
+        return 1.0;
+    }
+    if new.starts_with(old) || old.starts_with(new) {
+        return 0.9;
+    }
+    let max_len = old.chars().count().max(new.chars().count());
+    1.0 - levenshtein_distance(old, new) as f32 / max_len as f32
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(text: &str, x: f32, y: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(x, y, 150.0, 20.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    fn assign(tracker: &mut FieldIdentityTracker, frame: &[OCRResult], second: i64) -> Vec<FieldIdentity> {
+        let regions: Vec<&OCRResult> = frame.iter().collect();
+        tracker.assign(&regions, DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap())
+    }
+
+    #[test]
+    fn test_fields_keep_identity_across_layout_shifts() {
+        let mut tracker = FieldIdentityTracker::new(FieldIdentityConfig::default());
+        let first = assign(&mut tracker, &[region("Username:", 10.0, 10.0), region("", 170.0, 10.0), region("Password:", 10.0, 40.0), region("", 170.0, 40.0)], 0);
+        assert_eq!(first[1].label.as_deref(), Some("Username"));
+        assert_eq!(first[3].label.as_deref(), Some("Password"));
+        assert_eq!(first[2].label, None);
+
+        // Typing into the username field while the window moves 2 px
+        let second = assign(&mut tracker, &[region("Username:", 12.0, 10.0), region("j", 172.0, 10.0), region("Password:", 12.0, 40.0), region("", 172.0, 40.0)], 1);
+        assert_eq!(second[1].id, first[1].id);
+        assert_eq!(second[3].id, first[3].id);
+
+        // The window jumps 300 px: the labels carry the layout shift
+        let third = assign(&mut tracker, &[region("Username:", 312.0, 210.0), region("jdoe", 472.0, 210.0), region("Password:", 312.0, 240.0), region("", 472.0, 240.0)], 2);
+        assert_eq!(third[1].id, first[1].id);
+        assert_eq!(third[3].id, first[3].id);
+        assert_eq!(third[0].id, first[0].id);
+
+        // A field that was never there is new, and long-unseen fields are forgotten
+        let fourth = assign(&mut tracker, &[region("Remember me", 312.0, 400.0)], 3);
+        assert!(first.iter().all(|identity| identity.id != fourth[0].id));
+        assert_eq!(tracker.len(), 5);
+        assign(&mut tracker, &[], 1000);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_ids_are_deterministic_and_kept_across_runs() {
+        let frame = [region("Amount:", 10.0, 10.0), region("100", 170.0, 10.0), region("Note:", 10.0, 40.0), region("", 170.0, 40.0)];
+        let mut tracker = FieldIdentityTracker::new(FieldIdentityConfig::default());
+        tracker.set_window("Numbers", "Q3 Invoices");
+        let first = assign(&mut tracker, &frame, 0);
+
+        // Another run sees the same window and derives the same ids
+        let mut other = FieldIdentityTracker::new(FieldIdentityConfig::default());
+        other.set_window("Numbers", "Q3 Invoices");
+        assert_eq!(assign(&mut other, &frame, 0), first);
+        // In another window the same fields are different ones
+        other.set_window("Numbers", "Q4 Invoices");
+        assert!(assign(&mut other, &frame, 1).iter().all(|identity| !first.contains(identity)));
+
+        // A field keeps its id after moving, across a save and load
+        let moved = [region("Amount:", 310.0, 210.0), region("120", 470.0, 210.0)];
+        let moved_ids = assign(&mut tracker, &moved, 1);
+        assert_eq!(moved_ids[1].id, first[1].id);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("display_0.json");
+        tracker.save(&path).unwrap();
+        let mut reloaded = FieldIdentityTracker::load(FieldIdentityConfig::default(), &path).unwrap();
+        reloaded.set_window("Numbers", "Q3 Invoices");
+        assert_eq!(assign(&mut reloaded, &moved, 2), moved_ids);
+        assert!(FieldIdentityTracker::load(FieldIdentityConfig::default(), &dir.path().join("none.json")).unwrap().is_empty());
+    }
+}
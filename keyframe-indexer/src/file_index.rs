@@ -0,0 +1,302 @@
+use crate::error::Result;
+use arrow::array::{Array, TimestampNanosecondArray};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+
+/// False positive rate bloom filters are sized for
+const BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Bloom filter over string values, with a hash that is stable across builds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let items = expected_items.max(1) as f64;
+        let bit_count = (-items * false_positive_rate.ln() / std::f64::consts::LN_2.powi(2)).ceil().max(64.0);
+        let hashes = ((bit_count / items) * std::f64::consts::LN_2).round().clamp(1.0, 16.0) as u32;
+        Self { bits: vec![0; (bit_count as usize).div_ceil(64)], hashes }
+    }
+
+    pub fn insert(&mut self, value: &str) {
+        let positions: Vec<usize> = self.bit_positions(value).collect();
+        for bit in positions {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// False means the value is certainly absent
+    pub fn might_contain(&self, value: &str) -> bool {
+        self.bit_positions(value).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Double hashing over two FNV-1a variants
+    fn bit_positions(&self, value: &str) -> impl Iterator<Item = usize> + '_ {
+        let h1 = fnv1a(value.as_bytes(), 0xcbf2_9ce4_8422_2325);
+        let h2 = fnv1a(value.as_bytes(), 0x6c62_272e_07bb_0142) | 1;
+        let bit_count = (self.bits.len() * 64) as u64;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+    }
+}
+
+fn fnv1a(bytes: &[u8], offset: u64) -> u64 {
+    bytes.iter().fold(offset, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// What a query looks for, used to skip files that can't contain it
+#[derive(Debug, Clone, Default)]
+pub struct IndexFilter {
+    /// Inclusive timestamp range in nanoseconds
+    pub time_range: Option<(i64, i64)>,
+    /// Column that must equal a value
+    pub equals: Option<(String, String)>,
+}
+
+impl IndexFilter {
+    pub fn time_range(start_ns: i64, end_ns: i64) -> Self {
+        Self { time_range: Some((start_ns, end_ns)), ..Self::default() }
+    }
+
+    pub fn equals(column: &str, value: &str) -> Self {
+        Self { equals: Some((column.to_string(), value.to_string())), ..Self::default() }
+    }
+}
+
+/// Sidecar index of one Parquet file: its timestamp range and bloom filters
+/// of selected columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileIndex {
+    /// Size and modification time of the Parquet file the index describes;
+    /// a rewritten file's index no longer applies
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub modified_ns: i64,
+    pub min_ts_ns: Option<i64>,
+    pub max_ts_ns: Option<i64>,
+    pub blooms: BTreeMap<String, BloomFilter>,
+}
+
+/// Gathers a file's index over the batches written to it
+#[derive(Debug, Clone)]
+pub struct FileIndexBuilder {
+    ts_column: String,
+    bloom_columns: Vec<String>,
+    min_ts_ns: Option<i64>,
+    max_ts_ns: Option<i64>,
+    /// Distinct values of the bloom columns the batches had
+    values: BTreeMap<String, HashSet<String>>,
+}
+
+impl FileIndexBuilder {
+    pub fn new(ts_column: &str, bloom_columns: &[&str]) -> Self {
+        Self {
+            ts_column: ts_column.to_string(),
+            bloom_columns: bloom_columns.iter().map(|column| column.to_string()).collect(),
+            min_ts_ns: None,
+            max_ts_ns: None,
+            values: BTreeMap::new(),
+        }
+    }
+
+    pub fn add_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        if let Some(ts) = batch
+            .column_by_name(&self.ts_column)
+            .and_then(|c| c.as_any().downcast_ref::<TimestampNanosecondArray>())
+        {
+            self.min_ts_ns = self.min_ts_ns.into_iter().chain(ts.iter().flatten()).min();
+            self.max_ts_ns = self.max_ts_ns.into_iter().chain(ts.iter().flatten()).max();
+        }
+        for column in &self.bloom_columns {
+            let Some(array) = batch.column_by_name(column) else {
+                continue;
+            };
+            let formatter = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())?;
+            let values = self.values.entry(column.clone()).or_default();
+            values.extend((0..array.len()).filter(|row| array.is_valid(*row)).map(|row| formatter.value(row).to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> FileIndex {
+        let blooms = self
+            .values
+            .into_iter()
+            .map(|(column, values)| {
+                let mut bloom = BloomFilter::new(values.len(), BLOOM_FALSE_POSITIVE_RATE);
+                values.iter().for_each(|value| bloom.insert(value));
+                (column, bloom)
+            })
+            .collect();
+        FileIndex { size_bytes: 0, modified_ns: 0, min_ts_ns: self.min_ts_ns, max_ts_ns: self.max_ts_ns, blooms }
+    }
+}
+
+impl FileIndex {
+    /// Index a batch about to be written
+    pub fn from_batch(batch: &RecordBatch, ts_column: &str, bloom_columns: &[&str]) -> Result<Self> {
+        let mut builder = FileIndexBuilder::new(ts_column, bloom_columns);
+        builder.add_batch(batch)?;
+        Ok(builder.finish())
+    }
+
+    pub fn sidecar_path(parquet_file: &Path) -> PathBuf {
+        parquet_file.with_extension("index.json")
+    }
+
+    /// Store the index next to the file it describes, once that is written
+    pub fn write(mut self, parquet_file: &Path) -> Result<()> {
+        (self.size_bytes, self.modified_ns) = stamp(parquet_file)?;
+        std::fs::write(Self::sidecar_path(parquet_file), serde_json::to_vec(&self)?)?;
+        Ok(())
+    }
+
+    /// The file's index, if it has one that still matches it
+    pub fn load(parquet_file: &Path) -> Option<Self> {
+        let stamp = stamp(parquet_file).ok()?;
+        Self::read(parquet_file).filter(|index| index.describes(stamp))
+    }
+
+    fn read(parquet_file: &Path) -> Option<Self> {
+        let content = std::fs::read(Self::sidecar_path(parquet_file)).ok()?;
+        serde_json::from_slice(&content)
+            .map_err(|e| warn!("Ignoring unreadable index of {}: {}", parquet_file.display(), e))
+            .ok()
+    }
+
+    /// Whether the index was taken of a file with this size and modification time
+    fn describes(&self, (size_bytes, modified_ns): (u64, i64)) -> bool {
+        self.size_bytes == size_bytes && self.modified_ns == modified_ns
+    }
+
+    /// Drop the index of a file being deleted or rewritten, e.g. by erasure,
+    /// so it doesn't keep traces of removed values
+    pub fn remove(parquet_file: &Path) -> Result<()> {
+        match std::fs::remove_file(Self::sidecar_path(parquet_file)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// False if no row of the file can match; columns the index lacks match anything
+    pub fn may_match(&self, filter: &IndexFilter) -> bool {
+        if let (Some((start, end)), Some(min), Some(max)) = (filter.time_range, self.min_ts_ns, self.max_ts_ns) {
+            if max < start || min > end {
+                return false;
+            }
+        }
+        match &filter.equals {
+            Some((column, value)) => self.blooms.get(column).is_none_or(|bloom| bloom.might_contain(value)),
+            None => true,
+        }
+    }
+}
+
+/// Indexes of a directory's files kept in memory, so pruning reads each
+/// sidecar once rather than on every query. A cached index is used while its
+/// file keeps the size and modification time it was taken of.
+#[derive(Debug, Default)]
+pub struct FileIndexCache {
+    indexes: Mutex<HashMap<PathBuf, FileIndex>>,
+}
+
+impl FileIndexCache {
+    /// Files that may hold rows matching `filter`; files without a valid index are kept
+    pub fn prune(&self, files: Vec<PathBuf>, filter: &IndexFilter) -> Vec<PathBuf> {
+        let total = files.len();
+        let mut indexes = self.indexes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let kept: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|file| {
+                let Ok(stamp) = stamp(file) else {
+                    return true;
+                };
+                if !indexes.get(file).is_some_and(|index| index.describes(stamp)) {
+                    match FileIndex::read(file).filter(|index| index.describes(stamp)) {
+                        Some(index) => {
+                            indexes.insert(file.clone(), index);
+                        }
+                        None => {
+                            indexes.remove(file);
+                            return true;
+                        }
+                    }
+                }
+                indexes.get(file).is_none_or(|index| index.may_match(filter))
+            })
+            .collect();
+        debug!("Index pruning kept {} of {} files", kept.len(), total);
+        kept
+    }
+}
+
+/// Size and modification time of a file
+fn stamp(file: &Path) -> Result<(u64, i64)> {
+    let metadata = std::fs::metadata(file)?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as i64);
+    Ok((metadata.len(), modified_ns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+    use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_index_prunes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("target", DataType::Utf8, false),
+        ]));
+        let mut files = Vec::new();
+        for (i, target) in ["checkout_form", "login_form"].iter().enumerate() {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(TimestampNanosecondArray::from(vec![i as i64 * 100, i as i64 * 100 + 50])),
+                    Arc::new(StringArray::from(vec![*target, "shared"])),
+                ],
+            )
+            .unwrap();
+            let file = dir.path().join(format!("events_{}.parquet", i));
+            std::fs::write(&file, b"data").unwrap();
+            FileIndex::from_batch(&batch, "ts_ns", &["target"]).unwrap().write(&file).unwrap();
+            files.push(file);
+        }
+        let unindexed = dir.path().join("events_old.parquet");
+        std::fs::write(&unindexed, b"old").unwrap();
+        files.push(unindexed.clone());
+
+        let cache = FileIndexCache::default();
+        let kept = cache.prune(files.clone(), &IndexFilter::equals("target", "login_form"));
+        assert_eq!(kept, vec![files[1].clone(), unindexed.clone()]);
+        assert_eq!(cache.prune(files.clone(), &IndexFilter::equals("target", "shared")).len(), 3);
+        assert_eq!(cache.prune(files.clone(), &IndexFilter::time_range(0, 60)), vec![files[0].clone(), unindexed.clone()]);
+
+        // A rewritten file is scanned again
+        std::fs::write(&files[0], b"rewritten").unwrap();
+        assert!(FileIndex::load(&files[0]).is_none());
+
+        // Even when it keeps its size, the cached index is dropped
+        std::fs::write(&files[1], b"DATA").unwrap();
+        let rewritten = std::fs::File::options().write(true).open(&files[1]).unwrap();
+        rewritten.set_modified(UNIX_EPOCH + std::time::Duration::from_secs(1)).unwrap();
+        assert!(FileIndex::load(&files[1]).is_none());
+        assert_eq!(cache.prune(files.clone(), &IndexFilter::equals("target", "login_form")).len(), 3);
+    }
+}
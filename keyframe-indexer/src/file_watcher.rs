@@ -1,13 +1,270 @@
 use crate::error::{IndexerError, Result};
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::segment_ledger::SharedSegmentLedger;
+use chrono::{DateTime, Utc};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
+/// Extensions whose container is checked for a complete `moov` box
+const MP4_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v"];
+
+/// When and how new segments are picked up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileWatcherConfig {
+    /// Quiet time after a file's last event before it is looked at
+    pub debounce_ms: u64,
+    /// How long size and modification time must stay unchanged before a
+    /// segment is queued; files renamed into place only need one check
+    pub stable_secs: u64,
+    /// How often files waiting to settle are checked
+    pub check_interval_ms: u64,
+    /// Wait for MP4 and QuickTime files to have a complete `moov` box
+    pub check_container: bool,
+    /// Settled files whose container is still incomplete after this long are
+    /// queued anyway, and usually end up in the dead-letter store
+    pub max_wait_secs: u64,
+    /// Name endings of files still being written or downloaded; they are
+    /// picked up once renamed to their final name
+    pub temp_suffixes: Vec<String>,
+    /// Poll the directory instead of relying on file system events, which
+    /// network shares (SMB, NFS) don't deliver
+    pub poll: bool,
+    pub poll_interval_ms: u64,
+    /// On start, queue segments written while the service was not running
+    pub rescan_on_start: bool,
+    /// Also rescan periodically, as a safety net for missed events
+    pub rescan_interval_secs: Option<u64>,
+    /// Where the rescan watermark is kept; defaults to `<output_dir>/watcher_state.json`
+    pub state_path: Option<String>,
+}
+
+impl Default for FileWatcherConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 500,
+            stable_secs: 3,
+            check_interval_ms: 1000,
+            check_container: true,
+            max_wait_secs: 300,
+            temp_suffixes: [".tmp", ".part", ".partial", ".crdownload", ".download"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            poll: false,
+            poll_interval_ms: 2000,
+            rescan_on_start: true,
+            rescan_interval_secs: None,
+            state_path: None,
+        }
+    }
+}
+
+impl FileWatcherConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.check_interval_ms == 0 || self.poll_interval_ms == 0 {
+            return Err(IndexerError::Config(
+                "check_interval_ms and poll_interval_ms must be greater than 0".to_string(),
+            ));
+        }
+        if self.rescan_interval_secs == Some(0) {
+            return Err(IndexerError::Config("rescan_interval_secs must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Modification time up to which every segment has been queued
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScanState {
+    scanned_until: DateTime<Utc>,
+}
+
+/// A file waiting until it stops changing
+#[derive(Debug)]
+struct Candidate {
+    first_seen: Instant,
+    last_event: Instant,
+    /// Size and modification time at the last check
+    seen: Option<(u64, Option<SystemTime>)>,
+    stable_since: Instant,
+    renamed: bool,
+}
+
+/// Holds files back until they are completely written
+#[derive(Debug)]
+struct Stabilizer {
+    config: FileWatcherConfig,
+    candidates: HashMap<PathBuf, Candidate>,
+}
+
+impl Stabilizer {
+    fn new(config: FileWatcherConfig) -> Self {
+        Self { config, candidates: HashMap::new() }
+    }
+
+    /// Note an event on `path`; `renamed` marks a file moved into place
+    fn touch(&mut self, path: PathBuf, now: Instant, renamed: bool) {
+        let candidate = self.candidates.entry(path).or_insert(Candidate {
+            first_seen: now,
+            last_event: now,
+            seen: None,
+            stable_since: now,
+            renamed,
+        });
+        candidate.last_event = now;
+        candidate.renamed |= renamed;
+    }
+
+    fn remove(&mut self, path: &Path) {
+        self.candidates.remove(path);
+    }
+
+    /// Files that have settled, removed from the candidates
+    fn poll(&mut self, now: Instant) -> Vec<PathBuf> {
+        let debounce = Duration::from_millis(self.config.debounce_ms);
+        let stable_for = Duration::from_secs(self.config.stable_secs);
+        let max_wait = Duration::from_secs(self.config.max_wait_secs);
+        let mut ready = Vec::new();
+        let mut vanished = Vec::new();
+
+        for (path, candidate) in &mut self.candidates {
+            if now.duration_since(candidate.last_event) < debounce {
+                continue;
+            }
+            let Ok(metadata) = std::fs::metadata(path) else {
+                vanished.push(path.clone());
+                continue;
+            };
+            let current = (metadata.len(), metadata.modified().ok());
+            if candidate.seen != Some(current) {
+                candidate.seen = Some(current);
+                candidate.stable_since = now;
+                if !candidate.renamed {
+                    continue;
+                }
+            }
+            if current.0 == 0 || (!candidate.renamed && now.duration_since(candidate.stable_since) < stable_for) {
+                continue;
+            }
+            if self.config.check_container && !is_container_complete(path) {
+                if now.duration_since(candidate.first_seen) < max_wait {
+                    continue;
+                }
+                warn!("Container of {} is still incomplete; queueing it anyway", path.display());
+            }
+            ready.push(path.clone());
+        }
+
+        for path in vanished.iter().chain(&ready) {
+            self.candidates.remove(path);
+        }
+        ready.sort();
+        ready
+    }
+
+    fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
+
+/// Whether an MP4 or QuickTime file's top-level boxes are all present and one
+/// is `moov`; recorders write `moov` last unless they fragment the file. Other
+/// formats are not checked.
+fn is_container_complete(path: &Path) -> bool {
+    let is_mp4 = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MP4_EXTENSIONS.iter().any(|m| m.eq_ignore_ascii_case(e)));
+    !is_mp4 || has_complete_moov(path).unwrap_or(false)
+}
+
+fn has_complete_moov(path: &Path) -> std::io::Result<bool> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut offset = 0u64;
+    let mut moov = false;
+    while offset < len {
+        if len - offset < 8 {
+            return Ok(false);
+        }
+        let mut header = [0u8; 16];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut header[..8])?;
+        let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        if size == 1 {
+            // 64-bit size follows the type
+            if len - offset < 16 {
+                return Ok(false);
+            }
+            file.read_exact(&mut header[8..])?;
+            size = u64::from_be_bytes([
+                header[8], header[9], header[10], header[11], header[12], header[13], header[14], header[15],
+            ]);
+        } else if size == 0 {
+            // Box runs to the end of the file
+            size = len - offset;
+        }
+        if size < 8 {
+            return Ok(false);
+        }
+        moov |= &header[4..8] == b"moov";
+        offset = offset.saturating_add(size);
+    }
+    Ok(moov && offset == len)
+}
+
+/// What an event means for a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FileChange {
+    /// Created or written to
+    Written(PathBuf),
+    /// Moved into place under its final name
+    RenamedTo(PathBuf),
+    /// Deleted or moved away
+    Gone(PathBuf),
+}
+
+fn classify(event: Event) -> Vec<FileChange> {
+    let mut paths = event.paths.into_iter();
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any | ModifyKind::Other) => {
+            paths.map(FileChange::Written).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let from = paths.next().map(FileChange::Gone);
+            let to = paths.next().map(FileChange::RenamedTo);
+            from.into_iter().chain(to).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.map(FileChange::RenamedTo).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.map(FileChange::Gone).collect(),
+        // FSEvents reports both sides of a rename alike
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .map(|path| if path.exists() { FileChange::RenamedTo(path) } else { FileChange::Gone(path) })
+            .collect(),
+        EventKind::Remove(_) => paths.map(FileChange::Gone).collect(),
+        _ => Vec::new(),
+    }
+}
+
 pub struct FileWatcher {
     watch_dir: PathBuf,
     sender: mpsc::Sender<PathBuf>,
     video_extensions: Vec<String>,
+    ledger: Option<SharedSegmentLedger>,
+    config: FileWatcherConfig,
+    /// Rescan watermark file, when rescanning is wanted
+    state_path: Option<PathBuf>,
+    stabilizer: Stabilizer,
+    /// Segments queued by this watcher, so later events on them are ignored
+    queued: HashSet<PathBuf>,
 }
 
 impl FileWatcher {
@@ -39,88 +296,191 @@ impl FileWatcher {
             watch_dir: watch_path,
             sender,
             video_extensions,
+            ledger: None,
+            config: FileWatcherConfig::default(),
+            state_path: None,
+            stabilizer: Stabilizer::new(FileWatcherConfig::default()),
+            queued: HashSet::new(),
         })
     }
     
+    /// Record detected segments in a durable ledger before queueing them
+    pub fn with_ledger(mut self, ledger: SharedSegmentLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+    
+    pub fn with_config(mut self, config: FileWatcherConfig) -> Self {
+        self.stabilizer = Stabilizer::new(config.clone());
+        self.config = config;
+        self
+    }
+
+    /// Keep the rescan watermark in `path`
+    pub fn with_state_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_path = Some(path.into());
+        self
+    }
+
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting file watcher for directory: {}", self.watch_dir.display());
         
-        let (tx, mut rx) = std::sync::mpsc::channel();
-        let sender_clone = self.sender.clone();
-        let video_extensions = self.video_extensions.clone();
-        
-        // Create watcher
-        let mut watcher = RecommendedWatcher::new(
-            move |res: notify::Result<Event>| {
-                if let Err(e) = tx.send(res) {
-                    error!("Failed to send file event: {}", e);
-                }
-            },
-            Config::default(),
-        )?;
-        
-        // Start watching
+        let (tx, mut events) = mpsc::unbounded_channel();
+        let handler = move |res: notify::Result<Event>| {
+            if let Err(e) = tx.send(res) {
+                error!("Failed to send file event: {}", e);
+            }
+        };
+        let mut watcher: Box<dyn Watcher + Send> = if self.config.poll {
+            let interval = Duration::from_millis(self.config.poll_interval_ms);
+            Box::new(PollWatcher::new(handler, Config::default().with_poll_interval(interval))?)
+        } else {
+            Box::new(RecommendedWatcher::new(handler, Config::default())?)
+        };
         watcher.watch(&self.watch_dir, RecursiveMode::Recursive)?;
         
-        // Process events in a separate task
-        tokio::spawn(async move {
-            while let Ok(event_result) = rx.recv() {
-                match event_result {
-                    Ok(event) => {
-                        if let Err(e) = Self::handle_file_event(
-                            event,
-                            &sender_clone,
-                            &video_extensions,
-                        ).await {
-                            error!("Error handling file event: {}", e);
+        if self.config.rescan_on_start {
+            self.rescan()?;
+        }
+
+        let mut checks = tokio::time::interval(Duration::from_millis(self.config.check_interval_ms));
+        let rescan_every = Duration::from_secs(self.config.rescan_interval_secs.unwrap_or(3600));
+        let mut rescans = tokio::time::interval_at(tokio::time::Instant::now() + rescan_every, rescan_every);
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Some(Ok(event)) => self.handle_file_event(event, Instant::now()),
+                    Some(Err(e)) => error!("File watcher error: {}", e),
+                    None => return Ok(()),
+                },
+                _ = checks.tick(), if !self.stabilizer.is_empty() => {
+                    for path in self.stabilizer.poll(Instant::now()) {
+                        if let Err(e) = self.queue(path).await {
+                            error!("Error queueing video file: {}", e);
                         }
                     }
-                    Err(e) => {
-                        error!("File watcher error: {}", e);
+                }
+                _ = rescans.tick(), if self.config.rescan_interval_secs.is_some() => {
+                    if let Err(e) = self.rescan() {
+                        error!("Error rescanning {}: {}", self.watch_dir.display(), e);
                     }
                 }
             }
-        });
-        
-        // Keep the watcher alive
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         }
     }
     
-    async fn handle_file_event(
-        event: Event,
-        sender: &mpsc::Sender<PathBuf>,
-        video_extensions: &[String],
-    ) -> Result<()> {
-        match event.kind {
-            EventKind::Create(_) | EventKind::Modify(_) => {
-                for path in event.paths {
-                    if Self::is_video_file(&path, video_extensions) {
-                        debug!("Detected video file: {}", path.display());
-                        
-                        // Wait a bit to ensure file is completely written
-                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                        
-                        // Verify file is complete and readable
-                        if Self::is_file_complete(&path).await? {
-                            info!("Processing new video file: {}", path.display());
-                            if let Err(e) = sender.send(path).await {
-                                error!("Failed to send video path to processor: {}", e);
-                            }
-                        } else {
-                            warn!("Video file appears incomplete, skipping: {}", path.display());
-                        }
+    fn handle_file_event(&mut self, event: Event, now: Instant) {
+        for change in classify(event) {
+            match change {
+                FileChange::Written(path) | FileChange::RenamedTo(path) if !self.is_segment(&path) => {}
+                FileChange::Written(path) => {
+                    if !self.queued.contains(&path) {
+                        self.stabilizer.touch(path, now, false);
                     }
                 }
+                FileChange::RenamedTo(path) => {
+                    debug!("Video file moved into place: {}", path.display());
+                    self.queued.remove(&path);
+                    self.stabilizer.touch(path, now, true);
+                }
+                FileChange::Gone(path) => self.stabilizer.remove(&path),
             }
-            _ => {
-                // Ignore other event types
+        }
+    }
+
+    /// Record a settled segment in the ledger and hand it to the processor
+    async fn queue(&mut self, path: PathBuf) -> Result<()> {
+        // Persist before queueing so a full channel or restart cannot lose the segment
+        if let Some(ledger) = &self.ledger {
+            let newly_recorded = ledger
+                .lock()
+                .map_err(|_| IndexerError::Config("Segment ledger lock poisoned".to_string()))?
+                .record_pending(&path)?;
+
+            if !newly_recorded {
+                debug!("Video file already queued, skipping: {}", path.display());
+                return Ok(());
             }
         }
         
+        self.queued.insert(path.clone());
+        if let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+            self.advance_watermark(modified.into())?;
+        }
+        info!("Processing new video file: {}", path.display());
+        if let Err(e) = self.sender.send(path).await {
+            error!("Failed to send video path to processor: {}", e);
+        }
         Ok(())
     }
+
+    /// Hand segments modified after the watermark, such as those written while
+    /// the service was stopped, to the stabilizer.
+    ///
+    /// Without a watermark yet, everything already in the directory counts as
+    /// seen and only the watermark is stored.
+    fn rescan(&mut self) -> Result<()> {
+        let Some(state_path) = self.state_path.clone() else {
+            return Ok(());
+        };
+        let segments = self.segments_on_disk()?;
+        let Some(watermark) = load_watermark(&state_path) else {
+            let newest = segments.iter().map(|(_, modified)| *modified).max().unwrap_or_else(Utc::now);
+            info!("No rescan watermark yet; treating {} existing segments as seen", segments.len());
+            return save_watermark(&state_path, newest);
+        };
+
+        let now = Instant::now();
+        let mut found = 0;
+        for (path, modified) in segments {
+            let pending = match &self.ledger {
+                Some(ledger) => ledger.lock().map(|l| l.is_pending(&path)).unwrap_or(false),
+                None => false,
+            };
+            if modified > watermark && !pending && !self.queued.contains(&path) {
+                self.stabilizer.touch(path, now, false);
+                found += 1;
+            }
+        }
+        if found > 0 {
+            info!("Rescan found {} segments not yet queued", found);
+        }
+        Ok(())
+    }
+
+    /// Video segments under the watch directory with their modification times
+    fn segments_on_disk(&self) -> Result<Vec<(PathBuf, DateTime<Utc>)>> {
+        let mut segments = Vec::new();
+        let mut dirs = vec![self.watch_dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                let metadata = entry.metadata()?;
+                if metadata.is_dir() {
+                    dirs.push(path);
+                } else if self.is_segment(&path) {
+                    segments.push((path, metadata.modified()?.into()));
+                }
+            }
+        }
+        Ok(segments)
+    }
+
+    fn advance_watermark(&self, modified: DateTime<Utc>) -> Result<()> {
+        let Some(state_path) = &self.state_path else {
+            return Ok(());
+        };
+        match load_watermark(state_path) {
+            Some(watermark) if watermark >= modified => Ok(()),
+            _ => save_watermark(state_path, modified),
+        }
+    }
+
+    /// A video file under its final name
+    fn is_segment(&self, path: &Path) -> bool {
+        Self::is_video_file(path, &self.video_extensions) && !Self::is_temp_file(path, &self.config.temp_suffixes)
+    }
     
     fn is_video_file(path: &Path, video_extensions: &[String]) -> bool {
         if let Some(extension) = path.extension() {
@@ -131,28 +491,14 @@ impl FileWatcher {
         false
     }
     
-    async fn is_file_complete(path: &Path) -> Result<bool> {
-        // Check if file exists and is readable
-        if !path.exists() {
-            return Ok(false);
-        }
-        
-        // Get initial file size
-        let initial_size = match std::fs::metadata(path) {
-            Ok(metadata) => metadata.len(),
-            Err(_) => return Ok(false),
-        };
-        
-        // Wait a bit and check again
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        
-        let final_size = match std::fs::metadata(path) {
-            Ok(metadata) => metadata.len(),
-            Err(_) => return Ok(false),
+    /// Hidden files and names with a temporary suffix, before or after the
+    /// extension (`segment.mp4.part`, `segment.tmp.mp4`)
+    fn is_temp_file(path: &Path, temp_suffixes: &[String]) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return true;
         };
-        
-        // File is considered complete if size hasn't changed and is > 0
-        Ok(final_size > 0 && final_size == initial_size)
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        name.starts_with('.') || temp_suffixes.iter().any(|suffix| name.ends_with(suffix.as_str()) || stem.ends_with(suffix.as_str()))
     }
     
     pub fn add_video_extension(&mut self, extension: String) {
@@ -166,12 +512,48 @@ impl FileWatcher {
     }
 }
 
+fn load_watermark(path: &Path) -> Option<DateTime<Utc>> {
+    let content = std::fs::read(path).ok()?;
+    match serde_json::from_slice::<ScanState>(&content) {
+        Ok(state) => Some(state.scanned_until),
+        Err(e) => {
+            warn!("Ignoring unreadable watcher state {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn save_watermark(path: &Path, scanned_until: DateTime<Utc>) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, serde_json::to_vec(&ScanState { scanned_until })?)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
     use tokio::sync::mpsc;
     use std::fs;
+    use crate::segment_ledger::SegmentLedger;
+
+    /// MP4 boxes: `ftyp`, `mdat` with `payload` bytes, then `moov` if wanted
+    fn mp4_bytes(payload: usize, with_moov: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (kind, body) in [("ftyp", 8), ("mdat", payload), ("moov", 16)] {
+            if kind == "moov" && !with_moov {
+                break;
+            }
+            bytes.extend_from_slice(&((body + 8) as u32).to_be_bytes());
+            bytes.extend_from_slice(kind.as_bytes());
+            bytes.resize(bytes.len() + body, 0);
+        }
+        bytes
+    }
     
     #[tokio::test]
     async fn test_file_watcher_creation() {
@@ -200,21 +582,59 @@ mod tests {
         assert!(!FileWatcher::is_video_file(Path::new("test"), &video_extensions));
     }
     
-    #[tokio::test]
-    async fn test_file_completeness_check() {
+    #[test]
+    fn test_temp_files_and_renames() {
+        let suffixes = FileWatcherConfig::default().temp_suffixes;
+        assert!(FileWatcher::is_temp_file(Path::new("/videos/segment.tmp.mp4"), &suffixes));
+        assert!(FileWatcher::is_temp_file(Path::new("/videos/segment.mp4.part"), &suffixes));
+        assert!(FileWatcher::is_temp_file(Path::new("/videos/.segment.mp4"), &suffixes));
+        assert!(!FileWatcher::is_temp_file(Path::new("/videos/segment.mp4"), &suffixes));
+
+        let event = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(PathBuf::from("/videos/segment.tmp.mp4"))
+            .add_path(PathBuf::from("/videos/segment.mp4"));
+        assert_eq!(
+            classify(event),
+            vec![
+                FileChange::Gone(PathBuf::from("/videos/segment.tmp.mp4")),
+                FileChange::RenamedTo(PathBuf::from("/videos/segment.mp4")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_segments_wait_until_settled() {
         let temp_dir = TempDir::new().unwrap();
-        let test_file = temp_dir.path().join("test.mp4");
+        let config = FileWatcherConfig { debounce_ms: 100, stable_secs: 2, max_wait_secs: 60, ..FileWatcherConfig::default() };
+        let mut stabilizer = Stabilizer::new(config);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
         
-        // Non-existent file
-        assert!(!FileWatcher::is_file_complete(&test_file).await.unwrap());
+        // Written in place: ready once unchanged for `stable_secs`
+        let written = temp_dir.path().join("written.mkv");
+        fs::write(&written, b"video data").unwrap();
+        stabilizer.touch(written.clone(), at(0), false);
+        assert!(stabilizer.poll(at(50)).is_empty());
+        assert!(stabilizer.poll(at(200)).is_empty());
+        assert!(stabilizer.poll(at(1500)).is_empty());
+        assert_eq!(stabilizer.poll(at(2300)), vec![written]);
         
-        // Create empty file
-        fs::write(&test_file, b"").unwrap();
-        assert!(!FileWatcher::is_file_complete(&test_file).await.unwrap());
+        // An MP4 is held back until its `moov` box is written
+        let mp4 = temp_dir.path().join("segment.mp4");
+        fs::write(&mp4, &mp4_bytes(64, false)[..40]).unwrap();
+        stabilizer.touch(mp4.clone(), at(3000), true);
+        assert!(stabilizer.poll(at(3200)).is_empty());
+        fs::write(&mp4, mp4_bytes(64, true)).unwrap();
+        assert_eq!(stabilizer.poll(at(3400)), vec![mp4.clone()]);
         
-        // Create file with content
-        fs::write(&test_file, b"test content").unwrap();
-        assert!(FileWatcher::is_file_complete(&test_file).await.unwrap());
+        // Files that disappear are dropped
+        stabilizer.touch(temp_dir.path().join("gone.mkv"), at(0), false);
+        assert!(stabilizer.poll(at(5000)).is_empty());
+        assert!(stabilizer.is_empty());
+
+        assert!(has_complete_moov(&mp4).unwrap());
+        fs::write(&mp4, mp4_bytes(64, false)).unwrap();
+        assert!(!has_complete_moov(&mp4).unwrap());
     }
     
     #[test]
@@ -237,4 +657,52 @@ mod tests {
         watcher.set_video_extensions(new_extensions.clone());
         assert_eq!(watcher.video_extensions, new_extensions);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_detected_file_recorded_in_ledger() {
+        let temp_dir = TempDir::new().unwrap();
+        let video = temp_dir.path().join("segment.mp4");
+        fs::write(&video, b"video data").unwrap();
+        
+        let ledger = SegmentLedger::open_shared(temp_dir.path().join("ledger.jsonl")).unwrap();
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut watcher = FileWatcher::new(temp_dir.path().to_str().unwrap(), tx).unwrap().with_ledger(ledger.clone());
+        
+        watcher.queue(video.clone()).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), video);
+        assert!(ledger.lock().unwrap().is_pending(&video));
+        
+        // A repeated notification for a pending segment is not queued twice
+        watcher.queue(video.clone()).await.unwrap();
+        assert!(rx.try_recv().is_err());
+        let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(video);
+        watcher.handle_file_event(event, Instant::now());
+        assert!(watcher.stabilizer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rescan_finds_segments_written_while_stopped() {
+        let temp_dir = TempDir::new().unwrap();
+        let videos = temp_dir.path().join("videos");
+        fs::create_dir_all(videos.join("2024-01-02")).unwrap();
+        let old = videos.join("old.mkv");
+        let new = videos.join("2024-01-02").join("new.mkv");
+        let written_at = SystemTime::now() - Duration::from_secs(3600);
+        fs::write(&old, b"old").unwrap();
+        File::options().write(true).open(&old).unwrap().set_modified(written_at).unwrap();
+
+        let state_path = temp_dir.path().join("watcher_state.json");
+        let (tx, _rx) = mpsc::channel(10);
+        let mut watcher = FileWatcher::new(videos.to_str().unwrap(), tx).unwrap().with_state_path(&state_path);
+
+        // The first scan only records what is already there
+        watcher.rescan().unwrap();
+        assert!(watcher.stabilizer.is_empty());
+        let old_modified = fs::metadata(&old).unwrap().modified().unwrap();
+        assert_eq!(load_watermark(&state_path), Some(DateTime::<Utc>::from(old_modified)));
+
+        fs::write(&new, b"new").unwrap();
+        watcher.rescan().unwrap();
+        assert_eq!(watcher.stabilizer.candidates.keys().collect::<Vec<_>>(), vec![&new]);
+    }
+}
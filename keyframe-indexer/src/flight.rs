@@ -0,0 +1,570 @@
+use crate::access_audit::AccessAuditLog;
+use crate::auth::{AuthConfig, Scope};
+use crate::error::{IndexerError, Result};
+use crate::event_detector::EventType;
+use crate::output_query::OutputQuery;
+use crate::query_service::QueryService;
+use crate::shutdown::ShutdownSignal;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// Arrow Flight endpoint serving the frame, OCR and event tables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlightConfig {
+    /// Address the endpoint listens on; non-loopback addresses need API keys and TLS
+    pub bind_address: String,
+}
+
+impl Default for FlightConfig {
+    fn default() -> Self {
+        Self { bind_address: "127.0.0.1:50051".to_string() }
+    }
+}
+
+impl FlightConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.socket_addr().map(|_| ())
+    }
+
+    pub fn socket_addr(&self) -> Result<SocketAddr> {
+        self.bind_address
+            .parse()
+            .map_err(|e| IndexerError::Config(format!("Invalid flight bind_address '{}': {}", self.bind_address, e)))
+    }
+}
+
+/// Scope needed to read a table; tables not listed need none
+fn table_scope(table: &str) -> Option<Scope> {
+    match table {
+        "frames" => Some(Scope::ReadFrames),
+        "ocr_data" => Some(Scope::ReadOcr),
+        // Plugin tables hold detection results like the events
+        _ => Some(Scope::ReadEvents),
+    }
+}
+
+/// Scopes of every table `sql` mentions. Names are matched as prefixes so
+/// the raw tables behind a view (`events_raw`) need the view's scope too.
+#[cfg_attr(not(feature = "flight"), allow(dead_code))]
+fn required_scopes(sql: &str, tables: &[String]) -> Vec<Scope> {
+    let mut scopes = Vec::new();
+    for table in tables {
+        let mentioned = Regex::new(&format!(r"(?i)\b{}", regex::escape(table))).map_or(false, |r| r.is_match(sql));
+        if let Some(scope) = table_scope(table).filter(|s| mentioned && !scopes.contains(s)) {
+            scopes.push(scope);
+        }
+    }
+    scopes
+}
+
+/// Query service calls served as Flight actions, with their descriptions
+pub const QUERY_ACTIONS: &[(&str, &str)] = &[
+    ("ocr_by_text", "OCR results containing `text`"),
+    ("ocr_by_frame", "OCR results of `frame_id`"),
+    ("events_by_type", "Events of `event_type`, e.g. \"FieldChange\""),
+    ("events_by_time_range", "Events between `start` and `end` (RFC 3339)"),
+    ("events_by_confidence", "Events of at least `min_confidence`"),
+    ("frames_by_app", "Frame metadata of `app_name`"),
+];
+
+/// Parameters of a query action, sent as its JSON body
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ActionParams {
+    text: String,
+    frame_id: String,
+    event_type: Option<EventType>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    min_confidence: f32,
+    app_name: String,
+}
+
+/// Run a query action for `api_key` and return its rows as a JSON array.
+/// The query service checks the key's scopes and redacts the rows to its
+/// profile, so unlike the SQL tables actions serve every profile.
+pub async fn run_query_action(queries: &QueryService, api_key: &str, action: &str, body: &[u8]) -> Result<Vec<u8>> {
+    let params: ActionParams = if body.is_empty() {
+        ActionParams::default()
+    } else {
+        serde_json::from_slice(body)
+            .map_err(|e| IndexerError::Config(format!("Invalid parameters for action '{}': {}", action, e)))?
+    };
+    let missing = |name: &str| IndexerError::Config(format!("Action '{}' needs `{}`", action, name));
+
+    let rows = match action {
+        "ocr_by_text" => serde_json::to_vec(&queries.query_ocr_by_text(api_key, &params.text).await?)?,
+        "ocr_by_frame" => serde_json::to_vec(&queries.query_ocr_by_frame_id(api_key, &params.frame_id).await?)?,
+        "events_by_type" => {
+            let event_type = params.event_type.ok_or_else(|| missing("event_type"))?;
+            serde_json::to_vec(&queries.query_events_by_type(api_key, &event_type).await?)?
+        }
+        "events_by_time_range" => {
+            let start = params.start.ok_or_else(|| missing("start"))?;
+            let end = params.end.ok_or_else(|| missing("end"))?;
+            serde_json::to_vec(&queries.query_events_by_time_range(api_key, start, end).await?)?
+        }
+        "events_by_confidence" => {
+            serde_json::to_vec(&queries.query_events_by_confidence(api_key, params.min_confidence).await?)?
+        }
+        "frames_by_app" => serde_json::to_vec(&queries.query_frames_by_app(api_key, &params.app_name).await?)?,
+        _ => return Err(IndexerError::Config(format!("Unknown action '{}'", action))),
+    };
+    Ok(rows)
+}
+
+/// Serve `query`'s tables over Arrow Flight until `shutdown` is requested.
+///
+/// Clients send their API key as an `authorization: Bearer <key>` header.
+/// `list_flights` lists one flight per table, tickets are SQL queries and a
+/// command descriptor runs its SQL. Batches stream straight from DataFusion
+/// without redaction, so only keys with the `admin` redaction profile are
+/// served. With `queries` attached, the [`QUERY_ACTIONS`] are served through
+/// `do_action` to keys of any profile.
+#[cfg(feature = "flight")]
+pub async fn serve(
+    config: &FlightConfig,
+    auth: &AuthConfig,
+    query: OutputQuery,
+    queries: Option<QueryService>,
+    access_log: Option<AccessAuditLog>,
+    shutdown: ShutdownSignal,
+) -> Result<()> {
+    server::serve(config, auth, query, queries, access_log, shutdown).await
+}
+
+#[cfg(not(feature = "flight"))]
+pub async fn serve(
+    _config: &FlightConfig,
+    _auth: &AuthConfig,
+    _query: OutputQuery,
+    _queries: Option<QueryService>,
+    _access_log: Option<AccessAuditLog>,
+    _shutdown: ShutdownSignal,
+) -> Result<()> {
+    Err(IndexerError::Config("Arrow Flight needs a build with the `flight` feature".to_string()))
+}
+
+#[cfg(feature = "flight")]
+mod server {
+    use super::{required_scopes, run_query_action, FlightConfig, QUERY_ACTIONS};
+    use crate::access_audit::{AccessAuditLog, AccessOperation};
+    use crate::auth::{AuthConfig, Authenticator, Scope};
+    use crate::error::{IndexerError, Result};
+    use crate::output_query::OutputQuery;
+    use crate::query_service::QueryService;
+    use crate::redaction::RedactionProfile;
+    use crate::shutdown::ShutdownSignal;
+    use arrow::ipc::writer::IpcWriteOptions;
+    use arrow_flight::encode::FlightDataEncoderBuilder;
+    use arrow_flight::error::FlightError;
+    use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+    use arrow_flight::{
+        Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+        HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+    };
+    use futures::stream::{self, BoxStream};
+    use futures::{StreamExt, TryStreamExt};
+    use std::sync::Arc;
+    use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+    use tonic::{Request, Response, Status, Streaming};
+    use tracing::{info, warn};
+
+    struct FlightDataService {
+        query: Arc<OutputQuery>,
+        queries: Option<Arc<QueryService>>,
+        authenticator: Arc<Authenticator>,
+        access_log: Option<AccessAuditLog>,
+    }
+
+    /// API key of the `authorization: Bearer <key>` header
+    fn bearer_key<T>(request: &Request<T>) -> std::result::Result<&str, Status> {
+        request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing `authorization: Bearer <key>` header"))
+    }
+
+    /// gRPC status of an error; the message leads with its error code
+    fn status(error: IndexerError) -> Status {
+        let message = format!("{}: {}", error.code(), error);
+        match error.root() {
+            IndexerError::AccessDenied(_) => Status::permission_denied(message),
+            IndexerError::RateLimited(_) => Status::resource_exhausted(message),
+            IndexerError::Config(_) => Status::invalid_argument(message),
+            _ => Status::internal(message),
+        }
+    }
+
+    impl FlightDataService {
+        /// Check the caller's key against the tables `sql` reads
+        fn authorize<T>(&self, request: &Request<T>, sql: &str) -> std::result::Result<String, Status> {
+            let key = bearer_key(request)?;
+
+            let scopes = required_scopes(sql, self.query.tables());
+            let context = self
+                .authenticator
+                .authenticate(key, scopes.first().copied().unwrap_or(Scope::ReadEvents))
+                .map_err(status)?;
+            if let Some(missing) = scopes.iter().find(|scope| !context.has_scope(**scope)) {
+                return Err(Status::permission_denied(format!("API key '{}' lacks scope {:?}", context.key_id, missing)));
+            }
+            if context.profile != RedactionProfile::Admin {
+                return Err(Status::permission_denied(format!(
+                    "API key '{}' needs the admin redaction profile; Flight serves unredacted data",
+                    context.key_id
+                )));
+            }
+            Ok(context.key_id)
+        }
+
+        fn audit(&self, key_id: &str, sql: &str, error: Option<String>) {
+            if let Some(log) = &self.access_log {
+                if let Err(e) = log.record(&format!("api-key:{}", key_id), AccessOperation::Query, sql, error) {
+                    warn!("Failed to record Flight query in the access log: {}", e);
+                }
+            }
+        }
+
+        async fn flight_info(&self, sql: &str, descriptor: FlightDescriptor) -> std::result::Result<FlightInfo, Status> {
+            let schema = self.query.sql_schema(sql).await.map_err(status)?;
+            let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(sql.to_string()));
+            FlightInfo::new()
+                .try_with_schema(&schema)
+                .map_err(|e| Status::internal(e.to_string()))
+                .map(|info| info.with_endpoint(endpoint).with_descriptor(descriptor))
+        }
+    }
+
+    /// SQL a descriptor stands for: its command, or all rows of the table in its path
+    fn descriptor_sql(descriptor: &FlightDescriptor) -> std::result::Result<String, Status> {
+        if !descriptor.cmd.is_empty() {
+            return String::from_utf8(descriptor.cmd.to_vec()).map_err(|_| Status::invalid_argument("Command is not UTF-8 SQL"));
+        }
+        match descriptor.path.as_slice() {
+            [table] => Ok(format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""))),
+            _ => Err(Status::invalid_argument("Descriptor needs SQL as its command or a table name as its path")),
+        }
+    }
+
+    #[tonic::async_trait]
+    impl FlightService for FlightDataService {
+        type HandshakeStream = BoxStream<'static, std::result::Result<HandshakeResponse, Status>>;
+        type ListFlightsStream = BoxStream<'static, std::result::Result<FlightInfo, Status>>;
+        type DoGetStream = BoxStream<'static, std::result::Result<FlightData, Status>>;
+        type DoPutStream = BoxStream<'static, std::result::Result<PutResult, Status>>;
+        type DoActionStream = BoxStream<'static, std::result::Result<arrow_flight::Result, Status>>;
+        type ListActionsStream = BoxStream<'static, std::result::Result<ActionType, Status>>;
+        type DoExchangeStream = BoxStream<'static, std::result::Result<FlightData, Status>>;
+
+        async fn handshake(
+            &self,
+            _request: Request<Streaming<HandshakeRequest>>,
+        ) -> std::result::Result<Response<Self::HandshakeStream>, Status> {
+            Err(Status::unimplemented("Send the API key as an authorization header instead"))
+        }
+
+        async fn list_flights(
+            &self,
+            request: Request<Criteria>,
+        ) -> std::result::Result<Response<Self::ListFlightsStream>, Status> {
+            let mut flights = Vec::new();
+            for table in self.query.tables() {
+                let descriptor = FlightDescriptor::new_path(vec![table.to_string()]);
+                let sql = descriptor_sql(&descriptor)?;
+                // Tables the key may not read are left out rather than failing the listing
+                if self.authorize(&request, &sql).is_ok() {
+                    flights.push(Ok(self.flight_info(&sql, descriptor).await?));
+                }
+            }
+            Ok(Response::new(stream::iter(flights).boxed()))
+        }
+
+        async fn get_flight_info(
+            &self,
+            request: Request<FlightDescriptor>,
+        ) -> std::result::Result<Response<FlightInfo>, Status> {
+            let sql = descriptor_sql(request.get_ref())?;
+            self.authorize(&request, &sql)?;
+            Ok(Response::new(self.flight_info(&sql, request.get_ref().clone()).await?))
+        }
+
+        async fn poll_flight_info(
+            &self,
+            _request: Request<FlightDescriptor>,
+        ) -> std::result::Result<Response<PollInfo>, Status> {
+            Err(Status::unimplemented("Queries are not long-running; use get_flight_info"))
+        }
+
+        async fn get_schema(
+            &self,
+            request: Request<FlightDescriptor>,
+        ) -> std::result::Result<Response<SchemaResult>, Status> {
+            let sql = descriptor_sql(request.get_ref())?;
+            self.authorize(&request, &sql)?;
+            let schema = self.query.sql_schema(&sql).await.map_err(status)?;
+            let result = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
+                .try_into()
+                .map_err(|e: arrow::error::ArrowError| Status::internal(e.to_string()))?;
+            Ok(Response::new(result))
+        }
+
+        async fn do_get(&self, request: Request<Ticket>) -> std::result::Result<Response<Self::DoGetStream>, Status> {
+            let sql = std::str::from_utf8(&request.get_ref().ticket)
+                .map_err(|_| Status::invalid_argument("Ticket is not UTF-8 SQL"))?
+                .to_string();
+            let key_id = self.authorize(&request, &sql)?;
+
+            let result = self.query.sql_stream(&sql).await;
+            self.audit(&key_id, &sql, result.as_ref().err().map(|e| e.to_string()));
+            let (schema, batches) = result.map_err(status)?;
+
+            let batches = batches.map_err(|e| FlightError::ExternalError(Box::new(e)));
+            let data = FlightDataEncoderBuilder::new()
+                .with_schema(schema)
+                .build(batches)
+                .map_err(Status::from);
+            Ok(Response::new(data.boxed()))
+        }
+
+        async fn do_put(
+            &self,
+            _request: Request<Streaming<FlightData>>,
+        ) -> std::result::Result<Response<Self::DoPutStream>, Status> {
+            Err(Status::unimplemented("The Flight endpoint is read-only"))
+        }
+
+        async fn do_action(&self, request: Request<Action>) -> std::result::Result<Response<Self::DoActionStream>, Status> {
+            let queries = self.queries.as_ref().ok_or_else(|| Status::unimplemented("No query service is attached"))?;
+            let key = bearer_key(&request)?;
+            // The query service records the action in the access log itself
+            let action = request.get_ref();
+            let body = run_query_action(queries, key, &action.r#type, &action.body).await.map_err(status)?;
+            let result = arrow_flight::Result { body: body.into() };
+            Ok(Response::new(stream::once(async move { Ok(result) }).boxed()))
+        }
+
+        async fn list_actions(
+            &self,
+            _request: Request<Empty>,
+        ) -> std::result::Result<Response<Self::ListActionsStream>, Status> {
+            if self.queries.is_none() {
+                return Ok(Response::new(stream::empty().boxed()));
+            }
+            let actions = QUERY_ACTIONS.iter().map(|(name, description)| {
+                Ok(ActionType { r#type: name.to_string(), description: description.to_string() })
+            });
+            Ok(Response::new(stream::iter(actions.collect::<Vec<_>>()).boxed()))
+        }
+
+        async fn do_exchange(
+            &self,
+            _request: Request<Streaming<FlightData>>,
+        ) -> std::result::Result<Response<Self::DoExchangeStream>, Status> {
+            Err(Status::unimplemented("The Flight endpoint is read-only"))
+        }
+    }
+
+    pub(super) async fn serve(
+        config: &FlightConfig,
+        auth: &AuthConfig,
+        query: OutputQuery,
+        queries: Option<QueryService>,
+        access_log: Option<AccessAuditLog>,
+        mut shutdown: ShutdownSignal,
+    ) -> Result<()> {
+        let addr = config.socket_addr()?;
+        auth.check_bind_address(&addr)?;
+
+        let mut server = Server::builder();
+        if let Some(tls) = &auth.tls {
+            let identity = Identity::from_pem(std::fs::read(&tls.cert_path)?, std::fs::read(&tls.key_path)?);
+            let mut tls_config = ServerTlsConfig::new().identity(identity);
+            if let Some(ca_path) = &tls.client_ca_path {
+                tls_config = tls_config.client_ca_root(Certificate::from_pem(std::fs::read(ca_path)?));
+            }
+            server = server
+                .tls_config(tls_config)
+                .map_err(|e| IndexerError::Config(format!("Invalid TLS certificate or key: {}", e)))?;
+        }
+
+        info!("Serving {} over Arrow Flight on {}", query.tables().join(", "), addr);
+        let service = FlightDataService {
+            query: Arc::new(query),
+            queries: queries.map(Arc::new),
+            authenticator: Arc::new(Authenticator::new(auth.clone())?),
+            access_log,
+        };
+        server
+            .add_service(FlightServiceServer::new(service))
+            .serve_with_shutdown(addr, async move { shutdown.requested().await })
+            .await
+            .map_err(|e| IndexerError::Config(format!("Flight server on {} failed: {}", addr, e)))?;
+        info!("Arrow Flight endpoint stopped");
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::auth::{hash_key, ApiKeyConfig};
+        use crate::csv_writer::CsvWriter;
+        use crate::metadata_collector::FrameMetadata;
+        use crate::output_query::format_table;
+        use arrow::record_batch::RecordBatch;
+        use arrow_flight::decode::FlightRecordBatchStream;
+        use tempfile::TempDir;
+
+        fn frame(ts_ns: i64, app_name: &str) -> FrameMetadata {
+            FrameMetadata {
+                ts_ns,
+                monitor_id: 0,
+                segment_id: "segment".to_string(),
+                path: format!("/frames/frame_{}.png", ts_ns),
+                phash16: 0,
+                entropy: 1.0,
+                app_name: app_name.to_string(),
+                win_title: "Window".to_string(),
+                width: 1920,
+                height: 1080,
+                duplicate_of: None,
+                ocr_density: None,
+                visual: None,
+                screen_type: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_serves_frames_the_indexer_writes() {
+            let temp_dir = TempDir::new().unwrap();
+            let output_dir = temp_dir.path().join("output");
+            // The indexer writes frame metadata as CSV
+            let mut csv_writer = CsvWriter::new(output_dir.to_str().unwrap()).unwrap();
+            csv_writer.write_frame_metadata(&[frame(0, "Mail"), frame(1, "Terminal, zsh")]).await.unwrap();
+            csv_writer.finalize().await.unwrap();
+
+            let mut query = OutputQuery::open(None, None).await.unwrap();
+            query.register_frames(&output_dir).await.unwrap();
+            assert_eq!(query.tables(), &["frames"]);
+
+            let auth = AuthConfig {
+                api_keys: vec![ApiKeyConfig {
+                    id: "admin".to_string(),
+                    key_sha256: hash_key("admin-secret"),
+                    scopes: vec![Scope::ReadFrames],
+                    profile: RedactionProfile::Admin,
+                    rate_limit_per_minute: 0,
+                    expires_at: None,
+                    revoked: false,
+                }],
+                ..Default::default()
+            };
+            let service = FlightDataService {
+                query: Arc::new(query),
+                queries: None,
+                authenticator: Arc::new(Authenticator::new(auth).unwrap()),
+                access_log: None,
+            };
+
+            let mut request = Request::new(Ticket::new("SELECT app_name, width FROM frames ORDER BY ts_ns"));
+            request.metadata_mut().insert("authorization", "Bearer admin-secret".parse().unwrap());
+            let data = service.do_get(request).await.unwrap().into_inner();
+            let batches: Vec<RecordBatch> = FlightRecordBatchStream::new_from_flight_data(data.map_err(FlightError::from))
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(batches.iter().map(|batch| batch.num_rows()).sum::<usize>(), 2);
+            let table = format_table(&batches).unwrap();
+            assert!(table.contains("Mail"));
+            assert!(table.contains("Terminal, zsh"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{hash_key, ApiKeyConfig};
+    use crate::config::IndexerConfig;
+    use crate::metadata_collector::FrameMetadata;
+    use crate::parquet_writer::ParquetWriter;
+    use crate::redaction::RedactionProfile;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scopes_follow_tables_read() {
+        let tables = ["ocr_data", "events", "frames", "app_latency"].map(String::from);
+        assert_eq!(required_scopes("SELECT * FROM events", &tables), vec![Scope::ReadEvents]);
+        assert_eq!(required_scopes("select * from EVENTS_RAW", &tables), vec![Scope::ReadEvents]);
+        assert_eq!(
+            required_scopes("SELECT f.app_name, o.text FROM frames f JOIN ocr_data o ON o.frame_id = f.path", &tables),
+            vec![Scope::ReadOcr, Scope::ReadFrames]
+        );
+        assert_eq!(required_scopes("SELECT app, latency_ms FROM app_latency", &tables), vec![Scope::ReadEvents]);
+        assert!(required_scopes("SELECT 1", &tables).is_empty());
+        assert!(FlightConfig { bind_address: "localhost".to_string() }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_actions_are_scoped_and_redacted() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let mut frame_writer = ParquetWriter::new(output_dir.to_str().unwrap()).unwrap();
+        frame_writer.write_frame_metadata(&[FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "/frames/frame_0.png".to_string(),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: "Mail".to_string(),
+            win_title: "Inbox - jane@example.com".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }]).await.unwrap();
+        frame_writer.finalize().await.unwrap();
+
+        let config = IndexerConfig {
+            output_dir: output_dir.to_string_lossy().into_owned(),
+            auth: AuthConfig {
+                api_keys: vec![ApiKeyConfig {
+                    id: "reviewer".to_string(),
+                    key_sha256: hash_key("frames-secret"),
+                    scopes: vec![Scope::ReadEvents, Scope::ReadFrames],
+                    profile: RedactionProfile::Analyst,
+                    rate_limit_per_minute: 0,
+                    expires_at: None,
+                    revoked: false,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ocr_dir = temp_dir.path().join("ocr");
+        let events_dir = temp_dir.path().join("events");
+        let queries = QueryService::from_config(&config, ocr_dir.to_str().unwrap(), events_dir.to_str().unwrap()).unwrap();
+
+        // Analyst keys get redacted rows, not the unredacted SQL tables
+        let body = run_query_action(&queries, "frames-secret", "frames_by_app", br#"{"app_name": "mail"}"#).await.unwrap();
+        let frames: Vec<FrameMetadata> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].win_title, "Inbox - [EMAIL]");
+
+        let body = run_query_action(&queries, "frames-secret", "events_by_confidence", b"").await.unwrap();
+        assert_eq!(body, b"[]");
+
+        let denied = run_query_action(&queries, "frames-secret", "ocr_by_text", br#"{"text": "invoice"}"#).await;
+        assert!(matches!(denied.unwrap_err().root(), IndexerError::AccessDenied(_)));
+        assert!(run_query_action(&queries, "frames-secret", "events_by_time_range", b"{}").await.is_err());
+        assert!(run_query_action(&queries, "frames-secret", "drop_tables", b"").await.is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::modal_lifetime;
+use crate::suppression::SuppressionList;
+use crate::system_probe::SystemProbe;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Daily focus summary settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSummaryConfig {
+    pub enabled: bool,
+    /// Local time of day the summary for the current day is generated, `HH:MM`
+    pub time_of_day: String,
+    /// Directory for markdown notes; defaults to `<output_dir>/summaries`
+    pub output_dir: Option<String>,
+    /// Show a local notification when a summary is written (macOS)
+    pub notify: bool,
+    /// Minimum uninterrupted time in one app to count as a deep-work block
+    pub deep_work_min_minutes: i64,
+    /// Gaps between app switches longer than this are treated as idle time
+    pub idle_gap_minutes: i64,
+    /// Number of apps listed under top apps
+    pub top_app_count: usize,
+}
+
+impl Default for FocusSummaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time_of_day: "18:00".to_string(),
+            output_dir: None,
+            notify: true,
+            deep_work_min_minutes: 25,
+            idle_gap_minutes: 10,
+            top_app_count: 5,
+        }
+    }
+}
+
+impl FocusSummaryConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.parse_time_of_day()?;
+        if self.deep_work_min_minutes <= 0 || self.idle_gap_minutes <= 0 {
+            return Err(IndexerError::Config(
+                "focus_summary deep_work_min_minutes and idle_gap_minutes must be positive".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_time_of_day(&self) -> Result<NaiveTime> {
+        NaiveTime::parse_from_str(&self.time_of_day, "%H:%M").map_err(|e| {
+            IndexerError::Config(format!("Invalid focus_summary time_of_day '{}': {}", self.time_of_day, e))
+        })
+    }
+}
+
+/// Time spent in one application
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppUsage {
+    pub app_name: String,
+    pub minutes: i64,
+}
+
+/// Uninterrupted stretch in a single application
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeepWorkBlock {
+    pub app_name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DeepWorkBlock {
+    pub fn minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes()
+    }
+}
+
+/// One day of activity rolled up from the event store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailySummary {
+    pub date: NaiveDate,
+    pub active_minutes: i64,
+    pub top_apps: Vec<AppUsage>,
+    pub deep_work_blocks: Vec<DeepWorkBlock>,
+    pub error_count: usize,
+    /// App switched to most often, with the number of switches
+    pub most_switched_to: Option<(String, usize)>,
+}
+
+impl DailySummary {
+    /// Render the summary as a markdown note
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Focus summary {}\n", self.date);
+        let _ = writeln!(md, "- Active time: {}", format_minutes(self.active_minutes));
+        let _ = writeln!(md, "- Errors seen: {}", self.error_count);
+        if let Some((app, switches)) = &self.most_switched_to {
+            let _ = writeln!(md, "- Most switched to: {} ({} times)", app, switches);
+        }
+
+        let _ = writeln!(md, "\n## Top apps\n");
+        for usage in &self.top_apps {
+            let _ = writeln!(md, "- {}: {}", usage.app_name, format_minutes(usage.minutes));
+        }
+
+        let _ = writeln!(md, "\n## Deep-work blocks\n");
+        if self.deep_work_blocks.is_empty() {
+            let _ = writeln!(md, "_None today_");
+        }
+        for block in &self.deep_work_blocks {
+            let _ = writeln!(
+                md,
+                "- {}–{} {} ({})",
+                block.start.with_timezone(&Local).format("%H:%M"),
+                block.end.with_timezone(&Local).format("%H:%M"),
+                block.app_name,
+                format_minutes(block.minutes())
+            );
+        }
+
+        md
+    }
+
+    /// One-line text for a notification
+    pub fn headline(&self) -> String {
+        let top_app = self.top_apps.first().map(|u| u.app_name.as_str()).unwrap_or("no app");
+        format!(
+            "{} active, mostly in {}; {} deep-work blocks, {} errors",
+            format_minutes(self.active_minutes),
+            top_app,
+            self.deep_work_blocks.len(),
+            self.error_count
+        )
+    }
+}
+
+/// Builds daily summaries from navigation and error events
+pub struct FocusSummaryGenerator {
+    config: FocusSummaryConfig,
+    output_dir: PathBuf,
+    suppressions: Option<SuppressionList>,
+    probe: SystemProbe,
+}
+
+impl FocusSummaryGenerator {
+    pub fn new(config: FocusSummaryConfig, default_output_dir: &str) -> Result<Self> {
+        config.validate()?;
+        let output_dir = config
+            .output_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(default_output_dir).join("summaries"));
+        std::fs::create_dir_all(&output_dir)?;
+
+        Ok(Self { config, output_dir, suppressions: None, probe: SystemProbe::default() })
+    }
+
+    /// Show notifications through a shared probe
+    pub fn with_system_probe(mut self, probe: SystemProbe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Leave events muted by suppression rules out of summaries
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Summarize the events of one local day
+    pub fn summarize(&self, date: NaiveDate, events: &[DetectedEvent]) -> DailySummary {
+        let unsuppressed;
+        let events = match &self.suppressions {
+            Some(suppressions) => {
+                unsuppressed = suppressions.filter_events(events);
+                &unsuppressed[..]
+            }
+            None => events,
+        };
+        let mut events: Vec<&DetectedEvent> = events.iter().collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let error_count = events.iter().filter(|e| modal_lifetime::is_error(e)).count();
+
+        // App timeline: each switch starts a span that lasts until the next switch
+        let switches: Vec<(DateTime<Utc>, String)> = events
+            .iter()
+            .filter(|e| e.event_type == EventType::Navigation)
+            .filter_map(|e| switched_to_app(e).map(|app| (e.timestamp, app)))
+            .collect();
+
+        let idle_gap = Duration::minutes(self.config.idle_gap_minutes);
+        let mut seconds_by_app: HashMap<String, i64> = HashMap::new();
+        let mut switch_counts: HashMap<String, usize> = HashMap::new();
+        let mut blocks = Vec::new();
+        let mut current_block: Option<DeepWorkBlock> = None;
+
+        for (i, (start, app)) in switches.iter().enumerate() {
+            // The last span of the day, and spans ending in idle time, are capped at the idle gap
+            let next = switches.get(i + 1).map(|(t, _)| *t);
+            let end = next.filter(|t| *t - *start <= idle_gap).unwrap_or(*start + idle_gap);
+            *seconds_by_app.entry(app.clone()).or_insert(0) += (end - *start).num_seconds();
+
+            let previous_app = i.checked_sub(1).map(|p| &switches[p].1);
+            if previous_app != Some(app) {
+                *switch_counts.entry(app.clone()).or_insert(0) += 1;
+            }
+
+            current_block = match current_block.take() {
+                Some(mut block) if block.app_name == *app && block.end == *start => {
+                    block.end = end;
+                    Some(block)
+                }
+                finished => {
+                    blocks.extend(finished);
+                    Some(DeepWorkBlock { app_name: app.clone(), start: *start, end })
+                }
+            };
+        }
+        blocks.extend(current_block);
+
+        let min_block = Duration::minutes(self.config.deep_work_min_minutes);
+        let deep_work_blocks: Vec<DeepWorkBlock> = blocks.into_iter().filter(|b| b.end - b.start >= min_block).collect();
+
+        let active_minutes = seconds_by_app.values().sum::<i64>() / 60;
+        let mut top_apps: Vec<AppUsage> = seconds_by_app
+            .into_iter()
+            .map(|(app_name, seconds)| AppUsage { app_name, minutes: seconds / 60 })
+            .collect();
+        top_apps.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.app_name.cmp(&b.app_name)));
+        top_apps.truncate(self.config.top_app_count);
+
+        let most_switched_to = switch_counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+
+        DailySummary {
+            date,
+            active_minutes,
+            top_apps,
+            deep_work_blocks,
+            error_count,
+            most_switched_to,
+        }
+    }
+
+    /// Load a day's events, write the markdown note and optionally notify
+    pub async fn generate(&self, date: NaiveDate, event_store: &EventParquetWriter) -> Result<DailySummary> {
+        let (start, end) = local_day_bounds(date)?;
+        let events = event_store.query_by_time_range(start, end).await?;
+        self.write_summary(date, &events).await
+    }
+
+    /// Summarize a day's events, write the markdown note and optionally notify
+    pub async fn write_summary(&self, date: NaiveDate, events: &[DetectedEvent]) -> Result<DailySummary> {
+        let summary = self.summarize(date, events);
+
+        let note_path = self.output_dir.join(format!("{}.md", date));
+        std::fs::write(&note_path, summary.to_markdown())?;
+        info!("Wrote focus summary to {}", note_path.display());
+
+        if self.config.notify {
+            if let Err(e) = send_notification(&self.probe, "Daily focus summary", &summary.headline()).await {
+                warn!("Failed to show focus summary notification: {}", e);
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Next time a summary is due after `now`
+    pub fn next_run_after(&self, now: DateTime<Local>) -> Result<DateTime<Local>> {
+        let time = self.config.parse_time_of_day()?;
+        let today = now.date_naive().and_time(time);
+        let due = if today > now.naive_local() { today } else { today + Duration::days(1) };
+        Local
+            .from_local_datetime(&due)
+            .earliest()
+            .ok_or_else(|| IndexerError::Config(format!("No local time {} on {}", time, due.date())))
+    }
+
+    /// Generate a summary every day at the configured time
+    pub async fn run_scheduled(&self, event_store: &EventParquetWriter) -> Result<()> {
+        loop {
+            let due = self.next_run_after(Local::now())?;
+            let wait = (due - Local::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            if let Err(e) = self.generate(due.date_naive(), event_store).await {
+                warn!("Failed to generate focus summary for {}: {}", due.date_naive(), e);
+            }
+        }
+    }
+}
+
+/// Collects the events a running service publishes and writes the summary
+/// of the day once it is due
+pub struct FocusSummarySchedule {
+    generator: FocusSummaryGenerator,
+    /// Navigation and error events not yet summarized
+    events: Vec<DetectedEvent>,
+    due: DateTime<Local>,
+}
+
+impl FocusSummarySchedule {
+    pub fn new(generator: FocusSummaryGenerator, now: DateTime<Local>) -> Result<Self> {
+        let due = generator.next_run_after(now)?;
+        Ok(Self { generator, events: Vec::new(), due })
+    }
+
+    /// Switch to new settings, keeping the events collected so far
+    pub fn replace_generator(&mut self, generator: FocusSummaryGenerator, now: DateTime<Local>) -> Result<()> {
+        self.due = generator.next_run_after(now)?;
+        self.generator = generator;
+        Ok(())
+    }
+
+    /// When the next summary is written
+    pub fn due(&self) -> DateTime<Local> {
+        self.due
+    }
+
+    /// Keep the events a summary is built from
+    pub fn record(&mut self, events: &[DetectedEvent]) {
+        self.events.extend(
+            events
+                .iter()
+                .filter(|e| matches!(e.event_type, EventType::Navigation | EventType::ErrorDisplay))
+                .cloned(),
+        );
+    }
+
+    /// Write the summary of the due day once `now` has reached its time.
+    ///
+    /// Events of that day and earlier are dropped afterwards, whether or not
+    /// writing succeeded, so a failing note does not grow the buffer forever.
+    pub async fn run_if_due(&mut self, now: DateTime<Local>) -> Result<Option<DailySummary>> {
+        if now < self.due {
+            return Ok(None);
+        }
+        let date = self.due.date_naive();
+        let (start, end) = local_day_bounds(date)?;
+        let day: Vec<DetectedEvent> = self
+            .events
+            .iter()
+            .filter(|e| e.timestamp >= start && e.timestamp < end)
+            .cloned()
+            .collect();
+        self.events.retain(|e| e.timestamp >= end);
+        self.due = self.generator.next_run_after(now)?;
+        self.generator.write_summary(date, &day).await.map(Some)
+    }
+}
+
+/// App a navigation event switched to, from window or focus change metadata
+pub(crate) fn switched_to_app(event: &DetectedEvent) -> Option<String> {
+    ["current_app", "to_app", "app_name"]
+        .iter()
+        .find_map(|key| event.metadata.get(*key))
+        .filter(|app| !app.is_empty())
+        .cloned()
+}
+
+pub(crate) fn local_day_bounds(date: NaiveDate) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let local = |d: NaiveDate| {
+        Local
+            .from_local_datetime(&d.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| IndexerError::Config(format!("No local midnight on {}", d)))
+    };
+    Ok((local(date)?, local(date + Duration::days(1))?))
+}
+
+pub(crate) fn format_minutes(minutes: i64) -> String {
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+async fn send_notification(probe: &SystemProbe, title: &str, message: &str) -> Result<()> {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        escape(message),
+        escape(title)
+    );
+
+    probe.osascript("Notification Center", &script).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_switch(minute: i64, app: &str) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("current_app".to_string(), app.to_string());
+        DetectedEvent {
+            id: format!("switch_{}", minute),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap() + Duration::minutes(minute),
+            event_type: EventType::Navigation,
+            target: format!("window_{}", app),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: Vec::new(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_summary_rollup() {
+        let temp_dir = TempDir::new().unwrap();
+        let generator = FocusSummaryGenerator::new(FocusSummaryConfig::default(), temp_dir.path().to_str().unwrap()).unwrap();
+
+        // Xcode for 30 minutes in three uninterrupted spans, then short Slack/Safari hops
+        let mut events: Vec<DetectedEvent> = vec![
+            create_switch(0, "Xcode"),
+            create_switch(10, "Xcode"),
+            create_switch(20, "Xcode"),
+            create_switch(30, "Slack"),
+            create_switch(32, "Safari"),
+            create_switch(35, "Slack"),
+            create_switch(37, "Safari"),
+        ];
+        let mut error = create_switch(33, "Safari");
+        error.event_type = EventType::ErrorDisplay;
+        events.push(error);
+
+        let summary = generator.summarize(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(), &events);
+
+        assert_eq!(summary.top_apps[0], AppUsage { app_name: "Xcode".to_string(), minutes: 30 });
+        assert_eq!(summary.deep_work_blocks.len(), 1);
+        assert_eq!(summary.deep_work_blocks[0].minutes(), 30);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.most_switched_to, Some(("Safari".to_string(), 2)));
+        // The last span is capped at the idle gap
+        assert_eq!(summary.active_minutes, 30 + 2 + 3 + 2 + 10);
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.contains("# Focus summary 2024-03-05"));
+        assert!(markdown.contains("- Xcode: 30m"));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_writes_the_note_once_due() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = FocusSummaryConfig { time_of_day: "18:00".to_string(), notify: false, ..Default::default() };
+        let generator = FocusSummaryGenerator::new(config, temp_dir.path().to_str().unwrap()).unwrap();
+        let morning = Local.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let mut schedule = FocusSummarySchedule::new(generator, morning).unwrap();
+
+        let mut switch = create_switch(0, "Xcode");
+        switch.timestamp = morning.with_timezone(&Utc);
+        schedule.record(&[switch]);
+        assert!(schedule.run_if_due(morning).await.unwrap().is_none());
+
+        let evening = Local.with_ymd_and_hms(2024, 3, 5, 18, 0, 1).unwrap();
+        let summary = schedule.run_if_due(evening).await.unwrap().unwrap();
+        assert_eq!(summary.top_apps[0].app_name, "Xcode");
+        assert!(temp_dir.path().join("summaries/2024-03-05.md").exists());
+        assert_eq!(schedule.due().date_naive(), NaiveDate::from_ymd_opt(2024, 3, 6).unwrap());
+        assert!(schedule.run_if_due(evening).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,552 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::field_identity::FieldIdentity;
+use crate::layout_compat::{self, CompatibleTable};
+use crate::ocr_data::{BoundingBox, OCRResult};
+use crate::redaction::REDACTED_PLACEHOLDER;
+use arrow::array::{Array, ListBuilder, StringArray, StringBuilder, TimestampNanosecondArray, TimestampNanosecondBuilder, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Text of controls that act on a form
+const BUTTON_WORDS: &[&str] = &[
+    "submit", "send", "save", "create", "update", "ok", "cancel", "apply", "confirm", "next", "back",
+    "continue", "delete", "search", "login", "log in", "sign in", "sign up", "register",
+];
+
+/// Characters password fields show in place of what was typed
+const MASK_CHARS: &[char] = &['•', '●', '∙', '*'];
+
+/// Configuration for reconstructing forms from tracked fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormModelConfig {
+    /// Record the filled-in form at each submission
+    pub enabled: bool,
+    /// Fields that must hold a value for a submission to be recorded
+    pub min_filled_fields: usize,
+    /// Where form records are written; defaults to `forms` in `output_dir`
+    pub dir: Option<String>,
+    /// Labels of fields whose values are never recorded, matched as whole
+    /// words of the label, case-insensitively; masked password fields are
+    /// never recorded either
+    pub sensitive_labels: Vec<String>,
+}
+
+impl Default for FormModelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_filled_fields: 1,
+            dir: None,
+            sensitive_labels: [
+                "password", "passcode", "passwort", "pin", "secret", "token", "api key", "card number",
+                "cvv", "cvc", "security code", "iban", "ssn", "social security",
+            ]
+            .iter()
+            .map(|label| label.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl FormModelConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_filled_fields == 0 {
+            return Err(IndexerError::Config("form_model min_filled_fields must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Labelled input on a form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormField {
+    /// Persistent field id from field identity tracking
+    pub field_id: String,
+    pub label: Option<String>,
+    pub value: String,
+    pub roi: BoundingBox,
+}
+
+/// Control that submits, cancels or otherwise acts on a form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormButton {
+    pub text: String,
+    pub roi: BoundingBox,
+}
+
+/// Form on one frame: its labelled fields and its buttons
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormModel {
+    pub fields: Vec<FormField>,
+    pub buttons: Vec<FormButton>,
+}
+
+impl FormModel {
+    /// Form of a frame's regions, given each region's identity. Regions with a
+    /// label are fields and action words are buttons; other text is ignored.
+    pub fn from_frame(regions: &[&OCRResult], identities: &[FieldIdentity]) -> Self {
+        let mut model = Self::default();
+        for (region, identity) in regions.iter().zip(identities) {
+            if is_button(&region.text) {
+                model.buttons.push(FormButton { text: region.text.trim().to_string(), roi: region.roi.clone() });
+            } else if identity.label.is_some() {
+                model.fields.push(FormField {
+                    field_id: identity.id.clone(),
+                    label: identity.label.clone(),
+                    value: region.text.trim().to_string(),
+                    roi: region.roi.clone(),
+                });
+            }
+        }
+        model
+    }
+}
+
+/// Filled-in form as it was submitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormRecord {
+    pub record_id: String,
+    /// When the submission was seen
+    pub timestamp: DateTime<Utc>,
+    /// Frame the submission was seen in
+    pub frame_id: String,
+    /// Form submission event the record was taken at
+    pub submission_event_id: String,
+    /// Text of the submit control, e.g. `Save`
+    pub submitted_with: Option<String>,
+    /// Fields in reading order
+    pub fields: Vec<FormField>,
+    pub buttons: Vec<FormButton>,
+}
+
+impl FormRecord {
+    /// Submitted values by field label
+    pub fn values(&self) -> BTreeMap<String, String> {
+        self.fields
+            .iter()
+            .filter_map(|field| field.label.clone().map(|label| (label, field.value.clone())))
+            .collect()
+    }
+}
+
+/// Follows the form on screen and records it at each submission.
+///
+/// Field values are kept while the form is on screen, so a field scrolled out
+/// of view keeps its last value. Submit controls are usually visible the whole
+/// time, so each submission with filled fields replaces the form's pending
+/// record; the record is final once the form leaves the screen.
+#[derive(Debug, Clone)]
+pub struct FormModelBuilder {
+    config: FormModelConfig,
+    form: FormModel,
+    pending: Option<FormRecord>,
+    records: Vec<FormRecord>,
+}
+
+impl FormModelBuilder {
+    pub fn new(config: FormModelConfig) -> Self {
+        Self { config, form: FormModel::default(), pending: None, records: Vec::new() }
+    }
+
+    /// Update the form state with a frame
+    pub fn observe(&mut self, regions: &[&OCRResult], identities: &[FieldIdentity]) {
+        let model = FormModel::from_frame(regions, identities);
+        let same_form = model
+            .fields
+            .iter()
+            .any(|field| self.form.fields.iter().any(|known| known.field_id == field.field_id));
+        if !same_form {
+            // A screen without any of the form's fields shows another form, or none
+            self.finish();
+            self.form = FormModel::default();
+        }
+
+        for field in model.fields {
+            match self.form.fields.iter_mut().find(|known| known.field_id == field.field_id) {
+                Some(known) => *known = field,
+                None => self.form.fields.push(field),
+            }
+        }
+        if !model.buttons.is_empty() {
+            self.form.buttons = model.buttons;
+        }
+    }
+
+    /// Snapshot the form at a form submission among a frame's events, linking
+    /// the event to the record through its `form_record_id` metadata
+    pub fn record_submissions(&mut self, frame_id: &str, timestamp: DateTime<Utc>, events: &mut [DetectedEvent]) {
+        let Some(event) = events.iter_mut().find(|event| event.event_type == EventType::FormSubmission) else {
+            return;
+        };
+        let filled = self.form.fields.iter().filter(|field| !field.value.is_empty()).count();
+        if filled < self.config.min_filled_fields {
+            return;
+        }
+
+        let record_id = self
+            .pending
+            .as_ref()
+            .map(|record| record.record_id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut fields = self.form.fields.clone();
+        for field in fields.iter_mut().filter(|field| self.is_sensitive(field)) {
+            field.value = REDACTED_PLACEHOLDER.to_string();
+        }
+        fields.sort_by(|a, b| a.roi.y.total_cmp(&b.roi.y).then(a.roi.x.total_cmp(&b.roi.x)));
+        event.metadata.insert("form_record_id".to_string(), record_id.clone());
+        debug!("Form {} submitted with {} filled fields", record_id, filled);
+
+        self.pending = Some(FormRecord {
+            record_id,
+            timestamp,
+            frame_id: frame_id.to_string(),
+            submission_event_id: event.id.clone(),
+            submitted_with: event.value_to.clone(),
+            fields,
+            buttons: self.form.buttons.clone(),
+        });
+    }
+
+    /// Make the record of the form on screen final; call after the last frame
+    pub fn finish(&mut self) {
+        if let Some(record) = self.pending.take() {
+            self.records.push(record);
+        }
+    }
+
+    /// Records made final so far
+    pub fn take_records(&mut self) -> Vec<FormRecord> {
+        std::mem::take(&mut self.records)
+    }
+
+    /// Whether a field's value must not be recorded: a sensitive label, or a
+    /// value the screen itself masks
+    fn is_sensitive(&self, field: &FormField) -> bool {
+        let masked = !field.value.is_empty() && field.value.chars().all(|c| MASK_CHARS.contains(&c) || c.is_whitespace());
+        let Some(label) = &field.label else {
+            return masked;
+        };
+        let words = |text: &str| {
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+        };
+        let label = words(label);
+        masked
+            || self.config.sensitive_labels.iter().any(|sensitive| {
+                let sensitive = words(sensitive);
+                !sensitive.is_empty() && label.windows(sensitive.len()).any(|window| window == sensitive.as_slice())
+            })
+    }
+}
+
+fn is_button(text: &str) -> bool {
+    let text = text.trim().to_lowercase();
+    text.split_whitespace().count() <= 3
+        && BUTTON_WORDS.iter().any(|word| text == *word || text.starts_with(&format!("{} ", word)))
+}
+
+/// Parquet output for form records, one row per submitted form
+pub struct FormRecordParquetWriter {
+    output_dir: PathBuf,
+    schema: Arc<Schema>,
+    batch_size: usize,
+    current_batch: Vec<FormRecord>,
+}
+
+impl FormRecordParquetWriter {
+    pub fn new(output_dir: &str) -> Result<Self> {
+        let output_path = PathBuf::from(output_dir);
+        std::fs::create_dir_all(&output_path)?;
+
+        let string_list = || DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_id", DataType::Utf8, false),
+            Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("frame_id", DataType::Utf8, false),
+            Field::new("submission_event_id", DataType::Utf8, false),
+            Field::new("submitted_with", DataType::Utf8, true),
+            Field::new("field_count", DataType::UInt32, false),
+            Field::new("labels", string_list(), false),
+            Field::new("field_values", string_list(), false),
+            Field::new("fields", DataType::Utf8, false), // JSON-encoded fields
+            Field::new("buttons", DataType::Utf8, false), // JSON-encoded buttons
+        ]));
+
+        Ok(Self {
+            output_dir: output_path,
+            schema,
+            batch_size: 200,
+            current_batch: Vec::new(),
+        })
+    }
+
+    pub async fn write_records(&mut self, records: &[FormRecord]) -> Result<()> {
+        self.current_batch.extend_from_slice(records);
+        if self.current_batch.len() >= self.batch_size {
+            self.flush_batch().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered records to disk
+    pub async fn flush_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%f");
+        let file_path = self.output_dir.join(format!("form_records_{}.parquet", timestamp));
+
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        let mut writer = ArrowWriter::try_new(File::create(&file_path)?, self.schema.clone(), Some(props))?;
+        writer.write(&record_batch)?;
+        writer.close()?;
+
+        info!("Wrote {} form records to {}", self.current_batch.len(), file_path.display());
+        self.current_batch.clear();
+        Ok(())
+    }
+
+    pub async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch().await
+    }
+
+    /// Forms submitted within a time range, oldest first
+    pub async fn query_by_time_range(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Result<Vec<FormRecord>> {
+        self.run_query(&format!(
+            "SELECT * FROM form_records WHERE ts_ns >= {} AND ts_ns <= {} ORDER BY ts_ns ASC",
+            start_time.timestamp_nanos_opt().unwrap_or(0),
+            end_time.timestamp_nanos_opt().unwrap_or(0)
+        )).await
+    }
+
+    /// Register the stored records as the `form_records` table
+    pub async fn register_records(&self, ctx: &SessionContext) -> Result<()> {
+        CompatibleTable::new("form_records", self.schema.clone())
+            .register(ctx, &self.output_dir)
+            .await
+    }
+
+    pub fn get_output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    // MARK: - Private Helper Methods
+
+    async fn run_query(&self, sql: &str) -> Result<Vec<FormRecord>> {
+        if layout_compat::parquet_files(&self.output_dir)?.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = SessionContext::new();
+        self.register_records(&ctx).await?;
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let mut records = Vec::new();
+        for batch in &batches {
+            records.extend(form_records_from_batch(batch)?);
+        }
+        Ok(records)
+    }
+
+    fn create_record_batch(&self, records: &[FormRecord]) -> Result<RecordBatch> {
+        let mut ts_builder = TimestampNanosecondBuilder::new();
+        let mut labels_builder = ListBuilder::new(StringBuilder::new());
+        let mut values_builder = ListBuilder::new(StringBuilder::new());
+        let mut fields_json = Vec::with_capacity(records.len());
+        let mut buttons_json = Vec::with_capacity(records.len());
+
+        for record in records {
+            ts_builder.append_value(record.timestamp.timestamp_nanos_opt().unwrap_or(0));
+            for (label, value) in record.values() {
+                labels_builder.values().append_value(label);
+                values_builder.values().append_value(value);
+            }
+            labels_builder.append(true);
+            values_builder.append(true);
+            fields_json.push(serde_json::to_string(&record.fields)?);
+            buttons_json.push(serde_json::to_string(&record.buttons)?);
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(StringArray::from(records.iter().map(|r| r.record_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(ts_builder.finish()),
+                Arc::new(StringArray::from(records.iter().map(|r| r.frame_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(records.iter().map(|r| r.submission_event_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(records.iter().map(|r| r.submitted_with.as_deref()).collect::<Vec<_>>())),
+                Arc::new(UInt32Array::from(records.iter().map(|r| r.fields.len() as u32).collect::<Vec<_>>())),
+                Arc::new(labels_builder.finish()),
+                Arc::new(values_builder.finish()),
+                Arc::new(StringArray::from(fields_json)),
+                Arc::new(StringArray::from(buttons_json)),
+            ],
+        )?;
+
+        Ok(record_batch)
+    }
+}
+
+/// Form records of a batch read back from the `form_records` table
+pub fn form_records_from_batch(batch: &RecordBatch) -> Result<Vec<FormRecord>> {
+    let ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let timestamps = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let frame_ids = layout_compat::column::<StringArray>(batch, 2)?;
+    let event_ids = layout_compat::column::<StringArray>(batch, 3)?;
+    let submitted_with = layout_compat::column::<StringArray>(batch, 4)?;
+    let fields = layout_compat::column::<StringArray>(batch, 8)?;
+    let buttons = layout_compat::column::<StringArray>(batch, 9)?;
+
+    let mut records = Vec::with_capacity(batch.num_rows());
+    for i in 0..batch.num_rows() {
+        let record_fields = match serde_json::from_str(fields.value(i)) {
+            Ok(record_fields) => record_fields,
+            Err(e) => {
+                warn!("Skipping form record {} with unreadable fields: {}", ids.value(i), e);
+                continue;
+            }
+        };
+        records.push(FormRecord {
+            record_id: ids.value(i).to_string(),
+            timestamp: DateTime::from_timestamp_nanos(timestamps.value(i)),
+            frame_id: frame_ids.value(i).to_string(),
+            submission_event_id: event_ids.value(i).to_string(),
+            submitted_with: (!submitted_with.is_null(i)).then(|| submitted_with.value(i).to_string()),
+            fields: record_fields,
+            buttons: serde_json::from_str(buttons.value(i)).unwrap_or_default(),
+        });
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::{EventDetectionConfig, EventDetector};
+    use tempfile::TempDir;
+
+    fn region(text: &str, x: f32, y: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(x, y, 150.0, 20.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submitted_form_is_recorded_once() {
+        let mut config = EventDetectionConfig::default();
+        config.form_model.enabled = true;
+        let mut detector = EventDetector::with_config(config).unwrap();
+        let start = Utc::now();
+        let form = |vendor: &str, amount: &str| {
+            vec![
+                region("Vendor:", 10.0, 10.0), region(vendor, 170.0, 10.0),
+                region("Amount:", 10.0, 40.0), region(amount, 170.0, 40.0),
+                region("Save", 170.0, 80.0),
+            ]
+        };
+
+        // The Save button is visible while the form is filled in
+        for (second, (vendor, amount)) in [("", ""), ("ACME", ""), ("ACME", "120.00")].into_iter().enumerate() {
+            let at = start + chrono::Duration::seconds(second as i64);
+            detector.analyze_frame(&format!("frame_{}", second), &form(vendor, amount), at, 1920.0, 1080.0).unwrap();
+        }
+        // Still on screen, so the record may yet change
+        assert!(detector.take_form_records().is_empty());
+
+        // Saving clears the fields and then leaves the form
+        detector.analyze_frame("frame_3", &form("", ""), start + chrono::Duration::seconds(3), 1920.0, 1080.0).unwrap();
+        detector.analyze_frame("frame_4", &[region("Invoice saved", 10.0, 10.0)], start + chrono::Duration::seconds(4), 1920.0, 1080.0).unwrap();
+        detector.finish();
+        let records = detector.take_form_records();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.frame_id, "frame_2");
+        assert_eq!(record.submitted_with.as_deref(), Some("Save"));
+        assert_eq!(
+            record.values(),
+            BTreeMap::from([("Amount".to_string(), "120.00".to_string()), ("Vendor".to_string(), "ACME".to_string())])
+        );
+        assert_eq!(record.buttons.len(), 1);
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = FormRecordParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        writer.write_records(&records).await.unwrap();
+        writer.finalize().await.unwrap();
+        let stored = writer
+            .query_by_time_range(start, start + chrono::Duration::seconds(10))
+            .await
+            .unwrap();
+        assert_eq!(stored, records);
+    }
+
+    #[test]
+    fn test_sensitive_fields_are_masked() {
+        let mut builder = FormModelBuilder::new(FormModelConfig { enabled: true, ..FormModelConfig::default() });
+        let regions = [
+            region("Username:", 10.0, 10.0), region("jdoe", 170.0, 10.0),
+            region("Password:", 10.0, 40.0), region("hunter2", 170.0, 40.0),
+            region("Confirm:", 10.0, 70.0), region("•••••••", 170.0, 70.0),
+            region("Shipping:", 10.0, 100.0), region("Express", 170.0, 100.0),
+            region("Card Number:", 10.0, 130.0), region("4111 1111 1111 1111", 170.0, 130.0),
+        ];
+        let regions: Vec<&OCRResult> = regions.iter().collect();
+        let identities: Vec<FieldIdentity> = regions
+            .iter()
+            .enumerate()
+            .map(|(index, _)| FieldIdentity {
+                id: format!("field_{}", index),
+                label: crate::field_identity::infer_label(&regions, index, 250.0),
+            })
+            .collect();
+        builder.observe(&regions, &identities);
+
+        let mut events = vec![DetectedEvent {
+            id: "submit".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::FormSubmission,
+            target: "form".to_string(),
+            value_from: None,
+            value_to: Some("Sign up".to_string()),
+            confidence: 0.9,
+            evidence_frames: Vec::new(),
+            metadata: std::collections::HashMap::new(),
+        }];
+        builder.record_submissions("frame", Utc::now(), &mut events);
+        builder.finish();
+        let values = builder.take_records()[0].values();
+        assert_eq!(values["Username"], "jdoe");
+        assert_eq!(values["Shipping"], "Express");
+        assert_eq!(values["Password"], REDACTED_PLACEHOLDER);
+        assert_eq!(values["Confirm"], REDACTED_PLACEHOLDER);
+        assert_eq!(values["Card Number"], REDACTED_PLACEHOLDER);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::metadata_collector::FrameMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Frame deduplication settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameDedupConfig {
+    /// Skip metadata collection, OCR and event analysis for near-identical frames
+    pub enabled: bool,
+    /// Maximum pHash Hamming distance for two frames to count as duplicates
+    pub max_hamming_distance: u32,
+    /// Drop decoded frames whose pixels exactly match the last saved keyframe
+    /// before they are written or analyzed
+    pub skip_identical_frames: bool,
+    /// An identical frame is still saved this often, so an unchanged screen is
+    /// not mistaken for idle time; never when 0
+    pub identical_keep_secs: u64,
+}
+
+impl Default for FrameDedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_hamming_distance: 4,
+            skip_identical_frames: false,
+            identical_keep_secs: 60,
+        }
+    }
+}
+
+/// A frame already processed in the current segment
+#[derive(Debug, Clone)]
+struct ProcessedFrame {
+    phash: u64,
+    metadata: FrameMetadata,
+}
+
+/// Per-segment cache of processed frames keyed by perceptual hash.
+///
+/// Frames are compared against every unique frame seen so far in their
+/// segment, so a screen that flips back to an earlier state is also caught.
+pub struct FrameDeduplicator {
+    config: FrameDedupConfig,
+    segments: HashMap<String, Vec<ProcessedFrame>>,
+    duplicates_skipped: u64,
+}
+
+impl FrameDeduplicator {
+    pub fn new(config: FrameDedupConfig) -> Self {
+        Self {
+            config,
+            segments: HashMap::new(),
+            duplicates_skipped: 0,
+        }
+    }
+
+    pub fn update_config(&mut self, config: FrameDedupConfig) {
+        self.config = config;
+    }
+
+    /// The processed frame in the same segment this hash duplicates, if any
+    pub fn find_duplicate(&self, segment_id: &str, phash: u64) -> Option<&FrameMetadata> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        self.segments
+            .get(segment_id)?
+            .iter()
+            .filter_map(|frame| {
+                let distance = (frame.phash ^ phash).count_ones();
+                (distance <= self.config.max_hamming_distance).then_some((distance, &frame.metadata))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, metadata)| metadata)
+    }
+
+    /// Metadata for a duplicate frame, copied from the original instead of collected
+    pub fn duplicate_metadata(
+        &mut self,
+        original: &FrameMetadata,
+        ts_ns: i64,
+        path: &str,
+    ) -> FrameMetadata {
+        self.duplicates_skipped += 1;
+        debug!("Frame {} duplicates {}", path, original.path);
+
+        FrameMetadata {
+            ts_ns,
+            path: path.to_string(),
+            duplicate_of: Some(original.path.clone()),
+            ..original.clone()
+        }
+    }
+
+    /// Remember a processed (non-duplicate) frame
+    pub fn record(&mut self, phash: u64, metadata: &FrameMetadata) {
+        self.segments
+            .entry(metadata.segment_id.clone())
+            .or_default()
+            .push(ProcessedFrame { phash, metadata: metadata.clone() });
+    }
+
+    /// Release the cache of a finished segment
+    pub fn finish_segment(&mut self, segment_id: &str) {
+        self.segments.remove(segment_id);
+    }
+
+    pub fn duplicates_skipped(&self) -> u64 {
+        self.duplicates_skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_metadata(segment_id: &str, path: &str) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: segment_id.to_string(),
+            path: path.to_string(),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: "Safari".to_string(),
+            win_title: "Docs".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }
+    }
+
+    #[test]
+    fn test_frames_are_kept_unless_opted_in() {
+        // Existing setups keep every extracted keyframe after upgrading
+        let config = FrameDedupConfig::default();
+        assert!(!config.enabled);
+        assert!(!config.skip_identical_frames);
+        assert_eq!(config.identical_keep_secs, 60);
+    }
+
+    #[test]
+    fn test_duplicates_within_segment() {
+        let mut dedup = FrameDeduplicator::new(FrameDedupConfig { enabled: true, max_hamming_distance: 2, ..FrameDedupConfig::default() });
+        let original = create_metadata("segment_a", "frame_0.png");
+        dedup.record(0b1111_0000, &original);
+
+        // Within distance in the same segment
+        let found = dedup.find_duplicate("segment_a", 0b1111_0011).cloned().unwrap();
+        let duplicate = dedup.duplicate_metadata(&found, 500, "frame_1.png");
+        assert_eq!(duplicate.duplicate_of.as_deref(), Some("frame_0.png"));
+        assert_eq!(duplicate.app_name, "Safari");
+        assert_eq!(duplicate.ts_ns, 500);
+        assert!(duplicate.is_duplicate());
+
+        // Too far apart, or a different segment
+        assert!(dedup.find_duplicate("segment_a", 0b0000_1111).is_none());
+        assert!(dedup.find_duplicate("segment_b", 0b1111_0000).is_none());
+
+        dedup.finish_segment("segment_a");
+        assert!(dedup.find_duplicate("segment_a", 0b1111_0000).is_none());
+        assert_eq!(dedup.duplicates_skipped(), 1);
+    }
+}
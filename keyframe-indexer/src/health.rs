@@ -0,0 +1,314 @@
+use crate::error::{IndexerError, Result};
+use crate::live_snapshot::SnapshotTracker;
+use crate::segment_ledger::SharedSegmentLedger;
+use crate::system_probe::SystemProbe;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+/// Detectors whose running means new recordings are being picked up
+const WATCHER_DETECTORS: &[&str] = &["file_watcher", "screen_capture"];
+
+/// Largest request head read from a probe
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Health and readiness endpoints for service managers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Serve `/healthz` and `/readyz` while watching or capturing
+    pub enabled: bool,
+    /// Address the endpoints listen on; also where `indexer status` asks
+    pub bind_address: String,
+    /// Age of the oldest queued segment past which the service is not ready
+    pub max_writer_lag_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1:9108".to_string(),
+            max_writer_lag_secs: 300,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.bind_address
+            .parse::<SocketAddr>()
+            .map_err(|e| IndexerError::Config(format!("Invalid bind_address '{}': {}", self.bind_address, e)))?;
+        if self.max_writer_lag_secs == 0 {
+            return Err(IndexerError::Config("max_writer_lag_secs must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// What `/healthz`, `/readyz` and `indexer status` report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    /// False once the watcher or capture has stopped while it should be running
+    pub healthy: bool,
+    /// Healthy, watching, keeping up and with every permission it needs
+    pub ready: bool,
+    pub watcher_running: bool,
+    pub last_segment: Option<String>,
+    pub last_segment_processed_at: Option<DateTime<Utc>>,
+    pub pending_segments: usize,
+    /// Seconds the oldest queued segment has waited
+    pub writer_lag_secs: Option<u64>,
+    /// Whether each permission or resource the service uses is available
+    pub permissions: BTreeMap<String, bool>,
+    /// Why the service is unhealthy or not ready
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    /// Set while watching or capturing, so a stopped watcher counts as a failure
+    expect_watcher: bool,
+    last_segment: Option<String>,
+    last_segment_at: Option<DateTime<Utc>>,
+    permissions: BTreeMap<String, bool>,
+    probe: Option<SystemProbe>,
+}
+
+/// Shared view of the service's health; clones share it
+#[derive(Clone)]
+pub struct HealthMonitor {
+    config: HealthConfig,
+    output_dir: PathBuf,
+    snapshot: SnapshotTracker,
+    ledger: SharedSegmentLedger,
+    state: Arc<RwLock<HealthState>>,
+}
+
+impl HealthMonitor {
+    pub fn new(config: HealthConfig, output_dir: &Path, snapshot: SnapshotTracker, ledger: SharedSegmentLedger) -> Self {
+        Self {
+            config,
+            output_dir: output_dir.to_path_buf(),
+            snapshot,
+            ledger,
+            state: Arc::new(RwLock::new(HealthState::default())),
+        }
+    }
+
+    pub fn config(&self) -> &HealthConfig {
+        &self.config
+    }
+
+    /// Watch the probe used for AppleScript, whose circuit opens when
+    /// automation of System Events is denied or hangs
+    pub fn set_system_probe(&self, probe: SystemProbe) {
+        self.update(|state| state.probe = Some(probe));
+    }
+
+    pub fn set_expect_watcher(&self, expected: bool) {
+        self.update(|state| state.expect_watcher = expected);
+    }
+
+    pub fn set_permission(&self, name: &str, granted: bool) {
+        self.update(|state| {
+            state.permissions.insert(name.to_string(), granted);
+        });
+    }
+
+    pub fn record_segment(&self, segment: &str) {
+        self.update(|state| {
+            state.last_segment = Some(segment.to_string());
+            state.last_segment_at = Some(Utc::now());
+        });
+    }
+
+    pub fn status(&self) -> HealthStatus {
+        let now = Utc::now();
+        let (pending_segments, oldest_pending) = match self.ledger.lock() {
+            Ok(ledger) => (ledger.pending_count(), ledger.oldest_pending_at()),
+            Err(poisoned) => {
+                let ledger = poisoned.into_inner();
+                (ledger.pending_count(), ledger.oldest_pending_at())
+            }
+        };
+        let active = self.snapshot.snapshot().active_detectors;
+        let watcher_running = WATCHER_DETECTORS.iter().any(|d| active.iter().any(|a| a == d));
+
+        let state = match self.state.read() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut permissions = state.permissions.clone();
+        permissions.insert("output_dir".to_string(), is_writable(&self.output_dir));
+        if let Some(probe) = &state.probe {
+            permissions.insert("system_events".to_string(), !probe.is_open("System Events"));
+        }
+
+        let mut status = HealthStatus {
+            healthy: true,
+            ready: true,
+            watcher_running,
+            last_segment: state.last_segment.clone(),
+            last_segment_processed_at: state.last_segment_at,
+            pending_segments,
+            writer_lag_secs: oldest_pending.map(|at| (now - at).num_seconds().max(0) as u64),
+            permissions,
+            problems: Vec::new(),
+        };
+        if state.expect_watcher && !watcher_running {
+            status.healthy = false;
+            status.problems.push("watcher stopped".to_string());
+        } else if !watcher_running {
+            status.problems.push("not watching".to_string());
+        }
+        if let Some(lag) = status.writer_lag_secs.filter(|lag| *lag > self.config.max_writer_lag_secs) {
+            status.problems.push(format!("oldest queued segment waited {}s", lag));
+        }
+        for (name, _) in status.permissions.iter().filter(|(_, granted)| !**granted) {
+            status.problems.push(format!("{} unavailable", name));
+        }
+        status.ready = status.healthy && status.problems.is_empty();
+        status
+    }
+
+    fn update(&self, f: impl FnOnce(&mut HealthState)) {
+        match self.state.write() {
+            Ok(mut state) => f(&mut state),
+            Err(poisoned) => f(&mut poisoned.into_inner()),
+        }
+    }
+}
+
+/// Whether a file can be created in `dir`
+fn is_writable(dir: &Path) -> bool {
+    // Dot files are left out of every listing of data files
+    let probe = dir.join(format!(".health_{}", uuid::Uuid::new_v4()));
+    let writable = std::fs::write(&probe, b"").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    writable
+}
+
+/// Serve `/healthz` and `/readyz` on `address` until the task is aborted.
+///
+/// Both answer with the status as JSON: 200 when healthy or ready
+/// respectively, 503 otherwise.
+pub async fn serve(monitor: HealthMonitor, address: &str) -> Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(address).await?;
+    let local = listener.local_addr()?;
+    info!("Health endpoints listening on http://{}", local);
+    let task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let monitor = monitor.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream, &monitor).await {
+                            debug!("Health probe failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => debug!("Failed to accept health probe: {}", e),
+            }
+        }
+    });
+    Ok((local, task))
+}
+
+async fn respond(mut stream: TcpStream, monitor: &HealthMonitor) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&request);
+    let mut parts = head.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let status = monitor.status();
+    let (code, body) = match (method, path) {
+        ("GET", "/healthz") => (if status.healthy { 200 } else { 503 }, serde_json::to_string(&status)?),
+        ("GET", "/readyz") => (if status.ready { 200 } else { 503 }, serde_json::to_string(&status)?),
+        _ => (404, "{\"error\":\"not found\"}".to_string()),
+    };
+    let reason = match code {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Service Unavailable",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Ask a running service for its status at `path` (`/healthz` or `/readyz`),
+/// returning the HTTP status code and the reported status
+pub async fn fetch_status(address: &str, path: &str) -> Result<(u16, HealthStatus)> {
+    let mut stream = TcpStream::connect(address).await?;
+    let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, address);
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    let invalid = || IndexerError::Io(std::io::Error::other(format!("Invalid health response from {}", address)));
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(invalid)?;
+    let code = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(invalid)?;
+    Ok((code, serde_json::from_str(body)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment_ledger::SegmentLedger;
+
+    #[tokio::test]
+    async fn test_probes_follow_watcher_and_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = SegmentLedger::open_shared(dir.path().join("pending_segments.jsonl")).unwrap();
+        let snapshot = SnapshotTracker::new();
+        let monitor = HealthMonitor::new(HealthConfig::default(), dir.path(), snapshot.clone(), ledger);
+        let (address, server) = serve(monitor.clone(), "127.0.0.1:0").await.unwrap();
+        let address = address.to_string();
+
+        // Not watching yet: alive but not ready
+        let (code, status) = fetch_status(&address, "/healthz").await.unwrap();
+        assert_eq!((code, status.healthy, status.permissions["output_dir"]), (200, true, true));
+        assert_eq!(fetch_status(&address, "/readyz").await.unwrap().0, 503);
+
+        monitor.set_expect_watcher(true);
+        snapshot.set_detector_active("file_watcher", true);
+        monitor.record_segment("segment_1");
+        let (code, status) = fetch_status(&address, "/readyz").await.unwrap();
+        assert_eq!((code, status.last_segment.as_deref()), (200, Some("segment_1")));
+
+        monitor.set_permission("screen_recording", false);
+        assert_eq!(fetch_status(&address, "/readyz").await.unwrap().0, 503);
+
+        snapshot.set_detector_active("file_watcher", false);
+        let (code, status) = fetch_status(&address, "/healthz").await.unwrap();
+        assert_eq!((code, status.healthy), (503, false));
+        server.abort();
+    }
+}
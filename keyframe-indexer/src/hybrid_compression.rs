@@ -0,0 +1,216 @@
+use crate::error::{IndexerError, Result};
+use crate::keyframe_codec::{self, KeyframeStorageConfig};
+use crate::ocr_data::BoundingBox;
+use crate::visual_features;
+use image::{DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Settings for re-encoding keyframes with exact text and simplified background
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HybridCompressionConfig {
+    pub enabled: bool,
+    /// Pixels kept exact around each OCR box, so antialiased glyph edges survive
+    pub roi_padding: u32,
+    /// Side of the blocks a frame is split into; a block touching text is kept exact
+    pub block_size: u32,
+    /// Background blocks are averaged over square cells of this many pixels
+    pub background_cell: u32,
+    /// Levels per color channel left in background blocks
+    pub background_levels: u8,
+    /// Without OCR boxes, blocks with at least this share of sharp edges count as text
+    pub edge_fraction: f32,
+}
+
+impl Default for HybridCompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            roi_padding: 4,
+            block_size: 16,
+            background_cell: 4,
+            background_levels: 32,
+            edge_fraction: 0.08,
+        }
+    }
+}
+
+impl HybridCompressionConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.block_size < 4 || !(1..=self.block_size).contains(&self.background_cell) {
+            return Err(IndexerError::Config(
+                "hybrid block_size must be at least 4 and background_cell 1..=block_size".to_string(),
+            ));
+        }
+        if self.background_levels < 2 || !(self.edge_fraction > 0.0 && self.edge_fraction <= 1.0) {
+            return Err(IndexerError::Config(
+                "hybrid background_levels must be at least 2 and edge_fraction in (0, 1]".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of re-encoding one keyframe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HybridStats {
+    pub text_blocks: usize,
+    pub total_blocks: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Blocks of a frame that hold text and must stay pixel-exact
+struct TextMask {
+    block: u32,
+    cols: u32,
+    text: Vec<bool>,
+}
+
+impl TextMask {
+    fn new(image: &RgbImage, block: u32) -> Self {
+        let cols = image.width().div_ceil(block);
+        let rows = image.height().div_ceil(block);
+        Self { block, cols, text: vec![false; (cols * rows) as usize] }
+    }
+
+    /// Blocks overlapping any padded OCR box
+    fn from_rois(image: &RgbImage, rois: &[BoundingBox], config: &HybridCompressionConfig) -> Self {
+        let mut mask = Self::new(image, config.block_size);
+        let (width, height) = (image.width() as f32, image.height() as f32);
+        let padding = config.roi_padding as f32;
+        for roi in rois {
+            let x0 = (roi.x - padding).clamp(0.0, width) as u32;
+            let y0 = (roi.y - padding).clamp(0.0, height) as u32;
+            let x1 = (roi.x + roi.width + padding).clamp(0.0, width).ceil() as u32;
+            let y1 = (roi.y + roi.height + padding).clamp(0.0, height).ceil() as u32;
+            if x1 <= x0 || y1 <= y0 {
+                continue;
+            }
+            for row in y0 / mask.block..=(y1 - 1) / mask.block {
+                for col in x0 / mask.block..=(x1 - 1) / mask.block {
+                    mask.text[(row * mask.cols + col) as usize] = true;
+                }
+            }
+        }
+        mask
+    }
+
+    /// Blocks dense in sharp luma edges, the way glyphs are, for frames without OCR
+    fn from_edges(image: &RgbImage, config: &HybridCompressionConfig) -> Self {
+        let mut mask = Self::new(image, config.block_size);
+        let gray = image::imageops::grayscale(image);
+        mask.text = visual_features::text_like_blocks(&gray, config.block_size, config.edge_fraction);
+        mask
+    }
+
+    /// Pixel bounds of a block, clipped to the frame
+    fn bounds(&self, index: usize, image: &RgbImage) -> (u32, u32, u32, u32) {
+        let (col, row) = (index as u32 % self.cols, index as u32 / self.cols);
+        let (x0, y0) = (col * self.block, row * self.block);
+        (x0, y0, (x0 + self.block).min(image.width()), (y0 + self.block).min(image.height()))
+    }
+}
+
+/// Average background blocks over cells and cut their colors down to a few
+/// levels, leaving text blocks untouched; returns the number of text blocks
+fn flatten_background(image: &mut RgbImage, mask: &TextMask, config: &HybridCompressionConfig) -> usize {
+    let cell = config.background_cell;
+    let step = 255.0 / (config.background_levels - 1) as f32;
+    for index in (0..mask.text.len()).filter(|i| !mask.text[*i]) {
+        let (x0, y0, x1, y1) = mask.bounds(index, image);
+        for cy in (y0..y1).step_by(cell as usize) {
+            for cx in (x0..x1).step_by(cell as usize) {
+                let (cx1, cy1) = ((cx + cell).min(x1), (cy + cell).min(y1));
+                let mut sum = [0u32; 3];
+                for y in cy..cy1 {
+                    for x in cx..cx1 {
+                        for (total, value) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                            *total += value as u32;
+                        }
+                    }
+                }
+                let count = ((cx1 - cx) * (cy1 - cy)) as f32;
+                let color = sum.map(|total| ((total as f32 / count / step).round() * step).min(255.0) as u8);
+                for y in cy..cy1 {
+                    for x in cx..cx1 {
+                        image.put_pixel(x, y, image::Rgb(color));
+                    }
+                }
+            }
+        }
+    }
+    mask.text.iter().filter(|text| **text).count()
+}
+
+/// Re-encode a saved keyframe so its text stays pixel-exact and its background
+/// compresses well; `rois` are the frame's OCR boxes, or `None` when OCR has not
+/// run and text is found from edge density instead
+pub fn compress_keyframe(path: &Path, rois: Option<&[BoundingBox]>, storage: &KeyframeStorageConfig) -> Result<HybridStats> {
+    let config = &storage.hybrid;
+    let bytes_before = std::fs::metadata(path)?.len();
+    let mut image = image::open(path)?.to_rgb8();
+    let mask = match rois {
+        Some(rois) => TextMask::from_rois(&image, rois, config),
+        None => TextMask::from_edges(&image, config),
+    };
+    let total_blocks = mask.text.len();
+    let text_blocks = flatten_background(&mut image, &mask, config);
+    if text_blocks == total_blocks {
+        return Ok(HybridStats { text_blocks, total_blocks, bytes_before, bytes_after: bytes_before });
+    }
+
+    keyframe_codec::save_keyframe(&DynamicImage::ImageRgb8(image), path, storage)?;
+    let bytes_after = std::fs::metadata(path)?.len();
+    Ok(HybridStats { text_blocks, total_blocks, bytes_before, bytes_after })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyframe_codec::KeyframeCodec;
+
+    #[test]
+    fn test_text_stays_exact_and_background_shrinks() {
+        // A dark-on-light text line over a noisy photo-like background
+        let original = RgbImage::from_fn(128, 96, |x, y| {
+            if (8..24).contains(&y) && (8..72).contains(&x) {
+                if x % 3 == 0 || y % 5 == 0 { image::Rgb([10, 10, 10]) } else { image::Rgb([245, 245, 245]) }
+            } else {
+                let noise = ((x * 7919 + y * 104_729) % 37) as u8;
+                image::Rgb([90 + noise, 140 - noise, (x + y) as u8])
+            }
+        });
+        let storage = KeyframeStorageConfig {
+            codec: KeyframeCodec::Png,
+            hybrid: HybridCompressionConfig { enabled: true, ..HybridCompressionConfig::default() },
+            ..KeyframeStorageConfig::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let in_text = |x: u32, y: u32| (8..24).contains(&y) && (8..72).contains(&x);
+
+        let roi = BoundingBox { x: 8.0, y: 8.0, width: 64.0, height: 16.0 };
+        for rois in [Some(vec![roi]), None] {
+            let path = dir.path().join("frame.png");
+            original.save(&path).unwrap();
+            let stats = compress_keyframe(&path, rois.as_deref(), &storage).unwrap();
+            assert!(stats.text_blocks > 0 && stats.text_blocks < stats.total_blocks);
+            assert!(stats.bytes_after < stats.bytes_before);
+
+            let compressed = image::open(&path).unwrap().to_rgb8();
+            let mut changed = 0;
+            for (x, y, pixel) in compressed.enumerate_pixels() {
+                if in_text(x, y) {
+                    assert_eq!(pixel, original.get_pixel(x, y));
+                } else if pixel != original.get_pixel(x, y) {
+                    changed += 1;
+                }
+            }
+            // The background was flattened
+            assert!(changed > 128 * 96 / 2);
+        }
+
+        assert!(HybridCompressionConfig { background_levels: 1, ..HybridCompressionConfig::default() }.validate().is_err());
+    }
+}
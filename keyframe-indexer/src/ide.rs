@@ -0,0 +1,616 @@
+use crate::app_extractor::{AppExtractor, AppFrame};
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use crate::screen_classifier::ScreenType;
+use crate::terminal::{screen_lines, TerminalConfig, TerminalExtractor};
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Code editor extractor settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdeConfig {
+    pub enabled: bool,
+    /// Apps handled, matched case-insensitively as part of the app name or bundle id.
+    /// Frames classified as `ide` are handled regardless.
+    pub app_names: Vec<String>,
+    /// Top share of the frame holding the tab bar and breadcrumbs
+    pub tab_bar_ratio: f32,
+    /// Bottom share of the frame holding the problems panel and integrated terminal
+    pub panel_ratio: f32,
+    /// How long after editing code a failed build or test is tied to the edit
+    pub correlation_window_secs: u64,
+}
+
+impl Default for IdeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            app_names: [
+                "Visual Studio Code",
+                "com.microsoft.VSCode",
+                "Cursor",
+                "IntelliJ",
+                "PyCharm",
+                "WebStorm",
+                "GoLand",
+                "CLion",
+                "Rider",
+                "RustRover",
+                "Android Studio",
+                "Xcode",
+                "Sublime Text",
+                "Zed",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            tab_bar_ratio: 0.12,
+            panel_ratio: 0.4,
+            correlation_window_secs: 600,
+        }
+    }
+}
+
+/// An error or warning shown in the problems panel or build output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeDiagnostic {
+    /// `error` or `warning`
+    pub severity: String,
+    pub message: String,
+    /// Compiler or linter code, e.g. `E0308` or `ts(2304)`
+    pub code: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// What a code editor keyframe shows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeScreen {
+    /// File in the active editor
+    pub file: Option<String>,
+    pub language: Option<String>,
+    pub project: Option<String>,
+    /// The file has unsaved changes
+    pub dirty: bool,
+    pub diagnostics: Vec<CodeDiagnostic>,
+}
+
+/// Reads the open file, its language and shown diagnostics from code editors.
+///
+/// Emits `FileOpened` when the active file changes and `CodeDiagnostic` for
+/// each newly shown error or warning, tags code edits with their file, and
+/// runs the terminal extractor over the integrated terminal panel.
+pub struct IdeExtractor {
+    config: IdeConfig,
+    title_separator: Regex,
+    file_name: Regex,
+    diagnostic: Regex,
+    source_diagnostic: Regex,
+    location: Regex,
+    terminal: TerminalExtractor,
+    file: Option<String>,
+    /// Diagnostics on the previous frame, to emit only newly shown ones
+    shown: HashSet<(String, String)>,
+}
+
+impl IdeExtractor {
+    /// Extractor for `config`, reading the integrated terminal with `terminal`
+    pub fn new(config: IdeConfig, terminal: TerminalConfig) -> Result<Self> {
+        if !(0.0..0.5).contains(&config.tab_bar_ratio) || !(0.0..1.0).contains(&config.panel_ratio) {
+            return Err(IndexerError::Config(
+                "ide tab_bar_ratio must be below 0.5 and panel_ratio below 1.0".to_string(),
+            ));
+        }
+        if config.correlation_window_secs == 0 {
+            return Err(IndexerError::Config("ide correlation_window_secs must be positive".to_string()));
+        }
+        let invalid = |e: regex::Error| IndexerError::Config(format!("Invalid IDE pattern: {}", e));
+        let title_separator = Regex::new(r"\s+[—–-]\s+").map_err(invalid)?;
+        let file_name = Regex::new(r"[\w@+-][\w@+.-]*\.[A-Za-z][A-Za-z0-9]{0,9}\b").map_err(invalid)?;
+        // `error[E0308]: mismatched types`, `main.c:12:5: error: expected ';'`,
+        // `src/app.ts(12,5): error TS2304: Cannot find name 'x'`
+        let diagnostic = Regex::new(
+            r"(?i)^(?:(?P<file>[\w./\\-]+\.[A-Za-z0-9]{1,6})[:(](?P<line>\d+)(?:[:,]\s*\d+)?\)?:?\s*)?(?P<sev>error|warning)(?:\s*\[?(?P<code>[A-Z]{0,3}\d{2,5})\]?)?\s*:\s*(?P<msg>.{3,})$",
+        )
+        .map_err(invalid)?;
+        // Problems panel entries: `Cannot find name 'x'. ts(2304) [Ln 12, Col 5]`
+        let source_diagnostic = Regex::new(
+            r"^(?P<msg>.{3,}?)\s+(?P<code>(?:ts|rustc|eslint|pylint|Pylance|pyright|mypy)\([\w-]+\))(?:\s*\[Ln\s*(?P<line>\d+)[^\]]*\])?$",
+        )
+        .map_err(invalid)?;
+        // rustc's `--> src/main.rs:12:5` under the message
+        let location = Regex::new(r"^-->\s*(?P<file>\S+?):(?P<line>\d+)").map_err(invalid)?;
+        Ok(Self {
+            config,
+            title_separator,
+            file_name,
+            diagnostic,
+            source_diagnostic,
+            location,
+            terminal: TerminalExtractor::new(terminal)?,
+            file: None,
+            shown: HashSet::new(),
+        })
+    }
+
+    /// Read the open file and diagnostics from a keyframe
+    pub fn parse(&self, frame: &FrameMetadata, ocr_results: &[OCRResult]) -> IdeScreen {
+        let height = frame.height as f32;
+        let center = |r: &&OCRResult| r.roi.y + r.roi.height / 2.0;
+        let tab_bar: Vec<OCRResult> =
+            ocr_results.iter().filter(|r| center(r) < height * self.config.tab_bar_ratio).cloned().collect();
+        let panel: Vec<OCRResult> =
+            ocr_results.iter().filter(|r| center(r) > height * (1.0 - self.config.panel_ratio)).cloned().collect();
+
+        let (title_file, project, dirty) = self.title_file(&frame.win_title);
+        let file = title_file.or_else(|| self.tab_file(&screen_lines(&tab_bar)));
+        let language = file.as_deref().and_then(language_for).map(str::to_string);
+        let diagnostics = self.diagnostics(&screen_lines(&panel));
+        IdeScreen { file, language, project, dirty, diagnostics }
+    }
+
+    /// File, project and unsaved marker from a title such as
+    /// `● main.rs — keyframe-indexer — Visual Studio Code` or `indexer – main.rs`
+    fn title_file(&self, title: &str) -> (Option<String>, Option<String>, bool) {
+        let mut file = None;
+        let mut project = None;
+        let mut dirty = false;
+        for part in self.title_separator.split(title.trim()).map(str::trim).filter(|p| !p.is_empty()) {
+            let name = part.trim_start_matches(['●', '•']).trim();
+            // JetBrains and VS Code may follow the name with a path or `(Working Tree)`
+            let token = name.split_whitespace().next().unwrap_or_default();
+            let bare = token.trim_end_matches('*');
+            let is_file = self.file_name.find(bare).map_or(false, |m| m.start() == 0 && m.end() == bare.len());
+            if is_file && file.is_none() {
+                dirty = name.len() != part.len() || bare.len() != token.len();
+                file = Some(bare.to_string());
+            } else if !is_file && project.is_none() && !self.is_app_name(part) {
+                project = Some(part.to_string());
+            }
+        }
+        (file, project, dirty)
+    }
+
+    /// Active file from the tab bar: the last file in a breadcrumb path, or the only tab
+    fn tab_file(&self, lines: &[String]) -> Option<String> {
+        let breadcrumb = lines
+            .iter()
+            .filter(|line| line.contains('>') || line.contains('›'))
+            .find_map(|line| self.file_name.find_iter(line).last().map(|m| m.as_str().to_string()));
+        breadcrumb.or_else(|| {
+            let tabs: HashSet<&str> = lines.iter().flat_map(|line| self.file_name.find_iter(line)).map(|m| m.as_str()).collect();
+            (tabs.len() == 1).then(|| tabs.into_iter().next().unwrap_or_default().to_string())
+        })
+    }
+
+    fn diagnostics(&self, lines: &[String]) -> Vec<CodeDiagnostic> {
+        let mut diagnostics: Vec<CodeDiagnostic> = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if let Some(c) = self.diagnostic.captures(line) {
+                diagnostics.push(CodeDiagnostic {
+                    severity: c["sev"].to_lowercase(),
+                    message: c["msg"].trim().to_string(),
+                    code: c.name("code").map(|m| m.as_str().to_string()),
+                    file: c.name("file").map(|m| m.as_str().to_string()),
+                    line: c.name("line").and_then(|m| m.as_str().parse().ok()),
+                });
+            } else if let Some(c) = self.source_diagnostic.captures(line) {
+                diagnostics.push(CodeDiagnostic {
+                    severity: "error".to_string(),
+                    message: c["msg"].trim().to_string(),
+                    code: Some(c["code"].to_string()),
+                    file: None,
+                    line: c.name("line").and_then(|m| m.as_str().parse().ok()),
+                });
+            } else if let Some(c) = self.location.captures(line) {
+                if let Some(last) = diagnostics.last_mut().filter(|d| d.file.is_none()) {
+                    last.file = Some(c["file"].to_string());
+                    last.line = c["line"].parse().ok();
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn is_app_name(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.config.app_names.iter().any(|name| text.contains(&name.to_lowercase()))
+    }
+
+    fn event(
+        &self,
+        frame: &AppFrame<'_>,
+        event_type: EventType,
+        target: &str,
+        value_from: Option<String>,
+        value_to: Option<String>,
+        screen: &IdeScreen,
+    ) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("app_extractor".to_string(), "ide".to_string());
+        metadata.insert("ide".to_string(), frame.metadata.app_name.clone());
+        if let Some(language) = &screen.language {
+            metadata.insert("language".to_string(), language.clone());
+        }
+        if let Some(project) = &screen.project {
+            metadata.insert("project".to_string(), project.clone());
+        }
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: frame.timestamp,
+            event_type,
+            target: target.to_string(),
+            value_from,
+            value_to,
+            confidence: 0.85,
+            evidence_frames: vec![frame.metadata.path.clone()],
+            metadata,
+        }
+    }
+}
+
+impl AppExtractor for IdeExtractor {
+    fn name(&self) -> &str {
+        "ide"
+    }
+
+    fn handles(&self, frame: &FrameMetadata) -> bool {
+        frame.screen_type == Some(ScreenType::Ide) || self.is_app_name(&frame.app_name)
+    }
+
+    fn extract(&mut self, frame: &AppFrame<'_>, events: &mut Vec<DetectedEvent>) -> Result<()> {
+        let screen = self.parse(frame.metadata, frame.ocr_results);
+        let height = frame.metadata.height as f32;
+        let editor = height * self.config.tab_bar_ratio..height * (1.0 - self.config.panel_ratio);
+
+        // Text changes in the editor area are edits to the open file
+        if let Some(file) = &screen.file {
+            let in_editor = |e: &DetectedEvent| {
+                BoundingBox::from_metadata(&e.metadata).map_or(false, |r| editor.contains(&(r.y + r.height / 2.0)))
+            };
+            for event in events.iter_mut().filter(|e| is_edit(e) && in_editor(e)) {
+                event.metadata.insert("ide_file".to_string(), file.clone());
+                if let Some(language) = &screen.language {
+                    event.metadata.insert("language".to_string(), language.clone());
+                }
+            }
+        }
+
+        let mut typed = Vec::new();
+        if screen.file.is_some() && screen.file != self.file {
+            let file = screen.file.clone().unwrap_or_default();
+            let mut event = self.event(frame, EventType::FileOpened, &file, self.file.clone(), Some(file.clone()), &screen);
+            event.metadata.insert("unsaved".to_string(), screen.dirty.to_string());
+            typed.push(event);
+            self.file = screen.file.clone();
+        }
+
+        let shown: HashSet<(String, String)> =
+            screen.diagnostics.iter().map(|d| (d.severity.clone(), d.message.clone())).collect();
+        for diagnostic in &screen.diagnostics {
+            if self.shown.contains(&(diagnostic.severity.clone(), diagnostic.message.clone())) {
+                continue;
+            }
+            let file = diagnostic.file.clone().or_else(|| screen.file.clone()).unwrap_or_default();
+            let mut event =
+                self.event(frame, EventType::CodeDiagnostic, &file, None, Some(diagnostic.message.clone()), &screen);
+            event.metadata.insert("severity".to_string(), diagnostic.severity.clone());
+            if let Some(code) = &diagnostic.code {
+                event.metadata.insert("diagnostic_code".to_string(), code.clone());
+            }
+            if let Some(line) = diagnostic.line {
+                event.metadata.insert("line".to_string(), line.to_string());
+            }
+            typed.push(event);
+        }
+        self.shown = shown;
+
+        let panel: Vec<OCRResult> = frame
+            .ocr_results
+            .iter()
+            .filter(|r| r.roi.y + r.roi.height / 2.0 >= editor.end)
+            .cloned()
+            .collect();
+        self.terminal.extract(&AppFrame { metadata: frame.metadata, timestamp: frame.timestamp, ocr_results: &panel }, events)?;
+
+        events.extend(typed);
+        Ok(())
+    }
+}
+
+fn is_edit(event: &DetectedEvent) -> bool {
+    matches!(event.event_type, EventType::FieldChange | EventType::DataEntry | EventType::ClipboardPaste)
+}
+
+/// Language of a source file, from its extension
+pub fn language_for(file: &str) -> Option<&'static str> {
+    let extension = file.rsplit_once('.')?.1.to_lowercase();
+    let language = match extension.as_str() {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "ts" | "tsx" | "mts" | "cts" => "typescript",
+        "js" | "jsx" | "mjs" | "cjs" => "javascript",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "c++",
+        "m" | "mm" => "objective-c",
+        "cs" => "c#",
+        "rb" => "ruby",
+        "php" => "php",
+        "scala" => "scala",
+        "sh" | "bash" | "zsh" => "shell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" | "scss" => "css",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "md" => "markdown",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Whether a command builds or tests code: `build`, `test` or neither
+pub fn build_kind(command: &str) -> Option<&'static str> {
+    const TEST_WORDS: &[&str] = &["test", "tests", "pytest", "jest", "vitest", "rspec", "nextest", "ctest", "phpunit", "mocha"];
+    const BUILD_TOOLS: &[&str] = &[
+        "cargo", "make", "cmake", "ninja", "gcc", "g++", "clang", "clang++", "tsc", "mvn", "gradle", "gradlew",
+        "xcodebuild", "javac", "bazel", "webpack",
+    ];
+    // These also run other things, so only count with an explicit `build`
+    const RUNNERS: &[&str] = &["npm", "yarn", "pnpm", "go", "swift", "dotnet"];
+
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let program = words.first()?.rsplit('/').next().unwrap_or_default();
+    if words.iter().any(|w| TEST_WORDS.contains(w) || w.starts_with("test:")) {
+        Some("test")
+    } else if BUILD_TOOLS.contains(&program) || (RUNNERS.contains(&program) && words.iter().any(|w| w.starts_with("build"))) {
+        Some("build")
+    } else {
+        None
+    }
+}
+
+/// A code edit failed builds may be tied to
+#[derive(Debug, Clone)]
+struct CodeEdit {
+    timestamp: DateTime<Utc>,
+    file: String,
+    language: Option<String>,
+    evidence_frames: Vec<String>,
+}
+
+/// Ties each failed build or test run to the code edited in the window before it.
+///
+/// Failures come from `CommandExecuted` events with a `failure` exit status,
+/// including commands first reported as running; edits are field changes the
+/// IDE extractor tagged with `ide_file`. Edits are kept from one batch of
+/// events to the next, so a build fails in a later segment than the edits it
+/// follows. Failures with no edits in the window are left alone.
+#[derive(Debug, Default)]
+pub struct BuildFailureCorrelator {
+    /// Edits within the window of the latest event seen, oldest first
+    edits: Vec<CodeEdit>,
+}
+
+impl BuildFailureCorrelator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `BuildFailure` events for the failed builds in `events`, whose edits are
+    /// kept for later failures
+    pub fn correlate(&mut self, events: &[DetectedEvent], window: Duration) -> Vec<DetectedEvent> {
+        self.edits.extend(events.iter().filter(|e| is_edit(e)).filter_map(|e| {
+            Some(CodeEdit {
+                timestamp: e.timestamp,
+                file: e.metadata.get("ide_file")?.clone(),
+                language: e.metadata.get("language").cloned(),
+                evidence_frames: e.evidence_frames.clone(),
+            })
+        }));
+        self.edits.sort_by_key(|e| e.timestamp);
+
+        let failures: Vec<DetectedEvent> = events
+            .iter()
+            .filter(|e| {
+                e.event_type == EventType::CommandExecuted
+                    && e.metadata.get("exit_status").map(String::as_str) == Some("failure")
+            })
+            .filter_map(|failure| self.build_failure(failure, window))
+            .collect();
+
+        if let Some(latest) = events.iter().map(|e| e.timestamp).max() {
+            self.edits.retain(|e| latest - e.timestamp <= window);
+        }
+        failures
+    }
+
+    fn build_failure(&self, failure: &DetectedEvent, window: Duration) -> Option<DetectedEvent> {
+        let kind = build_kind(&failure.target)?;
+        let recent: Vec<&CodeEdit> = self
+            .edits
+            .iter()
+            .filter(|e| e.timestamp <= failure.timestamp && failure.timestamp - e.timestamp <= window)
+            .collect();
+        let last = *recent.last()?;
+        let mut files: Vec<&str> = Vec::new();
+        for edit in recent.iter().rev() {
+            if !files.contains(&edit.file.as_str()) {
+                files.push(&edit.file);
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("build_kind".to_string(), kind.to_string());
+        metadata.insert("related_file".to_string(), files[0].to_string());
+        metadata.insert("related_files".to_string(), files.join(","));
+        metadata.insert("edit_count".to_string(), recent.len().to_string());
+        metadata.insert(
+            "seconds_since_edit".to_string(),
+            (failure.timestamp - last.timestamp).num_seconds().to_string(),
+        );
+        metadata.insert("command_event_id".to_string(), failure.id.clone());
+        for key in ["exit_code", "working_directory"] {
+            if let Some(value) = failure.metadata.get(key) {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
+        if let Some(language) = &last.language {
+            metadata.insert("language".to_string(), language.clone());
+        }
+
+        let mut evidence_frames = failure.evidence_frames.clone();
+        evidence_frames.extend(last.evidence_frames.iter().filter(|f| !failure.evidence_frames.contains(f)).cloned());
+        Some(DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: failure.timestamp,
+            event_type: EventType::BuildFailure,
+            target: failure.target.clone(),
+            value_from: Some(files[0].to_string()),
+            value_to: None,
+            confidence: failure.confidence.min(0.8),
+            evidence_frames,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn ocr(text: &str, y: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame_1.png".to_string(),
+            roi: BoundingBox::new(20.0, y, 800.0, 18.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    fn code_edit(result: &OCRResult) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        result.roi.insert_metadata(&mut metadata);
+        DetectedEvent {
+            id: "edit".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap(),
+            event_type: EventType::FieldChange,
+            target: "field_generic".to_string(),
+            value_from: Some("let x = 1".to_string()),
+            value_to: Some(result.text.clone()),
+            confidence: 0.8,
+            evidence_frames: vec!["frame_1.png".to_string()],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_ide_file_diagnostics_and_build_failure() {
+        let frame = FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "frame_1.png".to_string(),
+            phash16: 0,
+            entropy: 5.0,
+            app_name: "Code".to_string(),
+            win_title: "● parser.rs — keyframe-indexer — Visual Studio Code".to_string(),
+            width: 1600,
+            height: 1000,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: Some(ScreenType::Ide),
+        };
+        let edited = ocr("let x: u32 = \"one\";", 300.0);
+        let ocr_results = vec![
+            ocr("main.rs parser.rs", 30.0),
+            edited.clone(),
+            ocr("error[E0308]: mismatched types", 700.0),
+            ocr("--> src/parser.rs:12:18", 720.0),
+            ocr("dev@box:~/src/keyframe-indexer$ cargo build", 800.0),
+            ocr("error: could not compile `keyframe-indexer`", 820.0),
+            ocr("dev@box:~/src/keyframe-indexer$", 840.0),
+        ];
+
+        let mut extractor = IdeExtractor::new(IdeConfig::default(), TerminalConfig::default()).unwrap();
+        assert!(extractor.handles(&frame));
+        let screen = extractor.parse(&frame, &ocr_results);
+        assert_eq!(screen.file.as_deref(), Some("parser.rs"));
+        assert_eq!(screen.language.as_deref(), Some("rust"));
+        assert_eq!(screen.project.as_deref(), Some("keyframe-indexer"));
+        assert!(screen.dirty);
+        assert_eq!(screen.diagnostics[0].code.as_deref(), Some("E0308"));
+        assert_eq!(screen.diagnostics[0].file.as_deref(), Some("src/parser.rs"));
+        assert_eq!(screen.diagnostics[0].line, Some(12));
+
+        let timestamp = Utc.with_ymd_and_hms(2024, 3, 5, 9, 1, 0).unwrap();
+        let app_frame = AppFrame { metadata: &frame, timestamp, ocr_results: &ocr_results };
+        let mut events = vec![code_edit(&edited)];
+        extractor.extract(&app_frame, &mut events).unwrap();
+        assert_eq!(events[0].metadata["ide_file"], "parser.rs");
+        let types: Vec<&EventType> = events.iter().map(|e| &e.event_type).collect();
+        assert!(types.contains(&&EventType::FileOpened));
+        assert!(types.contains(&&EventType::CodeDiagnostic));
+        let build = events.iter().find(|e| e.event_type == EventType::CommandExecuted).unwrap();
+        assert_eq!(build.metadata["exit_status"], "failure");
+
+        let failures = BuildFailureCorrelator::new().correlate(&events, Duration::minutes(10));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].event_type, EventType::BuildFailure);
+        assert_eq!(failures[0].metadata["build_kind"], "build");
+        assert_eq!(failures[0].metadata["related_file"], "parser.rs");
+        assert_eq!(failures[0].metadata["seconds_since_edit"], "60");
+
+        assert_eq!(build_kind("npm run test:unit"), Some("test"));
+        assert_eq!(build_kind("npm install"), None);
+    }
+
+    #[test]
+    fn test_build_failures_follow_edits_across_segments() {
+        let window = Duration::minutes(10);
+        let mut correlator = BuildFailureCorrelator::new();
+        let mut edit = code_edit(&ocr("fn parse() {}", 300.0));
+        edit.metadata.insert("ide_file".to_string(), "parser.rs".to_string());
+        assert!(correlator.correlate(&[edit.clone()], window).is_empty());
+
+        // The test run outlasts the segment and is reported failed in the next one
+        let mut metadata = HashMap::new();
+        metadata.insert("exit_status".to_string(), "failure".to_string());
+        metadata.insert("completes_event_id".to_string(), "started".to_string());
+        let failed = DetectedEvent {
+            id: "finished".to_string(),
+            timestamp: edit.timestamp + Duration::minutes(5),
+            event_type: EventType::CommandExecuted,
+            target: "cargo test".to_string(),
+            value_from: None,
+            value_to: Some("cargo test".to_string()),
+            confidence: 0.85,
+            evidence_frames: vec!["frame_9.png".to_string()],
+            metadata,
+        };
+        let failures = correlator.correlate(&[failed.clone()], window);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].metadata["build_kind"], "test");
+        assert_eq!(failures[0].metadata["seconds_since_edit"], "300");
+
+        // Edits older than the window are forgotten
+        let later = DetectedEvent { timestamp: edit.timestamp + Duration::minutes(20), ..failed };
+        assert!(correlator.correlate(&[later], window).is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::config::IndexerConfig;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventDetectionConfig, EventDetector};
+use crate::incremental_ocr::{IncrementalOcrConfig, IncrementalOcrScheduler, OcrRegionEngine};
+use crate::markdown_exporter::{EntityExtractor, ExtractedEntities};
+use crate::metadata_collector::MetadataCollector;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use crate::privacy_filter::PrivacyFilter;
+use crate::scene_detector::{FramePyramid, SceneDetector};
+use chrono::{DateTime, Utc};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Where the OCR text of an analyzed image came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrSource {
+    /// Results supplied by the caller, e.g. exported by the companion app
+    Provided,
+    /// Recognized by the configured OCR engine
+    Engine,
+    /// No OCR available; text-based detectors had nothing to work with
+    None,
+}
+
+/// Result of running the analysis stack on a single image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageAnalysis {
+    pub path: String,
+    pub frame_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub width: u32,
+    pub height: u32,
+    pub phash: u64,
+    pub entropy: f32,
+    pub ocr_source: OcrSource,
+    pub ocr_results: Vec<OCRResult>,
+    pub events: Vec<DetectedEvent>,
+    pub entities: ExtractedEntities,
+    /// Blocked by the privacy filter: nothing but the path, size and time is kept
+    pub redacted: bool,
+}
+
+/// Incremental OCR of consecutive screenshots in the same directory
+struct IncrementalState {
+    scheduler: IncrementalOcrScheduler,
+    /// Last screenshot of each directory, to find the regions that changed since
+    previous: HashMap<String, FramePyramid>,
+}
+
+/// One-shot analysis of a screenshot, without a video segment around it
+pub struct ImageAnalyzer {
+    scene_detector: SceneDetector,
+    entity_extractor: EntityExtractor,
+    ocr_engine: Option<Box<dyn OcrRegionEngine + Send + Sync>>,
+    event_detection: EventDetectionConfig,
+    incremental_ocr: IncrementalOcrConfig,
+    incremental: Mutex<Option<IncrementalState>>,
+    privacy: PrivacyFilter,
+}
+
+impl ImageAnalyzer {
+    pub fn new(config: &IndexerConfig) -> Result<Self> {
+        Ok(Self {
+            scene_detector: SceneDetector::new(config.scene_detection.clone())?,
+            entity_extractor: EntityExtractor::new(&config.markdown_export.ticket_pattern)?,
+            ocr_engine: None,
+            event_detection: config.event_detection_config(),
+            incremental_ocr: config.incremental_ocr.clone(),
+            incremental: Mutex::new(None),
+            privacy: PrivacyFilter::new(config.privacy.clone())?,
+        })
+    }
+
+    /// Recognize text with `engine` when no OCR results are supplied; with
+    /// `incremental_ocr` enabled only the regions that changed since the
+    /// previous screenshot of the same directory are read again
+    pub fn with_ocr_engine(mut self, engine: Box<dyn OcrRegionEngine + Send + Sync>) -> Self {
+        self.ocr_engine = Some(engine);
+        self.incremental = Mutex::new(self.incremental_ocr.enabled.then(|| IncrementalState {
+            scheduler: IncrementalOcrScheduler::new(self.incremental_ocr.clone()),
+            previous: HashMap::new(),
+        }));
+        self
+    }
+
+    /// Analyze the image at `path`; `provided_ocr` takes precedence over the OCR engine
+    pub async fn analyze(&self, path: &str, provided_ocr: Option<Vec<OCRResult>>) -> Result<ImageAnalysis> {
+        let image = image::open(path)
+            .map_err(|e| IndexerError::Metadata(format!("Failed to load image {}: {}", path, e)))?;
+        let (width, height) = (image.width(), image.height());
+
+        let frame_id = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string());
+        // The capture time of a screenshot is best approximated by its modification time
+        let timestamp = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        // Blocked apps and sites are recognized from the top of the image alone,
+        // before its full text is read or any events are detected
+        if self.is_blocked(&frame_id, &image, provided_ocr.as_deref())? {
+            debug!("Skipping {}: blocked by the privacy filter", path);
+            return Ok(ImageAnalysis {
+                path: path.to_string(),
+                frame_id,
+                timestamp,
+                width,
+                height,
+                phash: 0,
+                entropy: 0.0,
+                ocr_source: OcrSource::None,
+                ocr_results: Vec::new(),
+                events: Vec::new(),
+                entities: ExtractedEntities::default(),
+                redacted: true,
+            });
+        }
+
+        let phash = self.scene_detector.calculate_phash(&image)?;
+        let entropy = MetadataCollector::new()?.calculate_image_entropy(path).await?;
+
+        let (ocr_source, ocr_results) = match (provided_ocr, &self.ocr_engine) {
+            (Some(results), _) => (OcrSource::Provided, results),
+            (None, Some(engine)) => (OcrSource::Engine, self.recognize(engine.as_ref(), path, &frame_id, &image)?),
+            (None, None) => (OcrSource::None, Vec::new()),
+        };
+        debug!("Analyzing {} with {} OCR results ({:?})", path, ocr_results.len(), ocr_source);
+
+        // A fresh detector has no previous frame, so only standalone, error and modal events fire
+        let mut event_detector = EventDetector::with_config(self.event_detection.clone())?;
+        let mut events = event_detector.analyze_frame(&frame_id, &ocr_results, timestamp, width as f32, height as f32)?;
+        events.extend(event_detector.finish());
+
+        let mut entities = ExtractedEntities::default();
+        for result in &ocr_results {
+            self.entity_extractor.extract(&result.text, &mut entities);
+        }
+
+        Ok(ImageAnalysis {
+            path: path.to_string(),
+            frame_id,
+            timestamp,
+            width,
+            height,
+            phash,
+            entropy,
+            ocr_source,
+            ocr_results,
+            events,
+            entities,
+            redacted: false,
+        })
+    }
+
+    /// Whether the privacy filter blocks a screenshot, judged by the text of
+    /// its menu bar and address bar
+    fn is_blocked(&self, frame_id: &str, image: &DynamicImage, provided_ocr: Option<&[OCRResult]>) -> Result<bool> {
+        if !self.privacy.is_enabled() {
+            return Ok(false);
+        }
+        let header = match (provided_ocr, &self.ocr_engine) {
+            (Some(results), _) => results.to_vec(),
+            (None, Some(engine)) => {
+                let region = BoundingBox::new(0.0, 0.0, image.width() as f32, self.privacy.header_height(image.height()));
+                engine.recognize_region(frame_id, image, &region)?
+            }
+            (None, None) => Vec::new(),
+        };
+        Ok(self.privacy.check_screenshot(&header, image.height()).is_some())
+    }
+
+    /// Run the OCR engine on a screenshot, incrementally when enabled
+    fn recognize(&self, engine: &dyn OcrRegionEngine, path: &str, frame_id: &str, image: &DynamicImage) -> Result<Vec<OCRResult>> {
+        let mut incremental = self
+            .incremental
+            .lock()
+            .map_err(|_| IndexerError::ocr("Incremental OCR state lock poisoned"))?;
+        let Some(state) = incremental.as_mut() else {
+            let full_frame = BoundingBox::new(0.0, 0.0, image.width() as f32, image.height() as f32);
+            return engine.recognize_region(frame_id, image, &full_frame);
+        };
+
+        let stream_id = Path::new(path).parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        let pyramid = self.scene_detector.pyramid(image);
+        let change = state
+            .previous
+            .get(&stream_id)
+            .and_then(|previous| self.scene_detector.compare_frames(1, 0, previous, &pyramid));
+        let results = state.scheduler.process_frame(engine, &stream_id, frame_id, image, change.as_ref());
+        state.previous.insert(stream_id, pyramid);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, Rgb, RgbImage};
+    use tempfile::TempDir;
+
+    struct FixedOcr;
+
+    impl OcrRegionEngine for FixedOcr {
+        fn recognize_region(&self, frame_id: &str, _image: &DynamicImage, region: &BoundingBox) -> Result<Vec<OCRResult>> {
+            Ok(vec![OCRResult {
+                frame_id: frame_id.to_string(),
+                roi: BoundingBox::new(region.width / 4.0, region.height / 4.0, region.width / 2.0, 30.0),
+                text: "Error: upload failed, see https://status.example.com for OPS-17".to_string(),
+                language: "en-US".to_string(),
+                confidence: 0.95,
+                processed_at: Utc::now(),
+                processor: "fixed".to_string(),
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_single_image() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("screenshot.png");
+        RgbImage::from_fn(320, 200, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, 128]))
+            .save(&path)
+            .unwrap();
+        let path = path.to_str().unwrap();
+
+        let analyzer = ImageAnalyzer::new(&IndexerConfig::default()).unwrap();
+        let without_ocr = analyzer.analyze(path, None).await.unwrap();
+        assert_eq!(without_ocr.ocr_source, OcrSource::None);
+        assert_eq!((without_ocr.width, without_ocr.height), (320, 200));
+        assert_eq!(without_ocr.frame_id, "screenshot");
+        assert!(without_ocr.events.is_empty());
+
+        let analyzer = analyzer.with_ocr_engine(Box::new(FixedOcr));
+        let analysis = analyzer.analyze(path, None).await.unwrap();
+        assert_eq!(analysis.ocr_source, OcrSource::Engine);
+        assert!(analysis.events.iter().any(|e| e.event_type == crate::event_detector::EventType::ErrorDisplay));
+        assert!(analysis.entities.urls.contains("https://status.example.com"));
+        assert!(analysis.entities.tickets.contains("OPS-17"));
+        assert_eq!(without_ocr.phash, analysis.phash);
+        assert!(!analysis.redacted);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_app_is_skipped_before_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("vault.png");
+        RgbImage::from_pixel(400, 300, Rgb([240, 240, 240])).save(&path).unwrap();
+        let path = path.to_str().unwrap();
+
+        let text = |text: &str, x: f32, y: f32| OCRResult {
+            frame_id: "vault".to_string(),
+            roi: BoundingBox::new(x, y, 80.0, 10.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        };
+        // The first menu bar item names the frontmost app
+        let ocr = vec![
+            text("File", 120.0, 1.0),
+            text("1Password", 30.0, 1.0),
+            text("Error: wrong master password", 100.0, 150.0),
+        ];
+
+        let analyzer = ImageAnalyzer::new(&IndexerConfig::default()).unwrap();
+        let analysis = analyzer.analyze(path, Some(ocr)).await.unwrap();
+        assert!(analysis.redacted);
+        assert!(analysis.events.is_empty());
+        assert!(analysis.ocr_results.is_empty());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::error::Result;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use crate::ocr_density::OcrDensity;
+use crate::scene_detector::{SceneChange, SceneChangeType};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Recognizes text within a region of a frame.
+///
+/// Implemented by the OCR backends (Vision bridge, Tesseract, ...). Returned
+/// bounding boxes are in frame pixel coordinates.
+pub trait OcrRegionEngine {
+    fn recognize_region(&self, frame_id: &str, image: &DynamicImage, region: &BoundingBox) -> Result<Vec<OCRResult>>;
+}
+
+/// Configuration for incremental OCR scheduling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncrementalOcrConfig {
+    /// Re-OCR only changed regions and reuse cached text elsewhere
+    pub enabled: bool,
+    /// Pixels added around each changed region so text at tile edges is not cut
+    pub region_padding: u32,
+    /// Fraction of the frame above which a full pass is cheaper than regions
+    pub max_changed_area_ratio: f32,
+    /// Force a full pass after this many incremental frames to correct drift
+    pub full_refresh_interval: u32,
+    /// Text coverage percentage below which a stream counts as text-sparse
+    pub sparse_text_coverage: f32,
+    /// Text-sparse streams stretch the full refresh interval by this factor
+    pub sparse_refresh_multiplier: u32,
+}
+
+impl Default for IncrementalOcrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            region_padding: 8,
+            max_changed_area_ratio: 0.5,
+            full_refresh_interval: 30,
+            sparse_text_coverage: 1.0,
+            sparse_refresh_multiplier: 4,
+        }
+    }
+}
+
+/// What needs to be OCRed for a frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum OcrPlan {
+    /// OCR the whole frame
+    Full,
+    /// OCR only these regions and reuse cached text outside them
+    Regions(Vec<BoundingBox>),
+    /// The frame is known to be identical to the last one; reuse the cached text
+    Reuse,
+}
+
+/// Counters for OCR work done and avoided
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncrementalOcrStats {
+    pub full_passes: u64,
+    pub incremental_passes: u64,
+    pub reused_passes: u64,
+    pub pixels_ocred: u64,
+    pub pixels_total: u64,
+}
+
+impl IncrementalOcrStats {
+    /// Fraction of frame pixels that did not need OCR
+    pub fn pixels_saved_ratio(&self) -> f32 {
+        if self.pixels_total == 0 {
+            return 0.0;
+        }
+        1.0 - self.pixels_ocred as f32 / self.pixels_total as f32
+    }
+}
+
+/// Last OCR text of a capture stream
+#[derive(Debug, Clone)]
+struct CachedFrameText {
+    width: u32,
+    height: u32,
+    results: Vec<OCRResult>,
+    density: OcrDensity,
+    frames_since_full: u32,
+}
+
+/// Schedules OCR per capture stream from scene-change regions.
+///
+/// Each stream (usually a monitor) keeps the text of its last OCRed frame.
+/// Regions reported by scene detection are re-OCRed; cached results that
+/// overlap a changed region are dropped and everything else is carried over.
+pub struct IncrementalOcrScheduler {
+    config: IncrementalOcrConfig,
+    cache: HashMap<String, CachedFrameText>,
+    stats: IncrementalOcrStats,
+}
+
+impl IncrementalOcrScheduler {
+    pub fn new(config: IncrementalOcrConfig) -> Self {
+        Self {
+            config,
+            cache: HashMap::new(),
+            stats: IncrementalOcrStats::default(),
+        }
+    }
+
+    /// Decide what to OCR for the next frame of a stream.
+    ///
+    /// `scene_change` is the change reported for this frame. Without one the
+    /// whole frame is read: changes below the scene thresholds, such as a few
+    /// typed characters, still change the text.
+    pub fn plan(&self, stream_id: &str, width: u32, height: u32, scene_change: Option<&SceneChange>) -> OcrPlan {
+        if !self.config.enabled {
+            return OcrPlan::Full;
+        }
+
+        let Some(cached) = self.cache.get(stream_id) else {
+            return OcrPlan::Full;
+        };
+
+        if cached.width != width || cached.height != height {
+            return OcrPlan::Full;
+        }
+
+        // Drift matters little on screens with hardly any text, so shed those refreshes
+        let refresh_interval = if cached.density.text_coverage < self.config.sparse_text_coverage {
+            self.config.full_refresh_interval.saturating_mul(self.config.sparse_refresh_multiplier.max(1))
+        } else {
+            self.config.full_refresh_interval
+        };
+        if cached.frames_since_full >= refresh_interval {
+            return OcrPlan::Full;
+        }
+
+        let Some(change) = scene_change else {
+            return OcrPlan::Full;
+        };
+
+        // Cuts replace the whole screen; a capped region list leaves changed tiles out,
+        // and their cached text would be kept
+        if matches!(change.change_type, SceneChangeType::Cut) || change.changed_regions.is_empty() || change.regions_capped {
+            return OcrPlan::Full;
+        }
+
+        let regions = self.expand_regions(change, cached, width, height);
+        let changed_area: f32 = regions.iter().map(|r| r.area()).sum();
+        if changed_area / (width as f32 * height as f32) > self.config.max_changed_area_ratio {
+            return OcrPlan::Full;
+        }
+
+        OcrPlan::Regions(regions)
+    }
+
+    /// Plan, run the engine and merge with cached text for one frame
+    /// that scene detection has compared with the stream's previous one
+    pub fn process_frame(
+        &mut self,
+        engine: &dyn OcrRegionEngine,
+        stream_id: &str,
+        frame_id: &str,
+        image: &DynamicImage,
+        scene_change: Option<&SceneChange>,
+    ) -> Result<Vec<OCRResult>> {
+        let (width, height) = (image.width(), image.height());
+        let plan = self.plan(stream_id, width, height, scene_change);
+        debug!("OCR plan for frame {} on stream {}: {:?}", frame_id, stream_id, plan);
+
+        let fresh = match &plan {
+            OcrPlan::Full => engine.recognize_region(frame_id, image, &BoundingBox::new(0.0, 0.0, width as f32, height as f32))?,
+            OcrPlan::Regions(regions) => {
+                let mut results = Vec::new();
+                for region in regions {
+                    results.extend(engine.recognize_region(frame_id, image, region)?);
+                }
+                results
+            }
+            OcrPlan::Reuse => Vec::new(),
+        };
+
+        Ok(self.merge(stream_id, frame_id, width, height, &plan, fresh))
+    }
+
+    /// Combine freshly OCRed results with cached text outside the changed regions
+    pub fn merge(
+        &mut self,
+        stream_id: &str,
+        frame_id: &str,
+        width: u32,
+        height: u32,
+        plan: &OcrPlan,
+        fresh: Vec<OCRResult>,
+    ) -> Vec<OCRResult> {
+        let frame_pixels = width as u64 * height as u64;
+        self.stats.pixels_total += frame_pixels;
+
+        let (mut merged, frames_since_full) = match plan {
+            OcrPlan::Full => {
+                self.stats.full_passes += 1;
+                self.stats.pixels_ocred += frame_pixels;
+                (fresh, 0)
+            }
+            OcrPlan::Regions(regions) => {
+                self.stats.incremental_passes += 1;
+                self.stats.pixels_ocred += regions.iter().map(|r| r.area() as u64).sum::<u64>().min(frame_pixels);
+
+                let cached = self.cache.get(stream_id);
+                let mut merged: Vec<OCRResult> = cached
+                    .map(|c| c.results.iter()
+                        .filter(|r| !regions.iter().any(|region| overlaps(&r.roi, region)))
+                        .cloned()
+                        .collect())
+                    .unwrap_or_default();
+                merged.extend(fresh);
+                (merged, cached.map_or(0, |c| c.frames_since_full + 1))
+            }
+            OcrPlan::Reuse => {
+                self.stats.reused_passes += 1;
+                let cached = self.cache.get(stream_id);
+                (
+                    cached.map(|c| c.results.clone()).unwrap_or_default(),
+                    cached.map_or(0, |c| c.frames_since_full + 1),
+                )
+            }
+        };
+
+        for result in &mut merged {
+            result.frame_id = frame_id.to_string();
+        }
+        merged.sort_by(|a, b| a.roi.y.total_cmp(&b.roi.y).then(a.roi.x.total_cmp(&b.roi.x)));
+
+        self.cache.insert(stream_id.to_string(), CachedFrameText {
+            width,
+            height,
+            density: OcrDensity::from_results(&merged, width, height),
+            results: merged.clone(),
+            frames_since_full,
+        });
+
+        merged
+    }
+
+    /// Density of the last merged text of a stream
+    pub fn density(&self, stream_id: &str) -> Option<OcrDensity> {
+        self.cache.get(stream_id).map(|c| c.density)
+    }
+
+    /// Drop cached text, e.g. when a stream stops
+    pub fn invalidate(&mut self, stream_id: &str) {
+        self.cache.remove(stream_id);
+    }
+
+    pub fn get_stats(&self) -> &IncrementalOcrStats {
+        &self.stats
+    }
+
+    // MARK: - Private Helper Methods
+
+    /// Pad changed regions and grow them to cover any cached text they cut through
+    fn expand_regions(&self, change: &SceneChange, cached: &CachedFrameText, width: u32, height: u32) -> Vec<BoundingBox> {
+        let padding = self.config.region_padding as f32;
+        let mut regions: Vec<BoundingBox> = change
+            .changed_regions
+            .iter()
+            .map(|r| clip(
+                BoundingBox::new(r.x as f32 - padding, r.y as f32 - padding, r.width as f32 + 2.0 * padding, r.height as f32 + 2.0 * padding),
+                width,
+                height,
+            ))
+            .collect();
+
+        for region in &mut regions {
+            for result in cached.results.iter().filter(|r| overlaps(&r.roi, region)) {
+                *region = union(region, &result.roi);
+            }
+        }
+
+        merge_overlapping(regions)
+    }
+}
+
+/// Overlap with positive area; touching edges do not count
+fn overlaps(a: &BoundingBox, b: &BoundingBox) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn union(a: &BoundingBox, b: &BoundingBox) -> BoundingBox {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    BoundingBox::new(x, y, (a.x + a.width).max(b.x + b.width) - x, (a.y + a.height).max(b.y + b.height) - y)
+}
+
+fn clip(region: BoundingBox, width: u32, height: u32) -> BoundingBox {
+    let x = region.x.max(0.0);
+    let y = region.y.max(0.0);
+    let right = (region.x + region.width).min(width as f32);
+    let bottom = (region.y + region.height).min(height as f32);
+    BoundingBox::new(x, y, (right - x).max(0.0), (bottom - y).max(0.0))
+}
+
+/// Merge regions until none overlap, so no pixel is OCRed twice
+fn merge_overlapping(mut regions: Vec<BoundingBox>) -> Vec<BoundingBox> {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'outer: for i in 0..regions.len() {
+            for j in (i + 1)..regions.len() {
+                if overlaps(&regions[i], &regions[j]) {
+                    let other = regions.remove(j);
+                    regions[i] = union(&regions[i], &other);
+                    merged = true;
+                    break 'outer;
+                }
+            }
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HashAlgorithm;
+    use crate::scene_detector::{ChangedRegion, SceneThresholds};
+    use chrono::Utc;
+    use std::cell::RefCell;
+
+    /// Returns one result per requested region, labelled with a pass counter
+    struct FakeEngine {
+        calls: RefCell<Vec<BoundingBox>>,
+        label: &'static str,
+    }
+
+    impl OcrRegionEngine for FakeEngine {
+        fn recognize_region(&self, frame_id: &str, _image: &DynamicImage, region: &BoundingBox) -> Result<Vec<OCRResult>> {
+            self.calls.borrow_mut().push(region.clone());
+            // Simulate two text lines when the full frame is read
+            let lines = if region.width >= 400.0 {
+                vec![BoundingBox::new(10.0, 10.0, 200.0, 20.0), BoundingBox::new(10.0, 300.0, 200.0, 20.0)]
+            } else {
+                vec![BoundingBox::new(region.x + 1.0, region.y + 1.0, 20.0, 10.0)]
+            };
+            Ok(lines.into_iter().map(|roi| OCRResult {
+                frame_id: frame_id.to_string(),
+                roi,
+                text: self.label.to_string(),
+                language: "en".to_string(),
+                confidence: 0.9,
+                processed_at: Utc::now(),
+                processor: "fake".to_string(),
+            }).collect())
+        }
+    }
+
+    fn create_change(change_type: SceneChangeType, regions: Vec<(u32, u32, u32, u32)>) -> SceneChange {
+        SceneChange {
+            frame_index: 1,
+            timestamp_ns: 0,
+            change_type,
+            confidence: 0.5,
+            ssim_score: None,
+            phash_distance: None,
+            entropy_delta: None,
+            thresholds: SceneThresholds {
+                ssim_threshold: 0.8,
+                phash_distance_threshold: 10,
+                entropy_threshold: 0.1,
+                calibrated: false,
+            },
+            hash_algorithm: HashAlgorithm::Average,
+            changed_regions: regions.into_iter().map(|(x, y, width, height)| ChangedRegion {
+                grid_row: 0,
+                grid_col: 0,
+                x,
+                y,
+                width,
+                height,
+                change_score: 0.5,
+            }).collect(),
+            regions_capped: false,
+        }
+    }
+
+    #[test]
+    fn test_only_changed_regions_are_reocred() {
+        let config = IncrementalOcrConfig { enabled: true, region_padding: 0, ..Default::default() };
+        let mut scheduler = IncrementalOcrScheduler::new(config);
+        let image = DynamicImage::new_luma8(400, 400);
+
+        let first = FakeEngine { calls: RefCell::new(Vec::new()), label: "initial" };
+        let results = scheduler.process_frame(&first, "monitor_0", "frame_1", &image, None).unwrap();
+        assert_eq!(results.len(), 2);
+
+        // Only the bottom line changes
+        let second = FakeEngine { calls: RefCell::new(Vec::new()), label: "updated" };
+        let change = create_change(SceneChangeType::ContentChange, vec![(0, 290, 100, 50)]);
+        let results = scheduler.process_frame(&second, "monitor_0", "frame_2", &image, Some(&change)).unwrap();
+
+        let calls = second.calls.borrow();
+        assert_eq!(calls.len(), 1);
+        // Region grew to cover the cached line it cut through
+        assert_eq!(calls[0], BoundingBox::new(0.0, 290.0, 210.0, 50.0));
+
+        assert!(results.iter().all(|r| r.frame_id == "frame_2"));
+        assert_eq!(results.iter().filter(|r| r.text == "initial").count(), 1);
+        assert_eq!(results.iter().filter(|r| r.text == "updated").count(), 1);
+
+        // Cuts, capped region lists and frames without a reported change force a full pass
+        let cut = create_change(SceneChangeType::Cut, vec![(0, 0, 50, 50)]);
+        assert_eq!(scheduler.plan("monitor_0", 400, 400, Some(&cut)), OcrPlan::Full);
+        let mut capped = create_change(SceneChangeType::ContentChange, vec![(0, 290, 100, 50)]);
+        capped.regions_capped = true;
+        assert_eq!(scheduler.plan("monitor_0", 400, 400, Some(&capped)), OcrPlan::Full);
+        assert_eq!(scheduler.plan("monitor_0", 800, 400, None), OcrPlan::Full);
+
+        let stats = scheduler.get_stats();
+        assert_eq!((stats.full_passes, stats.incremental_passes, stats.reused_passes), (1, 1, 0));
+        assert!(stats.pixels_saved_ratio() > 0.3);
+        assert_eq!(scheduler.density("monitor_0").map(|d| d.box_count), Some(2));
+    }
+
+    #[test]
+    fn test_frames_without_a_change_are_read_again() {
+        let config = IncrementalOcrConfig { enabled: true, ..Default::default() };
+        let mut scheduler = IncrementalOcrScheduler::new(config);
+        let image = DynamicImage::new_luma8(400, 400);
+
+        let first = FakeEngine { calls: RefCell::new(Vec::new()), label: "before" };
+        scheduler.process_frame(&first, "monitor_0", "frame_1", &image, None).unwrap();
+
+        // A few typed characters stay below the scene thresholds
+        let second = FakeEngine { calls: RefCell::new(Vec::new()), label: "after" };
+        let results = scheduler.process_frame(&second, "monitor_0", "frame_2", &image, None).unwrap();
+        assert_eq!(second.calls.borrow().len(), 1);
+        assert!(results.iter().all(|r| r.text == "after"));
+    }
+}
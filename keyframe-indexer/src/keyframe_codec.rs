@@ -0,0 +1,173 @@
+use crate::error::{IndexerError, Result};
+use crate::hybrid_compression::HybridCompressionConfig;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+use image::{ColorType, DynamicImage, ImageEncoder};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Image format keyframes are saved in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyframeCodec {
+    #[default]
+    Png,
+    WebpLossless,
+    WebpLossy,
+    /// Lossy AVIF; needs a build with the `avif` feature
+    Avif,
+}
+
+impl KeyframeCodec {
+    pub const ALL: [KeyframeCodec; 4] = [Self::Png, Self::WebpLossless, Self::WebpLossy, Self::Avif];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::WebpLossless | Self::WebpLossy => "webp",
+            Self::Avif => "avif",
+        }
+    }
+
+    /// Whether text survives exactly, as re-running OCR on saved keyframes expects
+    pub fn is_lossless(&self) -> bool {
+        matches!(self, Self::Png | Self::WebpLossless)
+    }
+
+    /// Whether this build can encode the codec
+    pub fn is_available(&self) -> bool {
+        !matches!(self, Self::Avif) || cfg!(feature = "avif")
+    }
+}
+
+/// Format and quality of saved keyframes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyframeStorageConfig {
+    pub codec: KeyframeCodec,
+    /// Quality of lossy WebP and AVIF, 1-100
+    pub quality: u8,
+    /// AVIF encoder speed, 1 (smallest files) to 10 (fastest)
+    pub avif_speed: u8,
+    /// Re-encode analyzed keyframes with text exact and background simplified
+    pub hybrid: HybridCompressionConfig,
+}
+
+impl Default for KeyframeStorageConfig {
+    fn default() -> Self {
+        Self {
+            codec: KeyframeCodec::Png,
+            quality: 80,
+            avif_speed: 8,
+            hybrid: HybridCompressionConfig::default(),
+        }
+    }
+}
+
+impl KeyframeStorageConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=100).contains(&self.quality) || !(1..=10).contains(&self.avif_speed) {
+            return Err(IndexerError::Config(
+                "keyframe_storage quality must be 1-100 and avif_speed 1-10".to_string(),
+            ));
+        }
+        if !self.codec.is_available() {
+            return Err(IndexerError::Config(
+                "keyframe_storage codec avif needs a build with the `avif` feature".to_string(),
+            ));
+        }
+        if self.hybrid.enabled && !self.codec.is_lossless() {
+            return Err(IndexerError::Config(
+                "keyframe_storage hybrid compression needs a lossless codec: png or webp_lossless".to_string(),
+            ));
+        }
+        self.hybrid.validate()
+    }
+}
+
+/// Save a keyframe with the configured codec; `path` should end in the codec's extension
+pub fn save_keyframe(image: &DynamicImage, path: &Path, config: &KeyframeStorageConfig) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    encode(image, &mut writer, config)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Encode a keyframe with the configured codec; keyframes carry no alpha
+pub fn encode<W: Write>(image: &DynamicImage, writer: W, config: &KeyframeStorageConfig) -> Result<()> {
+    let rgb = image.to_rgb8();
+    let (width, height) = rgb.dimensions();
+    match config.codec {
+        KeyframeCodec::Png => PngEncoder::new(writer).write_image(rgb.as_raw(), width, height, ColorType::Rgb8)?,
+        KeyframeCodec::WebpLossless => WebPEncoder::new_with_quality(writer, WebPQuality::lossless())
+            .write_image(rgb.as_raw(), width, height, ColorType::Rgb8)?,
+        KeyframeCodec::WebpLossy => WebPEncoder::new_with_quality(writer, WebPQuality::lossy(config.quality))
+            .write_image(rgb.as_raw(), width, height, ColorType::Rgb8)?,
+        #[cfg(feature = "avif")]
+        KeyframeCodec::Avif => image::codecs::avif::AvifEncoder::new_with_speed_quality(writer, config.avif_speed, config.quality)
+            .write_image(rgb.as_raw(), width, height, ColorType::Rgb8)?,
+        #[cfg(not(feature = "avif"))]
+        KeyframeCodec::Avif => {
+            return Err(IndexerError::Config("AVIF keyframes need a build with the `avif` feature".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Size and encode time of one keyframe with one codec
+#[derive(Debug, Clone, Serialize)]
+pub struct CodecBenchmark {
+    pub codec: KeyframeCodec,
+    pub bytes: usize,
+    pub encode_ms: f64,
+}
+
+/// Encode `image` with each codec this build supports at `quality`
+pub fn benchmark(image: &DynamicImage, quality: u8) -> Result<Vec<CodecBenchmark>> {
+    let mut results = Vec::new();
+    for codec in KeyframeCodec::ALL.into_iter().filter(KeyframeCodec::is_available) {
+        let config = KeyframeStorageConfig { codec, quality, ..KeyframeStorageConfig::default() };
+        let mut encoded = Vec::new();
+        let started = Instant::now();
+        encode(image, &mut encoded, &config)?;
+        results.push(CodecBenchmark {
+            codec,
+            bytes: encoded.len(),
+            encode_ms: started.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codecs_round_trip_through_image_open() {
+        // Flat background with a little "text", like most screen content
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 32, |x, y| {
+            if (8..24).contains(&y) && x % 4 == 0 { image::Rgb([20, 20, 20]) } else { image::Rgb([250, 250, 250]) }
+        }));
+        let dir = tempfile::tempdir().unwrap();
+
+        for codec in [KeyframeCodec::Png, KeyframeCodec::WebpLossless, KeyframeCodec::WebpLossy] {
+            let config = KeyframeStorageConfig { codec, ..KeyframeStorageConfig::default() };
+            let path = dir.path().join(format!("frame_{:?}.{}", codec, codec.extension()));
+            save_keyframe(&image, &path, &config).unwrap();
+            let read = image::open(&path).unwrap();
+            assert_eq!((read.width(), read.height()), (64, 32));
+            if codec.is_lossless() {
+                assert_eq!(read.to_rgb8(), image.to_rgb8());
+            }
+        }
+
+        let results = benchmark(&image, 80).unwrap();
+        assert!(results.iter().all(|r| r.bytes > 0));
+        assert_eq!(results.len(), if cfg!(feature = "avif") { 4 } else { 3 });
+        assert!(KeyframeStorageConfig { quality: 0, ..KeyframeStorageConfig::default() }.validate().is_err());
+    }
+}
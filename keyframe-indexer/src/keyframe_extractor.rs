@@ -1,7 +1,11 @@
-use crate::error::{IndexerError, Result};
+#[cfg(feature = "ffmpeg")]
+use crate::burst_sampling::BurstSampler;
+use crate::burst_sampling::BurstSamplingConfig;
+use crate::error::{IndexerError, Result, ResultExt};
+use crate::keyframe_codec::{self, KeyframeStorageConfig};
 #[cfg(feature = "ffmpeg")]
 use ffmpeg_next as ffmpeg;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::{debug, warn, error};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -15,10 +19,28 @@ pub struct Keyframe {
     pub width: u32,
     pub height: u32,
     pub format: String,
+    /// Frame rate of the recording, when the decoder reports one
+    pub source_fps: Option<f32>,
+    /// Display the frame was captured from, when the source knows it;
+    /// otherwise the monitor is taken from the segment ID
+    pub display_id: Option<i32>,
 }
 
 pub struct KeyframeExtractor {
     extraction_fps: f32,
+    /// Keyframes of each segment are saved under `<frames_root>/<segment_id>`
+    frames_root: PathBuf,
+    /// Drop decoded frames identical to the last saved keyframe
+    skip_identical: bool,
+    /// An identical frame is still saved this often; never when 0
+    identical_keep_secs: u64,
+    /// Codec and quality keyframes are saved with
+    storage: KeyframeStorageConfig,
+    /// Extra frames kept while a toast or snackbar is on screen
+    #[cfg_attr(not(feature = "ffmpeg"), allow(dead_code))]
+    burst_sampling: BurstSamplingConfig,
+    /// Sample frames without converting or writing them
+    withhold_frames: bool,
 }
 
 impl KeyframeExtractor {
@@ -31,13 +53,57 @@ impl KeyframeExtractor {
             })?;
         }
         
-        Ok(Self { extraction_fps })
+        Ok(Self {
+            extraction_fps,
+            frames_root: PathBuf::from("./frames"),
+            skip_identical: false,
+            identical_keep_secs: 0,
+            storage: KeyframeStorageConfig::default(),
+            burst_sampling: BurstSamplingConfig::default(),
+            withhold_frames: false,
+        })
+    }
+    
+    pub fn set_frames_root(&mut self, frames_root: impl Into<PathBuf>) {
+        self.frames_root = frames_root.into();
+    }
+    
+    pub fn frames_root(&self) -> &Path {
+        &self.frames_root
     }
     
     pub fn set_extraction_rate(&mut self, fps: f32) {
         self.extraction_fps = fps;
     }
     
+    /// Skip frames whose pixels match the last saved keyframe exactly, before
+    /// they are converted and written; a repeat is still kept every `keep_secs`
+    pub fn set_identical_frame_filter(&mut self, enabled: bool, keep_secs: u64) {
+        self.skip_identical = enabled;
+        self.identical_keep_secs = keep_secs;
+    }
+    
+    pub fn set_storage(&mut self, storage: KeyframeStorageConfig) {
+        self.storage = storage;
+    }
+    
+    pub fn storage(&self) -> &KeyframeStorageConfig {
+        &self.storage
+    }
+    
+    /// Keep decoded frames between the regular samples for a while when a
+    /// small region appears near a screen edge
+    pub fn set_burst_sampling(&mut self, config: BurstSamplingConfig) {
+        self.burst_sampling = config;
+    }
+    
+    /// Sample frames as usual but never convert or write them, e.g. while the
+    /// privacy filter blocks what is on screen; the keyframes returned have no
+    /// file and only mark where frames were withheld
+    pub fn set_withhold_frames(&mut self, withhold: bool) {
+        self.withhold_frames = withhold;
+    }
+    
     pub async fn extract_keyframes(&self, video_path: &Path) -> Result<Vec<Keyframe>> {
         debug!("Extracting keyframes from: {}", video_path.display());
         
@@ -45,19 +111,17 @@ impl KeyframeExtractor {
         if !video_path.exists() {
             return Err(IndexerError::CorruptedVideo(
                 format!("Video file does not exist: {}", video_path.display())
-            ));
+            ).for_segment(video_path.display()));
         }
 
         #[cfg(feature = "ffmpeg")]
-        {
-            self.extract_keyframes_ffmpeg(video_path).await
-        }
+        let keyframes = self.extract_keyframes_ffmpeg(video_path).await;
         
+        // Mock implementation for testing without FFmpeg
         #[cfg(not(feature = "ffmpeg"))]
-        {
-            // Mock implementation for testing without FFmpeg
-            self.extract_keyframes_mock(video_path).await
-        }
+        let keyframes = self.extract_keyframes_mock(video_path).await;
+        
+        keyframes.for_segment(video_path.display())
     }
 
     #[cfg(feature = "ffmpeg")]
@@ -95,17 +159,23 @@ impl KeyframeExtractor {
         // Calculate frame interval based on extraction FPS
         let frame_rate = video_stream.avg_frame_rate();
         let source_fps = frame_rate.numerator() as f32 / frame_rate.denominator() as f32;
-        let frame_interval = (source_fps / self.extraction_fps).round() as usize;
+        // A rate above the recording's keeps every frame
+        let frame_interval = ((source_fps / self.extraction_fps).round() as usize).max(1);
         
         debug!("Source FPS: {}, Extraction FPS: {}, Frame interval: {}", 
                source_fps, self.extraction_fps, frame_interval);
         
         let mut keyframes = Vec::new();
         let mut frame_count = 0;
+        let mut repeats = RepeatFilter::new(
+            self.skip_identical,
+            (self.identical_keep_secs > 0).then(|| (self.identical_keep_secs as f32 * source_fps).round() as usize),
+        );
+        let mut burst = BurstSampler::new(self.burst_sampling.clone());
         let segment_id = self.generate_segment_id(video_path);
         
         // Create output directory for frames
-        let frames_dir = self.create_frames_directory(&segment_id)?;
+        let frames_dir = if self.withhold_frames { PathBuf::new() } else { self.create_frames_directory(&segment_id)? };
         
         for (stream, packet) in input_context.packets() {
             if stream.index() == video_stream_index {
@@ -113,8 +183,14 @@ impl KeyframeExtractor {
                 
                 let mut decoded_frame = ffmpeg::util::frame::Video::empty();
                 while decoder.receive_frame(&mut decoded_frame).is_ok() {
-                    if frame_count % frame_interval == 0 {
-                        match self.save_keyframe(&decoded_frame, &segment_id, &frames_dir, frame_count).await {
+                    let regular = frame_count % frame_interval == 0;
+                    let extra = !regular && burst.wants_extra(frame_count as f64 / source_fps as f64);
+                    if self.withhold_frames {
+                        if regular {
+                            keyframes.push(self.withheld_keyframe(&segment_id, frame_count, &decoded_frame));
+                        }
+                    } else if (regular || extra) && !repeats.is_repeat_frame(&decoded_frame, frame_count) {
+                        match self.save_keyframe(&decoded_frame, &segment_id, &frames_dir, frame_count, source_fps, &mut burst, extra).await {
                             Ok(keyframe) => {
                                 keyframes.push(keyframe);
                                 debug!("Extracted keyframe at frame {}", frame_count);
@@ -133,15 +209,28 @@ impl KeyframeExtractor {
         decoder.send_eof()?;
         let mut decoded_frame = ffmpeg::util::frame::Video::empty();
         while decoder.receive_frame(&mut decoded_frame).is_ok() {
-            if frame_count % frame_interval == 0 {
-                if let Ok(keyframe) = self.save_keyframe(&decoded_frame, &segment_id, &frames_dir, frame_count).await {
+            let regular = frame_count % frame_interval == 0;
+            let extra = !regular && burst.wants_extra(frame_count as f64 / source_fps as f64);
+            if self.withhold_frames {
+                if regular {
+                    keyframes.push(self.withheld_keyframe(&segment_id, frame_count, &decoded_frame));
+                }
+            } else if (regular || extra) && !repeats.is_repeat_frame(&decoded_frame, frame_count) {
+                if let Ok(keyframe) = self.save_keyframe(&decoded_frame, &segment_id, &frames_dir, frame_count, source_fps, &mut burst, extra).await {
                     keyframes.push(keyframe);
                 }
             }
             frame_count += 1;
         }
         
-        debug!("Extracted {} keyframes from {} total frames", keyframes.len(), frame_count);
+        for keyframe in &mut keyframes {
+            keyframe.source_fps = Some(source_fps);
+        }
+        
+        debug!(
+            "Extracted {} keyframes from {} total frames, skipping {} identical ones and adding {} in {} bursts",
+            keyframes.len(), frame_count, repeats.skipped, burst.extra_frames, burst.bursts
+        );
         
         if keyframes.is_empty() {
             warn!("No keyframes extracted from video: {}", video_path.display());
@@ -155,7 +244,7 @@ impl KeyframeExtractor {
         debug!("Using mock keyframe extraction for: {}", video_path.display());
         
         let segment_id = self.generate_segment_id(video_path);
-        let frames_dir = self.create_frames_directory(&segment_id)?;
+        let frames_dir = if self.withhold_frames { PathBuf::new() } else { self.create_frames_directory(&segment_id)? };
         
         // Create mock keyframes for testing
         let mut keyframes = Vec::new();
@@ -163,12 +252,14 @@ impl KeyframeExtractor {
         
         for i in 0..mock_frame_count {
             let keyframe_id = Uuid::new_v4();
-            let frame_filename = format!("frame_{}_{}.png", segment_id, i);
+            let frame_filename = format!("frame_{}_{}.{}", segment_id, i, self.storage.codec.extension());
             let frame_path = frames_dir.join(&frame_filename);
             
             // Create a simple test image (64x64 RGB)
-            let img = image::RgbImage::new(64, 64);
-            img.save(&frame_path)?;
+            if !self.withhold_frames {
+                let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(64, 64));
+                keyframe_codec::save_keyframe(&img, &frame_path, &self.storage)?;
+            }
             
             let timestamp_ns = (i as f64 / self.extraction_fps as f64 * 1_000_000_000.0) as i64;
             
@@ -176,10 +267,12 @@ impl KeyframeExtractor {
                 id: keyframe_id,
                 timestamp_ns,
                 segment_id: segment_id.clone(),
-                frame_path: frame_path.to_string_lossy().to_string(),
+                frame_path: if self.withhold_frames { String::new() } else { frame_path.to_string_lossy().to_string() },
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             });
         }
         
@@ -188,15 +281,19 @@ impl KeyframeExtractor {
     }
     
     #[cfg(feature = "ffmpeg")]
+    #[allow(clippy::too_many_arguments)]
     async fn save_keyframe(
         &self,
         frame: &ffmpeg::util::frame::Video,
         segment_id: &str,
         frames_dir: &Path,
         frame_number: usize,
+        source_fps: f32,
+        burst: &mut BurstSampler,
+        extra: bool,
     ) -> Result<Keyframe> {
         let keyframe_id = Uuid::new_v4();
-        let frame_filename = format!("frame_{}_{}.png", segment_id, frame_number);
+        let frame_filename = format!("frame_{}_{}.{}", segment_id, frame_number, self.storage.codec.extension());
         let frame_path = frames_dir.join(&frame_filename);
         
         // Convert FFmpeg frame to image
@@ -218,7 +315,7 @@ impl KeyframeExtractor {
         
         converter.run(frame, &mut rgb_frame)?;
         
-        // Save as PNG
+        // Save with the configured codec
         let rgb_data = rgb_frame.data(0);
         let img = image::RgbImage::from_raw(width, height, rgb_data.to_vec())
             .ok_or_else(|| IndexerError::Image(
@@ -227,7 +324,10 @@ impl KeyframeExtractor {
                 ))
             ))?;
         
-        img.save(&frame_path)?;
+        // Offset of the frame in the recording; burst frames fall between regular samples
+        let secs = frame_number as f64 / source_fps as f64;
+        burst.observe(secs, &img, extra);
+        keyframe_codec::save_keyframe(&image::DynamicImage::ImageRgb8(img), &frame_path, &self.storage)?;
         
         // Calculate timestamp in nanoseconds
         let timestamp_ns = (frame_number as f64 / self.extraction_fps as f64 * 1_000_000_000.0) as i64;
@@ -240,9 +340,27 @@ impl KeyframeExtractor {
             width,
             height,
             format,
+            source_fps: None,
+            display_id: None,
         })
     }
     
+    /// A sampled frame that was not written
+    #[cfg(feature = "ffmpeg")]
+    fn withheld_keyframe(&self, segment_id: &str, frame_number: usize, frame: &ffmpeg::util::frame::Video) -> Keyframe {
+        Keyframe {
+            id: Uuid::new_v4(),
+            timestamp_ns: (frame_number as f64 / self.extraction_fps as f64 * 1_000_000_000.0) as i64,
+            segment_id: segment_id.to_string(),
+            frame_path: String::new(),
+            width: frame.width(),
+            height: frame.height(),
+            format: format!("{:?}", frame.format()),
+            source_fps: None,
+            display_id: None,
+        }
+    }
+    
     fn generate_segment_id(&self, video_path: &Path) -> String {
         // Generate segment ID from video filename and timestamp
         let filename = video_path.file_stem()
@@ -252,13 +370,60 @@ impl KeyframeExtractor {
         format!("{}_{}", filename, timestamp)
     }
     
-    fn create_frames_directory(&self, segment_id: &str) -> Result<std::path::PathBuf> {
-        let frames_dir = std::path::PathBuf::from("./frames").join(segment_id);
+    fn create_frames_directory(&self, segment_id: &str) -> Result<PathBuf> {
+        let frames_dir = self.frames_root.join(segment_id);
         std::fs::create_dir_all(&frames_dir)?;
         Ok(frames_dir)
     }
 }
 
+/// Drops frames identical to the last one kept, going by a CRC of their pixels
+#[cfg_attr(not(feature = "ffmpeg"), allow(dead_code))]
+struct RepeatFilter {
+    enabled: bool,
+    /// Source frames after which a repeat is kept anyway, so unchanged screens leave a trace
+    keep_every: Option<usize>,
+    /// Checksum and number of the last frame kept
+    last: Option<(u32, usize)>,
+    skipped: usize,
+}
+
+#[cfg_attr(not(feature = "ffmpeg"), allow(dead_code))]
+impl RepeatFilter {
+    fn new(enabled: bool, keep_every: Option<usize>) -> Self {
+        Self { enabled, keep_every, last: None, skipped: 0 }
+    }
+    
+    /// Whether a frame is a repeat to skip; the checksum is only computed when enabled
+    #[cfg(feature = "ffmpeg")]
+    fn is_repeat_frame(&mut self, frame: &ffmpeg::util::frame::Video, frame_number: usize) -> bool {
+        self.enabled && self.is_repeat(frame_checksum(frame), frame_number)
+    }
+    
+    fn is_repeat(&mut self, checksum: u32, frame_number: usize) -> bool {
+        if let Some((last_checksum, last_frame)) = self.last {
+            let keep_anyway = self.keep_every.is_some_and(|every| frame_number - last_frame >= every);
+            if checksum == last_checksum && !keep_anyway {
+                self.skipped += 1;
+                return true;
+            }
+        }
+        self.last = Some((checksum, frame_number));
+        false
+    }
+}
+
+/// CRC32 of a decoded frame's planes. Row padding is included, so frames can
+/// only ever be missed as repeats, never mistaken for one
+#[cfg(feature = "ffmpeg")]
+fn frame_checksum(frame: &ffmpeg::util::frame::Video) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    for plane in 0..frame.planes() {
+        hasher.update(frame.data(plane));
+    }
+    hasher.finalize()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,7 +448,19 @@ mod tests {
         let extractor = KeyframeExtractor::new(1.0).unwrap();
         let result = extractor.extract_keyframes(Path::new("nonexistent.mp4")).await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), IndexerError::CorruptedVideo(_)));
+        assert!(matches!(result.unwrap_err().root(), IndexerError::CorruptedVideo(_)));
+    }
+    
+    #[test]
+    fn test_identical_frames_are_skipped_until_a_repeat_is_due() {
+        let mut repeats = RepeatFilter::new(true, Some(60));
+        let kept: Vec<usize> = [(1, 0), (1, 30), (1, 45), (2, 50), (2, 80), (2, 110), (2, 140), (1, 150)]
+            .into_iter()
+            .filter(|&(checksum, frame)| !repeats.is_repeat(checksum, frame))
+            .map(|(_, frame)| frame)
+            .collect();
+        assert_eq!(kept, vec![0, 50, 110, 150]);
+        assert_eq!(repeats.skipped, 4);
     }
     
     #[test]
@@ -0,0 +1,328 @@
+use crate::error::{IndexerError, Result};
+use arrow::array::Array;
+use arrow::datatypes::{DataType, Fields, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::listing::{ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl};
+use datafusion::datasource::MemTable;
+use datafusion::prelude::SessionContext;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Files of one output directory that share a schema and partition values
+#[derive(Debug, Clone)]
+pub struct LayoutGroup {
+    pub schema: SchemaRef,
+    /// `key=value` directories the files were found under, outermost first
+    pub partition: Vec<(String, String)>,
+    pub files: Vec<PathBuf>,
+}
+
+/// Rule filling a column of the current schema from a column older files have instead
+#[derive(Debug, Clone)]
+pub struct ColumnUpgrade {
+    pub column: String,
+    pub legacy_column: String,
+    /// SQL expression over the legacy column
+    pub expression: String,
+}
+
+/// Table readable across every layout its writer has produced.
+///
+/// Files may sit flat in the directory or under `key=value` partition
+/// directories, and may have been written with any earlier schema. Each
+/// layout is registered separately and projected onto the current schema:
+/// upgraded columns are computed from their legacy column, partition values
+/// fill matching columns and anything else missing reads as NULL.
+#[derive(Debug, Clone)]
+pub struct CompatibleTable {
+    name: String,
+    schema: SchemaRef,
+    upgrades: Vec<ColumnUpgrade>,
+}
+
+impl CompatibleTable {
+    pub fn new(name: &str, schema: SchemaRef) -> Self {
+        Self { name: name.to_string(), schema, upgrades: Vec::new() }
+    }
+
+    pub fn with_upgrade(mut self, column: &str, legacy_column: &str, expression: String) -> Self {
+        self.upgrades.push(ColumnUpgrade {
+            column: column.to_string(),
+            legacy_column: legacy_column.to_string(),
+            expression,
+        });
+        self
+    }
+
+    /// Register the unified view of `dir` with `ctx` under the table name
+    pub async fn register(&self, ctx: &SessionContext, dir: &Path) -> Result<()> {
+        self.register_groups(ctx, dir, scan_layouts(dir)?).await
+    }
+
+    /// Like `register`, but only over `files` of `dir`, e.g. those left after
+    /// index pruning; other files' footers are never read
+    pub async fn register_files(&self, ctx: &SessionContext, dir: &Path, files: &[PathBuf]) -> Result<()> {
+        let wanted: HashSet<&PathBuf> = files.iter().collect();
+        let entries = collect_files(dir)?.into_iter().filter(|(file, _)| wanted.contains(file)).collect();
+        self.register_groups(ctx, dir, group_layouts(entries)?).await
+    }
+
+    async fn register_groups(&self, ctx: &SessionContext, dir: &Path, groups: Vec<LayoutGroup>) -> Result<()> {
+        if groups.is_empty() {
+            let empty = MemTable::try_new(self.schema.clone(), vec![Vec::new()])?;
+            ctx.register_table(self.name.as_str(), Arc::new(empty))?;
+            return Ok(());
+        }
+
+        let mut selects = Vec::with_capacity(groups.len());
+        for (index, group) in groups.iter().enumerate() {
+            let layout_name = format!("{}_layout_{}", self.name, index);
+            let urls = group.files
+                .iter()
+                .map(|file| ListingTableUrl::parse(file.to_string_lossy()))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let options = ListingOptions::new(Arc::new(ParquetFormat::default())).with_file_extension(".parquet");
+            let config = ListingTableConfig::new_with_multi_paths(urls)
+                .with_listing_options(options)
+                .with_schema(group.schema.clone());
+            ctx.register_table(layout_name.as_str(), Arc::new(ListingTable::try_new(config)?))?;
+
+            selects.push(format!("SELECT {} FROM {}", self.projection(group)?.join(", "), quote(&layout_name)));
+        }
+
+        if groups.len() > 1 {
+            info!("Reading {} with {} layouts from {}", self.name, groups.len(), dir.display());
+        }
+        ctx.sql(&format!("CREATE VIEW {} AS {}", quote(&self.name), selects.join(" UNION ALL "))).await?;
+        Ok(())
+    }
+
+    fn projection(&self, group: &LayoutGroup) -> Result<Vec<String>> {
+        let mut columns = Vec::with_capacity(self.schema.fields().len());
+
+        for field in self.schema.fields() {
+            let name = field.name();
+            let target = field.data_type();
+
+            let expression = if let Ok(existing) = group.schema.field_with_name(name) {
+                if existing.data_type() == target {
+                    quote(name)
+                } else {
+                    cast(&quote(name), target, name)?
+                }
+            } else if let Some(upgrade) = self.upgrades
+                .iter()
+                .find(|u| &u.column == name && group.schema.field_with_name(&u.legacy_column).is_ok())
+            {
+                cast(&format!("({})", upgrade.expression), target, name)?
+            } else if let Some((_, value)) = group.partition.iter().find(|(key, _)| key == name) {
+                cast(&format!("'{}'", value.replace('\'', "''")), target, name)?
+            } else {
+                cast("NULL", target, name)?
+            };
+
+            columns.push(format!("{} AS {}", expression, quote(name)));
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Group the Parquet files under `dir` by schema and partition values
+pub fn scan_layouts(dir: &Path) -> Result<Vec<LayoutGroup>> {
+    let groups = group_layouts(collect_files(dir)?)?;
+    debug!("Found {} layouts in {}", groups.len(), dir.display());
+    Ok(groups)
+}
+
+fn group_layouts(entries: Vec<(PathBuf, Vec<(String, String)>)>) -> Result<Vec<LayoutGroup>> {
+    let mut groups: Vec<LayoutGroup> = Vec::new();
+
+    for (file, partition) in entries {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&file)?)?;
+        let fields: Fields = reader.schema().fields().clone();
+
+        match groups.iter_mut().find(|g| g.schema.fields() == &fields && g.partition == partition) {
+            Some(group) => group.files.push(file),
+            None => groups.push(LayoutGroup {
+                schema: Arc::new(Schema::new(fields)),
+                partition,
+                files: vec![file],
+            }),
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Every data file under `dir`, flat or partitioned
+pub fn parquet_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(collect_files(dir)?.into_iter().map(|(file, _)| file).collect())
+}
+
+fn collect_files(dir: &Path) -> Result<Vec<(PathBuf, Vec<(String, String)>)>> {
+    let mut files = Vec::new();
+    if dir.exists() {
+        collect_files_into(dir, &[], &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files_into(
+    dir: &Path,
+    partition: &[(String, String)],
+    files: &mut Vec<(PathBuf, Vec<(String, String)>)>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+            continue;
+        };
+        // Manifests, hidden files and decrypted query copies are not data
+        if name.starts_with('.') || name.starts_with('_') || name.ends_with(".tmp.parquet") {
+            continue;
+        }
+
+        if path.is_dir() {
+            if let Some((key, value)) = name.split_once('=') {
+                let mut nested = partition.to_vec();
+                nested.push((key.to_string(), value.to_string()));
+                collect_files_into(&path, &nested, files)?;
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("parquet") {
+            files.push((path, partition.to_vec()));
+        }
+    }
+    Ok(())
+}
+
+/// Column `index` of a batch read back from a file, as array type `T`; a
+/// missing column or one of another type is an error rather than a panic
+pub fn column<T: Array + 'static>(batch: &RecordBatch, index: usize) -> Result<&T> {
+    batch
+        .columns()
+        .get(index)
+        .and_then(|column| column.as_any().downcast_ref::<T>())
+        .ok_or_else(|| {
+            let name = batch.schema().fields().get(index).map(|field| field.name().clone()).unwrap_or_default();
+            IndexerError::Arrow(ArrowError::SchemaError(format!(
+                "Column {} '{}' is missing or not {}",
+                index,
+                name,
+                std::any::type_name::<T>()
+            )))
+        })
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn cast(expression: &str, data_type: &DataType, column: &str) -> Result<String> {
+    if data_type.is_nested() {
+        return Err(IndexerError::Config(format!(
+            "Column {} has a nested type and cannot be filled in for older layouts",
+            column
+        )));
+    }
+    Ok(format!("arrow_cast({}, '{}')", expression, data_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::{DetectedEvent, EventType};
+    use crate::event_parquet_writer::EventParquetWriter;
+    use arrow::array::{Float32Array, ListBuilder, StringArray, StringBuilder, TimestampNanosecondArray};
+    use arrow::datatypes::{Field, TimeUnit};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+    use tempfile::TempDir;
+
+    /// Event file as written before signatures and enum codes, in a partition directory
+    fn write_legacy_events(dir: &Path) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("event_id", DataType::Utf8, false),
+            Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("type", DataType::Utf8, false),
+            Field::new("target", DataType::Utf8, false),
+            Field::new("value_from", DataType::Utf8, true),
+            Field::new("value_to", DataType::Utf8, true),
+            Field::new("confidence", DataType::Float32, false),
+            Field::new("evidence_frames", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
+            Field::new("metadata", DataType::Utf8, true),
+        ]));
+
+        let mut evidence = ListBuilder::new(StringBuilder::new());
+        evidence.values().append_value("frame_0");
+        evidence.append(true);
+
+        let batch = RecordBatch::try_new(schema.clone(), vec![
+            Arc::new(StringArray::from(vec!["legacy_1"])),
+            Arc::new(TimestampNanosecondArray::from(vec![1_000_000_000])),
+            Arc::new(StringArray::from(vec!["error_display"])),
+            Arc::new(StringArray::from(vec!["old_dialog"])),
+            Arc::new(StringArray::from(vec![None::<&str>])),
+            Arc::new(StringArray::from(vec![Some("Disk full")])),
+            Arc::new(Float32Array::from(vec![0.9])),
+            Arc::new(evidence.finish()),
+            Arc::new(StringArray::from(vec![Some("{}")])),
+        ]).unwrap();
+
+        let partition = dir.join("date=2024-01-15");
+        std::fs::create_dir_all(&partition).unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(partition.join("events_legacy.parquet")).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    fn current_event(id: &str) -> DetectedEvent {
+        DetectedEvent {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "error_dialog".to_string(),
+            value_from: None,
+            value_to: Some("Connection failed".to_string()),
+            confidence: 0.85,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata: std::collections::HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mixed_layouts_query_as_one_table() {
+        let temp_dir = TempDir::new().unwrap();
+        write_legacy_events(temp_dir.path());
+
+        let mut writer = EventParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        writer.write_event(&current_event("current_1")).await.unwrap();
+        writer.finalize().await.unwrap();
+
+        let layouts = scan_layouts(temp_dir.path()).unwrap();
+        assert_eq!(layouts.len(), 2);
+        assert!(layouts.iter().any(|l| l.partition == vec![("date".to_string(), "2024-01-15".to_string())]));
+
+        let events = writer.query_by_type(&EventType::ErrorDisplay).await.unwrap();
+        let mut ids: Vec<_> = events.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["current_1", "legacy_1"]);
+
+        let legacy = events.iter().find(|e| e.id == "legacy_1").unwrap();
+        assert_eq!(legacy.target, "old_dialog");
+        assert_eq!(legacy.evidence_frames, vec!["frame_0".to_string()]);
+
+        // The string view covers legacy rows too
+        let ctx = SessionContext::new();
+        writer.register_events(&ctx).await.unwrap();
+        let batches = ctx.sql("SELECT type FROM events WHERE signature IS NULL").await.unwrap().collect().await.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+    }
+}
@@ -5,6 +5,7 @@ pub mod metadata_collector;
 pub mod csv_writer;
 pub mod error;
 pub mod config;
+pub mod config_watcher;
 pub mod csv_test;
 pub mod parquet_writer;
 pub mod ocr_data;
@@ -13,12 +14,94 @@ pub mod event_detector;
 pub mod event_parquet_writer;
 pub mod delta_analyzer;
 pub mod navigation_detector;
+pub mod browser_poller;
+pub mod system_probe;
 pub mod cursor_tracker;
 pub mod event_correlator;
 pub mod navigation_integration;
 pub mod integration_test;
 pub mod error_modal_detector;
 pub mod encryption;
+pub mod event_signing;
+pub mod redaction;
+pub mod query_service;
+pub mod auth;
+pub mod query_audit;
+pub mod segment_ledger;
+pub mod live_snapshot;
+pub mod event_triggers;
+pub mod notifier;
+pub mod incremental_ocr;
+pub mod calendar_context;
+pub mod frame_dedup;
+pub mod focus_summary;
+pub mod markdown_exporter;
+pub mod text_diff;
+pub mod image_analysis;
+pub mod batch_analysis;
+pub mod scroll_detector;
+pub mod plugin_tables;
+pub mod display_topology;
+pub mod enum_codes;
+pub mod layout_compat;
+pub mod stats_cache;
+pub mod file_index;
+pub mod writer_memory;
+pub mod parquet_tuning;
+pub mod query_stream;
+pub mod workflow_chain;
+pub mod suppression;
+pub mod ocr_density;
+pub mod correlation_parquet_writer;
+pub mod capture_profile;
+pub mod quickstart;
+pub mod error_recovery_analyzer;
+pub mod modal_lifetime;
+pub mod progress_tracker;
+pub mod template_matcher;
+pub mod privacy_filter;
+pub mod notification_detector;
+pub mod output_query;
+pub mod flight;
+pub mod capi;
+pub mod output_export;
+pub mod output_sink;
+pub mod sqlite_sink;
+pub mod duckdb_export;
+pub mod telemetry;
+pub mod logging;
+pub mod health;
+pub mod embeddings;
+pub mod report;
+pub mod workflow_miner;
+pub mod app_extractor;
+pub mod sap_gui;
+pub mod terminal;
+pub mod ide;
+pub mod shutdown;
+pub mod storage_monitor;
+pub mod dead_letter;
+pub mod audio_analyzer;
+pub mod capture;
+pub mod display_timeline;
+pub mod adaptive_fps;
+pub mod burst_sampling;
+pub mod keyframe_codec;
+pub mod hybrid_compression;
+pub mod visual_features;
+pub mod screen_classifier;
+pub mod erasure;
+pub mod access_audit;
+pub mod anonymizer;
+pub mod event_dedup;
+pub mod calibration;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod field_identity;
+pub mod form_model;
+pub mod table_extractor;
+pub mod link_detector;
+pub mod retention;
 
 #[cfg(test)]
 pub mod ocr_parquet_tests;
@@ -34,105 +117,2148 @@ pub mod error_modal_tests;
 
 pub mod simple_event_test;
 
-pub use keyframe_extractor::KeyframeExtractor;
-pub use scene_detector::SceneDetector;
-pub use file_watcher::FileWatcher;
+pub use keyframe_extractor::{Keyframe, KeyframeExtractor};
+pub use scene_detector::{SceneDetector, SceneAnalysis, SceneChange, ChangedRegion};
+pub use file_watcher::{FileWatcher, FileWatcherConfig};
 pub use metadata_collector::MetadataCollector;
 pub use csv_writer::CsvWriter;
 pub use error::{IndexerError, Result};
-pub use config::IndexerConfig;
+pub use config::{ConfigIssue, IndexerConfig};
+pub use config_watcher::ConfigWatcher;
 pub use parquet_writer::ParquetWriter;
-pub use ocr_data::{OCRResult, OCRBatch, BoundingBox};
+pub use ocr_data::{valid_results, OCRResult, OCRBatch, BoundingBox, CoordinateSpace, RoiScaleConfig, RoiNormalizer};
 pub use ocr_parquet_writer::{OCRParquetWriter, OCRStatistics};
 pub use event_detector::{EventDetector, DetectedEvent, EventType, EventDetectionConfig};
 pub use event_parquet_writer::{EventParquetWriter, EventStatistics};
 pub use delta_analyzer::{DeltaAnalyzer, DeltaAnalysisConfig, FieldChangeInfo, FieldStateInfo};
 pub use navigation_detector::{NavigationDetector, NavigationDetectionConfig, WindowState, TabState, FocusEvent};
-pub use cursor_tracker::{CursorTracker, CursorTrackingConfig, CursorPosition, ClickEvent, MovementTrail, TrailType};
+pub use browser_poller::{Browser, BrowserPoller, BrowserPollerConfig, TabDebouncer};
+pub use system_probe::{SystemProbe, SystemProbeConfig};
+pub use cursor_tracker::{CursorTracker, CursorTrackingConfig, CursorPosition, ClickEvent, MovementTrail, TrailType, GestureKind, GesturePhase, GestureSample, InputGesture};
 pub use event_correlator::{EventCorrelator, CorrelationConfig, CorrelationResult, CorrelationType};
+pub use correlation_parquet_writer::CorrelationParquetWriter;
+pub use capture_profile::{CaptureProfile, CaptureProfileChange, CaptureProfileConfig, CaptureProfileTracker};
 pub use navigation_integration::{NavigationIntegrationService, NavigationIntegrationConfig, NavigationStatistics};
-pub use error_modal_detector::{ErrorModalDetector, ErrorModalDetectionConfig, ErrorModalEvent, ErrorModalType, SeverityLevel, PatternMatch, LayoutAnalysis};
+pub use error_modal_detector::{DialogButton, ErrorModalDetector, ErrorModalDetectionConfig, ErrorModalEvent, ErrorModalType, SeverityLevel, PatternMatch, LayoutAnalysis};
 pub use encryption::{EncryptionManager, SecureParquetWriter};
+pub use event_signing::{EventSigner, EventVerifier, SignedEvent};
+pub use redaction::{Redactor, RedactionConfig, RedactionProfile};
+pub use query_service::QueryService;
+pub use query_audit::{QueryAuditLog, QueryAuditEntry};
+pub use auth::{Authenticator, AuthConfig, AuthContext, ApiKeyConfig, Scope, TlsConfig};
+pub use segment_ledger::{SegmentLedger, SharedSegmentLedger};
+pub use dead_letter::{DeadLetter, DeadLetterStore, FailedItemKind, FailureStage, OutputStep, RetrySummary};
+pub use live_snapshot::{LiveSnapshot, SnapshotTracker};
+pub use event_triggers::{TriggerEngine, TriggerConfig, EventPredicate};
+pub use notifier::{WebhookNotifier, NotifierConfig, NotifierKind};
+pub use incremental_ocr::{IncrementalOcrScheduler, IncrementalOcrConfig, IncrementalOcrStats, OcrPlan, OcrRegionEngine};
+pub use calendar_context::{CalendarContext, CalendarConfig, CalendarEvent, MeetingAppUsage, SegmentCalendarTag};
+pub use frame_dedup::{FrameDeduplicator, FrameDedupConfig};
+pub use focus_summary::{FocusSummaryGenerator, FocusSummarySchedule, FocusSummaryConfig, DailySummary, AppUsage, DeepWorkBlock};
+pub use markdown_exporter::{MarkdownExporter, MarkdownExportConfig, DailyLog, AppSpan, NotableEvent, EntityExtractor, ExtractedEntities};
+pub use text_diff::{TextDiff, DiffGranularity, DiffOp, ChangeKind};
+pub use image_analysis::{ImageAnalyzer, ImageAnalysis, OcrSource};
+pub use batch_analysis::{BatchCheckpoint, BatchFailure, BatchOptions, BatchSummary};
+pub use scroll_detector::{ScrollDetector, ScrollDetectionConfig, ScrollDirection, ScrollMotion};
+pub use plugin_tables::{PluginTableRegistry, PluginTablesConfig, PluginManifest, TablePlugin, TableDefinition, TableSpec, ColumnSpec, ColumnType, TablePartitioning, PluginTableStatistics};
+pub use display_topology::{DisplayTopology, DisplayInfo, LocalPoint};
+pub use enum_codes::{EnumCode, EnumCodeTable};
+pub use layout_compat::{CompatibleTable, LayoutGroup, ColumnUpgrade};
+pub use suppression::{SuppressionList, SuppressionRule};
+pub use ocr_density::{OcrDensity, OcrQualityConfig, OcrQualityMonitor, OcrQualityRegression};
+pub use error_recovery_analyzer::{ErrorRecoveryAnalyzer, ErrorRecoveryConfig, ErrorRecoveryParquetWriter, ErrorRecoverySequence, RecoveryAction, RecoveryActionKind};
+pub use modal_lifetime::{ModalLifetimeConfig, ModalLifetimeTracker};
+pub use progress_tracker::{ProgressOutcome, ProgressTracker, ProgressTrackingConfig};
+pub use notification_detector::{NotificationBanner, NotificationDetectionConfig, NotificationDetector};
+pub use output_query::{CannedQuery, OutputQuery, CANNED_QUERIES};
+pub use flight::FlightConfig;
+pub use writer_memory::{MemoryBudget, WriterMemoryConfig};
+pub use logging::{LogFormat, LoggingConfig, RateLimitLayer, RollingFileWriter};
+pub use health::{HealthConfig, HealthMonitor, HealthStatus};
+pub use parquet_tuning::ParquetTuningConfig;
+pub use output_export::ExportFormat;
+pub use template_matcher::{DialogTemplate, TemplateMatch, TemplateMatcher, TemplateMatchingConfig};
+pub use privacy_filter::{PrivacyFilter, PrivacyFilterConfig, PrivacyMatch, RedactedInterval};
+pub use output_sink::{JsonlSink, OutputSink, OutputSinkKind};
+pub use sqlite_sink::SqliteSink;
+pub use duckdb_export::{export_duckdb, DuckDbExportSummary, DuckDbSources};
+pub use embeddings::{EmbeddingIndex, EmbeddingStore, EmbeddingsConfig, FrameEmbedding, SimilarFrame, TextEmbedder};
+pub use report::{ReportConfig, ReportFormat, ReportGenerator, ScreenTypeActivity, SessionReport};
+pub use app_extractor::{AppExtractor, AppExtractorRegistry, AppExtractorsConfig, AppFrame};
+pub use sap_gui::{SapField, SapGuiConfig, SapGuiExtractor, SapScreen};
+pub use terminal::{CommandStatus, TerminalCommand, TerminalConfig, TerminalExtractor};
+pub use ide::{BuildFailureCorrelator, CodeDiagnostic, IdeConfig, IdeExtractor, IdeScreen};
+pub use workflow_miner::{ActionStep, ProcedureCandidate, ProcedureExample, WorkflowMiner, WorkflowMinerConfig};
+pub use quickstart::{QuickstartDataset, QueryExample};
+pub use workflow_chain::{WorkflowChain, WorkflowChainBuilder, WorkflowChainConfig, WorkflowChainParquetWriter, WorkflowOutcome, WorkflowStep, WorkflowStepKind};
+pub use shutdown::ShutdownSignal;
+pub use storage_monitor::{StorageChange, StorageLevel, StorageMonitor, StorageMonitorConfig};
+pub use audio_analyzer::{AudioAnalyzer, AudioAnalyzerConfig, MeetingInterval, SpeechSpan};
+pub use capture::{CaptureConfig, CaptureSegmenter, CapturedFrame, CapturedSegment, FrameSource};
+pub use display_timeline::{DisplayTimeline, DisplayTimelineConfig, TimelineEntry};
+pub use adaptive_fps::{AdaptiveFps, AdaptiveFpsConfig};
+pub use burst_sampling::{BurstSampler, BurstSamplingConfig};
+pub use keyframe_codec::{CodecBenchmark, KeyframeCodec, KeyframeStorageConfig};
+pub use hybrid_compression::{HybridCompressionConfig, HybridStats};
+pub use visual_features::{DominantColor, VisualFeatures};
+pub use screen_classifier::{ScreenClassifier, ScreenClassifierConfig, ScreenType};
+pub use erasure::{Eraser, ErasureMode, ErasureReport, ErasureRequest};
+pub use access_audit::{AccessAuditEntry, AccessAuditLog, AccessAuditor, AccessOperation, AuditVerification};
+pub use anonymizer::{Anonymizer, AnonymizerConfig};
+pub use event_dedup::{EventDedupConfig, EventDeduplicator};
+pub use calibration::{Calibration, CalibrationConfig, CalibrationLabel, PlattCurve};
+#[cfg(any(test, feature = "test-support"))]
+pub use test_support::{Accuracy, GroundTruthEvent, ScreenScript, SyntheticRecording};
+pub use field_identity::{FieldIdentity, FieldIdentityConfig, FieldIdentityTracker};
+pub use form_model::{FormButton, FormField, FormModel, FormModelBuilder, FormModelConfig, FormRecord, FormRecordParquetWriter};
+pub use table_extractor::{CellChange, ExtractedTable, TableDiff, TableExtractorConfig, TableSnapshot, TableTracker};
+pub use link_detector::{FoundLink, LinkDetector, LinkDetectorConfig, LinkKind};
+pub use retention::{RetentionEnforcer, RetentionReport};
 
 use anyhow::Result as AnyhowResult;
-use std::path::Path;
+use batch_analysis::BatchInputKind;
+use error::ResultExt;
+use std::collections::{hash_map, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tracing::{info, error, warn};
+use tracing::{info, info_span, error, warn, Instrument};
 
 pub struct IndexerService {
     config: IndexerConfig,
     extractor: KeyframeExtractor,
-    detector: SceneDetector,
+    /// One scene detector per display, each with its own calibration
+    detectors: HashMap<i32, SceneDetector>,
     metadata_collector: MetadataCollector,
     csv_writer: CsvWriter,
+    /// Buffer budget shared by the frame and event writers
+    memory_budget: MemoryBudget,
+    ledger: SharedSegmentLedger,
+    /// Segments and frames that failed, kept for `retry-failed`
+    dead_letters: DeadLetterStore,
+    /// Outputs of the item being processed written so far, including those
+    /// an earlier failed attempt wrote
+    committed: BTreeSet<OutputStep>,
+    snapshot: SnapshotTracker,
+    /// Backs the `/healthz` and `/readyz` endpoints
+    health: HealthMonitor,
+    calendar: Option<CalendarContext>,
+    dedup: FrameDeduplicator,
+    ocr_quality: OcrQualityMonitor,
+    capture_profiles: CaptureProfileTracker,
+    suppressions: SuppressionList,
+    /// Known dialog screenshots, when any are configured
+    templates: Option<Arc<TemplateMatcher>>,
+    /// Only estimate what would be written; keyframes go to a scratch directory
+    dry_run: bool,
+    /// Reloads edits to the config file while watching
+    config_watcher: Option<ConfigWatcher>,
+    privacy: PrivacyFilter,
+    /// Extra output formats from `output_sinks`
+    sinks: Vec<Box<dyn OutputSink>>,
+    /// Embeds each segment's OCR text when `embeddings` is enabled
+    embeddings: Option<Arc<EmbeddingStore>>,
+    /// Cuts back processing as disk space or the session's budget runs low
+    storage: Option<StorageMonitor>,
+    /// Finds meetings from segment audio when `audio_analyzer` is enabled
+    audio: Option<AudioAnalyzer>,
+    /// Merges the displays' keyframes into one timeline when `display_timeline` is enabled
+    timeline: Option<DisplayTimeline>,
+    /// Tunes each display's extraction rate to its activity when `adaptive_fps` is enabled
+    adaptive_fps: Option<AdaptiveFps>,
+    /// Labels keyframes with a screen type when `screen_classifier` is enabled
+    screen_classifier: Option<ScreenClassifier>,
+    /// Fitted confidence curves, when `calibration` is enabled and has been fitted
+    calibration: Option<Calibration>,
+    /// Stores submitted forms when `event_detection.form_model` is enabled
+    forms: Option<FormRecordParquetWriter>,
+    /// Runs the configured `triggers` commands for detected events
+    triggers: Option<TriggerEngine>,
+    /// Posts detected events to the configured `notifiers` webhooks
+    notifier: Option<WebhookNotifier>,
+    /// Signs published events when `encryption.sign_events` is set
+    event_signer: Option<EventSigner>,
+    /// Writes the daily note from published events when `focus_summary` is enabled
+    focus_summary: Option<FocusSummarySchedule>,
+    /// Output tables declared by detector plugins
+    plugin_tables: PluginTableRegistry,
+    /// Attached displays, enumerated when watching or capturing starts
+    display_topology: Option<DisplayTopology>,
+    /// Links published events in pairs for the analyses built on correlations,
+    /// and stores the pairs it finds
+    correlator: Option<(EventCorrelator, CorrelationParquetWriter)>,
+    /// Stitches correlations into chains when `workflow_chains` is enabled
+    workflow_chains: Option<(WorkflowChainBuilder, WorkflowChainParquetWriter)>,
+    /// Follows errors to their resolution when `error_recovery` is enabled
+    error_recovery: Option<(ErrorRecoveryAnalyzer, ErrorRecoveryParquetWriter)>,
+    /// Live window, tab and cursor tracking while capturing, with `navigation_tracking`
+    navigation: Option<NavigationIntegrationService>,
+    /// Deletes expired keyframes and outputs when `retention` is enabled
+    retention: Option<RetentionEnforcer>,
+    /// Event detection state of each display, kept from one segment to the next
+    display_detection: HashMap<i32, DisplayDetection>,
+    /// State built with settings since replaced, finished with the next segment
+    retired_detection: Vec<(i32, DisplayDetection)>,
+    /// Code edits failed builds in later segments are tied to, from every display
+    build_failures: BuildFailureCorrelator,
+}
+
+/// A display's recording counts as paused when a segment starts this long
+/// after the last frame analyzed, and its detection state starts over
+const DETECTION_CONTINUITY_SECS: i64 = 60;
+
+/// Event detection of one display across its segments, so dialogs, commands
+/// and repeated events spanning a segment boundary are followed through
+struct DisplayDetection {
+    detector: EventDetector,
+    extractors: AppExtractorRegistry,
+    /// Collapses repeats once app extractors and screen types have seen each frame's events
+    deduplicator: EventDeduplicator,
+    /// Time of the last frame analyzed
+    last_frame: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DisplayDetection {
+    fn new(config: &IndexerConfig, display: i32) -> AnyhowResult<Self> {
+        let detection_config = config.event_detection_config();
+        let deduplicator = EventDeduplicator::new(detection_config.dedup.clone());
+        let field_identity = detection_config.field_identity.clone();
+        let mut detector = EventDetector::with_config(EventDetectionConfig {
+            dedup: EventDedupConfig { enabled: false, ..detection_config.dedup.clone() },
+            ..detection_config
+        })?;
+        // Fields keep the ids an earlier run gave them
+        let path = config.field_identities_path(display);
+        match FieldIdentityTracker::load(field_identity, &path) {
+            Ok(tracker) => detector.set_field_identities(tracker),
+            Err(e) => warn!("Failed to read followed fields from {}: {}", path.display(), e),
+        }
+        let extractors = AppExtractorRegistry::from_config(&config.app_extractors)?;
+        Ok(Self { detector, extractors, deduplicator, last_frame: None })
+    }
+    
+    /// Release what is held back for frames still to come
+    fn finish(&mut self) -> Vec<DetectedEvent> {
+        let mut events = self.detector.finish();
+        events.extend(self.deduplicator.flush());
+        events
+    }
+}
+
+/// What processing a segment produced
+#[derive(Default)]
+struct ProcessedSegment {
+    frame_metadata: Vec<metadata_collector::FrameMetadata>,
+    scene_changes: usize,
+    /// Stored OCR results of each non-duplicate keyframe, by path
+    frame_ocr: HashMap<String, Vec<OCRResult>>,
+    quality_regressions: usize,
+    /// Resolution and frame rate the segment was recorded with
+    profile: Option<CaptureProfile>,
+    /// Set when the profile differs from the stream's previous segment
+    profile_change: Option<CaptureProfileChange>,
+    /// Bytes of keyframes and frame metadata, estimated in dry-run mode
+    estimated_bytes: u64,
+    /// Wall-clock time of the segment's first frame
+    start: chrono::DateTime<chrono::Utc>,
+    /// Keyframes dropped by the privacy filter
+    redacted_frames: usize,
+    /// Calls found from the segment's audio
+    meetings: Vec<MeetingInterval>,
+}
+
+/// Where a segment's keyframes came from
+#[derive(Clone, Copy)]
+enum SegmentSource<'a> {
+    /// Extracted from a recorded video file
+    Video(&'a Path),
+    /// Grabbed from the screen, starting at the given time
+    Live(chrono::DateTime<chrono::Utc>),
+}
+
+impl std::fmt::Display for SegmentSource<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Video(path) => write!(f, "{}", path.display()),
+            Self::Live(start) => write!(f, "live capture from {}", start.format("%H:%M:%S")),
+        }
+    }
 }
 
 impl IndexerService {
     pub fn new(config: IndexerConfig) -> AnyhowResult<Self> {
-        let extractor = KeyframeExtractor::new(config.extraction_fps)?;
-        let detector = SceneDetector::new(config.scene_detection.clone())?;
-        let metadata_collector = MetadataCollector::new()?;
-        let csv_writer = CsvWriter::new(&config.output_dir)?;
+        let mut extractor = KeyframeExtractor::new(config.extraction_fps)?;
+        extractor.set_identical_frame_filter(config.frame_dedup.skip_identical_frames, config.frame_dedup.identical_keep_secs);
+        extractor.set_storage(config.keyframe_storage.clone());
+        extractor.set_burst_sampling(config.burst_sampling.clone());
+        // Detectors are created per display as its segments arrive
+        SceneDetector::new(config.scene_detection.clone())?;
+        let mut metadata_collector = MetadataCollector::new()?;
+        let probe = SystemProbe::new(config.system_probe.clone());
+        metadata_collector.set_system_probe(probe.clone());
+        // One budget for every writer's buffers, so together they stay under it
+        let memory_budget = MemoryBudget::new(&config.writer_memory);
+        let mut csv_writer = CsvWriter::new(&config.output_dir)?;
+        csv_writer.set_memory_budget(memory_budget.clone());
+        let ledger = SegmentLedger::open_shared(config.ledger_path())?;
+        let dead_letters = DeadLetterStore::open(config.dead_letter_dir())?;
+        let snapshot = SnapshotTracker::new();
+        let health = HealthMonitor::new(config.health.clone(), Path::new(&config.output_dir), snapshot.clone(), ledger.clone());
+        health.set_system_probe(probe);
+        let calendar = Self::load_calendar(&config);
+        let dedup = FrameDeduplicator::new(config.frame_dedup.clone());
+        let ocr_quality = OcrQualityMonitor::new(config.ocr_quality.clone());
+        let capture_profiles = CaptureProfileTracker::new(config.capture_profile.clone());
+        let suppressions = config.suppression_list()?;
+        let privacy = PrivacyFilter::new(config.privacy.clone())?;
+        let sinks = output_sink::open_sinks(&config.output_sinks, &config.output_dir)?;
+        let embeddings = Self::embedding_store(&config.embeddings, &config.output_dir)?;
+        let storage = Self::storage_monitor(&config);
+        let audio = Self::audio_analyzer(&config.audio_analyzer)?;
+        let timeline = config.display_timeline.enabled.then(|| DisplayTimeline::new(&config.display_timeline));
+        let adaptive_fps = Self::adaptive_fps(&config);
+        let screen_classifier = Self::screen_classifier(&config);
+        let calibration = Self::load_calibration(&config);
+        let forms = config
+            .event_detection
+            .form_model
+            .enabled
+            .then(|| FormRecordParquetWriter::new(&config.forms_dir().to_string_lossy()))
+            .transpose()?;
+        let templates = Some(TemplateMatcher::new(config.template_matching.clone())?)
+            .filter(|matcher| !matcher.is_empty())
+            .map(Arc::new);
+        let triggers = Self::trigger_engine(&config, &suppressions)?;
+        let notifier = Self::webhook_notifier(&config, &suppressions)?;
+        let event_signer = config.event_signer()?;
+        let focus_summary = Self::focus_summary_generator(&config, &suppressions)?
+            .map(|generator| FocusSummarySchedule::new(generator, chrono::Local::now()))
+            .transpose()?;
+        let plugin_tables = Self::plugin_table_registry(&config)?;
+        let workflow_chains = config
+            .workflow_chains
+            .enabled
+            .then(|| WorkflowChainParquetWriter::new(&config.workflow_chains_dir().to_string_lossy()))
+            .transpose()?
+            .map(|writer| (WorkflowChainBuilder::new(config.workflow_chains.clone()), writer));
+        let error_recovery = config
+            .error_recovery
+            .enabled
+            .then(|| ErrorRecoveryParquetWriter::new(&config.error_recovery_dir().to_string_lossy()))
+            .transpose()?
+            .map(|writer| (ErrorRecoveryAnalyzer::new(config.error_recovery.clone()), writer));
+        let correlator = (workflow_chains.is_some() || error_recovery.is_some())
+            .then(|| CorrelationParquetWriter::new(&config.correlations_dir().to_string_lossy()))
+            .transpose()?
+            .map(|writer| (EventCorrelator::with_config(config.correlation.clone()), writer));
+        let retention = Self::retention_enforcer(&config, extractor.frames_root());
         
         Ok(Self {
             config,
             extractor,
-            detector,
+            detectors: HashMap::new(),
             metadata_collector,
             csv_writer,
+            memory_budget,
+            ledger,
+            dead_letters,
+            committed: BTreeSet::new(),
+            snapshot,
+            health,
+            calendar,
+            dedup,
+            ocr_quality,
+            capture_profiles,
+            suppressions,
+            templates,
+            dry_run: false,
+            config_watcher: None,
+            privacy,
+            sinks,
+            embeddings,
+            storage,
+            audio,
+            timeline,
+            adaptive_fps,
+            screen_classifier,
+            calibration,
+            forms,
+            triggers,
+            notifier,
+            event_signer,
+            focus_summary,
+            plugin_tables,
+            display_topology: None,
+            correlator,
+            workflow_chains,
+            error_recovery,
+            navigation: None,
+            retention,
+            display_detection: HashMap::new(),
+            retired_detection: Vec::new(),
+            build_failures: BuildFailureCorrelator::new(),
         })
     }
     
+    /// Apply edits to the watched config file while watching, without a restart
+    pub fn watch_config(&mut self, watcher: ConfigWatcher) {
+        self.config_watcher = Some(watcher);
+    }
+    
+    /// Apply a new, validated config to the running components.
+    ///
+    /// Learned state such as scene calibration and OCR baselines is kept.
+    /// Settings in [`RESTART_REQUIRED_SETTINGS`](config_watcher::RESTART_REQUIRED_SETTINGS)
+    /// keep their current values until the service restarts.
+    pub fn apply_config(&mut self, mut config: IndexerConfig) -> AnyhowResult<()> {
+        // Build everything that can fail first, so a bad config changes nothing
+        let templates = Some(TemplateMatcher::new(config.template_matching.clone())?)
+            .filter(|matcher| !matcher.is_empty())
+            .map(Arc::new);
+        SuppressionList::new(config.suppressions.clone())?;
+        // Outcomes of commands still running are lost with the old engine, so it is only replaced when the triggers changed
+        let triggers = (config.triggers != self.config.triggers && !self.dry_run)
+            .then(|| Self::trigger_engine(&config, &self.suppressions))
+            .transpose()?;
+        // Messages still being posted finish with the old notifier
+        let notifier = (config.notifiers != self.config.notifiers && !self.dry_run)
+            .then(|| Self::webhook_notifier(&config, &self.suppressions))
+            .transpose()?;
+        let focus_summary = (config.focus_summary != self.config.focus_summary && !self.dry_run)
+            .then(|| Self::focus_summary_generator(&config, &self.suppressions))
+            .transpose()?;
+        let privacy = PrivacyFilter::new(config.privacy.clone())?;
+        let audio = Self::audio_analyzer(&config.audio_analyzer)?;
+        // Reopening would start a new SQLite session file, so sinks are kept unless the list changed
+        let sinks = (config.output_sinks != self.config.output_sinks)
+            .then(|| output_sink::open_sinks(&config.output_sinks, &self.config.output_dir))
+            .transpose()?;
+        // Loading a local model is slow, so the store is only rebuilt when its settings changed
+        let embeddings = (config.embeddings != self.config.embeddings)
+            .then(|| Self::embedding_store(&config.embeddings, &self.config.output_dir))
+            .transpose()?;
+        
+        let ignored = config_watcher::restart_required_changes(&self.config, &config);
+        if !ignored.is_empty() {
+            warn!("Config changes to {} take effect after a restart", ignored.join(", "));
+        }
+        config.output_dir = self.config.output_dir.clone();
+        config.pending_ledger_path = self.config.pending_ledger_path.clone();
+        config.dead_letter_dir = self.config.dead_letter_dir.clone();
+        config.suppression_store_path = self.config.suppression_store_path.clone();
+        config.max_concurrent_processing = self.config.max_concurrent_processing;
+        config.auth = self.config.auth.clone();
+        config.logging = self.config.logging.clone();
+        config.health = self.config.health.clone();
+        config.file_watcher = self.config.file_watcher.clone();
+        config.plugin_tables = self.config.plugin_tables.clone();
+        config.correlation = self.config.correlation.clone();
+        config.workflow_chains = self.config.workflow_chains.clone();
+        config.error_recovery = self.config.error_recovery.clone();
+        config.navigation_tracking = self.config.navigation_tracking;
+        config.encryption.sign_events = self.config.encryption.sign_events;
+        
+        for detector in self.detectors.values_mut() {
+            detector.update_config(config.scene_detection.clone());
+        }
+        self.retired_detection.extend(self.display_detection.drain());
+        self.dedup.update_config(config.frame_dedup.clone());
+        self.extractor.set_identical_frame_filter(config.frame_dedup.skip_identical_frames, config.frame_dedup.identical_keep_secs);
+        self.extractor.set_storage(config.keyframe_storage.clone());
+        self.extractor.set_burst_sampling(config.burst_sampling.clone());
+        self.ocr_quality.update_config(config.ocr_quality.clone());
+        self.capture_profiles.update_config(config.capture_profile.clone());
+        self.suppressions.replace_config_rules(config.suppressions.clone())?;
+        self.templates = templates;
+        self.privacy = privacy;
+        self.audio = audio;
+        if let Some(sinks) = sinks {
+            self.sinks = sinks;
+        }
+        if let Some(embeddings) = embeddings {
+            self.embeddings = embeddings;
+        }
+        if let Some(triggers) = triggers {
+            self.triggers = triggers;
+        }
+        if let Some(notifier) = notifier {
+            self.notifier = notifier;
+        }
+        // Events collected for today's note are kept across setting changes
+        match (self.focus_summary.as_mut(), focus_summary.flatten()) {
+            (Some(schedule), Some(generator)) => schedule.replace_generator(generator, chrono::Local::now())?,
+            (None, Some(generator)) => self.focus_summary = Some(FocusSummarySchedule::new(generator, chrono::Local::now())?),
+            (_, None) if !config.focus_summary.enabled => self.focus_summary = None,
+            (_, None) => {}
+        }
+        if config.system_probe != self.config.system_probe {
+            let probe = SystemProbe::new(config.system_probe.clone());
+            self.metadata_collector.set_system_probe(probe.clone());
+            self.health.set_system_probe(probe);
+        }
+        // Keep what the session has written so far unless monitoring is switched on or off
+        match (self.storage.as_mut(), config.storage_monitor.enabled && !self.dry_run) {
+            (Some(monitor), true) if config.system_probe == self.config.system_probe => {
+                monitor.update_config(config.storage_monitor.clone())
+            }
+            _ => self.storage = Self::storage_monitor(&config).filter(|_| !self.dry_run),
+        }
+        match (self.timeline.as_mut(), config.display_timeline.enabled && !self.dry_run) {
+            (Some(timeline), true) => timeline.update_config(&config.display_timeline),
+            (None, true) => self.timeline = Some(DisplayTimeline::new(&config.display_timeline)),
+            (Some(timeline), false) => {
+                warn!("Display timeline disabled; {} entries not yet merged are dropped", timeline.held_back());
+                self.timeline = None;
+            }
+            (None, false) => {}
+        }
+        // Learned rates are kept unless tuning is switched on or off
+        match (self.adaptive_fps.as_mut(), config.adaptive_fps.enabled) {
+            (Some(adaptive), true) => adaptive.update_config(config.adaptive_fps.clone(), config.extraction_fps),
+            _ => self.adaptive_fps = Self::adaptive_fps(&config),
+        }
+        if let Some(navigation) = &mut self.navigation {
+            navigation.update_config(Self::navigation_config(&config));
+        }
+        if config.retention != self.config.retention && !self.dry_run {
+            self.retention = Self::retention_enforcer(&config, self.extractor.frames_root());
+        }
+        self.screen_classifier = Self::screen_classifier(&config);
+        self.calibration = Self::load_calibration(&config);
+        self.config = config;
+        self.calendar = Self::load_calendar(&self.config);
+        
+        info!("Applied updated configuration");
+        Ok(())
+    }
+    
+    /// Reload the config file if it changed; an invalid edit is logged and ignored
+    fn reload_config_if_changed(&mut self) {
+        let Some(watcher) = self.config_watcher.as_mut() else {
+            return;
+        };
+        let config = match watcher.check() {
+            Ok(Some(config)) => config,
+            Ok(None) => return,
+            Err(e) => {
+                error!("Ignoring invalid config in {}: {}", watcher.path().display(), e);
+                return;
+            }
+        };
+        if let Err(e) = self.apply_config(config) {
+            error!("Failed to apply updated config: {}", e);
+        }
+    }
+    
+    /// Run the full pipeline without writing outputs, estimating their size instead.
+    ///
+    /// Keyframes are still extracted for analysis, into `scratch_dir`, and each
+    /// segment's keyframes are deleted once it has been analyzed.
+    pub fn enable_dry_run(&mut self, scratch_dir: &Path) {
+        self.extractor.set_frames_root(scratch_dir.join("frames"));
+        self.dry_run = true;
+        self.storage = None;
+        self.triggers = None;
+        self.notifier = None;
+        self.focus_summary = None;
+        self.timeline = None;
+        self.retention = None;
+    }
+    
+    /// Scene detector of one display, created on its first segment
+    fn scene_detector(&mut self, monitor_id: i32) -> Result<&SceneDetector> {
+        match self.detectors.entry(monitor_id) {
+            hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+            hash_map::Entry::Vacant(entry) => Ok(entry.insert(SceneDetector::new(self.config.scene_detection.clone())?)),
+        }
+    }
+    
+    fn audio_analyzer(config: &AudioAnalyzerConfig) -> AnyhowResult<Option<AudioAnalyzer>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Ok(Some(AudioAnalyzer::new(config.clone())?))
+    }
+    
+    fn storage_monitor(config: &IndexerConfig) -> Option<StorageMonitor> {
+        config.storage_monitor.enabled.then(|| {
+            StorageMonitor::new(
+                config.storage_monitor.clone(),
+                &config.output_dir,
+                SystemProbe::new(config.system_probe.clone()),
+            )
+        })
+    }
+    
+    /// Extraction rate from the config, reduced while storage is low
+    fn adaptive_fps(config: &IndexerConfig) -> Option<AdaptiveFps> {
+        config
+            .adaptive_fps
+            .enabled
+            .then(|| AdaptiveFps::new(config.adaptive_fps.clone(), config.extraction_fps))
+    }
+    
+    fn screen_classifier(config: &IndexerConfig) -> Option<ScreenClassifier> {
+        config
+            .screen_classifier
+            .enabled
+            .then(|| ScreenClassifier::new(config.screen_classifier.clone()))
+    }
+    
+    /// Keyframe rate for a display: its adaptive rate when tuning, else `configured`,
+    /// capped while storage is low
+    fn extraction_rate(&self, monitor_id: i32, configured: f32) -> f32 {
+        let fps = self.adaptive_fps.as_ref().map_or(configured, |a| a.rate(monitor_id));
+        match self.storage.as_ref().filter(|m| m.level() >= StorageLevel::ReducedRate) {
+            Some(monitor) => monitor.config().reduced_fps.min(fps),
+            None => fps,
+        }
+    }
+    
+    /// Re-check storage and apply a new level, recording the change as an
+    /// event; returns whether processing is paused
+    async fn check_storage(&mut self) -> bool {
+        let Some(monitor) = self.storage.as_mut() else {
+            return false;
+        };
+        let change = monitor.check().await;
+        let paused = monitor.level() == StorageLevel::Paused;
+        if let Some(change) = change {
+            if change.to > change.from {
+                warn!("Low on storage; {}", change);
+            } else {
+                info!("Storage recovered; {}", change);
+            }
+            if let Err(e) = self.publish_events(&[change.to_event(chrono::Utc::now())]).await {
+                warn!("Failed to record storage change: {}", e);
+            }
+        }
+        paused
+    }
+    
+    /// Once storage has recovered from a pause, process the segments that
+    /// were left queued meanwhile
+    async fn resume_after_storage_pause(&mut self) -> AnyhowResult<()> {
+        let was_paused = self.storage.as_ref().is_some_and(|m| m.level() == StorageLevel::Paused);
+        if !was_paused || self.check_storage().await {
+            return Ok(());
+        }
+        let pending = self.lock_ledger()?.pending_segments();
+        info!("Processing {} segments queued while paused", pending.len());
+        for video_path in pending {
+            self.process_and_ack(&video_path).await?;
+        }
+        Ok(())
+    }
+    
+    fn trigger_engine(config: &IndexerConfig, suppressions: &SuppressionList) -> AnyhowResult<Option<TriggerEngine>> {
+        if !config.triggers.iter().any(|t| t.enabled) {
+            return Ok(None);
+        }
+        Ok(Some(TriggerEngine::new(config.triggers.clone())?.with_suppressions(suppressions.clone())))
+    }
+    
+    fn webhook_notifier(config: &IndexerConfig, suppressions: &SuppressionList) -> AnyhowResult<Option<WebhookNotifier>> {
+        if !config.notifiers.iter().any(|n| n.enabled) {
+            return Ok(None);
+        }
+        Ok(Some(WebhookNotifier::new(config.notifiers.clone())?.with_suppressions(suppressions.clone())))
+    }
+    
+    /// Look up the attached displays, so dialogs in full-desktop captures are
+    /// judged per display; segments are analyzed without it if that fails
+    async fn enumerate_displays(&mut self) {
+        let probe = SystemProbe::new(self.config.system_probe.clone());
+        match DisplayTopology::enumerate(&probe).await {
+            Ok(topology) => {
+                info!("Found {} attached displays", topology.displays.len());
+                self.display_topology = Some(topology);
+            }
+            Err(e) => warn!("Display layout unavailable; analyzing each capture as one display: {}", e),
+        }
+    }
+    
+    /// Stored plugin tables plus those of the configured manifests
+    fn plugin_table_registry(config: &IndexerConfig) -> AnyhowResult<PluginTableRegistry> {
+        let mut registry = PluginTableRegistry::open(&config.plugin_tables_dir().to_string_lossy())?;
+        for manifest in &config.plugin_tables.manifests {
+            registry.register_manifest(&manifest.plugin, Path::new(&manifest.path))?;
+        }
+        Ok(registry)
+    }
+    
+    /// Tables of detector plugins; in-process plugins register and write through it
+    pub fn plugin_tables(&mut self) -> &mut PluginTableRegistry {
+        &mut self.plugin_tables
+    }
+    
+    fn focus_summary_generator(config: &IndexerConfig, suppressions: &SuppressionList) -> AnyhowResult<Option<FocusSummaryGenerator>> {
+        if !config.focus_summary.enabled {
+            return Ok(None);
+        }
+        let generator = FocusSummaryGenerator::new(config.focus_summary.clone(), &config.output_dir)?
+            .with_system_probe(SystemProbe::new(config.system_probe.clone()))
+            .with_suppressions(suppressions.clone());
+        Ok(Some(generator))
+    }
+    
+    /// Write the daily focus summary once its time of day has passed
+    async fn run_focus_summary_if_due(&mut self) {
+        let Some(schedule) = self.focus_summary.as_mut() else {
+            return;
+        };
+        if let Err(e) = schedule.run_if_due(chrono::Local::now()).await {
+            warn!("Failed to generate focus summary: {}", e);
+        }
+    }
+    
+    fn retention_enforcer(config: &IndexerConfig, frames_root: &Path) -> Option<RetentionEnforcer> {
+        config.retention.enabled.then(|| {
+            RetentionEnforcer::new(
+                config.retention.clone(),
+                &config.output_dir,
+                config.ocr_results_dir(),
+                config.navigation_dir(),
+                frames_root,
+            )
+            .with_stores(config.embeddings.dir(&config.output_dir), config.forms_dir())
+        })
+    }
+    
+    /// Delete keyframes and outputs past their retention, at most once a day
+    async fn enforce_retention_if_due(&mut self) {
+        let now = chrono::Utc::now();
+        let Some(retention) = self.retention.as_mut().filter(|retention| retention.is_due(now)) else {
+            return;
+        };
+        retention.mark_run(now);
+        let retention = retention.clone();
+        match tokio::task::spawn_blocking(move || retention.enforce(now)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Failed to enforce retention: {}", e),
+            Err(e) => warn!("Retention task failed: {}", e),
+        }
+    }
+    
+    fn navigation_config(config: &IndexerConfig) -> NavigationIntegrationConfig {
+        NavigationIntegrationConfig {
+            navigation_config: config.navigation.clone(),
+            cursor_config: config.cursor.clone(),
+            correlation_config: config.correlation.clone(),
+            system_probe: config.system_probe.clone(),
+            ..NavigationIntegrationConfig::default()
+        }
+    }
+    
+    /// Start live navigation tracking when `navigation_tracking` is set
+    fn start_navigation_tracking(&mut self) {
+        if !self.config.navigation_tracking || self.dry_run {
+            return;
+        }
+        let dir = self.config.navigation_dir();
+        let navigation = NavigationIntegrationService::with_config(&dir.to_string_lossy(), Self::navigation_config(&self.config))
+            .and_then(|mut navigation| {
+                if let Some(signer) = self.config.event_signer()? {
+                    navigation.enable_event_signing(signer);
+                }
+                let correlations = CorrelationParquetWriter::new(&self.config.correlations_dir().to_string_lossy())?;
+                Ok(navigation.with_correlation_writer(correlations))
+            });
+        match navigation {
+            Ok(mut navigation) => {
+                navigation.set_memory_budget(self.memory_budget.clone());
+                navigation.set_parquet_tuning(self.config.parquet_tuning.clone());
+                if let Some(topology) = &self.display_topology {
+                    navigation.set_display_topology(topology.clone());
+                }
+                self.snapshot.set_detector_active("navigation_detector", true);
+                self.snapshot.set_detector_active("cursor_tracker", true);
+                self.navigation = Some(navigation);
+            }
+            Err(e) => warn!("Navigation tracking disabled: {}", e),
+        }
+    }
+    
+    /// Read the live window, tab and cursor state and publish the events it
+    /// produced, so they reach the sinks, triggers and focus summary
+    async fn track_navigation(&mut self) {
+        let Some(navigation) = self.navigation.as_mut() else {
+            return;
+        };
+        let now = chrono::Utc::now();
+        let frame_id = format!("live_{}", now.timestamp_millis());
+        match navigation.process_frame(&frame_id, now).await {
+            Ok(result) if !result.detected_events.is_empty() => {
+                if let Err(e) = self.publish_events(&result.detected_events).await {
+                    warn!("Failed to record navigation events: {}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Navigation tracking failed: {}", e),
+        }
+    }
+    
+    fn embedding_store(config: &EmbeddingsConfig, output_dir: &str) -> AnyhowResult<Option<Arc<EmbeddingStore>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Ok(Some(Arc::new(config.store(output_dir)?)))
+    }
+    
+    fn load_calendar(config: &IndexerConfig) -> Option<CalendarContext> {
+        let ics_path = config.calendar.ics_path.as_ref().filter(|_| config.calendar.enabled)?;
+        match CalendarContext::load_ics(ics_path) {
+            Ok(calendar) => Some(calendar),
+            Err(e) => {
+                warn!("Calendar enrichment disabled: {}", e);
+                None
+            }
+        }
+    }
+    
+    fn load_calibration(config: &IndexerConfig) -> Option<Calibration> {
+        if !config.calibration.enabled {
+            return None;
+        }
+        match Calibration::load(&config.calibration_path()) {
+            Ok(calibration) => calibration,
+            Err(e) => {
+                warn!("Confidence calibration disabled: {}", e);
+                None
+            }
+        }
+    }
+    
+    /// Shared handle to the live activity snapshot
+    pub fn snapshot_tracker(&self) -> SnapshotTracker {
+        self.snapshot.clone()
+    }
+    
+    /// Shared handle to the health and readiness status
+    pub fn health_monitor(&self) -> HealthMonitor {
+        self.health.clone()
+    }
+    
+    /// Serve the health endpoints when `health` is enabled
+    async fn serve_health(&self) -> AnyhowResult<Option<tokio::task::JoinHandle<()>>> {
+        if !self.config.health.enabled {
+            return Ok(None);
+        }
+        let (_, server) = health::serve(self.health.clone(), &self.config.health.bind_address).await?;
+        Ok(Some(server))
+    }
+    
+    /// Shared suppression rules, for alerting, reporting and the control API
+    pub fn suppression_list(&self) -> SuppressionList {
+        self.suppressions.clone()
+    }
+    
+    /// Watch `watch_dir` until Ctrl+C or SIGTERM, then shut down cleanly
     pub async fn start_watching(&mut self, watch_dir: &str) -> AnyhowResult<()> {
+        self.start_watching_until(watch_dir, ShutdownSignal::listen()?).await
+    }
+    
+    /// Watch `watch_dir` until `shutdown` is requested.
+    ///
+    /// The segment being processed when shutdown is requested is finished;
+    /// segments queued behind it stay in the ledger and are replayed on the
+    /// next start. Batched output is flushed before returning.
+    pub async fn start_watching_until(&mut self, watch_dir: &str, mut shutdown: ShutdownSignal) -> AnyhowResult<()> {
         let (tx, mut rx) = mpsc::channel(100);
-        let mut file_watcher = FileWatcher::new(watch_dir, tx)?;
+        let mut file_watcher = FileWatcher::new(watch_dir, tx)?
+            .with_ledger(self.ledger.clone())
+            .with_config(self.config.file_watcher.clone())
+            .with_state_path(self.config.watcher_state_path());
         
         info!("Starting file watcher for directory: {}", watch_dir);
-        file_watcher.start().await?;
+        self.enumerate_displays().await;
+        let health_server = self.serve_health().await?;
+        self.health.set_expect_watcher(true);
+        let snapshot = self.snapshot.clone();
+        snapshot.set_detector_active("file_watcher", true);
+        snapshot.set_detector_active("scene_detector", true);
+        let watcher_task = tokio::spawn(async move {
+            if let Err(e) = file_watcher.start().await {
+                error!("File watcher stopped: {}", e);
+            }
+            snapshot.set_detector_active("file_watcher", false);
+        });
         
-        while let Some(video_path) = rx.recv().await {
-            if let Err(e) = self.process_video_segment(&video_path).await {
+        // Replay segments that were queued but not acknowledged before the last shutdown
+        let pending = self.lock_ledger()?.pending_segments();
+        if !pending.is_empty() {
+            info!("Replaying {} pending video segments from ledger", pending.len());
+        }
+        for video_path in pending {
+            if shutdown.is_requested() {
+                break;
+            }
+            if !video_path.exists() {
+                warn!("Pending video segment no longer exists: {}", video_path.display());
+                self.lock_ledger()?.ack(&video_path)?;
+                continue;
+            }
+            self.process_and_ack(&video_path).await?;
+        }
+        
+        let mut config_poll = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        while !shutdown.is_requested() {
+            tokio::select! {
+                // A request that came in during the last segment wins over the next one
+                biased;
+                _ = shutdown.requested() => break,
+                video_path = rx.recv() => match video_path {
+                    Some(video_path) => self.process_and_ack(&video_path).await?,
+                    None => break,
+                },
+                _ = config_poll.tick() => {
+                    self.reload_config_if_changed();
+                    self.resume_after_storage_pause().await?;
+                    self.run_focus_summary_if_due().await;
+                    self.enforce_retention_if_due().await;
+                }
+            }
+        }
+        
+        // Dropping the watcher stops new segments from being queued
+        watcher_task.abort();
+        self.health.set_expect_watcher(false);
+        self.snapshot.set_detector_active("file_watcher", false);
+        self.snapshot.set_detector_active("scene_detector", false);
+        let result = self.shutdown().await;
+        if let Some(server) = health_server {
+            server.abort();
+        }
+        result
+    }
+    
+    /// Capture the screen directly and process it segment by segment until
+    /// shutdown is requested; no video files are written in between
+    pub async fn start_capture(&mut self, mut shutdown: ShutdownSignal) -> AnyhowResult<()> {
+        let config = self.config.capture.clone();
+        // Sources fail to open without screen recording permission
+        let sources = capture::screen_sources(&config);
+        self.health.set_permission("screen_recording", sources.is_ok());
+        let mut sources = sources?;
+        let mut segmenters: HashMap<u32, CaptureSegmenter> = sources
+            .iter()
+            .map(|source| {
+                let display_id = source.display_id();
+                let mut segmenter = CaptureSegmenter::new(&config, display_id, self.extractor.frames_root());
+                segmenter.set_storage(self.extractor.storage().clone());
+                segmenter.set_burst_sampling(self.config.burst_sampling.clone());
+                segmenter.set_rate(self.extraction_rate(display_id as i32, config.fps));
+                (display_id, segmenter)
+            })
+            .collect();
+        // Frames withheld by the privacy filter, by display, until an allowed frame follows
+        let mut withheld: HashMap<u32, RedactedInterval> = HashMap::new();
+        // All displays feed one channel; segments are processed as each display's closes
+        let (tx, mut frames) = mpsc::channel(config.channel_capacity.max(1) * sources.len());
+        for source in &mut sources {
+            source.start(tx.clone())?;
+            info!("Capturing display {} at {} fps", source.display_id(), config.fps);
+        }
+        drop(tx);
+        self.enumerate_displays().await;
+        self.start_navigation_tracking();
+        let health_server = self.serve_health().await?;
+        self.health.set_expect_watcher(true);
+        self.snapshot.set_detector_active("screen_capture", true);
+        self.snapshot.set_detector_active("scene_detector", true);
+        
+        let mut config_poll = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        let mut navigation_poll = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        while !shutdown.is_requested() {
+            tokio::select! {
+                biased;
+                _ = shutdown.requested() => break,
+                frame = frames.recv() => {
+                    let Some(frame) = frame else {
+                        warn!("Screen capture stopped delivering frames");
+                        self.snapshot.set_detector_active("screen_capture", false);
+                        break;
+                    };
+                    if !segmenters.contains_key(&frame.display_id) {
+                        continue;
+                    }
+                    // Frames of a blocked app or site are never written
+                    if self.blocked_on_screen().await.is_some() {
+                        let interval = withheld.entry(frame.display_id).or_insert_with(|| RedactedInterval {
+                            segment_id: format!("capture_monitor{}_{}", frame.display_id, frame.timestamp.format("%Y%m%d_%H%M%S")),
+                            monitor_id: frame.display_id as i32,
+                            start: frame.timestamp,
+                            end: frame.timestamp,
+                            frame_count: 0,
+                        });
+                        interval.end = frame.timestamp;
+                        interval.frame_count += 1;
+                        continue;
+                    }
+                    if let Some(interval) = withheld.remove(&frame.display_id) {
+                        self.record_redacted_intervals(&[interval]).await;
+                    }
+                    let Some(segmenter) = segmenters.get_mut(&frame.display_id) else {
+                        continue;
+                    };
+                    match segmenter.push(&frame) {
+                        Ok(Some(segment)) => {
+                            self.process_captured(&segment).await;
+                            segmenter.set_rate(self.extraction_rate(frame.display_id as i32, config.fps));
+                        }
+                        Ok(None) => {}
+                        Err(e) => warn!("Failed to save frame captured from display {}: {}", frame.display_id, e),
+                    }
+                }
+                _ = config_poll.tick() => {
+                    self.reload_config_if_changed();
+                    self.run_focus_summary_if_due().await;
+                    self.enforce_retention_if_due().await;
+                }
+                _ = navigation_poll.tick() => self.track_navigation().await,
+            }
+        }
+        
+        for source in &mut sources {
+            if let Err(e) = source.stop() {
+                warn!("Failed to stop capturing display {}: {}", source.display_id(), e);
+            }
+        }
+        // Segments in progress are kept rather than dropped
+        for segment in segmenters.values_mut().filter_map(CaptureSegmenter::finish) {
+            self.process_captured(&segment).await;
+        }
+        let withheld: Vec<RedactedInterval> = withheld.into_values().collect();
+        if !withheld.is_empty() {
+            self.record_redacted_intervals(&withheld).await;
+        }
+        self.health.set_expect_watcher(false);
+        self.snapshot.set_detector_active("screen_capture", false);
+        self.snapshot.set_detector_active("scene_detector", false);
+        let result = self.shutdown().await;
+        if let Some(server) = health_server {
+            server.abort();
+        }
+        result
+    }
+    
+    async fn process_captured(&mut self, segment: &CapturedSegment) {
+        // Its keyframes stay on disk, so a failed segment is kept by its directory
+        let frames_dir = segment
+            .keyframes
+            .first()
+            .and_then(|k| Path::new(&k.frame_path).parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        self.begin_item(&frames_dir);
+        match self.process_captured_segment(segment).await {
+            Ok(()) => {
+                self.health.record_segment(&segment.segment_id);
+                self.item_done(&frames_dir);
+            }
+            Err(e) => {
+                error!("Failed to process captured segment {}: {}", segment.segment_id, e);
+                self.dead_letter(&frames_dir, FailedItemKind::CapturedSegment, &e);
+            }
+        }
+    }
+    
+    /// Flush batched output, finish the sinks and compact the ledger.
+    ///
+    /// Called when watching stops; one-shot runs call it once they are done.
+    pub async fn shutdown(&mut self) -> AnyhowResult<()> {
+        if let Some(mut navigation) = self.navigation.take() {
+            navigation.finalize().await?;
+            self.snapshot.set_detector_active("navigation_detector", false);
+            self.snapshot.set_detector_active("cursor_tracker", false);
+        }
+        let (held_events, held_forms) = self.finish_detection();
+        if !self.dry_run {
+            if !held_events.is_empty() {
+                self.publish_events(&held_events).await?;
+            }
+            self.write_form_records(&held_forms).await?;
+        }
+        // Outcomes of trigger commands still running are stored with the other events
+        if let Some(triggers) = &self.triggers {
+            triggers.wait_idle().await;
+            let mut follow_ups = triggers.drain_follow_up_events();
+            if !follow_ups.is_empty() {
+                self.sign_events(&mut follow_ups)?;
+                self.write_events_to_sinks(&follow_ups)?;
+            }
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.wait_idle().await;
+        }
+        self.csv_writer.finalize().await?;
+        // Nothing more will arrive, so entries waiting for other displays are written as they are
+        let remaining = self.timeline.as_mut().map(DisplayTimeline::finish).unwrap_or_default();
+        if !remaining.is_empty() {
+            self.csv_writer.write_timeline(&remaining).await?;
+        }
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.finish() {
+                error!("Failed to finish {} output: {}", sink.name(), e);
+            }
+        }
+        if let Some(forms) = &mut self.forms {
+            forms.finalize().await?;
+        }
+        // Chains still open can no longer grow
+        if let Some((builder, writer)) = &mut self.workflow_chains {
+            writer.write_chains(&builder.flush()).await?;
+            writer.finalize().await?;
+        }
+        if let Some((analyzer, writer)) = &mut self.error_recovery {
+            writer.write_sequences(&analyzer.flush()).await?;
+            writer.finalize().await?;
+        }
+        if let Some((correlator, writer)) = &mut self.correlator {
+            writer.finalize().await?;
+            if let Err(e) = correlator.persist_patterns() {
+                warn!("Failed to save correlation patterns: {}", e);
+            }
+        }
+        self.plugin_tables.flush().await?;
+        self.dead_letters.compact()?;
+        let mut ledger = self.lock_ledger()?;
+        ledger.compact()?;
+        info!("Output flushed; {} segments left for the next start", ledger.pending_count());
+        Ok(())
+    }
+    
+    /// Number of segments recorded in the ledger that have not been processed yet
+    pub fn pending_segment_count(&self) -> AnyhowResult<usize> {
+        Ok(self.lock_ledger()?.pending_count())
+    }
+    
+    /// Process every segment and image under `dir` once, without watching.
+    ///
+    /// Scrolling is detected between segment keyframes; with an OCR store, events
+    /// are also detected from the stored OCR text of each image and keyframe.
+    /// Failures are collected rather than aborting.
+    pub async fn analyze_directory(
+        &mut self,
+        dir: &Path,
+        ocr_store: Option<&OCRParquetWriter>,
+    ) -> AnyhowResult<BatchSummary> {
+        self.analyze_archive(dir, ocr_store, &BatchOptions::default()).await
+    }
+    
+    /// Reprocess an archive of recordings, e.g. to backfill after changing
+    /// detection settings.
+    ///
+    /// Files are processed in path order, limited to the `since`/`until`
+    /// window; with a checkpoint, finished files are recorded as they
+    /// complete and skipped when the run is resumed.
+    pub async fn analyze_archive(
+        &mut self,
+        dir: &Path,
+        ocr_store: Option<&OCRParquetWriter>,
+        options: &BatchOptions,
+    ) -> AnyhowResult<BatchSummary> {
+        let started = std::time::Instant::now();
+        let image_analyzer = ImageAnalyzer::new(&self.config)?;
+        let mut summary = BatchSummary { dry_run: self.dry_run, ..BatchSummary::default() };
+        // A dry run must not record files as done
+        let mut checkpoint = options
+            .checkpoint
+            .as_ref()
+            .filter(|_| !self.dry_run)
+            .map(|path| BatchCheckpoint::open(path, options.resume))
+            .transpose()?;
+        
+        let mut inputs = Vec::new();
+        for (path, kind) in batch_analysis::discover_inputs(dir, &self.config.video_extensions)? {
+            if !options.includes(&path)? {
+                summary.filtered_out += 1;
+            } else if checkpoint.as_ref().is_some_and(|c| c.is_done(&path)) {
+                summary.already_done += 1;
+            } else {
+                inputs.push((path, kind));
+            }
+        }
+        info!(
+            "Analyzing {} files in {} ({} outside the time range, {} already done)",
+            inputs.len(), dir.display(), summary.filtered_out, summary.already_done
+        );
+        
+        let total = inputs.len();
+        for (index, input) in inputs.into_iter().enumerate() {
+            // Finished files are already checkpointed, so a resumed run picks up here
+            if options.shutdown.as_ref().is_some_and(|s| s.is_requested()) {
+                info!("Stopping after {} of {} files", index, total);
+                summary.interrupted = true;
+                break;
+            }
+            if self.check_storage().await {
+                warn!("Stopping after {} of {} files; too little storage left to continue", index, total);
+                summary.interrupted = true;
+                break;
+            }
+            let path = input.0.clone();
+            let failures = summary.failures.len();
+            self.analyze_input(input, ocr_store, &image_analyzer, &mut summary).await;
+            
+            if let Some(checkpoint) = checkpoint.as_mut().filter(|_| summary.failures.len() == failures) {
+                checkpoint.mark_done(&path)?;
+            }
+            
+            let elapsed = started.elapsed();
+            let remaining = elapsed.mul_f64((total - index - 1) as f64 / (index + 1) as f64);
+            info!(
+                "[{}/{}] {:.0}% done, about {}s left: {}",
+                index + 1, total, (index + 1) as f64 * 100.0 / total as f64, remaining.as_secs(), path.display()
+            );
+        }
+        
+        summary.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(summary)
+    }
+    
+    /// Process a single segment or image once, or everything under a directory
+    /// as [`analyze_archive`](Self::analyze_archive) does
+    pub async fn analyze_path(
+        &mut self,
+        path: &Path,
+        ocr_store: Option<&OCRParquetWriter>,
+        options: &BatchOptions,
+    ) -> AnyhowResult<BatchSummary> {
+        if path.is_dir() {
+            return self.analyze_archive(path, ocr_store, options).await;
+        }
+        let kind = batch_analysis::classify_input(path, &self.config.video_extensions)
+            .ok_or_else(|| anyhow::anyhow!("Not a video segment or image: {}", path.display()))?;
+        
+        let started = std::time::Instant::now();
+        let image_analyzer = ImageAnalyzer::new(&self.config)?;
+        let mut summary = BatchSummary { dry_run: self.dry_run, ..BatchSummary::default() };
+        self.analyze_input((path.to_path_buf(), kind), ocr_store, &image_analyzer, &mut summary).await;
+        summary.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(summary)
+    }
+    
+    /// Process one file, recording its results or failure in `summary`
+    async fn analyze_input(
+        &mut self,
+        (path, kind): (PathBuf, BatchInputKind),
+        ocr_store: Option<&OCRParquetWriter>,
+        image_analyzer: &ImageAnalyzer,
+        summary: &mut BatchSummary,
+    ) {
+        self.begin_item(&path);
+        let failures = summary.failures.len();
+        match kind {
+            BatchInputKind::Segment => match self.process_video_segment(&path, ocr_store).await {
+                Ok(segment) => {
+                    summary.segments_processed += 1;
+                    summary.keyframes += segment.frame_metadata.len();
+                    summary.scene_changes += segment.scene_changes;
+                    summary.ocr_quality_regressions += segment.quality_regressions;
+                    summary.estimated_storage_bytes += segment.estimated_bytes;
+                    summary.redacted_frames += segment.redacted_frames;
+                    match self.detect_segment_events(&segment).await {
+                        Ok((events, forms)) => {
+                            summary.record_events(&events);
+                            if self.dry_run {
+                                summary.estimated_storage_bytes += batch_analysis::estimated_event_bytes(&events);
+                            } else if let Err(e) = self.publish_events(&events).await {
+                                self.record_input_failure(&path, kind, e.into(), summary);
+                            } else if let Err(e) = self.write_form_records(&forms).await {
+                                self.record_input_failure(&path, kind, e.into(), summary);
+                            }
+                        }
+                        Err(e) => self.record_input_failure(&path, kind, e.into(), summary),
+                    }
+                    if self.dry_run {
+                        Self::remove_scratch_keyframes(&segment);
+                    }
+                }
+                Err(e) => self.record_input_failure(&path, kind, e.into(), summary),
+            },
+            BatchInputKind::Image => {
+                let path_str = path.to_string_lossy();
+                let ocr = match ocr_store {
+                    Some(store) => match batch_analysis::stored_ocr_for_image(store, &path_str).await {
+                        Ok(results) => Some(results),
+                        Err(e) => {
+                            let e = IndexerError::ocr("Failed to read stored OCR results").caused_by(e).for_frame(&path_str);
+                            self.record_input_failure(&path, kind, e.into(), summary);
+                            return;
+                        }
+                    },
+                    None => None,
+                };
+                let analysis = image_analyzer
+                    .analyze(&path_str, ocr)
+                    .instrument(info_span!("event_detect", frame_id = %path_str))
+                    .await
+                    .for_frame(&path_str);
+                match analysis {
+                    Ok(analysis) if analysis.redacted => {
+                        summary.redacted_frames += 1;
+                        if !self.dry_run {
+                            self.record_redacted_image(&analysis).await;
+                        }
+                    }
+                    Ok(analysis) => {
+                        summary.images_analyzed += 1;
+                        summary.record_events(&analysis.events);
+                        if self.dry_run {
+                            summary.estimated_storage_bytes += batch_analysis::estimated_event_bytes(&analysis.events);
+                        } else if let Err(e) = self.publish_events(&analysis.events).await {
+                            self.record_input_failure(&path, kind, e.into(), summary);
+                        }
+                    }
+                    Err(e) => self.record_input_failure(&path, kind, e.into(), summary),
+                }
+            }
+        }
+        if summary.failures.len() == failures {
+            self.item_done(&path);
+        }
+    }
+    
+    /// Record intervals withheld outside of a segment; a failure only loses the marker
+    async fn record_redacted_intervals(&mut self, intervals: &[RedactedInterval]) {
+        if let Err(e) = self.csv_writer.write_redacted_intervals(intervals).await {
+            warn!("Failed to record {} redacted intervals: {}", intervals.len(), e);
+        }
+    }
+    
+    /// Leave only the time of a screenshot blocked by the privacy filter
+    async fn record_redacted_image(&mut self, analysis: &ImageAnalysis) {
+        let interval = RedactedInterval {
+            segment_id: analysis.frame_id.clone(),
+            monitor_id: 0,
+            start: analysis.timestamp,
+            end: analysis.timestamp,
+            frame_count: 1,
+        };
+        if let Err(e) = self.csv_writer.write_redacted_intervals(&[interval]).await {
+            warn!("Failed to record redacted screenshot {}: {}", analysis.path, e);
+        }
+    }
+    
+    /// Note a file's failure in the batch summary and the dead-letter store
+    fn record_input_failure(&mut self, path: &Path, kind: BatchInputKind, error: anyhow::Error, summary: &mut BatchSummary) {
+        let item_kind = match kind {
+            BatchInputKind::Segment => FailedItemKind::Segment,
+            BatchInputKind::Image => FailedItemKind::Frame,
+        };
+        self.dead_letter(path, item_kind, &error);
+        summary.record_failure(path, error);
+    }
+    
+    /// Write a segment's frame metadata and redacted intervals to CSV, and its
+    /// frames and OCR results to the configured sinks
+    async fn write_segment_outputs(
+        &mut self,
+        frame_metadata: &[metadata_collector::FrameMetadata],
+        frame_ocr: &HashMap<String, Vec<OCRResult>>,
+        redacted: &[RedactedInterval],
+    ) -> AnyhowResult<()> {
+        // Outputs an earlier attempt at the segment wrote are skipped
+        if !self.committed.contains(&OutputStep::FrameRows) {
+            self.csv_writer.write_frame_metadata(frame_metadata).await?;
+            self.committed.insert(OutputStep::FrameRows);
+        }
+        if !redacted.is_empty() && !self.committed.contains(&OutputStep::RedactedIntervals) {
+            self.csv_writer.write_redacted_intervals(redacted).await?;
+            self.committed.insert(OutputStep::RedactedIntervals);
+        }
+        let ocr_results: Vec<OCRResult> = frame_ocr.values().flatten().cloned().collect();
+        for (index, sink) in self.sinks.iter_mut().enumerate() {
+            if !self.committed.contains(&OutputStep::SinkFrames(index)) {
+                sink.write_frames(frame_metadata)?;
+                self.committed.insert(OutputStep::SinkFrames(index));
+            }
+            if !self.committed.contains(&OutputStep::SinkOcr(index)) {
+                sink.write_ocr(&ocr_results)?;
+                self.committed.insert(OutputStep::SinkOcr(index));
+            }
+        }
+        // Embeddings only add search, so a failing model or endpoint doesn't fail the segment.
+        // The model or the HTTP request blocks, so it runs off the async workers
+        if let Some(store) = self.embeddings.clone().filter(|_| !self.committed.contains(&OutputStep::Embeddings)) {
+            let span = info_span!("embed", results = ocr_results.len());
+            match tokio::task::spawn_blocking(move || span.in_scope(|| store.embed_ocr_batch(&ocr_results))).await {
+                Ok(Ok(_)) => {
+                    self.committed.insert(OutputStep::Embeddings);
+                }
+                Ok(Err(e)) => warn!("Failed to embed OCR text: {}", e),
+                Err(e) => warn!("Embedding task failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+    
+    /// Fire triggers for newly detected events, then write them to the sinks
+    /// together with the outcomes of trigger commands that finished meanwhile
+    async fn publish_events(&mut self, events: &[DetectedEvent]) -> Result<()> {
+        let mut events = events.to_vec();
+        self.sign_events(&mut events)?;
+        // A retry doesn't fire triggers or notify again for events an earlier attempt published
+        let dispatched = self.committed.contains(&OutputStep::EventsDispatched);
+        if let Some(triggers) = &self.triggers {
+            if !dispatched {
+                triggers.dispatch(&events);
+            }
+            let mut follow_ups = triggers.drain_follow_up_events();
+            self.sign_events(&mut follow_ups)?;
+            events.extend(follow_ups);
+        }
+        if !dispatched {
+            if let Some(notifier) = &self.notifier {
+                notifier.dispatch(&events);
+            }
+            self.snapshot.record_events(&events);
+            if let Some(schedule) = &mut self.focus_summary {
+                schedule.record(&events);
+            }
+            self.committed.insert(OutputStep::EventsDispatched);
+        }
+        if !self.committed.contains(&OutputStep::Correlations) {
+            self.correlate_events(&events).await?;
+            self.committed.insert(OutputStep::Correlations);
+        }
+        self.write_events_to_sinks(&events)
+    }
+    
+    /// Attach a signature to each event's metadata when `encryption.sign_events`
+    /// is set; signing is deterministic, so a retry attaches the same one
+    fn sign_events(&self, events: &mut [DetectedEvent]) -> Result<()> {
+        if let Some(signer) = &self.event_signer {
+            for event in events {
+                signer.attach_signature(event)?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Correlate events one at a time, in time order, and write the
+    /// correlations found with the workflow chains and error recoveries they complete
+    async fn correlate_events(&mut self, events: &[DetectedEvent]) -> Result<()> {
+        let Some((correlator, writer)) = self.correlator.as_mut() else {
+            return Ok(());
+        };
+        let mut ordered: Vec<&DetectedEvent> = events.iter().collect();
+        ordered.sort_by_key(|e| e.timestamp);
+        
+        let mut found = Vec::new();
+        let mut chains = Vec::new();
+        let mut recoveries = Vec::new();
+        for event in ordered {
+            correlator.add_detected_event(event);
+            let correlations = correlator.analyze_correlations(event.timestamp)?;
+            if let Some((builder, _)) = &mut self.workflow_chains {
+                builder.add_correlations(correlator.get_recent_events(), &correlations);
+                chains.extend(builder.take_completed(event.timestamp));
+            }
+            if let Some((analyzer, _)) = &mut self.error_recovery {
+                analyzer.add_events(std::slice::from_ref(event));
+                analyzer.add_correlations(correlator.get_recent_events(), &correlations);
+                recoveries.extend(analyzer.take_completed(event.timestamp));
+            }
+            found.extend(correlations);
+        }
+        writer.write_correlations(&found).await?;
+        if let Some((_, writer)) = &mut self.workflow_chains {
+            writer.write_chains(&chains).await?;
+        }
+        if let Some((_, writer)) = &mut self.error_recovery {
+            writer.write_sequences(&recoveries).await?;
+        }
+        Ok(())
+    }
+    
+    fn write_events_to_sinks(&mut self, events: &[DetectedEvent]) -> Result<()> {
+        for (index, sink) in self.sinks.iter_mut().enumerate() {
+            if !self.committed.contains(&OutputStep::SinkEvents(index)) {
+                sink.write_events(events)?;
+                self.committed.insert(OutputStep::SinkEvents(index));
+            }
+        }
+        Ok(())
+    }
+    
+    async fn write_form_records(&mut self, records: &[FormRecord]) -> Result<()> {
+        match &mut self.forms {
+            Some(forms) if !records.is_empty() && !self.committed.contains(&OutputStep::Forms) => {
+                forms.write_records(records).await?;
+                self.committed.insert(OutputStep::Forms);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Delete a dry-run segment's keyframes from the scratch directory
+    fn remove_scratch_keyframes(segment: &ProcessedSegment) {
+        let Some(frames_dir) = segment.frame_metadata.first().and_then(|m| Path::new(&m.path).parent()) else {
+            return;
+        };
+        if let Err(e) = std::fs::remove_dir_all(frames_dir) {
+            warn!("Failed to remove scratch keyframes in {}: {}", frames_dir.display(), e);
+        }
+    }
+    
+    /// Detect scrolling between a processed segment's keyframes, text events
+    /// from their stored OCR results when the segment was processed with an OCR
+    /// store, and known dialogs when templates are configured. Also returns the
+    /// forms submitted in the segment when form recording is enabled.
+    #[tracing::instrument(
+        name = "event_detect",
+        skip_all,
+        fields(segment_id = segment.frame_metadata.first().map(|m| m.segment_id.as_str()).unwrap_or_default())
+    )]
+    async fn detect_segment_events(&mut self, segment: &ProcessedSegment) -> AnyhowResult<(Vec<DetectedEvent>, Vec<FormRecord>)> {
+        let frame_metadata = &segment.frame_metadata;
+        let segment_start = segment.start;
+        let scroll_detector = ScrollDetector::new(self.config.scroll_detection.clone());
+        let monitor_id = frame_metadata.first().map(|m| m.monitor_id);
+        let display = monitor_id.unwrap_or_default();
+        let mut events = Vec::new();
+        let mut form_records = Vec::new();
+        
+        // What state built with replaced settings, or before the display's recording paused, held back
+        let paused = self.display_detection.get(&display)
+            .and_then(|state| state.last_frame)
+            .is_some_and(|last| segment_start - last > chrono::Duration::seconds(DETECTION_CONTINUITY_SECS));
+        if paused {
+            if let Some(state) = self.display_detection.remove(&display) {
+                self.retired_detection.push((display, state));
+            }
+        }
+        for (retired_display, mut state) in self.retired_detection.drain(..) {
+            let mut held = state.finish();
+            for event in &mut held {
+                event.metadata.entry("screen_id".to_string()).or_insert_with(|| retired_display.to_string());
+            }
+            events.extend(held);
+            form_records.extend(state.detector.take_form_records());
+        }
+        
+        let state = match self.display_detection.entry(display) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => entry.insert(DisplayDetection::new(&self.config, display)?),
+        };
+        let DisplayDetection { detector, extractors, deduplicator, last_frame } = state;
+        if let Some(templates) = &self.templates {
+            detector.set_template_matcher(templates.clone());
+        }
+        if let Some(topology) = &self.display_topology {
+            detector.set_display_topology(topology.clone());
+        }
+        // OCR regions are scaled with the display's factor, and events carry the display
+        detector.set_capture_screen(monitor_id);
+        let mut previous: Option<&metadata_collector::FrameMetadata> = None;
+        
+        // Pixel thresholds follow the resolution this segment was recorded at
+        if let Some(profile) = &segment.profile {
+            let scale = self.capture_profiles.threshold_scale(profile);
+            detector.set_resolution_scale(scale);
+            if let (Some(change), Some(first)) = (&segment.profile_change, frame_metadata.first()) {
+                events.push(change.to_event(segment_start, &first.path, scale));
+            }
+        }
+        
+        for metadata in frame_metadata.iter().filter(|m| !m.is_duplicate()) {
+            let _frame = info_span!("frame", frame_id = %metadata.path).entered();
+            let timestamp = segment_start + chrono::Duration::nanoseconds(metadata.ts_ns);
+            *last_frame = Some(timestamp);
+            if self.config.audio_analyzer.exclude_from_detail && segment.meetings.iter().any(|m| m.contains(timestamp)) {
+                previous = None;
+                continue;
+            }
+            
+            if let Some(previous) = previous.filter(|_| self.config.scroll_detection.enabled) {
+                if let Some(motion) = scroll_detector.detect_between(&previous.path, &metadata.path)? {
+                    events.push(scroll_detector.to_event(&motion, &metadata.path, &previous.path, timestamp));
+                }
+            }
+            previous = Some(metadata);
+            
+            // Template matching needs only the keyframe image, not its OCR text
+            let ocr_results = segment.frame_ocr.get(&metadata.path);
+            if ocr_results.is_some() || self.templates.is_some() {
+                let ocr_results = ocr_results.map(Vec::as_slice).unwrap_or(&[]);
+                detector.set_window(&metadata.app_name, &metadata.win_title);
+                let mut frame_events = detector.analyze_frame(
+                    &metadata.path,
+                    ocr_results,
+                    timestamp,
+                    metadata.width as f32,
+                    metadata.height as f32,
+                )?;
+                // App-specific extractors turn generic events into typed ones
+                extractors.extract(&AppFrame { metadata, timestamp, ocr_results }, &mut frame_events)?;
+                if let Some(screen_type) = metadata.screen_type {
+                    for event in &mut frame_events {
+                        event.metadata.insert("screen_type".to_string(), screen_type.as_str().to_string());
+                    }
+                }
+                events.extend(deduplicator.process(timestamp, frame_events));
+            }
+        }
+        form_records.extend(detector.take_form_records());
+        if !self.dry_run {
+            let path = self.config.field_identities_path(display);
+            if let Err(e) = detector.field_identities().save(&path) {
+                warn!("Failed to save followed fields to {}: {}", path.display(), e);
+            }
+        }
+        let ide_config = &self.config.app_extractors.ide;
+        if self.config.app_extractors.enabled && ide_config.enabled {
+            let window = chrono::Duration::seconds(ide_config.correlation_window_secs as i64);
+            let failures = self.build_failures.correlate(&events, window);
+            events.extend(failures);
+        }
+        // Layout analysis may already have placed a dialog on another display of a full-desktop capture
+        if let Some(monitor_id) = monitor_id {
+            for event in &mut events {
+                event.metadata.entry("screen_id".to_string()).or_insert_with(|| monitor_id.to_string());
+            }
+        }
+        if let Some(calibration) = &self.calibration {
+            calibration.apply(&mut events);
+        }
+        Ok((events, form_records))
+    }
+    
+    /// Finish every display's detection state, returning the events and forms
+    /// it held back for frames that will not come
+    fn finish_detection(&mut self) -> (Vec<DetectedEvent>, Vec<FormRecord>) {
+        let mut events = Vec::new();
+        let mut form_records = Vec::new();
+        let states = self.retired_detection.drain(..).chain(self.display_detection.drain());
+        for (display, mut state) in states {
+            let mut held = state.finish();
+            for event in &mut held {
+                event.metadata.entry("screen_id".to_string()).or_insert_with(|| display.to_string());
+            }
+            events.extend(held);
+            form_records.extend(state.detector.take_form_records());
+        }
+        if let Some(calibration) = &self.calibration {
+            calibration.apply(&mut events);
+        }
+        (events, form_records)
+    }
+    
+    /// Re-encode a segment's keyframes with their OCR text regions exact and the
+    /// rest simplified
+    fn compress_keyframes(
+        &self,
+        frame_metadata: &[metadata_collector::FrameMetadata],
+        frame_ocr: &HashMap<String, Vec<OCRResult>>,
+    ) {
+        let (mut before, mut after) = (0, 0);
+        for metadata in frame_metadata {
+            // No stored text may just mean OCR has not run yet, so edges decide then
+            let rois: Option<Vec<BoundingBox>> = frame_ocr
+                .get(&metadata.path)
+                .filter(|results| !results.is_empty())
+                .map(|results| results.iter().map(|r| r.roi.clone()).collect());
+            match hybrid_compression::compress_keyframe(Path::new(&metadata.path), rois.as_deref(), self.extractor.storage()) {
+                Ok(stats) => {
+                    before += stats.bytes_before;
+                    after += stats.bytes_after;
+                }
+                Err(e) => warn!("Failed to compress keyframe {}: {}", metadata.path, e),
+            }
+        }
+        if before > 0 {
+            info!("Hybrid compression took keyframes from {} to {} bytes", before, after);
+        }
+    }
+    
+    /// Bytes of a segment's keyframes and frame metadata
+    fn segment_bytes(frame_metadata: &[metadata_collector::FrameMetadata]) -> AnyhowResult<u64> {
+        let mut bytes = CsvWriter::estimated_size(frame_metadata)?;
+        for metadata in frame_metadata {
+            bytes += std::fs::metadata(&metadata.path).map(|m| m.len()).unwrap_or(0);
+        }
+        Ok(bytes)
+    }
+    
+    /// Wall-clock start of a segment.
+    ///
+    /// Segments are written as they are recorded, so the file's modification time
+    /// marks the end of the recording.
+    fn segment_start(
+        video_path: &Path,
+        frame_metadata: &[metadata_collector::FrameMetadata],
+    ) -> AnyhowResult<chrono::DateTime<chrono::Utc>> {
+        let last_offset_ns = frame_metadata.iter().map(|m| m.ts_ns).max().unwrap_or(0);
+        let recorded_until: chrono::DateTime<chrono::Utc> = std::fs::metadata(video_path)?.modified()?.into();
+        Ok(recorded_until - chrono::Duration::nanoseconds(last_offset_ns))
+    }
+    
+    async fn process_and_ack(&mut self, video_path: &Path) -> AnyhowResult<()> {
+        // Segments replayed after a pause may still be queued by the watcher as well
+        if !self.lock_ledger()?.is_pending(video_path) {
+            return Ok(());
+        }
+        // Left pending in the ledger until storage recovers
+        if self.check_storage().await {
+            return Ok(());
+        }
+        
+        self.process_watched_segment(video_path).await;
+        
+        // Failed segments are acknowledged too so a bad file is not retried forever;
+        // `retry-failed` picks them up from the dead-letter store
+        self.lock_ledger()?.ack(video_path)?;
+        Ok(())
+    }
+    
+    /// Process a segment the watcher picked up, keeping it for `retry-failed`
+    /// if it fails; returns whether it was processed
+    async fn process_watched_segment(&mut self, video_path: &Path) -> bool {
+        self.begin_item(video_path);
+        match self.process_video_segment(video_path, None).await {
+            Ok(_) => {
+                self.health.record_segment(&video_path.display().to_string());
+                self.item_done(video_path);
+                true
+            }
+            Err(e) => {
                 error!("Failed to process video segment {}: {}", video_path.display(), e);
+                self.dead_letter(video_path, FailedItemKind::WatchedSegment, &e);
+                false
             }
         }
+    }
+    
+    /// Start processing `item`, picking up the outputs an earlier failed
+    /// attempt already wrote so they aren't written again
+    fn begin_item(&mut self, item: &Path) {
+        self.committed = self.dead_letters.get(item).map(|entry| entry.committed.clone()).unwrap_or_default();
+    }
+    
+    /// Drop a processed item from the dead-letter store
+    fn item_done(&mut self, item: &Path) {
+        self.committed.clear();
+        if self.dry_run {
+            return;
+        }
+        if let Err(e) = self.dead_letters.resolve(item) {
+            warn!("Failed to resolve {} in the dead-letter store: {}", item.display(), e);
+        }
+    }
+    
+    /// Keep a failed item for `retry-failed` with the outputs it wrote; a dry
+    /// run keeps nothing
+    fn dead_letter(&mut self, item: &Path, kind: FailedItemKind, error: &anyhow::Error) {
+        let committed = std::mem::take(&mut self.committed);
+        if self.dry_run {
+            return;
+        }
+        if let Err(e) = self.dead_letters.record_failure(item, kind, error, committed) {
+            warn!("Failed to record {} in the dead-letter store: {}", item.display(), e);
+        }
+    }
+    
+    /// Process the dead-lettered items again, e.g. after a fix.
+    ///
+    /// Items are processed the way they were when they failed, skipping the
+    /// outputs they already wrote. Items that fail again stay in the store
+    /// with their retry count raised; items that reached `max_retries` failed
+    /// retries are left alone.
+    pub async fn retry_failed(
+        &mut self,
+        ocr_store: Option<&OCRParquetWriter>,
+        max_retries: Option<u32>,
+    ) -> AnyhowResult<RetrySummary> {
+        let mut summary = RetrySummary::default();
+        let entries = self.dead_letters.entries();
+        info!("Retrying {} failed items", entries.len());
+        for entry in entries {
+            if max_retries.is_some_and(|max| entry.retry_count >= max) {
+                summary.skipped += 1;
+                continue;
+            }
+            if !entry.item.exists() {
+                warn!("Failed item no longer exists: {}", entry.item.display());
+                self.dead_letters.resolve(&entry.item)?;
+                summary.missing += 1;
+                continue;
+            }
+            summary.retried += 1;
+            let recovered = match entry.kind {
+                FailedItemKind::WatchedSegment => self.process_watched_segment(&entry.item).await,
+                _ => match self.analyze_path(&entry.item, ocr_store, &BatchOptions::default()).await {
+                    // Files of a captured segment that fail again are kept one by one
+                    Ok(batch) => batch.is_success() || entry.kind == FailedItemKind::CapturedSegment,
+                    Err(e) => {
+                        self.begin_item(&entry.item);
+                        self.dead_letter(&entry.item, entry.kind, &e);
+                        false
+                    }
+                },
+            };
+            if recovered {
+                self.dead_letters.resolve(&entry.item)?;
+                summary.recovered += 1;
+            } else {
+                summary.still_failing += 1;
+            }
+        }
+        Ok(summary)
+    }
+    
+    fn lock_ledger(&self) -> AnyhowResult<std::sync::MutexGuard<'_, SegmentLedger>> {
+        self.ledger
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Segment ledger lock poisoned"))
+    }
+    
+    async fn tag_calendar_meetings(
+        &mut self,
+        segment_start: chrono::DateTime<chrono::Utc>,
+        segment_id: &str,
+        frame_metadata: &[metadata_collector::FrameMetadata],
+    ) -> AnyhowResult<()> {
+        let Some(calendar) = self.calendar.as_mut() else {
+            return Ok(());
+        };
+        calendar.refresh_if_changed()?;
         
+        let tags = calendar.tag_segment(segment_id, segment_start, frame_metadata);
+        if !tags.is_empty() {
+            info!("Segment {} overlaps {} calendar meetings", segment_id, tags.len());
+            self.csv_writer.write_calendar_tags(&tags).await?;
+            let usage = calendar.app_usage(&tags, segment_start, frame_metadata);
+            self.csv_writer.write_calendar_app_usage(&usage).await?;
+        }
         Ok(())
     }
     
-    async fn process_video_segment(&mut self, video_path: &Path) -> AnyhowResult<()> {
+    #[tracing::instrument(name = "segment", skip_all, fields(path = %video_path.display(), segment_id = tracing::field::Empty))]
+    async fn process_video_segment(
+        &mut self,
+        video_path: &Path,
+        ocr_store: Option<&OCRParquetWriter>,
+    ) -> AnyhowResult<ProcessedSegment> {
         info!("Processing video segment: {}", video_path.display());
         
+        // The rate follows the display's recent activity and the storage level
+        let stem = video_path.file_stem().unwrap_or_default().to_string_lossy();
+        let fps = self.extraction_rate(metadata_collector::monitor_id_from_segment(&stem), self.config.extraction_fps);
+        self.extractor.set_extraction_rate(fps);
+        // Frames of a blocked app or site are never written
+        let blocked = self.blocked_on_screen().await;
+        if let Some(rule) = &blocked {
+            info!("Withholding keyframes of {}: {:?} is on screen", video_path.display(), rule);
+        }
+        self.extractor.set_withhold_frames(blocked.is_some());
+        
         // Extract keyframes
-        let keyframes = match self.extractor.extract_keyframes(video_path).await {
+        let keyframes = match self.extractor.extract_keyframes(video_path).instrument(info_span!("extract")).await {
             Ok(frames) => frames,
-            Err(e) => {
+            // Unreadable videos keep their own error code
+            Err(e) if matches!(e.root(), IndexerError::CorruptedVideo(_) | IndexerError::UnsupportedFormat(_)) => {
                 error!("Failed to extract keyframes from {}: {}", video_path.display(), e);
                 return Err(e.into());
             }
+            Err(e) => {
+                error!("Failed to extract keyframes from {}: {}", video_path.display(), e);
+                let message = format!("Failed to extract keyframes from {}", video_path.display());
+                return Err(IndexerError::extraction(message).caused_by(e).for_segment(video_path.display()).into());
+            }
         };
         
         if keyframes.is_empty() {
             warn!("No keyframes extracted from {}", video_path.display());
-            return Ok(());
+            return Ok(ProcessedSegment::default());
         }
         
+        tracing::Span::current().record("segment_id", keyframes[0].segment_id.as_str());
+        if blocked.is_some() {
+            return self.withhold_segment(video_path, &keyframes).await;
+        }
         info!("Extracted {} keyframes from {}", keyframes.len(), video_path.display());
         
-        // Detect scene changes
-        let scene_changes = self.detector.detect_scene_changes(&keyframes)?;
-        info!("Detected {} scene changes", scene_changes.len());
+        let segment = self.process_keyframes(&keyframes, SegmentSource::Video(video_path), ocr_store).await?;
+        info!("Successfully processed video segment: {}", video_path.display());
+        Ok(segment)
+    }
+    
+    /// Whether the privacy filter blocks the frontmost app or window, checked
+    /// before frames are saved; a failed check blocks
+    async fn blocked_on_screen(&mut self) -> Option<PrivacyMatch> {
+        if !self.privacy.is_enabled() {
+            return None;
+        }
+        match self.metadata_collector.active_app_info().await {
+            Ok((app, win_title)) => self.privacy.check_app(&app, &win_title),
+            Err(e) => {
+                warn!("Failed to read the frontmost app for the privacy filter: {}", e);
+                Some(PrivacyMatch::App(String::new()))
+            }
+        }
+    }
+    
+    /// Record a segment whose frames were withheld as one redacted interval
+    async fn withhold_segment(&mut self, video_path: &Path, keyframes: &[Keyframe]) -> AnyhowResult<ProcessedSegment> {
+        let last_offset_ns = keyframes.iter().map(|k| k.timestamp_ns).max().unwrap_or(0);
+        let end: chrono::DateTime<chrono::Utc> = std::fs::metadata(video_path)?.modified()?.into();
+        let start = end - chrono::Duration::nanoseconds(last_offset_ns);
+        let interval = RedactedInterval {
+            segment_id: keyframes[0].segment_id.clone(),
+            monitor_id: keyframes[0]
+                .display_id
+                .unwrap_or_else(|| metadata_collector::monitor_id_from_segment(&keyframes[0].segment_id)),
+            start,
+            end,
+            frame_count: keyframes.len(),
+        };
+        info!("Redacted all {} keyframes of {}", keyframes.len(), video_path.display());
+        if !self.dry_run && !self.committed.contains(&OutputStep::RedactedIntervals) {
+            self.csv_writer.write_redacted_intervals(&[interval]).await?;
+            self.committed.insert(OutputStep::RedactedIntervals);
+        }
+        Ok(ProcessedSegment { start, redacted_frames: keyframes.len(), ..ProcessedSegment::default() })
+    }
+    
+    /// Process frames captured live as one segment
+    #[tracing::instrument(name = "segment", skip_all, fields(segment_id = tracing::field::Empty))]
+    pub async fn process_captured_segment(&mut self, segment: &CapturedSegment) -> AnyhowResult<()> {
+        let Some(first) = segment.keyframes.first() else {
+            return Ok(());
+        };
+        tracing::Span::current().record("segment_id", first.segment_id.as_str());
+        // Nothing queues live frames, so a paused segment is dropped
+        if self.check_storage().await {
+            warn!("Dropping {} captured keyframes; storage is paused", segment.keyframes.len());
+            if let Some(frames_dir) = Path::new(&first.frame_path).parent() {
+                let _ = std::fs::remove_dir_all(frames_dir);
+            }
+            return Ok(());
+        }
+        let processed = self.process_keyframes(&segment.keyframes, SegmentSource::Live(segment.start), None).await?;
+        info!("Processed {} live keyframes of {}", processed.frame_metadata.len(), first.segment_id);
+        Ok(())
+    }
+    
+    /// Scene detection, metadata, privacy and storage handling and output for
+    /// one segment's keyframes
+    async fn process_keyframes(
+        &mut self,
+        keyframes: &[Keyframe],
+        source: SegmentSource<'_>,
+        ocr_store: Option<&OCRParquetWriter>,
+    ) -> AnyhowResult<ProcessedSegment> {
+        // Detect scene changes against the display's own calibration
+        let monitor_id = keyframes[0]
+            .display_id
+            .unwrap_or_else(|| metadata_collector::monitor_id_from_segment(&keyframes[0].segment_id));
+        let segment_id = keyframes[0].segment_id.clone();
+        let detector = self.scene_detector(monitor_id)?;
+        let analysis = info_span!("scene_detect", frames = keyframes.len(), monitor_id)
+            .in_scope(|| detector.analyze_keyframes(keyframes))
+            .map_err(|e| {
+                IndexerError::scene_detection(format!("Failed to analyze {} keyframes", keyframes.len()))
+                    .caused_by(e)
+                    .for_segment(&segment_id)
+            })?;
+        info!("Detected {} scene changes", analysis.scene_changes.len());
+        if let Some(adaptive) = self.adaptive_fps.as_mut() {
+            if let Some(fps) = adaptive.observe(monitor_id, keyframes.len(), analysis.scene_changes.len()) {
+                info!("Extraction rate of display {} is now {} fps", monitor_id, fps);
+            }
+        }
         
-        // Collect metadata for each keyframe
+        // Collect metadata for each keyframe; near-duplicates copy it from the original
         let mut frame_metadata = Vec::new();
-        for keyframe in &keyframes {
-            let metadata = self.metadata_collector.collect_metadata(keyframe).await?;
+        for (keyframe, phash) in keyframes.iter().zip(analysis.frame_hashes.iter().copied()) {
+            let duplicate_of = phash.and_then(|h| self.dedup.find_duplicate(&keyframe.segment_id, h).cloned());
+            let metadata = match duplicate_of {
+                Some(original) => self.dedup.duplicate_metadata(&original, keyframe.timestamp_ns, &keyframe.frame_path),
+                None => {
+                    let metadata = self
+                        .metadata_collector
+                        .collect_metadata(keyframe)
+                        .instrument(info_span!("metadata", frame_id = %keyframe.frame_path))
+                        .await
+                        .for_frame(&keyframe.frame_path)
+                        .for_segment(&segment_id)?;
+                    if let Some(h) = phash {
+                        self.dedup.record(h, &metadata);
+                    }
+                    metadata
+                }
+            };
             frame_metadata.push(metadata);
         }
+        self.dedup.finish_segment(&keyframes[0].segment_id);
         
-        // Write to CSV
-        self.csv_writer.write_frame_metadata(&frame_metadata).await?;
+        let profile = CaptureProfile::of_keyframe(&keyframes[0]);
+        let profile_change = self.capture_profiles.observe(&frame_metadata[0].monitor_id.to_string(), profile);
         
-        info!("Successfully processed video segment: {}", video_path.display());
-        Ok(())
+        let duplicates = frame_metadata.iter().filter(|m| m.is_duplicate()).count();
+        if duplicates > 0 {
+            info!("Skipped {} duplicate keyframes", duplicates);
+        }
+        
+        if let Some(classifier) = &self.screen_classifier {
+            classifier.label(&mut frame_metadata);
+        }
+        
+        // Density metrics go into the frame metadata, so OCR is looked up before writing it
+        let mut frame_ocr = HashMap::new();
+        if let Some(store) = ocr_store {
+            for metadata in frame_metadata.iter().filter(|m| !m.is_duplicate()) {
+                let ocr_results = batch_analysis::stored_ocr_for_image(store, &metadata.path).await.map_err(|e| {
+                    IndexerError::ocr("Failed to read stored OCR results")
+                        .caused_by(e)
+                        .for_frame(&metadata.path)
+                        .for_segment(&segment_id)
+                })?;
+                frame_ocr.insert(metadata.path.clone(), ocr_results);
+            }
+        }
+        
+        // Frames of blocked apps and sites leave only an opaque interval behind
+        let segment_start = match source {
+            SegmentSource::Video(video_path) => Self::segment_start(video_path, &frame_metadata)?,
+            SegmentSource::Live(start) => start,
+        };
+        let redacted = self.privacy.filter_segment(&mut frame_metadata, &mut frame_ocr, segment_start);
+        let redacted_frames: usize = redacted.iter().map(|interval| interval.frame_count).sum();
+        if redacted_frames > 0 {
+            info!("Redacted {} keyframes of {}", redacted_frames, source);
+            let kept: HashSet<&str> = frame_metadata.iter().map(|m| m.path.as_str()).collect();
+            // Only rules matching the stored OCR text get here, after the keyframe was written
+            for keyframe in keyframes.iter().filter(|k| !kept.contains(k.frame_path.as_str())) {
+                match std::fs::remove_file(&keyframe.frame_path) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        return Err(IndexerError::from(e).for_frame(&keyframe.frame_path).for_segment(&segment_id).into());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        // Calls keep their time range; with `exclude_from_detail`, their keyframes' text is dropped
+        let meetings = match (&self.audio, &source) {
+            (Some(analyzer), SegmentSource::Video(video_path)) => {
+                // Decoding with ffmpeg blocks, so it runs off the async workers
+                let (analyzer, video_path, frames) = (analyzer.clone(), video_path.to_path_buf(), frame_metadata.clone());
+                let span = info_span!("audio");
+                let analyzed = tokio::task::spawn_blocking(move || {
+                    span.in_scope(|| analyzer.analyze_segment(&video_path, segment_start, &frames))
+                })
+                .await;
+                match analyzed {
+                    Ok(Ok(meetings)) => meetings,
+                    Ok(Err(e)) => {
+                        warn!("Failed to analyze audio of {}: {}", source, e);
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        warn!("Audio analysis task failed: {}", e);
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+        if !meetings.is_empty() {
+            info!("Found {} meetings in {}", meetings.len(), source);
+            if self.config.audio_analyzer.exclude_from_detail {
+                let stripped = audio_analyzer::strip_meeting_ocr(&meetings, &frame_metadata, &mut frame_ocr, segment_start);
+                info!("Dropped OCR text of {} keyframes captured during meetings", stripped);
+            }
+        }
+        
+        // Low on space: only thumbnails of the analyzed keyframes are kept
+        let thumbnails_only = self.storage.as_ref().filter(|m| m.level() >= StorageLevel::ThumbnailsOnly);
+        if thumbnails_only.is_none() && self.extractor.storage().hybrid.enabled {
+            self.compress_keyframes(&frame_metadata, &frame_ocr);
+        }
+        if let Some(monitor) = thumbnails_only {
+            for metadata in &frame_metadata {
+                if let Err(e) = storage_monitor::shrink_to_thumbnail(
+                    Path::new(&metadata.path),
+                    monitor.config().thumbnail_width,
+                    self.extractor.storage(),
+                ) {
+                    warn!("Failed to shrink keyframe {}: {}", metadata.path, e);
+                }
+            }
+        }
+        
+        let mut quality_regressions = 0;
+        for metadata in frame_metadata.iter_mut() {
+            let Some(ocr_results) = frame_ocr.get(&metadata.path) else {
+                continue;
+            };
+            let density = OcrDensity::from_results(ocr_results, metadata.width, metadata.height);
+            if self.ocr_quality.observe(&metadata.app_name, &density).is_some() {
+                quality_regressions += 1;
+            }
+            metadata.ocr_density = Some(density);
+        }
+        
+        if let Some(latest) = frame_metadata.iter().max_by_key(|m| m.ts_ns) {
+            self.snapshot.record_frame(latest);
+        }
+        
+        let mut estimated_bytes = 0;
+        if self.dry_run {
+            estimated_bytes = Self::segment_bytes(&frame_metadata)?;
+        } else {
+            self.write_segment_outputs(&frame_metadata, &frame_ocr, &redacted)
+                .instrument(info_span!("write_outputs", frames = frame_metadata.len()))
+                .await?;
+            if !self.committed.contains(&OutputStep::Timeline) {
+                let merged = match self.timeline.as_mut() {
+                    Some(timeline) => {
+                        timeline.push_segment(&frame_metadata, segment_start);
+                        timeline.drain_ready()
+                    }
+                    None => Vec::new(),
+                };
+                if !merged.is_empty() {
+                    self.csv_writer.write_timeline(&merged).await?;
+                }
+                self.committed.insert(OutputStep::Timeline);
+            }
+            if self.storage.is_some() {
+                let written = Self::segment_bytes(&frame_metadata)?;
+                if let Some(monitor) = self.storage.as_mut() {
+                    monitor.record_written(written);
+                }
+            }
+            
+            if !self.committed.contains(&OutputStep::CalendarTags) {
+                match self.tag_calendar_meetings(segment_start, &keyframes[0].segment_id, &frame_metadata).await {
+                    Ok(()) => {
+                        self.committed.insert(OutputStep::CalendarTags);
+                    }
+                    Err(e) => warn!("Failed to tag calendar meetings for {}: {}", source, e),
+                }
+            }
+            if !meetings.is_empty() && !self.committed.contains(&OutputStep::MeetingIntervals) {
+                self.csv_writer.write_meeting_intervals(&meetings).await?;
+                self.committed.insert(OutputStep::MeetingIntervals);
+            }
+        }
+        
+        Ok(ProcessedSegment {
+            frame_metadata,
+            scene_changes: analysis.scene_changes.len(),
+            frame_ocr,
+            quality_regressions,
+            profile: Some(profile),
+            profile_change,
+            estimated_bytes,
+            start: segment_start,
+            redacted_frames,
+            meetings,
+        })
     }
 }
\ No newline at end of file
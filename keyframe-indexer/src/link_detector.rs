@@ -0,0 +1,337 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::ocr_data::OCRResult;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Top-level domains recognized without a scheme, `www.` or path, so that
+/// file names like `notes.txt` aren't taken for hosts
+const BARE_DOMAIN_TLDS: &[&str] = &[
+    "com", "org", "net", "edu", "gov", "io", "dev", "app", "ai", "co", "de", "uk", "fr", "nl", "ch", "at", "eu",
+    "us", "ca", "au", "jp", "info", "biz",
+];
+
+/// Characters OCR picks up after a link that are sentence punctuation
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"'];
+
+/// Configuration for reporting URLs, email addresses and file paths seen on screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkDetectorConfig {
+    pub enabled: bool,
+    pub urls: bool,
+    pub emails: bool,
+    pub file_paths: bool,
+    /// Keep URL query strings, which often carry tokens and session ids.
+    /// Fragments are always dropped
+    pub keep_query: bool,
+}
+
+impl Default for LinkDetectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            urls: true,
+            emails: true,
+            file_paths: true,
+            keep_query: false,
+        }
+    }
+}
+
+/// Kind of reference found in text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    Url,
+    Email,
+    FilePath,
+}
+
+impl LinkKind {
+    pub fn event_type(self) -> EventType {
+        match self {
+            Self::Url => EventType::UrlDisplay,
+            Self::Email => EventType::EmailDisplay,
+            Self::FilePath => EventType::FilePathDisplay,
+        }
+    }
+}
+
+/// Reference found in text, with its normalized form
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoundLink {
+    pub kind: LinkKind,
+    /// Text as OCR read it
+    pub raw: String,
+    /// Canonical form: URLs with a lowercase scheme and host and no default
+    /// port, fragment or (unless kept) query, lowercase emails, paths without
+    /// doubled separators
+    pub normalized: String,
+    /// Extra facts for the event's metadata, e.g. `domain`
+    pub details: HashMap<String, String>,
+}
+
+/// Finds URLs, email addresses and file paths in OCR text and reports each
+/// when it appears on screen.
+///
+/// Address bars and documents are read from the pixels, so links are found
+/// even when the browser's tab scripting is unavailable. Addresses without a
+/// scheme, as address bars show them, count when they start with `www.`, have a
+/// path or end in a common top-level domain.
+pub struct LinkDetector {
+    config: LinkDetectorConfig,
+    url_regex: Regex,
+    bare_url_regex: Regex,
+    email_regex: Regex,
+    unix_path_regex: Regex,
+    windows_path_regex: Regex,
+    /// Links on the previous frame, which are not reported again
+    on_screen: HashSet<(LinkKind, String)>,
+}
+
+impl LinkDetector {
+    pub fn new(config: LinkDetectorConfig) -> Result<Self> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| IndexerError::Config(format!("Invalid link pattern: {}", e)))
+        };
+        Ok(Self {
+            config,
+            url_regex: compile(r#"(?i)\b(?:https?|ftp)://[^\s<>()\[\]"'`]+"#)?,
+            bare_url_regex: compile(r#"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,24}(?::\d{2,5})?(?:/[^\s<>()\[\]"'`]*)?"#)?,
+            email_regex: compile(r"(?i)\b[a-z0-9._%+-]+@(?:[a-z0-9-]+\.)+[a-z]{2,24}\b")?,
+            unix_path_regex: compile(r#"(?:^|[\s"'(=])((?:~|/[\w.@+-]+)(?:/+[\w.@+-]+)+/?)"#)?,
+            windows_path_regex: compile(r#"(?i)\b([a-z]:\\(?:[^\\/:*?"<>|\s]+\\)*[^\\/:*?"<>|\s]*)"#)?,
+            on_screen: HashSet::new(),
+        })
+    }
+
+    /// Links in one piece of text, in the order they appear
+    pub fn find(&self, text: &str) -> Vec<FoundLink> {
+        let mut taken: Vec<Range<usize>> = Vec::new();
+        let mut found: Vec<(usize, FoundLink)> = Vec::new();
+
+        if self.config.emails {
+            for m in self.email_regex.find_iter(text) {
+                if claim(&mut taken, m.range()) {
+                    found.push((m.start(), normalize_email(m.as_str())));
+                }
+            }
+        }
+        if self.config.urls {
+            for m in self.url_regex.find_iter(text) {
+                if let Some(link) = normalize_url(m.as_str(), self.config.keep_query) {
+                    if claim(&mut taken, m.range()) {
+                        found.push((m.start(), link));
+                    }
+                }
+            }
+            for m in self.bare_url_regex.find_iter(text) {
+                let preceded_by_path = text[..m.start()].ends_with(['/', '\\', '@', '.']);
+                if preceded_by_path || !is_bare_address(m.as_str()) {
+                    continue;
+                }
+                if let Some(link) = normalize_url(&format!("https://{}", m.as_str()), self.config.keep_query) {
+                    if claim(&mut taken, m.range()) {
+                        let raw = if self.config.keep_query { m.as_str() } else { strip_query(m.as_str()) };
+                        found.push((m.start(), FoundLink { raw: raw.to_string(), ..link }));
+                    }
+                }
+            }
+        }
+        if self.config.file_paths {
+            let paths = self.unix_path_regex.captures_iter(text).chain(self.windows_path_regex.captures_iter(text));
+            for m in paths.filter_map(|captures| captures.get(1)) {
+                if let Some(link) = normalize_path(m.as_str()) {
+                    if claim(&mut taken, m.range()) {
+                        found.push((m.start(), link));
+                    }
+                }
+            }
+        }
+
+        found.sort_by_key(|(start, _)| *start);
+        found.into_iter().map(|(_, link)| link).collect()
+    }
+
+    /// Events for the links on a frame that weren't on the previous one
+    pub fn detect(&mut self, frame_id: &str, timestamp: DateTime<Utc>, regions: &[&OCRResult]) -> Vec<DetectedEvent> {
+        let mut events = Vec::new();
+        let mut on_screen = HashSet::new();
+
+        for region in regions {
+            for link in self.find(&region.text) {
+                let key = (link.kind, link.normalized.clone());
+                let seen_before = self.on_screen.contains(&key);
+                if !on_screen.insert(key) || seen_before {
+                    continue;
+                }
+                let mut metadata = link.details;
+                region.roi.insert_metadata(&mut metadata);
+                metadata.insert("source".to_string(), "ocr".to_string());
+                events.push(DetectedEvent {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    timestamp,
+                    event_type: link.kind.event_type(),
+                    target: link.normalized,
+                    value_from: None,
+                    value_to: Some(link.raw),
+                    confidence: region.confidence * 0.9,
+                    evidence_frames: vec![frame_id.to_string()],
+                    metadata,
+                });
+            }
+        }
+
+        self.on_screen = on_screen;
+        events
+    }
+
+    pub fn clear(&mut self) {
+        self.on_screen.clear();
+    }
+}
+
+/// Take a span of text for one link unless an earlier link has part of it
+fn claim(taken: &mut Vec<Range<usize>>, range: Range<usize>) -> bool {
+    let free = !taken.iter().any(|t| t.start < range.end && range.start < t.end);
+    if free {
+        taken.push(range);
+    }
+    free
+}
+
+fn is_bare_address(text: &str) -> bool {
+    let host = text.split(['/', ':']).next().unwrap_or(text);
+    let tld = host.rsplit('.').next().unwrap_or("").to_lowercase();
+    host.to_lowercase().starts_with("www.") || text.contains('/') || BARE_DOMAIN_TLDS.contains(&tld.as_str())
+}
+
+/// URL text without its query string and fragment
+fn strip_query(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+fn normalize_url(raw: &str, keep_query: bool) -> Option<FoundLink> {
+    let raw = raw.trim_end_matches(TRAILING_PUNCTUATION);
+    let raw = if keep_query { raw } else { strip_query(raw) };
+    let (scheme, rest) = raw.split_once("://")?;
+    let scheme = scheme.to_lowercase();
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let authority = authority.to_lowercase();
+    let host = authority.rsplit('@').next().unwrap_or(&authority);
+    let host = match (scheme.as_str(), host.rsplit_once(':')) {
+        ("http", Some((host, "80"))) | ("https", Some((host, "443"))) => host,
+        _ => host,
+    };
+    let host_name = host.split(':').next().unwrap_or(host);
+    if host_name.is_empty() || (!host_name.contains('.') && host_name != "localhost") {
+        return None;
+    }
+    let path = if path.is_empty() { "/" } else { path };
+
+    let mut details = HashMap::new();
+    details.insert("domain".to_string(), host_name.trim_start_matches("www.").to_string());
+    details.insert("url_scheme".to_string(), scheme.clone());
+    Some(FoundLink {
+        kind: LinkKind::Url,
+        raw: raw.to_string(),
+        normalized: format!("{}://{}{}", scheme, host, path),
+        details,
+    })
+}
+
+fn normalize_email(raw: &str) -> FoundLink {
+    let normalized = raw.trim_end_matches(TRAILING_PUNCTUATION).to_lowercase();
+    let mut details = HashMap::new();
+    if let Some((_, domain)) = normalized.rsplit_once('@') {
+        details.insert("domain".to_string(), domain.to_string());
+    }
+    FoundLink { kind: LinkKind::Email, raw: raw.to_string(), normalized, details }
+}
+
+fn normalize_path(raw: &str) -> Option<FoundLink> {
+    let trimmed = raw.trim_end_matches(TRAILING_PUNCTUATION);
+    let separator = if trimmed.contains('\\') { '\\' } else { '/' };
+    let mut normalized = String::with_capacity(trimmed.len());
+    for c in trimmed.chars() {
+        if !(c == separator && normalized.ends_with(separator)) {
+            normalized.push(c);
+        }
+    }
+    while normalized.len() > 1 && normalized.ends_with(separator) && !normalized.ends_with(":\\") {
+        normalized.pop();
+    }
+    let file_name = normalized.rsplit(separator).next().unwrap_or("").to_string();
+    if file_name.is_empty() || file_name.ends_with(':') {
+        return None;
+    }
+
+    let mut details = HashMap::new();
+    if let Some((_, extension)) = file_name.rsplit_once('.').filter(|(stem, _)| !stem.is_empty()) {
+        details.insert("extension".to_string(), extension.to_lowercase());
+    }
+    details.insert("file_name".to_string(), file_name);
+    Some(FoundLink { kind: LinkKind::FilePath, raw: raw.to_string(), normalized, details })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr_data::BoundingBox;
+
+    #[test]
+    fn test_links_are_found_normalized_and_reported_once() {
+        let mut detector = LinkDetector::new(LinkDetectorConfig { enabled: true, ..LinkDetectorConfig::default() }).unwrap();
+
+        let links = detector.find("See HTTPS://Docs.Example.com:443/Guide#setup, mail Jane.Doe@Example.COM or open ~/Reports//q3.PDF.");
+        let found: Vec<(LinkKind, &str)> = links.iter().map(|l| (l.kind, l.normalized.as_str())).collect();
+        assert_eq!(found, [
+            (LinkKind::Url, "https://docs.example.com/Guide"),
+            (LinkKind::Email, "jane.doe@example.com"),
+            (LinkKind::FilePath, "~/Reports/q3.PDF"),
+        ]);
+        assert_eq!(links[0].details.get("domain").map(String::as_str), Some("docs.example.com"));
+        assert_eq!(links[2].details.get("extension").map(String::as_str), Some("pdf"));
+
+        // Query strings carry tokens, so they are dropped unless kept
+        let with_token = detector.find("https://app.example.com/reset?token=s3cr3t#top");
+        assert_eq!(with_token[0].normalized, "https://app.example.com/reset");
+        assert_eq!(with_token[0].raw, "https://app.example.com/reset");
+        let bare = detector.find("example.com/search?q=rust");
+        assert_eq!(bare[0].raw, "example.com/search");
+        let keeping = LinkDetector::new(LinkDetectorConfig { keep_query: true, ..LinkDetectorConfig::default() }).unwrap();
+        let kept = keeping.find("https://app.example.com/reset?token=s3cr3t#top");
+        assert_eq!(kept[0].normalized, "https://app.example.com/reset?token=s3cr3t");
+
+        // Address bars leave out the scheme; file names are not hosts
+        let bare = detector.find("www.example.org github.com/rust-lang/rust notes.txt C:\\Users\\ann\\Desktop\\plan.xlsx");
+        let found: Vec<&str> = bare.iter().map(|l| l.normalized.as_str()).collect();
+        assert_eq!(found, ["https://www.example.org/", "https://github.com/rust-lang/rust", "C:\\Users\\ann\\Desktop\\plan.xlsx"]);
+
+        let region = OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(200.0, 40.0, 400.0, 20.0),
+            text: "github.com/rust-lang/rust".to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        };
+        let events = detector.detect("frame_1", Utc::now(), &[&region]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, EventType::UrlDisplay);
+        assert_eq!(events[0].metadata.get("domain").map(String::as_str), Some("github.com"));
+        // Still on screen
+        assert!(detector.detect("frame_2", Utc::now(), &[&region]).is_empty());
+        // Gone and back
+        detector.detect("frame_3", Utc::now(), &[]);
+        assert_eq!(detector.detect("frame_4", Utc::now(), &[&region]).len(), 1);
+    }
+}
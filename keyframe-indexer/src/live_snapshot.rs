@@ -0,0 +1,186 @@
+use crate::cursor_tracker::CursorPosition;
+use crate::event_detector::DetectedEvent;
+use crate::metadata_collector::FrameMetadata;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+/// Number of recent events kept in the live snapshot
+const MAX_RECENT_EVENTS: usize = 10;
+
+/// Consolidated view of what is on screen right now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiveSnapshot {
+    /// When the snapshot was taken
+    pub captured_at: Option<DateTime<Utc>>,
+    /// Frontmost application from the latest keyframe
+    pub frontmost_app: Option<String>,
+    /// Window title from the latest keyframe
+    pub window_title: Option<String>,
+    /// Path of the latest keyframe image
+    pub last_keyframe_path: Option<String>,
+    /// Timestamp of the latest keyframe in nanoseconds
+    pub last_keyframe_ts_ns: Option<i64>,
+    /// Most recent events, newest first
+    pub recent_events: Vec<DetectedEvent>,
+    /// Last observed cursor position
+    pub cursor_position: Option<CursorPosition>,
+    /// Detectors currently running
+    pub active_detectors: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct SnapshotState {
+    last_frame: Option<FrameMetadata>,
+    recent_events: VecDeque<DetectedEvent>,
+    cursor_position: Option<CursorPosition>,
+    active_detectors: BTreeSet<String>,
+}
+
+/// Cheap, shareable tracker that pipeline stages update as they run.
+///
+/// Cloning shares the same underlying state, so the indexer, the navigation
+/// service and the query layer can all hold a handle.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotTracker {
+    state: Arc<RwLock<SnapshotState>>,
+}
+
+impl SnapshotTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the latest processed keyframe
+    pub fn record_frame(&self, metadata: &FrameMetadata) {
+        if let Ok(mut state) = self.state.write() {
+            let is_newer = state
+                .last_frame
+                .as_ref()
+                .map_or(true, |last| metadata.ts_ns >= last.ts_ns);
+            if is_newer {
+                state.last_frame = Some(metadata.clone());
+            }
+        }
+    }
+
+    /// Record newly detected events
+    pub fn record_events(&self, events: &[DetectedEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        if let Ok(mut state) = self.state.write() {
+            for event in events {
+                state.recent_events.push_front(event.clone());
+            }
+            state
+                .recent_events
+                .make_contiguous()
+                .sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            state.recent_events.truncate(MAX_RECENT_EVENTS);
+        }
+    }
+
+    /// Record the latest cursor position
+    pub fn record_cursor(&self, position: &CursorPosition) {
+        if let Ok(mut state) = self.state.write() {
+            state.cursor_position = Some(position.clone());
+        }
+    }
+
+    /// Mark a detector as running or stopped
+    pub fn set_detector_active(&self, detector: &str, active: bool) {
+        if let Ok(mut state) = self.state.write() {
+            if active {
+                state.active_detectors.insert(detector.to_string());
+            } else {
+                state.active_detectors.remove(detector);
+            }
+        }
+    }
+
+    /// Take a consistent copy of the current state
+    pub fn snapshot(&self) -> LiveSnapshot {
+        let state = match self.state.read() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        LiveSnapshot {
+            captured_at: Some(Utc::now()),
+            frontmost_app: state.last_frame.as_ref().map(|f| f.app_name.clone()),
+            window_title: state.last_frame.as_ref().map(|f| f.win_title.clone()),
+            last_keyframe_path: state.last_frame.as_ref().map(|f| f.path.clone()),
+            last_keyframe_ts_ns: state.last_frame.as_ref().map(|f| f.ts_ns),
+            recent_events: state.recent_events.iter().cloned().collect(),
+            cursor_position: state.cursor_position.clone(),
+            active_detectors: state.active_detectors.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use std::collections::HashMap;
+
+    fn create_frame(ts_ns: i64, app_name: &str) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: format!("/frames/frame_{}.png", ts_ns),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: app_name.to_string(),
+            win_title: "Window".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }
+    }
+
+    fn create_event(id: usize) -> DetectedEvent {
+        DetectedEvent {
+            id: format!("event_{}", id),
+            timestamp: Utc::now() + chrono::Duration::milliseconds(id as i64),
+            event_type: EventType::Navigation,
+            target: "window".to_string(),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_tracks_latest_state() {
+        let tracker = SnapshotTracker::new();
+        let shared = tracker.clone();
+
+        tracker.record_frame(&create_frame(2_000, "Safari"));
+        // Late-arriving older frames do not replace the current one
+        shared.record_frame(&create_frame(1_000, "Mail"));
+        tracker.set_detector_active("scene_detector", true);
+        tracker.set_detector_active("file_watcher", true);
+        tracker.set_detector_active("file_watcher", false);
+
+        let events: Vec<DetectedEvent> = (0..15).map(create_event).collect();
+        shared.record_events(&events);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.frontmost_app.as_deref(), Some("Safari"));
+        assert_eq!(snapshot.last_keyframe_ts_ns, Some(2_000));
+        assert_eq!(snapshot.recent_events.len(), MAX_RECENT_EVENTS);
+        assert_eq!(snapshot.recent_events[0].id, "event_14");
+        assert_eq!(snapshot.active_detectors, vec!["scene_detector".to_string()]);
+        assert!(snapshot.cursor_position.is_none());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::{IndexerError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, listing the enclosing spans with their
+    /// fields such as `segment_id` and `frame_id`
+    Json,
+}
+
+/// Log level, format, rate limits and log file of the service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Most verbose level logged: `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    pub format: LogFormat,
+    /// Debug and info lines each pipeline stage may log per window; 0 disables
+    /// the limit. Warnings and errors are never dropped.
+    pub max_lines_per_stage: u32,
+    pub rate_window_ms: u64,
+    /// File logs are also written to, rotated by size
+    pub file: Option<String>,
+    /// Size at which the log file is rotated
+    pub max_file_bytes: u64,
+    /// Rotated files kept besides the current one, as `<file>.1` (newest) and up
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            max_lines_per_stage: 50,
+            rate_window_ms: 1000,
+            file: None,
+            max_file_bytes: 50 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.level_filter()?;
+        if self.rate_window_ms == 0 {
+            return Err(IndexerError::Config("rate_window_ms must be greater than 0".to_string()));
+        }
+        if self.max_file_bytes == 0 {
+            return Err(IndexerError::Config("max_file_bytes must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn level_filter(&self) -> Result<LevelFilter> {
+        self.level
+            .parse()
+            .map_err(|_| IndexerError::Config(format!("Unknown log level '{}'", self.level)))
+    }
+}
+
+/// Fixed-window line counts per stage
+#[derive(Debug)]
+struct StageLimiter {
+    max_per_window: u32,
+    window: Duration,
+    windows: HashMap<&'static str, (Instant, u32)>,
+}
+
+impl StageLimiter {
+    fn allow(&mut self, stage: &'static str, now: Instant) -> bool {
+        let (started, count) = self.windows.entry(stage).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+        *count += 1;
+        *count <= self.max_per_window
+    }
+}
+
+/// Drops debug and info lines of a stage past its per-window limit, so
+/// per-frame logging of high frame rate sessions doesn't flood the logs.
+///
+/// A line's stage is the innermost span it is logged in (`extract`, `frame`,
+/// `ocr_write`, ...); lines outside any span are not limited.
+pub struct RateLimitLayer {
+    limiter: Mutex<StageLimiter>,
+    suppressed: Arc<AtomicU64>,
+}
+
+impl RateLimitLayer {
+    pub fn new(max_per_window: u32, window: Duration) -> Self {
+        Self {
+            limiter: Mutex::new(StageLimiter { max_per_window, window, windows: HashMap::new() }),
+            suppressed: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Count of dropped lines, readable after the layer is installed
+    pub fn suppressed(&self) -> Arc<AtomicU64> {
+        self.suppressed.clone()
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        if *event.metadata().level() <= Level::WARN {
+            return true;
+        }
+        let Some(stage) = ctx.event_span(event).map(|span| span.name()) else {
+            return true;
+        };
+        let allowed = match self.limiter.lock() {
+            Ok(mut limiter) => limiter.allow(stage, Instant::now()),
+            Err(_) => true,
+        };
+        if !allowed {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+}
+
+#[derive(Debug)]
+struct RollingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RollingFile {
+    /// Shift `<file>.N` to `<file>.N+1`, dropping the oldest, and start a new file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(rotated_path(&self.path, self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    std::fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Log file appender that rotates once the file reaches a size; clones
+/// write to the same file
+#[derive(Debug, Clone)]
+pub struct RollingFileWriter {
+    inner: Arc<Mutex<RollingFile>>,
+}
+
+impl RollingFileWriter {
+    pub fn open(path: &Path, max_bytes: u64, max_files: usize) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            inner: Arc::new(Mutex::new(RollingFile { path: path.to_path_buf(), file, size, max_bytes, max_files })),
+        })
+    }
+}
+
+impl Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut file = self.inner.lock().map_err(|_| std::io::Error::other("Log file lock poisoned"))?;
+        if file.size > 0 && file.size + buf.len() as u64 > file.max_bytes {
+            file.rotate()?;
+        }
+        let written = file.file.write(buf)?;
+        file.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let mut file = self.inner.lock().map_err(|_| std::io::Error::other("Log file lock poisoned"))?;
+        file.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_limits_and_rotation() {
+        let mut limiter = StageLimiter { max_per_window: 2, window: Duration::from_secs(1), windows: HashMap::new() };
+        let start = Instant::now();
+        assert!(limiter.allow("frame", start));
+        assert!(limiter.allow("frame", start));
+        assert!(!limiter.allow("frame", start));
+        assert!(limiter.allow("extract", start));
+        assert!(limiter.allow("frame", start + Duration::from_secs(1)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("indexer.log");
+        let mut writer = RollingFileWriter::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+}
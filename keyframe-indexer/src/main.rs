@@ -1,47 +1,1154 @@
 use anyhow::Result;
-use clap::Parser;
-use keyframe_indexer::{IndexerService, IndexerConfig};
+use clap::{Parser, Subcommand, ValueEnum};
+use chrono::{DateTime, Utc};
+use keyframe_indexer::{
+    AccessAuditLog, AccessAuditor, AccessOperation, Anonymizer, BatchOptions, Calibration, ConfigIssue, ConfigWatcher, DuckDbSources, Eraser, ErasureMode, ErasureRequest, EventParquetWriter, ExportFormat, FormRecordParquetWriter, IndexerService, IndexerConfig, ImageAnalyzer, MarkdownExporter, MemoryBudget,
+    OCRParquetWriter, OCRResult, OutputQuery, ParquetWriter, QueryService, QuickstartDataset, ReportFormat, ReportGenerator, ShutdownSignal, WorkflowChainParquetWriter, WorkflowMiner, CANNED_QUERIES,
+};
+use keyframe_indexer::audio_analyzer::load_meetings;
+use keyframe_indexer::batch_analysis::{parse_time_bound, stored_ocr_for_image};
+use keyframe_indexer::calibration::load_labels;
+use keyframe_indexer::flight;
+use keyframe_indexer::health;
+use keyframe_indexer::output_export::{
+    export_embeddings, export_events, export_form_records, export_frames, export_ocr, export_timeline, load_frames, load_timeline,
+};
+use keyframe_indexer::output_query::{format_json, format_table};
+use keyframe_indexer::telemetry;
+use std::path::{Path, PathBuf};
 use tracing::{info, error};
-use tracing_subscriber;
 
 #[derive(Parser)]
 #[command(name = "keyframe-indexer")]
 #[command(about = "A service for extracting keyframes from video segments")]
 struct Cli {
     /// Configuration file path
-    #[arg(short, long, default_value = "config.json")]
+    #[arg(short, long, default_value = "config.json", global = true)]
     config: String,
     
-    /// Watch directory for new video segments
+    /// Watch directory for new video segments (same as `watch <dir>`)
     #[arg(short, long)]
     watch_dir: Option<String>,
     
     /// Output directory for frame metadata
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     output_dir: Option<String>,
+    
+    /// Export pipeline spans to this OTLP gRPC endpoint; defaults to `OTEL_EXPORTER_OTLP_ENDPOINT`
+    #[arg(long, global = true)]
+    otlp_endpoint: Option<String>,
+    
+    /// Identity recorded in the data access log; defaults to the login user
+    #[arg(long, global = true)]
+    principal: Option<String>,
+    
+    /// Override a config setting, e.g. `--set scene_detection.ssim_threshold=0.9`; repeatable
+    #[arg(long = "set", value_name = "SETTING=VALUE", global = true)]
+    set: Vec<String>,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Watch a directory for new video segments and index them as they arrive
+    Watch {
+        /// Directory the recorder writes segments to
+        dir: String,
+    },
+    
+    /// Capture the screen directly and index it as it is recorded (macOS 13+, `capture` feature)
+    Capture,
+    
+    /// Process a video segment, an image or every one in a directory once and print a summary
+    #[command(alias = "analyze-dir")]
+    Process {
+        /// File or directory to process (directories are searched recursively)
+        path: String,
+        
+        /// OCR Parquet directory used to detect events on keyframes and images
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Only files recorded at or after this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+        
+        /// Only files recorded before this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+        
+        /// Skip files finished by an earlier run that was interrupted
+        #[arg(long)]
+        resume: bool,
+        
+        /// Progress file for resuming; defaults to `<output_dir>/batch_checkpoint.jsonl`
+        #[arg(long)]
+        checkpoint: Option<String>,
+        
+        /// Run the full pipeline but write nothing; print counts and the estimated storage instead
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Process the segments and frames that failed earlier again, e.g. after a fix
+    RetryFailed {
+        /// OCR Parquet directory used to detect events on keyframes and images
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Leave items that already failed this many retries alone
+        #[arg(long)]
+        max_retries: Option<u32>,
+        
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Ask the running service whether it is healthy and ready; exits non-zero when it is not
+    Status {
+        /// Address of the health endpoints; defaults to `health.bind_address`
+        #[arg(long)]
+        address: Option<String>,
+        
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Run the analysis stack on a single image and print the results as JSON
+    IndexImage {
+        /// Image to analyze
+        path: String,
+        
+        /// JSON file with OCR results for the image (an array of OCR results)
+        #[arg(long)]
+        ocr_json: Option<String>,
+        
+        /// OCR Parquet directory to look up stored results for the image
+        #[arg(long)]
+        ocr_dir: Option<String>,
+    },
+    
+    /// Run SQL or a canned query against the OCR (`ocr_data`) and event (`events`) outputs
+    Query {
+        /// SQL to run
+        sql: Option<String>,
+        
+        /// Name of a canned query to run instead of SQL
+        #[arg(long, conflicts_with = "sql")]
+        canned: Option<String>,
+        
+        /// List the canned queries
+        #[arg(long)]
+        list: bool,
+        
+        /// OCR Parquet directory
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: Option<String>,
+        
+        /// Print rows as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Find frames whose screen text is similar to a frame or a description, using stored embeddings
+    Similar {
+        /// Description of the screen, e.g. "invoice approval"
+        #[arg(long, conflicts_with = "frame", required_unless_present = "frame")]
+        text: Option<String>,
+        
+        /// Keyframe path to find similar screens for
+        #[arg(long)]
+        frame: Option<String>,
+        
+        /// Number of frames to return
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+        
+        /// Print the frames as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Convert stored outputs to CSV, JSON lines, a markdown daily log or a DuckDB database
+    Export {
+        /// Output to convert
+        #[arg(value_enum)]
+        table: ExportTable,
+        
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormatArg,
+        
+        /// OCR Parquet directory
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: Option<String>,
+        
+        /// Day to export as a markdown log (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+        
+        /// File to write (the directory for markdown); defaults to stdout, except for DuckDB
+        #[arg(long)]
+        output: Option<String>,
+        
+        /// Replace usernames, email addresses, window titles and URLs with pseudonyms
+        #[arg(long)]
+        anonymize: bool,
+        
+        /// Salt for the pseudonyms; reuse it to keep them stable across exports of one session
+        #[arg(long, requires = "anonymize")]
+        salt: Option<String>,
+    },
+    
+    /// Summarize a session as a Markdown or HTML report: time per app, navigation, errors and workflows
+    Report {
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: String,
+        
+        /// Workflow chain Parquet directory; defaults to the configured `workflow_chains` directory
+        #[arg(long)]
+        workflows_dir: Option<String>,
+        
+        /// Local day to report on (YYYY-MM-DD)
+        #[arg(long, conflicts_with_all = ["since", "until"], required_unless_present = "since")]
+        date: Option<chrono::NaiveDate>,
+        
+        /// Session start (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+        
+        /// Session end (RFC 3339 or YYYY-MM-DD, UTC); defaults to now
+        #[arg(long, value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+        
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ReportFormatArg,
+        
+        /// File to write; defaults to stdout
+        #[arg(long)]
+        output: Option<String>,
+    },
+    
+    /// Find action sequences repeated across sessions: candidate standard operating procedures
+    MineWorkflows {
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: String,
+        
+        /// Only events at or after this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+        
+        /// Only events before this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+        
+        /// Print the procedures as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Fit per-detector confidence curves from labeled events and store them for future events
+    Calibrate {
+        /// Event Parquet directory holding the labeled events
+        #[arg(long)]
+        events_dir: String,
+        
+        /// CSV file with `event_id` and `correct` (true or false positive) columns
+        #[arg(long)]
+        labels: String,
+        
+        /// Print the fitted curves as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Erase every keyframe, frame row, OCR result and event matching a time range, app or URL
+    Erase {
+        /// OCR Parquet directory; defaults to `ocr.results_dir`
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: String,
+        
+        /// Erase from this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+        
+        /// Erase before this time (RFC 3339 or YYYY-MM-DD, UTC)
+        #[arg(long, value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+        
+        /// App to erase; repeatable
+        #[arg(long = "app")]
+        apps: Vec<String>,
+        
+        /// Text in window titles to erase, e.g. a domain
+        #[arg(long)]
+        url: Option<String>,
+        
+        /// Keep rows with their text blanked and keyframes blacked out instead of deleting them
+        #[arg(long)]
+        redact: bool,
+        
+        /// Who asked for the erasure, recorded in the audit log
+        #[arg(long)]
+        requested_by: String,
+        
+        /// Report what would be erased without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Serve frames, OCR results and events over Arrow Flight until Ctrl+C (`flight` feature)
+    ServeFlight {
+        /// OCR Parquet directory
+        #[arg(long)]
+        ocr_dir: Option<String>,
+        
+        /// Event Parquet directory
+        #[arg(long)]
+        events_dir: Option<String>,
+    },
+    
+    /// Generate a small synthetic dataset and print example queries against it
+    Quickstart {
+        /// Directory to write the dataset to (must be empty); defaults to a new temp directory
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    
+    /// Check the data access audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    
+    /// Inspect the layered configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Load the config file with environment and `--set` overrides and report every invalid setting
+    Validate {
+        /// Print the issues as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Check the hash chain of the data access log; fails if an entry was modified, reordered or removed
+    Verify {
+        /// Log to check; defaults to `access_audit_path`
+        #[arg(long)]
+        path: Option<String>,
+        
+        /// Head hash printed by an earlier check; also catches entries removed from the end
+        #[arg(long)]
+        expect_head: Option<String>,
+        
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportTable {
+    Events,
+    Ocr,
+    /// Frame metadata from the output directory
+    Frames,
+    /// Merged cross-display timeline from the output directory
+    Timeline,
+    /// Submitted form records
+    Forms,
+    /// OCR text embeddings of the configured model
+    Embeddings,
+    /// Frames, OCR results and events in one DuckDB database with prebuilt views; needs `--output`
+    Duckdb,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormatArg {
+    Csv,
+    Jsonl,
+    /// Daily log note; events only, needs `--date`
+    Markdown,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormatArg {
+    Markdown,
+    Html,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::init();
-    
     let cli = Cli::parse();
     
-    let config = IndexerConfig::from_file(&cli.config)
-        .unwrap_or_else(|_| {
-            info!("Using default configuration");
-            IndexerConfig::default()
-        });
+    let watching = cli.command.is_none() || matches!(cli.command, Some(Command::Watch { .. } | Command::Capture));
+    let otlp_endpoint = cli.otlp_endpoint.clone().or_else(|| std::env::var(telemetry::OTLP_ENDPOINT_ENV).ok());
+    
+    if let Some(Command::Config { action: ConfigAction::Validate { json } }) = cli.command {
+        return validate_config(Path::new(&cli.config), &cli.set, json);
+    }
+    
+    // Logging is configured by the config file, so it is loaded first
+    let config_path = Path::new(&cli.config);
+    let mut config = IndexerConfig::load(Some(config_path), &cli.set)?;
+    // One-shot commands print results on stdout, so their logs go to stderr
+    let _telemetry = telemetry::init(otlp_endpoint.as_deref(), !watching, &config.logging)?;
+    if !config_path.exists() {
+        info!("No config file at {}; using defaults", config_path.display());
+    }
+    if let Some(output_dir) = cli.output_dir {
+        config.output_dir = output_dir;
+    }
+    
+    let principal = cli.principal
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string());
+    let access = AccessAuditLog::open(config.access_audit_path())?.for_principal(&principal);
     
+    match cli.command {
+        Some(Command::Watch { dir }) => watch(config, ConfigWatcher::new(&cli.config).with_overrides(cli.set), &dir).await,
+        Some(Command::Capture) => capture(config, ConfigWatcher::new(&cli.config).with_overrides(cli.set)).await,
+        Some(Command::Process { path, ocr_dir, json, since, until, resume, checkpoint, dry_run }) => {
+            let options = BatchOptions {
+                since,
+                until,
+                checkpoint: Some(checkpoint.map(PathBuf::from).unwrap_or_else(|| config.batch_checkpoint_path())),
+                resume,
+                shutdown: Some(ShutdownSignal::listen()?),
+            };
+            let ocr_dir = ocr_dir.or_else(|| config.ocr.results_dir.clone());
+            process(config, &access, &path, ocr_dir, json, &options, dry_run).await
+        }
+        Some(Command::RetryFailed { ocr_dir, max_retries, json }) => {
+            let ocr_dir = ocr_dir.or_else(|| config.ocr.results_dir.clone());
+            retry_failed(config, &access, ocr_dir, max_retries, json).await
+        }
+        Some(Command::Status { address, json }) => {
+            status(&address.unwrap_or_else(|| config.health.bind_address.clone()), json).await
+        }
+        Some(Command::IndexImage { path, ocr_json, ocr_dir }) => {
+            let ocr_dir = ocr_dir.or_else(|| config.ocr.results_dir.clone());
+            index_image(&config, &access, &path, ocr_json, ocr_dir).await
+        }
+        Some(Command::Query { sql, canned, list, ocr_dir, events_dir, json }) => {
+            let target = sql.clone().or_else(|| canned.as_ref().map(|name| format!("canned {}", name)));
+            let result = query(&config, sql, canned, list, ocr_dir.or_else(|| config.ocr.results_dir.clone()), events_dir, json).await;
+            match target {
+                Some(target) if !list => audited(&access, AccessOperation::Query, &target, result),
+                _ => result,
+            }
+        }
+        Some(Command::Similar { text, frame, k, json }) => {
+            let target = match (&text, &frame) {
+                (Some(text), _) => format!("similar to text '{}'", text),
+                (None, frame) => format!("similar to frame {}", frame.as_deref().unwrap_or_default()),
+            };
+            audited(&access, AccessOperation::Query, &target, similar(&config, text, frame, k, json))
+        }
+        Some(Command::Export { table, format, ocr_dir, events_dir, date, output, anonymize, salt }) => {
+            let ocr_dir = ocr_dir.or_else(|| config.ocr.results_dir.clone());
+            let anonymizer = if anonymize {
+                let salt = salt.unwrap_or_else(|| {
+                    let salt = Anonymizer::generate_salt();
+                    eprintln!("Anonymization salt: {} (pass --salt to reuse it for this session)", salt);
+                    salt
+                });
+                Some(Anonymizer::new(&config.anonymizer, &salt)?)
+            } else {
+                None
+            };
+            let target = format!(
+                "{} to {}",
+                table.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default(),
+                output.as_deref().unwrap_or("stdout")
+            );
+            let result = export(&config, &access, table, format, ocr_dir, events_dir, date, output, anonymizer.as_ref()).await;
+            audited(&access, AccessOperation::Export, &target, result)
+        }
+        Some(Command::Report { events_dir, workflows_dir, date, since, until, format, output }) => {
+            let target = format!("report of {} to {}", events_dir, output.as_deref().unwrap_or("stdout"));
+            let result = report(&config, &events_dir, workflows_dir, date, since, until, format, output).await;
+            audited(&access, AccessOperation::Export, &target, result)
+        }
+        Some(Command::MineWorkflows { events_dir, since, until, json }) => {
+            let target = format!("mine workflows in {}", events_dir);
+            audited(&access, AccessOperation::Query, &target, mine_workflows(&config, &events_dir, since, until, json).await)
+        }
+        Some(Command::Calibrate { events_dir, labels, json }) => {
+            let target = format!("calibrate from {}", events_dir);
+            audited(&access, AccessOperation::Query, &target, calibrate(&config, &events_dir, &labels, json).await)
+        }
+        Some(Command::Erase { ocr_dir, events_dir, since, until, apps, url, redact, requested_by, dry_run, json }) => {
+            let ocr_dir = ocr_dir
+                .or_else(|| config.ocr.results_dir.clone())
+                .ok_or_else(|| anyhow::anyhow!("Give --ocr-dir or set ocr.results_dir"))?;
+            let mode = if redact { ErasureMode::Redact } else { ErasureMode::Delete };
+            let request = ErasureRequest { since, until, apps, url, mode, requested_by };
+            erase(&config, &access, &ocr_dir, &events_dir, &request, dry_run, json)
+        }
+        Some(Command::ServeFlight { ocr_dir, events_dir }) => {
+            let ocr_dir = ocr_dir.or_else(|| config.ocr.results_dir.clone());
+            serve_flight(&config, ocr_dir, events_dir).await
+        }
+        Some(Command::Audit { action: AuditAction::Verify { path, expect_head, json } }) => {
+            let path = path.map(PathBuf::from).unwrap_or_else(|| config.access_audit_path());
+            verify_audit(&path, expect_head.as_deref(), json)
+        }
+        Some(Command::Quickstart { dir }) => quickstart(dir).await,
+        Some(Command::Config { .. }) => unreachable!("config commands run before the config is loaded"),
+        None => match cli.watch_dir {
+            Some(watch_dir) => watch(config, ConfigWatcher::new(&cli.config).with_overrides(cli.set), &watch_dir).await,
+            None => {
+                error!("No command given; run `indexer watch <dir>` or see `indexer --help`");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+async fn watch(config: IndexerConfig, config_watcher: ConfigWatcher, watch_dir: &str) -> Result<()> {
+    let mut service = IndexerService::new(config)?;
+    service.watch_config(config_watcher);
+    info!("Starting indexer service watching directory: {}", watch_dir);
+    service.start_watching(watch_dir).await?;
+    Ok(())
+}
+
+async fn capture(config: IndexerConfig, config_watcher: ConfigWatcher) -> Result<()> {
     let mut service = IndexerService::new(config)?;
+    service.watch_config(config_watcher);
+    service.start_capture(ShutdownSignal::listen()?).await?;
+    Ok(())
+}
+
+/// Print every invalid setting with its path; exits non-zero when there are any
+fn validate_config(config_path: &Path, overrides: &[String], json: bool) -> Result<()> {
+    let mut issues = Vec::new();
+    match IndexerConfig::from_layers(Some(config_path), std::env::vars(), overrides) {
+        Ok(config) => {
+            if config_path.exists() {
+                let unknown = IndexerConfig::unknown_settings(config_path)?;
+                issues.extend(unknown.into_iter().map(|setting| ConfigIssue::new(setting, "unknown setting")));
+            }
+            issues.extend(config.issues());
+            issues.extend(config.path_issues());
+        }
+        Err(e) => issues.push(ConfigIssue::new(config_path.display().to_string(), e.to_string())),
+    }
     
-    if let Some(watch_dir) = cli.watch_dir {
-        info!("Starting indexer service watching directory: {}", watch_dir);
-        service.start_watching(&watch_dir).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else if issues.is_empty() {
+        println!("{}: valid", config_path.display());
     } else {
-        error!("No watch directory specified");
+        for issue in &issues {
+            println!("{}", issue);
+        }
+    }
+    
+    if !issues.is_empty() {
         std::process::exit(1);
     }
+    Ok(())
+}
+
+/// OCR store at `dir`, decrypting files when the config enables encryption
+fn open_ocr_store(config: &IndexerConfig, access: &AccessAuditor, dir: &str) -> Result<OCRParquetWriter> {
+    let mut store = OCRParquetWriter::new(dir)?;
+    store.set_memory_budget(MemoryBudget::new(&config.writer_memory));
+    store.set_parquet_tuning(config.parquet_tuning.clone());
+    if config.encryption.enabled {
+        store.enable_encryption()?;
+        store.set_access_auditor(access.clone());
+    }
+    Ok(store)
+}
+
+/// Record a command's data access; a command whose access can't be recorded fails
+fn audited<T>(access: &AccessAuditor, operation: AccessOperation, target: &str, result: Result<T>) -> Result<T> {
+    access.record(operation, target, result.as_ref().err().map(|e| e.to_string()))?;
+    result
+}
+
+async fn index_image(
+    config: &IndexerConfig,
+    access: &AccessAuditor,
+    path: &str,
+    ocr_json: Option<String>,
+    ocr_dir: Option<String>,
+) -> Result<()> {
+    let provided_ocr: Option<Vec<OCRResult>> = match (ocr_json, ocr_dir) {
+        (Some(json_path), _) => Some(serde_json::from_str(&std::fs::read_to_string(json_path)?)?),
+        (None, Some(ocr_dir)) => {
+            let store = open_ocr_store(config, access, &ocr_dir)?;
+            Some(stored_ocr_for_image(&store, path).await?)
+        }
+        (None, None) => None,
+    };
     
+    let analysis = ImageAnalyzer::new(config)?.analyze(path, provided_ocr).await?;
+    println!("{}", serde_json::to_string_pretty(&analysis)?);
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Exits non-zero when any file fails, so CI runs over recorded videos can gate on it
+async fn process(
+    config: IndexerConfig,
+    access: &AccessAuditor,
+    path: &str,
+    ocr_dir: Option<String>,
+    json: bool,
+    options: &BatchOptions,
+    dry_run: bool,
+) -> Result<()> {
+    let ocr_store = ocr_dir.map(|d| open_ocr_store(&config, access, &d)).transpose()?;
+    
+    // Whatever the service sets up on disk goes to a scratch directory removed afterwards
+    let scratch_dir = std::env::temp_dir().join(format!("keyframe-indexer-dry-run-{}", std::process::id()));
+    let mut config = config;
+    if dry_run {
+        config.output_dir = scratch_dir.join("output").to_string_lossy().into_owned();
+        config.pending_ledger_path = None;
+        config.dead_letter_dir = None;
+    }
+    let mut service = IndexerService::new(config)?;
+    if dry_run {
+        service.enable_dry_run(&scratch_dir);
+    }
+    let result = service.analyze_path(Path::new(path), ocr_store.as_ref(), options).await;
+    let flushed = service.shutdown().await;
+    if dry_run {
+        let _ = std::fs::remove_dir_all(&scratch_dir);
+    }
+    let summary = result?;
+    flushed?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print!("{}", summary.to_text());
+    }
+    
+    if !summary.is_success() {
+        error!("{} files failed to analyze", summary.failures.len());
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Exits non-zero when any item is still failing
+async fn retry_failed(
+    config: IndexerConfig,
+    access: &AccessAuditor,
+    ocr_dir: Option<String>,
+    max_retries: Option<u32>,
+    json: bool,
+) -> Result<()> {
+    let ocr_store = ocr_dir.map(|d| open_ocr_store(&config, access, &d)).transpose()?;
+    let mut service = IndexerService::new(config)?;
+    let result = service.retry_failed(ocr_store.as_ref(), max_retries).await;
+    let flushed = service.shutdown().await;
+    let summary = result?;
+    flushed?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print!("{}", summary.to_text());
+    }
+    
+    if summary.still_failing > 0 {
+        error!("{} items are still failing", summary.still_failing);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn status(address: &str, json: bool) -> Result<()> {
+    let (_, status) = health::fetch_status(address, "/readyz")
+        .await
+        .map_err(|e| anyhow::anyhow!("Indexer not reachable at {} (is `health.enabled` set?): {}", address, e))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        println!("Healthy:        {}", status.healthy);
+        println!("Ready:          {}", status.ready);
+        println!("Watching:       {}", status.watcher_running);
+        println!(
+            "Last segment:   {}",
+            match (&status.last_segment, status.last_segment_processed_at) {
+                (Some(segment), Some(at)) => format!("{} at {}", segment, at.to_rfc3339()),
+                _ => "none".to_string(),
+            }
+        );
+        println!("Queued:         {}", status.pending_segments);
+        println!("Writer lag:     {}s", status.writer_lag_secs.unwrap_or(0));
+        for (name, granted) in &status.permissions {
+            println!("{:<16}{}", format!("{}:", name), if *granted { "ok" } else { "unavailable" });
+        }
+        for problem in &status.problems {
+            println!("Problem:        {}", problem);
+        }
+    }
+    if !status.ready {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn query(
+    config: &IndexerConfig,
+    sql: Option<String>,
+    canned: Option<String>,
+    list: bool,
+    ocr_dir: Option<String>,
+    events_dir: Option<String>,
+    json: bool,
+) -> Result<()> {
+    if list {
+        for query in CANNED_QUERIES {
+            println!("{:<20} {} ({})", query.name, query.description, query.table);
+        }
+        return Ok(());
+    }
+    
+    let mut output = OutputQuery::open(ocr_dir.as_deref().map(Path::new), events_dir.as_deref().map(Path::new)).await?;
+    output.register_plugin_tables(&config.plugin_tables_dir()).await?;
+    let batches = match (sql, canned) {
+        (Some(sql), _) => output.sql(&sql).await?,
+        (None, Some(name)) => output.canned(&name).await?,
+        (None, None) => anyhow::bail!("Give SQL to run, --canned <name> or --list"),
+    };
+    
+    if json {
+        println!("{}", format_json(&batches)?);
+    } else {
+        println!("{}", format_table(&batches)?);
+    }
+    Ok(())
+}
+
+async fn serve_flight(config: &IndexerConfig, ocr_dir: Option<String>, events_dir: Option<String>) -> Result<()> {
+    let mut output = OutputQuery::open(ocr_dir.as_deref().map(Path::new), events_dir.as_deref().map(Path::new)).await?;
+    output.register_frames(Path::new(&config.output_dir)).await?;
+    output.register_plugin_tables(&config.plugin_tables_dir()).await?;
+    // Redacted queries for non-admin keys read the same datasets
+    let queries = match (&ocr_dir, &events_dir) {
+        (Some(ocr_dir), Some(events_dir)) => Some(QueryService::from_config(config, ocr_dir, events_dir)?),
+        _ => None,
+    };
+    let access_log = AccessAuditLog::open(config.access_audit_path())?;
+    flight::serve(&config.flight, &config.auth, output, queries, Some(access_log), ShutdownSignal::listen()?).await?;
+    Ok(())
+}
+
+fn similar(config: &IndexerConfig, text: Option<String>, frame: Option<String>, k: usize, json: bool) -> Result<()> {
+    if !config.embeddings.enabled {
+        anyhow::bail!("Embeddings are not enabled; set embeddings.enabled and a provider in the config");
+    }
+    let store = config.embeddings.store(&config.output_dir)?;
+    let index = store.index()?;
+    let hits = match (text, frame) {
+        (Some(text), _) => store.search_text(&index, &text, k)?,
+        (None, Some(frame)) => index.similar_to_frame(&frame, k)?,
+        (None, None) => anyhow::bail!("Give --text or --frame"),
+    };
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+    } else {
+        for hit in hits {
+            let text: String = hit.text.chars().take(80).collect();
+            println!("{:.3}  {}  {}", hit.score, hit.frame_id, text);
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn export(
+    config: &IndexerConfig,
+    access: &AccessAuditor,
+    table: ExportTable,
+    format: ExportFormatArg,
+    ocr_dir: Option<String>,
+    events_dir: Option<String>,
+    date: Option<chrono::NaiveDate>,
+    output: Option<String>,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<()> {
+    if matches!(table, ExportTable::Duckdb) {
+        let Some(output) = output else {
+            anyhow::bail!("DuckDB export needs --output <file>");
+        };
+        if config.encryption.enabled {
+            anyhow::bail!("DuckDB can't read encrypted OCR files; export OCR to JSON lines instead");
+        }
+        let sources = DuckDbSources {
+            frames_dir: Some(PathBuf::from(&config.output_dir)),
+            ocr_dir: ocr_dir.map(PathBuf::from),
+            events_dir: events_dir.map(PathBuf::from),
+        };
+        let summary = match anonymizer {
+            // DuckDB reads the files itself, so it loads anonymized copies of them
+            Some(anonymizer) => {
+                let staging = std::env::temp_dir().join(format!("keyframe-anonymized-{}", uuid::Uuid::new_v4().simple()));
+                let summary = match stage_anonymized(config, access, &sources, anonymizer, &staging).await {
+                    Ok(staged) => keyframe_indexer::export_duckdb(&staged, Path::new(&output)).map_err(anyhow::Error::from),
+                    Err(e) => Err(e),
+                };
+                if staging.exists() {
+                    std::fs::remove_dir_all(&staging)?;
+                }
+                summary?
+            }
+            None => keyframe_indexer::export_duckdb(&sources, Path::new(&output))?,
+        };
+        for (table, rows) in &summary.tables {
+            info!("Exported {} rows to table {}", rows, table);
+        }
+        println!("{}", summary.path.display());
+        return Ok(());
+    }
+    
+    let ocr_store = ocr_dir.map(|d| open_ocr_store(config, access, &d)).transpose()?;
+    let event_store = events_dir.map(|d| EventParquetWriter::new(&d)).transpose()?;
+    
+    let format = match format {
+        ExportFormatArg::Csv => ExportFormat::Csv,
+        ExportFormatArg::Jsonl => ExportFormat::JsonLines,
+        ExportFormatArg::Markdown => {
+            if !matches!(table, ExportTable::Events) {
+                anyhow::bail!("Markdown export covers events only");
+            }
+            let (Some(event_store), Some(date)) = (event_store, date) else {
+                anyhow::bail!("Markdown export needs --events-dir and --date");
+            };
+            let mut markdown_config = config.markdown_export.clone();
+            if output.is_some() {
+                markdown_config.output_dir = output;
+            }
+            let mut exporter = MarkdownExporter::new(markdown_config, &config.output_dir)?
+                .with_suppressions(config.suppression_list()?);
+            if let Some(anonymizer) = anonymizer {
+                exporter = exporter.with_anonymizer(anonymizer.clone());
+            }
+            let note = exporter.export(date, &event_store, ocr_store.as_ref()).await?;
+            println!("{}", note.display());
+            return Ok(());
+        }
+    };
+    
+    let writer: Box<dyn std::io::Write> = match &output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let rows = match table {
+        ExportTable::Events => {
+            let Some(store) = event_store else {
+                anyhow::bail!("Exporting events needs --events-dir");
+            };
+            let mut events = store.query_by_confidence(0.0).await?;
+            if let Some(anonymizer) = anonymizer {
+                events = anonymizer.anonymize_events(events);
+            }
+            export_events(&events, format, writer)?
+        }
+        ExportTable::Ocr => {
+            let Some(store) = ocr_store else {
+                anyhow::bail!("Exporting OCR results needs --ocr-dir");
+            };
+            let mut results = store.query_by_confidence(0.0).await?;
+            if let Some(anonymizer) = anonymizer {
+                results = anonymizer.anonymize_ocr(results);
+            }
+            export_ocr(&results, format, writer)?
+        }
+        ExportTable::Frames => {
+            let mut frames = load_frames(Path::new(&config.output_dir)).await?;
+            if let Some(anonymizer) = anonymizer {
+                frames = anonymizer.anonymize_frames(frames);
+            }
+            export_frames(&frames, format, writer)?
+        }
+        ExportTable::Timeline => {
+            let mut entries = load_timeline(Path::new(&config.output_dir))?;
+            if let Some(anonymizer) = anonymizer {
+                entries = anonymizer.anonymize_timeline(entries);
+            }
+            export_timeline(&entries, format, writer)?
+        }
+        ExportTable::Forms => {
+            let store = FormRecordParquetWriter::new(&config.forms_dir().to_string_lossy())?;
+            let mut records = store.query_by_time_range(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC).await?;
+            if let Some(anonymizer) = anonymizer {
+                records = anonymizer.anonymize_form_records(records);
+            }
+            export_form_records(&records, format, writer)?
+        }
+        ExportTable::Embeddings => {
+            if !config.embeddings.enabled {
+                anyhow::bail!("Embeddings are not enabled; set embeddings.enabled and a provider in the config");
+            }
+            let mut embeddings = config.embeddings.store(&config.output_dir)?.load()?;
+            if let Some(anonymizer) = anonymizer {
+                embeddings = anonymizer.anonymize_embeddings(embeddings, config.embeddings.embedder()?.as_ref())?;
+            }
+            export_embeddings(&embeddings, format, writer)?
+        }
+        ExportTable::Duckdb => unreachable!("DuckDB export returns above"),
+    };
+    info!("Exported {} rows", rows);
+    Ok(())
+}
+
+/// Write anonymized copies of the frames, OCR results and events of `sources`
+/// to `dir`, and return them as the sources to export
+async fn stage_anonymized(
+    config: &IndexerConfig,
+    access: &AccessAuditor,
+    sources: &DuckDbSources,
+    anonymizer: &Anonymizer,
+    dir: &Path,
+) -> Result<DuckDbSources> {
+    let mut staged = DuckDbSources::default();
+    if let Some(frames_dir) = &sources.frames_dir {
+        let frames = anonymizer.anonymize_frames(load_frames(frames_dir).await?);
+        let mut writer = ParquetWriter::new(&dir.to_string_lossy())?;
+        writer.write_frame_metadata(&frames).await?;
+        writer.finalize().await?;
+        staged.frames_dir = Some(dir.to_path_buf());
+    }
+    if let Some(ocr_dir) = &sources.ocr_dir {
+        let results = open_ocr_store(config, access, &ocr_dir.to_string_lossy())?.query_by_confidence(0.0).await?;
+        let staged_dir = dir.join("ocr");
+        let mut writer = OCRParquetWriter::new(&staged_dir.to_string_lossy())?;
+        writer.write_ocr_results(&anonymizer.anonymize_ocr(results)).await?;
+        writer.finalize().await?;
+        staged.ocr_dir = Some(staged_dir);
+    }
+    if let Some(events_dir) = &sources.events_dir {
+        let events = EventParquetWriter::new(&events_dir.to_string_lossy())?.query_by_confidence(0.0).await?;
+        let staged_dir = dir.join("events");
+        let mut writer = EventParquetWriter::new(&staged_dir.to_string_lossy())?;
+        writer.write_events(&anonymizer.anonymize_events(events)).await?;
+        writer.finalize().await?;
+        staged.events_dir = Some(staged_dir);
+    }
+    Ok(staged)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn report(
+    config: &IndexerConfig,
+    events_dir: &str,
+    workflows_dir: Option<String>,
+    date: Option<chrono::NaiveDate>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    format: ReportFormatArg,
+    output: Option<String>,
+) -> Result<()> {
+    let event_store = EventParquetWriter::new(events_dir)?;
+    let workflows_dir = workflows_dir
+        .map(PathBuf::from)
+        .or_else(|| Some(config.workflow_chains_dir()).filter(|dir| dir.is_dir()));
+    let chain_store = workflows_dir.map(|d| WorkflowChainParquetWriter::new(&d.to_string_lossy())).transpose()?;
+    let generator = ReportGenerator::new(config.report.clone())?
+        .with_workflow_config(config.correlation.clone(), config.workflow_chains.clone())
+        .with_suppressions(config.suppression_list()?)
+        .with_meetings(load_meetings(Path::new(&config.output_dir))?);
+    let report = match (date, since) {
+        (Some(date), _) => generator.generate_day(date, &event_store, chain_store.as_ref()).await?,
+        (None, Some(since)) => {
+            let until = until.unwrap_or_else(Utc::now);
+            generator.generate(since, until, &event_store, chain_store.as_ref()).await?
+        }
+        (None, None) => anyhow::bail!("Give --date or --since"),
+    };
+    
+    let format = match format {
+        ReportFormatArg::Markdown => ReportFormat::Markdown,
+        ReportFormatArg::Html => ReportFormat::Html,
+    };
+    let rendered = report.render(format);
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            println!("{}", path);
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+async fn mine_workflows(
+    config: &IndexerConfig,
+    events_dir: &str,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    json: bool,
+) -> Result<()> {
+    let store = EventParquetWriter::new(events_dir)?;
+    let events = match (since, until) {
+        (None, None) => store.query_by_confidence(0.0).await?,
+        (since, until) => {
+            let since = since.unwrap_or(DateTime::<Utc>::MIN_UTC);
+            store.query_by_time_range(since, until.unwrap_or_else(Utc::now)).await?
+        }
+    };
+    let events = config.suppression_list()?.filter_events(&events);
+    let procedures = WorkflowMiner::new(config.workflow_miner.clone())?.mine(&events);
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&procedures)?);
+    } else {
+        for (index, procedure) in procedures.iter().enumerate() {
+            println!("{}. {} sessions, {} steps", index + 1, procedure.support, procedure.steps.len());
+            for step in &procedure.steps {
+                println!("   - {}", step);
+            }
+            for example in &procedure.examples {
+                println!("   e.g. {}: {}", example.start.format("%Y-%m-%d %H:%M"), example.frame_ids.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn calibrate(config: &IndexerConfig, events_dir: &str, labels: &str, json: bool) -> Result<()> {
+    let labels = load_labels(Path::new(labels))?;
+    let events = EventParquetWriter::new(events_dir)?.query_by_confidence(0.0).await?;
+    let calibration = Calibration::fit(&events, &labels, config.calibration.min_samples);
+    let path = config.calibration_path();
+    calibration.save(&path)?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&calibration)?);
+    } else {
+        for (detector, curve) in &calibration.curves {
+            println!(
+                "{}: {} labels, {} correct; 0.5 -> {:.2}, 0.9 -> {:.2}",
+                detector, curve.samples, curve.positives, curve.apply(0.5), curve.apply(0.9)
+            );
+        }
+        for (detector, samples) in &calibration.skipped {
+            println!("{}: only {} labels, needs {}; left uncalibrated", detector, samples, config.calibration.min_samples);
+        }
+        println!("Saved to {}", path.display());
+    }
+    Ok(())
+}
+
+fn erase(
+    config: &IndexerConfig,
+    access: &AccessAuditor,
+    ocr_dir: &str,
+    events_dir: &str,
+    request: &ErasureRequest,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let eraser = Eraser::new(&config.output_dir, ocr_dir, events_dir)
+        .with_embeddings_dir(config.embeddings.dir(&config.output_dir))
+        .with_forms_dir(config.forms_dir())
+        .with_access_auditor(access.clone());
+    let report = eraser.erase(request, dry_run)?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let verb = if dry_run { "Would erase" } else { "Erased" };
+        println!(
+            "{} {} frames ({} keyframe files, {} frame rows), {} OCR rows and {} events",
+            verb, report.frames, report.keyframe_files, report.frame_rows, report.ocr_rows, report.events
+        );
+        println!(
+            "   {} embeddings, {} form records, {} meeting rows and {} rows in output sinks",
+            report.embeddings, report.form_records, report.meeting_rows, report.sink_rows
+        );
+        let rewrite = if dry_run { "would rewrite" } else { "rewrote" };
+        for file in &report.files_rewritten {
+            println!("   {} {}", rewrite, file);
+        }
+        for file in &report.files_deleted {
+            println!("   deleted {}", file);
+        }
+        for store in &report.not_erased {
+            println!("   not covered, left as is: {}", store);
+        }
+        println!("Audit entry {}", report.erasure_id);
+    }
+    Ok(())
+}
+
+fn verify_audit(path: &Path, expect_head: Option<&str>, json: bool) -> Result<()> {
+    let verification = AccessAuditLog::open(path)?.verify(expect_head)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&verification)?);
+    } else {
+        println!("{} entries verified in {}", verification.entries, path.display());
+        if let Some(head) = &verification.head {
+            println!("Head {}", head);
+        }
+    }
+    if let Some(broken) = verification.broken {
+        anyhow::bail!("Audit log broken at line {}: {}", broken.line, broken.reason);
+    }
+    Ok(())
+}
+
+async fn quickstart(dir: Option<String>) -> Result<()> {
+    let root = match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::temp_dir().join(format!(
+            "keyframe-indexer-quickstart-{}",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S")
+        )),
+    };
+    let dataset = QuickstartDataset::generate(&root).await?;
+    
+    println!(
+        "Quickstart dataset in {}: {} frames, {} OCR results, {} events\n",
+        dataset.root.display(), dataset.frames, dataset.ocr_results, dataset.events
+    );
+    for example in dataset.query_examples() {
+        println!("# {}\n{}\n", example.description, example.command);
+    }
+    Ok(())
+}
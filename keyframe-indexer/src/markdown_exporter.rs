@@ -0,0 +1,472 @@
+use crate::anonymizer::Anonymizer;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::focus_summary::{format_minutes, local_day_bounds, switched_to_app};
+use crate::ocr_data::OCRResult;
+use crate::ocr_parquet_writer::OCRParquetWriter;
+use crate::suppression::SuppressionList;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Template used when no `template_path` is configured
+pub const DEFAULT_TEMPLATE: &str = "---
+date: {{date}}
+tags: [daily-log]
+---
+
+# {{date}}
+
+## Timeline
+
+{{timeline}}
+
+## Notable events
+
+{{events}}
+
+## Links
+
+{{urls}}
+
+## Tickets
+
+{{tickets}}
+";
+
+/// Markdown daily log export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownExportConfig {
+    pub enabled: bool,
+    /// Directory the `<date>.md` notes are written to, e.g. a folder in an Obsidian vault;
+    /// defaults to `<output_dir>/daily`
+    pub output_dir: Option<String>,
+    /// Markdown template with `{{date}}`, `{{timeline}}`, `{{events}}`, `{{urls}}`
+    /// and `{{tickets}}` placeholders; the built-in template is used when unset
+    pub template_path: Option<String>,
+    /// Directory holding keyframe images, used to link thumbnails for event frames
+    pub frames_dir: Option<String>,
+    /// Event types listed under notable events
+    pub notable_event_types: Vec<EventType>,
+    /// Minimum confidence for an event to be listed
+    pub min_event_confidence: f32,
+    /// Regex matching ticket identifiers in event and OCR text
+    pub ticket_pattern: String,
+    /// Gaps between app switches longer than this end the timeline entry
+    pub idle_gap_minutes: i64,
+}
+
+impl Default for MarkdownExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output_dir: None,
+            template_path: None,
+            frames_dir: None,
+            notable_event_types: vec![
+                EventType::ErrorDisplay,
+                EventType::ModalAppearance,
+                EventType::FormSubmission,
+            ],
+            min_event_confidence: 0.7,
+            ticket_pattern: r"\b[A-Z][A-Z0-9]{1,9}-\d{1,7}\b".to_string(),
+            idle_gap_minutes: 10,
+        }
+    }
+}
+
+impl MarkdownExportConfig {
+    pub fn validate(&self) -> Result<()> {
+        Regex::new(&self.ticket_pattern).map_err(|e| {
+            IndexerError::Config(format!("Invalid markdown_export ticket_pattern: {}", e))
+        })?;
+        if self.idle_gap_minutes <= 0 {
+            return Err(IndexerError::Config("markdown_export idle_gap_minutes must be positive".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.min_event_confidence) {
+            return Err(IndexerError::Config(
+                "markdown_export min_event_confidence must be between 0 and 1".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Time spent in one app between two switches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSpan {
+    pub app_name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// An event worth a line in the daily log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotableEvent {
+    pub timestamp: DateTime<Utc>,
+    pub event_type: EventType,
+    pub description: String,
+    /// Path of a keyframe showing the event, if one could be found
+    pub thumbnail: Option<String>,
+}
+
+/// URLs and ticket identifiers seen during the day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractedEntities {
+    pub urls: BTreeSet<String>,
+    pub tickets: BTreeSet<String>,
+}
+
+/// Finds URLs and ticket identifiers in free text
+pub struct EntityExtractor {
+    url_regex: Regex,
+    ticket_regex: Regex,
+}
+
+impl EntityExtractor {
+    pub fn new(ticket_pattern: &str) -> Result<Self> {
+        let url_regex = Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#)
+            .map_err(|e| IndexerError::Config(format!("Invalid URL pattern: {}", e)))?;
+        let ticket_regex = Regex::new(ticket_pattern)
+            .map_err(|e| IndexerError::Config(format!("Invalid ticket pattern: {}", e)))?;
+        Ok(Self { url_regex, ticket_regex })
+    }
+
+    /// Add the entities found in `text` to `entities`
+    pub fn extract(&self, text: &str, entities: &mut ExtractedEntities) {
+        for url in self.url_regex.find_iter(text) {
+            let url = url.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            entities.urls.insert(url.to_string());
+        }
+        for ticket in self.ticket_regex.find_iter(text) {
+            entities.tickets.insert(ticket.as_str().to_string());
+        }
+    }
+}
+
+/// Everything that goes into one daily note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyLog {
+    pub date: NaiveDate,
+    pub timeline: Vec<AppSpan>,
+    pub notable_events: Vec<NotableEvent>,
+    pub entities: ExtractedEntities,
+}
+
+/// Writes per-day markdown notes for personal knowledge bases
+pub struct MarkdownExporter {
+    config: MarkdownExportConfig,
+    output_dir: PathBuf,
+    template: String,
+    entity_extractor: EntityExtractor,
+    suppressions: Option<SuppressionList>,
+    anonymizer: Option<Anonymizer>,
+}
+
+impl MarkdownExporter {
+    pub fn new(config: MarkdownExportConfig, default_output_dir: &str) -> Result<Self> {
+        config.validate()?;
+        let output_dir = config
+            .output_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(default_output_dir).join("daily"));
+        std::fs::create_dir_all(&output_dir)?;
+
+        let template = match &config.template_path {
+            Some(path) => std::fs::read_to_string(path).map_err(|e| {
+                IndexerError::Config(format!("Failed to read markdown template {}: {}", path, e))
+            })?,
+            None => DEFAULT_TEMPLATE.to_string(),
+        };
+
+        let entity_extractor = EntityExtractor::new(&config.ticket_pattern)?;
+
+        Ok(Self { config, output_dir, template, entity_extractor, suppressions: None, anonymizer: None })
+    }
+
+    /// Leave events muted by suppression rules out of the notes
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Pseudonymize exported events and OCR text; notes link no keyframes,
+    /// as the images show everything the pseudonyms replace
+    pub fn with_anonymizer(mut self, anonymizer: Anonymizer) -> Self {
+        self.anonymizer = Some(anonymizer);
+        self
+    }
+
+    /// Assemble the log for one day from its events and the OCR text of their frames
+    pub fn build_log(&self, date: NaiveDate, events: &[DetectedEvent], ocr_results: &[OCRResult]) -> DailyLog {
+        let unsuppressed;
+        let events = match &self.suppressions {
+            Some(suppressions) => {
+                unsuppressed = suppressions.filter_events(events);
+                &unsuppressed[..]
+            }
+            None => events,
+        };
+        let mut events: Vec<&DetectedEvent> = events.iter().collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let notable_events = events
+            .iter()
+            .filter(|e| self.config.notable_event_types.contains(&e.event_type))
+            .filter(|e| e.confidence >= self.config.min_event_confidence)
+            .map(|e| NotableEvent {
+                timestamp: e.timestamp,
+                event_type: e.event_type.clone(),
+                description: describe_event(e),
+                thumbnail: e
+                    .evidence_frames
+                    .iter()
+                    .filter(|_| self.anonymizer.is_none())
+                    .find_map(|frame| thumbnail_for(frame, self.config.frames_dir.as_deref())),
+            })
+            .collect();
+
+        let mut entities = ExtractedEntities::default();
+        for event in &events {
+            let texts = [Some(&event.target), event.value_from.as_ref(), event.value_to.as_ref()];
+            for text in texts.into_iter().flatten().chain(event.metadata.values()) {
+                self.entity_extractor.extract(text, &mut entities);
+            }
+        }
+        for result in ocr_results {
+            self.entity_extractor.extract(&result.text, &mut entities);
+        }
+
+        DailyLog {
+            date,
+            timeline: app_timeline(&events, Duration::minutes(self.config.idle_gap_minutes)),
+            notable_events,
+            entities,
+        }
+    }
+
+    /// Render a log through the configured template
+    pub fn render(&self, log: &DailyLog) -> String {
+        let mut timeline = String::new();
+        for span in &log.timeline {
+            let _ = writeln!(
+                timeline,
+                "- {}–{} **{}** ({})",
+                span.start.with_timezone(&Local).format("%H:%M"),
+                span.end.with_timezone(&Local).format("%H:%M"),
+                span.app_name,
+                format_minutes((span.end - span.start).num_minutes())
+            );
+        }
+
+        let mut events = String::new();
+        for event in &log.notable_events {
+            let _ = writeln!(
+                events,
+                "- {} {:?}: {}",
+                event.timestamp.with_timezone(&Local).format("%H:%M:%S"),
+                event.event_type,
+                event.description
+            );
+            if let Some(thumbnail) = &event.thumbnail {
+                let _ = writeln!(events, "  ![frame](<{}>)", thumbnail);
+            }
+        }
+
+        let bullet_list = |items: &BTreeSet<String>| {
+            items.iter().map(|item| format!("- {}\n", item)).collect::<String>()
+        };
+
+        self.template
+            .replace("{{date}}", &log.date.to_string())
+            .replace("{{timeline}}", or_none(&timeline))
+            .replace("{{events}}", or_none(&events))
+            .replace("{{urls}}", or_none(&bullet_list(&log.entities.urls)))
+            .replace("{{tickets}}", or_none(&bullet_list(&log.entities.tickets)))
+    }
+
+    /// Load a day from the stores and write `<date>.md`
+    pub async fn export(
+        &self,
+        date: NaiveDate,
+        event_store: &EventParquetWriter,
+        ocr_store: Option<&OCRParquetWriter>,
+    ) -> Result<PathBuf> {
+        let (start, end) = local_day_bounds(date)?;
+        let events = event_store.query_by_time_range(start, end).await?;
+
+        // OCR text is looked up only for frames that carry evidence of an event
+        let mut ocr_results = Vec::new();
+        if let Some(ocr_store) = ocr_store {
+            let frames: BTreeSet<&String> = events.iter().flat_map(|e| e.evidence_frames.iter()).collect();
+            for frame_id in frames {
+                match ocr_store.query_by_frame_id(frame_id).await {
+                    Ok(results) => ocr_results.extend(results),
+                    Err(e) => warn!("Failed to load OCR text for frame {}: {}", frame_id, e),
+                }
+            }
+        }
+
+        let (events, ocr_results) = match &self.anonymizer {
+            Some(anonymizer) => {
+                // Suppression rules match the original titles, so they apply first
+                let events = match &self.suppressions {
+                    Some(suppressions) => suppressions.filter_events(&events),
+                    None => events,
+                };
+                (anonymizer.anonymize_events(events), anonymizer.anonymize_ocr(ocr_results))
+            }
+            None => (events, ocr_results),
+        };
+        let log = self.build_log(date, &events, &ocr_results);
+        let note_path = self.output_dir.join(format!("{}.md", date));
+        std::fs::write(&note_path, self.render(&log))?;
+        info!(
+            "Exported daily log to {} ({} events, {} URLs, {} tickets)",
+            note_path.display(),
+            log.notable_events.len(),
+            log.entities.urls.len(),
+            log.entities.tickets.len()
+        );
+
+        Ok(note_path)
+    }
+}
+
+/// App spans from navigation events, merging consecutive switches to the same app.
+///
+/// Each switch lasts until the next one, capped at `idle_gap`.
+pub(crate) fn app_timeline(events: &[&DetectedEvent], idle_gap: Duration) -> Vec<AppSpan> {
+    let switches: Vec<(DateTime<Utc>, String)> = events
+        .iter()
+        .filter(|e| e.event_type == EventType::Navigation)
+        .filter_map(|e| switched_to_app(e).map(|app| (e.timestamp, app)))
+        .collect();
+
+    let mut timeline: Vec<AppSpan> = Vec::new();
+    for (i, (start, app)) in switches.iter().enumerate() {
+        let next = switches.get(i + 1).map(|(t, _)| *t);
+        let end = next.filter(|t| *t - *start <= idle_gap).unwrap_or(*start + idle_gap);
+
+        match timeline.last_mut() {
+            Some(last) if last.app_name == *app && last.end == *start => last.end = end,
+            _ => timeline.push(AppSpan { app_name: app.clone(), start: *start, end }),
+        }
+    }
+
+    timeline
+}
+
+/// Keyframe image for an evidence frame: frame ids that are image paths are used
+/// directly, otherwise `<frames_dir>/<frame_id>.<ext>` is linked when it exists
+/// in any keyframe codec
+pub(crate) fn thumbnail_for(frame_id: &str, frames_dir: Option<&str>) -> Option<String> {
+    let path = Path::new(frame_id);
+    let is_image = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| matches!(ext.to_lowercase().as_str(), "png" | "jpg" | "jpeg" | "webp" | "avif"));
+    if is_image {
+        return Some(frame_id.to_string());
+    }
+
+    let frames_dir = Path::new(frames_dir?);
+    let candidate = ["png", "webp", "avif"]
+        .iter()
+        .map(|ext| frames_dir.join(format!("{}.{}", frame_id, ext)))
+        .find(|candidate| candidate.exists());
+    if let Some(candidate) = candidate {
+        Some(candidate.to_string_lossy().into_owned())
+    } else {
+        debug!("No thumbnail found for frame {}", frame_id);
+        None
+    }
+}
+
+pub(crate) fn describe_event(event: &DetectedEvent) -> String {
+    match (&event.value_from, &event.value_to) {
+        (Some(from), Some(to)) => format!("{}: {} → {}", event.subject(), from, to),
+        (None, Some(to)) => format!("{}: {}", event.subject(), to),
+        _ => event.subject().to_string(),
+    }
+}
+
+fn or_none(section: &str) -> &str {
+    if section.is_empty() {
+        "_None_\n"
+    } else {
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn create_event(minute: i64, event_type: EventType, target: &str, metadata: &[(&str, &str)]) -> DetectedEvent {
+        DetectedEvent {
+            id: format!("event_{}", minute),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap() + Duration::minutes(minute),
+            event_type,
+            target: target.to_string(),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: vec![format!("/frames/frame_{}.png", minute)],
+            metadata: metadata.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_daily_log_rendering() {
+        let temp_dir = TempDir::new().unwrap();
+        let exporter = MarkdownExporter::new(MarkdownExportConfig::default(), temp_dir.path().to_str().unwrap()).unwrap();
+
+        let events = vec![
+            create_event(0, EventType::Navigation, "window", &[("current_app", "Safari"), ("url", "https://jira.example.com/browse/OPS-42.")]),
+            create_event(5, EventType::Navigation, "window", &[("current_app", "Safari")]),
+            create_event(8, EventType::Navigation, "window", &[("current_app", "Xcode")]),
+            create_event(9, EventType::ErrorDisplay, "Build failed", &[]),
+            // Below the confidence threshold
+            DetectedEvent { confidence: 0.2, ..create_event(10, EventType::ModalAppearance, "Save?", &[]) },
+        ];
+        let ocr = vec![OCRResult {
+            frame_id: "frame_9".to_string(),
+            roi: crate::ocr_data::BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            text: "Fixes APP-7, see https://example.com/docs".to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }];
+
+        let log = exporter.build_log(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(), &events, &ocr);
+
+        // Consecutive Safari switches merge into one span
+        assert_eq!(log.timeline.len(), 2);
+        assert_eq!(log.timeline[0].app_name, "Safari");
+        assert_eq!((log.timeline[0].end - log.timeline[0].start).num_minutes(), 8);
+        assert_eq!(log.notable_events.len(), 1);
+        assert_eq!(log.notable_events[0].thumbnail.as_deref(), Some("/frames/frame_9.png"));
+        assert!(log.entities.urls.contains("https://jira.example.com/browse/OPS-42"));
+        assert!(log.entities.urls.contains("https://example.com/docs"));
+        assert_eq!(log.entities.tickets.iter().collect::<Vec<_>>(), vec!["APP-7", "OPS-42"]);
+
+        let markdown = exporter.render(&log);
+        assert!(markdown.contains("# 2024-03-05"));
+        assert!(markdown.contains("**Xcode**"));
+        assert!(markdown.contains("ErrorDisplay: Build failed"));
+        assert!(markdown.contains("![frame](</frames/frame_9.png>)"));
+        assert!(markdown.contains("- OPS-42"));
+    }
+}
@@ -1,7 +1,10 @@
 use crate::error::{IndexerError, Result};
 use crate::keyframe_extractor::Keyframe;
+use crate::ocr_density::OcrDensity;
+use crate::screen_classifier::ScreenType;
+use crate::visual_features::VisualFeatures;
+use crate::system_probe::SystemProbe;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 use tracing::{debug, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,12 +19,33 @@ pub struct FrameMetadata {
     pub win_title: String,
     pub width: u32,
     pub height: u32,
+    /// Path of the earlier frame in the segment this frame duplicates, if any
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    /// OCR box count, text coverage and confidence, once the frame's OCR is known
+    #[serde(default)]
+    pub ocr_density: Option<OcrDensity>,
+    /// Dominant colors, edge density and estimated text density of the image
+    #[serde(default)]
+    pub visual: Option<VisualFeatures>,
+    /// Browser, IDE, terminal or video call, once the frame is classified
+    #[serde(default)]
+    pub screen_type: Option<ScreenType>,
+}
+
+impl FrameMetadata {
+    /// Duplicate frames carry copied metadata and are skipped by OCR and event analysis
+    pub fn is_duplicate(&self) -> bool {
+        self.duplicate_of.is_some()
+    }
 }
 
 pub struct MetadataCollector {
     // Cache for active application info to avoid repeated system calls
     app_cache: Option<(String, String, std::time::Instant)>,
     cache_duration: std::time::Duration,
+    /// Runs the AppleScript probes with timeouts
+    probe: SystemProbe,
 }
 
 impl MetadataCollector {
@@ -29,14 +53,20 @@ impl MetadataCollector {
         Ok(Self {
             app_cache: None,
             cache_duration: std::time::Duration::from_secs(1), // Cache for 1 second
+            probe: SystemProbe::default(),
         })
     }
     
+    /// Share a probe, and its record of unresponsive apps, with other trackers
+    pub fn set_system_probe(&mut self, probe: SystemProbe) {
+        self.probe = probe;
+    }
+    
     pub async fn collect_metadata(&mut self, keyframe: &Keyframe) -> Result<FrameMetadata> {
         debug!("Collecting metadata for keyframe: {}", keyframe.id);
         
         // Get active application and window information
-        let (app_name, win_title) = self.get_active_app_info().await?;
+        let (app_name, win_title) = self.active_app_info().await?;
         
         // Calculate perceptual hash (simplified 16-bit version)
         let phash16 = self.calculate_simple_phash(&keyframe.frame_path).await?;
@@ -44,8 +74,11 @@ impl MetadataCollector {
         // Calculate image entropy
         let entropy = self.calculate_image_entropy(&keyframe.frame_path).await?;
         
-        // Extract monitor ID from segment ID or default to 0
-        let monitor_id = self.extract_monitor_id(&keyframe.segment_id);
+        // Cheap appearance features for telling screen types apart
+        let visual = self.calculate_visual_features(&keyframe.frame_path).await?;
+        
+        // The capture source's display when known, else the one named in the segment ID
+        let monitor_id = keyframe.display_id.unwrap_or_else(|| self.extract_monitor_id(&keyframe.segment_id));
         
         Ok(FrameMetadata {
             ts_ns: keyframe.timestamp_ns,
@@ -58,10 +91,15 @@ impl MetadataCollector {
             win_title,
             width: keyframe.width,
             height: keyframe.height,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: Some(visual),
+            screen_type: None,
         })
     }
     
-    async fn get_active_app_info(&mut self) -> Result<(String, String)> {
+    /// Frontmost app and window title, cached for a short while
+    pub async fn active_app_info(&mut self) -> Result<(String, String)> {
         // Check cache first
         if let Some((app_name, win_title, timestamp)) = &self.app_cache {
             if timestamp.elapsed() < self.cache_duration {
@@ -94,19 +132,14 @@ impl MetadataCollector {
             end tell
         "#;
         
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| IndexerError::Metadata(format!("Failed to execute AppleScript: {}", e)))?;
-        
-        if !output.status.success() {
-            warn!("AppleScript failed: {}", String::from_utf8_lossy(&output.stderr));
-            return Ok(("Unknown".to_string(), "Unknown".to_string()));
-        }
-        
-        let result = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = result.trim().split('|').collect();
+        let result = match self.probe.osascript("System Events", script).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Active app probe failed: {}", e);
+                return Ok(("Unknown".to_string(), "Unknown".to_string()));
+            }
+        };
+        let parts: Vec<&str> = result.split('|').collect();
         
         let app_name = parts.get(0).unwrap_or(&"Unknown").to_string();
         let win_title = parts.get(1).unwrap_or(&"Unknown").to_string();
@@ -115,7 +148,7 @@ impl MetadataCollector {
         Ok((app_name, win_title))
     }
     
-    async fn calculate_simple_phash(&self, image_path: &str) -> Result<i64> {
+    pub(crate) async fn calculate_simple_phash(&self, image_path: &str) -> Result<i64> {
         // Load and process image for pHash calculation
         let img = image::open(image_path)
             .map_err(|e| IndexerError::Metadata(format!("Failed to load image: {}", e)))?;
@@ -142,7 +175,7 @@ impl MetadataCollector {
         Ok(hash)
     }
     
-    async fn calculate_image_entropy(&self, image_path: &str) -> Result<f32> {
+    pub(crate) async fn calculate_image_entropy(&self, image_path: &str) -> Result<f32> {
         let img = image::open(image_path)
             .map_err(|e| IndexerError::Metadata(format!("Failed to load image: {}", e)))?;
         
@@ -168,19 +201,14 @@ impl MetadataCollector {
         Ok(entropy)
     }
     
+    pub(crate) async fn calculate_visual_features(&self, image_path: &str) -> Result<VisualFeatures> {
+        let img = image::open(image_path)
+            .map_err(|e| IndexerError::Metadata(format!("Failed to load image: {}", e)))?;
+        Ok(VisualFeatures::from_image(&img))
+    }
+    
     fn extract_monitor_id(&self, segment_id: &str) -> i32 {
-        // Try to extract monitor ID from segment ID
-        // Format might be: "segment_monitor1_timestamp" or similar
-        if let Some(monitor_part) = segment_id.split('_').find(|part| part.starts_with("monitor")) {
-            if let Some(id_str) = monitor_part.strip_prefix("monitor") {
-                if let Ok(id) = id_str.parse::<i32>() {
-                    return id;
-                }
-            }
-        }
-        
-        // Default to monitor 0 if not found
-        0
+        monitor_id_from_segment(segment_id)
     }
     
     pub fn clear_cache(&mut self) {
@@ -192,6 +220,15 @@ impl MetadataCollector {
     }
 }
 
+/// Monitor named in a segment ID such as "segment_monitor1_timestamp"; 0 when none is
+pub fn monitor_id_from_segment(segment_id: &str) -> i32 {
+    segment_id
+        .split('_')
+        .find_map(|part| part.strip_prefix("monitor"))
+        .and_then(|id| id.parse().ok())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,6 +303,8 @@ mod tests {
             width: 64,
             height: 64,
             format: "RGB".to_string(),
+            source_fps: None,
+            display_id: None,
         };
         
         let mut collector = MetadataCollector::new().unwrap();
@@ -0,0 +1,291 @@
+use crate::enum_codes::EnumCode;
+use crate::event_detector::{levenshtein_distance, DetectedEvent, EventType};
+use crate::ocr_data::BoundingBox;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Metadata key with the lifecycle phase of a dialog event, `appeared` or `dismissed`
+pub const MODAL_PHASE_KEY: &str = "modal_phase";
+pub const PHASE_APPEARED: &str = "appeared";
+pub const PHASE_DISMISSED: &str = "dismissed";
+/// Metadata key on a dismissal with the event type of the dialog, e.g. `error_display`
+pub const DIALOG_TYPE_KEY: &str = "dialog_type";
+
+/// Configuration for following dialogs across frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModalLifetimeConfig {
+    pub enabled: bool,
+    /// Minimum ROI overlap for a detection to be the same dialog as in an earlier frame
+    pub min_iou: f32,
+    /// Minimum text similarity (0-1) for a detection to be the same dialog
+    pub min_text_similarity: f32,
+    /// Consecutive frames a dialog must be missing from before it counts as dismissed
+    pub missing_frames_to_dismiss: u32,
+}
+
+impl Default for ModalLifetimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_iou: 0.5,
+            min_text_similarity: 0.7,
+            // A dialog hidden for a frame behind another window is still open
+            missing_frames_to_dismiss: 3,
+        }
+    }
+}
+
+/// A dialog currently on screen
+#[derive(Debug, Clone)]
+struct ActiveModal {
+    modal_id: String,
+    appeared: DetectedEvent,
+    roi: Option<BoundingBox>,
+    text: String,
+    first_seen: DateTime<Utc>,
+    last_frame: String,
+    frames_seen: u32,
+    missed_frames: u32,
+    /// First frame, and its time, the dialog was missing from
+    first_missed: Option<(String, DateTime<Utc>)>,
+}
+
+/// Turns per-frame error and modal detections into one event when a dialog
+/// appears and one `ModalDismissal` event when it is dismissed.
+///
+/// Detections in consecutive frames are the same dialog when their type
+/// matches, their ROIs overlap and their text is similar. Frames must be
+/// tracked in time order.
+pub struct ModalLifetimeTracker {
+    config: ModalLifetimeConfig,
+    active: Vec<ActiveModal>,
+}
+
+impl ModalLifetimeTracker {
+    pub fn new(config: ModalLifetimeConfig) -> Self {
+        Self {
+            config,
+            active: Vec::new(),
+        }
+    }
+
+    /// Replace a frame's error and modal detections with appear and dismiss
+    /// events; other events pass through unchanged
+    pub fn track_frame(&mut self, frame_id: &str, timestamp: DateTime<Utc>, events: Vec<DetectedEvent>) -> Vec<DetectedEvent> {
+        if !self.config.enabled {
+            return events;
+        }
+
+        let mut output = Vec::with_capacity(events.len());
+        let mut seen = vec![false; self.active.len()];
+
+        for event in events {
+            if !matches!(event.event_type, EventType::ErrorDisplay | EventType::ModalAppearance) {
+                output.push(event);
+                continue;
+            }
+
+            let roi = BoundingBox::from_metadata(&event.metadata);
+            let text = event.value_to.clone().unwrap_or_else(|| event.target.clone());
+            let matched = self.active.iter().enumerate().position(|(index, modal)| {
+                !seen[index] && self.is_same_dialog(modal, &event, roi.as_ref(), &text)
+            });
+
+            match matched {
+                Some(index) => {
+                    seen[index] = true;
+                    let modal = &mut self.active[index];
+                    modal.frames_seen += 1;
+                    modal.missed_frames = 0;
+                    modal.first_missed = None;
+                    modal.last_frame = frame_id.to_string();
+                }
+                None => {
+                    let modal_id = uuid::Uuid::new_v4().to_string();
+                    let mut appeared = event;
+                    appeared.metadata.insert(MODAL_PHASE_KEY.to_string(), PHASE_APPEARED.to_string());
+                    appeared.metadata.insert("modal_id".to_string(), modal_id.clone());
+                    debug!("Dialog {} appeared in frame {}: {}", modal_id, frame_id, text);
+
+                    self.active.push(ActiveModal {
+                        modal_id,
+                        appeared: appeared.clone(),
+                        roi,
+                        text,
+                        first_seen: timestamp,
+                        last_frame: frame_id.to_string(),
+                        frames_seen: 1,
+                        missed_frames: 0,
+                        first_missed: None,
+                    });
+                    seen.push(true);
+                    output.push(appeared);
+                }
+            }
+        }
+
+        // Dialogs missing from this frame are gone once they stay missing long enough
+        let mut still_active = Vec::with_capacity(self.active.len());
+        for (modal, seen) in std::mem::take(&mut self.active).into_iter().zip(seen) {
+            if seen {
+                still_active.push(modal);
+                continue;
+            }
+            let mut modal = modal;
+            modal.missed_frames += 1;
+            modal.first_missed.get_or_insert_with(|| (frame_id.to_string(), timestamp));
+            if modal.missed_frames >= self.config.missing_frames_to_dismiss.max(1) {
+                output.push(Self::dismissed(modal));
+            } else {
+                still_active.push(modal);
+            }
+        }
+        self.active = still_active;
+
+        output
+    }
+
+    /// Dialogs on screen as of the last tracked frame
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Forget all dialogs without reporting them as dismissed
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    fn is_same_dialog(&self, modal: &ActiveModal, event: &DetectedEvent, roi: Option<&BoundingBox>, text: &str) -> bool {
+        if modal.appeared.event_type != event.event_type {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (&modal.roi, roi) {
+            if a.iou(b) < self.config.min_iou {
+                return false;
+            }
+        }
+        text_similarity(&modal.text, text) >= self.config.min_text_similarity
+    }
+
+    /// Dismissal of a dialog as of the first frame it was missing from
+    fn dismissed(modal: ActiveModal) -> DetectedEvent {
+        let (frame_id, timestamp) = modal.first_missed.expect("dismissed dialogs have been missed");
+        let duration_ms = (timestamp - modal.first_seen).num_milliseconds().max(0);
+        debug!("Dialog {} dismissed after {} ms", modal.modal_id, duration_ms);
+
+        let mut metadata = modal.appeared.metadata;
+        metadata.insert(MODAL_PHASE_KEY.to_string(), PHASE_DISMISSED.to_string());
+        metadata.insert("display_duration_ms".to_string(), duration_ms.to_string());
+        metadata.insert("frames_seen".to_string(), modal.frames_seen.to_string());
+        metadata.insert(DIALOG_TYPE_KEY.to_string(), modal.appeared.event_type.name().to_string());
+
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: EventType::ModalDismissal,
+            target: modal.appeared.target,
+            value_from: Some(modal.text),
+            value_to: None,
+            confidence: modal.appeared.confidence,
+            evidence_frames: vec![modal.last_frame, frame_id],
+            metadata,
+        }
+    }
+}
+
+/// Whether an event reports a dialog going away; dismissals stored before
+/// they had their own type kept the dialog's type and a dismissed phase
+pub fn is_dismissal(event: &DetectedEvent) -> bool {
+    event.event_type == EventType::ModalDismissal
+        || event.metadata.get(MODAL_PHASE_KEY).map(String::as_str) == Some(PHASE_DISMISSED)
+}
+
+/// Type of the dialog a dismissal reports, `ErrorDisplay` or `ModalAppearance`
+pub fn dismissed_type(event: &DetectedEvent) -> Option<EventType> {
+    if !is_dismissal(event) {
+        return None;
+    }
+    if event.event_type != EventType::ModalDismissal {
+        return Some(event.event_type.clone());
+    }
+    event.metadata.get(DIALOG_TYPE_KEY).map(String::as_str).and_then(EventType::from_name)
+}
+
+/// Whether an event reports an error appearing, not one going away
+pub fn is_error(event: &DetectedEvent) -> bool {
+    event.event_type == EventType::ErrorDisplay && !is_dismissal(event)
+}
+
+fn text_similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein_distance(a, b) as f32 / max_len as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::collections::HashMap;
+
+    fn dialog(text: &str, x: f32, at: DateTime<Utc>) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("roi_x".to_string(), x.to_string());
+        metadata.insert("roi_y".to_string(), "200".to_string());
+        metadata.insert("roi_width".to_string(), "400".to_string());
+        metadata.insert("roi_height".to_string(), "200".to_string());
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: at,
+            event_type: EventType::ErrorDisplay,
+            target: "error_dialog".to_string(),
+            value_from: None,
+            value_to: Some(text.to_string()),
+            confidence: 0.9,
+            evidence_frames: Vec::new(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_persistent_dialog_yields_appear_and_dismiss() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        let mut tracker = ModalLifetimeTracker::new(ModalLifetimeConfig::default());
+
+        let first = tracker.track_frame("frame_1", at(0), vec![dialog("Connection failed", 300.0, at(0))]);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].metadata[MODAL_PHASE_KEY], PHASE_APPEARED);
+
+        // Same dialog with slightly different OCR, plus a second dialog elsewhere
+        let second = tracker.track_frame("frame_2", at(2), vec![
+            dialog("Connection faled", 305.0, at(2)),
+            dialog("Disk almost full", 1200.0, at(2)),
+        ]);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].value_to.as_deref(), Some("Disk almost full"));
+        assert_eq!(tracker.active_count(), 2);
+
+        // Missing from one or two frames is not yet a dismissal
+        assert!(tracker.track_frame("frame_3", at(5), Vec::new()).is_empty());
+        assert!(tracker.track_frame("frame_4", at(6), Vec::new()).is_empty());
+
+        let fifth = tracker.track_frame("frame_5", at(7), Vec::new());
+        assert_eq!(fifth.len(), 2);
+        assert!(fifth.iter().all(is_dismissal));
+        assert!(fifth.iter().all(|e| dismissed_type(e) == Some(EventType::ErrorDisplay)));
+        let connection = fifth.iter().find(|e| e.value_from.as_deref() == Some("Connection failed")).unwrap();
+        assert_eq!(connection.metadata["display_duration_ms"], "5000");
+        assert_eq!(connection.metadata["frames_seen"], "2");
+        assert_eq!(connection.metadata["modal_id"], first[0].metadata["modal_id"]);
+        assert_eq!(connection.evidence_frames, vec!["frame_2".to_string(), "frame_3".to_string()]);
+        assert_eq!(tracker.active_count(), 0);
+    }
+}
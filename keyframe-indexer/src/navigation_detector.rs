@@ -1,9 +1,10 @@
+use crate::browser_poller::{query_frontmost_tab, BrowserPoller, BrowserPollerConfig};
 use crate::error::{IndexerError, Result};
 use crate::event_detector::{DetectedEvent, EventType};
+use crate::system_probe::SystemProbe;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::process::Command;
 use tracing::{debug, info, warn, error};
 
 /// Navigation event detector for window and tab changes according to requirements 4.2 and 4.3
@@ -18,10 +19,15 @@ pub struct NavigationDetector {
     focus_history: Vec<FocusEvent>,
     /// Maximum history size to maintain
     max_history_size: usize,
+    /// Background browser poller, started on the first tab detection
+    browser_poller: Option<BrowserPoller>,
+    /// Runs the AppleScript probes with timeouts
+    probe: SystemProbe,
 }
 
 /// Configuration for navigation detection behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NavigationDetectionConfig {
     /// Enable window change detection
     pub enable_window_detection: bool,
@@ -33,6 +39,8 @@ pub struct NavigationDetectionConfig {
     pub min_detection_interval_ms: u64,
     /// Confidence threshold for navigation events
     pub min_confidence: f32,
+    /// How browser tabs are read
+    pub browser_poller: BrowserPollerConfig,
 }
 
 impl Default for NavigationDetectionConfig {
@@ -43,6 +51,7 @@ impl Default for NavigationDetectionConfig {
             enable_focus_detection: true,
             min_detection_interval_ms: 100,
             min_confidence: 0.8,
+            browser_poller: BrowserPollerConfig::default(),
         }
     }
 }
@@ -68,6 +77,16 @@ pub struct TabState {
     pub timestamp: DateTime<Utc>,
 }
 
+impl TabState {
+    /// Same browser, tab, title and URL, whenever each was observed
+    pub fn same_tab(&self, other: &TabState) -> bool {
+        self.app_name == other.app_name
+            && self.tab_title == other.tab_title
+            && self.url == other.url
+            && self.tab_index == other.tab_index
+    }
+}
+
 /// Represents an application focus change event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FocusEvent {
@@ -93,9 +112,17 @@ impl NavigationDetector {
             previous_tab_state: None,
             focus_history: Vec::new(),
             max_history_size: 100,
+            browser_poller: None,
+            probe: SystemProbe::default(),
         }
     }
     
+    /// Share a probe, and its record of unresponsive apps, with other trackers
+    pub fn set_system_probe(&mut self, probe: SystemProbe) {
+        self.probe = probe;
+        self.browser_poller = None;
+    }
+    
     /// Detect navigation events by analyzing current system state
     pub async fn detect_navigation_events(&mut self, frame_id: &str, timestamp: DateTime<Utc>) -> Result<Vec<DetectedEvent>> {
         debug!("Detecting navigation events for frame {}", frame_id);
@@ -174,36 +201,46 @@ impl NavigationDetector {
         Ok(events)
     }
     
-    /// Detect tab changes in browsers and tab-based applications
+    /// Detect tab changes in browsers and tab-based applications.
+    ///
+    /// With the browser poller enabled, changes reported by its background task
+    /// since the previous frame are used; otherwise the frontmost browser is queried now.
     async fn detect_tab_changes(&mut self, frame_id: &str, timestamp: DateTime<Utc>) -> Result<Vec<DetectedEvent>> {
-        let current_tab_state = self.get_current_tab_state().await?;
+        let observed: Vec<TabState> = if self.config.browser_poller.enabled {
+            self.browser_poller
+                .get_or_insert_with(|| BrowserPoller::spawn(self.config.browser_poller.clone(), self.probe.clone()))
+                .drain()
+        } else {
+            query_frontmost_tab(&self.probe, &self.config.browser_poller.browsers).await?.into_iter().collect()
+        };
         let mut events = Vec::new();
         
-        if let Some(current_tab) = current_tab_state {
+        for current_tab in observed {
             // Check if tab state has changed
             if let Some(previous_tab) = &self.previous_tab_state {
-                if current_tab != *previous_tab {
-                    // Check minimum interval
-                    let time_diff = timestamp.signed_duration_since(previous_tab.timestamp);
-                    if time_diff.num_milliseconds() >= self.config.min_detection_interval_ms as i64 {
-                        
-                        let event = DetectedEvent {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            timestamp,
-                            event_type: EventType::Navigation,
-                            target: format!("tab_{}_{}", current_tab.app_name, current_tab.tab_index.unwrap_or(0)),
-                            value_from: Some(previous_tab.tab_title.clone()),
-                            value_to: Some(current_tab.tab_title.clone()),
-                            confidence: self.config.min_confidence * 0.9, // Slightly lower confidence for tab detection
-                            evidence_frames: vec![frame_id.to_string()],
-                            metadata: self.create_tab_metadata(&current_tab, previous_tab),
-                        };
-                        
-                        events.push(event);
-                        debug!("Detected tab change: {} -> {}", 
-                               previous_tab.tab_title, 
-                               current_tab.tab_title);
-                    }
+                if current_tab.same_tab(previous_tab) {
+                    continue;
+                }
+                // Check minimum interval
+                let time_diff = current_tab.timestamp.signed_duration_since(previous_tab.timestamp);
+                if time_diff.num_milliseconds() >= self.config.min_detection_interval_ms as i64 {
+                    
+                    let event = DetectedEvent {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        timestamp,
+                        event_type: EventType::Navigation,
+                        target: format!("tab_{}_{}", current_tab.app_name, current_tab.tab_index.unwrap_or(0)),
+                        value_from: Some(previous_tab.tab_title.clone()),
+                        value_to: Some(current_tab.tab_title.clone()),
+                        confidence: self.config.min_confidence * 0.9, // Slightly lower confidence for tab detection
+                        evidence_frames: vec![frame_id.to_string()],
+                        metadata: self.create_tab_metadata(&current_tab, previous_tab),
+                    };
+                    
+                    events.push(event);
+                    debug!("Detected tab change: {} -> {}", 
+                           previous_tab.tab_title, 
+                           current_tab.tab_title);
                 }
             }
             
@@ -285,23 +322,11 @@ impl NavigationDetector {
             end tell
         "#;
         
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| IndexerError::Navigation(format!("Failed to get window state: {}", e)))?;
-        
-        if !output.status.success() {
-            return Err(IndexerError::Navigation(
-                format!("AppleScript failed: {}", String::from_utf8_lossy(&output.stderr))
-            ));
-        }
-        
-        let result = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = result.trim().split('|').collect();
+        let result = self.probe.osascript("System Events", script).await?;
+        let parts: Vec<&str> = result.split('|').collect();
         
         if parts.len() < 5 {
-            return Err(IndexerError::Navigation("Invalid AppleScript response".to_string()));
+            return Err(IndexerError::navigation("Invalid AppleScript response"));
         }
         
         Ok(WindowState {
@@ -314,104 +339,6 @@ impl NavigationDetector {
         })
     }
     
-    /// Get current tab state for browsers and tab-based applications
-    async fn get_current_tab_state(&self) -> Result<Option<TabState>> {
-        // Try to get tab information from supported browsers
-        if let Ok(safari_tab) = self.get_safari_tab_state().await {
-            return Ok(Some(safari_tab));
-        }
-        
-        if let Ok(chrome_tab) = self.get_chrome_tab_state().await {
-            return Ok(Some(chrome_tab));
-        }
-        
-        // Add support for other browsers as needed
-        Ok(None)
-    }
-    
-    /// Get Safari tab state using AppleScript
-    async fn get_safari_tab_state(&self) -> Result<TabState> {
-        let script = r#"
-            tell application "Safari"
-                if (count of windows) > 0 then
-                    set currentTab to current tab of front window
-                    set tabTitle to name of currentTab
-                    set tabURL to URL of currentTab
-                    set tabIndex to index of currentTab
-                    return tabTitle & "|" & tabURL & "|" & tabIndex
-                else
-                    return "||0"
-                end if
-            end tell
-        "#;
-        
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| IndexerError::Navigation(format!("Failed to get Safari tab: {}", e)))?;
-        
-        if !output.status.success() {
-            return Err(IndexerError::Navigation("Safari not available".to_string()));
-        }
-        
-        let result = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = result.trim().split('|').collect();
-        
-        if parts.len() < 3 {
-            return Err(IndexerError::Navigation("Invalid Safari response".to_string()));
-        }
-        
-        Ok(TabState {
-            app_name: "Safari".to_string(),
-            tab_title: parts[0].to_string(),
-            url: if parts[1].is_empty() { None } else { Some(parts[1].to_string()) },
-            tab_index: parts[2].parse().ok(),
-            timestamp: Utc::now(),
-        })
-    }
-    
-    /// Get Chrome tab state using AppleScript
-    async fn get_chrome_tab_state(&self) -> Result<TabState> {
-        let script = r#"
-            tell application "Google Chrome"
-                if (count of windows) > 0 then
-                    set currentTab to active tab of front window
-                    set tabTitle to title of currentTab
-                    set tabURL to URL of currentTab
-                    return tabTitle & "|" & tabURL & "|1"
-                else
-                    return "||0"
-                end if
-            end tell
-        "#;
-        
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| IndexerError::Navigation(format!("Failed to get Chrome tab: {}", e)))?;
-        
-        if !output.status.success() {
-            return Err(IndexerError::Navigation("Chrome not available".to_string()));
-        }
-        
-        let result = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = result.trim().split('|').collect();
-        
-        if parts.len() < 3 {
-            return Err(IndexerError::Navigation("Invalid Chrome response".to_string()));
-        }
-        
-        Ok(TabState {
-            app_name: "Google Chrome".to_string(),
-            tab_title: parts[0].to_string(),
-            url: if parts[1].is_empty() { None } else { Some(parts[1].to_string()) },
-            tab_index: parts[2].parse().ok(),
-            timestamp: Utc::now(),
-        })
-    }
-    
     /// Get current application focus state
     async fn get_current_focus_state(&self) -> Result<FocusEvent> {
         let current_window = self.get_current_window_state().await?;
@@ -512,6 +439,10 @@ impl NavigationDetector {
     
     /// Update configuration
     pub fn update_config(&mut self, config: NavigationDetectionConfig) {
+        // A restarted poller picks up new browsers and intervals
+        if config.browser_poller != self.config.browser_poller {
+            self.browser_poller = None;
+        }
         self.config = config;
     }
 }
@@ -573,6 +504,7 @@ mod tests {
             enable_focus_detection: false,
             min_detection_interval_ms: 500,
             min_confidence: 0.9,
+            ..NavigationDetectionConfig::default()
         };
         
         detector.update_config(new_config.clone());
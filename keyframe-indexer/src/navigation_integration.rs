@@ -2,8 +2,17 @@ use crate::error::{IndexerError, Result};
 use crate::event_detector::{DetectedEvent, EventType};
 use crate::navigation_detector::{NavigationDetector, NavigationDetectionConfig};
 use crate::cursor_tracker::{CursorTracker, CursorTrackingConfig};
+use crate::display_topology::DisplayTopology;
 use crate::event_correlator::{EventCorrelator, CorrelationConfig, CorrelationResult};
 use crate::event_parquet_writer::EventParquetWriter;
+use crate::event_signing::EventSigner;
+use crate::correlation_parquet_writer::CorrelationParquetWriter;
+use crate::live_snapshot::SnapshotTracker;
+use crate::event_triggers::TriggerEngine;
+use crate::notifier::WebhookNotifier;
+use crate::system_probe::{SystemProbe, SystemProbeConfig};
+use crate::parquet_tuning::ParquetTuningConfig;
+use crate::writer_memory::MemoryBudget;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
@@ -21,10 +30,18 @@ pub struct NavigationIntegrationService {
     event_correlator: EventCorrelator,
     /// Event storage writer
     event_writer: EventParquetWriter,
+    /// Correlation storage writer; correlations are only returned without one
+    correlation_writer: Option<CorrelationParquetWriter>,
     /// Configuration for the integration service
     pub config: NavigationIntegrationConfig,
     /// Performance metrics
     metrics: NavigationMetrics,
+    /// Live activity snapshot updated after each frame
+    snapshot: Option<SnapshotTracker>,
+    /// External command triggers fired by detected events
+    triggers: Option<TriggerEngine>,
+    /// Chat webhooks posted to for detected events
+    notifier: Option<WebhookNotifier>,
 }
 
 /// Configuration for the navigation integration service
@@ -42,6 +59,8 @@ pub struct NavigationIntegrationConfig {
     pub event_batch_size: usize,
     /// Processing interval in milliseconds
     pub processing_interval_ms: u64,
+    /// Timeouts and circuit breaking shared by the navigation and cursor probes
+    pub system_probe: SystemProbeConfig,
 }
 
 impl Default for NavigationIntegrationConfig {
@@ -53,6 +72,7 @@ impl Default for NavigationIntegrationConfig {
             enable_comprehensive_logging: true,
             event_batch_size: 50,
             processing_interval_ms: 100,
+            system_probe: SystemProbeConfig::default(),
         }
     }
 }
@@ -86,8 +106,12 @@ impl NavigationIntegrationService {
     
     /// Create a new navigation integration service with custom configuration
     pub fn with_config(event_storage_dir: &str, config: NavigationIntegrationConfig) -> Result<Self> {
-        let navigation_detector = NavigationDetector::with_config(config.navigation_config.clone());
-        let cursor_tracker = CursorTracker::with_config(config.cursor_config.clone());
+        // One probe, so an app that stops responding is skipped by both trackers
+        let probe = SystemProbe::new(config.system_probe.clone());
+        let mut navigation_detector = NavigationDetector::with_config(config.navigation_config.clone());
+        navigation_detector.set_system_probe(probe.clone());
+        let mut cursor_tracker = CursorTracker::with_config(config.cursor_config.clone());
+        cursor_tracker.set_system_probe(probe);
         let event_correlator = EventCorrelator::with_config(config.correlation_config.clone());
         let event_writer = EventParquetWriter::new(event_storage_dir)?;
         
@@ -96,11 +120,42 @@ impl NavigationIntegrationService {
             cursor_tracker,
             event_correlator,
             event_writer,
+            correlation_writer: None,
             config,
             metrics: NavigationMetrics::default(),
+            snapshot: None,
+            triggers: None,
+            notifier: None,
         })
     }
     
+    /// Publish detected events, cursor position and detector state to a live snapshot
+    pub fn with_snapshot_tracker(mut self, snapshot: SnapshotTracker) -> Self {
+        for detector in ["navigation_detector", "cursor_tracker", "event_correlator"] {
+            snapshot.set_detector_active(detector, true);
+        }
+        self.snapshot = Some(snapshot);
+        self
+    }
+    
+    /// Store every correlation found, not only return it
+    pub fn with_correlation_writer(mut self, writer: CorrelationParquetWriter) -> Self {
+        self.correlation_writer = Some(writer);
+        self
+    }
+    
+    /// Run configured external commands when detected events match their predicates
+    pub fn with_trigger_engine(mut self, triggers: TriggerEngine) -> Self {
+        self.triggers = Some(triggers);
+        self
+    }
+    
+    /// Post Slack or Teams messages when detected events match a notifier's predicate
+    pub fn with_notifier(mut self, notifier: WebhookNotifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+    
     /// Process a frame and detect all navigation and interaction events
     pub async fn process_frame(&mut self, frame_id: &str, timestamp: DateTime<Utc>) -> Result<NavigationEventResult> {
         let start_time = std::time::Instant::now();
@@ -176,7 +231,16 @@ impl NavigationIntegrationService {
             }
         };
         
-        // 5. Store events in Parquet format
+        // 5. Fire triggers and collect outcomes of commands that finished since the last frame
+        if let Some(triggers) = &self.triggers {
+            triggers.dispatch(&all_events);
+            all_events.extend(triggers.drain_follow_up_events());
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.dispatch(&all_events);
+        }
+        
+        // Store events, including trigger outcomes, in Parquet format
         if !all_events.is_empty() {
             if let Err(e) = self.event_writer.write_events(&all_events).await {
                 error!("Failed to write events for frame {}: {}", frame_id, e);
@@ -184,7 +248,20 @@ impl NavigationIntegrationService {
             }
         }
         
-        // 6. Update metrics
+        if let Some(writer) = self.correlation_writer.as_mut().filter(|_| !correlations.is_empty()) {
+            if let Err(e) = writer.write_correlations(&correlations).await {
+                error!("Failed to write correlations for frame {}: {}", frame_id, e);
+                self.metrics.error_count += 1;
+            }
+        }
+        
+        // 6. Update metrics and the live snapshot
+        if let Some(snapshot) = &self.snapshot {
+            snapshot.record_events(&all_events);
+            if let Some(position) = self.cursor_tracker.get_current_position() {
+                snapshot.record_cursor(position);
+            }
+        }
         self.metrics.total_events_detected += all_events.len() as u64;
         self.metrics.processing_time_ms += start_time.elapsed().as_millis() as u64;
         self.metrics.last_update = Some(timestamp);
@@ -278,13 +355,32 @@ impl NavigationIntegrationService {
     /// Flush all pending data to storage
     pub async fn flush(&mut self) -> Result<()> {
         self.event_writer.flush_batch().await?;
+        if let Some(writer) = &mut self.correlation_writer {
+            writer.flush_batch().await?;
+        }
         info!("NavigationIntegrationService flushed all pending data");
         Ok(())
     }
     
     /// Finalize the service and close all resources
     pub async fn finalize(&mut self) -> Result<()> {
+        if let Some(notifier) = &self.notifier {
+            notifier.wait_idle().await;
+        }
+        if let Some(triggers) = &self.triggers {
+            triggers.wait_idle().await;
+            let follow_ups = triggers.drain_follow_up_events();
+            if !follow_ups.is_empty() {
+                self.event_writer.write_events(&follow_ups).await?;
+            }
+        }
         self.event_writer.finalize().await?;
+        if let Some(writer) = &mut self.correlation_writer {
+            writer.finalize().await?;
+        }
+        if let Err(e) = self.event_correlator.persist_patterns() {
+            warn!("Failed to save correlation patterns: {}", e);
+        }
         self.navigation_detector.clear_state();
         self.cursor_tracker.clear_history();
         self.event_correlator.clear_data();
@@ -292,6 +388,31 @@ impl NavigationIntegrationService {
         Ok(())
     }
     
+    /// Hold buffered events against a memory budget shared with other writers
+    pub fn set_memory_budget(&mut self, budget: MemoryBudget) {
+        self.event_writer.set_memory_budget(budget);
+    }
+    
+    pub fn set_parquet_tuning(&mut self, config: ParquetTuningConfig) {
+        self.event_writer.set_parquet_tuning(config);
+    }
+    
+    /// Sign the events it stores, so readers can check they were not altered
+    pub fn enable_event_signing(&mut self, signer: EventSigner) {
+        self.event_writer.enable_signing(signer);
+    }
+    
+    /// Tag cursor and gesture events with the display they happen on
+    pub fn set_display_topology(&mut self, topology: DisplayTopology) {
+        self.cursor_tracker.set_display_topology(topology);
+    }
+    
+    /// Re-derive cursor movement thresholds after the capture resolution changed;
+    /// the configured values stay the reference
+    pub fn set_resolution_scale(&mut self, factor: f32) {
+        self.cursor_tracker.update_config(self.config.cursor_config.scaled(factor));
+    }
+    
     /// Update service configuration
     pub fn update_config(&mut self, config: NavigationIntegrationConfig) {
         self.navigation_detector.update_config(config.navigation_config.clone());
@@ -0,0 +1,264 @@
+use crate::error::{IndexerError, Result};
+use crate::ocr_data::{BoundingBox, OCRResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Configuration for detecting Notification Center banners and menu-bar alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationDetectionConfig {
+    pub enabled: bool,
+    /// Banners appear in this right-hand fraction of the screen
+    pub region_width_ratio: f32,
+    /// Banners appear in this top fraction of the screen
+    pub region_height_ratio: f32,
+    /// Text above this line is the menu bar itself
+    pub menu_bar_height: f32,
+    /// Largest banner size in pixels; larger text blocks are windows
+    pub max_banner_width: f32,
+    pub max_banner_height: f32,
+    /// Largest vertical gap between two lines of one banner
+    pub max_line_gap: f32,
+    /// Banner shape: at least this wide, its text ending this close to the
+    /// right screen edge, in at most `max_banner_lines` lines
+    pub min_banner_width: f32,
+    pub max_right_margin: f32,
+    pub max_banner_lines: usize,
+    /// App names recognized in a banner's header line
+    pub known_apps: Vec<String>,
+}
+
+impl Default for NotificationDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            region_width_ratio: 0.3,
+            region_height_ratio: 0.35,
+            menu_bar_height: 48.0,
+            max_banner_width: 800.0,
+            max_banner_height: 300.0,
+            max_line_gap: 24.0,
+            min_banner_width: 200.0,
+            max_right_margin: 120.0,
+            max_banner_lines: 4,
+            known_apps: [
+                "Mail", "Messages", "Calendar", "Reminders", "FaceTime", "Slack", "Microsoft Teams",
+                "Outlook", "Zoom", "Safari", "Finder", "System Settings", "Software Update",
+                "App Store", "Music", "Notes", "Xcode",
+            ]
+            .iter()
+            .map(|app| app.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl NotificationDetectionConfig {
+    /// Pixel thresholds scaled for a capture resolution `factor` times the reference
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            menu_bar_height: self.menu_bar_height * factor,
+            max_banner_width: self.max_banner_width * factor,
+            max_banner_height: self.max_banner_height * factor,
+            max_line_gap: self.max_line_gap * factor,
+            min_banner_width: self.min_banner_width * factor,
+            max_right_margin: self.max_right_margin * factor,
+            ..self.clone()
+        }
+    }
+}
+
+/// A transient banner in the top-right corner of the screen
+#[derive(Debug, Clone)]
+pub struct NotificationBanner {
+    /// App that posted the notification, when the banner names it
+    pub app_name: Option<String>,
+    pub title: String,
+    pub body: String,
+    /// Relative or clock time shown on the banner, e.g. "now" or "2m ago"
+    pub shown_time: Option<String>,
+    pub roi: BoundingBox,
+    /// Indices of the banner's OCR results in the analyzed slice
+    pub members: Vec<usize>,
+}
+
+/// Finds Notification Center banners by their position and size.
+///
+/// Banners sit below the menu bar at the right edge of the screen and hold a
+/// few short lines: an optional app header with a time, a title and a body.
+/// They are too small and too far off-center for dialog layout analysis.
+/// Text in the banner region is only a banner when it also names an app,
+/// shows a time label or has a banner's shape, so sidebars and toolbars of
+/// windows in that corner are not mistaken for notifications.
+pub struct NotificationDetector {
+    config: NotificationDetectionConfig,
+    /// Relative or clock time shown in a banner's header
+    time_label: Regex,
+}
+
+impl NotificationDetector {
+    pub fn new(config: NotificationDetectionConfig) -> Result<Self> {
+        let time_label = Regex::new(r"(?i)^(now|yesterday|\d{1,2}\s?(m|min|h|hr|d)( ago)?|\d{1,2}:\d{2}(\s?[ap]m)?)$")
+            .map_err(|e| IndexerError::Config(format!("Invalid notification time pattern: {}", e)))?;
+        Ok(Self { config, time_label })
+    }
+
+    pub fn detect(&self, ocr_results: &[&OCRResult], screen_width: f32, screen_height: f32) -> Vec<NotificationBanner> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let region_left = screen_width * (1.0 - self.config.region_width_ratio);
+        let region_bottom = screen_height * self.config.region_height_ratio;
+        let mut candidates: Vec<usize> = (0..ocr_results.len())
+            .filter(|&i| {
+                let roi = &ocr_results[i].roi;
+                roi.x >= region_left
+                    && roi.y >= self.config.menu_bar_height
+                    && roi.y + roi.height <= region_bottom
+                    && !ocr_results[i].text.trim().is_empty()
+            })
+            .collect();
+        candidates.sort_by(|&a, &b| {
+            ocr_results[a].roi.y.partial_cmp(&ocr_results[b].roi.y).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        // Stack lines into blocks; a wider gap separates two stacked banners.
+        // The region is a narrow strip, so lines side by side share a row of one banner
+        let mut blocks: Vec<Vec<usize>> = Vec::new();
+        for index in candidates {
+            let roi = &ocr_results[index].roi;
+            let joins = blocks.last().is_some_and(|block| {
+                let bbox = bounding_box(ocr_results, block);
+                roi.y - (bbox.y + bbox.height) <= self.config.max_line_gap
+            });
+            match blocks.last_mut() {
+                Some(block) if joins => block.push(index),
+                _ => blocks.push(vec![index]),
+            }
+        }
+
+        blocks
+            .into_iter()
+            .filter_map(|block| self.to_banner(ocr_results, block, screen_width))
+            .collect()
+    }
+
+    fn to_banner(&self, ocr_results: &[&OCRResult], mut members: Vec<usize>, screen_width: f32) -> Option<NotificationBanner> {
+        let roi = bounding_box(ocr_results, &members);
+        if members.len() < 2 || roi.width > self.config.max_banner_width || roi.height > self.config.max_banner_height {
+            return None;
+        }
+
+        // Reading order: by line, then left to right
+        members.sort_by(|&a, &b| {
+            let (ra, rb) = (&ocr_results[a].roi, &ocr_results[b].roi);
+            if (ra.y - rb.y).abs() < ra.height.min(rb.height) / 2.0 {
+                ra.x.partial_cmp(&rb.x)
+            } else {
+                ra.y.partial_cmp(&rb.y)
+            }
+            .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut shown_time = None;
+        let mut lines = Vec::new();
+        for &index in &members {
+            let text = ocr_results[index].text.trim();
+            if shown_time.is_none() && self.time_label.is_match(text) {
+                shown_time = Some(text.to_string());
+            } else {
+                lines.push(text);
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+
+        // Older banners and grouped notifications start with the app name in a header
+        let app_name = self.app_name(lines[0]);
+        if app_name.is_none() && shown_time.is_none() && !self.has_banner_shape(&roi, lines.len(), screen_width) {
+            return None;
+        }
+        if app_name.is_some() && lines.len() > 1 {
+            lines.remove(0);
+        }
+
+        Some(NotificationBanner {
+            app_name,
+            title: lines[0].to_string(),
+            body: lines[1..].join(" "),
+            shown_time,
+            roi,
+            members,
+        })
+    }
+
+    fn has_banner_shape(&self, roi: &BoundingBox, line_count: usize, screen_width: f32) -> bool {
+        roi.width >= self.config.min_banner_width
+            && screen_width - (roi.x + roi.width) <= self.config.max_right_margin
+            && line_count <= self.config.max_banner_lines
+    }
+
+    fn app_name(&self, header: &str) -> Option<String> {
+        if let Some(app) = self.config.known_apps.iter().find(|app| app.eq_ignore_ascii_case(header)) {
+            return Some(app.clone());
+        }
+        // Unknown apps are recognized by the all-caps header style
+        let letters: Vec<char> = header.chars().filter(|c| c.is_alphabetic()).collect();
+        (letters.len() >= 2 && letters.iter().all(|c| c.is_uppercase())).then(|| header.to_string())
+    }
+}
+
+fn bounding_box(ocr_results: &[&OCRResult], members: &[usize]) -> BoundingBox {
+    let mut min_x = f32::MAX;
+    let mut min_y = f32::MAX;
+    let mut max_x = f32::MIN;
+    let mut max_y = f32::MIN;
+    for &index in members {
+        let roi = &ocr_results[index].roi;
+        min_x = min_x.min(roi.x);
+        min_y = min_y.min(roi.y);
+        max_x = max_x.max(roi.x + roi.width);
+        max_y = max_y.max(roi.y + roi.height);
+    }
+    BoundingBox::new(min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, x: f32, y: f32, width: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(x, y, width, 24.0),
+            text: text.to_string(),
+            language: "en".to_string(),
+            confidence: 0.9,
+            processed_at: chrono::Utc::now(),
+            processor: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_text_in_the_corner_needs_a_banner_signal() {
+        let detector = NotificationDetector::new(NotificationDetectionConfig::default()).unwrap();
+
+        // Toolbar labels of a window in the corner: no app header, time or banner shape
+        let toolbar = [line("Share", 2100.0, 90.0, 60.0), line("Export", 2100.0, 118.0, 70.0)];
+        let refs: Vec<&OCRResult> = toolbar.iter().collect();
+        assert!(detector.detect(&refs, 2880.0, 1800.0).is_empty());
+
+        // A banner without a header, recognized by its shape at the screen edge
+        let banner = [
+            line("Deploy failed", 2400.0, 90.0, 260.0),
+            line("Pipeline #42 failed on main", 2400.0, 118.0, 440.0),
+        ];
+        let refs: Vec<&OCRResult> = banner.iter().collect();
+        let banners = detector.detect(&refs, 2880.0, 1800.0);
+        assert_eq!(banners.len(), 1);
+        assert_eq!(banners[0].title, "Deploy failed");
+        assert_eq!(banners[0].app_name, None);
+    }
+}
@@ -0,0 +1,457 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_signing::SIGNATURE_METADATA_KEY;
+use crate::event_triggers::{render_template, EventPredicate};
+use crate::redaction::{RedactionProfile, Redactor};
+use crate::suppression::SuppressionList;
+use base64::Engine;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Metadata keys naming the app an event happened in, most specific first
+const APP_METADATA_KEYS: [&str; 3] = ["app_name", "current_app", "to_app"];
+
+/// Chat service a webhook posts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifierKind {
+    Slack,
+    Teams,
+}
+
+/// Posts matching events to a Slack or Microsoft Teams incoming webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotifierConfig {
+    /// Unique notifier name, used in logs
+    pub name: String,
+    pub kind: NotifierKind,
+    pub webhook_url: String,
+    #[serde(default)]
+    pub predicate: EventPredicate,
+    /// Message title; takes the same placeholders as trigger arguments
+    #[serde(default = "default_title_template")]
+    pub title_template: String,
+    /// Maximum messages per minute
+    #[serde(default)]
+    pub max_per_minute: Option<u32>,
+    /// Attach a thumbnail of the first evidence frame
+    #[serde(default = "default_notifier_flag")]
+    pub include_thumbnail: bool,
+    #[serde(default = "default_thumbnail_width")]
+    pub thumbnail_width: u32,
+    /// Public URL frames are served under. Slack only shows images by URL,
+    /// so Slack messages carry no thumbnail without it; Teams cards embed
+    /// the image instead
+    #[serde(default)]
+    pub thumbnail_base_url: Option<String>,
+    /// Redaction applied to events before they leave the machine; thumbnails
+    /// can't be masked, so they are only sent with `admin`
+    #[serde(default = "default_notifier_redaction")]
+    pub redaction: RedactionProfile,
+    #[serde(default = "default_notifier_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_notifier_flag")]
+    pub enabled: bool,
+}
+
+fn default_title_template() -> String {
+    "{type}: {target}".to_string()
+}
+
+fn default_notifier_flag() -> bool {
+    true
+}
+
+fn default_notifier_redaction() -> RedactionProfile {
+    RedactionProfile::Analyst
+}
+
+fn default_thumbnail_width() -> u32 {
+    320
+}
+
+fn default_notifier_timeout_secs() -> u64 {
+    10
+}
+
+impl NotifierConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(IndexerError::Config("Notifier name must not be empty".to_string()));
+        }
+        if !self.webhook_url.starts_with("https://") && !self.webhook_url.starts_with("http://") {
+            return Err(IndexerError::Config(format!("Notifier '{}' webhook_url must be an http(s) URL", self.name)));
+        }
+        if !(16..=1920).contains(&self.thumbnail_width) {
+            return Err(IndexerError::Config(format!(
+                "Notifier '{}' thumbnail_width must be between 16 and 1920",
+                self.name
+            )));
+        }
+        if self.timeout_secs == 0 {
+            return Err(IndexerError::Config(format!("Notifier '{}' timeout_secs must be greater than 0", self.name)));
+        }
+        CompiledNotifier::compile(self.clone()).map(|_| ())
+    }
+}
+
+/// Notifier with compiled predicate patterns and send history
+struct CompiledNotifier {
+    config: NotifierConfig,
+    target_regex: Option<Regex>,
+    value_regex: Option<Regex>,
+    recent_sends: Mutex<VecDeque<Instant>>,
+}
+
+impl CompiledNotifier {
+    fn compile(config: NotifierConfig) -> Result<Self> {
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    Regex::new(p).map_err(|e| {
+                        IndexerError::Config(format!("Notifier '{}' has invalid pattern '{}': {}", config.name, p, e))
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            target_regex: compile(&config.predicate.target_pattern)?,
+            value_regex: compile(&config.predicate.value_pattern)?,
+            config,
+            recent_sends: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    fn matches(&self, event: &DetectedEvent) -> bool {
+        let predicate = &self.config.predicate;
+        (predicate.event_types.is_empty() || predicate.event_types.contains(&event.event_type))
+            && predicate.min_confidence.map_or(true, |min| event.confidence >= min)
+            && self.target_regex.as_ref().map_or(true, |r| r.is_match(&event.target))
+            && self
+                .value_regex
+                .as_ref()
+                .map_or(true, |r| event.value_to.as_deref().map_or(false, |v| r.is_match(v)))
+            && predicate.metadata.iter().all(|(key, value)| event.metadata.get(key) == Some(value))
+    }
+
+    /// Record a send if the rate limit allows it
+    fn try_acquire(&self) -> bool {
+        let Some(limit) = self.config.max_per_minute else {
+            return true;
+        };
+        let mut sends = match self.recent_sends.lock() {
+            Ok(sends) => sends,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let now = Instant::now();
+        while sends.front().map_or(false, |t| now.duration_since(*t) >= Duration::from_secs(60)) {
+            sends.pop_front();
+        }
+        if sends.len() >= limit as usize {
+            return false;
+        }
+        sends.push_back(now);
+        true
+    }
+}
+
+/// Sends chat messages for detected events.
+///
+/// Like triggers, messages are posted in the background and never block
+/// frame processing; failures are logged, not retried.
+pub struct WebhookNotifier {
+    notifiers: Vec<CompiledNotifier>,
+    redactor: Redactor,
+    running: Mutex<Vec<JoinHandle<()>>>,
+    suppressions: Option<SuppressionList>,
+}
+
+impl WebhookNotifier {
+    pub fn new(configs: Vec<NotifierConfig>) -> Result<Self> {
+        let mut notifiers = Vec::new();
+        for config in configs {
+            config.validate()?;
+            if config.enabled {
+                notifiers.push(CompiledNotifier::compile(config)?);
+            }
+        }
+        info!("Webhook notifier initialized with {} notifiers", notifiers.len());
+        Ok(Self { notifiers, redactor: Redactor::new()?, running: Mutex::new(Vec::new()), suppressions: None })
+    }
+
+    /// Skip events muted by suppression rules
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Post a message for every notifier matching the events; returns the number queued
+    pub fn dispatch(&self, events: &[DetectedEvent]) -> usize {
+        let mut queued = 0;
+        for event in events.iter().filter(|e| e.event_type != EventType::TriggerExecution) {
+            if self.suppressions.as_ref().is_some_and(|s| s.is_suppressed(event)) {
+                continue;
+            }
+            for notifier in self.notifiers.iter().filter(|n| n.matches(event)) {
+                if !notifier.try_acquire() {
+                    debug!("Notifier '{}' rate limited, skipping event {}", notifier.config.name, event.id);
+                    continue;
+                }
+                let config = notifier.config.clone();
+                let Some(event) = self.redactor.redact_events(config.redaction, vec![event.clone()]).pop() else {
+                    continue;
+                };
+                let handle = tokio::task::spawn_blocking(move || {
+                    let payload = build_payload(&config, &event);
+                    let sent = ureq::post(&config.webhook_url)
+                        .timeout(Duration::from_secs(config.timeout_secs))
+                        .send_json(payload);
+                    match sent {
+                        Ok(_) => debug!("Notifier '{}' posted event {}", config.name, event.id),
+                        Err(e) => warn!("Notifier '{}' failed to post event {}: {}", config.name, event.id, e),
+                    }
+                });
+                if let Ok(mut running) = self.running.lock() {
+                    running.retain(|h| !h.is_finished());
+                    running.push(handle);
+                }
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    /// Wait for all pending messages to be sent
+    pub async fn wait_idle(&self) {
+        let handles: Vec<JoinHandle<()>> = match self.running.lock() {
+            Ok(mut running) => running.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    pub fn notifier_count(&self) -> usize {
+        self.notifiers.len()
+    }
+}
+
+/// Severity shown on the message: the event's own, or one implied by its type
+fn severity(event: &DetectedEvent) -> String {
+    if let Some(severity) = event.metadata.get("severity") {
+        return severity.to_lowercase();
+    }
+    match event.event_type {
+        EventType::ErrorDisplay | EventType::BuildFailure => "error",
+        EventType::CodeDiagnostic => "warning",
+        _ => "info",
+    }
+    .to_string()
+}
+
+fn app_context(event: &DetectedEvent) -> Option<String> {
+    let app = APP_METADATA_KEYS.iter().find_map(|key| event.metadata.get(*key))?;
+    Some(match event.metadata.get("window_title").filter(|t| !t.is_empty()) {
+        Some(title) => format!("{} — {}", app, title),
+        None => app.clone(),
+    })
+}
+
+/// Webhook body for an event in the notifier's chat format
+pub fn build_payload(config: &NotifierConfig, event: &DetectedEvent) -> Value {
+    let title = render_template(&config.title_template, event);
+    let severity = severity(event);
+    let app = app_context(event).unwrap_or_else(|| "unknown app".to_string());
+    let detail = event.value_to.as_deref().unwrap_or(&event.target);
+    let time = event.timestamp.to_rfc3339();
+    let frame = event
+        .evidence_frames
+        .first()
+        .filter(|_| config.include_thumbnail && config.redaction == RedactionProfile::Admin);
+    // Only unredacted events still carry theirs
+    let signature = event.metadata.get(SIGNATURE_METADATA_KEY);
+
+    match config.kind {
+        NotifierKind::Slack => {
+            let mut blocks = vec![
+                json!({ "type": "header", "text": { "type": "plain_text", "text": title } }),
+                json!({ "type": "section", "text": { "type": "mrkdwn", "text": detail } }),
+                json!({ "type": "context", "elements": [
+                    { "type": "mrkdwn", "text": format!("*{}* · {} · {}", severity, app, time) }
+                ] }),
+            ];
+            if let Some(signature) = signature {
+                blocks.push(json!({ "type": "context", "elements": [
+                    { "type": "mrkdwn", "text": format!("Signature `{}`", signature) }
+                ] }));
+            }
+            if let (Some(frame), Some(base)) = (frame, &config.thumbnail_base_url) {
+                if let Some(name) = Path::new(frame).file_name() {
+                    blocks.push(json!({
+                        "type": "image",
+                        "image_url": format!("{}/{}", base.trim_end_matches('/'), name.to_string_lossy()),
+                        "alt_text": "Evidence frame",
+                    }));
+                }
+            }
+            json!({ "text": title, "blocks": blocks })
+        }
+        NotifierKind::Teams => {
+            let mut body = vec![
+                json!({ "type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true }),
+                json!({ "type": "TextBlock", "text": detail, "wrap": true }),
+                json!({ "type": "FactSet", "facts": [
+                    { "title": "Severity", "value": severity },
+                    { "title": "App", "value": app },
+                    { "title": "Time", "value": time },
+                ] }),
+            ];
+            if let Some(signature) = signature {
+                body.push(json!({ "type": "TextBlock", "text": format!("Signature {}", signature), "isSubtle": true, "wrap": true }));
+            }
+            if let Some(data) = frame.and_then(|f| thumbnail_png(f, config.thumbnail_width)) {
+                body.push(json!({
+                    "type": "Image",
+                    "url": format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(&data)),
+                    "altText": "Evidence frame",
+                }));
+            }
+            json!({
+                "type": "message",
+                "attachments": [{
+                    "contentType": "application/vnd.microsoft.card.adaptive",
+                    "content": {
+                        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                        "type": "AdaptiveCard",
+                        "version": "1.4",
+                        "body": body,
+                    },
+                }],
+            })
+        }
+    }
+}
+
+/// PNG of a frame scaled down to `width`; `None` if the frame can't be read
+fn thumbnail_png(path: &str, width: u32) -> Option<Vec<u8>> {
+    let image = match image::open(path) {
+        Ok(image) => image,
+        Err(e) => {
+            debug!("No thumbnail for {}: {}", path, e);
+            return None;
+        }
+    };
+    let height = (image.height() as f32 * width as f32 / image.width().max(1) as f32).round().max(1.0) as u32;
+    let thumbnail = image.thumbnail(width.min(image.width()), height);
+    let mut data = Vec::new();
+    thumbnail.write_to(&mut Cursor::new(&mut data), image::ImageOutputFormat::Png).ok()?;
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_slack_and_teams_payloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let frame = dir.path().join("frame_1.png");
+        image::RgbImage::from_pixel(640, 360, image::Rgb([200, 30, 30])).save(&frame).unwrap();
+
+        let event = DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "checkout_form".to_string(),
+            value_from: None,
+            value_to: Some("Payment failed".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec![frame.to_string_lossy().to_string()],
+            metadata: HashMap::from([("app_name".to_string(), "Safari".to_string())]),
+        };
+        let slack = NotifierConfig {
+            name: "alerts".to_string(),
+            kind: NotifierKind::Slack,
+            webhook_url: "https://hooks.slack.com/services/T/B/X".to_string(),
+            predicate: EventPredicate { event_types: vec![EventType::ErrorDisplay], ..Default::default() },
+            title_template: default_title_template(),
+            max_per_minute: Some(1),
+            include_thumbnail: true,
+            thumbnail_width: 160,
+            thumbnail_base_url: Some("https://frames.example.com/".to_string()),
+            redaction: RedactionProfile::Admin,
+            timeout_secs: 5,
+            enabled: true,
+        };
+        slack.validate().unwrap();
+
+        let payload = build_payload(&slack, &event);
+        assert_eq!(payload["blocks"][0]["text"]["text"], "ErrorDisplay: checkout_form");
+        assert!(payload["blocks"][2]["elements"][0]["text"].as_str().unwrap().starts_with("*error* · Safari"));
+        assert_eq!(payload["blocks"][3]["image_url"], "https://frames.example.com/frame_1.png");
+
+        let teams = NotifierConfig { kind: NotifierKind::Teams, ..slack.clone() };
+        let card = &build_payload(&teams, &event)["attachments"][0]["content"]["body"];
+        assert_eq!(card[2]["facts"][1]["value"], "Safari");
+        assert!(card[3]["url"].as_str().unwrap().starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+        let notifier = CompiledNotifier::compile(slack).unwrap();
+        assert!(notifier.matches(&event));
+        assert!(notifier.try_acquire() && !notifier.try_acquire());
+    }
+
+    #[test]
+    fn test_messages_are_redacted_before_sending() {
+        let event = DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "login_form".to_string(),
+            value_from: None,
+            value_to: Some("No account for jane.doe@example.com".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1.png".to_string()],
+            metadata: HashMap::from([
+                ("app_name".to_string(), "Safari".to_string()),
+                ("window_title".to_string(), "Inbox - jane.doe@example.com".to_string()),
+                (SIGNATURE_METADATA_KEY.to_string(), "c0ffee".to_string()),
+            ]),
+        };
+        let config: NotifierConfig = serde_json::from_value(json!({
+            "name": "alerts",
+            "kind": "slack",
+            "webhook_url": "https://hooks.slack.com/services/T/B/X",
+            "thumbnail_base_url": "https://frames.example.com",
+        }))
+        .unwrap();
+        assert_eq!(config.redaction, RedactionProfile::Analyst);
+
+        let redactor = Redactor::new().unwrap();
+        let redacted = redactor.redact_events(config.redaction, vec![event.clone()]).pop().unwrap();
+        let payload = build_payload(&config, &redacted).to_string();
+        assert!(!payload.contains("jane.doe@example.com"));
+        // A masked event no longer matches its signature, so it is not sent
+        assert!(!payload.contains("c0ffee"));
+        // No thumbnail: the screenshot can't be masked
+        assert_eq!(build_payload(&config, &redacted)["blocks"].as_array().unwrap().len(), 3);
+
+        let auditor = NotifierConfig { redaction: RedactionProfile::Auditor, ..config };
+        let redacted = redactor.redact_events(auditor.redaction, vec![event]).pop().unwrap();
+        let payload = build_payload(&auditor, &redacted);
+        assert!(!payload.to_string().contains("No account"));
+        assert!(!payload.to_string().contains("Inbox"));
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use crate::display_topology::DisplayTopology;
+use crate::error::{IndexerError, Result};
+use tracing::warn;
 
 /// OCR result data structure matching the design specification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -38,31 +43,92 @@ impl BoundingBox {
         Self { x, y, width, height }
     }
     
+    /// Box recorded in event metadata under `roi_x`, `roi_y`, `roi_width` and `roi_height`
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        let value = |key: &str| metadata.get(key)?.parse::<f32>().ok();
+        Some(Self::new(value("roi_x")?, value("roi_y")?, value("roi_width")?, value("roi_height")?))
+    }
+    
+    /// Record the box in event metadata, as read by `from_metadata`
+    pub fn insert_metadata(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert("roi_x".to_string(), self.x.to_string());
+        metadata.insert("roi_y".to_string(), self.y.to_string());
+        metadata.insert("roi_width".to_string(), self.width.to_string());
+        metadata.insert("roi_height".to_string(), self.height.to_string());
+    }
+    
+    /// Box spanning two opposite corners, given in any order
+    pub fn from_corners(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self::new(x1.min(x2), y1.min(y2), (x2 - x1).abs(), (y2 - y1).abs())
+    }
+    
+    /// Smallest box containing every box; `None` when there are none
+    pub fn enclosing<'a>(boxes: impl IntoIterator<Item = &'a BoundingBox>) -> Option<Self> {
+        boxes.into_iter().fold(None, |hull: Option<BoundingBox>, roi| {
+            Some(match hull {
+                Some(hull) => hull.union(roi),
+                None => roi.clone(),
+            })
+        })
+    }
+    
+    /// X coordinate of the right edge
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+    
+    /// Y coordinate of the bottom edge
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+    
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+    
     /// Calculate the area of the bounding box
     pub fn area(&self) -> f32 {
         self.width * self.height
     }
     
-    /// Check if this bounding box intersects with another
+    /// Check if this bounding box intersects with another; touching edges count
     pub fn intersects(&self, other: &BoundingBox) -> bool {
-        !(self.x + self.width < other.x ||
-          other.x + other.width < self.x ||
-          self.y + self.height < other.y ||
-          other.y + other.height < self.y)
+        !(self.right() < other.x ||
+          other.right() < self.x ||
+          self.bottom() < other.y ||
+          other.bottom() < self.y)
+    }
+    
+    /// Region both boxes cover, empty when they only touch
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self::from_corners(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        ))
+    }
+    
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        Self::from_corners(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
     }
     
     /// Calculate intersection over union (IoU) with another bounding box
     pub fn iou(&self, other: &BoundingBox) -> f32 {
-        if !self.intersects(other) {
+        let Some(intersection) = self.intersection(other) else {
             return 0.0;
-        }
+        };
         
-        let intersection_x = self.x.max(other.x);
-        let intersection_y = self.y.max(other.y);
-        let intersection_width = (self.x + self.width).min(other.x + other.width) - intersection_x;
-        let intersection_height = (self.y + self.height).min(other.y + other.height) - intersection_y;
-        
-        let intersection_area = intersection_width * intersection_height;
+        let intersection_area = intersection.area();
         let union_area = self.area() + other.area() - intersection_area;
         
         if union_area > 0.0 {
@@ -71,6 +137,206 @@ impl BoundingBox {
             0.0
         }
     }
+    
+    /// Whether a point lies inside the box or on its edge
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.right() && y >= self.y && y <= self.bottom()
+    }
+    
+    /// Whether `other` lies entirely inside this box
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        other.x >= self.x && other.y >= self.y && other.right() <= self.right() && other.bottom() <= self.bottom()
+    }
+    
+    /// Grown by `padding` on every side; a negative padding shrinks it, down to
+    /// an empty box at its center
+    pub fn expand(&self, padding: f32) -> BoundingBox {
+        self.expand_xy(padding, padding)
+    }
+    
+    /// Grown by `horizontal` on the left and right and `vertical` on the top and bottom
+    pub fn expand_xy(&self, horizontal: f32, vertical: f32) -> BoundingBox {
+        let (center_x, center_y) = self.center();
+        let width = (self.width + 2.0 * horizontal).max(0.0);
+        let height = (self.height + 2.0 * vertical).max(0.0);
+        Self::new(center_x - width / 2.0, center_y - height / 2.0, width, height)
+    }
+    
+    /// Part of the box on a `width` x `height` screen; `None` when it is entirely off screen
+    pub fn clip(&self, width: f32, height: f32) -> Option<BoundingBox> {
+        let clipped = self.intersection(&Self::new(0.0, 0.0, width, height))?;
+        (clipped.area() > 0.0).then_some(clipped)
+    }
+    
+    /// Distance between the centers of two boxes
+    pub fn center_distance(&self, other: &BoundingBox) -> f32 {
+        let (x1, y1) = self.center();
+        let (x2, y2) = other.center();
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        (dx * dx + dy * dy).sqrt()
+    }
+    
+    /// In fractions of a `frame_width` x `frame_height` frame
+    pub fn to_normalized(&self, frame_width: f32, frame_height: f32) -> BoundingBox {
+        Self::new(self.x / frame_width, self.y / frame_height, self.width / frame_width, self.height / frame_height)
+    }
+    
+    /// In pixels of a `frame_width` x `frame_height` frame, from fractions of it
+    pub fn to_absolute(&self, frame_width: f32, frame_height: f32) -> BoundingBox {
+        Self::new(self.x * frame_width, self.y * frame_height, self.width * frame_width, self.height * frame_height)
+    }
+    
+    /// Whether every coordinate is finite and the size is not negative
+    pub fn is_valid(&self) -> bool {
+        [self.x, self.y, self.width, self.height].iter().all(|v| v.is_finite())
+            && self.width >= 0.0
+            && self.height >= 0.0
+    }
+    
+    /// Reject boxes geometry cannot be done on: NaN or infinite coordinates and negative sizes
+    pub fn validate(&self) -> Result<()> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(IndexerError::InvalidOcr(format!(
+                "ROI ({}, {}, {}x{}) has a non-finite coordinate or a negative size",
+                self.x, self.y, self.width, self.height
+            )))
+        }
+    }
+}
+
+impl OCRResult {
+    /// Check the ROI, and that confidence is a number from 0 to 1
+    pub fn validate(&self) -> Result<()> {
+        self.roi.validate()?;
+        if !(0.0..=1.0).contains(&self.confidence) {
+            return Err(IndexerError::InvalidOcr(format!("confidence {} is not between 0 and 1", self.confidence)));
+        }
+        Ok(())
+    }
+}
+
+/// Results fit for analysis, dropping those that fail validation; borrowed
+/// unchanged when all pass
+pub fn valid_results(results: &[OCRResult]) -> Cow<'_, [OCRResult]> {
+    if results.iter().all(|result| result.validate().is_ok()) {
+        return Cow::Borrowed(results);
+    }
+    Cow::Owned(results
+        .iter()
+        .filter(|result| match result.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Dropping OCR result of {}: {}", result.frame_id, e);
+                false
+            }
+        })
+        .cloned()
+        .collect())
+}
+
+/// Unit upstream OCR reports ROIs in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSpace {
+    /// Keyframe pixels; no conversion needed
+    #[default]
+    Pixels,
+    /// Display points, which are 2x smaller than pixels on Retina displays
+    Points,
+    /// Fractions of the frame size (0.0 to 1.0), top-left origin
+    Normalized,
+}
+
+/// Conversion of OCR ROIs to keyframe pixels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoiScaleConfig {
+    pub source_space: CoordinateSpace,
+    /// Pixels per point for displays without an entry below
+    pub default_scale_factor: f32,
+    /// Pixels per point by display id, overriding the enumerated topology
+    pub display_scale_factors: HashMap<i32, f32>,
+}
+
+impl Default for RoiScaleConfig {
+    fn default() -> Self {
+        Self {
+            source_space: CoordinateSpace::Pixels,
+            default_scale_factor: 1.0,
+            display_scale_factors: HashMap::new(),
+        }
+    }
+}
+
+/// Brings OCR ROIs into keyframe pixels so spatial comparisons see one scale
+#[derive(Debug, Clone, Default)]
+pub struct RoiNormalizer {
+    config: RoiScaleConfig,
+}
+
+impl RoiNormalizer {
+    pub fn new(config: RoiScaleConfig) -> Self {
+        Self { config }
+    }
+    
+    /// Take scale factors of displays not configured explicitly from `topology`
+    pub fn with_topology(mut self, topology: &DisplayTopology) -> Self {
+        for display in &topology.displays {
+            self.config.display_scale_factors.entry(display.id).or_insert(display.scale_factor);
+        }
+        self
+    }
+    
+    /// Pixels per point on a display
+    pub fn scale_factor(&self, screen_id: Option<i32>) -> f32 {
+        screen_id
+            .and_then(|id| self.config.display_scale_factors.get(&id).copied())
+            .filter(|scale| *scale > 0.0)
+            .unwrap_or(self.config.default_scale_factor)
+    }
+    
+    /// Whether ROIs from this display are already in pixels
+    pub fn is_identity(&self, screen_id: Option<i32>) -> bool {
+        match self.config.source_space {
+            CoordinateSpace::Pixels => true,
+            CoordinateSpace::Points => self.scale_factor(screen_id) == 1.0,
+            CoordinateSpace::Normalized => false,
+        }
+    }
+    
+    /// Convert an ROI to pixels of a `frame_width` x `frame_height` keyframe captured from `screen_id`
+    pub fn to_pixels(&self, roi: &BoundingBox, screen_id: Option<i32>, frame_width: f32, frame_height: f32) -> BoundingBox {
+        match self.config.source_space {
+            CoordinateSpace::Pixels => roi.clone(),
+            CoordinateSpace::Points => {
+                let scale = self.scale_factor(screen_id);
+                roi.to_absolute(scale, scale)
+            }
+            CoordinateSpace::Normalized => roi.to_absolute(frame_width, frame_height),
+        }
+    }
+    
+    /// Results with ROIs in pixels, borrowed unchanged when no conversion applies
+    pub fn normalize<'a>(
+        &self,
+        results: &'a [OCRResult],
+        screen_id: Option<i32>,
+        frame_width: f32,
+        frame_height: f32,
+    ) -> Cow<'a, [OCRResult]> {
+        if self.is_identity(screen_id) {
+            return Cow::Borrowed(results);
+        }
+        Cow::Owned(results
+            .iter()
+            .map(|result| OCRResult {
+                roi: self.to_pixels(&result.roi, screen_id, frame_width, frame_height),
+                ..result.clone()
+            })
+            .collect())
+    }
 }
 
 /// Batch of OCR results for efficient processing
@@ -126,9 +392,81 @@ impl OCRBatch {
     }
 }
 
+/// Proptest generators for OCR input, malformed and hostile values included
+#[cfg(test)]
+pub(crate) mod strategies {
+    use super::*;
+    use proptest::prelude::*;
+    
+    /// Any coordinate, including NaN, infinities and negatives
+    pub fn coordinate() -> impl Strategy<Value = f32> {
+        prop_oneof![
+            8 => 0.0f32..4000.0,
+            1 => -4000.0f32..0.0,
+            1 => Just(f32::NAN),
+            1 => Just(f32::INFINITY),
+            1 => Just(f32::NEG_INFINITY),
+            1 => proptest::num::f32::ANY,
+        ]
+    }
+    
+    pub fn bounding_box() -> impl Strategy<Value = BoundingBox> {
+        (coordinate(), coordinate(), coordinate(), coordinate())
+            .prop_map(|(x, y, width, height)| BoundingBox::new(x, y, width, height))
+    }
+    
+    /// Text OCR could plausibly return, plus control characters, combining
+    /// marks, right-to-left scripts and very long lines
+    pub fn ocr_text() -> impl Strategy<Value = String> {
+        prop_oneof![
+            4 => "[A-Za-z0-9 .,:@_-]{0,40}",
+            2 => "(Error|Warning|OK|Cancel|Save|Username|Password|Invalid)[: ]{0,2}[A-Za-z0-9 ]{0,20}",
+            1 => "\\PC{0,60}",
+            1 => "[\\x00-\\x1f\\u{202e}\\u{200b}\\u{0301}]{0,20}",
+            1 => "[\\u{0600}-\\u{06ff}\\u{4e00}-\\u{4e50}\\u{1f600}-\\u{1f640} ]{40,80}",
+            1 => ".{200,600}",
+        ]
+    }
+    
+    pub fn ocr_result(frame_id: String) -> impl Strategy<Value = OCRResult> {
+        (
+            bounding_box(),
+            ocr_text(),
+            prop_oneof![4 => 0.0f32..=1.0, 1 => proptest::num::f32::ANY],
+        )
+            .prop_map(move |(roi, text, confidence)| OCRResult {
+                frame_id: frame_id.clone(),
+                roi,
+                text,
+                language: "en-US".to_string(),
+                confidence,
+                processed_at: Utc::now(),
+                processor: "proptest".to_string(),
+            })
+    }
+    
+    /// OCR results of one frame
+    pub fn frame(frame_id: &str) -> impl Strategy<Value = Vec<OCRResult>> {
+        proptest::collection::vec(ocr_result(frame_id.to_string()), 0..12)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    
+    proptest! {
+        #[test]
+        fn prop_valid_results_keep_only_usable_rois(results in strategies::frame("frame_1")) {
+            let valid = valid_results(&results);
+            prop_assert!(valid.iter().all(|result| result.roi.is_valid() && result.validate().is_ok()));
+            prop_assert_eq!(valid.len(), results.iter().filter(|result| result.validate().is_ok()).count());
+            if valid.len() == results.len() {
+                prop_assert!(matches!(valid, Cow::Borrowed(_)));
+            }
+        }
+    }
     
     #[test]
     fn test_bounding_box_area() {
@@ -159,6 +497,73 @@ mod tests {
         assert!((iou_same - 1.0).abs() < 0.001);
     }
     
+    #[test]
+    fn test_bounding_box_geometry() {
+        let a = BoundingBox::new(0.0, 0.0, 100.0, 100.0);
+        let b = BoundingBox::new(50.0, 50.0, 100.0, 100.0);
+        
+        assert_eq!(a.intersection(&b), Some(BoundingBox::new(50.0, 50.0, 50.0, 50.0)));
+        assert_eq!(a.intersection(&BoundingBox::new(200.0, 0.0, 10.0, 10.0)), None);
+        assert_eq!(a.union(&b), BoundingBox::new(0.0, 0.0, 150.0, 150.0));
+        assert_eq!(BoundingBox::enclosing([&a, &b, &BoundingBox::new(-10.0, 20.0, 5.0, 5.0)]), Some(BoundingBox::new(-10.0, 0.0, 160.0, 150.0)));
+        assert_eq!(BoundingBox::enclosing(std::iter::empty()), None);
+        
+        assert!(a.contains(&BoundingBox::new(10.0, 10.0, 90.0, 90.0)));
+        assert!(!a.contains(&b));
+        assert!(a.contains_point(100.0, 0.0));
+        assert!(!a.contains_point(100.5, 50.0));
+        
+        assert_eq!(a.expand(10.0), BoundingBox::new(-10.0, -10.0, 120.0, 120.0));
+        assert_eq!(a.expand(-60.0), BoundingBox::new(50.0, 50.0, 0.0, 0.0));
+        assert_eq!(a.expand_xy(5.0, 0.0), BoundingBox::new(-5.0, 0.0, 110.0, 100.0));
+        
+        assert_eq!(b.clip(120.0, 80.0), Some(BoundingBox::new(50.0, 50.0, 70.0, 30.0)));
+        assert_eq!(b.clip(40.0, 40.0), None);
+        
+        assert_eq!(a.center(), (50.0, 50.0));
+        assert!((a.center_distance(&b) - 50.0 * 2f32.sqrt()).abs() < 1e-4);
+        let normalized = b.to_normalized(200.0, 400.0);
+        assert_eq!(normalized, BoundingBox::new(0.25, 0.125, 0.5, 0.25));
+        assert_eq!(normalized.to_absolute(200.0, 400.0), b);
+    }
+    
+    #[test]
+    fn test_roi_normalization_to_pixels() {
+        let mut display_scale_factors = HashMap::new();
+        display_scale_factors.insert(2, 1.0);
+        let normalizer = RoiNormalizer::new(RoiScaleConfig {
+            source_space: CoordinateSpace::Points,
+            default_scale_factor: 2.0,
+            display_scale_factors,
+        });
+        let roi = BoundingBox::new(10.0, 20.0, 100.0, 30.0);
+        
+        assert_eq!(normalizer.to_pixels(&roi, None, 2880.0, 1800.0), BoundingBox::new(20.0, 40.0, 200.0, 60.0));
+        assert_eq!(normalizer.to_pixels(&roi, Some(2), 1920.0, 1080.0), roi);
+        assert!(normalizer.is_identity(Some(2)));
+        
+        let results = vec![OCRResult {
+            frame_id: "frame_1".to_string(),
+            roi: roi.clone(),
+            text: "Save".to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }];
+        assert!(matches!(normalizer.normalize(&results, Some(2), 1920.0, 1080.0), Cow::Borrowed(_)));
+        assert_eq!(normalizer.normalize(&results, Some(1), 2880.0, 1800.0)[0].roi.width, 200.0);
+        
+        let normalized = RoiNormalizer::new(RoiScaleConfig {
+            source_space: CoordinateSpace::Normalized,
+            ..RoiScaleConfig::default()
+        });
+        assert_eq!(
+            normalized.to_pixels(&BoundingBox::new(0.5, 0.25, 0.1, 0.05), None, 2000.0, 1000.0),
+            BoundingBox::new(1000.0, 250.0, 200.0, 50.0)
+        );
+    }
+    
     #[test]
     fn test_ocr_batch_creation() {
         let results = vec![
@@ -0,0 +1,282 @@
+use crate::ocr_data::OCRResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tracing::warn;
+
+/// How much text OCR found in a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OcrDensity {
+    /// Number of OCR boxes
+    pub box_count: u32,
+    /// Percentage (0-100) of the frame covered by OCR boxes; overlaps count once
+    pub text_coverage: f32,
+    /// Mean OCR confidence, 0.0 without boxes
+    pub mean_confidence: f32,
+}
+
+impl OcrDensity {
+    /// Metrics of a frame's OCR results; boxes are clipped to the frame
+    pub fn from_results(results: &[OCRResult], width: u32, height: u32) -> Self {
+        if results.is_empty() {
+            return Self::default();
+        }
+
+        let frame_area = width as f32 * height as f32;
+        let text_coverage = if frame_area > 0.0 {
+            (covered_area(results, width as f32, height as f32) / frame_area * 100.0).min(100.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            box_count: results.len() as u32,
+            text_coverage,
+            mean_confidence: results.iter().map(|r| r.confidence).sum::<f32>() / results.len() as f32,
+        }
+    }
+
+    /// Average of several frames' metrics
+    pub fn mean<'a>(densities: impl IntoIterator<Item = &'a OcrDensity>) -> Self {
+        let mut sum = Self::default();
+        let mut count = 0u32;
+        let mut box_count = 0u64;
+        for density in densities {
+            box_count += density.box_count as u64;
+            sum.text_coverage += density.text_coverage;
+            sum.mean_confidence += density.mean_confidence;
+            count += 1;
+        }
+        if count == 0 {
+            return sum;
+        }
+
+        Self {
+            box_count: (box_count / count as u64) as u32,
+            text_coverage: sum.text_coverage / count as f32,
+            mean_confidence: sum.mean_confidence / count as f32,
+        }
+    }
+}
+
+/// Area of the union of all boxes, by sweeping vertical slabs between box edges
+fn covered_area(results: &[OCRResult], width: f32, height: f32) -> f32 {
+    let boxes: Vec<(f32, f32, f32, f32)> = results
+        .iter()
+        .map(|r| (
+            r.roi.x.clamp(0.0, width),
+            r.roi.y.clamp(0.0, height),
+            (r.roi.x + r.roi.width).clamp(0.0, width),
+            (r.roi.y + r.roi.height).clamp(0.0, height),
+        ))
+        .filter(|(x0, y0, x1, y1)| x1 > x0 && y1 > y0)
+        .collect();
+
+    let mut xs: Vec<f32> = boxes.iter().flat_map(|(x0, _, x1, _)| [*x0, *x1]).collect();
+    xs.sort_by(f32::total_cmp);
+    xs.dedup();
+
+    let mut area = 0.0;
+    for slab in xs.windows(2) {
+        let (left, right) = (slab[0], slab[1]);
+        let mut spans: Vec<(f32, f32)> = boxes
+            .iter()
+            .filter(|(x0, _, x1, _)| *x0 <= left && *x1 >= right)
+            .map(|(_, y0, _, y1)| (*y0, *y1))
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut covered = 0.0;
+        let mut current: Option<(f32, f32)> = None;
+        for (start, end) in spans {
+            current = match current {
+                Some((s, e)) if start <= e => Some((s, e.max(end))),
+                Some((s, e)) => {
+                    covered += e - s;
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((s, e)) = current {
+            covered += e - s;
+        }
+        area += covered * (right - left);
+    }
+    area
+}
+
+/// Settings for OCR quality regression detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrQualityConfig {
+    pub enabled: bool,
+    /// Frames per application forming the baseline
+    pub baseline_frames: usize,
+    /// Most recent frames compared against the baseline
+    pub recent_frames: usize,
+    /// Drop in mean confidence (0-1) that counts as a regression
+    pub max_confidence_drop: f32,
+    /// Recent text coverage below this fraction of the baseline counts as a regression
+    pub min_coverage_ratio: f32,
+}
+
+impl Default for OcrQualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            baseline_frames: 200,
+            recent_frames: 20,
+            max_confidence_drop: 0.15,
+            min_coverage_ratio: 0.3,
+        }
+    }
+}
+
+/// OCR of an application got noticeably worse than its own history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrQualityRegression {
+    pub app_name: String,
+    pub baseline: OcrDensity,
+    pub recent: OcrDensity,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+struct AppHistory {
+    baseline: VecDeque<OcrDensity>,
+    recent: VecDeque<OcrDensity>,
+    regressed: bool,
+}
+
+/// Compares each application's recent OCR metrics with its own baseline.
+///
+/// A regression is reported once when it starts and again only after the
+/// metrics have recovered, so a lasting problem does not flood the log.
+pub struct OcrQualityMonitor {
+    config: OcrQualityConfig,
+    apps: HashMap<String, AppHistory>,
+}
+
+impl OcrQualityMonitor {
+    pub fn new(config: OcrQualityConfig) -> Self {
+        Self {
+            config,
+            apps: HashMap::new(),
+        }
+    }
+
+    /// Apply new thresholds; per-app baselines are kept
+    pub fn update_config(&mut self, config: OcrQualityConfig) {
+        self.config = config;
+    }
+
+    /// Record a frame's metrics; returns a regression when one starts
+    pub fn observe(&mut self, app_name: &str, density: &OcrDensity) -> Option<OcrQualityRegression> {
+        if !self.config.enabled || self.config.recent_frames == 0 {
+            return None;
+        }
+
+        let history = self.apps.entry(app_name.to_string()).or_default();
+        history.recent.push_back(*density);
+        if history.recent.len() > self.config.recent_frames {
+            if let Some(oldest) = history.recent.pop_front() {
+                history.baseline.push_back(oldest);
+            }
+            if history.baseline.len() > self.config.baseline_frames {
+                history.baseline.pop_front();
+            }
+        }
+
+        // Wait for a full window and enough history to compare against
+        if history.recent.len() < self.config.recent_frames
+            || history.baseline.len() < self.config.baseline_frames.min(self.config.recent_frames * 2)
+        {
+            return None;
+        }
+
+        let baseline = OcrDensity::mean(&history.baseline);
+        let recent = OcrDensity::mean(&history.recent);
+        let reason = if baseline.mean_confidence - recent.mean_confidence > self.config.max_confidence_drop {
+            Some(format!(
+                "mean confidence fell from {:.2} to {:.2}",
+                baseline.mean_confidence, recent.mean_confidence
+            ))
+        } else if baseline.text_coverage > 0.0 && recent.text_coverage < baseline.text_coverage * self.config.min_coverage_ratio {
+            Some(format!(
+                "text coverage fell from {:.1}% to {:.1}%",
+                baseline.text_coverage, recent.text_coverage
+            ))
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) if !history.regressed => {
+                history.regressed = true;
+                warn!("OCR quality regression in {}: {}", app_name, reason);
+                Some(OcrQualityRegression {
+                    app_name: app_name.to_string(),
+                    baseline,
+                    recent,
+                    reason,
+                })
+            }
+            Some(_) => None,
+            None => {
+                history.regressed = false;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr_data::BoundingBox;
+    use chrono::Utc;
+
+    fn result(x: f32, y: f32, width: f32, height: f32, confidence: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame_1".to_string(),
+            roi: BoundingBox::new(x, y, width, height),
+            text: "text".to_string(),
+            language: "en-US".to_string(),
+            confidence,
+            processed_at: Utc::now(),
+            processor: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_density_and_regression() {
+        // Two overlapping boxes count their overlap once; the third is clipped to the frame
+        let results = vec![
+            result(0.0, 0.0, 20.0, 10.0, 0.9),
+            result(10.0, 0.0, 20.0, 10.0, 0.7),
+            result(90.0, 90.0, 20.0, 20.0, 0.8),
+        ];
+        let density = OcrDensity::from_results(&results, 100, 100);
+        assert_eq!(density.box_count, 3);
+        assert!((density.text_coverage - 4.0).abs() < 0.001);
+        assert!((density.mean_confidence - 0.8).abs() < 0.001);
+        assert_eq!(OcrDensity::from_results(&[], 100, 100), OcrDensity::default());
+
+        let mut monitor = OcrQualityMonitor::new(OcrQualityConfig {
+            baseline_frames: 10,
+            recent_frames: 5,
+            ..OcrQualityConfig::default()
+        });
+        let good = OcrDensity { box_count: 40, text_coverage: 30.0, mean_confidence: 0.9 };
+        let blurry = OcrDensity { box_count: 35, text_coverage: 28.0, mean_confidence: 0.5 };
+        for _ in 0..15 {
+            assert!(monitor.observe("Safari", &good).is_none());
+        }
+
+        let regressions: Vec<_> = (0..10).filter_map(|_| monitor.observe("Safari", &blurry)).collect();
+        assert_eq!(regressions.len(), 1);
+        assert!(regressions[0].reason.contains("confidence"));
+        // Other applications have their own baseline
+        assert!(monitor.observe("Terminal", &blurry).is_none());
+    }
+}
@@ -92,6 +92,7 @@ fn create_large_ocr_dataset(size: usize) -> Vec<OCRResult> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::TryStreamExt;
     use tokio;
     
     #[tokio::test]
@@ -116,10 +117,10 @@ mod tests {
         writer.flush_batch().await.unwrap();
         
         // Verify file was created
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert_eq!(entries.len(), 1);
         
-        let file_path = entries[0].as_ref().unwrap().path();
+        let file_path = entries[0].clone();
         assert!(file_path.extension().unwrap() == "parquet");
         assert!(file_path.file_name().unwrap().to_str().unwrap().starts_with("ocr_"));
     }
@@ -137,7 +138,7 @@ mod tests {
         writer.flush_batch().await.unwrap();
         
         // Verify file was created
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert_eq!(entries.len(), 1);
     }
     
@@ -155,10 +156,37 @@ mod tests {
         }
         
         // Should have created multiple files due to small batch size
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert!(entries.len() >= 2);
     }
     
+    #[tokio::test]
+    async fn test_spill_under_memory_pressure() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = OCRParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        let budget = crate::writer_memory::MemoryBudget::new(&crate::writer_memory::WriterMemoryConfig {
+            max_buffered_bytes: 1,
+            flush_threshold_bytes: 1024 * 1024,
+        });
+        writer.set_memory_budget(budget.clone());
+        
+        // Every write exceeds the budget and is spilled rather than flushed
+        let test_results = create_test_ocr_results();
+        for result in &test_results {
+            writer.write_ocr_results(&[result.clone()]).await.unwrap();
+        }
+        assert_eq!(budget.used(), 0);
+        assert!(crate::layout_compat::parquet_files(temp_dir.path()).unwrap().is_empty());
+        
+        // The spilled results end up in one file
+        writer.finalize().await.unwrap();
+        assert_eq!(crate::layout_compat::parquet_files(temp_dir.path()).unwrap().len(), 1);
+        let stats = writer.get_statistics().await.unwrap();
+        assert_eq!(stats.total_records, test_results.len() as u64);
+        let spill_dir = temp_dir.path().join(crate::writer_memory::SPILL_DIR);
+        assert_eq!(std::fs::read_dir(spill_dir).unwrap().count(), 0);
+    }
+    
     #[tokio::test]
     async fn test_query_by_frame_id() {
         let temp_dir = TempDir::new().unwrap();
@@ -177,6 +205,27 @@ mod tests {
         }
     }
     
+    #[tokio::test]
+    async fn test_stream_by_frame_id_with_quotes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = OCRParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        
+        let mut test_results = create_test_ocr_results();
+        test_results[2].frame_id = "frame_'002".to_string();
+        writer.write_ocr_results(&test_results).await.unwrap();
+        writer.flush_batch().await.unwrap();
+        
+        // A quote is matched literally rather than ending the filter
+        let stream = writer.stream_by_frame_id("frame_'002").await.unwrap();
+        let results: Vec<OCRResult> = stream.try_collect().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].text, "Bonjour le monde");
+        
+        let injected = writer.query_by_frame_id("x' OR '1'='1").await.unwrap();
+        assert!(injected.is_empty());
+        assert!(writer.query_by_language("en' OR '1'='1").await.unwrap().is_empty());
+    }
+    
     #[tokio::test]
     async fn test_query_by_text() {
         let temp_dir = TempDir::new().unwrap();
@@ -270,10 +319,10 @@ mod tests {
         writer.flush_batch().await.unwrap();
         
         // Verify file was created successfully with compression
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert_eq!(entries.len(), 1);
         
-        let file_path = entries[0].as_ref().unwrap().path();
+        let file_path = entries[0].clone();
         let metadata = std::fs::metadata(&file_path).unwrap();
         assert!(metadata.len() > 0);
     }
@@ -290,7 +339,7 @@ mod tests {
         writer.finalize().await.unwrap();
         
         // Verify file was created
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert_eq!(entries.len(), 1);
     }
     
@@ -346,10 +395,10 @@ mod tests {
         println!("Write performance: {} records in {:?}", large_dataset.len(), write_duration);
         
         // Verify file was created
-        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        let entries = crate::layout_compat::parquet_files(temp_dir.path()).unwrap();
         assert_eq!(entries.len(), 1);
         
-        let file_path = entries[0].as_ref().unwrap().path();
+        let file_path = entries[0].clone();
         let metadata = std::fs::metadata(&file_path).unwrap();
         println!("File size: {} bytes", metadata.len());
         
@@ -498,9 +547,9 @@ mod benchmarks {
             let write_duration = start.elapsed();
             
             // Get file size
-            let entries: Vec<_> = std::fs::read_dir(&compression_dir).unwrap().collect();
+            let entries = crate::layout_compat::parquet_files(&compression_dir).unwrap();
             let file_size = if !entries.is_empty() {
-                std::fs::metadata(entries[0].as_ref().unwrap().path()).unwrap().len()
+                std::fs::metadata(&entries[0]).unwrap().len()
             } else {
                 0
             };
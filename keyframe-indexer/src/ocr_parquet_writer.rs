@@ -1,6 +1,13 @@
 use crate::error::{IndexerError, Result};
 use crate::ocr_data::{OCRResult, OCRBatch, BoundingBox};
+use crate::access_audit::{AccessAuditor, AccessOperation};
 use crate::encryption::{EncryptionManager, SecureParquetWriter};
+use crate::layout_compat::{self, CompatibleTable};
+use crate::file_index::{FileIndex, FileIndexBuilder, FileIndexCache, IndexFilter};
+use crate::stats_cache::{FileStats, StatsManifest};
+use crate::query_stream::{self, RowStream};
+use crate::parquet_tuning::{ParquetSizing, ParquetTuningConfig, RowGroupAutotuner};
+use crate::writer_memory::{MemoryBudget, MemoryReservation, SpillBuffer};
 use arrow::array::{
     Array, Float32Array, StringArray, TimestampNanosecondArray, StructArray
 };
@@ -15,18 +22,35 @@ use std::sync::Arc;
 use tracing::{debug, info, error, warn};
 use chrono::{DateTime, Utc};
 use datafusion::prelude::*;
+use futures::TryStreamExt;
 use datafusion::arrow::array::TimestampNanosecondBuilder;
 
+/// Columns counted by value in the statistics manifest
+const OCR_STAT_COLUMNS: &[&str] = &["language", "processor"];
+
+/// Parquet sizes used until rows have been measured, or always without
+/// autotuning; large row groups compress OCR text well
+const DEFAULT_SIZING: ParquetSizing = ParquetSizing { max_row_group_size: 50000, write_batch_size: 2048 };
+
 /// OCR Parquet writer with efficient indexing and querying capabilities
 pub struct OCRParquetWriter {
     output_dir: PathBuf,
     schema: Arc<Schema>,
     batch_size: usize,
     current_batch: Vec<OCRResult>,
+    /// Estimated size of `current_batch`, held against the memory budget
+    buffered: MemoryReservation,
+    /// Records moved to disk while the budget was exceeded
+    spill: SpillBuffer,
+    /// Row group and write batch sizes, tuned to the row width
+    sizing: RowGroupAutotuner,
     compression: Compression,
     enable_dictionary_encoding: bool,
     secure_writer: Option<SecureParquetWriter>,
     encryption_enabled: bool,
+    access_auditor: Option<AccessAuditor>,
+    /// Sidecar indexes read so far, used to skip files
+    indexes: FileIndexCache,
 }
 
 impl OCRParquetWriter {
@@ -55,21 +79,26 @@ impl OCRParquetWriter {
         ]));
         
         Ok(Self {
+            spill: SpillBuffer::new(&output_path),
             output_dir: output_path,
             schema,
             batch_size: 5000, // Larger batch size for OCR data
             current_batch: Vec::new(),
+            buffered: MemoryBudget::default().reservation(),
+            sizing: RowGroupAutotuner::new(ParquetTuningConfig::default(), DEFAULT_SIZING),
             compression: Compression::SNAPPY, // Good balance of speed and compression
             enable_dictionary_encoding: true, // Efficient for repeated strings
             secure_writer: None,
             encryption_enabled: false,
+            access_auditor: None,
+            indexes: FileIndexCache::default(),
         })
     }
     
     /// Enable encryption for all Parquet files
     pub fn enable_encryption(&mut self) -> Result<()> {
         let secure_writer = SecureParquetWriter::new()
-            .map_err(|e| IndexerError::ProcessingError(format!("Failed to initialize encryption: {}", e)))?;
+            .map_err(|e| IndexerError::encryption("Failed to initialize encryption").caused_by(e))?;
         
         self.secure_writer = Some(secure_writer);
         self.encryption_enabled = true;
@@ -94,39 +123,92 @@ impl OCRParquetWriter {
     pub fn encrypt_existing_file<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
         if let Some(ref secure_writer) = self.secure_writer {
             secure_writer.encrypt_existing_parquet(file_path)
-                .map_err(|e| IndexerError::ProcessingError(format!("Failed to encrypt file: {}", e)))?;
+                .map_err(|e| IndexerError::encryption("Failed to encrypt file").caused_by(e))?;
         } else {
-            return Err(IndexerError::ProcessingError("Encryption not enabled".to_string()));
+            return Err(IndexerError::encryption("Encryption not enabled"));
         }
         Ok(())
     }
     
+    /// Record every decryption in a data access log
+    pub fn set_access_auditor(&mut self, auditor: AccessAuditor) {
+        self.access_auditor = Some(auditor);
+    }
+    
     /// Decrypt an existing Parquet file
     pub fn decrypt_existing_file<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
-        if let Some(ref secure_writer) = self.secure_writer {
-            secure_writer.decrypt_existing_parquet(file_path)
-                .map_err(|e| IndexerError::ProcessingError(format!("Failed to decrypt file: {}", e)))?;
-        } else {
-            return Err(IndexerError::ProcessingError("Encryption not enabled".to_string()));
+        let file_path = file_path.as_ref();
+        let result = match self.secure_writer {
+            Some(ref secure_writer) => secure_writer.decrypt_existing_parquet(file_path)
+                .map_err(|e| IndexerError::encryption("Failed to decrypt file").caused_by(e)),
+            None => Err(IndexerError::encryption("Encryption not enabled")),
+        };
+        self.audit_decryption(&file_path.display().to_string(), result)
+    }
+    
+    fn audit_decryption<T>(&self, target: &str, result: Result<T>) -> Result<T> {
+        match &self.access_auditor {
+            Some(auditor) => auditor.audited(AccessOperation::Decrypt, target, result),
+            None => result,
         }
-        Ok(())
     }
     
     /// Write OCR results to Parquet format
+    #[tracing::instrument(
+        name = "ocr_write",
+        skip_all,
+        fields(results = results.len(), frame_id = results.first().map(|r| r.frame_id.as_str()).unwrap_or_default())
+    )]
     pub async fn write_ocr_results(&mut self, results: &[OCRResult]) -> Result<()> {
         debug!("Writing {} OCR results", results.len());
         
         // Add to current batch
         self.current_batch.extend_from_slice(results);
+        self.buffered.grow(results.iter().map(buffered_size).sum());
         
-        // Write batch if it's large enough
-        if self.current_batch.len() >= self.batch_size {
+        // Write batch if it's large enough, by rows or bytes
+        let rows = self.current_batch.len() + self.spill.rows();
+        let bytes = self.buffered.bytes() + self.spill.bytes();
+        if rows >= self.batch_size || bytes >= self.buffered.budget().flush_threshold_bytes() {
             self.flush_batch().await?;
+        } else if self.buffered.budget().is_exceeded() {
+            // Spill files are plain Arrow IPC, so encrypted results are flushed instead
+            if self.encryption_enabled {
+                self.flush_batch().await?;
+            } else {
+                self.spill_batch()?;
+            }
         }
         
         Ok(())
     }
     
+    /// Share a memory budget with other writers; records buffered so far
+    /// move to the new budget
+    pub fn set_memory_budget(&mut self, budget: MemoryBudget) {
+        let bytes = self.buffered.bytes();
+        self.buffered = budget.reservation();
+        self.buffered.grow(bytes);
+    }
+    
+    /// Choose how row group and write batch sizes are picked
+    pub fn set_parquet_tuning(&mut self, config: ParquetTuningConfig) {
+        self.sizing = RowGroupAutotuner::new(config, DEFAULT_SIZING);
+    }
+    
+    /// Move the buffered records to a spill file until the next flush
+    fn spill_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        self.sizing.observe(&record_batch);
+        self.spill.spill(&record_batch, self.buffered.bytes())?;
+        self.current_batch.clear();
+        self.buffered.clear();
+        Ok(())
+    }
+    
     /// Write an OCR batch
     pub async fn write_ocr_batch(&mut self, batch: &OCRBatch) -> Result<()> {
         self.write_ocr_results(&batch.results).await
@@ -134,11 +216,11 @@ impl OCRParquetWriter {
     
     /// Flush current batch to disk
     pub async fn flush_batch(&mut self) -> Result<()> {
-        if self.current_batch.is_empty() {
+        if self.current_batch.is_empty() && self.spill.is_empty() {
             return Ok(());
         }
         
-        info!("Flushing OCR batch of {} records", self.current_batch.len());
+        info!("Flushing OCR batch of {} records", self.current_batch.len() + self.spill.rows());
         
         // Generate filename with timestamp for partitioning
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
@@ -146,13 +228,23 @@ impl OCRParquetWriter {
         let file_path = self.output_dir.join(filename);
         
         // Create record batch from current data
-        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let record_batch = match self.current_batch.is_empty() {
+            true => None,
+            false => Some(self.create_record_batch(&self.current_batch)?),
+        };
         
-        // Write to Parquet file with optimized settings
-        self.write_record_batch(&file_path, record_batch).await?;
+        // Write to Parquet file with optimized settings, after any spilled records
+        let (stats, index) = self.write_record_batch(&file_path, record_batch).await?;
+        if let Err(e) = StatsManifest::record(&self.output_dir, &file_path, stats) {
+            warn!("Failed to record statistics of {}: {}", file_path.display(), e);
+        }
+        if let Err(e) = index.write(&file_path) {
+            warn!("Failed to write index of {}: {}", file_path.display(), e);
+        }
         
         // Clear current batch
         self.current_batch.clear();
+        self.buffered.clear();
         
         info!("Successfully wrote OCR data to: {}", file_path.display());
         Ok(())
@@ -229,18 +321,28 @@ impl OCRParquetWriter {
         Ok(record_batch)
     }
     
-    /// Write record batch to Parquet file with optimized settings
-    async fn write_record_batch(&self, file_path: &Path, record_batch: RecordBatch) -> Result<()> {
+    /// Write the spilled batches and then `record_batch` to a Parquet file with
+    /// optimized settings; returns the file's statistics and index
+    async fn write_record_batch(
+        &mut self,
+        file_path: &Path,
+        record_batch: Option<RecordBatch>,
+    ) -> Result<(FileStats, FileIndex)> {
         // Write to temporary file first
         let temp_path = file_path.with_extension("tmp.parquet");
         
         let file = File::create(&temp_path)?;
         
+        if let Some(record_batch) = &record_batch {
+            self.sizing.observe(record_batch);
+        }
+        let sizing = self.sizing.sizing();
+        
         // Configure writer properties for optimal compression and performance
         let mut props_builder = WriterProperties::builder()
             .set_compression(self.compression)
-            .set_write_batch_size(2048)
-            .set_max_row_group_size(50000) // Larger row groups for better compression
+            .set_write_batch_size(sizing.write_batch_size)
+            .set_max_row_group_size(sizing.max_row_group_size)
             .set_created_by("AlwaysOnAI OCR Indexer".to_string());
         
         // Enable dictionary encoding for string columns to reduce size
@@ -258,8 +360,19 @@ impl OCRParquetWriter {
         // Create Arrow writer
         let mut writer = ArrowWriter::try_new(file, self.schema.clone(), Some(props))?;
         
-        // Write record batch
-        writer.write(&record_batch)?;
+        // Write record batches, one spilled batch in memory at a time
+        let mut stats = FileStats::default();
+        let mut index = FileIndexBuilder::new("processed_at", &["frame_id"]);
+        let mut write = |batch: RecordBatch| -> Result<()> {
+            stats.add_batch(&batch, OCR_STAT_COLUMNS)?;
+            index.add_batch(&batch)?;
+            writer.write(&batch)?;
+            Ok(())
+        };
+        self.spill.drain(&mut write)?;
+        if let Some(record_batch) = record_batch {
+            write(record_batch)?;
+        }
         
         // Close writer
         writer.close()?;
@@ -269,14 +382,14 @@ impl OCRParquetWriter {
             if let Some(ref secure_writer) = self.secure_writer {
                 // Encrypt the temporary file and move to final location
                 secure_writer.encrypt_file_to(&temp_path, file_path)
-                    .map_err(|e| IndexerError::ProcessingError(format!("Failed to encrypt Parquet file: {}", e)))?;
+                    .map_err(|e| IndexerError::encryption("Failed to encrypt Parquet file").caused_by(e))?;
                 
                 // Remove temporary file
                 std::fs::remove_file(&temp_path)?;
                 
                 debug!("Successfully wrote encrypted OCR Parquet file: {}", file_path.display());
             } else {
-                return Err(IndexerError::ProcessingError("Encryption enabled but secure writer not initialized".to_string()));
+                return Err(IndexerError::encryption("Encryption enabled but secure writer not initialized"));
             }
         } else {
             // Move temporary file to final location
@@ -284,188 +397,134 @@ impl OCRParquetWriter {
             debug!("Successfully wrote OCR Parquet file: {}", file_path.display());
         }
         
-        Ok(())
+        Ok((stats, index.finish()))
     }
     
     /// Prepare files for querying (decrypt if necessary)
-    async fn prepare_files_for_query(&self) -> Result<Vec<PathBuf>> {
-        let parquet_files = self.get_parquet_files()?;
-        
+    async fn prepare_files_for_query(&self, parquet_files: Vec<PathBuf>) -> Result<QueryFiles> {
         if !self.encryption_enabled {
-            return Ok(parquet_files);
+            return Ok(QueryFiles { paths: parquet_files, decrypted: false });
         }
         
         // If encryption is enabled, we need to decrypt files temporarily for querying
-        let mut temp_files = Vec::new();
+        let mut temp_files = QueryFiles { paths: Vec::new(), decrypted: true };
         
         if let Some(ref secure_writer) = self.secure_writer {
             for file_path in parquet_files {
                 let temp_path = file_path.with_extension("query.tmp.parquet");
                 
                 // Decrypt to temporary file
-                secure_writer.decrypt_file_to(&file_path, &temp_path)
-                    .map_err(|e| IndexerError::ProcessingError(format!("Failed to decrypt file for query: {}", e)))?;
+                let result = secure_writer.decrypt_file_to(&file_path, &temp_path)
+                    .map_err(|e| IndexerError::encryption("Failed to decrypt file for query").caused_by(e));
+                self.audit_decryption(&file_path.display().to_string(), result)?;
                 
-                temp_files.push(temp_path);
+                temp_files.paths.push(temp_path);
             }
         }
         
         Ok(temp_files)
     }
     
-    /// Clean up temporary query files
-    async fn cleanup_query_files(&self, temp_files: Vec<PathBuf>) -> Result<()> {
-        for temp_file in temp_files {
-            if temp_file.exists() {
-                std::fs::remove_file(&temp_file)?;
-            }
+    /// Stream OCR data by frame ID
+    pub async fn stream_by_frame_id(&self, frame_id: &str) -> Result<RowStream<OCRResult>> {
+        // Prepare files for querying (decrypt if necessary)
+        let parquet_files = self.indexes.prune(self.get_parquet_files()?, &IndexFilter::equals("frame_id", frame_id));
+        let query_files = self.prepare_files_for_query(parquet_files).await?;
+        if query_files.paths.is_empty() {
+            return Ok(query_stream::empty());
         }
-        Ok(())
+        
+        // The frame ID is a literal, never part of the SQL text
+        let ctx = query_stream::spilling_context()?;
+        let paths: Vec<String> = query_files.paths.iter().map(|path| path.display().to_string()).collect();
+        let batches = ctx
+            .read_parquet(paths, ParquetReadOptions::default())
+            .await?
+            .filter(col("frame_id").eq(lit(frame_id)))?
+            .execute_stream()
+            .await?;
+        
+        // Decrypted copies are removed once the stream is dropped
+        Ok(query_stream::rows(batches, move |batch| {
+            let _files = &query_files;
+            ocr_results_from_batch(batch)
+        }))
     }
     
     /// Query OCR data by frame ID
     pub async fn query_by_frame_id(&self, frame_id: &str) -> Result<Vec<OCRResult>> {
-        let ctx = SessionContext::new();
-        
-        // Prepare files for querying (decrypt if necessary)
-        let query_files = self.prepare_files_for_query().await?;
-        if query_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        // Register files for querying
-        for (i, file_path) in query_files.iter().enumerate() {
-            let table_name = format!("ocr_data_{}", i);
-            ctx.register_parquet(&table_name, file_path.to_str().unwrap(), ParquetReadOptions::default()).await?;
-        }
-        
-        // Create union query for all tables
-        let table_names: Vec<String> = (0..query_files.len())
-            .map(|i| format!("ocr_data_{}", i))
-            .collect();
-        let union_query = table_names.join(" UNION ALL SELECT * FROM ");
-        let sql = format!("SELECT * FROM ({}) WHERE frame_id = '{}'", 
-                         format!("SELECT * FROM {}", union_query), frame_id);
-        
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        // Clean up temporary files
-        self.cleanup_query_files(query_files).await?;
-        
-        // Convert results back to OCRResult structs
-        self.record_batches_to_ocr_results(batches)
+        self.stream_by_frame_id(frame_id).await?.try_collect().await
     }
     
-    /// Query OCR data by text content (full-text search)
-    pub async fn query_by_text(&self, search_text: &str) -> Result<Vec<OCRResult>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("ocr_data", &table_path, ParquetReadOptions::default()).await?;
-        
+    /// Stream OCR data by text content (full-text search), most confident first
+    pub async fn stream_by_text(&self, search_text: &str) -> Result<RowStream<OCRResult>> {
         // Case-insensitive text search
         let sql = format!(
             "SELECT * FROM ocr_data WHERE LOWER(text) LIKE LOWER('%{}%') ORDER BY confidence DESC",
             search_text.replace("'", "''") // Escape single quotes
         );
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_ocr_results(batches)
+        self.stream_ocr_data(&sql).await
     }
     
-    /// Query OCR data by confidence threshold
-    pub async fn query_by_confidence(&self, min_confidence: f32) -> Result<Vec<OCRResult>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("ocr_data", &table_path, ParquetReadOptions::default()).await?;
-        
+    /// Query OCR data by text content (full-text search)
+    pub async fn query_by_text(&self, search_text: &str) -> Result<Vec<OCRResult>> {
+        self.stream_by_text(search_text).await?.try_collect().await
+    }
+    
+    /// Stream OCR data by confidence threshold, most confident first
+    pub async fn stream_by_confidence(&self, min_confidence: f32) -> Result<RowStream<OCRResult>> {
         let sql = format!(
             "SELECT * FROM ocr_data WHERE confidence >= {} ORDER BY confidence DESC",
             min_confidence
         );
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_ocr_results(batches)
+        self.stream_ocr_data(&sql).await
+    }
+    
+    /// Query OCR data by confidence threshold
+    pub async fn query_by_confidence(&self, min_confidence: f32) -> Result<Vec<OCRResult>> {
+        self.stream_by_confidence(min_confidence).await?.try_collect().await
+    }
+    
+    /// Stream OCR data by language
+    pub async fn stream_by_language(&self, language: &str) -> Result<RowStream<OCRResult>> {
+        let sql = format!("SELECT * FROM ocr_data WHERE language = '{}'", language.replace("'", "''"));
+        self.stream_ocr_data(&sql).await
     }
     
     /// Query OCR data by language
     pub async fn query_by_language(&self, language: &str) -> Result<Vec<OCRResult>> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(Vec::new());
+        self.stream_by_language(language).await?.try_collect().await
+    }
+    
+    /// Run `sql` over the `ocr_data` table, converting one record batch at a time
+    async fn stream_ocr_data(&self, sql: &str) -> Result<RowStream<OCRResult>> {
+        if self.get_parquet_files()?.is_empty() {
+            return Ok(query_stream::empty());
         }
         
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("ocr_data", &table_path, ParquetReadOptions::default()).await?;
-        
-        let sql = format!("SELECT * FROM ocr_data WHERE language = '{}'", language);
-        let df = ctx.sql(&sql).await?;
-        let batches = df.collect().await?;
-        
-        self.record_batches_to_ocr_results(batches)
+        let ctx = query_stream::spilling_context()?;
+        self.register_ocr_data(&ctx).await?;
+        query_stream::query_rows(&ctx, sql, ocr_results_from_batch).await
     }
     
-    /// Get statistics about stored OCR data
+    /// Statistics about stored OCR data from the directory's statistics
+    /// manifest; only files written without it, or rewritten since, are read
     pub async fn get_statistics(&self) -> Result<OCRStatistics> {
-        let ctx = SessionContext::new();
-        
-        let parquet_files = self.get_parquet_files()?;
-        if parquet_files.is_empty() {
-            return Ok(OCRStatistics::default());
-        }
-        
-        let table_path = format!("{}/*.parquet", self.output_dir.display());
-        ctx.register_parquet("ocr_data", &table_path, ParquetReadOptions::default()).await?;
-        
-        // Get basic statistics
-        let count_sql = "SELECT COUNT(*) as total_records FROM ocr_data";
-        let count_df = ctx.sql(count_sql).await?;
-        let count_batches = count_df.collect().await?;
-        
-        let avg_confidence_sql = "SELECT AVG(confidence) as avg_confidence FROM ocr_data";
-        let avg_df = ctx.sql(avg_confidence_sql).await?;
-        let avg_batches = avg_df.collect().await?;
-        
-        let lang_stats_sql = "SELECT language, COUNT(*) as count FROM ocr_data GROUP BY language ORDER BY count DESC";
-        let lang_df = ctx.sql(lang_stats_sql).await?;
-        let lang_batches = lang_df.collect().await?;
-        
-        // Extract statistics (simplified for now)
-        let total_records = if !count_batches.is_empty() && count_batches[0].num_rows() > 0 {
-            // In a real implementation, you'd extract the actual count value
-            count_batches[0].num_rows() as u64
-        } else {
-            0
-        };
+        let totals = StatsManifest::refresh(&self.output_dir, OCR_STAT_COLUMNS)?.totals();
+        let distribution = |column| totals.counts(column).map(|(value, count)| (value.to_string(), count)).collect();
         
         Ok(OCRStatistics {
-            total_records,
-            average_confidence: 0.85, // Placeholder
-            language_distribution: std::collections::HashMap::new(), // Placeholder
-            processor_distribution: std::collections::HashMap::new(), // Placeholder
-            total_size_bytes: self.calculate_total_size()?,
+            total_records: totals.rows,
+            average_confidence: totals.average_confidence(),
+            language_distribution: distribution("language"),
+            processor_distribution: distribution("processor"),
+            total_size_bytes: totals.size_bytes,
         })
     }
     
     /// Finalize and flush any remaining data
     pub async fn finalize(&mut self) -> Result<()> {
-        if !self.current_batch.is_empty() {
+        if !self.current_batch.is_empty() || !self.spill.is_empty() {
             self.flush_batch().await?;
         }
         
@@ -476,62 +535,14 @@ impl OCRParquetWriter {
     // MARK: - Private Helper Methods
     
     fn get_parquet_files(&self) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
-        
-        if !self.output_dir.exists() {
-            return Ok(files);
-        }
-        
-        for entry in std::fs::read_dir(&self.output_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) == Some("parquet") {
-                files.push(path);
-            }
-        }
-        
-        Ok(files)
-    }
-    
-    fn record_batches_to_ocr_results(&self, batches: Vec<RecordBatch>) -> Result<Vec<OCRResult>> {
-        let mut results = Vec::new();
-        
-        for batch in batches {
-            // Extract data from batch (simplified implementation)
-            // In a real implementation, you'd properly extract all fields
-            let frame_ids = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
-            let texts = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
-            let languages = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
-            let confidences = batch.column(4).as_any().downcast_ref::<Float32Array>().unwrap();
-            let processors = batch.column(6).as_any().downcast_ref::<StringArray>().unwrap();
-            
-            for i in 0..batch.num_rows() {
-                results.push(OCRResult {
-                    frame_id: frame_ids.value(i).to_string(),
-                    roi: BoundingBox::new(0.0, 0.0, 100.0, 20.0), // Placeholder - would extract from struct
-                    text: texts.value(i).to_string(),
-                    language: languages.value(i).to_string(),
-                    confidence: confidences.value(i),
-                    processed_at: Utc::now(), // Placeholder - would extract from timestamp
-                    processor: processors.value(i).to_string(),
-                });
-            }
-        }
-        
-        Ok(results)
+        layout_compat::parquet_files(&self.output_dir)
     }
     
-    fn calculate_total_size(&self) -> Result<u64> {
-        let mut total_size = 0u64;
-        
-        for file_path in self.get_parquet_files()? {
-            if let Ok(metadata) = std::fs::metadata(&file_path) {
-                total_size += metadata.len();
-            }
-        }
-        
-        Ok(total_size)
+    /// Register the stored files of every layout as the `ocr_data` table
+    pub async fn register_ocr_data(&self, ctx: &SessionContext) -> Result<()> {
+        CompatibleTable::new("ocr_data", self.schema.clone())
+            .register(ctx, &self.output_dir)
+            .await
     }
     
     // MARK: - Configuration Methods
@@ -557,6 +568,72 @@ impl OCRParquetWriter {
     }
 }
 
+/// Files a query reads; decrypted copies are removed when it is done
+struct QueryFiles {
+    paths: Vec<PathBuf>,
+    decrypted: bool,
+}
+
+impl Drop for QueryFiles {
+    fn drop(&mut self) {
+        if self.decrypted {
+            for path in &self.paths {
+                if let Err(e) = std::fs::remove_file(path) {
+                    warn!("Failed to remove decrypted query file {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Estimated bytes a buffered result holds, dominated by its text
+fn buffered_size(result: &OCRResult) -> usize {
+    std::mem::size_of::<OCRResult>()
+        + result.frame_id.len()
+        + result.text.len()
+        + result.language.len()
+        + result.processor.len()
+}
+
+/// OCR results of a batch read back from an OCR file. Rows that fail
+/// validation, such as NaN or negative-size ROIs, are dropped; a batch with
+/// unexpected columns is an error.
+pub fn ocr_results_from_batch(batch: &RecordBatch) -> Result<Vec<OCRResult>> {
+    let frame_ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let rois = layout_compat::column::<StructArray>(batch, 1)?;
+    let (xs, ys) = (roi_coordinate(rois, "x")?, roi_coordinate(rois, "y")?);
+    let (widths, heights) = (roi_coordinate(rois, "width")?, roi_coordinate(rois, "height")?);
+    let texts = layout_compat::column::<StringArray>(batch, 2)?;
+    let languages = layout_compat::column::<StringArray>(batch, 3)?;
+    let confidences = layout_compat::column::<Float32Array>(batch, 4)?;
+    let processed_at = layout_compat::column::<TimestampNanosecondArray>(batch, 5)?;
+    let processors = layout_compat::column::<StringArray>(batch, 6)?;
+    
+    let mut results = Vec::with_capacity(batch.num_rows());
+    for i in 0..batch.num_rows() {
+        let result = OCRResult {
+            frame_id: frame_ids.value(i).to_string(),
+            roi: BoundingBox::new(xs.value(i), ys.value(i), widths.value(i), heights.value(i)),
+            text: texts.value(i).to_string(),
+            language: languages.value(i).to_string(),
+            confidence: confidences.value(i),
+            processed_at: DateTime::from_timestamp_nanos(processed_at.value(i)),
+            processor: processors.value(i).to_string(),
+        };
+        match result.validate() {
+            Ok(()) => results.push(result),
+            Err(e) => warn!("Skipping stored OCR result of {}: {}", result.frame_id, e),
+        }
+    }
+    Ok(results)
+}
+
+fn roi_coordinate<'a>(rois: &'a StructArray, name: &str) -> Result<&'a Float32Array> {
+    rois.column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<Float32Array>())
+        .ok_or_else(|| IndexerError::InvalidOcr(format!("ROI column has no {} coordinate", name)))
+}
+
 /// Statistics about stored OCR data
 #[derive(Debug, Clone)]
 pub struct OCRStatistics {
@@ -0,0 +1,281 @@
+use crate::csv_writer::{write_frame_rows, CsvWriter};
+use crate::display_timeline::TimelineEntry;
+use crate::embeddings::FrameEmbedding;
+use crate::enum_codes::EnumCode;
+use crate::error::Result;
+use crate::event_detector::DetectedEvent;
+use crate::form_model::FormRecord;
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::parquet_writer::ParquetWriter;
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File formats stored outputs can be converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// Write events in `format`, returning the number of rows written.
+///
+/// CSV rows hold the type name, an RFC 3339 timestamp, the evidence frames
+/// separated by `;` and the metadata as JSON.
+pub fn export_events<W: Write>(events: &[DetectedEvent], format: ExportFormat, output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(events, output),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record([
+                "event_id", "timestamp", "type", "target", "value_from", "value_to",
+                "confidence", "evidence_frames", "metadata",
+            ]).map_err(std::io::Error::from)?;
+            for event in events {
+                writer.write_record([
+                    event.id.clone(),
+                    event.timestamp.to_rfc3339(),
+                    event.event_type.name().to_string(),
+                    event.target.clone(),
+                    event.value_from.clone().unwrap_or_default(),
+                    event.value_to.clone().unwrap_or_default(),
+                    event.confidence.to_string(),
+                    event.evidence_frames.join(";"),
+                    serde_json::to_string(&event.metadata)?,
+                ]).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            Ok(events.len())
+        }
+    }
+}
+
+/// Write OCR results in `format`, returning the number of rows written
+pub fn export_ocr<W: Write>(results: &[OCRResult], format: ExportFormat, output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(results, output),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record([
+                "frame_id", "text", "language", "confidence", "x", "y", "width", "height",
+                "processor", "processed_at",
+            ]).map_err(std::io::Error::from)?;
+            for result in results {
+                writer.write_record([
+                    result.frame_id.clone(),
+                    result.text.clone(),
+                    result.language.clone(),
+                    result.confidence.to_string(),
+                    result.roi.x.to_string(),
+                    result.roi.y.to_string(),
+                    result.roi.width.to_string(),
+                    result.roi.height.to_string(),
+                    result.processor.clone(),
+                    result.processed_at.to_rfc3339(),
+                ]).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            Ok(results.len())
+        }
+    }
+}
+
+/// Write frame metadata in `format`, returning the number of rows written.
+///
+/// CSV rows have the columns of the stored frame CSV files.
+pub fn export_frames<W: Write>(frames: &[FrameMetadata], format: ExportFormat, mut output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(frames, output),
+        ExportFormat::Csv => {
+            write_frame_rows(&mut output, frames)?;
+            output.flush()?;
+            Ok(frames.len())
+        }
+    }
+}
+
+/// Write cross-display timeline entries in `format`, returning the number of rows written
+pub fn export_timeline<W: Write>(entries: &[TimelineEntry], format: ExportFormat, output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(entries, output),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record(["timestamp", "monitor_id", "segment_id", "path", "app_name", "win_title"])
+                .map_err(std::io::Error::from)?;
+            for entry in entries {
+                writer.write_record([
+                    entry.timestamp.to_rfc3339(),
+                    entry.monitor_id.to_string(),
+                    entry.segment_id.clone(),
+                    entry.path.clone(),
+                    entry.app_name.clone(),
+                    entry.win_title.clone(),
+                ]).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            Ok(entries.len())
+        }
+    }
+}
+
+/// Write form records in `format`, returning the number of rows written.
+///
+/// CSV rows hold the fields and buttons as JSON.
+pub fn export_form_records<W: Write>(records: &[FormRecord], format: ExportFormat, output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(records, output),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record([
+                "record_id", "timestamp", "frame_id", "submission_event_id", "submitted_with", "fields", "buttons",
+            ]).map_err(std::io::Error::from)?;
+            for record in records {
+                writer.write_record([
+                    record.record_id.clone(),
+                    record.timestamp.to_rfc3339(),
+                    record.frame_id.clone(),
+                    record.submission_event_id.clone(),
+                    record.submitted_with.clone().unwrap_or_default(),
+                    serde_json::to_string(&record.fields)?,
+                    serde_json::to_string(&record.buttons)?,
+                ]).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            Ok(records.len())
+        }
+    }
+}
+
+/// Write frame embeddings in `format`, returning the number of rows written.
+///
+/// CSV rows hold the vector components separated by `;`.
+pub fn export_embeddings<W: Write>(embeddings: &[FrameEmbedding], format: ExportFormat, output: W) -> Result<usize> {
+    match format {
+        ExportFormat::JsonLines => write_json_lines(embeddings, output),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record(["frame_id", "model", "text", "embedded_at", "vector"])
+                .map_err(std::io::Error::from)?;
+            for embedding in embeddings {
+                let vector: Vec<String> = embedding.vector.iter().map(|v| v.to_string()).collect();
+                writer.write_record([
+                    embedding.frame_id.clone(),
+                    embedding.model.clone(),
+                    embedding.text.clone(),
+                    embedding.embedded_at.to_rfc3339(),
+                    vector.join(";"),
+                ]).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+            Ok(embeddings.len())
+        }
+    }
+}
+
+/// Read every `timeline_*.csv` file in `dir`, skipping rows that don't parse
+pub fn load_timeline(dir: &Path) -> Result<Vec<TimelineEntry>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            name.starts_with("timeline_") && name.ends_with(".csv")
+        })
+        .collect();
+    files.sort();
+
+    let mut entries = Vec::new();
+    for file in files {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(&file).map_err(std::io::Error::from)?;
+        for record in reader.records() {
+            let record = record.map_err(std::io::Error::from)?;
+            let field = |i: usize| record.get(i).unwrap_or_default().to_string();
+            let (Ok(timestamp), Ok(monitor_id)) = (DateTime::parse_from_rfc3339(&field(0)), field(1).parse()) else {
+                continue;
+            };
+            entries.push(TimelineEntry {
+                timestamp: timestamp.with_timezone(&Utc),
+                monitor_id,
+                segment_id: field(2),
+                path: field(3),
+                app_name: field(4),
+                win_title: field(5),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Read every `frames_*.parquet` and `frames_*.csv` file in `dir`
+pub async fn load_frames(dir: &Path) -> Result<Vec<FrameMetadata>> {
+    let dir_name = dir.to_string_lossy();
+    let parquet_reader = ParquetWriter::new(&dir_name)?;
+    let csv_reader = CsvWriter::new(&dir_name)?;
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("frames_")))
+        .collect();
+    files.sort();
+
+    let mut frames = Vec::new();
+    for file in files {
+        match file.extension().and_then(|extension| extension.to_str()) {
+            Some("parquet") => frames.extend(parquet_reader.read_parquet_file(&file).await?),
+            Some("csv") => frames.extend(csv_reader.read_csv_file(&file).await?),
+            _ => {}
+        }
+    }
+    Ok(frames)
+}
+
+pub(crate) fn write_json_lines<T: serde::Serialize, W: Write>(rows: &[T], mut output: W) -> Result<usize> {
+    for row in rows {
+        serde_json::to_writer(&mut output, row)?;
+        output.write_all(b"\n")?;
+    }
+    output.flush()?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_export_events_as_csv_and_json_lines() {
+        let event = DetectedEvent {
+            id: "event-1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "login_form".to_string(),
+            value_from: None,
+            value_to: Some("Invalid password, try again".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1".to_string(), "frame_2".to_string()],
+            metadata: HashMap::new(),
+        };
+
+        let mut csv = Vec::new();
+        assert_eq!(export_events(&[event.clone()], ExportFormat::Csv, &mut csv).unwrap(), 1);
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("event_id,timestamp,type"));
+        assert!(lines[1].contains("error_display") && lines[1].contains("\"Invalid password, try again\""));
+        assert!(lines[1].contains("frame_1;frame_2"));
+
+        let mut json = Vec::new();
+        export_events(&[event.clone(), event], ExportFormat::JsonLines, &mut json).unwrap();
+        let rows: Vec<DetectedEvent> = String::from_utf8(json)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].id, "event-1");
+    }
+}
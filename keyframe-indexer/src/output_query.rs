@@ -0,0 +1,260 @@
+use crate::error::{IndexerError, Result};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::layout_compat;
+use crate::ocr_parquet_writer::OCRParquetWriter;
+use crate::output_export::load_frames;
+use crate::parquet_writer::ParquetWriter;
+use crate::plugin_tables::PluginTableRegistry;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::listing::{ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl};
+use datafusion::datasource::MemTable;
+use datafusion::execution::context::SQLOptions;
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+/// A ready-made query over the indexer's outputs
+#[derive(Debug, Clone, Copy)]
+pub struct CannedQuery {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Table the query reads, `events` or `ocr_data`
+    pub table: &'static str,
+    pub sql: &'static str,
+}
+
+pub const CANNED_QUERIES: &[CannedQuery] = &[
+    CannedQuery {
+        name: "event-counts",
+        description: "Number of events of each type",
+        table: "events",
+        sql: "SELECT type, COUNT(*) AS events, AVG(confidence) AS avg_confidence FROM events GROUP BY type ORDER BY events DESC",
+    },
+    CannedQuery {
+        name: "recent-events",
+        description: "The 50 latest events",
+        table: "events",
+        sql: "SELECT ts_ns, type, target, value_from, value_to, confidence FROM events ORDER BY ts_ns DESC LIMIT 50",
+    },
+    CannedQuery {
+        name: "errors",
+        description: "Error displays with their type, severity and message",
+        table: "events",
+        sql: "SELECT ts_ns, modal_type, severity, value_to AS message, confidence FROM events WHERE type = 'error_display' ORDER BY ts_ns",
+    },
+    CannedQuery {
+        name: "notifications",
+        description: "Notification Center banners",
+        table: "events",
+        sql: "SELECT ts_ns, value_to AS message, confidence FROM events WHERE modal_type = 'system_notification' ORDER BY ts_ns",
+    },
+    CannedQuery {
+        name: "ocr-languages",
+        description: "OCR results per language with their average confidence",
+        table: "ocr_data",
+        sql: "SELECT language, COUNT(*) AS results, AVG(confidence) AS avg_confidence FROM ocr_data GROUP BY language ORDER BY results DESC",
+    },
+    CannedQuery {
+        name: "low-confidence-ocr",
+        description: "OCR results below 0.5 confidence, worst first",
+        table: "ocr_data",
+        sql: "SELECT frame_id, text, confidence FROM ocr_data WHERE confidence < 0.5 ORDER BY confidence",
+    },
+];
+
+impl CannedQuery {
+    pub fn find(name: &str) -> Option<&'static CannedQuery> {
+        CANNED_QUERIES.iter().find(|query| query.name == name)
+    }
+}
+
+/// SQL access to stored OCR results (`ocr_data`), events (`events`) and,
+/// once registered, frame metadata (`frames`) and plugin tables
+pub struct OutputQuery {
+    ctx: SessionContext,
+    tables: Vec<String>,
+}
+
+impl OutputQuery {
+    /// Register the outputs found in the given directories; directories
+    /// without Parquet files are skipped
+    pub async fn open(ocr_dir: Option<&Path>, event_dir: Option<&Path>) -> Result<Self> {
+        let ctx = SessionContext::new();
+        let mut tables = Vec::new();
+
+        if let Some(dir) = ocr_dir.filter(|dir| has_parquet_files(dir)) {
+            OCRParquetWriter::new(&dir.to_string_lossy())?.register_ocr_data(&ctx).await?;
+            tables.push("ocr_data".to_string());
+        }
+        if let Some(dir) = event_dir.filter(|dir| has_parquet_files(dir)) {
+            EventParquetWriter::new(&dir.to_string_lossy())?.register_events(&ctx).await?;
+            tables.push("events".to_string());
+        }
+
+        info!("Registered output tables: {}", tables.join(", "));
+        Ok(Self { ctx, tables })
+    }
+
+    /// Register the frame metadata in `output_dir` as the `frames` table:
+    /// the `frames_*.csv` files the service writes and any `frames_*.parquet`
+    /// files; nothing is registered if there are none
+    pub async fn register_frames(&mut self, output_dir: &Path) -> Result<()> {
+        if has_frame_csv_files(output_dir)? {
+            // CSV rows carry no column types, so they are read back into the
+            // Parquet frame schema and served from memory
+            let frames = load_frames(output_dir).await?;
+            let batch = ParquetWriter::new(&output_dir.to_string_lossy())?.create_record_batch(&frames)?;
+            let table = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+            self.ctx.register_table("frames", Arc::new(table))?;
+            self.tables.push("frames".to_string());
+            return Ok(());
+        }
+        let urls = layout_compat::parquet_files(output_dir)?
+            .iter()
+            .filter(|file| file.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("frames_")))
+            .map(|file| ListingTableUrl::parse(file.to_string_lossy()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if urls.is_empty() {
+            return Ok(());
+        }
+        let options = ListingOptions::new(Arc::new(ParquetFormat::default())).with_file_extension(".parquet");
+        let config = ListingTableConfig::new_with_multi_paths(urls)
+            .with_listing_options(options)
+            .infer_schema(&self.ctx.state())
+            .await?;
+        self.ctx.register_table("frames", Arc::new(ListingTable::try_new(config)?))?;
+        self.tables.push("frames".to_string());
+        Ok(())
+    }
+
+    /// Register every table stored by detector plugins under `root`
+    pub async fn register_plugin_tables(&mut self, root: &Path) -> Result<()> {
+        if !root.is_dir() {
+            return Ok(());
+        }
+        let registry = PluginTableRegistry::open(&root.to_string_lossy())?;
+        registry.register_with(&self.ctx).await?;
+        self.tables.extend(registry.table_names().into_iter().map(str::to_string));
+        Ok(())
+    }
+
+    /// Tables available to queries
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+
+    pub async fn sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        if self.tables.is_empty() {
+            return Err(IndexerError::Config("No OCR or event output to query".to_string()));
+        }
+        Ok(self.ctx.sql(sql).await?.collect().await?)
+    }
+
+    /// Run a read-only query and stream its batches as they are produced;
+    /// statements that create, change or drop data are rejected
+    pub async fn sql_stream(&self, sql: &str) -> Result<(SchemaRef, SendableRecordBatchStream)> {
+        let frame = self.read_only(sql).await?;
+        let schema: SchemaRef = Arc::new(frame.schema().as_arrow().clone());
+        Ok((schema, frame.execute_stream().await?))
+    }
+
+    /// Schema a read-only query's results will have, without running it
+    pub async fn sql_schema(&self, sql: &str) -> Result<SchemaRef> {
+        Ok(Arc::new(self.read_only(sql).await?.schema().as_arrow().clone()))
+    }
+
+    async fn read_only(&self, sql: &str) -> Result<DataFrame> {
+        if self.tables.is_empty() {
+            return Err(IndexerError::Config("No OCR, event or frame output to query".to_string()));
+        }
+        let options = SQLOptions::new().with_allow_ddl(false).with_allow_dml(false).with_allow_statements(false);
+        Ok(self.ctx.sql_with_options(sql, options).await?)
+    }
+
+    pub async fn canned(&self, name: &str) -> Result<Vec<RecordBatch>> {
+        let query = CannedQuery::find(name).ok_or_else(|| {
+            let names: Vec<&str> = CANNED_QUERIES.iter().map(|query| query.name).collect();
+            IndexerError::Config(format!("Unknown canned query '{}'; available: {}", name, names.join(", ")))
+        })?;
+        if !self.tables.iter().any(|table| table == query.table) {
+            return Err(IndexerError::Config(format!(
+                "Canned query '{}' needs the {} table",
+                query.name, query.table
+            )));
+        }
+        self.sql(query.sql).await
+    }
+}
+
+/// Query results as an aligned text table
+pub fn format_table(batches: &[RecordBatch]) -> Result<String> {
+    Ok(arrow::util::pretty::pretty_format_batches(batches)?.to_string())
+}
+
+/// Query results as a JSON array of row objects
+pub fn format_json(batches: &[RecordBatch]) -> Result<String> {
+    let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    let json = writer.into_inner();
+    Ok(if json.is_empty() { "[]".to_string() } else { String::from_utf8_lossy(&json).into_owned() })
+}
+
+fn has_parquet_files(dir: &Path) -> bool {
+    layout_compat::parquet_files(dir).map(|files| !files.is_empty()).unwrap_or(false)
+}
+
+fn has_frame_csv_files(dir: &Path) -> Result<bool> {
+    if !dir.is_dir() {
+        return Ok(false);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if name.starts_with("frames_") && name.ends_with(".csv") && path.is_file() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quickstart::QuickstartDataset;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_canned_and_ad_hoc_queries() {
+        let temp_dir = TempDir::new().unwrap();
+        let dataset = QuickstartDataset::generate(temp_dir.path()).await.unwrap();
+
+        let query = OutputQuery::open(Some(dataset.ocr_dir().as_path()), Some(dataset.events_dir().as_path())).await.unwrap();
+        assert_eq!(query.tables(), &["ocr_data", "events"]);
+
+        let counts = query.canned("event-counts").await.unwrap();
+        let total: usize = counts.iter().map(|batch| batch.num_rows()).sum();
+        assert!(total > 0);
+        assert!(format_table(&counts).unwrap().contains("error_display"));
+
+        let rows = query.sql("SELECT COUNT(*) AS results FROM ocr_data").await.unwrap();
+        assert!(format_json(&rows).unwrap().contains(&format!("\"results\":{}", dataset.ocr_results)));
+
+        assert!(query.canned("no-such-query").await.is_err());
+        let events_only = OutputQuery::open(None, Some(dataset.events_dir().as_path())).await.unwrap();
+        assert!(events_only.canned("ocr-languages").await.is_err());
+
+        let mut with_frames = query;
+        with_frames.register_frames(&dataset.frames_dir()).await.unwrap();
+        assert_eq!(with_frames.tables(), &["ocr_data", "events", "frames"]);
+        let (schema, _) = with_frames.sql_stream("SELECT * FROM frames").await.unwrap();
+        assert!(schema.field_with_name("app_name").is_ok());
+        assert!(with_frames.sql_stream("DROP VIEW events").await.is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::error::Result;
+use crate::event_detector::DetectedEvent;
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::output_export;
+use crate::sqlite_sink::SqliteSink;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Extra formats outputs are written in, next to the CSV and Parquet files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSinkKind {
+    /// Daily JSON Lines files under `<output_dir>/jsonl/`
+    Jsonl,
+    /// One indexed database per session under `<output_dir>/sqlite/`
+    Sqlite,
+}
+
+/// Destination for the frame metadata, OCR results and events the indexer produces
+pub trait OutputSink: Send {
+    fn name(&self) -> &str;
+
+    fn write_frames(&mut self, frames: &[FrameMetadata]) -> Result<()>;
+
+    fn write_ocr(&mut self, results: &[OCRResult]) -> Result<()>;
+
+    fn write_events(&mut self, events: &[DetectedEvent]) -> Result<()>;
+
+    /// Make everything written so far durable; called once before exiting
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Open a sink of each kind in `kinds`, writing under `output_dir`
+pub fn open_sinks(kinds: &[OutputSinkKind], output_dir: &str) -> Result<Vec<Box<dyn OutputSink>>> {
+    let mut sinks: Vec<Box<dyn OutputSink>> = Vec::new();
+    for kind in kinds {
+        match kind {
+            OutputSinkKind::Jsonl => sinks.push(Box::new(JsonlSink::new(Path::new(output_dir).join("jsonl"))?)),
+            OutputSinkKind::Sqlite => {
+                let file_name = format!("session_{}.db", Utc::now().format("%Y%m%d_%H%M%S"));
+                sinks.push(Box::new(SqliteSink::open(Path::new(output_dir).join("sqlite").join(file_name))?));
+            }
+        }
+    }
+    Ok(sinks)
+}
+
+/// Appends each kind of output to its own daily JSON Lines file, e.g.
+/// `frames_20250101.jsonl`, with one object per line in the same shape as
+/// `export --format jsonl`
+pub struct JsonlSink {
+    dir: PathBuf,
+}
+
+impl JsonlSink {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn append<T: Serialize>(&self, table: &str, rows: &[T]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let file_path = self.dir.join(format!("{}_{}.jsonl", table, Utc::now().format("%Y%m%d")));
+        let file = OpenOptions::new().create(true).append(true).open(&file_path)?;
+        output_export::write_json_lines(rows, BufWriter::new(file))?;
+        debug!("Wrote {} {} rows to {}", rows.len(), table, file_path.display());
+        Ok(())
+    }
+}
+
+impl OutputSink for JsonlSink {
+    fn name(&self) -> &str {
+        "jsonl"
+    }
+
+    fn write_frames(&mut self, frames: &[FrameMetadata]) -> Result<()> {
+        self.append("frames", frames)
+    }
+
+    fn write_ocr(&mut self, results: &[OCRResult]) -> Result<()> {
+        self.append("ocr", results)
+    }
+
+    fn write_events(&mut self, events: &[DetectedEvent]) -> Result<()> {
+        self.append("events", events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::EncryptionManager;
+    use crate::event_detector::EventType;
+    use crate::event_signing::{EventSigner, EventVerifier};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_jsonl_sink_appends_one_file_per_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut sinks = open_sinks(&[OutputSinkKind::Jsonl], &temp_dir.path().to_string_lossy()).unwrap();
+        assert_eq!(sinks.len(), 1);
+
+        let frame = FrameMetadata {
+            ts_ns: 1_000,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "frame_1.png".to_string(),
+            phash16: 42,
+            entropy: 5.0,
+            app_name: "Xcode".to_string(),
+            win_title: "main.swift".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        };
+        sinks[0].write_frames(&[frame.clone()]).unwrap();
+        sinks[0].write_frames(&[frame]).unwrap();
+        sinks[0].write_events(&[]).unwrap();
+
+        let dir = temp_dir.path().join("jsonl");
+        let frames_file = dir.join(format!("frames_{}.jsonl", Utc::now().format("%Y%m%d")));
+        let rows: Vec<FrameMetadata> = std::fs::read_to_string(frames_file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].app_name, "Xcode");
+        // Nothing to write creates no file
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_signed_event_rows_verify() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut sink = JsonlSink::new(temp_dir.path()).unwrap();
+        let signer = EventSigner::with_manager(EncryptionManager::with_key(&[7u8; 32]));
+        let mut event = DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "checkout_form".to_string(),
+            value_from: None,
+            value_to: Some("Payment failed".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1.png".to_string()],
+            metadata: HashMap::from([("app_name".to_string(), "Safari".to_string())]),
+        };
+        signer.attach_signature(&mut event).unwrap();
+        sink.write_events(&[event]).unwrap();
+
+        let events_file = temp_dir.path().join(format!("events_{}.jsonl", Utc::now().format("%Y%m%d")));
+        let mut row: DetectedEvent = serde_json::from_str(std::fs::read_to_string(events_file).unwrap().trim()).unwrap();
+        let verifier = EventVerifier::from_public_key_hex(&signer.public_key_hex()).unwrap();
+        assert!(verifier.verify_attached(&row).unwrap());
+
+        row.value_to = Some("Payment succeeded".to_string());
+        assert!(!verifier.verify_attached(&row).unwrap());
+    }
+}
@@ -26,6 +26,10 @@ async fn test_csv_writer_standalone() -> Result<()> {
             win_title: "Test Window".to_string(),
             width: 1920,
             height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         },
         FrameMetadata {
             ts_ns: 2000000000,
@@ -38,6 +42,10 @@ async fn test_csv_writer_standalone() -> Result<()> {
             win_title: "Another Window".to_string(),
             width: 2560,
             height: 1440,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         },
     ];
     
@@ -103,6 +111,10 @@ async fn test_csv_performance() -> Result<()> {
             win_title: format!("Window_{}", i % 10),
             width: 1920 + (i % 4) * 320,
             height: 1080 + (i % 3) * 240,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
         });
     }
     
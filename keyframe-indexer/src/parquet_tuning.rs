@@ -0,0 +1,154 @@
+use crate::error::{IndexerError, Result};
+use arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// How writers size Parquet row groups and write batches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParquetTuningConfig {
+    /// Derive sizes from the row width of the first batches instead of each
+    /// writer's fixed defaults; off by default, as files flushed at the
+    /// writers' row counts rarely reach a tuned row group size
+    pub autotune: bool,
+    /// Batches measured before the sizes are settled
+    pub sample_batches: usize,
+    /// In-memory bytes a row group should hold
+    pub target_row_group_bytes: usize,
+    /// In-memory bytes handed to the column writers at a time
+    pub target_write_batch_bytes: usize,
+}
+
+impl Default for ParquetTuningConfig {
+    fn default() -> Self {
+        Self {
+            autotune: false,
+            sample_batches: 3,
+            target_row_group_bytes: 32 * 1024 * 1024,
+            target_write_batch_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl ParquetTuningConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_batches == 0 {
+            return Err(IndexerError::Config("sample_batches must be greater than 0".to_string()));
+        }
+        if self.target_write_batch_bytes == 0 || self.target_row_group_bytes < self.target_write_batch_bytes {
+            return Err(IndexerError::Config(
+                "target_row_group_bytes must be at least target_write_batch_bytes, which must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Row counts passed to `WriterProperties`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParquetSizing {
+    pub max_row_group_size: usize,
+    pub write_batch_size: usize,
+}
+
+const MIN_ROW_GROUP_ROWS: usize = 1024;
+const MAX_ROW_GROUP_ROWS: usize = 1_000_000;
+const MIN_WRITE_BATCH_ROWS: usize = 64;
+const MAX_WRITE_BATCH_ROWS: usize = 8192;
+
+/// Picks a writer's Parquet sizes from the average row width of its first
+/// batches. Wide rows (long OCR text) get smaller row groups, so row group
+/// statistics prune more finely; narrow rows get larger ones, which compress
+/// better. Sizes stay fixed once sampled so a session's files are uniform.
+#[derive(Debug, Clone)]
+pub struct RowGroupAutotuner {
+    config: ParquetTuningConfig,
+    defaults: ParquetSizing,
+    sampled_batches: usize,
+    sampled_rows: usize,
+    sampled_bytes: usize,
+}
+
+impl RowGroupAutotuner {
+    /// `defaults` apply until a batch is measured, or always without autotuning
+    pub fn new(config: ParquetTuningConfig, defaults: ParquetSizing) -> Self {
+        Self { config, defaults, sampled_batches: 0, sampled_rows: 0, sampled_bytes: 0 }
+    }
+
+    /// Measure a batch about to be written, while still sampling
+    pub fn observe(&mut self, batch: &RecordBatch) {
+        if !self.config.autotune || self.sampled_batches >= self.config.sample_batches || batch.num_rows() == 0 {
+            return;
+        }
+        self.sampled_batches += 1;
+        self.sampled_rows += batch.num_rows();
+        self.sampled_bytes += batch.get_array_memory_size();
+
+        if self.sampled_batches == self.config.sample_batches {
+            let sizing = self.sizing();
+            info!(
+                "Parquet sizes settled at {} rows per row group, {} per write batch ({} bytes per row)",
+                sizing.max_row_group_size,
+                sizing.write_batch_size,
+                self.row_width().unwrap_or_default()
+            );
+        }
+    }
+
+    /// Average in-memory bytes per sampled row
+    pub fn row_width(&self) -> Option<usize> {
+        (self.sampled_rows > 0).then(|| (self.sampled_bytes / self.sampled_rows).max(1))
+    }
+
+    pub fn sizing(&self) -> ParquetSizing {
+        match self.row_width() {
+            Some(width) if self.config.autotune => ParquetSizing {
+                max_row_group_size: (self.config.target_row_group_bytes / width)
+                    .clamp(MIN_ROW_GROUP_ROWS, MAX_ROW_GROUP_ROWS),
+                write_batch_size: (self.config.target_write_batch_bytes / width)
+                    .clamp(MIN_WRITE_BATCH_ROWS, MAX_WRITE_BATCH_ROWS),
+            },
+            _ => self.defaults,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(text_len: usize) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("text", DataType::Utf8, false)]));
+        let texts = vec!["x".repeat(text_len); 500];
+        RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(texts))]).unwrap()
+    }
+
+    #[test]
+    fn test_wide_rows_get_smaller_row_groups() {
+        let defaults = ParquetSizing { max_row_group_size: 50_000, write_batch_size: 2048 };
+        let config = ParquetTuningConfig { autotune: true, sample_batches: 2, ..ParquetTuningConfig::default() };
+
+        let mut narrow = RowGroupAutotuner::new(config.clone(), defaults);
+        assert_eq!(narrow.sizing(), defaults);
+        narrow.observe(&batch(8));
+        narrow.observe(&batch(8));
+        let mut wide = RowGroupAutotuner::new(config.clone(), defaults);
+        wide.observe(&batch(4096));
+        wide.observe(&batch(4096));
+        assert!(wide.sizing().max_row_group_size < narrow.sizing().max_row_group_size);
+        assert!(wide.sizing().max_row_group_size < defaults.max_row_group_size);
+        assert!(wide.sizing().write_batch_size < defaults.write_batch_size);
+
+        // Sizes are settled after the sampled batches
+        let settled = wide.sizing();
+        wide.observe(&batch(8));
+        assert_eq!(wide.sizing(), settled);
+
+        let mut fixed = RowGroupAutotuner::new(ParquetTuningConfig { autotune: false, ..config }, defaults);
+        fixed.observe(&batch(4096));
+        assert_eq!(fixed.sizing(), defaults);
+    }
+}
@@ -1,7 +1,12 @@
 use crate::error::{IndexerError, Result};
+use crate::layout_compat;
 use crate::metadata_collector::FrameMetadata;
+use crate::ocr_density::OcrDensity;
+use crate::screen_classifier::ScreenType;
+use crate::visual_features::{DominantColor, VisualFeatures};
 use arrow::array::{
-    Array, Int32Array, Int64Array, Float32Array, StringArray, UInt32Array
+    Array, Int32Array, Int64Array, Float32Array, Float32Builder, ListArray, ListBuilder, StringArray,
+    StringBuilder, UInt32Array
 };
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
@@ -39,6 +44,15 @@ impl ParquetWriter {
             Field::new("win_title", DataType::Utf8, false),
             Field::new("width", DataType::UInt32, false),
             Field::new("height", DataType::UInt32, false),
+            Field::new("duplicate_of", DataType::Utf8, true),
+            Field::new("ocr_box_count", DataType::UInt32, true),
+            Field::new("ocr_text_coverage", DataType::Float32, true),
+            Field::new("ocr_mean_confidence", DataType::Float32, true),
+            Field::new("dominant_colors", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), true),
+            Field::new("dominant_color_shares", DataType::List(Arc::new(Field::new("item", DataType::Float32, true))), true),
+            Field::new("edge_density", DataType::Float32, true),
+            Field::new("ui_text_density", DataType::Float32, true),
+            Field::new("screen_type", DataType::Utf8, true),
         ]));
         
         Ok(Self {
@@ -88,7 +102,7 @@ impl ParquetWriter {
         Ok(())
     }
     
-    fn create_record_batch(&self, metadata: &[FrameMetadata]) -> Result<RecordBatch> {
+    pub(crate) fn create_record_batch(&self, metadata: &[FrameMetadata]) -> Result<RecordBatch> {
         let len = metadata.len();
         
         // Create arrays for each column
@@ -132,6 +146,51 @@ impl ParquetWriter {
             metadata.iter().map(|m| m.height).collect::<Vec<_>>()
         );
         
+        let duplicate_of_array = StringArray::from(
+            metadata.iter().map(|m| m.duplicate_of.as_deref()).collect::<Vec<_>>()
+        );
+        
+        // Null until the frame's OCR is known
+        let ocr_box_count_array = UInt32Array::from(
+            metadata.iter().map(|m| m.ocr_density.map(|d| d.box_count)).collect::<Vec<_>>()
+        );
+        
+        let ocr_text_coverage_array = Float32Array::from(
+            metadata.iter().map(|m| m.ocr_density.map(|d| d.text_coverage)).collect::<Vec<_>>()
+        );
+        
+        let ocr_mean_confidence_array = Float32Array::from(
+            metadata.iter().map(|m| m.ocr_density.map(|d| d.mean_confidence)).collect::<Vec<_>>()
+        );
+        
+        // Palette as `#rrggbb` colors with their percentage of the frame, most common first
+        let mut dominant_colors_builder = ListBuilder::new(StringBuilder::new());
+        let mut dominant_color_shares_builder = ListBuilder::new(Float32Builder::new());
+        for visual in metadata.iter().map(|m| m.visual.as_ref()) {
+            if let Some(visual) = visual {
+                for color in &visual.dominant_colors {
+                    dominant_colors_builder.values().append_value(color.hex());
+                    dominant_color_shares_builder.values().append_value(color.share);
+                }
+            }
+            dominant_colors_builder.append(visual.is_some());
+            dominant_color_shares_builder.append(visual.is_some());
+        }
+        let dominant_colors_array = dominant_colors_builder.finish();
+        let dominant_color_shares_array = dominant_color_shares_builder.finish();
+        
+        let edge_density_array = Float32Array::from(
+            metadata.iter().map(|m| m.visual.as_ref().map(|v| v.edge_density)).collect::<Vec<_>>()
+        );
+        
+        let ui_text_density_array = Float32Array::from(
+            metadata.iter().map(|m| m.visual.as_ref().map(|v| v.ui_text_density)).collect::<Vec<_>>()
+        );
+        
+        let screen_type_array = StringArray::from(
+            metadata.iter().map(|m| m.screen_type.map(|t| t.as_str())).collect::<Vec<_>>()
+        );
+        
         // Create record batch
         let record_batch = RecordBatch::try_new(
             self.schema.clone(),
@@ -146,6 +205,15 @@ impl ParquetWriter {
                 Arc::new(win_title_array),
                 Arc::new(width_array),
                 Arc::new(height_array),
+                Arc::new(duplicate_of_array),
+                Arc::new(ocr_box_count_array),
+                Arc::new(ocr_text_coverage_array),
+                Arc::new(ocr_mean_confidence_array),
+                Arc::new(dominant_colors_array),
+                Arc::new(dominant_color_shares_array),
+                Arc::new(edge_density_array),
+                Arc::new(ui_text_density_array),
+                Arc::new(screen_type_array),
             ],
         )?;
         
@@ -206,45 +274,117 @@ impl ParquetWriter {
         
         let file = File::open(file_path)?;
         let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
-        let mut reader = builder.build()?;
+        let reader = builder.build()?;
         
         let mut metadata_records = Vec::new();
         
-        while let Some(batch_result) = reader.next() {
-            let batch = batch_result?;
-            
-            // Extract data from batch
-            let ts_ns = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
-            let monitor_id = batch.column(1).as_any().downcast_ref::<Int32Array>().unwrap();
-            let segment_id = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
-            let path = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
-            let phash16 = batch.column(4).as_any().downcast_ref::<Int64Array>().unwrap();
-            let entropy = batch.column(5).as_any().downcast_ref::<Float32Array>().unwrap();
-            let app_name = batch.column(6).as_any().downcast_ref::<StringArray>().unwrap();
-            let win_title = batch.column(7).as_any().downcast_ref::<StringArray>().unwrap();
-            let width = batch.column(8).as_any().downcast_ref::<UInt32Array>().unwrap();
-            let height = batch.column(9).as_any().downcast_ref::<UInt32Array>().unwrap();
-            
-            for i in 0..batch.num_rows() {
-                metadata_records.push(FrameMetadata {
-                    ts_ns: ts_ns.value(i),
-                    monitor_id: monitor_id.value(i),
-                    segment_id: segment_id.value(i).to_string(),
-                    path: path.value(i).to_string(),
-                    phash16: phash16.value(i),
-                    entropy: entropy.value(i),
-                    app_name: app_name.value(i).to_string(),
-                    win_title: win_title.value(i).to_string(),
-                    width: width.value(i),
-                    height: height.value(i),
-                });
-            }
+        for batch in reader {
+            metadata_records.extend(frames_from_batch(&batch?)?);
         }
         
         Ok(metadata_records)
     }
 }
 
+/// Frame metadata of a batch read back from a frames file; a batch with
+/// unexpected columns is an error rather than a panic
+pub fn frames_from_batch(batch: &RecordBatch) -> Result<Vec<FrameMetadata>> {
+    let mut metadata_records = Vec::with_capacity(batch.num_rows());
+    
+    // Extract data from batch
+    let ts_ns = layout_compat::column::<Int64Array>(batch, 0)?;
+    let monitor_id = layout_compat::column::<Int32Array>(batch, 1)?;
+    let segment_id = layout_compat::column::<StringArray>(batch, 2)?;
+    let path = layout_compat::column::<StringArray>(batch, 3)?;
+    let phash16 = layout_compat::column::<Int64Array>(batch, 4)?;
+    let entropy = layout_compat::column::<Float32Array>(batch, 5)?;
+    let app_name = layout_compat::column::<StringArray>(batch, 6)?;
+    let win_title = layout_compat::column::<StringArray>(batch, 7)?;
+    let width = layout_compat::column::<UInt32Array>(batch, 8)?;
+    let height = layout_compat::column::<UInt32Array>(batch, 9)?;
+    // Absent in files written before deduplication
+    let duplicate_of = batch.column_by_name("duplicate_of")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+    // Absent in files written before OCR density metrics
+    let ocr_box_count = batch.column_by_name("ocr_box_count")
+        .and_then(|c| c.as_any().downcast_ref::<UInt32Array>());
+    let ocr_text_coverage = batch.column_by_name("ocr_text_coverage")
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+    let ocr_mean_confidence = batch.column_by_name("ocr_mean_confidence")
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+    // Absent in files written before visual features
+    let dominant_colors = batch.column_by_name("dominant_colors")
+        .and_then(|c| c.as_any().downcast_ref::<ListArray>());
+    let dominant_color_shares = batch.column_by_name("dominant_color_shares")
+        .and_then(|c| c.as_any().downcast_ref::<ListArray>());
+    let edge_density = batch.column_by_name("edge_density")
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+    let ui_text_density = batch.column_by_name("ui_text_density")
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+    // Absent in files written before screen classification
+    let screen_type = batch.column_by_name("screen_type")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+    
+    for i in 0..batch.num_rows() {
+        let ocr_density = match (ocr_box_count, ocr_text_coverage, ocr_mean_confidence) {
+            (Some(boxes), Some(coverage), Some(confidence)) if !boxes.is_null(i) => Some(OcrDensity {
+                box_count: boxes.value(i),
+                text_coverage: coverage.value(i),
+                mean_confidence: confidence.value(i),
+            }),
+            _ => None,
+        };
+        
+        let visual = match (dominant_colors, dominant_color_shares, edge_density, ui_text_density) {
+            (Some(colors), Some(shares), Some(edges), Some(text)) if !edges.is_null(i) => {
+                let colors = colors.value(i);
+                let shares = shares.value(i);
+                let dominant_colors = match (
+                    colors.as_any().downcast_ref::<StringArray>(),
+                    shares.as_any().downcast_ref::<Float32Array>(),
+                ) {
+                    (Some(colors), Some(shares)) => colors
+                        .iter()
+                        .zip(shares.iter())
+                        .filter_map(|(color, share)| DominantColor::from_hex(color?, share?))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                Some(VisualFeatures {
+                    dominant_colors,
+                    edge_density: edges.value(i),
+                    ui_text_density: text.value(i),
+                })
+            }
+            _ => None,
+        };
+
+        metadata_records.push(FrameMetadata {
+            ts_ns: ts_ns.value(i),
+            monitor_id: monitor_id.value(i),
+            segment_id: segment_id.value(i).to_string(),
+            path: path.value(i).to_string(),
+            phash16: phash16.value(i),
+            entropy: entropy.value(i),
+            app_name: app_name.value(i).to_string(),
+            win_title: win_title.value(i).to_string(),
+            width: width.value(i),
+            height: height.value(i),
+            duplicate_of: duplicate_of
+                .filter(|c| !c.is_null(i))
+                .map(|c| c.value(i).to_string()),
+            ocr_density,
+            visual,
+            screen_type: screen_type
+                .filter(|c| !c.is_null(i))
+                .and_then(|c| ScreenType::parse(c.value(i))),
+        });
+    }
+    
+    Ok(metadata_records)
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,6 +404,10 @@ mod tests {
                 win_title: "Test Window".to_string(),
                 width: 1920,
                 height: 1080,
+                duplicate_of: None,
+                ocr_density: None,
+                visual: None,
+                screen_type: None,
             },
             FrameMetadata {
                 ts_ns: 2000000000,
@@ -276,6 +420,17 @@ mod tests {
                 win_title: "Another Window".to_string(),
                 width: 2560,
                 height: 1440,
+                duplicate_of: None,
+                ocr_density: Some(OcrDensity { box_count: 12, text_coverage: 18.5, mean_confidence: 0.93 }),
+                visual: Some(VisualFeatures {
+                    dominant_colors: vec![
+                        DominantColor { rgb: [30, 30, 30], share: 71.5 },
+                        DominantColor { rgb: [200, 200, 200], share: 20.0 },
+                    ],
+                    edge_density: 24.5,
+                    ui_text_density: 62.0,
+                }),
+                screen_type: Some(ScreenType::Terminal),
             },
         ]
     }
@@ -321,6 +476,9 @@ mod tests {
             assert_eq!(original.win_title, read.win_title);
             assert_eq!(original.width, read.width);
             assert_eq!(original.height, read.height);
+            assert_eq!(original.ocr_density, read.ocr_density);
+            assert_eq!(original.visual, read.visual);
+            assert_eq!(original.screen_type, read.screen_type);
         }
     }
     
@@ -353,7 +511,7 @@ mod tests {
         let writer = ParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
         
         let schema = writer.get_schema();
-        assert_eq!(schema.fields().len(), 10);
+        assert_eq!(schema.fields().len(), 14);
         
         // Check field names and types
         assert_eq!(schema.field(0).name(), "ts_ns");
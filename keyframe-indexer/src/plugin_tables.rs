@@ -0,0 +1,749 @@
+use crate::error::{IndexerError, Result};
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, BooleanBuilder, PrimitiveBuilder, StringBuilder, TimestampNanosecondArray,
+};
+use arrow::compute::{concat_batches, filter_record_batch};
+use arrow::datatypes::{
+    ArrowPrimitiveType, DataType, Field, Float32Type, Float64Type, Int32Type, Int64Type, Schema, SchemaRef,
+    TimeUnit, TimestampNanosecondType,
+};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Tables owned by the indexer itself; plugins may not shadow them
+const RESERVED_TABLES: &[&str] = &["events", "ocr_data", "frames", "query_audit"];
+/// Hive-style partition column of daily-partitioned tables
+const PARTITION_COLUMN: &str = "date";
+/// Schema manifest kept next to each table's files
+const SCHEMA_MANIFEST: &str = "_schema.json";
+
+/// Where plugin tables are stored and which declarations are loaded at startup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginTablesConfig {
+    /// Root of the plugin tables; defaults to `<output_dir>/plugin_tables`
+    pub dir: Option<String>,
+    /// JSON table manifests shipped with script and WASM plugins
+    pub manifests: Vec<PluginManifest>,
+}
+
+/// A plugin's JSON table manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub plugin: String,
+    pub path: String,
+}
+
+/// How a plugin table's files are laid out on disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TablePartitioning {
+    /// All files in the table directory
+    #[default]
+    None,
+    /// One `date=YYYY-MM-DD` directory per UTC day, queryable as a `date` column
+    Daily,
+}
+
+/// Column types available to plugins that declare tables as JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Utf8,
+    Boolean,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    /// Nanoseconds since the epoch; JSON rows may also give an RFC 3339 string
+    TimestampNs,
+}
+
+const COLUMN_TYPES: [ColumnType; 7] = [
+    ColumnType::Utf8,
+    ColumnType::Boolean,
+    ColumnType::Int32,
+    ColumnType::Int64,
+    ColumnType::Float32,
+    ColumnType::Float64,
+    ColumnType::TimestampNs,
+];
+
+impl ColumnType {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Int32 => DataType::Int32,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float32 => DataType::Float32,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::TimestampNs => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        }
+    }
+}
+
+/// Column of a JSON table declaration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    /// Rows without a value for a required column are rejected
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Table declaration in JSON form, for script and WASM plugins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSpec {
+    pub name: String,
+    pub columns: Vec<ColumnSpec>,
+    #[serde(default)]
+    pub partitioning: TablePartitioning,
+    #[serde(default)]
+    pub timestamp_column: Option<String>,
+}
+
+impl TableSpec {
+    pub fn to_definition(&self) -> TableDefinition {
+        let fields: Vec<Field> = self.columns
+            .iter()
+            .map(|c| Field::new(&c.name, c.column_type.data_type(), !c.required))
+            .collect();
+
+        TableDefinition {
+            name: self.name.clone(),
+            schema: Arc::new(Schema::new(fields)),
+            partitioning: self.partitioning,
+            timestamp_column: self.timestamp_column.clone(),
+        }
+    }
+}
+
+/// A plugin-owned output table: its Arrow schema and storage layout
+#[derive(Debug, Clone)]
+pub struct TableDefinition {
+    pub name: String,
+    pub schema: SchemaRef,
+    pub partitioning: TablePartitioning,
+    /// Nanosecond timestamp column whose UTC day picks the partition;
+    /// without it rows are partitioned by the day they are flushed
+    pub timestamp_column: Option<String>,
+}
+
+impl TableDefinition {
+    pub fn new(name: &str, schema: SchemaRef) -> Self {
+        Self {
+            name: name.to_string(),
+            schema,
+            partitioning: TablePartitioning::None,
+            timestamp_column: None,
+        }
+    }
+
+    /// Partition by day, optionally taking the day from `timestamp_column`
+    pub fn partitioned_daily(mut self, timestamp_column: Option<&str>) -> Self {
+        self.partitioning = TablePartitioning::Daily;
+        self.timestamp_column = timestamp_column.map(str::to_string);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_identifier("table", &self.name)?;
+        if RESERVED_TABLES.contains(&self.name.as_str()) {
+            return Err(IndexerError::Config(format!("Table name {} is reserved", self.name)));
+        }
+        if self.schema.fields().is_empty() {
+            return Err(IndexerError::Config(format!("Table {} declares no columns", self.name)));
+        }
+        if self.partitioning == TablePartitioning::Daily && self.schema.field_with_name(PARTITION_COLUMN).is_ok() {
+            return Err(IndexerError::Config(format!(
+                "Table {} is partitioned daily, so it cannot declare a {} column",
+                self.name, PARTITION_COLUMN
+            )));
+        }
+        if let Some(column) = &self.timestamp_column {
+            let field = self.schema.field_with_name(column).map_err(|_| {
+                IndexerError::Config(format!("Timestamp column {} is not in table {}", column, self.name))
+            })?;
+            if field.data_type() != &ColumnType::TimestampNs.data_type() {
+                return Err(IndexerError::Config(format!(
+                    "Timestamp column {} of table {} must be a nanosecond timestamp",
+                    column, self.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Detector plugin that stores its results in tables it declares itself
+pub trait TablePlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn tables(&self) -> Vec<TableDefinition>;
+}
+
+/// Storage statistics of a plugin table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginTableStatistics {
+    pub table: String,
+    pub plugin: String,
+    pub row_count: u64,
+    pub file_count: usize,
+    pub partitions: Vec<String>,
+    pub total_size_bytes: u64,
+    /// Rows buffered but not yet flushed
+    pub pending_rows: usize,
+}
+
+/// Schema as recorded in a table's manifest, to catch incompatible re-declarations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredTable {
+    plugin: String,
+    partitioning: TablePartitioning,
+    timestamp_column: Option<String>,
+    columns: Vec<(String, String, bool)>,
+}
+
+impl StoredTable {
+    /// Definition of a stored table, for reading it without its plugin
+    fn to_definition(&self, name: &str) -> Result<TableDefinition> {
+        let fields = self.columns
+            .iter()
+            .map(|(column, data_type, nullable)| {
+                COLUMN_TYPES
+                    .iter()
+                    .map(ColumnType::data_type)
+                    .find(|t| t.to_string() == *data_type)
+                    .map(|t| Field::new(column, t, *nullable))
+                    .ok_or_else(|| IndexerError::Config(format!("Unsupported type {} of column {}.{}", data_type, name, column)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TableDefinition {
+            name: name.to_string(),
+            schema: Arc::new(Schema::new(fields)),
+            partitioning: self.partitioning,
+            timestamp_column: self.timestamp_column.clone(),
+        })
+    }
+
+    fn new(plugin: &str, definition: &TableDefinition) -> Self {
+        Self {
+            plugin: plugin.to_string(),
+            partitioning: definition.partitioning,
+            timestamp_column: definition.timestamp_column.clone(),
+            columns: definition.schema
+                .fields()
+                .iter()
+                .map(|f| (f.name().clone(), f.data_type().to_string(), f.is_nullable()))
+                .collect(),
+        }
+    }
+}
+
+struct RegisteredTable {
+    plugin: String,
+    definition: TableDefinition,
+    dir: PathBuf,
+    pending: Vec<RecordBatch>,
+    pending_rows: usize,
+}
+
+/// Provisions Parquet storage for tables declared by detector plugins.
+///
+/// Each table lives under `<root>/<plugin>/<table>/`; the registry buffers and
+/// writes its rows, keeps per-page column statistics, and registers every table
+/// with a DataFusion context under its declared name.
+pub struct PluginTableRegistry {
+    root: PathBuf,
+    batch_size: usize,
+    compression: Compression,
+    tables: BTreeMap<String, RegisteredTable>,
+}
+
+impl PluginTableRegistry {
+    pub fn new(root: &str) -> Result<Self> {
+        let root = PathBuf::from(root);
+        std::fs::create_dir_all(&root)?;
+
+        Ok(Self {
+            root,
+            batch_size: 1000,
+            compression: Compression::SNAPPY,
+            tables: BTreeMap::new(),
+        })
+    }
+
+    /// Open `root` with every table already stored under it, so they can be
+    /// queried without the plugins that declared them
+    pub fn open(root: &str) -> Result<Self> {
+        let mut registry = Self::new(root)?;
+        for plugin_dir in read_dirs(&registry.root)? {
+            let Some(plugin) = plugin_dir.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+                continue;
+            };
+            for table_dir in read_dirs(&plugin_dir)? {
+                let manifest_path = table_dir.join(SCHEMA_MANIFEST);
+                let Some(name) = table_dir.file_name().and_then(|n| n.to_str()).filter(|_| manifest_path.exists()) else {
+                    continue;
+                };
+                let stored: StoredTable = serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+                registry.register_table(&plugin, stored.to_definition(name)?)?;
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Register every table a plugin declares
+    pub fn register_plugin(&mut self, plugin: &dyn TablePlugin) -> Result<()> {
+        for definition in plugin.tables() {
+            self.register_table(plugin.name(), definition)?;
+        }
+        Ok(())
+    }
+
+    /// Register tables from a JSON manifest (an array of table specs) shipped with a script or WASM plugin
+    pub fn register_manifest(&mut self, plugin: &str, manifest_path: &Path) -> Result<()> {
+        let specs: Vec<TableSpec> = serde_json::from_str(&std::fs::read_to_string(manifest_path)?)?;
+        for spec in &specs {
+            self.register_table(plugin, spec.to_definition())?;
+        }
+        Ok(())
+    }
+
+    /// Provision storage for a table; re-registering the same schema is a no-op
+    pub fn register_table(&mut self, plugin: &str, definition: TableDefinition) -> Result<()> {
+        validate_identifier("plugin", plugin)?;
+        definition.validate()?;
+
+        let stored = StoredTable::new(plugin, &definition);
+        if let Some(existing) = self.tables.get(&definition.name) {
+            if StoredTable::new(&existing.plugin, &existing.definition) == stored {
+                return Ok(());
+            }
+            return Err(IndexerError::Config(format!(
+                "Table {} is already registered by plugin {}",
+                definition.name, existing.plugin
+            )));
+        }
+
+        let dir = self.root.join(plugin).join(&definition.name);
+        std::fs::create_dir_all(&dir)?;
+
+        // Files already on disk must stay readable with the declared schema
+        let manifest_path = dir.join(SCHEMA_MANIFEST);
+        if manifest_path.exists() {
+            let previous: StoredTable = serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+            if previous != stored {
+                return Err(IndexerError::Config(format!(
+                    "Table {} was stored with a different schema; migrate or remove {} first",
+                    definition.name,
+                    dir.display()
+                )));
+            }
+        } else {
+            std::fs::write(&manifest_path, serde_json::to_string_pretty(&stored)?)?;
+        }
+
+        info!("Registered plugin table {} for plugin {}", definition.name, plugin);
+        self.tables.insert(definition.name.clone(), RegisteredTable {
+            plugin: plugin.to_string(),
+            definition,
+            dir,
+            pending: Vec::new(),
+            pending_rows: 0,
+        });
+        Ok(())
+    }
+
+    pub fn table_names(&self) -> Vec<&str> {
+        self.tables.keys().map(String::as_str).collect()
+    }
+
+    pub fn schema(&self, table: &str) -> Option<SchemaRef> {
+        self.tables.get(table).map(|t| t.definition.schema.clone())
+    }
+
+    /// Buffer a record batch for `table`; it must have the declared schema
+    pub async fn write_batch(&mut self, table: &str, batch: RecordBatch) -> Result<()> {
+        let batch_size = self.batch_size;
+        let registered = self.table_mut(table)?;
+        if batch.schema().fields() != registered.definition.schema.fields() {
+            return Err(IndexerError::Config(format!("Batch schema does not match table {}", table)));
+        }
+
+        registered.pending_rows += batch.num_rows();
+        registered.pending.push(batch);
+        if registered.pending_rows >= batch_size {
+            self.flush_table(table).await?;
+        }
+        Ok(())
+    }
+
+    /// Buffer JSON objects as rows of `table`, keyed by column name
+    pub async fn write_rows(&mut self, table: &str, rows: &[Value]) -> Result<()> {
+        let schema = self.table_mut(table)?.definition.schema.clone();
+        let batch = rows_to_batch(&schema, rows)?;
+        self.write_batch(table, batch).await
+    }
+
+    /// Write all buffered rows of every table
+    pub async fn flush(&mut self) -> Result<()> {
+        let names: Vec<String> = self.tables.keys().cloned().collect();
+        for name in names {
+            self.flush_table(&name).await?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered rows of one table, one file per partition
+    pub async fn flush_table(&mut self, table: &str) -> Result<()> {
+        let compression = self.compression;
+        let registered = self.table_mut(table)?;
+        if registered.pending.is_empty() {
+            return Ok(());
+        }
+
+        let batch = concat_batches(&registered.definition.schema, &registered.pending)?;
+        let stamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let suffix = &uuid::Uuid::new_v4().simple().to_string()[..8];
+
+        for (partition, rows) in partition_batch(&registered.definition, &batch)? {
+            let dir = match &partition {
+                Some(day) => registered.dir.join(format!("{}={}", PARTITION_COLUMN, day)),
+                None => registered.dir.clone(),
+            };
+            std::fs::create_dir_all(&dir)?;
+            let file_path = dir.join(format!("{}_{}_{}.parquet", table, stamp, suffix));
+            write_parquet(&file_path, &registered.definition.schema, &rows, compression)?;
+            debug!("Wrote {} rows to {}", rows.num_rows(), file_path.display());
+        }
+
+        info!("Flushed {} rows of plugin table {}", registered.pending_rows, table);
+        registered.pending.clear();
+        registered.pending_rows = 0;
+        Ok(())
+    }
+
+    /// Register every plugin table with `ctx` under its declared name
+    pub async fn register_with(&self, ctx: &SessionContext) -> Result<()> {
+        for (name, table) in &self.tables {
+            let path = format!("{}/", table.dir.display());
+            let mut options = ParquetReadOptions::default().schema(table.definition.schema.as_ref());
+            if table.definition.partitioning == TablePartitioning::Daily {
+                options = options.table_partition_cols(vec![(PARTITION_COLUMN.to_string(), DataType::Utf8)]);
+            }
+            ctx.register_parquet(name, &path, options).await?;
+        }
+        Ok(())
+    }
+
+    /// Run SQL over the flushed rows of all plugin tables
+    pub async fn query(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let ctx = SessionContext::new();
+        self.register_with(&ctx).await?;
+        let df = ctx.sql(sql).await?;
+        Ok(df.collect().await?)
+    }
+
+    /// Row, file and partition counts read from the Parquet footers
+    pub fn statistics(&self, table: &str) -> Result<PluginTableStatistics> {
+        let registered = self.tables
+            .get(table)
+            .ok_or_else(|| IndexerError::Config(format!("Unknown plugin table {}", table)))?;
+
+        let mut statistics = PluginTableStatistics {
+            table: table.to_string(),
+            plugin: registered.plugin.clone(),
+            pending_rows: registered.pending_rows,
+            ..PluginTableStatistics::default()
+        };
+        let mut partitions = BTreeSet::new();
+
+        for file_path in parquet_files(&registered.dir)? {
+            let reader = SerializedFileReader::new(File::open(&file_path)?)?;
+            statistics.row_count += reader.metadata().file_metadata().num_rows() as u64;
+            statistics.file_count += 1;
+            statistics.total_size_bytes += std::fs::metadata(&file_path)?.len();
+
+            let partition = file_path.parent()
+                .and_then(|dir| dir.file_name())
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&format!("{}=", PARTITION_COLUMN)).map(str::to_string));
+            partitions.extend(partition);
+        }
+
+        statistics.partitions = partitions.into_iter().collect();
+        Ok(statistics)
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    fn table_mut(&mut self, table: &str) -> Result<&mut RegisteredTable> {
+        self.tables
+            .get_mut(table)
+            .ok_or_else(|| IndexerError::Config(format!("Unknown plugin table {}", table)))
+    }
+}
+
+/// Plugin and table names become directory and SQL identifiers
+fn validate_identifier(kind: &str, name: &str) -> Result<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(IndexerError::Config(format!(
+            "Invalid {} name {:?}: use lowercase letters, digits and underscores",
+            kind, name
+        )))
+    }
+}
+
+/// Split a batch by the UTC day of each row
+fn partition_batch(definition: &TableDefinition, batch: &RecordBatch) -> Result<Vec<(Option<String>, RecordBatch)>> {
+    if definition.partitioning == TablePartitioning::None {
+        return Ok(vec![(None, batch.clone())]);
+    }
+
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    let timestamps = definition.timestamp_column
+        .as_deref()
+        .and_then(|column| batch.column_by_name(column))
+        .and_then(|column| column.as_any().downcast_ref::<TimestampNanosecondArray>());
+    let days: Vec<String> = match timestamps {
+        Some(timestamps) => (0..batch.num_rows())
+            .map(|i| {
+                if timestamps.is_null(i) {
+                    today.clone()
+                } else {
+                    DateTime::from_timestamp_nanos(timestamps.value(i)).format("%Y-%m-%d").to_string()
+                }
+            })
+            .collect(),
+        None => vec![today; batch.num_rows()],
+    };
+
+    let mut partitions = Vec::new();
+    for day in days.iter().collect::<BTreeSet<_>>() {
+        let mask = BooleanArray::from(days.iter().map(|d| d == day).collect::<Vec<_>>());
+        partitions.push((Some(day.clone()), filter_record_batch(batch, &mask)?));
+    }
+    Ok(partitions)
+}
+
+fn write_parquet(file_path: &Path, schema: &SchemaRef, batch: &RecordBatch, compression: Compression) -> Result<()> {
+    // Page statistics let queries on plugin columns skip pages without a schema-specific index
+    let props = WriterProperties::builder()
+        .set_compression(compression)
+        .set_statistics_enabled(EnabledStatistics::Page)
+        .set_max_row_group_size(10000)
+        .set_created_by("AlwaysOnAI Plugin Tables".to_string())
+        .build();
+
+    let mut writer = ArrowWriter::try_new(File::create(file_path)?, schema.clone(), Some(props))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Subdirectories of `dir`, sorted; none if it does not exist
+fn read_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Parquet files under `dir`, including partition subdirectories
+fn parquet_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().and_then(|e| e.to_str()) == Some("parquet") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Convert JSON objects into a record batch with `schema`; missing keys and nulls become nulls
+fn rows_to_batch(schema: &SchemaRef, rows: &[Value]) -> Result<RecordBatch> {
+    let columns = schema.fields()
+        .iter()
+        .map(|field| json_column(field, rows))
+        .collect::<Result<Vec<ArrayRef>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn json_column(field: &Field, rows: &[Value]) -> Result<ArrayRef> {
+    match field.data_type() {
+        DataType::Utf8 => {
+            let mut builder = StringBuilder::with_capacity(rows.len(), rows.len() * 16);
+            for value in json_values(field, rows) {
+                match value {
+                    Some(Value::String(s)) => builder.append_value(s),
+                    Some(other) => builder.append_value(other.to_string()),
+                    None => builder.append_null(),
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+        DataType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(rows.len());
+            for value in json_values(field, rows) {
+                match value {
+                    Some(v) => builder.append_value(v.as_bool().ok_or_else(|| type_mismatch(field, v))?),
+                    None => builder.append_null(),
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+        DataType::Int32 => primitive_column::<Int32Type>(field, rows, |v| v.as_i64().and_then(|n| i32::try_from(n).ok())),
+        DataType::Int64 => primitive_column::<Int64Type>(field, rows, Value::as_i64),
+        DataType::Float32 => primitive_column::<Float32Type>(field, rows, |v| v.as_f64().map(|n| n as f32)),
+        DataType::Float64 => primitive_column::<Float64Type>(field, rows, Value::as_f64),
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => primitive_column::<TimestampNanosecondType>(field, rows, |v| {
+            v.as_i64().or_else(|| {
+                v.as_str()
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                    .and_then(|t| t.timestamp_nanos_opt())
+            })
+        }),
+        other => Err(IndexerError::Config(format!(
+            "Column {} has type {}, which JSON rows cannot fill",
+            field.name(),
+            other
+        ))),
+    }
+}
+
+fn json_values<'a>(field: &'a Field, rows: &'a [Value]) -> impl Iterator<Item = Option<&'a Value>> + 'a {
+    rows.iter().map(move |row| row.get(field.name()).filter(|v| !v.is_null()))
+}
+
+fn primitive_column<T: ArrowPrimitiveType>(
+    field: &Field,
+    rows: &[Value],
+    parse: impl Fn(&Value) -> Option<T::Native>,
+) -> Result<ArrayRef> {
+    let mut builder = PrimitiveBuilder::<T>::with_capacity(rows.len());
+    for value in json_values(field, rows) {
+        match value {
+            Some(v) => builder.append_value(parse(v).ok_or_else(|| type_mismatch(field, v))?),
+            None => builder.append_null(),
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+fn type_mismatch(field: &Field, value: &Value) -> IndexerError {
+    IndexerError::Config(format!("Column {} expects {}, got {}", field.name(), field.data_type(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_compat;
+    use arrow::array::Int64Array;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn latency_spec() -> TableSpec {
+        serde_json::from_value(json!({
+            "name": "app_latency",
+            "columns": [
+                { "name": "ts", "type": "timestamp_ns", "required": true },
+                { "name": "app", "type": "utf8", "required": true },
+                { "name": "latency_ms", "type": "float64" }
+            ],
+            "partitioning": "daily",
+            "timestamp_column": "ts"
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_plugin_table_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut registry = PluginTableRegistry::new(temp_dir.path().to_str().unwrap()).unwrap();
+        registry.register_table("perf_probe", latency_spec().to_definition()).unwrap();
+        // Same declaration again is fine; a clashing one or a built-in name is not
+        registry.register_table("perf_probe", latency_spec().to_definition()).unwrap();
+        assert!(registry.register_table("other", latency_spec().to_definition()).is_err());
+        let mut reserved = latency_spec();
+        reserved.name = "events".to_string();
+        assert!(registry.register_table("perf_probe", reserved.to_definition()).is_err());
+
+        registry.write_rows("app_latency", &[
+            json!({ "ts": "2026-10-01T09:00:00Z", "app": "Safari", "latency_ms": 120.5 }),
+            json!({ "ts": "2026-10-01T17:30:00Z", "app": "Mail" }),
+            json!({ "ts": "2026-10-02T08:15:00Z", "app": "Safari", "latency_ms": 80.0 }),
+        ]).await.unwrap();
+        assert!(registry.write_rows("app_latency", &[json!({ "app": "Xcode" })]).await.is_err());
+        registry.flush().await.unwrap();
+
+        let statistics = registry.statistics("app_latency").unwrap();
+        assert_eq!(statistics.row_count, 3);
+        assert_eq!(statistics.partitions, vec!["2026-10-01".to_string(), "2026-10-02".to_string()]);
+        assert_eq!(statistics.pending_rows, 0);
+
+        let batches = registry
+            .query("SELECT COUNT(*) AS n FROM app_latency WHERE date = '2026-10-01' AND app = 'Safari'")
+            .await
+            .unwrap();
+        let counts = layout_compat::column::<Int64Array>(&batches[0], 0).unwrap();
+        assert_eq!(counts.value(0), 1);
+
+        // A later run must declare the same schema for the stored files
+        let mut reopened = PluginTableRegistry::new(temp_dir.path().to_str().unwrap()).unwrap();
+        let mut changed = latency_spec();
+        changed.columns.pop();
+        assert!(reopened.register_table("perf_probe", changed.to_definition()).is_err());
+
+        // Stored tables are found again without their plugin
+        let opened = PluginTableRegistry::open(temp_dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(opened.table_names(), vec!["app_latency"]);
+        assert_eq!(opened.schema("app_latency").unwrap(), latency_spec().to_definition().schema);
+        let batches = opened.query("SELECT COUNT(*) AS n FROM app_latency").await.unwrap();
+        let counts = layout_compat::column::<Int64Array>(&batches[0], 0).unwrap();
+        assert_eq!(counts.value(0), 3);
+    }
+}
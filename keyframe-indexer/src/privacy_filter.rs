@@ -0,0 +1,301 @@
+use crate::error::{IndexerError, Result};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Apps and sites whose frames are never stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyFilterConfig {
+    pub enabled: bool,
+    /// Frontmost apps whose frames are dropped, e.g. password managers
+    pub blocked_apps: Vec<String>,
+    /// When set, frames of any other app are dropped too
+    pub allowed_apps: Vec<String>,
+    /// Domains whose pages are dropped; subdomains match too
+    pub blocked_domains: Vec<String>,
+    /// Exceptions to `blocked_domains`, e.g. a bank's public help site
+    pub allowed_domains: Vec<String>,
+    /// Apps whose window title and address bar are checked for a blocked domain
+    pub browser_apps: Vec<String>,
+    /// Top fraction of a browser frame searched for the address bar
+    pub address_bar_ratio: f32,
+    /// Top fraction of a screenshot holding the menu bar, whose first item
+    /// names the frontmost app
+    pub menu_bar_ratio: f32,
+}
+
+impl Default for PrivacyFilterConfig {
+    fn default() -> Self {
+        let names = |list: &[&str]| -> Vec<String> { list.iter().map(|name| name.to_string()).collect() };
+        Self {
+            enabled: true,
+            blocked_apps: names(&["1Password", "Bitwarden", "Dashlane", "KeePassXC", "Keychain Access", "LastPass", "Passwords"]),
+            allowed_apps: Vec::new(),
+            blocked_domains: Vec::new(),
+            allowed_domains: Vec::new(),
+            browser_apps: names(&["Safari", "Google Chrome", "Firefox", "Arc", "Microsoft Edge", "Brave Browser"]),
+            address_bar_ratio: 0.15,
+            menu_bar_ratio: 0.04,
+        }
+    }
+}
+
+/// Why a frame was dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivacyMatch {
+    App(String),
+    Domain(String),
+}
+
+/// Frames dropped by the privacy filter.
+///
+/// Only the time range is kept, not the app or site that caused it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactedInterval {
+    pub segment_id: String,
+    pub monitor_id: i32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub frame_count: usize,
+}
+
+pub struct PrivacyFilter {
+    config: PrivacyFilterConfig,
+    /// Host names in window titles and address bars, with or without a scheme
+    host: Regex,
+}
+
+impl PrivacyFilter {
+    pub fn new(config: PrivacyFilterConfig) -> Result<Self> {
+        if !(0.0..=1.0).contains(&config.address_bar_ratio) {
+            return Err(IndexerError::Config("address_bar_ratio must be between 0 and 1".to_string()));
+        }
+        if !(0.0..=1.0).contains(&config.menu_bar_ratio) {
+            return Err(IndexerError::Config("menu_bar_ratio must be between 0 and 1".to_string()));
+        }
+        let host = Regex::new(r"(?i)\b(?:https?://)?((?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+[a-z]{2,})\b")
+            .map_err(|e| IndexerError::Config(format!("Invalid host pattern: {}", e)))?;
+        Ok(Self { config, host })
+    }
+
+    /// The rule blocking a frame, checked against its app, window title and address bar text
+    pub fn check(&self, frame: &FrameMetadata, ocr_results: &[OCRResult]) -> Option<PrivacyMatch> {
+        self.check_frame(&frame.app_name, &frame.win_title, frame.height, ocr_results)
+    }
+
+    /// The rule blocking whatever is on screen, from the frontmost app and
+    /// window title alone, so a frame can be withheld before it is saved
+    pub fn check_app(&self, app: &str, win_title: &str) -> Option<PrivacyMatch> {
+        self.check_frame(app, win_title, 0, &[])
+    }
+
+    /// The rule blocking a screenshot that comes without app or window title.
+    /// The app is read from the menu bar, so `ocr_results` only need to cover
+    /// the top [`header_height`](Self::header_height) of the image.
+    pub fn check_screenshot(&self, ocr_results: &[OCRResult], height: u32) -> Option<PrivacyMatch> {
+        let menu_bar_bottom = height as f32 * self.config.menu_bar_ratio;
+        let app = ocr_results
+            .iter()
+            .filter(|r| r.roi.y + r.roi.height <= menu_bar_bottom && !r.text.trim().is_empty())
+            .min_by(|a, b| a.roi.x.total_cmp(&b.roi.x))
+            .map(|r| r.text.trim())
+            .unwrap_or_default();
+        self.check_frame(app, "", height, ocr_results)
+    }
+
+    /// Height of the top strip of an image the rules look at
+    pub fn header_height(&self, height: u32) -> f32 {
+        height as f32 * self.config.address_bar_ratio.max(self.config.menu_bar_ratio)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn check_frame(&self, app: &str, win_title: &str, height: u32, ocr_results: &[OCRResult]) -> Option<PrivacyMatch> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let is_app = |names: &[String]| names.iter().any(|name| name.eq_ignore_ascii_case(app));
+        if is_app(&self.config.blocked_apps) || (!self.config.allowed_apps.is_empty() && !is_app(&self.config.allowed_apps)) {
+            return Some(PrivacyMatch::App(app.to_string()));
+        }
+
+        if self.config.blocked_domains.is_empty() || !is_app(&self.config.browser_apps) {
+            return None;
+        }
+        let address_bar_bottom = height as f32 * self.config.address_bar_ratio;
+        std::iter::once(win_title)
+            .chain(ocr_results.iter().filter(|r| r.roi.y < address_bar_bottom).map(|r| r.text.as_str()))
+            .flat_map(|text| self.host.captures_iter(text).map(|c| c[1].to_lowercase()))
+            .find(|host| {
+                matches_domain(host, &self.config.blocked_domains) && !matches_domain(host, &self.config.allowed_domains)
+            })
+            .map(PrivacyMatch::Domain)
+    }
+
+    /// Drop a segment's blocked frames and their OCR results, returning the dropped
+    /// frames as intervals of consecutive frames.
+    ///
+    /// Duplicates of a dropped frame are dropped too, since they show the same screen.
+    pub fn filter_segment(
+        &self,
+        frames: &mut Vec<FrameMetadata>,
+        frame_ocr: &mut HashMap<String, Vec<OCRResult>>,
+        segment_start: DateTime<Utc>,
+    ) -> Vec<RedactedInterval> {
+        let mut blocked_paths = HashSet::new();
+        let blocked: Vec<bool> = frames
+            .iter()
+            .map(|frame| {
+                let blocked = match &frame.duplicate_of {
+                    Some(original) if blocked_paths.contains(original) => true,
+                    _ => {
+                        let ocr = frame_ocr.get(&frame.path).map(Vec::as_slice).unwrap_or(&[]);
+                        self.check(frame, ocr).is_some()
+                    }
+                };
+                if blocked {
+                    blocked_paths.insert(frame.path.clone());
+                }
+                blocked
+            })
+            .collect();
+
+        let mut intervals: Vec<RedactedInterval> = Vec::new();
+        let mut previous_blocked = false;
+        for (frame, &is_blocked) in frames.iter().zip(&blocked) {
+            if is_blocked {
+                let time = segment_start + Duration::nanoseconds(frame.ts_ns);
+                match intervals.last_mut().filter(|_| previous_blocked) {
+                    Some(interval) => {
+                        interval.end = time;
+                        interval.frame_count += 1;
+                    }
+                    None => intervals.push(RedactedInterval {
+                        segment_id: frame.segment_id.clone(),
+                        monitor_id: frame.monitor_id,
+                        start: time,
+                        end: time,
+                        frame_count: 1,
+                    }),
+                }
+            }
+            previous_blocked = is_blocked;
+        }
+
+        let mut blocked = blocked.into_iter();
+        frames.retain(|_| !blocked.next().unwrap_or(false));
+        frame_ocr.retain(|path, _| !blocked_paths.contains(path));
+        intervals
+    }
+}
+
+fn matches_domain(host: &str, domains: &[String]) -> bool {
+    domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches("*.").to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr_data::BoundingBox;
+
+    fn frame(ts_ns: i64, app: &str, title: &str) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: format!("frame_{}.png", ts_ns),
+            phash16: 0,
+            entropy: 5.0,
+            app_name: app.to_string(),
+            win_title: title.to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }
+    }
+
+    #[test]
+    fn test_blocked_apps_and_domains_become_redacted_intervals() {
+        let filter = PrivacyFilter::new(PrivacyFilterConfig {
+            blocked_domains: vec!["chase.com".to_string()],
+            allowed_domains: vec!["help.chase.com".to_string()],
+            ..PrivacyFilterConfig::default()
+        })
+        .unwrap();
+
+        let mut duplicate = frame(3, "1Password", "Vault");
+        duplicate.duplicate_of = Some("frame_2.png".to_string());
+        let mut frames = vec![
+            frame(1, "Xcode", "main.swift"),
+            frame(2, "1Password", "Vault"),
+            duplicate,
+            frame(4, "Safari", "Accounts"),
+            frame(5, "Safari", "Help Center"),
+            frame(6, "Slack", "general"),
+        ];
+
+        let address_bar = |frame_id: &str, text: &str, y: f32| OCRResult {
+            frame_id: frame_id.to_string(),
+            roi: BoundingBox::new(400.0, y, 600.0, 24.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        };
+        let mut frame_ocr = HashMap::new();
+        frame_ocr.insert("frame_4.png".to_string(), vec![address_bar("frame_4.png", "https://secure.chase.com/accounts", 60.0)]);
+        frame_ocr.insert("frame_5.png".to_string(), vec![
+            address_bar("frame_5.png", "help.chase.com/faq", 60.0),
+            // Page text below the address bar is not checked
+            address_bar("frame_5.png", "Pay with chase.com", 600.0),
+        ]);
+
+        let start = Utc::now();
+        let intervals = filter.filter_segment(&mut frames, &mut frame_ocr, start);
+
+        let kept: Vec<i64> = frames.iter().map(|f| f.ts_ns).collect();
+        assert_eq!(kept, vec![1, 5, 6]);
+        assert!(!frame_ocr.contains_key("frame_4.png"));
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[0].frame_count, 3);
+        assert_eq!(intervals[0].start, start + Duration::nanoseconds(2));
+        assert_eq!(intervals[0].end, start + Duration::nanoseconds(4));
+
+        let allowlist = PrivacyFilter::new(PrivacyFilterConfig {
+            allowed_apps: vec!["Xcode".to_string()],
+            ..PrivacyFilterConfig::default()
+        })
+        .unwrap();
+        assert_eq!(allowlist.check(&frame(1, "Slack", "general"), &[]), Some(PrivacyMatch::App("Slack".to_string())));
+        assert_eq!(allowlist.check(&frame(1, "Xcode", "main.swift"), &[]), None);
+    }
+
+    #[test]
+    fn test_app_check_before_saving_uses_title_only() {
+        let filter = PrivacyFilter::new(PrivacyFilterConfig {
+            blocked_domains: vec!["chase.com".to_string()],
+            ..PrivacyFilterConfig::default()
+        })
+        .unwrap();
+        assert_eq!(filter.check_app("1Password", ""), Some(PrivacyMatch::App("1Password".to_string())));
+        assert_eq!(filter.check_app("Safari", "secure.chase.com - Accounts"), Some(PrivacyMatch::Domain("secure.chase.com".to_string())));
+        assert_eq!(filter.check_app("Safari", "Accounts"), None);
+
+        let disabled = PrivacyFilter::new(PrivacyFilterConfig { enabled: false, ..PrivacyFilterConfig::default() }).unwrap();
+        assert_eq!(disabled.check_app("1Password", ""), None);
+    }
+}
@@ -0,0 +1,515 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::ocr_data::BoundingBox;
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Following progress dialogs until they finish or are cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressTrackingConfig {
+    pub enabled: bool,
+    /// Minimum ROI overlap for a progress dialog to be the one from an earlier frame
+    pub min_iou: f32,
+    /// Consecutive frames a dialog must be missing from before it counts as
+    /// ended, so one frame of poor OCR does not end it
+    pub missing_frames_to_end: u32,
+    /// Percentage from which a dialog that goes away counts as finished
+    pub complete_percent: f32,
+}
+
+impl Default for ProgressTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_iou: 0.5,
+            missing_frames_to_end: 3,
+            complete_percent: 99.0,
+        }
+    }
+}
+
+impl ProgressTrackingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.min_iou) {
+            return Err(IndexerError::Config("progress min_iou must be between 0 and 1".to_string()));
+        }
+        if !(0.0..=100.0).contains(&self.complete_percent) {
+            return Err(IndexerError::Config("progress complete_percent must be between 0 and 100".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// How a progress dialog ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressOutcome {
+    /// Reached completion, said so, or was due to complete by the time it went away
+    Finished,
+    /// Said it was cancelled, or went away well before it was due to complete
+    Cancelled,
+    /// Went away with no percentage or remaining time to judge by
+    Unknown,
+    /// Still on screen, without showing completion, when the recording stopped
+    Ongoing,
+}
+
+impl ProgressOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Finished => "finished",
+            Self::Cancelled => "cancelled",
+            Self::Unknown => "unknown",
+            Self::Ongoing => "ongoing",
+        }
+    }
+}
+
+/// What one frame of a progress dialog says
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressReading {
+    pub percent: Option<f32>,
+    pub remaining: Option<Duration>,
+    pub completed: bool,
+    pub cancelled: bool,
+}
+
+/// Reads percentages, `3 of 10` counters, remaining times and completion words
+/// from progress dialog text
+pub struct ProgressParser {
+    percent: Regex,
+    count: Regex,
+    remaining: Regex,
+    clock: Regex,
+    completed: Regex,
+    cancelled: Regex,
+}
+
+impl ProgressParser {
+    pub fn new() -> Result<Self> {
+        let invalid = |e: regex::Error| IndexerError::Config(format!("Invalid progress pattern: {}", e));
+        Ok(Self {
+            percent: Regex::new(r"(\d{1,3}(?:[.,]\d+)?)\s*%").map_err(invalid)?,
+            count: Regex::new(r"(?i)\b(\d+)\s+(?:of|/)\s+(\d+)\b").map_err(invalid)?,
+            // `About 2 minutes remaining`, `5 sec left`
+            remaining: Regex::new(r"(?i)(\d+)\s*(hours?|hrs?|h|minutes?|mins?|m|seconds?|secs?|s)\b\s+(?:remaining|left)")
+                .map_err(invalid)?,
+            // `00:01:30 remaining`, `ETA 02:15`
+            clock: Regex::new(r"(?i)(?:eta:?\s*(\d{1,2}(?::\d{2}){1,2})|(\d{1,2}(?::\d{2}){1,2})\s*(?:remaining|left))")
+                .map_err(invalid)?,
+            completed: Regex::new(r"(?i)\b(?:complete|completed|done|finished|succeeded)\b").map_err(invalid)?,
+            cancelled: Regex::new(r"(?i)\b(?:cancell?ed|aborted|stopped|interrupted)\b").map_err(invalid)?,
+        })
+    }
+
+    pub fn parse(&self, text: &str) -> ProgressReading {
+        let percent = self
+            .percent
+            .captures(text)
+            .and_then(|c| c[1].replace(',', ".").parse::<f32>().ok())
+            .filter(|p| *p <= 100.0)
+            .or_else(|| {
+                let c = self.count.captures(text)?;
+                let (done, total) = (c[1].parse::<f32>().ok()?, c[2].parse::<f32>().ok()?);
+                (total > 0.0 && done <= total).then_some(done / total * 100.0)
+            });
+        let remaining = self
+            .clock
+            .captures(text)
+            .and_then(|c| c.get(1).or_else(|| c.get(2)))
+            .and_then(|m| clock_duration(m.as_str()))
+            .or_else(|| {
+                let c = self.remaining.captures(text)?;
+                let amount: i64 = c[1].parse().ok()?;
+                let seconds = match c[2].to_lowercase().chars().next()? {
+                    'h' => amount * 3600,
+                    'm' => amount * 60,
+                    _ => amount,
+                };
+                Some(Duration::seconds(seconds))
+            });
+        ProgressReading {
+            percent,
+            remaining,
+            completed: self.completed.is_match(text),
+            cancelled: self.cancelled.is_match(text),
+        }
+    }
+}
+
+/// `mm:ss` or `hh:mm:ss`
+fn clock_duration(text: &str) -> Option<Duration> {
+    let parts: Vec<i64> = text.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let seconds = parts.iter().fold(0, |total, part| total * 60 + part);
+    Some(Duration::seconds(seconds))
+}
+
+/// A progress dialog on screen
+#[derive(Debug, Clone)]
+struct ActiveProgress {
+    progress_id: String,
+    label: String,
+    roi: Option<BoundingBox>,
+    started: DateTime<Utc>,
+    first_frame: String,
+    last_seen: DateTime<Utc>,
+    last_frame: String,
+    /// First and latest readings with a percentage
+    first_percent: Option<(DateTime<Utc>, f32)>,
+    last_percent: Option<(DateTime<Utc>, f32)>,
+    /// Latest remaining time and when it was read
+    remaining: Option<(DateTime<Utc>, Duration)>,
+    completed: bool,
+    cancelled: bool,
+    frames_seen: u32,
+    missed_frames: u32,
+    /// First frame, and its time, of the frames the dialog is currently missing from
+    gone_since: Option<(DateTime<Utc>, String)>,
+    confidence: f32,
+}
+
+impl ActiveProgress {
+    /// Percentage points per millisecond between the first and latest readings
+    fn rate(&self) -> Option<f64> {
+        let ((t0, p0), (t1, p1)) = (self.first_percent?, self.last_percent?);
+        let elapsed = (t1 - t0).num_milliseconds();
+        (elapsed > 0 && p1 > p0).then_some((p1 - p0) as f64 / elapsed as f64)
+    }
+
+    /// When the operation was due to complete, from the remaining time or the rate
+    fn expected_end(&self) -> Option<DateTime<Utc>> {
+        if let Some((at, remaining)) = self.remaining {
+            return Some(at + remaining);
+        }
+        let (at, percent) = self.last_percent?;
+        let rate = self.rate()?;
+        Some(at + Duration::milliseconds(((100.0 - percent) as f64 / rate) as i64))
+    }
+}
+
+/// Follows progress dialogs across frames and emits one `ProgressCompleted`
+/// event per dialog once it goes away.
+///
+/// Each frame's progress dialog detections are matched to earlier ones by ROI
+/// overlap. Frames must be tracked in time order.
+pub struct ProgressTracker {
+    config: ProgressTrackingConfig,
+    parser: ProgressParser,
+    active: Vec<ActiveProgress>,
+}
+
+impl ProgressTracker {
+    pub fn new(config: ProgressTrackingConfig) -> Result<Self> {
+        Ok(Self { config, parser: ProgressParser::new()?, active: Vec::new() })
+    }
+
+    /// Follow the progress dialogs among a frame's events; returns events for
+    /// dialogs that ended
+    pub fn track_frame(&mut self, frame_id: &str, timestamp: DateTime<Utc>, events: &[DetectedEvent]) -> Vec<DetectedEvent> {
+        let mut seen = vec![false; self.active.len()];
+        for event in events.iter().filter(|e| is_progress_dialog(e)) {
+            let text = event.value_to.clone().unwrap_or_else(|| event.target.clone());
+            let reading = self.parser.parse(&text);
+            let roi = BoundingBox::from_metadata(&event.metadata);
+            let label = progress_label(&text);
+            let matched = self.active.iter().enumerate().position(|(index, progress)| {
+                !seen[index]
+                    && match (&progress.roi, &roi) {
+                        (Some(a), Some(b)) => a.iou(b) >= self.config.min_iou,
+                        _ => progress.label == label,
+                    }
+            });
+            let index = match matched {
+                Some(index) => index,
+                None => {
+                    debug!("Progress dialog appeared in frame {}: {}", frame_id, text);
+                    self.active.push(ActiveProgress {
+                        progress_id: uuid::Uuid::new_v4().to_string(),
+                        label,
+                        roi: roi.clone(),
+                        started: timestamp,
+                        first_frame: frame_id.to_string(),
+                        last_seen: timestamp,
+                        last_frame: frame_id.to_string(),
+                        first_percent: None,
+                        last_percent: None,
+                        remaining: None,
+                        completed: false,
+                        cancelled: false,
+                        frames_seen: 0,
+                        missed_frames: 0,
+                        gone_since: None,
+                        confidence: event.confidence,
+                    });
+                    seen.push(false);
+                    self.active.len() - 1
+                }
+            };
+
+            seen[index] = true;
+            let progress = &mut self.active[index];
+            progress.roi = roi.or(progress.roi.take());
+            progress.last_seen = timestamp;
+            progress.last_frame = frame_id.to_string();
+            progress.frames_seen += 1;
+            progress.missed_frames = 0;
+            progress.gone_since = None;
+            if let Some(percent) = reading.percent {
+                progress.first_percent.get_or_insert((timestamp, percent));
+                progress.last_percent = Some((timestamp, percent));
+            }
+            if let Some(remaining) = reading.remaining {
+                progress.remaining = Some((timestamp, remaining));
+            }
+            progress.completed |= reading.completed || reading.percent.map_or(false, |p| p >= 100.0);
+            progress.cancelled |= reading.cancelled;
+        }
+
+        let mut ended = Vec::new();
+        let mut still_active = Vec::with_capacity(self.active.len());
+        for (mut progress, seen) in std::mem::take(&mut self.active).into_iter().zip(seen) {
+            if !seen {
+                progress.missed_frames += 1;
+                progress.gone_since.get_or_insert_with(|| (timestamp, frame_id.to_string()));
+            }
+            if !seen && progress.missed_frames >= self.config.missing_frames_to_end.max(1) {
+                // It ended when it was first missing, not when that was confirmed
+                let (gone_at, gone_frame) = progress.gone_since.clone().unwrap_or((timestamp, frame_id.to_string()));
+                ended.push(self.completed_event(progress, &gone_frame, gone_at));
+            } else {
+                still_active.push(progress);
+            }
+        }
+        self.active = still_active;
+        ended
+    }
+
+    /// End the dialogs still followed when the recording stops. Dialogs keep
+    /// being followed across segments, so this is not called between them.
+    /// Those gone for fewer than `missing_frames_to_end` frames end when they
+    /// went; those still on screen are `finished` if they show completion and
+    /// `ongoing` otherwise.
+    pub fn finish(&mut self) -> Vec<DetectedEvent> {
+        std::mem::take(&mut self.active)
+            .into_iter()
+            .map(|progress| match progress.gone_since.clone() {
+                Some((gone_at, gone_frame)) => self.completed_event(progress, &gone_frame, gone_at),
+                None => {
+                    let (frame, at) = (progress.last_frame.clone(), progress.last_seen);
+                    self.ongoing_event(progress, &frame, at)
+                }
+            })
+            .collect()
+    }
+
+    /// Progress dialogs on screen as of the last tracked frame
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    fn outcome(&self, progress: &ActiveProgress, ended: DateTime<Utc>) -> ProgressOutcome {
+        let reached = progress.last_percent.map_or(false, |(_, p)| p >= self.config.complete_percent);
+        if progress.cancelled {
+            return ProgressOutcome::Cancelled;
+        }
+        if progress.completed || reached {
+            return ProgressOutcome::Finished;
+        }
+        match progress.expected_end() {
+            // It was due to complete before the frame it was gone in
+            Some(expected) if expected <= ended => ProgressOutcome::Finished,
+            Some(_) => ProgressOutcome::Cancelled,
+            None => ProgressOutcome::Unknown,
+        }
+    }
+
+    /// Event for a dialog on screen until the end, which only ended if it said so
+    fn ongoing_event(&self, progress: ActiveProgress, frame_id: &str, last_seen: DateTime<Utc>) -> DetectedEvent {
+        let reached = progress.last_percent.map_or(false, |(_, p)| p >= self.config.complete_percent);
+        let outcome = if progress.cancelled {
+            ProgressOutcome::Cancelled
+        } else if progress.completed || reached {
+            ProgressOutcome::Finished
+        } else {
+            ProgressOutcome::Ongoing
+        };
+        self.progress_event(progress, frame_id, last_seen, outcome)
+    }
+
+    fn completed_event(&self, progress: ActiveProgress, frame_id: &str, ended: DateTime<Utc>) -> DetectedEvent {
+        let outcome = self.outcome(&progress, ended);
+        self.progress_event(progress, frame_id, ended, outcome)
+    }
+
+    fn progress_event(
+        &self,
+        progress: ActiveProgress,
+        frame_id: &str,
+        ended: DateTime<Utc>,
+        outcome: ProgressOutcome,
+    ) -> DetectedEvent {
+        let duration_ms = (ended - progress.started).num_milliseconds().max(0);
+        debug!("Progress dialog {} ended after {} ms: {}", progress.progress_id, duration_ms, outcome.as_str());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("progress_id".to_string(), progress.progress_id.clone());
+        metadata.insert("progress_outcome".to_string(), outcome.as_str().to_string());
+        metadata.insert("progress_started_at".to_string(), progress.started.to_rfc3339());
+        metadata.insert("progress_ended_at".to_string(), ended.to_rfc3339());
+        metadata.insert("last_seen_at".to_string(), progress.last_seen.to_rfc3339());
+        metadata.insert("duration_ms".to_string(), duration_ms.to_string());
+        metadata.insert("frames_seen".to_string(), progress.frames_seen.to_string());
+        if let Some((_, percent)) = progress.first_percent {
+            metadata.insert("first_percent".to_string(), format!("{:.0}", percent));
+        }
+        if let Some((_, percent)) = progress.last_percent {
+            metadata.insert("last_percent".to_string(), format!("{:.0}", percent));
+        }
+        // Time already shown plus what the dialog said was left, or 100% at the observed rate
+        let estimated_total_ms = match progress.remaining {
+            Some((at, remaining)) => Some((at - progress.started + remaining).num_milliseconds()),
+            None => progress.rate().map(|rate| (100.0 / rate) as i64),
+        };
+        if let Some(total) = estimated_total_ms {
+            metadata.insert("estimated_total_ms".to_string(), total.to_string());
+        }
+        if let Some(roi) = &progress.roi {
+            roi.insert_metadata(&mut metadata);
+        }
+
+        let mut evidence_frames = vec![progress.first_frame.clone()];
+        for frame in [progress.last_frame.clone(), frame_id.to_string()] {
+            if !evidence_frames.contains(&frame) {
+                evidence_frames.push(frame);
+            }
+        }
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: ended,
+            event_type: EventType::ProgressCompleted,
+            target: progress.label,
+            value_from: progress.first_percent.map(|(_, p)| format!("{:.0}%", p)),
+            value_to: Some(outcome.as_str().to_string()),
+            confidence: progress.confidence,
+            evidence_frames,
+            metadata,
+        }
+    }
+}
+
+fn is_progress_dialog(event: &DetectedEvent) -> bool {
+    event.metadata.get("error_modal_type").map(String::as_str) == Some("progress_dialog")
+}
+
+/// Words that only go with the numbers in progress text
+const NUMBER_WORDS: &[&str] = &[
+    "of", "remaining", "left", "eta", "about", "hours", "hour", "hrs", "minutes", "minute", "mins", "min", "seconds",
+    "second", "secs", "sec",
+];
+
+/// Dialog text without its numbers, e.g. `Copying files` from `Copying files 45% 2 of 5`
+fn progress_label(text: &str) -> String {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .filter(|word| !NUMBER_WORDS.contains(&word.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric())))
+        .take(8)
+        .collect();
+    if words.is_empty() {
+        "progress".to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(text: &str, at: DateTime<Utc>) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("error_modal_type".to_string(), "progress_dialog".to_string());
+        BoundingBox::new(500.0, 300.0, 400.0, 150.0).insert_metadata(&mut metadata);
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: at,
+            event_type: EventType::ModalAppearance,
+            target: "progress_dialog_Info".to_string(),
+            value_from: None,
+            value_to: Some(text.to_string()),
+            confidence: 0.8,
+            evidence_frames: Vec::new(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_progress_finished_and_cancelled() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        let config = ProgressTrackingConfig { enabled: true, missing_frames_to_end: 1, ..Default::default() };
+        let mut tracker = ProgressTracker::new(config).unwrap();
+
+        assert!(tracker.track_frame("frame_1", at(0), &[progress("Copying files 20% 2 minutes remaining", at(0))]).is_empty());
+        assert!(tracker.track_frame("frame_2", at(30), &[progress("Copying files 60% 20 seconds left", at(30))]).is_empty());
+        assert_eq!(tracker.active_count(), 1);
+
+        // Due to complete at 50s, and gone by 60s
+        let ended = tracker.track_frame("frame_3", at(60), &[]);
+        assert_eq!(ended.len(), 1);
+        let event = &ended[0];
+        assert_eq!(event.event_type, EventType::ProgressCompleted);
+        assert_eq!(event.target, "Copying files");
+        assert_eq!(event.metadata["progress_outcome"], "finished");
+        assert_eq!(event.metadata["duration_ms"], "60000");
+        assert_eq!(event.metadata["estimated_total_ms"], "50000");
+        assert_eq!(event.value_from.as_deref(), Some("20%"));
+        assert_eq!(event.evidence_frames, ["frame_1", "frame_2", "frame_3"]);
+
+        // At 10% per 10s this one had 80s to go when it disappeared
+        tracker.track_frame("frame_4", at(100), &[progress("Exporting 3 of 30", at(100))]);
+        tracker.track_frame("frame_5", at(110), &[progress("Exporting 6 of 30", at(110))]);
+        let ended = tracker.track_frame("frame_6", at(120), &[]);
+        assert_eq!(ended[0].metadata["progress_outcome"], "cancelled");
+        assert_eq!(ended[0].metadata["last_percent"], "20");
+
+        tracker.track_frame("frame_7", at(200), &[progress("Upload cancelled", at(200))]);
+        assert_eq!(tracker.track_frame("frame_8", at(205), &[])[0].value_to.as_deref(), Some("cancelled"));
+    }
+
+    #[test]
+    fn test_progress_survives_ocr_dropouts_and_is_reported_when_recording_stops() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        let mut tracker = ProgressTracker::new(ProgressTrackingConfig { enabled: true, ..Default::default() }).unwrap();
+
+        tracker.track_frame("frame_1", at(0), &[progress("Exporting 10%", at(0))]);
+        // One frame of unreadable text does not end it
+        assert!(tracker.track_frame("frame_2", at(10), &[]).is_empty());
+        tracker.track_frame("frame_3", at(20), &[progress("Exporting 30%", at(20))]);
+        assert_eq!(tracker.active_count(), 1);
+
+        // Gone for good: timed at the first frame it was missing from
+        assert!(tracker.track_frame("frame_4", at(30), &[]).is_empty());
+        assert!(tracker.track_frame("frame_5", at(40), &[]).is_empty());
+        let ended = tracker.track_frame("frame_6", at(50), &[]);
+        assert_eq!(ended.len(), 1);
+        assert_eq!(ended[0].metadata["duration_ms"], "30000");
+        assert_eq!(ended[0].evidence_frames, ["frame_1", "frame_3", "frame_4"]);
+
+        // Still running when the recording stops
+        tracker.track_frame("frame_7", at(60), &[progress("Rendering 40%", at(60))]);
+        let stopped = tracker.finish();
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0].metadata["progress_outcome"], "ongoing");
+        assert_eq!(tracker.active_count(), 0);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::error::Result;
+use crate::layout_compat::{self, CompatibleTable};
+use arrow::array::{
+    Array, BooleanArray, ListArray, StringArray, StringBuilder, TimestampNanosecondArray,
+    TimestampNanosecondBuilder, UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// One query made against the indexed datasets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryAuditEntry {
+    pub audit_id: String,
+    pub timestamp: DateTime<Utc>,
+    /// API key id (or key fingerprint) that issued the query
+    pub principal: String,
+    /// Redaction profile applied, if the caller was authorized
+    pub profile: Option<String>,
+    /// Query operation, e.g. "query_ocr_by_text"
+    pub operation: String,
+    /// Human-readable filter description
+    pub filter: String,
+    /// Datasets touched by the query
+    pub datasets: Vec<String>,
+    pub rows_returned: u64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Append-only audit table of queries, stored as Parquet
+pub struct QueryAuditLog {
+    output_dir: PathBuf,
+    schema: Arc<Schema>,
+    batch_size: usize,
+    current_batch: Vec<QueryAuditEntry>,
+}
+
+impl QueryAuditLog {
+    pub fn new(output_dir: &str) -> Result<Self> {
+        let output_path = PathBuf::from(output_dir);
+        std::fs::create_dir_all(&output_path)?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("audit_id", DataType::Utf8, false),
+            Field::new("ts_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("principal", DataType::Utf8, false),
+            Field::new("profile", DataType::Utf8, true),
+            Field::new("operation", DataType::Utf8, false),
+            Field::new("filter", DataType::Utf8, false),
+            Field::new("datasets", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
+            Field::new("rows_returned", DataType::UInt64, false),
+            Field::new("duration_ms", DataType::UInt64, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("error", DataType::Utf8, true),
+        ]));
+
+        Ok(Self {
+            output_dir: output_path,
+            schema,
+            batch_size: 100,
+            current_batch: Vec::new(),
+        })
+    }
+
+    /// Record a query; entries are flushed to Parquet in batches
+    pub async fn record(&mut self, entry: QueryAuditEntry) -> Result<()> {
+        debug!(
+            "Audit: {} ran {} over {:?} returning {} rows in {}ms",
+            entry.principal, entry.operation, entry.datasets, entry.rows_returned, entry.duration_ms
+        );
+
+        self.current_batch.push(entry);
+        if self.current_batch.len() >= self.batch_size {
+            self.flush_batch().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush buffered audit entries to disk
+    pub async fn flush_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%f");
+        let file_path = self.output_dir.join(format!("query_audit_{}.parquet", timestamp));
+
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_created_by("AlwaysOnAI Query Audit".to_string())
+            .build();
+
+        let mut writer = ArrowWriter::try_new(File::create(&file_path)?, self.schema.clone(), Some(props))?;
+        writer.write(&record_batch)?;
+        writer.close()?;
+
+        info!("Wrote {} query audit entries to {}", self.current_batch.len(), file_path.display());
+        self.current_batch.clear();
+        Ok(())
+    }
+
+    /// Access history for a principal, newest first
+    pub async fn query_by_principal(&self, principal: &str) -> Result<Vec<QueryAuditEntry>> {
+        let sql = format!(
+            "SELECT * FROM query_audit WHERE principal = '{}' ORDER BY ts_ns DESC",
+            principal.replace('\'', "''")
+        );
+        let mut entries = self.run_query(&sql).await?;
+        entries.extend(self.current_batch.iter().filter(|e| e.principal == principal).cloned());
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(entries)
+    }
+
+    /// Access history within a time range, oldest first
+    pub async fn query_by_time_range(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<QueryAuditEntry>> {
+        let start_ns = start_time.timestamp_nanos_opt().unwrap_or(0);
+        let end_ns = end_time.timestamp_nanos_opt().unwrap_or(0);
+
+        let sql = format!(
+            "SELECT * FROM query_audit WHERE ts_ns >= {} AND ts_ns <= {} ORDER BY ts_ns ASC",
+            start_ns, end_ns
+        );
+        let mut entries = self.run_query(&sql).await?;
+        entries.extend(
+            self.current_batch
+                .iter()
+                .filter(|e| e.timestamp >= start_time && e.timestamp <= end_time)
+                .cloned(),
+        );
+        entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(entries)
+    }
+
+    /// Total rows disclosed to a principal across all recorded queries
+    pub async fn rows_returned_to(&self, principal: &str) -> Result<u64> {
+        Ok(self
+            .query_by_principal(principal)
+            .await?
+            .iter()
+            .filter(|e| e.success)
+            .map(|e| e.rows_returned)
+            .sum())
+    }
+
+    pub async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch().await
+    }
+
+    pub fn get_output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    // MARK: - Private Helper Methods
+
+    fn get_parquet_files(&self) -> Result<Vec<PathBuf>> {
+        layout_compat::parquet_files(&self.output_dir)
+    }
+
+    async fn run_query(&self, sql: &str) -> Result<Vec<QueryAuditEntry>> {
+        if self.get_parquet_files()?.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = SessionContext::new();
+        CompatibleTable::new("query_audit", self.schema.clone())
+            .register(&ctx, &self.output_dir)
+            .await?;
+
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let mut entries = Vec::new();
+        for batch in &batches {
+            entries.extend(audit_entries_from_batch(batch)?);
+        }
+        Ok(entries)
+    }
+
+    fn create_record_batch(&self, entries: &[QueryAuditEntry]) -> Result<RecordBatch> {
+        let mut timestamp_builder = TimestampNanosecondBuilder::new();
+        for entry in entries {
+            timestamp_builder.append_value(entry.timestamp.timestamp_nanos_opt().unwrap_or(0));
+        }
+
+        let mut datasets_builder = arrow::array::ListBuilder::new(StringBuilder::new());
+        for entry in entries {
+            for dataset in &entry.datasets {
+                datasets_builder.values().append_value(dataset);
+            }
+            datasets_builder.append(true);
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(StringArray::from(entries.iter().map(|e| e.audit_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(timestamp_builder.finish()),
+                Arc::new(StringArray::from(entries.iter().map(|e| e.principal.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(entries.iter().map(|e| e.profile.as_deref()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(entries.iter().map(|e| e.operation.as_str()).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(entries.iter().map(|e| e.filter.as_str()).collect::<Vec<_>>())),
+                Arc::new(datasets_builder.finish()),
+                Arc::new(UInt64Array::from(entries.iter().map(|e| e.rows_returned).collect::<Vec<_>>())),
+                Arc::new(UInt64Array::from(entries.iter().map(|e| e.duration_ms).collect::<Vec<_>>())),
+                Arc::new(BooleanArray::from(entries.iter().map(|e| e.success).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(entries.iter().map(|e| e.error.as_deref()).collect::<Vec<_>>())),
+            ],
+        )?;
+
+        Ok(record_batch)
+    }
+}
+
+/// Audit entries of a batch read back from an audit file; a batch with
+/// unexpected columns is an error rather than a panic
+pub fn audit_entries_from_batch(batch: &RecordBatch) -> Result<Vec<QueryAuditEntry>> {
+    let mut entries = Vec::with_capacity(batch.num_rows());
+
+    let ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let timestamps = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let principals = layout_compat::column::<StringArray>(batch, 2)?;
+    let profiles = layout_compat::column::<StringArray>(batch, 3)?;
+    let operations = layout_compat::column::<StringArray>(batch, 4)?;
+    let filters = layout_compat::column::<StringArray>(batch, 5)?;
+    let datasets = layout_compat::column::<ListArray>(batch, 6)?;
+    let rows = layout_compat::column::<UInt64Array>(batch, 7)?;
+    let durations = layout_compat::column::<UInt64Array>(batch, 8)?;
+    let successes = layout_compat::column::<BooleanArray>(batch, 9)?;
+    let errors = layout_compat::column::<StringArray>(batch, 10)?;
+
+    for i in 0..batch.num_rows() {
+        let dataset_values = datasets.value(i);
+        let dataset_names = dataset_values
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .map(|names| names.iter().flatten().map(|n| n.to_string()).collect())
+            .unwrap_or_default();
+
+        entries.push(QueryAuditEntry {
+            audit_id: ids.value(i).to_string(),
+            timestamp: DateTime::from_timestamp_nanos(timestamps.value(i)),
+            principal: principals.value(i).to_string(),
+            profile: if profiles.is_null(i) { None } else { Some(profiles.value(i).to_string()) },
+            operation: operations.value(i).to_string(),
+            filter: filters.value(i).to_string(),
+            datasets: dataset_names,
+            rows_returned: rows.value(i),
+            duration_ms: durations.value(i),
+            success: successes.value(i),
+            error: if errors.is_null(i) { None } else { Some(errors.value(i).to_string()) },
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_entry(principal: &str, rows: u64, success: bool) -> QueryAuditEntry {
+        QueryAuditEntry {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            principal: principal.to_string(),
+            profile: Some("analyst".to_string()),
+            operation: "query_ocr_by_text".to_string(),
+            filter: "text LIKE '%invoice%'".to_string(),
+            datasets: vec!["ocr".to_string()],
+            rows_returned: rows,
+            duration_ms: 12,
+            success,
+            error: if success { None } else { Some("denied".to_string()) },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_roundtrip_and_row_accounting() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut audit_log = QueryAuditLog::new(temp_dir.path().to_str().unwrap()).unwrap();
+
+        audit_log.record(create_entry("alice", 5, true)).await.unwrap();
+        audit_log.record(create_entry("alice", 0, false)).await.unwrap();
+        audit_log.flush_batch().await.unwrap();
+        // Unflushed entries are visible to reviewers as well
+        audit_log.record(create_entry("alice", 3, true)).await.unwrap();
+        audit_log.record(create_entry("bob", 7, true)).await.unwrap();
+
+        let alice = audit_log.query_by_principal("alice").await.unwrap();
+        assert_eq!(alice.len(), 3);
+        assert_eq!(alice[0].datasets, vec!["ocr".to_string()]);
+        assert_eq!(audit_log.rows_returned_to("alice").await.unwrap(), 8);
+        assert_eq!(audit_log.rows_returned_to("bob").await.unwrap(), 7);
+    }
+}
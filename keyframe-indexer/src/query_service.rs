@@ -0,0 +1,573 @@
+use crate::access_audit::{AccessAuditLog, AccessOperation};
+use crate::auth::{hash_key, ApiKeyConfig, AuthConfig, Authenticator, Scope};
+use crate::config::IndexerConfig;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::live_snapshot::{LiveSnapshot, SnapshotTracker};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::ocr_parquet_writer::OCRParquetWriter;
+use crate::output_export::load_frames;
+use crate::query_audit::{QueryAuditEntry, QueryAuditLog};
+use crate::redaction::{RedactionConfig, RedactionProfile, Redactor};
+use crate::suppression::{SuppressionList, SuppressionRule};
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// Caller identity resolved from an API key
+struct Caller {
+    principal: String,
+    profile: RedactionProfile,
+}
+
+/// Shared read access to the OCR and event datasets.
+///
+/// Every query is made on behalf of an API key; the key selects a redaction
+/// profile which is enforced before results leave the service. Keys are
+/// resolved through an authenticator, which checks scopes and rate limits:
+/// OCR text, frames and events each need their own scope. Keys of a static
+/// redaction mapping are kept only as hashes and granted the scopes of their
+/// profile; an attached authenticator replaces them. With an
+/// audit log attached, every query (including denied ones) is recorded; an
+/// access log also chains it into the tamper-evident record of all data access.
+pub struct QueryService {
+    ocr_store: OCRParquetWriter,
+    event_store: EventParquetWriter,
+    redactor: Redactor,
+    authenticator: Authenticator,
+    audit_log: Option<Mutex<QueryAuditLog>>,
+    access_log: Option<AccessAuditLog>,
+    frames_dir: Option<PathBuf>,
+    snapshot: Option<SnapshotTracker>,
+    suppressions: Option<SuppressionList>,
+}
+
+impl QueryService {
+    pub fn new(ocr_dir: &str, event_dir: &str, redaction_config: RedactionConfig) -> Result<Self> {
+        Ok(Self {
+            ocr_store: OCRParquetWriter::new(ocr_dir)?,
+            event_store: EventParquetWriter::new(event_dir)?,
+            redactor: Redactor::new()?,
+            authenticator: Self::profile_authenticator(&redaction_config)?,
+            audit_log: None,
+            access_log: None,
+            frames_dir: None,
+            snapshot: None,
+            suppressions: None,
+        })
+    }
+    
+    /// A service for the datasets of a config, with its API keys and access log.
+    /// Without configured keys every query is denied.
+    pub fn from_config(config: &IndexerConfig, ocr_dir: &str, event_dir: &str) -> Result<Self> {
+        Ok(Self::new(ocr_dir, event_dir, RedactionConfig::default())?
+            .with_authenticator(Authenticator::new(config.auth.clone())?)
+            .with_access_log(AccessAuditLog::open(config.access_audit_path())?)
+            .with_frames_dir(&config.output_dir))
+    }
+    
+    /// Serve frame metadata from the frame CSV and Parquet files in a directory
+    pub fn with_frames_dir(mut self, frames_dir: &str) -> Self {
+        self.frames_dir = Some(PathBuf::from(frames_dir));
+        self
+    }
+    
+    /// Resolve API keys through an authenticator
+    pub fn with_authenticator(mut self, authenticator: Authenticator) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+    
+    /// Record every query in an audit table
+    pub fn with_audit_log(mut self, audit_log: QueryAuditLog) -> Self {
+        self.audit_log = Some(Mutex::new(audit_log));
+        self
+    }
+
+    /// Record every query in the hash-chained data access log
+    pub fn with_access_log(mut self, access_log: AccessAuditLog) -> Self {
+        self.access_log = Some(access_log);
+        self
+    }
+
+    /// Serve live snapshots from a tracker shared with the pipeline
+    pub fn with_snapshot_tracker(mut self, snapshot: SnapshotTracker) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Manage suppression rules shared with alerting and reporting
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Resolve the redaction profile for an API key
+    pub fn resolve_profile(&self, api_key: &str) -> Result<RedactionProfile> {
+        Ok(self.authorize(api_key, Scope::ReadEvents)?.profile)
+    }
+
+    /// Search OCR text; denied for profiles without OCR access
+    pub async fn query_ocr_by_text(&self, api_key: &str, search_text: &str) -> Result<Vec<OCRResult>> {
+        let filter = format!("text LIKE '%{}%'", search_text);
+        self.audited(api_key, Scope::ReadOcr, "query_ocr_by_text", filter, &["ocr"], |profile| async move {
+            let results = self.ocr_store.query_by_text(search_text).await?;
+            self.redactor.redact_ocr_results(profile, results)
+        }).await
+    }
+
+    /// OCR results for a single frame
+    pub async fn query_ocr_by_frame_id(&self, api_key: &str, frame_id: &str) -> Result<Vec<OCRResult>> {
+        let filter = format!("frame_id = '{}'", frame_id);
+        self.audited(api_key, Scope::ReadOcr, "query_ocr_by_frame_id", filter, &["ocr"], |profile| async move {
+            let results = self.ocr_store.query_by_frame_id(frame_id).await?;
+            self.redactor.redact_ocr_results(profile, results)
+        }).await
+    }
+
+    /// Events of a given type
+    pub async fn query_events_by_type(&self, api_key: &str, event_type: &EventType) -> Result<Vec<DetectedEvent>> {
+        let filter = format!("type = {:?}", event_type);
+        self.audited(api_key, Scope::ReadEvents, "query_events_by_type", filter, &["events"], |profile| async move {
+            let events = self.event_store.query_by_type(event_type).await?;
+            Ok(self.redactor.redact_events(profile, events))
+        }).await
+    }
+
+    /// Events within a time range
+    pub async fn query_events_by_time_range(
+        &self,
+        api_key: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<DetectedEvent>> {
+        let filter = format!("ts BETWEEN {} AND {}", start_time.to_rfc3339(), end_time.to_rfc3339());
+        self.audited(api_key, Scope::ReadEvents, "query_events_by_time_range", filter, &["events"], |profile| async move {
+            let events = self.event_store.query_by_time_range(start_time, end_time).await?;
+            Ok(self.redactor.redact_events(profile, events))
+        }).await
+    }
+
+    /// Events above a confidence threshold
+    pub async fn query_events_by_confidence(&self, api_key: &str, min_confidence: f32) -> Result<Vec<DetectedEvent>> {
+        let filter = format!("confidence >= {}", min_confidence);
+        self.audited(api_key, Scope::ReadEvents, "query_events_by_confidence", filter, &["events"], |profile| async move {
+            let events = self.event_store.query_by_confidence(min_confidence).await?;
+            Ok(self.redactor.redact_events(profile, events))
+        }).await
+    }
+    
+    /// Frame metadata of an app, including keyframe paths; requires the read-frames scope
+    pub async fn query_frames_by_app(&self, api_key: &str, app_name: &str) -> Result<Vec<FrameMetadata>> {
+        let filter = format!("app_name = '{}'", app_name);
+        self.audited(api_key, Scope::ReadFrames, "query_frames_by_app", filter, &["frames"], |profile| async move {
+            let frames = self.read_frames().await?
+                .into_iter()
+                .filter(|frame| frame.app_name.eq_ignore_ascii_case(app_name))
+                .collect();
+            Ok(self.redactor.redact_frames(profile, frames))
+        }).await
+    }
+    
+    /// What is happening right now: frontmost app, last keyframe, recent events,
+    /// cursor position and active detectors. Served from memory, no Parquet scan.
+    pub async fn now(&self, api_key: &str) -> Result<LiveSnapshot> {
+        let snapshot = self.snapshot.as_ref()
+            .ok_or_else(|| IndexerError::Config("Live snapshot is not enabled".to_string()))?;
+
+        let mut results = self.audited(api_key, Scope::ReadEvents, "now", String::new(), &["live"], |profile| async move {
+            Ok(vec![self.redactor.redact_snapshot(profile, snapshot.snapshot())])
+        }).await?;
+
+        Ok(results.remove(0))
+    }
+    
+    /// Review the query history of a principal; requires admin access
+    pub async fn review_access_history(&self, api_key: &str, principal: &str) -> Result<Vec<QueryAuditEntry>> {
+        let audit_log = self.audit_log.as_ref()
+            .ok_or_else(|| IndexerError::Config("Query audit logging is not enabled".to_string()))?;
+        
+        let filter = format!("principal = '{}'", principal);
+        self.audited(api_key, Scope::Admin, "review_access_history", filter, &["query_audit"], |_| async move {
+            audit_log.lock().await.query_by_principal(principal).await
+        }).await
+    }
+    
+    /// Current suppression rules; requires admin access
+    pub async fn list_suppressions(&self, api_key: &str) -> Result<Vec<SuppressionRule>> {
+        let suppressions = self.suppression_list()?;
+        self.audited(api_key, Scope::Admin, "list_suppressions", String::new(), &["suppressions"], |_| async move {
+            Ok(suppressions.rules())
+        }).await
+    }
+    
+    /// Add or replace a suppression rule; requires admin access
+    pub async fn add_suppression(&self, api_key: &str, rule: SuppressionRule) -> Result<()> {
+        let suppressions = self.suppression_list()?;
+        let filter = format!("name = '{}'", rule.name);
+        self.audited(api_key, Scope::Admin, "add_suppression", filter, &["suppressions"], |_| async move {
+            suppressions.add(rule)?;
+            Ok(vec![()])
+        }).await?;
+        Ok(())
+    }
+    
+    /// Remove a suppression rule; returns whether it existed. Requires admin access
+    pub async fn remove_suppression(&self, api_key: &str, name: &str) -> Result<bool> {
+        let suppressions = self.suppression_list()?;
+        let filter = format!("name = '{}'", name);
+        let mut removed = self.audited(api_key, Scope::Admin, "remove_suppression", filter, &["suppressions"], |_| async move {
+            Ok(vec![suppressions.remove(name)?])
+        }).await?;
+        Ok(removed.remove(0))
+    }
+    
+    /// Flush buffered audit entries
+    pub async fn finalize(&mut self) -> Result<()> {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.lock().await.finalize().await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the keys, including those of an attached authenticator, with a static redaction mapping
+    pub fn update_redaction_config(&mut self, redaction_config: RedactionConfig) -> Result<()> {
+        self.authenticator = Self::profile_authenticator(&redaction_config)?;
+        Ok(())
+    }
+
+    // MARK: - Private Helper Methods
+
+    /// Authorize, run and audit a query
+    async fn audited<T, F, Fut>(
+        &self,
+        api_key: &str,
+        scope: Scope,
+        operation: &str,
+        filter: String,
+        datasets: &[&str],
+        run: F,
+    ) -> Result<Vec<T>>
+    where
+        F: FnOnce(RedactionProfile) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        let started = Instant::now();
+        
+        let (principal, profile, result) = match self.authorize(api_key, scope) {
+            Ok(caller) => (caller.principal, Some(caller.profile), run(caller.profile).await),
+            Err(e) => (Self::key_fingerprint(api_key), None, Err(e)),
+        };
+        
+        if let Some(access_log) = &self.access_log {
+            let target = if filter.is_empty() { operation.to_string() } else { format!("{} {}", operation, filter) };
+            let error = result.as_ref().err().map(|e| e.to_string());
+            access_log.record(&principal, AccessOperation::Query, &target, error)?;
+        }
+        
+        if let Some(audit_log) = &self.audit_log {
+            let entry = QueryAuditEntry {
+                audit_id: uuid::Uuid::new_v4().to_string(),
+                timestamp: Utc::now(),
+                principal,
+                profile: profile.map(|p| p.to_string()),
+                operation: operation.to_string(),
+                filter,
+                datasets: datasets.iter().map(|d| d.to_string()).collect(),
+                rows_returned: result.as_ref().map(|rows| rows.len() as u64).unwrap_or(0),
+                duration_ms: started.elapsed().as_millis() as u64,
+                success: result.is_ok(),
+                error: result.as_ref().err().map(|e| e.to_string()),
+            };
+            
+            // Fail closed: a query that cannot be audited is not returned
+            audit_log.lock().await.record(entry).await?;
+        }
+        
+        result
+    }
+
+    fn authorize(&self, api_key: &str, scope: Scope) -> Result<Caller> {
+        let context = self.authenticator.authenticate(api_key, scope)?;
+        let caller = Caller { principal: context.key_id, profile: context.profile };
+        
+        // Keyframes show the same text OCR reads from them
+        if scope == Scope::ReadFrames && !caller.profile.can_read_ocr() {
+            return Err(IndexerError::AccessDenied(format!(
+                "Profile '{}' cannot read frames",
+                caller.profile
+            )));
+        }
+        
+        if scope == Scope::ReadOcr && !caller.profile.can_read_ocr() {
+            debug!("Rejected OCR query for {} profile", caller.profile);
+            return Err(IndexerError::AccessDenied(format!(
+                "Profile '{}' cannot query OCR text",
+                caller.profile
+            )));
+        }
+        
+        Ok(caller)
+    }
+    
+    /// Every frame row in the frame directory
+    async fn read_frames(&self) -> Result<Vec<FrameMetadata>> {
+        let frames_dir = self.frames_dir.as_ref()
+            .ok_or_else(|| IndexerError::Config("Frame queries are not enabled".to_string()))?;
+        load_frames(frames_dir).await
+    }
+    
+    fn suppression_list(&self) -> Result<&SuppressionList> {
+        self.suppressions.as_ref()
+            .ok_or_else(|| IndexerError::Config("Suppression rules are not enabled".to_string()))
+    }
+    
+    /// Stable, non-reversible identifier for keys without a configured id
+    fn key_fingerprint(api_key: &str) -> String {
+        format!("key:{}", &hash_key(api_key)[..12])
+    }
+    
+    /// Authenticate the keys of a static redaction mapping by their hashes,
+    /// granting each the scopes its profile allows
+    fn profile_authenticator(redaction_config: &RedactionConfig) -> Result<Authenticator> {
+        let api_keys = redaction_config
+            .api_keys
+            .iter()
+            .map(|(api_key, &profile)| ApiKeyConfig {
+                id: Self::key_fingerprint(api_key),
+                key_sha256: hash_key(api_key),
+                scopes: match profile {
+                    RedactionProfile::Admin => vec![Scope::Admin],
+                    RedactionProfile::Analyst => vec![Scope::ReadEvents, Scope::ReadOcr, Scope::ReadFrames],
+                    RedactionProfile::Auditor => vec![Scope::ReadEvents],
+                },
+                profile,
+                rate_limit_per_minute: 0,
+                expires_at: None,
+                revoked: false,
+            })
+            .collect();
+        Authenticator::new(AuthConfig { api_keys, ..Default::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parquet_writer::ParquetWriter;
+    use crate::ocr_data::BoundingBox;
+    use tempfile::TempDir;
+
+    fn create_service(temp_dir: &TempDir) -> QueryService {
+        let mut config = RedactionConfig::default();
+        config.api_keys.insert("admin-key".to_string(), RedactionProfile::Admin);
+        config.api_keys.insert("analyst-key".to_string(), RedactionProfile::Analyst);
+        config.api_keys.insert("auditor-key".to_string(), RedactionProfile::Auditor);
+
+        let ocr_dir = temp_dir.path().join("ocr");
+        let event_dir = temp_dir.path().join("events");
+        QueryService::new(ocr_dir.to_str().unwrap(), event_dir.to_str().unwrap(), config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_profile_enforcement_on_ocr_queries() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut service = create_service(&temp_dir);
+
+        service
+            .ocr_store
+            .write_ocr_results(&[OCRResult {
+                frame_id: "frame_1".to_string(),
+                roi: BoundingBox::new(0.0, 0.0, 200.0, 20.0),
+                text: "Send invoice to billing@example.com".to_string(),
+                language: "en".to_string(),
+                confidence: 0.95,
+                processed_at: Utc::now(),
+                processor: "vision".to_string(),
+            }])
+            .await
+            .unwrap();
+        service.ocr_store.finalize().await.unwrap();
+
+        let admin = service.query_ocr_by_frame_id("admin-key", "frame_1").await.unwrap();
+        assert!(admin.iter().all(|r| r.text.contains("billing@example.com")));
+
+        let analyst = service.query_ocr_by_frame_id("analyst-key", "frame_1").await.unwrap();
+        assert!(analyst.iter().all(|r| !r.text.contains("billing@example.com")));
+
+        assert!(service.query_ocr_by_text("auditor-key", "invoice").await.is_err());
+        assert!(service.query_ocr_by_text("unknown-key", "invoice").await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_queries_are_audited() {
+        let temp_dir = TempDir::new().unwrap();
+        let audit_dir = temp_dir.path().join("audit");
+        let audit_log = QueryAuditLog::new(audit_dir.to_str().unwrap()).unwrap();
+        let service = create_service(&temp_dir).with_audit_log(audit_log);
+        
+        service.query_events_by_confidence("analyst-key", 0.5).await.unwrap();
+        assert!(service.query_ocr_by_text("auditor-key", "invoice").await.is_err());
+        
+        let analyst = QueryService::key_fingerprint("analyst-key");
+        let history = service.review_access_history("admin-key", &analyst).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].operation, "query_events_by_confidence");
+        assert_eq!(history[0].datasets, vec!["events".to_string()]);
+        assert!(history[0].success);
+        
+        let auditor = QueryService::key_fingerprint("auditor-key");
+        let denied = service.review_access_history("admin-key", &auditor).await.unwrap();
+        assert!(!denied[0].success);
+        
+        // Only admins may review access history
+        assert!(service.review_access_history("analyst-key", &analyst).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_now_snapshot_is_redacted() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = SnapshotTracker::new();
+        let service = create_service(&temp_dir).with_snapshot_tracker(tracker.clone());
+        
+        tracker.record_frame(&crate::metadata_collector::FrameMetadata {
+            ts_ns: 1_000,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "/frames/frame_1000.png".to_string(),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: "Mail".to_string(),
+            win_title: "Inbox - jane@example.com".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        });
+        
+        let admin = service.now("admin-key").await.unwrap();
+        assert_eq!(admin.frontmost_app.as_deref(), Some("Mail"));
+        assert_eq!(admin.last_keyframe_path.as_deref(), Some("/frames/frame_1000.png"));
+        
+        let analyst = service.now("analyst-key").await.unwrap();
+        assert_eq!(analyst.window_title.as_deref(), Some("Inbox - [EMAIL]"));
+        
+        let auditor = service.now("auditor-key").await.unwrap();
+        assert!(auditor.window_title.is_none());
+        assert!(auditor.last_keyframe_path.is_none());
+        
+        assert!(service.now("unknown-key").await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_token_scopes_separate_ocr_frames_and_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let frames_dir = temp_dir.path().join("frames");
+        let mut frame_writer = ParquetWriter::new(frames_dir.to_str().unwrap()).unwrap();
+        frame_writer.write_frame_metadata(&[FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "/frames/frame_0.png".to_string(),
+            phash16: 0,
+            entropy: 1.0,
+            app_name: "Mail".to_string(),
+            win_title: "Inbox - jane@example.com".to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        }]).await.unwrap();
+        frame_writer.finalize().await.unwrap();
+        
+        let key = |id: &str, secret: &str, scopes: Vec<Scope>| crate::auth::ApiKeyConfig {
+            id: id.to_string(),
+            key_sha256: hash_key(secret),
+            scopes,
+            profile: RedactionProfile::Analyst,
+            rate_limit_per_minute: 0,
+            expires_at: None,
+            revoked: false,
+        };
+        let config = IndexerConfig {
+            output_dir: frames_dir.to_string_lossy().into_owned(),
+            auth: crate::auth::AuthConfig {
+                api_keys: vec![
+                    key("dashboard", "events-secret", vec![Scope::ReadEvents]),
+                    key("reviewer", "frames-secret", vec![Scope::ReadEvents, Scope::ReadFrames]),
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ocr_dir = temp_dir.path().join("ocr");
+        let event_dir = temp_dir.path().join("events");
+        let service = QueryService::from_config(&config, ocr_dir.to_str().unwrap(), event_dir.to_str().unwrap()).unwrap();
+        
+        // Aggregate events don't open up OCR text or frames
+        assert!(service.query_events_by_confidence("events-secret", 0.0).await.is_ok());
+        assert!(service.query_ocr_by_text("events-secret", "invoice").await.is_err());
+        assert!(service.query_frames_by_app("events-secret", "Mail").await.is_err());
+        assert!(service.query_ocr_by_text("frames-secret", "invoice").await.is_err());
+        
+        let frames = service.query_frames_by_app("frames-secret", "mail").await.unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].win_title, "Inbox - [EMAIL]");
+        
+        // Denied queries are in the access log too
+        let access_log = AccessAuditLog::open(config.access_audit_path()).unwrap().verify(None).unwrap();
+        assert_eq!(access_log.entries, 5);
+    }
+    
+    #[tokio::test]
+    async fn test_suppressions_are_admin_managed() {
+        let temp_dir = TempDir::new().unwrap();
+        let suppressions = SuppressionList::new(Vec::new()).unwrap();
+        let service = create_service(&temp_dir).with_suppressions(suppressions.clone());
+        
+        let rule = SuppressionRule {
+            name: "updates".to_string(),
+            event_types: vec![EventType::ModalAppearance],
+            app: None,
+            message_pattern: Some("(?i)update available".to_string()),
+            expires_at: Some(Utc::now() + chrono::Duration::days(7)),
+            reason: None,
+        };
+        assert!(service.add_suppression("analyst-key", rule.clone()).await.is_err());
+        service.add_suppression("admin-key", rule.clone()).await.unwrap();
+        
+        // The shared list sees the change
+        assert_eq!(suppressions.rules(), vec![rule]);
+        assert_eq!(service.list_suppressions("admin-key").await.unwrap().len(), 1);
+        assert!(service.remove_suppression("admin-key", "updates").await.unwrap());
+        assert!(!service.remove_suppression("admin-key", "updates").await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_redaction_keys_only_get_their_profile_scopes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut service = create_service(&temp_dir)
+            .with_frames_dir(temp_dir.path().to_str().unwrap())
+            .with_suppressions(SuppressionList::new(Vec::new()).unwrap());
+        
+        assert!(service.query_events_by_confidence("auditor-key", 0.0).await.is_ok());
+        assert!(service.query_frames_by_app("auditor-key", "Mail").await.is_err());
+        assert!(service.list_suppressions("auditor-key").await.is_err());
+        
+        // Keys dropped from the mapping stop authenticating
+        let mut config = RedactionConfig::default();
+        config.api_keys.insert("auditor-key".to_string(), RedactionProfile::Auditor);
+        service.update_redaction_config(config).unwrap();
+        assert!(service.query_events_by_confidence("analyst-key", 0.0).await.is_err());
+        assert!(service.query_events_by_confidence("auditor-key", 0.0).await.is_ok());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::error::Result;
+use arrow::record_batch::RecordBatch;
+use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::prelude::{SessionConfig, SessionContext};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::sync::Arc;
+
+/// Memory DataFusion may use for one streamed query; sorts beyond it spill
+/// to temporary files instead of growing
+pub const QUERY_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Rows of a query in result order, read one record batch at a time
+pub type RowStream<T> = BoxStream<'static, Result<T>>;
+
+/// Session whose operators spill to disk past `QUERY_MEMORY_LIMIT`
+pub fn spilling_context() -> Result<SessionContext> {
+    let runtime = RuntimeEnv::new(RuntimeConfig::new().with_memory_limit(QUERY_MEMORY_LIMIT, 1.0))?;
+    Ok(SessionContext::new_with_config_rt(SessionConfig::new(), Arc::new(runtime)))
+}
+
+/// Run `sql` on `ctx` and convert each result batch as it arrives
+pub async fn query_rows<T, F>(ctx: &SessionContext, sql: &str, convert: F) -> Result<RowStream<T>>
+where
+    T: Send + 'static,
+    F: Fn(&RecordBatch) -> Result<Vec<T>> + Send + 'static,
+{
+    let batches = ctx.sql(sql).await?.execute_stream().await?;
+    Ok(rows(batches, convert))
+}
+
+/// Convert each batch of `batches` into rows as it arrives
+pub fn rows<T, F>(batches: SendableRecordBatchStream, convert: F) -> RowStream<T>
+where
+    T: Send + 'static,
+    F: Fn(&RecordBatch) -> Result<Vec<T>> + Send + 'static,
+{
+    batches
+        .map(move |batch| convert(&batch?))
+        .map_ok(|rows| stream::iter(rows.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+}
+
+/// Stream of no rows, for queries over no files
+pub fn empty<T: Send + 'static>() -> RowStream<T> {
+    stream::empty().boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::datasource::MemTable;
+
+    #[tokio::test]
+    async fn test_rows_stream_in_order() {
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+        let batches = (0..4)
+            .map(|i| RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![i * 2, i * 2 + 1]))]).unwrap())
+            .collect();
+        let ctx = spilling_context().unwrap();
+        ctx.register_table("numbers", Arc::new(MemTable::try_new(schema, vec![batches]).unwrap())).unwrap();
+
+        let numbers = |batch: &RecordBatch| -> Result<Vec<i64>> {
+            let column = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+            Ok((0..column.len()).map(|i| column.value(i)).collect())
+        };
+        let mut stream = query_rows(&ctx, "SELECT n FROM numbers WHERE n % 3 = 0 ORDER BY n DESC", numbers).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), 6);
+        let rest: Vec<i64> = stream.try_collect().await.unwrap();
+        assert_eq!(rest, vec![3, 0]);
+
+        let none: Vec<i64> = empty().try_collect().await.unwrap();
+        assert!(none.is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::metadata_collector::{FrameMetadata, MetadataCollector};
+use crate::ocr_data::{BoundingBox, OCRResult};
+use crate::ocr_density::OcrDensity;
+use crate::ocr_parquet_writer::OCRParquetWriter;
+use crate::parquet_writer::ParquetWriter;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const FRAME_WIDTH: u32 = 640;
+const FRAME_HEIGHT: u32 = 400;
+/// Seconds between synthetic frames
+const FRAME_INTERVAL_SECS: i64 = 5;
+
+/// One screen of the synthetic session: app, window and the text on it
+struct ScriptedFrame {
+    app_name: &'static str,
+    win_title: &'static str,
+    /// Text boxes as (text, x, y, width, height, confidence)
+    text: &'static [(&'static str, f32, f32, f32, f32, f32)],
+    /// Red banner behind the first text box, as error dialogs have
+    error_banner: bool,
+}
+
+/// A sign-in attempt that fails once, succeeds and moves on to a report
+const SCRIPT: &[ScriptedFrame] = &[
+    ScriptedFrame {
+        app_name: "Safari",
+        win_title: "Sign in - Example Portal",
+        text: &[
+            ("Sign in", 40.0, 30.0, 160.0, 30.0, 0.98),
+            ("Username", 40.0, 100.0, 120.0, 20.0, 0.96),
+            ("Password", 40.0, 160.0, 120.0, 20.0, 0.95),
+        ],
+        error_banner: false,
+    },
+    ScriptedFrame {
+        app_name: "Safari",
+        win_title: "Sign in - Example Portal",
+        text: &[
+            ("Sign in", 40.0, 30.0, 160.0, 30.0, 0.98),
+            ("Username", 40.0, 100.0, 120.0, 20.0, 0.96),
+            ("jdoe", 200.0, 100.0, 80.0, 20.0, 0.93),
+            ("Password", 40.0, 160.0, 120.0, 20.0, 0.95),
+        ],
+        error_banner: false,
+    },
+    ScriptedFrame {
+        app_name: "Safari",
+        win_title: "Sign in - Example Portal",
+        text: &[
+            ("Error: Invalid password", 40.0, 240.0, 360.0, 24.0, 0.94),
+            ("Username", 40.0, 100.0, 120.0, 20.0, 0.96),
+            ("jdoe", 200.0, 100.0, 80.0, 20.0, 0.93),
+            ("Password", 40.0, 160.0, 120.0, 20.0, 0.95),
+        ],
+        error_banner: true,
+    },
+    ScriptedFrame {
+        app_name: "Safari",
+        win_title: "Dashboard - Example Portal",
+        text: &[
+            ("Welcome back, jdoe", 40.0, 30.0, 300.0, 30.0, 0.97),
+            ("Open invoices: 12", 40.0, 100.0, 260.0, 20.0, 0.92),
+            ("Reports", 40.0, 160.0, 110.0, 20.0, 0.96),
+        ],
+        error_banner: false,
+    },
+    ScriptedFrame {
+        app_name: "Numbers",
+        win_title: "Q3 Invoices.numbers",
+        text: &[
+            ("Q3 Invoices", 40.0, 30.0, 200.0, 30.0, 0.97),
+            ("Total 48,210.00", 40.0, 100.0, 240.0, 20.0, 0.91),
+        ],
+        error_banner: false,
+    },
+];
+
+/// A ready-to-run command against a generated dataset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryExample {
+    pub description: String,
+    pub command: String,
+}
+
+/// Small synthetic dataset (frame images, frame metadata, OCR and events)
+/// for trying out queries and reports without running a recorder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickstartDataset {
+    pub root: PathBuf,
+    pub frames: usize,
+    pub ocr_results: usize,
+    pub events: usize,
+}
+
+impl QuickstartDataset {
+    /// Write the dataset under `root`, which must be missing or empty
+    pub async fn generate(root: &Path) -> Result<Self> {
+        if root.read_dir().map(|mut entries| entries.next().is_some()).unwrap_or(false) {
+            return Err(IndexerError::Config(format!(
+                "Quickstart directory {} is not empty",
+                root.display()
+            )));
+        }
+
+        let dataset = Self {
+            root: root.to_path_buf(),
+            frames: 0,
+            ocr_results: 0,
+            events: 0,
+        };
+        std::fs::create_dir_all(dataset.images_dir())?;
+
+        let start = Self::start_time();
+        let collector = MetadataCollector::new()?;
+        let mut frames = Vec::new();
+        let mut ocr_results = Vec::new();
+        for (index, scripted) in SCRIPT.iter().enumerate() {
+            let path = dataset.images_dir().join(format!("frame_{:04}.png", index));
+            let path_str = path.to_string_lossy().to_string();
+            let timestamp = start + Duration::seconds(index as i64 * FRAME_INTERVAL_SECS);
+
+            render_frame(scripted).save(&path)?;
+
+            let results: Vec<OCRResult> = scripted
+                .text
+                .iter()
+                .map(|(text, x, y, width, height, confidence)| OCRResult {
+                    frame_id: path_str.clone(),
+                    roi: BoundingBox::new(*x, *y, *width, *height),
+                    text: text.to_string(),
+                    language: "en-US".to_string(),
+                    confidence: *confidence,
+                    processed_at: timestamp,
+                    processor: "quickstart".to_string(),
+                })
+                .collect();
+
+            frames.push(FrameMetadata {
+                ts_ns: timestamp.timestamp_nanos_opt().unwrap_or(0),
+                monitor_id: 0,
+                segment_id: "quickstart_monitor0_0001".to_string(),
+                path: path_str.clone(),
+                phash16: collector.calculate_simple_phash(&path_str).await?,
+                entropy: collector.calculate_image_entropy(&path_str).await?,
+                app_name: scripted.app_name.to_string(),
+                win_title: scripted.win_title.to_string(),
+                width: FRAME_WIDTH,
+                height: FRAME_HEIGHT,
+                duplicate_of: None,
+                ocr_density: Some(OcrDensity::from_results(&results, FRAME_WIDTH, FRAME_HEIGHT)),
+                visual: None,
+                screen_type: None,
+            });
+            ocr_results.extend(results);
+        }
+        let events = scripted_events(&frames);
+
+        let mut frame_writer = ParquetWriter::new(&dataset.frames_dir().to_string_lossy())?;
+        frame_writer.write_frame_metadata(&frames).await?;
+        frame_writer.finalize().await?;
+
+        let mut ocr_writer = OCRParquetWriter::new(&dataset.ocr_dir().to_string_lossy())?;
+        ocr_writer.write_ocr_results(&ocr_results).await?;
+        ocr_writer.finalize().await?;
+
+        let mut event_writer = EventParquetWriter::new(&dataset.events_dir().to_string_lossy())?;
+        event_writer.write_events(&events).await?;
+        event_writer.finalize().await?;
+
+        info!(
+            "Generated quickstart dataset in {}: {} frames, {} OCR results, {} events",
+            root.display(), frames.len(), ocr_results.len(), events.len()
+        );
+        Ok(Self {
+            frames: frames.len(),
+            ocr_results: ocr_results.len(),
+            events: events.len(),
+            ..dataset
+        })
+    }
+
+    /// First frame time; fixed so the examples' time ranges always match
+    pub fn start_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap()
+    }
+
+    pub fn images_dir(&self) -> PathBuf {
+        self.root.join("images")
+    }
+
+    pub fn frames_dir(&self) -> PathBuf {
+        self.root.join("frames")
+    }
+
+    pub fn ocr_dir(&self) -> PathBuf {
+        self.root.join("ocr")
+    }
+
+    pub fn events_dir(&self) -> PathBuf {
+        self.root.join("events")
+    }
+
+    /// Commands to try against the dataset: the indexer's own subcommands and
+    /// SQL over the Parquet files with `indexer query` and `datafusion-cli`
+    pub fn query_examples(&self) -> Vec<QueryExample> {
+        let images = self.images_dir().display().to_string();
+        let frames = self.frames_dir().display().to_string();
+        let ocr = self.ocr_dir().display().to_string();
+        let events = self.events_dir().display().to_string();
+        let sql = |table: &str, location: &str, query: &str| {
+            format!(
+                "datafusion-cli -c \"CREATE EXTERNAL TABLE {} STORED AS PARQUET LOCATION '{}/'\" -c \"{}\"",
+                table, location, query
+            )
+        };
+
+        vec![
+            QueryExample {
+                description: "Analyze every frame image with its stored OCR".to_string(),
+                command: format!("indexer process {} --ocr-dir {}", images, ocr),
+            },
+            QueryExample {
+                description: "Inspect the frame showing the sign-in error".to_string(),
+                command: format!("indexer index-image {}/frame_0002.png --ocr-dir {}", images, ocr),
+            },
+            QueryExample {
+                description: "Time spent per application, with OCR text coverage".to_string(),
+                command: sql(
+                    "frames",
+                    &frames,
+                    "SELECT app_name, COUNT(*) AS frames, AVG(ocr_text_coverage) AS coverage FROM frames GROUP BY app_name",
+                ),
+            },
+            QueryExample {
+                description: "Full-text search over OCR results".to_string(),
+                command: sql(
+                    "ocr",
+                    &ocr,
+                    "SELECT frame_id, text, confidence FROM ocr WHERE text LIKE '%Invalid%'",
+                ),
+            },
+            QueryExample {
+                description: "Error displays with their severity, via a canned query".to_string(),
+                command: format!("indexer query --canned errors --events-dir {}", events),
+            },
+            QueryExample {
+                description: format!(
+                    "Events in time order (type codes are listed in {}/_enum_codes.json)",
+                    events
+                ),
+                command: sql(
+                    "events",
+                    &events,
+                    "SELECT ts_ns, type_code, target, value_to, confidence FROM events ORDER BY ts_ns",
+                ),
+            },
+        ]
+    }
+}
+
+/// Grey blocks stand in for the text lines so image metrics see some structure
+fn render_frame(scripted: &ScriptedFrame) -> RgbImage {
+    let mut image = RgbImage::from_pixel(FRAME_WIDTH, FRAME_HEIGHT, Rgb([245, 245, 245]));
+    for (index, (_, x, y, width, height, _)) in scripted.text.iter().enumerate() {
+        let colour = if scripted.error_banner && index == 0 {
+            Rgb([200, 40, 40])
+        } else {
+            Rgb([60, 60, 60])
+        };
+        let (x0, y0) = (*x as u32, *y as u32);
+        let x1 = ((x + width) as u32).min(FRAME_WIDTH);
+        let y1 = ((y + height) as u32).min(FRAME_HEIGHT);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                image.put_pixel(px, py, colour);
+            }
+        }
+    }
+    image
+}
+
+/// The events a detector would report for the script
+fn scripted_events(frames: &[FrameMetadata]) -> Vec<DetectedEvent> {
+    let event = |index: usize, event_type: EventType, target: &str, from: Option<&str>, to: Option<&str>, confidence: f32| {
+        let frame = &frames[index];
+        DetectedEvent {
+            id: format!("quickstart_event_{}", index),
+            timestamp: DateTime::from_timestamp_nanos(frame.ts_ns),
+            event_type,
+            target: target.to_string(),
+            value_from: from.map(str::to_string),
+            value_to: to.map(str::to_string),
+            confidence,
+            evidence_frames: vec![frame.path.clone()],
+            metadata: HashMap::from([("app_name".to_string(), frame.app_name.clone())]),
+        }
+    };
+
+    vec![
+        event(1, EventType::FieldChange, "Username", None, Some("jdoe"), 0.93),
+        event(2, EventType::ErrorDisplay, "Error: Invalid password", None, Some("Invalid password"), 0.94),
+        event(3, EventType::Navigation, "Example Portal", Some("Sign in"), Some("Dashboard"), 0.9),
+        event(4, EventType::Navigation, "application", Some("Safari"), Some("Numbers"), 0.95),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_generate_quickstart_dataset() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("quickstart");
+
+        let dataset = QuickstartDataset::generate(&root).await.unwrap();
+        assert_eq!(dataset.frames, SCRIPT.len());
+        assert_eq!(dataset.events, 4);
+        assert!(dataset.images_dir().join("frame_0000.png").exists());
+
+        let ocr = OCRParquetWriter::new(&dataset.ocr_dir().to_string_lossy()).unwrap();
+        let hits = ocr.query_by_text("Invalid password").await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].frame_id.ends_with("frame_0002.png"));
+
+        let events = EventParquetWriter::new(&dataset.events_dir().to_string_lossy()).unwrap();
+        assert_eq!(events.query_by_type(&EventType::ErrorDisplay).await.unwrap().len(), 1);
+
+        let examples = dataset.query_examples();
+        assert!(examples.iter().all(|e| e.command.contains(&*root.to_string_lossy())));
+
+        // Generating twice into the same directory would duplicate the data
+        assert!(QuickstartDataset::generate(&root).await.is_err());
+    }
+}
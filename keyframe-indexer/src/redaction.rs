@@ -0,0 +1,395 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::DetectedEvent;
+use crate::event_signing::SIGNATURE_METADATA_KEY;
+use crate::live_snapshot::LiveSnapshot;
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::text_diff::{DiffGranularity, TextDiff};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use tracing::warn;
+
+/// Redaction profile applied to query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionProfile {
+    /// Full OCR text and event values
+    Admin,
+    /// OCR text and event values with sensitive entities masked
+    Analyst,
+    /// Event metadata only; no screen text
+    Auditor,
+}
+
+impl RedactionProfile {
+    /// Whether this profile may search or read OCR text at all
+    pub fn can_read_ocr(&self) -> bool {
+        !matches!(self, RedactionProfile::Auditor)
+    }
+}
+
+impl fmt::Display for RedactionProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedactionProfile::Admin => write!(f, "admin"),
+            RedactionProfile::Analyst => write!(f, "analyst"),
+            RedactionProfile::Auditor => write!(f, "auditor"),
+        }
+    }
+}
+
+/// Mapping of API keys to redaction profiles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// API key to profile assignments
+    pub api_keys: HashMap<String, RedactionProfile>,
+}
+
+impl RedactionConfig {
+    /// Resolve the profile for an API key, rejecting unknown keys
+    pub fn profile_for_key(&self, api_key: &str) -> Result<RedactionProfile> {
+        self.api_keys
+            .get(api_key)
+            .copied()
+            .ok_or_else(|| IndexerError::AccessDenied("Unknown API key".to_string()))
+    }
+}
+
+struct EntityPattern {
+    regex: Regex,
+    replacement: &'static str,
+}
+
+/// Applies redaction profiles to OCR results and detected events
+pub struct Redactor {
+    entity_patterns: Vec<EntityPattern>,
+}
+
+/// Placeholder used where a profile hides a value entirely
+pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+
+/// Event metadata auditors still see: classifications, geometry, counts and
+/// app names. Any other key may carry a window title or screen text.
+const AUDITOR_METADATA_KEYS: &[&str] = &[
+    "app_name", "current_app", "previous_app", "from_app", "to_app",
+    "bundle_id", "current_bundle_id", "previous_bundle_id", "from_bundle_id", "to_bundle_id",
+    "app_extractor", "button", "build_kind", "cells_changed", "change_type", "click_count", "click_type",
+    "detection_method", "diff_deleted_chars", "diff_granularity", "diff_inserted_chars", "diff_kind",
+    "display_duration_ms",
+    "duration_ms", "error_modal_type", "event_type", "exit_code", "exit_status", "frames_seen",
+    "gesture_kind", "gesture_phase", "input_source", "language", "match_score", "merged_event_count",
+    "modifiers", "navigation_type", "paste_evidence", "processor", "progress_outcome",
+    "roi_height", "roi_width", "roi_x", "roi_y", "rows_added", "rows_removed",
+    "screen_height", "screen_id", "screen_type", "screen_width",
+    "scroll_direction", "scroll_dx", "scroll_dy", "severity", "status", "table_columns", "table_rows",
+];
+
+impl Redactor {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            entity_patterns: Self::compile_entity_patterns()?,
+        })
+    }
+
+    /// Mask sensitive entities (emails, card numbers, phone numbers, ...) in text
+    pub fn mask_entities(&self, text: &str) -> String {
+        let mut masked = text.to_string();
+        for pattern in &self.entity_patterns {
+            masked = pattern.regex.replace_all(&masked, pattern.replacement).into_owned();
+        }
+        masked
+    }
+
+    /// Apply a profile to OCR results
+    pub fn redact_ocr_results(
+        &self,
+        profile: RedactionProfile,
+        results: Vec<OCRResult>,
+    ) -> Result<Vec<OCRResult>> {
+        match profile {
+            RedactionProfile::Admin => Ok(results),
+            RedactionProfile::Analyst => Ok(results
+                .into_iter()
+                .map(|mut result| {
+                    result.text = self.mask_entities(&result.text);
+                    result
+                })
+                .collect()),
+            RedactionProfile::Auditor => Err(IndexerError::AccessDenied(
+                "Auditor profile cannot access OCR text".to_string(),
+            )),
+        }
+    }
+
+    /// Apply a profile to detected events
+    pub fn redact_events(&self, profile: RedactionProfile, events: Vec<DetectedEvent>) -> Vec<DetectedEvent> {
+        match profile {
+            RedactionProfile::Admin => events,
+            RedactionProfile::Analyst => events
+                .into_iter()
+                .map(|mut event| {
+                    event.target = self.mask_entities(&event.target);
+                    event.value_from = event.value_from.map(|v| self.mask_entities(&v));
+                    event.value_to = event.value_to.map(|v| self.mask_entities(&v));
+                    event.metadata = self.redact_metadata(profile, event.metadata);
+                    self.rediff_masked_values(&mut event);
+                    event
+                })
+                .collect(),
+            RedactionProfile::Auditor => events
+                .into_iter()
+                .map(|mut event| {
+                    event.target = REDACTED_PLACEHOLDER.to_string();
+                    event.value_from = None;
+                    event.value_to = None;
+                    event.metadata = self.redact_metadata(profile, event.metadata);
+                    event
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply a profile to event metadata, which holds window titles and OCR
+    /// text as well; auditors keep only the keys that hold neither
+    pub fn redact_metadata(&self, profile: RedactionProfile, metadata: HashMap<String, String>) -> HashMap<String, String> {
+        match profile {
+            RedactionProfile::Admin => metadata,
+            // Masked events no longer match their signature
+            RedactionProfile::Analyst => metadata
+                .into_iter()
+                .filter(|(key, _)| key != SIGNATURE_METADATA_KEY)
+                .map(|(key, value)| {
+                    let value = self.mask_entities(&value);
+                    (key, value)
+                })
+                .collect(),
+            RedactionProfile::Auditor => metadata
+                .into_iter()
+                .filter(|(key, _)| AUDITOR_METADATA_KEYS.contains(&key.as_str()))
+                .collect(),
+        }
+    }
+
+    /// Apply a profile to frame metadata; window titles are treated like event targets
+    pub fn redact_frames(&self, profile: RedactionProfile, frames: Vec<FrameMetadata>) -> Vec<FrameMetadata> {
+        match profile {
+            RedactionProfile::Admin => frames,
+            RedactionProfile::Analyst => frames
+                .into_iter()
+                .map(|mut frame| {
+                    frame.win_title = self.mask_entities(&frame.win_title);
+                    frame
+                })
+                .collect(),
+            RedactionProfile::Auditor => frames
+                .into_iter()
+                .map(|mut frame| {
+                    frame.win_title = REDACTED_PLACEHOLDER.to_string();
+                    frame
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply a profile to a live snapshot; auditors do not see window titles
+    pub fn redact_snapshot(&self, profile: RedactionProfile, mut snapshot: LiveSnapshot) -> LiveSnapshot {
+        match profile {
+            RedactionProfile::Admin => {}
+            RedactionProfile::Analyst => {
+                snapshot.frontmost_app = snapshot.frontmost_app.map(|a| self.mask_entities(&a));
+                snapshot.window_title = snapshot.window_title.map(|t| self.mask_entities(&t));
+                snapshot.last_keyframe_path = snapshot.last_keyframe_path.map(|p| self.mask_entities(&p));
+            }
+            RedactionProfile::Auditor => {
+                snapshot.window_title = None;
+                snapshot.last_keyframe_path = None;
+            }
+        }
+        snapshot.recent_events = self.redact_events(profile, snapshot.recent_events);
+        snapshot
+    }
+
+    // MARK: - Private Helper Methods
+
+    /// Masking the ops JSON misses entities split across ops, so the diff is
+    /// recomputed from the already masked values
+    fn rediff_masked_values(&self, event: &mut DetectedEvent) {
+        if !event.metadata.contains_key("diff_ops") {
+            return;
+        }
+        match (&event.value_from, &event.value_to) {
+            (Some(from), Some(to)) => {
+                let granularity = event
+                    .metadata
+                    .get("diff_granularity")
+                    .map(String::as_str)
+                    .and_then(DiffGranularity::parse)
+                    .unwrap_or_default();
+                TextDiff::compute(from, to, granularity).write_metadata(&mut event.metadata);
+            }
+            _ => {
+                event.metadata.remove("diff_ops");
+            }
+        }
+    }
+
+    fn compile_entity_patterns() -> Result<Vec<EntityPattern>> {
+        // Order matters: more specific patterns run before the generic phone pattern
+        let patterns = vec![
+            (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[EMAIL]"),
+            (r"\b(?:\d[ -]?){12,18}\d\b", "[CARD]"),
+            (r"\b\d{3}-\d{2}-\d{4}\b", "[SSN]"),
+            (r"\b(?:\d{1,3}\.){3}\d{1,3}\b", "[IP]"),
+            (r"\+?\d{0,3}[ .-]?\(?\d{3}\)?[ .-]?\d{3}[ .-]?\d{4}\b", "[PHONE]"),
+            (r"(?i)\b(password|passwd|pwd|token|secret)(\s*[:=]\s*)\S+", "$1$2[SECRET]"),
+        ];
+
+        let mut compiled = Vec::new();
+        for (pattern, replacement) in patterns {
+            match Regex::new(pattern) {
+                Ok(regex) => compiled.push(EntityPattern { regex, replacement }),
+                Err(e) => {
+                    warn!("Failed to compile redaction pattern '{}': {}", pattern, e);
+                }
+            }
+        }
+
+        Ok(compiled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use crate::ocr_data::BoundingBox;
+    use chrono::Utc;
+
+    fn create_ocr_result(text: &str) -> OCRResult {
+        OCRResult {
+            frame_id: "frame_1".to_string(),
+            roi: BoundingBox::new(0.0, 0.0, 100.0, 20.0),
+            text: text.to_string(),
+            language: "en".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entity_masking() {
+        let redactor = Redactor::new().unwrap();
+
+        assert_eq!(redactor.mask_entities("Contact jane.doe@example.com"), "Contact [EMAIL]");
+        assert_eq!(redactor.mask_entities("Card 4111 1111 1111 1111"), "Card [CARD]");
+        assert_eq!(redactor.mask_entities("SSN 123-45-6789"), "SSN [SSN]");
+        assert_eq!(redactor.mask_entities("Host 192.168.1.20"), "Host [IP]");
+        assert_eq!(redactor.mask_entities("password: hunter2"), "password: [SECRET]");
+        assert_eq!(redactor.mask_entities("Save changes"), "Save changes");
+    }
+
+    #[test]
+    fn test_profiles_on_ocr_results() {
+        let redactor = Redactor::new().unwrap();
+        let results = vec![create_ocr_result("Email me at bob@example.com")];
+
+        let admin = redactor.redact_ocr_results(RedactionProfile::Admin, results.clone()).unwrap();
+        assert_eq!(admin[0].text, "Email me at bob@example.com");
+
+        let analyst = redactor.redact_ocr_results(RedactionProfile::Analyst, results.clone()).unwrap();
+        assert_eq!(analyst[0].text, "Email me at [EMAIL]");
+
+        assert!(redactor.redact_ocr_results(RedactionProfile::Auditor, results).is_err());
+    }
+
+    #[test]
+    fn test_auditor_sees_only_event_metadata() {
+        let redactor = Redactor::new().unwrap();
+        let event = DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::FieldChange,
+            target: "email_field".to_string(),
+            value_from: Some("old@example.com".to_string()),
+            value_to: Some("new@example.com".to_string()),
+            confidence: 0.8,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata: HashMap::from([
+                ("roi_x".to_string(), "10".to_string()),
+                ("current_window".to_string(), "Inbox - new@example.com".to_string()),
+                ("ocr".to_string(), "Email: new@example.com".to_string()),
+            ]),
+        };
+
+        let redacted = redactor.redact_events(RedactionProfile::Auditor, vec![event.clone()]);
+        assert_eq!(redacted[0].target, REDACTED_PLACEHOLDER);
+        assert!(redacted[0].value_from.is_none());
+        assert_eq!(redacted[0].evidence_frames, event.evidence_frames);
+        assert_eq!(redacted[0].metadata, HashMap::from([("roi_x".to_string(), "10".to_string())]));
+
+        let masked = redactor.redact_events(RedactionProfile::Analyst, vec![event]);
+        assert_eq!(masked[0].value_to.as_deref(), Some("[EMAIL]"));
+        assert_eq!(masked[0].metadata["current_window"], "Inbox - [EMAIL]");
+        assert_eq!(masked[0].metadata["ocr"], "Email: [EMAIL]");
+    }
+
+    #[test]
+    fn test_analyst_snapshot_masks_every_text_field() {
+        let redactor = Redactor::new().unwrap();
+        let snapshot = LiveSnapshot {
+            frontmost_app: Some("Mail".to_string()),
+            window_title: Some("Inbox - jane@example.com".to_string()),
+            last_keyframe_path: Some("/keyframes/jane@example.com/frame_1.png".to_string()),
+            ..Default::default()
+        };
+
+        let masked = redactor.redact_snapshot(RedactionProfile::Analyst, snapshot.clone());
+        assert_eq!(masked.frontmost_app.as_deref(), Some("Mail"));
+        assert_eq!(masked.window_title.as_deref(), Some("Inbox - [EMAIL]"));
+        assert_eq!(masked.last_keyframe_path.as_deref(), Some("/keyframes/[EMAIL]/frame_1.png"));
+
+        let audited = redactor.redact_snapshot(RedactionProfile::Auditor, snapshot);
+        assert!(audited.window_title.is_none());
+        assert!(audited.last_keyframe_path.is_none());
+    }
+
+    #[test]
+    fn test_diff_ops_do_not_leak_masked_values() {
+        let redactor = Redactor::new().unwrap();
+        let from = "Contact: ";
+        let to = "Contact: jane@example.com";
+        let mut metadata = HashMap::new();
+        TextDiff::compute(from, to, DiffGranularity::Character).write_metadata(&mut metadata);
+        let event = DetectedEvent {
+            id: "event_1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::FieldChange,
+            target: "field_1".to_string(),
+            value_from: Some(from.to_string()),
+            value_to: Some(to.to_string()),
+            confidence: 0.8,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata,
+        };
+
+        let masked = redactor.redact_events(RedactionProfile::Analyst, vec![event.clone()]);
+        assert!(!masked[0].metadata["diff_ops"].contains("jane"));
+        assert!(masked[0].metadata["diff_ops"].contains("[EMAIL]"));
+
+        let audited = redactor.redact_events(RedactionProfile::Auditor, vec![event]);
+        assert!(!audited[0].metadata.contains_key("diff_ops"));
+        assert_eq!(audited[0].metadata["diff_kind"], "append");
+    }
+
+    #[test]
+    fn test_unknown_api_key_rejected() {
+        let mut config = RedactionConfig::default();
+        config.api_keys.insert("key-analyst".to_string(), RedactionProfile::Analyst);
+
+        assert_eq!(config.profile_for_key("key-analyst").unwrap(), RedactionProfile::Analyst);
+        assert!(config.profile_for_key("missing").is_err());
+    }
+}
@@ -0,0 +1,584 @@
+use crate::audio_analyzer::MeetingInterval;
+use crate::error::{IndexerError, Result};
+use crate::event_correlator::CorrelationConfig;
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::event_parquet_writer::EventParquetWriter;
+use crate::focus_summary::{format_minutes, local_day_bounds, AppUsage};
+use crate::markdown_exporter::{app_timeline, describe_event, thumbnail_for, AppSpan, NotableEvent};
+use crate::modal_lifetime;
+use crate::suppression::SuppressionList;
+use crate::workflow_chain::{chains_from_events, WorkflowChain, WorkflowChainConfig, WorkflowChainParquetWriter};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use tracing::info;
+
+/// Session report settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    /// Directory holding keyframe images, used for error thumbnails
+    pub frames_dir: Option<String>,
+    /// Gaps between app switches longer than this count as idle
+    pub idle_gap_minutes: i64,
+    /// Most errors listed; the rest are only counted
+    pub max_errors: usize,
+    /// Fewest steps a workflow needs to be listed
+    pub min_workflow_steps: usize,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            frames_dir: None,
+            idle_gap_minutes: 10,
+            max_errors: 50,
+            min_workflow_steps: 3,
+        }
+    }
+}
+
+impl ReportConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.idle_gap_minutes <= 0 {
+            return Err(IndexerError::Config("report idle_gap_minutes must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// One recording session rolled up for reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub active_minutes: i64,
+    /// Every app used, most time first
+    pub app_usage: Vec<AppUsage>,
+    /// App switches in time order
+    pub navigation: Vec<AppSpan>,
+    pub errors: Vec<NotableEvent>,
+    /// Errors beyond `max_errors`
+    pub omitted_errors: usize,
+    /// Workflow chains with at least `min_workflow_steps` steps, in time order
+    pub workflows: Vec<WorkflowChain>,
+    /// Calls during the session; their errors and workflows are left out
+    #[serde(default)]
+    pub meetings: Vec<MeetingInterval>,
+    /// Events and errors per screen type, most events first
+    #[serde(default)]
+    pub screen_types: Vec<ScreenTypeActivity>,
+}
+
+/// Activity on one kind of screen, from the `screen_type` of its events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenTypeActivity {
+    pub screen_type: String,
+    pub events: usize,
+    pub errors: usize,
+}
+
+impl SessionReport {
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Session report {} – {}\n", local_time(self.start), local_time(self.end));
+        let _ = writeln!(md, "- Active time: {}", format_minutes(self.active_minutes));
+        let _ = writeln!(md, "- Errors: {}", self.errors.len() + self.omitted_errors);
+        let _ = writeln!(md, "- Workflows: {}", self.workflows.len());
+        if !self.meetings.is_empty() {
+            let _ = writeln!(md, "- Meetings: {} ({})", self.meetings.len(), format_minutes(self.meeting_minutes()));
+        }
+
+        let _ = writeln!(md, "\n## Time per application\n");
+        let _ = writeln!(md, "| Application | Time | Share |");
+        let _ = writeln!(md, "|---|---|---|");
+        for usage in &self.app_usage {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {:.0}% |",
+                usage.app_name.replace('|', "\\|"),
+                format_minutes(usage.minutes),
+                self.share(usage)
+            );
+        }
+
+        if !self.screen_types.is_empty() {
+            let _ = writeln!(md, "\n## Screen types\n");
+            let _ = writeln!(md, "| Screen type | Events | Errors |");
+            let _ = writeln!(md, "|---|---|---|");
+            for activity in &self.screen_types {
+                let _ = writeln!(md, "| {} | {} | {} |", activity.screen_type, activity.events, activity.errors);
+            }
+        }
+
+        let _ = writeln!(md, "\n## Navigation\n");
+        for span in &self.navigation {
+            let _ = writeln!(
+                md,
+                "- {}–{} **{}** ({})",
+                span.start.with_timezone(&Local).format("%H:%M"),
+                span.end.with_timezone(&Local).format("%H:%M"),
+                span.app_name,
+                format_minutes((span.end - span.start).num_minutes())
+            );
+        }
+
+        let _ = writeln!(md, "\n## Errors\n");
+        for error in &self.errors {
+            let _ = writeln!(md, "- {} {}", error.timestamp.with_timezone(&Local).format("%H:%M:%S"), error.description);
+            if let Some(thumbnail) = &error.thumbnail {
+                let _ = writeln!(md, "  ![frame](<{}>)", thumbnail);
+            }
+        }
+        if self.omitted_errors > 0 {
+            let _ = writeln!(md, "- _{} more_", self.omitted_errors);
+        }
+
+        if !self.meetings.is_empty() {
+            let _ = writeln!(md, "\n## Meetings\n");
+            for meeting in &self.meetings {
+                let _ = writeln!(
+                    md,
+                    "- {}–{} {}",
+                    meeting.start.with_timezone(&Local).format("%H:%M"),
+                    meeting.end.with_timezone(&Local).format("%H:%M"),
+                    meeting.app
+                );
+            }
+        }
+
+        let _ = writeln!(md, "\n## Workflows\n");
+        for chain in &self.workflows {
+            let _ = writeln!(
+                md,
+                "- {} {} ({}, {:.1}s)",
+                chain.start.with_timezone(&Local).format("%H:%M:%S"),
+                workflow_steps(chain),
+                chain.outcome.as_str(),
+                chain.duration_ms() as f64 / 1000.0
+            );
+        }
+        md
+    }
+
+    /// Self-contained HTML page; thumbnails link to the keyframe files
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = format!("Session report {} – {}", local_time(self.start), local_time(self.end));
+        let _ = writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+        let _ = writeln!(html, "<title>{}</title>", escape_html(&title));
+        let _ = writeln!(
+            html,
+            "<style>body{{font-family:sans-serif;max-width:960px;margin:2em auto}}\
+             table{{border-collapse:collapse}}td,th{{padding:4px 12px;text-align:left}}\
+             .bar{{background:#4a90d9;height:10px}}img{{max-width:320px;display:block;margin:4px 0}}</style>"
+        );
+        let _ = writeln!(html, "</head>\n<body>\n<h1>{}</h1>", escape_html(&title));
+        let _ = writeln!(
+            html,
+            "<p>Active time: {} &middot; Errors: {} &middot; Workflows: {}</p>",
+            format_minutes(self.active_minutes),
+            self.errors.len() + self.omitted_errors,
+            self.workflows.len()
+        );
+
+        let _ = writeln!(html, "<h2>Time per application</h2>\n<table>");
+        for usage in &self.app_usage {
+            let share = self.share(usage);
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td><div class=\"bar\" style=\"width:{:.0}px\"></div></td><td>{:.0}%</td></tr>",
+                escape_html(&usage.app_name),
+                format_minutes(usage.minutes),
+                share * 3.0,
+                share
+            );
+        }
+        let _ = writeln!(html, "</table>");
+
+        if !self.screen_types.is_empty() {
+            let _ = writeln!(html, "<h2>Screen types</h2>\n<table>\n<tr><th>Screen type</th><th>Events</th><th>Errors</th></tr>");
+            for activity in &self.screen_types {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&activity.screen_type),
+                    activity.events,
+                    activity.errors
+                );
+            }
+            let _ = writeln!(html, "</table>");
+        }
+
+        let _ = writeln!(html, "<h2>Navigation</h2>\n<ol>");
+        for span in &self.navigation {
+            let _ = writeln!(
+                html,
+                "<li>{}–{} <b>{}</b> ({})</li>",
+                span.start.with_timezone(&Local).format("%H:%M"),
+                span.end.with_timezone(&Local).format("%H:%M"),
+                escape_html(&span.app_name),
+                format_minutes((span.end - span.start).num_minutes())
+            );
+        }
+        let _ = writeln!(html, "</ol>");
+
+        let _ = writeln!(html, "<h2>Errors</h2>\n<ul>");
+        for error in &self.errors {
+            let _ = write!(
+                html,
+                "<li>{} {}",
+                error.timestamp.with_timezone(&Local).format("%H:%M:%S"),
+                escape_html(&error.description)
+            );
+            if let Some(thumbnail) = &error.thumbnail {
+                let _ = write!(html, "<img src=\"{}\" alt=\"frame\">", escape_html(&file_url(thumbnail)));
+            }
+            let _ = writeln!(html, "</li>");
+        }
+        if self.omitted_errors > 0 {
+            let _ = writeln!(html, "<li><i>{} more</i></li>", self.omitted_errors);
+        }
+        let _ = writeln!(html, "</ul>");
+
+        if !self.meetings.is_empty() {
+            let _ = writeln!(
+                html,
+                "<h2>Meetings</h2>\n<p>{} meetings, {}</p>\n<ul>",
+                self.meetings.len(),
+                format_minutes(self.meeting_minutes())
+            );
+            for meeting in &self.meetings {
+                let _ = writeln!(
+                    html,
+                    "<li>{}–{} {}</li>",
+                    meeting.start.with_timezone(&Local).format("%H:%M"),
+                    meeting.end.with_timezone(&Local).format("%H:%M"),
+                    escape_html(&meeting.app)
+                );
+            }
+            let _ = writeln!(html, "</ul>");
+        }
+
+        let _ = writeln!(html, "<h2>Workflows</h2>\n<ul>");
+        for chain in &self.workflows {
+            let _ = writeln!(
+                html,
+                "<li>{} {} ({}, {:.1}s)</li>",
+                chain.start.with_timezone(&Local).format("%H:%M:%S"),
+                escape_html(&workflow_steps(chain)),
+                chain.outcome.as_str(),
+                chain.duration_ms() as f64 / 1000.0
+            );
+        }
+        let _ = writeln!(html, "</ul>\n</body>\n</html>");
+        html
+    }
+
+    fn meeting_minutes(&self) -> i64 {
+        self.meetings.iter().map(|m| (m.end - m.start).num_seconds()).sum::<i64>() / 60
+    }
+
+    /// Percentage of the active time spent in an app
+    fn share(&self, usage: &AppUsage) -> f64 {
+        if self.active_minutes == 0 {
+            0.0
+        } else {
+            usage.minutes as f64 * 100.0 / self.active_minutes as f64
+        }
+    }
+}
+
+/// Builds session reports from stored events and workflow chains
+pub struct ReportGenerator {
+    config: ReportConfig,
+    suppressions: Option<SuppressionList>,
+    meetings: Vec<MeetingInterval>,
+    correlation: CorrelationConfig,
+    chains: WorkflowChainConfig,
+}
+
+impl ReportGenerator {
+    pub fn new(config: ReportConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            suppressions: None,
+            meetings: Vec::new(),
+            correlation: CorrelationConfig::default(),
+            chains: WorkflowChainConfig::default(),
+        })
+    }
+
+    /// Settings used to chain the session's events when no chains were stored for it
+    pub fn with_workflow_config(mut self, correlation: CorrelationConfig, chains: WorkflowChainConfig) -> Self {
+        self.correlation = correlation;
+        self.chains = chains;
+        self
+    }
+
+    /// Leave events muted by suppression rules out of reports
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Leave errors and workflows during these meetings out of reports and list
+    /// the meetings instead; app switches still count towards time per app
+    pub fn with_meetings(mut self, meetings: Vec<MeetingInterval>) -> Self {
+        self.meetings = meetings;
+        self
+    }
+
+    /// Aggregate the events and workflow chains of the session from `start` to `end`
+    pub fn build(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        events: &[DetectedEvent],
+        chains: &[WorkflowChain],
+    ) -> SessionReport {
+        let unsuppressed;
+        let events = match &self.suppressions {
+            Some(suppressions) => {
+                unsuppressed = suppressions.filter_events(events);
+                &unsuppressed[..]
+            }
+            None => events,
+        };
+        let in_meeting = |time: DateTime<Utc>| self.meetings.iter().any(|m| m.contains(time));
+        let mut events: Vec<&DetectedEvent> = events
+            .iter()
+            .filter(|e| e.timestamp >= start && e.timestamp < end)
+            .filter(|e| e.event_type == EventType::Navigation || !in_meeting(e.timestamp))
+            .collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let navigation = app_timeline(&events, Duration::minutes(self.config.idle_gap_minutes));
+        let mut seconds_by_app: HashMap<&str, i64> = HashMap::new();
+        for span in &navigation {
+            *seconds_by_app.entry(span.app_name.as_str()).or_insert(0) += (span.end - span.start).num_seconds();
+        }
+        let active_minutes = seconds_by_app.values().sum::<i64>() / 60;
+        let mut app_usage: Vec<AppUsage> = seconds_by_app
+            .into_iter()
+            .map(|(app_name, seconds)| AppUsage { app_name: app_name.to_string(), minutes: seconds / 60 })
+            .collect();
+        app_usage.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.app_name.cmp(&b.app_name)));
+
+        let mut by_screen_type: HashMap<&str, ScreenTypeActivity> = HashMap::new();
+        for event in &events {
+            if let Some(screen_type) = event.metadata.get("screen_type") {
+                let activity = by_screen_type.entry(screen_type.as_str()).or_insert_with(|| ScreenTypeActivity {
+                    screen_type: screen_type.clone(),
+                    events: 0,
+                    errors: 0,
+                });
+                activity.events += 1;
+                if modal_lifetime::is_error(event) {
+                    activity.errors += 1;
+                }
+            }
+        }
+        let mut screen_types: Vec<ScreenTypeActivity> = by_screen_type.into_values().collect();
+        screen_types.sort_by(|a, b| b.events.cmp(&a.events).then_with(|| a.screen_type.cmp(&b.screen_type)));
+
+        let mut errors: Vec<NotableEvent> = events
+            .iter()
+            .filter(|e| modal_lifetime::is_error(e))
+            .map(|e| NotableEvent {
+                timestamp: e.timestamp,
+                event_type: e.event_type.clone(),
+                description: describe_event(e),
+                thumbnail: e
+                    .evidence_frames
+                    .iter()
+                    .find_map(|frame| thumbnail_for(frame, self.config.frames_dir.as_deref())),
+            })
+            .collect();
+        let omitted_errors = errors.len().saturating_sub(self.config.max_errors);
+        errors.truncate(self.config.max_errors);
+
+        let mut workflows: Vec<WorkflowChain> = chains
+            .iter()
+            .filter(|c| c.start >= start && c.start < end && c.steps.len() >= self.config.min_workflow_steps)
+            .filter(|c| !in_meeting(c.start))
+            .cloned()
+            .collect();
+        workflows.sort_by_key(|c| c.start);
+
+        SessionReport {
+            start,
+            end,
+            active_minutes,
+            app_usage,
+            navigation,
+            errors,
+            omitted_errors,
+            workflows,
+            meetings: self.meetings.iter().filter(|m| m.start < end && m.end > start).cloned().collect(),
+            screen_types,
+        }
+    }
+
+    /// Load a session from the stores. Without stored workflow chains for the
+    /// session, chains are built from its events
+    pub async fn generate(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        event_store: &EventParquetWriter,
+        chain_store: Option<&WorkflowChainParquetWriter>,
+    ) -> Result<SessionReport> {
+        let events = event_store.query_by_time_range(start, end).await?;
+        let mut chains = match chain_store {
+            Some(store) => store.query_by_time_range(start, end).await?,
+            None => Vec::new(),
+        };
+        if chains.is_empty() {
+            chains = chains_from_events(&events, self.correlation.clone(), self.chains.clone())?;
+        }
+        let report = self.build(start, end, &events, &chains);
+        info!(
+            "Built session report: {} apps, {} errors, {} workflows",
+            report.app_usage.len(),
+            report.errors.len() + report.omitted_errors,
+            report.workflows.len()
+        );
+        Ok(report)
+    }
+
+    /// Report on one local calendar day
+    pub async fn generate_day(
+        &self,
+        date: NaiveDate,
+        event_store: &EventParquetWriter,
+        chain_store: Option<&WorkflowChainParquetWriter>,
+    ) -> Result<SessionReport> {
+        let (start, end) = local_day_bounds(date)?;
+        self.generate(start, end, event_store, chain_store).await
+    }
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn workflow_steps(chain: &WorkflowChain) -> String {
+    chain.step_kinds().iter().map(|kind| kind.as_str()).collect::<Vec<_>>().join(" → ")
+}
+
+fn file_url(path: &str) -> String {
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        path.to_string()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow_chain::{WorkflowOutcome, WorkflowStep, WorkflowStepKind};
+    use chrono::TimeZone;
+
+    fn event(minute: i64, event_type: EventType, target: &str, app: Option<&str>) -> DetectedEvent {
+        DetectedEvent {
+            id: format!("event_{}", minute),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap() + Duration::minutes(minute),
+            event_type,
+            target: target.to_string(),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: vec![format!("/frames/frame_{}.png", minute)],
+            metadata: app.map(|app| [("current_app".to_string(), app.to_string())].into()).unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_session_report_sections() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let mut events = vec![
+            event(0, EventType::Navigation, "window", Some("Xcode")),
+            event(6, EventType::Navigation, "window", Some("Safari")),
+            event(8, EventType::ErrorDisplay, "<Build> failed", None),
+            event(8, EventType::FieldChange, "Scheme", None),
+            event(8, EventType::ModalDismissal, "<Build> failed", None),
+            event(9, EventType::Navigation, "window", Some("Xcode")),
+            // After the session
+            event(120, EventType::ErrorDisplay, "Later error", None),
+        ];
+        let step = |second: i64, kind: WorkflowStepKind| WorkflowStep {
+            event_id: format!("step_{}", second),
+            timestamp: start + Duration::seconds(second),
+            kind,
+            frame_id: "frame".to_string(),
+            confidence: 0.9,
+        };
+        let chain = |id: &str, kinds: &[WorkflowStepKind]| WorkflowChain {
+            chain_id: id.to_string(),
+            start: start + Duration::minutes(1),
+            end: start + Duration::minutes(1) + Duration::seconds(kinds.len() as i64),
+            steps: kinds.iter().enumerate().map(|(i, kind)| step(60 + i as i64, *kind)).collect(),
+            outcome: WorkflowOutcome::Success,
+            confidence: 0.8,
+            correlation_ids: Vec::new(),
+        };
+        let chains = vec![
+            chain("login", &[WorkflowStepKind::FieldChange, WorkflowStepKind::Submit, WorkflowStepKind::Navigation]),
+            chain("short", &[WorkflowStepKind::Click, WorkflowStepKind::Navigation]),
+        ];
+
+        // Stored before dismissals had their own type
+        let mut legacy_dismissal = event(8, EventType::ErrorDisplay, "<Build> failed", None);
+        legacy_dismissal.metadata.insert(modal_lifetime::MODAL_PHASE_KEY.to_string(), modal_lifetime::PHASE_DISMISSED.to_string());
+        events.push(legacy_dismissal);
+
+        for event in &mut events[2..4] {
+            event.metadata.insert("screen_type".to_string(), "ide".to_string());
+        }
+
+        let generator = ReportGenerator::new(ReportConfig::default()).unwrap();
+        let report = generator.build(start, start + Duration::hours(1), &events, &chains);
+
+        assert_eq!(report.app_usage[0], AppUsage { app_name: "Xcode".to_string(), minutes: 16 });
+        assert_eq!(report.active_minutes, 19);
+        assert_eq!(report.navigation.len(), 3);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].thumbnail.as_deref(), Some("/frames/frame_8.png"));
+        assert_eq!(report.workflows.len(), 1);
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| Xcode | 16m | 84% |"));
+        assert!(markdown.contains("| ide | 2 | 1 |"));
+        assert!(markdown.contains("field_change → submit → navigation (success, 3.0s)"));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("&lt;Build&gt; failed"));
+        assert!(html.contains("<img src=\"file:///frames/frame_8.png\""));
+        assert!(!html.contains("Later error"));
+    }
+}
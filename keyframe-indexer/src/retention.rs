@@ -0,0 +1,181 @@
+use crate::config::RetentionConfig;
+use crate::erasure::{ErasureMode, ErasureReport, ErasureRequest, Eraser};
+use crate::error::Result;
+use crate::keyframe_codec::KeyframeCodec;
+use chrono::{DateTime, Duration, Utc};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Who retention erasures are recorded as in the erasure audit log
+pub const RETENTION_REQUESTER: &str = "retention";
+
+/// How often retention is enforced while watching or capturing
+const ENFORCE_INTERVAL_HOURS: i64 = 24;
+
+/// What one retention run deleted
+#[derive(Debug, Clone)]
+pub struct RetentionReport {
+    pub keyframes_deleted: usize,
+    /// Erasure of outputs older than `output_days`
+    pub outputs: ErasureReport,
+}
+
+/// Deletes keyframe images older than `keyframe_days`, and frame rows, OCR
+/// results and events older than `output_days` through the eraser, so each
+/// run is recorded in the erasure audit log
+#[derive(Debug, Clone)]
+pub struct RetentionEnforcer {
+    config: RetentionConfig,
+    output_dir: PathBuf,
+    ocr_dir: PathBuf,
+    events_dir: PathBuf,
+    frames_root: PathBuf,
+    embeddings_dir: Option<PathBuf>,
+    forms_dir: Option<PathBuf>,
+    last_run: Option<DateTime<Utc>>,
+}
+
+impl RetentionEnforcer {
+    pub fn new(
+        config: RetentionConfig,
+        output_dir: impl Into<PathBuf>,
+        ocr_dir: impl Into<PathBuf>,
+        events_dir: impl Into<PathBuf>,
+        frames_root: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            config,
+            output_dir: output_dir.into(),
+            ocr_dir: ocr_dir.into(),
+            events_dir: events_dir.into(),
+            frames_root: frames_root.into(),
+            embeddings_dir: None,
+            forms_dir: None,
+            last_run: None,
+        }
+    }
+
+    /// Expire embeddings and form records from configured directories
+    /// rather than their defaults under the output directory
+    pub fn with_stores(mut self, embeddings_dir: impl Into<PathBuf>, forms_dir: impl Into<PathBuf>) -> Self {
+        self.embeddings_dir = Some(embeddings_dir.into());
+        self.forms_dir = Some(forms_dir.into());
+        self
+    }
+
+    /// Whether a run is due: never run yet, or the last run is a day old
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_run.map_or(true, |last| now - last >= Duration::hours(ENFORCE_INTERVAL_HOURS))
+    }
+
+    pub fn mark_run(&mut self, now: DateTime<Utc>) {
+        self.last_run = Some(now);
+    }
+
+    /// Delete everything older than the configured retention as of `now`
+    pub fn enforce(&self, now: DateTime<Utc>) -> Result<RetentionReport> {
+        let keyframe_cutoff = now - Duration::days(self.config.keyframe_days as i64);
+        let keyframes_deleted = delete_keyframes_before(&self.frames_root, keyframe_cutoff)?;
+
+        let request = ErasureRequest {
+            until: Some(now - Duration::days(self.config.output_days as i64)),
+            mode: ErasureMode::Delete,
+            requested_by: RETENTION_REQUESTER.to_string(),
+            ..ErasureRequest::default()
+        };
+        let mut eraser = Eraser::new(&self.output_dir, &self.ocr_dir, &self.events_dir);
+        if let Some(dir) = &self.embeddings_dir {
+            eraser = eraser.with_embeddings_dir(dir);
+        }
+        if let Some(dir) = &self.forms_dir {
+            eraser = eraser.with_forms_dir(dir);
+        }
+        let outputs = eraser.erase(&request, false)?;
+
+        info!(
+            "Retention deleted {} keyframes and {} frame rows, {} OCR rows and {} events",
+            keyframes_deleted, outputs.frame_rows, outputs.ocr_rows, outputs.events
+        );
+        Ok(RetentionReport { keyframes_deleted, outputs })
+    }
+}
+
+/// Delete keyframe images last written before `cutoff`, and segment
+/// directories left empty
+fn delete_keyframes_before(dir: &Path, cutoff: DateTime<Utc>) -> Result<usize> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut deleted = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            deleted += delete_keyframes_before(&path, cutoff)?;
+            if std::fs::read_dir(&path)?.next().is_none() {
+                std::fs::remove_dir(&path)?;
+            }
+            continue;
+        }
+        let is_keyframe = path.extension().and_then(|e| e.to_str()).is_some_and(|extension| {
+            KeyframeCodec::ALL.iter().any(|codec| codec.extension() == extension) || extension == "jpg"
+        });
+        let modified = std::fs::metadata(&path)?.modified().map(DateTime::<Utc>::from);
+        match modified {
+            Ok(modified) if is_keyframe && modified < cutoff => {
+                std::fs::remove_file(&path)?;
+                deleted += 1;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to read the age of {}: {}", path.display(), e),
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_enforce_deletes_only_expired_keyframes() {
+        let temp_dir = TempDir::new().unwrap();
+        let frames_root = temp_dir.path().join("frames");
+        let segment = frames_root.join("segment_1");
+        std::fs::create_dir_all(&segment).unwrap();
+        let keyframe = segment.join("frame_0001.png");
+        std::fs::write(&keyframe, b"png").unwrap();
+
+        let config = RetentionConfig { enabled: true, keyframe_days: 30, output_days: 365 };
+        let enforcer = RetentionEnforcer::new(
+            config,
+            temp_dir.path(),
+            temp_dir.path().join("ocr"),
+            temp_dir.path().join("events"),
+            &frames_root,
+        );
+
+        // Written just now, so kept
+        let report = enforcer.enforce(Utc::now()).unwrap();
+        assert_eq!(report.keyframes_deleted, 0);
+        assert!(keyframe.exists());
+
+        // Forty days later it has expired, and its segment directory goes with it
+        let report = enforcer.enforce(Utc::now() + Duration::days(40)).unwrap();
+        assert_eq!(report.keyframes_deleted, 1);
+        assert_eq!(report.outputs.requested_by, RETENTION_REQUESTER);
+        assert_eq!(report.outputs.events, 0);
+        assert!(!segment.exists());
+        assert!(frames_root.exists());
+    }
+
+    #[test]
+    fn test_runs_once_a_day() {
+        let mut enforcer = RetentionEnforcer::new(RetentionConfig::default(), "out", "ocr", "events", "frames");
+        let now = Utc::now();
+        assert!(enforcer.is_due(now));
+        enforcer.mark_run(now);
+        assert!(!enforcer.is_due(now + Duration::hours(23)));
+        assert!(enforcer.is_due(now + Duration::hours(24)));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::app_extractor::{AppExtractor, AppFrame};
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SAP GUI extractor settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SapGuiConfig {
+    pub enabled: bool,
+    /// Apps handled, matched case-insensitively as part of the app name
+    pub app_names: Vec<String>,
+    /// Top share of the frame holding the command field and toolbars
+    pub toolbar_ratio: f32,
+    /// Bottom share of the frame holding the status bar
+    pub status_bar_ratio: f32,
+}
+
+impl Default for SapGuiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            app_names: vec!["SAP GUI".to_string(), "SAPGUI".to_string(), "SAP Logon".to_string()],
+            toolbar_ratio: 0.15,
+            status_bar_ratio: 0.08,
+        }
+    }
+}
+
+/// A labelled input field read from the screen layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SapField {
+    pub label: String,
+    pub value: String,
+    pub roi: BoundingBox,
+}
+
+/// What an SAP GUI keyframe shows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SapScreen {
+    /// Transaction shown in the status bar, e.g. `VA01`
+    pub tcode: Option<String>,
+    /// Transaction code typed into the command field, without its `/n` prefix
+    pub command: Option<String>,
+    pub title: Option<String>,
+    pub fields: Vec<SapField>,
+}
+
+/// Recognizes SAP GUI transaction codes, screen titles and field labels,
+/// emitting `TCodeEntered` and `TransactionStart` events and naming field
+/// changes after their labels.
+pub struct SapGuiExtractor {
+    config: SapGuiConfig,
+    command: Regex,
+    status: Regex,
+    tcode: Option<String>,
+    last_command: Option<String>,
+}
+
+impl SapGuiExtractor {
+    pub fn new(config: SapGuiConfig) -> Result<Self> {
+        if !(0.0..0.5).contains(&config.toolbar_ratio) || !(0.0..0.5).contains(&config.status_bar_ratio) {
+            return Err(IndexerError::Config("sap_gui toolbar and status bar ratios must be below 0.5".to_string()));
+        }
+        let invalid = |e: regex::Error| IndexerError::Config(format!("Invalid SAP GUI pattern: {}", e));
+        // `/nVA01`, `/oSE38` or a bare code such as `ME21N`
+        let command = Regex::new(r"(?i)^(?:/[no]?\s*((?:/[a-z0-9]+/)?[a-z][a-z0-9_]{1,19})|([A-Z]{2,4}[0-9]{2,3}[A-Z]?))$")
+            .map_err(invalid)?;
+        // System, session and client, then the transaction: `PRD (1) 800 | VA01 | host`
+        let status = Regex::new(r"\b[A-Z0-9]{3}\s*\(\d+\)\s*\d{3}[\s|▼▾]*((?:/[A-Z0-9]+/)?[A-Z][A-Z0-9_]{1,19})\b")
+            .map_err(invalid)?;
+        Ok(Self { config, command, status, tcode: None, last_command: None })
+    }
+
+    /// Read the transaction, command field, title and fields from a keyframe
+    pub fn parse(&self, frame: &FrameMetadata, ocr_results: &[OCRResult]) -> SapScreen {
+        let height = frame.height as f32;
+        let toolbar_bottom = height * self.config.toolbar_ratio;
+        let status_top = height * (1.0 - self.config.status_bar_ratio);
+        let center = |r: &OCRResult| r.roi.y + r.roi.height / 2.0;
+
+        let command = ocr_results
+            .iter()
+            .filter(|r| center(r) < toolbar_bottom)
+            .find_map(|r| self.command_code(&r.text));
+
+        let mut status_bar: Vec<&OCRResult> = ocr_results.iter().filter(|r| center(r) > status_top).collect();
+        status_bar.sort_by(|a, b| a.roi.x.total_cmp(&b.roi.x));
+        let status_text = status_bar.iter().map(|r| r.text.as_str()).collect::<Vec<_>>().join(" ");
+        let tcode = self.status.captures(&status_text).map(|c| c[1].to_string());
+
+        // The window title is the screen title; otherwise the tallest text below the toolbar
+        let title = Some(frame.win_title.trim())
+            .filter(|t| !t.is_empty() && !self.is_app_name(t))
+            .map(str::to_string)
+            .or_else(|| {
+                ocr_results
+                    .iter()
+                    .filter(|r| center(r) >= toolbar_bottom && center(r) < toolbar_bottom * 2.0)
+                    .max_by(|a, b| a.roi.height.total_cmp(&b.roi.height))
+                    .map(|r| r.text.trim().to_string())
+            });
+
+        let body: Vec<&OCRResult> = ocr_results
+            .iter()
+            .filter(|r| center(r) >= toolbar_bottom && center(r) <= status_top)
+            .collect();
+        let fields = label_fields(&body, frame.width as f32 * 0.25);
+
+        SapScreen { tcode, command, title, fields }
+    }
+
+    fn command_code(&self, text: &str) -> Option<String> {
+        let captures = self.command.captures(text.trim())?;
+        captures.get(1).or_else(|| captures.get(2)).map(|m| m.as_str().to_uppercase())
+    }
+
+    fn is_app_name(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.config.app_names.iter().any(|name| text.contains(&name.to_lowercase()))
+    }
+
+    fn event(
+        &self,
+        frame: &AppFrame<'_>,
+        event_type: EventType,
+        target: &str,
+        value_from: Option<String>,
+        value_to: Option<String>,
+        screen: &SapScreen,
+    ) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("app_extractor".to_string(), "sap_gui".to_string());
+        if let Some(title) = &screen.title {
+            metadata.insert("sap_screen_title".to_string(), title.clone());
+        }
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: frame.timestamp,
+            event_type,
+            target: target.to_string(),
+            value_from,
+            value_to,
+            confidence: 0.9,
+            evidence_frames: vec![frame.metadata.path.clone()],
+            metadata,
+        }
+    }
+}
+
+impl AppExtractor for SapGuiExtractor {
+    fn name(&self) -> &str {
+        "sap_gui"
+    }
+
+    fn handles(&self, frame: &FrameMetadata) -> bool {
+        self.is_app_name(&frame.app_name)
+    }
+
+    fn extract(&mut self, frame: &AppFrame<'_>, events: &mut Vec<DetectedEvent>) -> Result<()> {
+        let screen = self.parse(frame.metadata, frame.ocr_results);
+        let toolbar_bottom = frame.metadata.height as f32 * self.config.toolbar_ratio;
+        let mut typed = Vec::new();
+
+        if screen.command.is_some() && screen.command != self.last_command {
+            let code = screen.command.clone().unwrap_or_default();
+            // The command field's own text change is replaced by the typed event
+            events.retain(|e| {
+                !(is_field_edit(e)
+                    && roi(e).map_or(false, |r| r.y < toolbar_bottom)
+                    && e.value_to.as_deref().and_then(|v| self.command_code(v)).as_deref() == Some(code.as_str()))
+            });
+            typed.push(self.event(frame, EventType::TCodeEntered, "command_field", self.tcode.clone(), Some(code), &screen));
+        }
+        self.last_command = screen.command.clone();
+
+        if let Some(tcode) = screen.tcode.as_ref().filter(|t| Some(*t) != self.tcode.as_ref()) {
+            typed.push(self.event(frame, EventType::TransactionStart, tcode, self.tcode.clone(), Some(tcode.clone()), &screen));
+            self.tcode = Some(tcode.clone());
+        }
+
+        for event in events.iter_mut().filter(|e| is_field_edit(e)) {
+            let field = roi(event).and_then(|r| screen.fields.iter().find(|f| f.roi.iou(&r) > 0.5));
+            if let Some(field) = field {
+                event.target = field.label.clone();
+                event.metadata.insert("field_label".to_string(), field.label.clone());
+                event.metadata.insert("app_extractor".to_string(), "sap_gui".to_string());
+            }
+        }
+
+        events.extend(typed);
+        if let Some(tcode) = &self.tcode {
+            for event in events.iter_mut() {
+                event.metadata.insert("sap_tcode".to_string(), tcode.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_field_edit(event: &DetectedEvent) -> bool {
+    matches!(event.event_type, EventType::FieldChange | EventType::ClipboardPaste)
+}
+
+/// Region a generic event was detected in, from its metadata
+fn roi(event: &DetectedEvent) -> Option<BoundingBox> {
+    BoundingBox::from_metadata(&event.metadata)
+}
+
+/// Pair each label with the input box right of it on the same row.
+///
+/// Boxes are walked left to right per row, so `Order Type [OR] Sales Org [1000]`
+/// gives two fields.
+fn label_fields(body: &[&OCRResult], max_gap: f32) -> Vec<SapField> {
+    let mut boxes: Vec<&OCRResult> = body.to_vec();
+    boxes.sort_by(|a, b| a.roi.y.total_cmp(&b.roi.y));
+
+    let mut rows: Vec<Vec<&OCRResult>> = Vec::new();
+    for result in boxes {
+        let center = result.roi.y + result.roi.height / 2.0;
+        match rows.last_mut() {
+            Some(row) if (center - (row[0].roi.y + row[0].roi.height / 2.0)).abs() < row[0].roi.height.max(result.roi.height) / 2.0 => {
+                row.push(result)
+            }
+            _ => rows.push(vec![result]),
+        }
+    }
+
+    let mut fields = Vec::new();
+    for mut row in rows {
+        row.sort_by(|a, b| a.roi.x.total_cmp(&b.roi.x));
+        let mut i = 0;
+        while i + 1 < row.len() {
+            let (label, value) = (row[i], row[i + 1]);
+            let gap = value.roi.x - (label.roi.x + label.roi.width);
+            if label.text.chars().any(char::is_alphabetic) && gap >= -2.0 && gap <= max_gap {
+                fields.push(SapField {
+                    label: label.text.trim().trim_end_matches(':').trim().to_string(),
+                    value: value.text.trim().to_string(),
+                    roi: value.roi.clone(),
+                });
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn ocr(text: &str, x: f32, y: f32, width: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame_1.png".to_string(),
+            roi: BoundingBox::new(x, y, width, 20.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    fn field_change(result: &OCRResult, from: &str) -> DetectedEvent {
+        let metadata = [
+            ("roi_x", result.roi.x),
+            ("roi_y", result.roi.y),
+            ("roi_width", result.roi.width),
+            ("roi_height", result.roi.height),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        DetectedEvent {
+            id: format!("generic_{}", result.text),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap(),
+            event_type: EventType::FieldChange,
+            target: "field_generic".to_string(),
+            value_from: Some(from.to_string()),
+            value_to: Some(result.text.clone()),
+            confidence: 0.8,
+            evidence_frames: vec!["frame_1.png".to_string()],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_sap_transaction_and_field_events() {
+        let frame = FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "frame_1.png".to_string(),
+            phash16: 0,
+            entropy: 5.0,
+            app_name: "SAP GUI for Windows".to_string(),
+            win_title: "Create Sales Order: Initial Screen".to_string(),
+            width: 1600,
+            height: 1000,
+            duplicate_of: None,
+            ocr_density: None,
+            visual: None,
+            screen_type: None,
+        };
+        let command = ocr("/nVA01", 20.0, 40.0, 120.0);
+        let order_type = ocr("OR", 260.0, 300.0, 60.0);
+        let ocr_results = vec![
+            command.clone(),
+            ocr("Order Type", 40.0, 300.0, 150.0),
+            order_type.clone(),
+            ocr("Sales Organization", 400.0, 300.0, 200.0),
+            ocr("1000", 640.0, 300.0, 80.0),
+            ocr("PRD (1) 800 | VA01 | sapprd01", 1100.0, 960.0, 400.0),
+        ];
+
+        let mut extractor = SapGuiExtractor::new(SapGuiConfig::default()).unwrap();
+        assert!(extractor.handles(&frame));
+        let screen = extractor.parse(&frame, &ocr_results);
+        assert_eq!(screen.tcode.as_deref(), Some("VA01"));
+        assert_eq!(screen.command.as_deref(), Some("VA01"));
+        assert_eq!(screen.fields.len(), 2);
+        assert_eq!(screen.fields[1].label, "Sales Organization");
+
+        let app_frame = AppFrame { metadata: &frame, timestamp: Utc::now(), ocr_results: &ocr_results };
+        let mut events = vec![field_change(&command, ""), field_change(&order_type, "")];
+        extractor.extract(&app_frame, &mut events).unwrap();
+
+        let types: Vec<&EventType> = events.iter().map(|e| &e.event_type).collect();
+        assert_eq!(types, [&EventType::FieldChange, &EventType::TCodeEntered, &EventType::TransactionStart]);
+        assert_eq!(events[0].target, "Order Type");
+        assert!(events.iter().all(|e| e.metadata["sap_tcode"] == "VA01"));
+        assert_eq!(events[2].metadata["sap_screen_title"], "Create Sales Order: Initial Screen");
+
+        // The same screen again adds nothing new
+        let mut events = Vec::new();
+        extractor.extract(&app_frame, &mut events).unwrap();
+        assert!(events.is_empty());
+    }
+}
@@ -1,12 +1,17 @@
-use crate::error::{IndexerError, Result};
+use crate::error::{IndexerError, Result, ResultExt};
 use crate::keyframe_extractor::Keyframe;
-use crate::config::SceneDetectionConfig;
-use image::{DynamicImage, ImageBuffer, Rgb};
+use crate::config::{HashAlgorithm, SceneDetectionConfig};
+use crate::ocr_data::BoundingBox;
+use image::{DynamicImage, GrayImage};
 use imageproc::stats::histogram;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::Path;
-use tracing::{debug, warn};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SceneChange {
     pub frame_index: usize,
     pub timestamp_ns: i64,
@@ -15,9 +20,121 @@ pub struct SceneChange {
     pub ssim_score: Option<f32>,
     pub phash_distance: Option<u32>,
     pub entropy_delta: Option<f32>,
+    /// Thresholds in effect when this change was classified
+    pub thresholds: SceneThresholds,
+    /// Hash algorithm behind `phash_distance`
+    pub hash_algorithm: HashAlgorithm,
+    /// Tiles of the diff grid that changed the most, most changed first
+    pub changed_regions: Vec<ChangedRegion>,
+    /// More tiles changed than `changed_regions` holds
+    pub regions_capped: bool,
+}
+
+/// A tile of the block-wise diff grid that changed between two frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedRegion {
+    pub grid_row: u32,
+    pub grid_col: u32,
+    /// Tile rectangle in source frame pixels
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Mean absolute luma difference within the tile, 0-1
+    pub change_score: f32,
+}
+
+impl ChangedRegion {
+    /// Region as an OCR bounding box, for restricting OCR to changed areas
+    pub fn to_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.x as f32, self.y as f32, self.width as f32, self.height as f32)
+    }
+}
+
+/// Scene changes plus per-frame hashes from one pass over a segment
+#[derive(Debug, Clone, Default)]
+pub struct SceneAnalysis {
+    pub scene_changes: Vec<SceneChange>,
+    /// Perceptual hash per keyframe; `None` where the image failed to load
+    pub frame_hashes: Vec<Option<u64>>,
+}
+
+/// Thresholds used to classify a frame pair, either static or learned
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneThresholds {
+    pub ssim_threshold: f32,
+    pub phash_distance_threshold: u32,
+    pub entropy_threshold: f32,
+    /// True when derived from rolling statistics rather than the config
+    pub calibrated: bool,
+}
+
+impl SceneThresholds {
+    fn from_config(config: &SceneDetectionConfig) -> Self {
+        Self {
+            ssim_threshold: config.ssim_threshold,
+            phash_distance_threshold: config.phash_distance_threshold,
+            entropy_threshold: config.entropy_threshold,
+            calibrated: false,
+        }
+    }
 }
 
+/// Metrics measured between a frame and the previous loaded frame
 #[derive(Debug, Clone)]
+struct PairMetrics {
+    index: usize,
+    ssim_score: f32,
+    phash_distance: u32,
+    entropy_delta: f32,
+    changed_regions: Vec<ChangedRegion>,
+    regions_capped: bool,
+}
+
+/// Rolling window of frame-pair metrics used for auto-calibration
+#[derive(Debug, Default)]
+struct SceneCalibrator {
+    ssim_scores: VecDeque<f32>,
+    phash_distances: VecDeque<f32>,
+    entropy_deltas: VecDeque<f32>,
+}
+
+impl SceneCalibrator {
+    fn observe(&mut self, metrics: &[PairMetrics], window: usize) {
+        for m in metrics {
+            Self::push_bounded(&mut self.ssim_scores, m.ssim_score, window);
+            Self::push_bounded(&mut self.phash_distances, m.phash_distance as f32, window);
+            Self::push_bounded(&mut self.entropy_deltas, m.entropy_delta, window);
+        }
+    }
+    
+    fn sample_count(&self) -> usize {
+        self.ssim_scores.len()
+    }
+    
+    /// Median/MAD based thresholds; robust to the scene changes themselves being outliers
+    fn thresholds(&self, sensitivity: f32) -> SceneThresholds {
+        let (ssim_median, ssim_dev) = median_and_deviation(&self.ssim_scores);
+        let (phash_median, phash_dev) = median_and_deviation(&self.phash_distances);
+        let (entropy_median, entropy_dev) = median_and_deviation(&self.entropy_deltas);
+        
+        SceneThresholds {
+            ssim_threshold: (ssim_median - sensitivity * ssim_dev).clamp(0.0, 0.995),
+            phash_distance_threshold: (phash_median + sensitivity * phash_dev).round().clamp(2.0, 32.0) as u32,
+            entropy_threshold: (entropy_median + sensitivity * entropy_dev).clamp(0.01, 2.0),
+            calibrated: true,
+        }
+    }
+    
+    fn push_bounded(values: &mut VecDeque<f32>, value: f32, window: usize) {
+        values.push_back(value);
+        while values.len() > window {
+            values.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub enum SceneChangeType {
     Cut,           // Abrupt scene change
     Fade,          // Gradual transition
@@ -25,112 +142,289 @@ pub enum SceneChangeType {
     ContentChange, // UI or content modification
 }
 
+/// Edge length of the base pyramid level used for SSIM
+const PYRAMID_BASE_SIZE: u32 = 64;
+/// Edge length of the pyramid level used for the perceptual hash
+const PYRAMID_HASH_SIZE: u32 = 8;
+
+/// Downscaled luma pyramid for a single frame.
+///
+/// Built once per frame and reused for every pairwise comparison, so the
+/// full-resolution image is decoded and resized exactly once.
+#[derive(Debug, Clone)]
+pub struct FramePyramid {
+    /// Luma levels from PYRAMID_BASE_SIZE down to PYRAMID_HASH_SIZE, halving each step
+    pub levels: Vec<GrayImage>,
+    pub phash: u64,
+    pub entropy: f32,
+    /// Dimensions of the source frame
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FramePyramid {
+    pub fn from_image(image: &DynamicImage) -> Self {
+        Self::from_image_with_hash(image, HashAlgorithm::Average)
+    }
+    
+    pub fn from_image_with_hash(image: &DynamicImage, hash_algorithm: HashAlgorithm) -> Self {
+        let luma = image.to_luma8();
+        let entropy = luma_entropy(&luma);
+        
+        let mut levels = vec![image::imageops::resize(
+            &luma,
+            PYRAMID_BASE_SIZE,
+            PYRAMID_BASE_SIZE,
+            image::imageops::FilterType::Lanczos3,
+        )];
+        let mut size = PYRAMID_BASE_SIZE / 2;
+        while size >= PYRAMID_HASH_SIZE {
+            let previous = levels.last().unwrap();
+            levels.push(image::imageops::resize(previous, size, size, image::imageops::FilterType::Triangle));
+            size /= 2;
+        }
+        
+        let phash = match hash_algorithm {
+            HashAlgorithm::Average => average_hash(levels.last().unwrap()),
+            HashAlgorithm::Difference => difference_hash(&levels[0]),
+            HashAlgorithm::Wavelet => wavelet_hash(&levels[0]),
+        };
+        
+        Self { levels, phash, entropy, width: image.width(), height: image.height() }
+    }
+    
+    /// Level used for structural similarity
+    pub fn base(&self) -> &GrayImage {
+        &self.levels[0]
+    }
+}
+
 pub struct SceneDetector {
     config: SceneDetectionConfig,
+    calibrator: Mutex<SceneCalibrator>,
 }
 
 impl SceneDetector {
     pub fn new(config: SceneDetectionConfig) -> Result<Self> {
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            calibrator: Mutex::new(SceneCalibrator::default()),
+        })
     }
     
     pub fn detect_scene_changes(&self, keyframes: &[Keyframe]) -> Result<Vec<SceneChange>> {
-        if keyframes.len() < 2 {
-            return Ok(Vec::new());
+        Ok(self.analyze_keyframes(keyframes)?.scene_changes)
+    }
+    
+    /// Detect scene changes and return the perceptual hash of every frame that loaded
+    pub fn analyze_keyframes(&self, keyframes: &[Keyframe]) -> Result<SceneAnalysis> {
+        if keyframes.is_empty() {
+            return Ok(SceneAnalysis::default());
         }
         
-        let mut scene_changes = Vec::new();
-        let mut previous_image: Option<DynamicImage> = None;
-        let mut previous_phash: Option<u64> = None;
-        let mut previous_entropy: Option<f32> = None;
-        
-        for (index, keyframe) in keyframes.iter().enumerate() {
-            let current_image = match self.load_image(&keyframe.frame_path) {
-                Ok(img) => img,
+        // Decode and downscale every frame once, in parallel
+        let pyramids: Vec<(usize, FramePyramid)> = keyframes
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, keyframe)| match self.build_pyramid(&keyframe.frame_path) {
+                Ok(pyramid) => Some((index, pyramid)),
                 Err(e) => {
                     warn!("Failed to load keyframe image {}: {}", keyframe.frame_path, e);
-                    continue;
-                }
-            };
-            
-            let current_phash = self.calculate_phash(&current_image)?;
-            let current_entropy = self.calculate_entropy(&current_image)?;
-            
-            if let (Some(prev_img), Some(prev_phash), Some(prev_entropy)) = 
-                (&previous_image, previous_phash, previous_entropy) {
-                
-                // Calculate SSIM
-                let ssim_score = self.calculate_ssim(prev_img, &current_image)?;
-                
-                // Calculate pHash distance
-                let phash_distance = self.hamming_distance(prev_phash, current_phash);
-                
-                // Calculate entropy delta
-                let entropy_delta = (current_entropy - prev_entropy).abs();
-                
-                // Determine if this is a scene change
-                if let Some(change_type) = self.classify_scene_change(
-                    ssim_score,
-                    phash_distance,
-                    entropy_delta,
-                ) {
-                    let confidence = self.calculate_confidence(ssim_score, phash_distance, entropy_delta);
-                    
-                    scene_changes.push(SceneChange {
-                        frame_index: index,
-                        timestamp_ns: keyframe.timestamp_ns,
-                        change_type,
-                        confidence,
-                        ssim_score: Some(ssim_score),
-                        phash_distance: Some(phash_distance),
-                        entropy_delta: Some(entropy_delta),
-                    });
-                    
-                    debug!("Scene change detected at frame {}: SSIM={:.3}, pHash distance={}, entropy delta={:.3}",
-                           index, ssim_score, phash_distance, entropy_delta);
+                    None
                 }
-            }
-            
-            previous_image = Some(current_image);
-            previous_phash = Some(current_phash);
-            previous_entropy = Some(current_entropy);
-        }
+            })
+            .collect();
+        
+        // Compare each frame with the previous successfully loaded one
+        let metrics: Vec<PairMetrics> = pyramids
+            .par_windows(2)
+            .map(|pair| self.compare_pyramids(pair[1].0, &pair[0].1, &pair[1].1))
+            .collect();
+        
+        let thresholds = self.thresholds_for_segment(&metrics);
+        
+        let scene_changes: Vec<SceneChange> = metrics
+            .iter()
+            .filter_map(|m| self.evaluate_pair(m, keyframes[m.index].timestamp_ns, &thresholds))
+            .collect();
         
         debug!("Detected {} scene changes out of {} keyframes", scene_changes.len(), keyframes.len());
-        Ok(scene_changes)
+        
+        let mut frame_hashes = vec![None; keyframes.len()];
+        for (index, pyramid) in &pyramids {
+            frame_hashes[*index] = Some(pyramid.phash);
+        }
+        
+        Ok(SceneAnalysis { scene_changes, frame_hashes })
+    }
+    
+    /// Compare a frame with the one before it, for callers that see frames
+    /// one at a time; uses the current thresholds without calibrating them
+    pub fn compare_frames(
+        &self,
+        frame_index: usize,
+        timestamp_ns: i64,
+        previous: &FramePyramid,
+        current: &FramePyramid,
+    ) -> Option<SceneChange> {
+        let metrics = self.compare_pyramids(frame_index, previous, current);
+        self.evaluate_pair(&metrics, timestamp_ns, &self.current_thresholds())
+    }
+    
+    /// Load a frame from disk and build its luma pyramid
+    pub fn build_pyramid(&self, path: &str) -> Result<FramePyramid> {
+        let image = self.load_image(path).for_frame(path)?;
+        Ok(FramePyramid::from_image_with_hash(&image, self.config.hash_algorithm))
     }
     
     pub fn calculate_phash(&self, image: &DynamicImage) -> Result<u64> {
-        // Resize to 8x8 for pHash calculation
-        let small_image = image.resize_exact(8, 8, image::imageops::FilterType::Lanczos3);
-        let gray_image = small_image.to_luma8();
-        
-        // Calculate average pixel value
-        let mut sum = 0u32;
-        for pixel in gray_image.pixels() {
-            sum += pixel[0] as u32;
+        Ok(self.pyramid(image).phash)
+    }
+    
+    /// Luma pyramid of an already loaded frame, hashed with the configured algorithm
+    pub fn pyramid(&self, image: &DynamicImage) -> FramePyramid {
+        FramePyramid::from_image_with_hash(image, self.config.hash_algorithm)
+    }
+    
+    pub fn calculate_ssim(&self, img1: &DynamicImage, img2: &DynamicImage) -> Result<f32> {
+        let pyramid1 = FramePyramid::from_image(img1);
+        let pyramid2 = FramePyramid::from_image(img2);
+        Ok(self.ssim_luma(pyramid1.base(), pyramid2.base()))
+    }
+    
+    /// Thresholds that would apply to the next segment
+    pub fn current_thresholds(&self) -> SceneThresholds {
+        if !self.config.auto_calibrate {
+            return SceneThresholds::from_config(&self.config);
         }
-        let average = sum / 64;
         
-        // Generate hash based on pixels above/below average
-        let mut hash = 0u64;
-        for (i, pixel) in gray_image.pixels().enumerate() {
-            if pixel[0] as u32 > average {
-                hash |= 1 << i;
+        match self.calibrator.lock() {
+            Ok(calibrator) if calibrator.sample_count() >= self.config.calibration_min_samples => {
+                calibrator.thresholds(self.config.calibration_sensitivity)
             }
+            _ => SceneThresholds::from_config(&self.config),
+        }
+    }
+    
+    /// Apply new thresholds; learned statistics are kept unless calibration settings changed
+    pub fn update_config(&mut self, config: SceneDetectionConfig) {
+        let recalibrate = config.calibration_window != self.config.calibration_window
+            || config.auto_calibrate != self.config.auto_calibrate;
+        self.config = config;
+        if recalibrate {
+            self.reset_calibration();
+        }
+    }
+    
+    /// Discard learned statistics, e.g. after the capture setup changes
+    pub fn reset_calibration(&self) {
+        if let Ok(mut calibrator) = self.calibrator.lock() {
+            *calibrator = SceneCalibrator::default();
+        }
+    }
+    
+    fn thresholds_for_segment(&self, metrics: &[PairMetrics]) -> SceneThresholds {
+        if !self.config.auto_calibrate {
+            return SceneThresholds::from_config(&self.config);
+        }
+        
+        if let Ok(mut calibrator) = self.calibrator.lock() {
+            calibrator.observe(metrics, self.config.calibration_window.max(1));
         }
         
-        Ok(hash)
+        let thresholds = self.current_thresholds();
+        if thresholds.calibrated {
+            info!("Calibrated scene thresholds: SSIM<{:.3}, pHash>{}, entropy>{:.3}",
+                  thresholds.ssim_threshold, thresholds.phash_distance_threshold, thresholds.entropy_threshold);
+        }
+        thresholds
     }
     
-    pub fn calculate_ssim(&self, img1: &DynamicImage, img2: &DynamicImage) -> Result<f32> {
-        // Convert to grayscale and resize to same dimensions
-        let gray1 = img1.resize_exact(64, 64, image::imageops::FilterType::Lanczos3).to_luma8();
-        let gray2 = img2.resize_exact(64, 64, image::imageops::FilterType::Lanczos3).to_luma8();
+    fn compare_pyramids(&self, index: usize, previous: &FramePyramid, current: &FramePyramid) -> PairMetrics {
+        let mut changed_regions = self.changed_tiles(previous, current);
+        let regions_capped = changed_regions.len() > self.config.max_changed_regions;
+        changed_regions.truncate(self.config.max_changed_regions);
+        PairMetrics {
+            index,
+            ssim_score: self.ssim_luma(previous.base(), current.base()),
+            phash_distance: self.hamming_distance(previous.phash, current.phash),
+            entropy_delta: (current.entropy - previous.entropy).abs(),
+            changed_regions,
+            regions_capped,
+        }
+    }
+    
+    /// Compute the block-wise diff grid on the base level and keep the top changed tiles
+    pub fn changed_regions(&self, previous: &FramePyramid, current: &FramePyramid) -> Vec<ChangedRegion> {
+        let mut regions = self.changed_tiles(previous, current);
+        regions.truncate(self.config.max_changed_regions);
+        regions
+    }
+    
+    /// Every tile of the diff grid above the change threshold, most changed first
+    fn changed_tiles(&self, previous: &FramePyramid, current: &FramePyramid) -> Vec<ChangedRegion> {
+        let grid = self.config.region_grid_size.clamp(1, PYRAMID_BASE_SIZE);
+        let scores = block_diff_grid(previous.base(), current.base(), grid);
         
+        let mut regions: Vec<ChangedRegion> = scores
+            .iter()
+            .enumerate()
+            .filter(|(_, &score)| score > self.config.region_change_threshold)
+            .map(|(i, &score)| {
+                let row = i as u32 / grid;
+                let col = i as u32 % grid;
+                let (x0, x1) = tile_span(col, grid, current.width);
+                let (y0, y1) = tile_span(row, grid, current.height);
+                ChangedRegion {
+                    grid_row: row,
+                    grid_col: col,
+                    x: x0,
+                    y: y0,
+                    width: x1 - x0,
+                    height: y1 - y0,
+                    change_score: score,
+                }
+            })
+            .collect();
+        
+        regions.sort_by(|a, b| b.change_score.total_cmp(&a.change_score));
+        regions
+    }
+    
+    fn evaluate_pair(
+        &self,
+        metrics: &PairMetrics,
+        timestamp_ns: i64,
+        thresholds: &SceneThresholds,
+    ) -> Option<SceneChange> {
+        let (index, ssim_score, phash_distance, entropy_delta) =
+            (metrics.index, metrics.ssim_score, metrics.phash_distance, metrics.entropy_delta);
+        
+        let change_type = Self::classify_with_thresholds(thresholds, ssim_score, phash_distance, entropy_delta)?;
+        let confidence = self.calculate_confidence(ssim_score, phash_distance, entropy_delta);
+        
+        debug!("Scene change detected at frame {}: SSIM={:.3}, pHash distance={}, entropy delta={:.3}",
+               index, ssim_score, phash_distance, entropy_delta);
+        
+        Some(SceneChange {
+            frame_index: index,
+            timestamp_ns,
+            change_type,
+            confidence,
+            ssim_score: Some(ssim_score),
+            phash_distance: Some(phash_distance),
+            entropy_delta: Some(entropy_delta),
+            thresholds: *thresholds,
+            hash_algorithm: self.config.hash_algorithm,
+            changed_regions: metrics.changed_regions.clone(),
+            regions_capped: metrics.regions_capped,
+        })
+    }
+    
+    fn ssim_luma(&self, gray1: &GrayImage, gray2: &GrayImage) -> f32 {
         // Calculate means
-        let mean1 = self.calculate_mean(&gray1);
-        let mean2 = self.calculate_mean(&gray2);
+        let mean1 = self.calculate_mean(gray1);
+        let mean2 = self.calculate_mean(gray2);
         
         // Calculate variances and covariance
         let mut var1 = 0.0;
@@ -159,31 +453,16 @@ impl SceneDetector {
         let numerator = (2.0 * mean1 * mean2 + c1) * (2.0 * covar + c2);
         let denominator = (mean1 * mean1 + mean2 * mean2 + c1) * (var1 + var2 + c2);
         
-        Ok(numerator / denominator)
+        numerator / denominator
     }
     
-    fn calculate_mean(&self, image: &ImageBuffer<image::Luma<u8>, Vec<u8>>) -> f32 {
+    fn calculate_mean(&self, image: &GrayImage) -> f32 {
         let sum: u32 = image.pixels().map(|p| p[0] as u32).sum();
         sum as f32 / (image.width() * image.height()) as f32
     }
     
     fn calculate_entropy(&self, image: &DynamicImage) -> Result<f32> {
-        let gray_image = image.to_luma8();
-        let hist = histogram(&gray_image);
-        
-        let total_pixels = (gray_image.width() * gray_image.height()) as f32;
-        let mut entropy = 0.0;
-        
-        for channel in hist.channels.iter() {
-            for &count in channel.iter() {
-                if count > 0 {
-                    let probability = count as f32 / total_pixels;
-                    entropy -= probability * probability.log2();
-                }
-            }
-        }
-        
-        Ok(entropy)
+        Ok(luma_entropy(&image.to_luma8()))
     }
     
     fn hamming_distance(&self, hash1: u64, hash2: u64) -> u32 {
@@ -195,19 +474,33 @@ impl SceneDetector {
         ssim_score: f32,
         phash_distance: u32,
         entropy_delta: f32,
+    ) -> Option<SceneChangeType> {
+        Self::classify_with_thresholds(
+            &SceneThresholds::from_config(&self.config),
+            ssim_score,
+            phash_distance,
+            entropy_delta,
+        )
+    }
+    
+    fn classify_with_thresholds(
+        thresholds: &SceneThresholds,
+        ssim_score: f32,
+        phash_distance: u32,
+        entropy_delta: f32,
     ) -> Option<SceneChangeType> {
         // Scene change detection based on thresholds
-        if ssim_score < self.config.ssim_threshold {
-            if phash_distance > self.config.phash_distance_threshold * 2 {
+        if ssim_score < thresholds.ssim_threshold {
+            if phash_distance > thresholds.phash_distance_threshold * 2 {
                 Some(SceneChangeType::Cut)
-            } else if entropy_delta > self.config.entropy_threshold * 2.0 {
+            } else if entropy_delta > thresholds.entropy_threshold * 2.0 {
                 Some(SceneChangeType::ContentChange)
             } else {
                 Some(SceneChangeType::Fade)
             }
-        } else if phash_distance > self.config.phash_distance_threshold {
+        } else if phash_distance > thresholds.phash_distance_threshold {
             Some(SceneChangeType::Motion)
-        } else if entropy_delta > self.config.entropy_threshold {
+        } else if entropy_delta > thresholds.entropy_threshold {
             Some(SceneChangeType::ContentChange)
         } else {
             None
@@ -229,6 +522,149 @@ impl SceneDetector {
     }
 }
 
+/// Mean absolute difference per tile (0-1), row-major over a `grid` x `grid` layout
+fn block_diff_grid(previous: &GrayImage, current: &GrayImage, grid: u32) -> Vec<f32> {
+    let mut scores = Vec::with_capacity((grid * grid) as usize);
+    
+    for row in 0..grid {
+        let (y0, y1) = tile_span(row, grid, current.height());
+        for col in 0..grid {
+            let (x0, x1) = tile_span(col, grid, current.width());
+            
+            let mut total = 0u32;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    total += (previous.get_pixel(x, y)[0] as i32 - current.get_pixel(x, y)[0] as i32).unsigned_abs();
+                }
+            }
+            
+            let count = ((x1 - x0) * (y1 - y0)).max(1);
+            scores.push(total as f32 / (count as f32 * 255.0));
+        }
+    }
+    
+    scores
+}
+
+/// Start and end (exclusive) of tile `index` when `length` is split into `grid` tiles
+fn tile_span(index: u32, grid: u32, length: u32) -> (u32, u32) {
+    let start = (index as u64 * length as u64 / grid as u64) as u32;
+    let end = ((index as u64 + 1) * length as u64 / grid as u64) as u32;
+    (start, end)
+}
+
+/// Median and scaled median absolute deviation (comparable to a standard deviation)
+fn median_and_deviation(values: &VecDeque<f32>) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    
+    let median_of = |sorted: &[f32]| {
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        }
+    };
+    
+    let mut sorted: Vec<f32> = values.iter().copied().collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = median_of(&sorted);
+    
+    let mut deviations: Vec<f32> = sorted.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(|a, b| a.total_cmp(b));
+    
+    (median, median_of(&deviations) * 1.4826)
+}
+
+/// Difference hash: one bit per horizontally adjacent pixel pair in a 9x8 thumbnail
+fn difference_hash(base: &GrayImage) -> u64 {
+    let thumbnail = image::imageops::resize(base, 9, 8, image::imageops::FilterType::Triangle);
+    
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            if thumbnail.get_pixel(x + 1, y)[0] > thumbnail.get_pixel(x, y)[0] {
+                hash |= 1 << (y * 8 + x);
+            }
+        }
+    }
+    
+    hash
+}
+
+/// Wavelet hash over the 8x8 Haar low-frequency band of the base level.
+///
+/// Repeated Haar decomposition keeps only the LL band, which equals the block
+/// average at each step. Removing the DC component and thresholding at the
+/// median (as imagehash's whash does) reduces to a median threshold on the band.
+fn wavelet_hash(base: &GrayImage) -> u64 {
+    let mut size = base.width() as usize;
+    let mut band: Vec<f32> = base.pixels().map(|p| p[0] as f32).collect();
+    
+    while size > 8 {
+        let half = size / 2;
+        let mut next = vec![0.0; half * half];
+        for y in 0..half {
+            for x in 0..half {
+                let i = 2 * y * size + 2 * x;
+                next[y * half + x] = (band[i] + band[i + 1] + band[i + size] + band[i + size + 1]) / 4.0;
+            }
+        }
+        band = next;
+        size = half;
+    }
+    
+    let mut sorted = band.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0;
+    
+    let mut hash = 0u64;
+    for (i, value) in band.iter().enumerate().take(64) {
+        if *value > median {
+            hash |= 1 << i;
+        }
+    }
+    
+    hash
+}
+
+/// Shannon entropy of a luma histogram
+fn luma_entropy(gray_image: &GrayImage) -> f32 {
+    let hist = histogram(gray_image);
+    
+    let total_pixels = (gray_image.width() * gray_image.height()) as f32;
+    let mut entropy = 0.0;
+    
+    for channel in hist.channels.iter() {
+        for &count in channel.iter() {
+            if count > 0 {
+                let probability = count as f32 / total_pixels;
+                entropy -= probability * probability.log2();
+            }
+        }
+    }
+    
+    entropy
+}
+
+/// Average hash over a small luma level: one bit per pixel above the mean
+fn average_hash(gray_image: &GrayImage) -> u64 {
+    let pixel_count = (gray_image.width() * gray_image.height()).max(1);
+    let sum: u32 = gray_image.pixels().map(|p| p[0] as u32).sum();
+    let average = sum / pixel_count;
+    
+    let mut hash = 0u64;
+    for (i, pixel) in gray_image.pixels().enumerate().take(64) {
+        if pixel[0] as u32 > average {
+            hash |= 1 << i;
+        }
+    }
+    
+    hash
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,6 +714,16 @@ mod tests {
         assert!(detector.is_ok());
     }
     
+    #[test]
+    fn test_frame_pyramid_levels() {
+        let pyramid = FramePyramid::from_image(&create_checkerboard_image(640, 480, 16));
+        
+        let sizes: Vec<u32> = pyramid.levels.iter().map(|level| level.width()).collect();
+        assert_eq!(sizes, vec![64, 32, 16, 8]);
+        assert!(pyramid.levels.iter().all(|level| level.width() == level.height()));
+        assert!(pyramid.entropy > 0.9, "Checkerboard should have ~1 bit of entropy, got {}", pyramid.entropy);
+    }
+    
     #[test]
     fn test_phash_calculation() {
         let config = SceneDetectionConfig::default();
@@ -299,6 +745,26 @@ mod tests {
         assert_eq!(black_hash, black_hash2);
     }
     
+    #[test]
+    fn test_alternative_hash_algorithms() {
+        for hash_algorithm in [HashAlgorithm::Difference, HashAlgorithm::Wavelet] {
+            let config = SceneDetectionConfig {
+                hash_algorithm,
+                ..Default::default()
+            };
+            let detector = SceneDetector::new(config).unwrap();
+            
+            let gradient = create_gradient_image(64, 64);
+            let checker = create_checkerboard_image(64, 64, 16);
+            
+            let gradient_hash = detector.calculate_phash(&gradient).unwrap();
+            assert_eq!(gradient_hash, detector.calculate_phash(&gradient).unwrap());
+            
+            let distance = detector.hamming_distance(gradient_hash, detector.calculate_phash(&checker).unwrap());
+            assert!(distance > 10, "{:?} should separate distinct images, got {}", hash_algorithm, distance);
+        }
+    }
+    
     #[test]
     fn test_phash_similarity() {
         let config = SceneDetectionConfig::default();
@@ -390,6 +856,7 @@ mod tests {
             ssim_threshold: 0.8,
             phash_distance_threshold: 10,
             entropy_threshold: 0.1,
+            ..Default::default()
         };
         let detector = SceneDetector::new(config).unwrap();
         
@@ -415,6 +882,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             },
             Keyframe {
                 id: uuid::Uuid::new_v4(),
@@ -424,6 +893,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             },
             Keyframe {
                 id: uuid::Uuid::new_v4(),
@@ -433,6 +904,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             },
         ];
         
@@ -484,6 +957,57 @@ mod tests {
         assert!(confidence >= 0.0 && confidence <= 1.0, "Confidence should be between 0 and 1, got {}", confidence);
     }
     
+    #[test]
+    fn test_auto_calibration_adapts_to_noisy_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SceneDetectionConfig {
+            auto_calibrate: true,
+            calibration_min_samples: 5,
+            ..Default::default()
+        };
+        let detector = SceneDetector::new(config).unwrap();
+        assert!(!detector.current_thresholds().calibrated);
+        
+        // A block jumping between two positions simulates an animated UI changing every frame
+        let mut keyframes = Vec::new();
+        for i in 0..12 {
+            let img_path = temp_dir.path().join(format!("anim_{}.png", i));
+            let offset = if i % 2 == 0 { 8 } else { 40 };
+            let img: RgbImage = ImageBuffer::from_fn(64, 64, |x, y| {
+                if x >= offset && x < offset + 8 && y >= 24 && y < 32 {
+                    Rgb([255, 255, 255])
+                } else {
+                    Rgb([128, 128, 128])
+                }
+            });
+            save_test_image(&DynamicImage::ImageRgb8(img), &img_path).unwrap();
+            keyframes.push(Keyframe {
+                id: uuid::Uuid::new_v4(),
+                timestamp_ns: i as i64 * 1_000_000_000,
+                frame_path: img_path.to_string_lossy().to_string(),
+                segment_id: "animated".to_string(),
+                width: 64,
+                height: 64,
+                format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
+            });
+        }
+        
+        let static_detector = SceneDetector::new(SceneDetectionConfig::default()).unwrap();
+        let static_changes = static_detector.detect_scene_changes(&keyframes).unwrap();
+        let calibrated_changes = detector.detect_scene_changes(&keyframes).unwrap();
+        
+        assert!(detector.current_thresholds().calibrated);
+        assert!(calibrated_changes.len() < static_changes.len(),
+                "Calibration should suppress constant animation: {} vs {}",
+                calibrated_changes.len(), static_changes.len());
+        assert!(calibrated_changes.iter().all(|c| c.thresholds.calibrated));
+        
+        detector.reset_calibration();
+        assert!(!detector.current_thresholds().calibrated);
+    }
+    
     #[test]
     fn test_empty_keyframes() {
         let config = SceneDetectionConfig::default();
@@ -514,6 +1038,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             },
         ];
         
@@ -544,6 +1070,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             });
         }
         
@@ -577,6 +1105,8 @@ mod tests {
                 width: 64,
                 height: 64,
                 format: "RGB24".to_string(),
+                source_fps: None,
+                display_id: None,
             },
         ];
         
@@ -585,4 +1115,36 @@ mod tests {
         assert!(changes.is_ok());
         assert!(changes.unwrap().is_empty());
     }
+    
+    #[test]
+    fn test_changed_regions_localize_ui_change() {
+        let config = SceneDetectionConfig {
+            region_grid_size: 4,
+            ..Default::default()
+        };
+        let detector = SceneDetector::new(config).unwrap();
+        
+        let before = create_test_image(1280, 640, [0, 0, 0]);
+        let after: RgbImage = ImageBuffer::from_fn(1280, 640, |x, y| {
+            if x < 320 && y < 160 {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+        
+        let regions = detector.changed_regions(
+            &FramePyramid::from_image(&before),
+            &FramePyramid::from_image(&DynamicImage::ImageRgb8(after)),
+        );
+        
+        assert!(!regions.is_empty());
+        assert_eq!((regions[0].grid_row, regions[0].grid_col), (0, 0));
+        assert_eq!((regions[0].x, regions[0].y, regions[0].width, regions[0].height), (0, 0, 320, 160));
+        assert!(regions.iter().all(|r| r.grid_row <= 1 && r.grid_col <= 1));
+        
+        // Identical frames have no changed regions
+        let unchanged = detector.changed_regions(&FramePyramid::from_image(&before), &FramePyramid::from_image(&before));
+        assert!(unchanged.is_empty());
+    }
 }
\ No newline at end of file
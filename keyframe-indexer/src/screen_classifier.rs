@@ -0,0 +1,224 @@
+use crate::error::{IndexerError, Result};
+use crate::metadata_collector::FrameMetadata;
+use crate::visual_features::VisualFeatures;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kind of screen a keyframe shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenType {
+    Browser,
+    Ide,
+    Terminal,
+    VideoCall,
+    Other,
+}
+
+impl ScreenType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Browser => "browser",
+            Self::Ide => "ide",
+            Self::Terminal => "terminal",
+            Self::VideoCall => "video_call",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Self::Browser, Self::Ide, Self::Terminal, Self::VideoCall, Self::Other]
+            .into_iter()
+            .find(|screen_type| screen_type.as_str() == value)
+    }
+}
+
+/// Applications of each screen type, matched case-insensitively against the
+/// start of the app name
+const KNOWN_APPS: &[(ScreenType, &[&str])] = &[
+    (ScreenType::VideoCall, &["zoom.us", "zoom", "microsoft teams", "webex", "facetime", "skype"]),
+    (ScreenType::Terminal, &["terminal", "iterm2", "iterm", "warp", "alacritty", "kitty", "wezterm", "hyper", "ghostty"]),
+    (
+        ScreenType::Ide,
+        &[
+            "xcode", "code", "visual studio", "cursor", "intellij", "pycharm", "webstorm", "goland", "clion",
+            "rider", "rustrover", "android studio", "sublime text", "zed", "nova",
+        ],
+    ),
+    (
+        ScreenType::Browser,
+        &["safari", "google chrome", "chromium", "firefox", "microsoft edge", "arc", "brave", "opera", "vivaldi"],
+    ),
+];
+
+/// Window titles of calls held in a browser or another app
+const CALL_TITLES: &[&str] = &["meet.google.com", "google meet", "zoom meeting", "teams meeting", "webex meeting"];
+
+/// Screen type labelling settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenClassifierConfig {
+    pub enabled: bool,
+    /// Screen type of further apps, by case-insensitive app name prefix; these
+    /// win over the built-in list
+    pub apps: HashMap<String, ScreenType>,
+    /// Frames of unknown apps are labelled from their look only at this confidence or above
+    pub min_visual_confidence: f32,
+}
+
+impl Default for ScreenClassifierConfig {
+    fn default() -> Self {
+        Self { enabled: true, apps: HashMap::new(), min_visual_confidence: 0.6 }
+    }
+}
+
+impl ScreenClassifierConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.min_visual_confidence) {
+            return Err(IndexerError::Config(
+                "screen_classifier min_visual_confidence must be between 0 and 1".to_string(),
+            ));
+        }
+        if self.apps.keys().any(|app| app.trim().is_empty()) {
+            return Err(IndexerError::Config("screen_classifier apps must not have empty names".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Labels keyframes with a screen type, from the app and window title first and
+/// from the frame's look when the app is unknown
+pub struct ScreenClassifier {
+    config: ScreenClassifierConfig,
+    apps: Vec<(String, ScreenType)>,
+}
+
+impl ScreenClassifier {
+    pub fn new(config: ScreenClassifierConfig) -> Self {
+        // Longer prefixes first, so "codeblocks" rules win over "code"
+        let mut apps: Vec<(String, ScreenType)> =
+            config.apps.iter().map(|(app, screen_type)| (app.to_lowercase(), *screen_type)).collect();
+        apps.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { config, apps }
+    }
+
+    /// Screen type of a frame, or `None` when neither its app nor its look is telling
+    pub fn classify(&self, frame: &FrameMetadata) -> Option<ScreenType> {
+        let title = frame.win_title.to_lowercase();
+        if CALL_TITLES.iter().any(|call| title.contains(call)) {
+            return Some(ScreenType::VideoCall);
+        }
+
+        let app = frame.app_name.to_lowercase();
+        let configured = self.apps.iter().find(|(prefix, _)| app.starts_with(prefix.as_str()));
+        if let Some((_, screen_type)) = configured {
+            return Some(*screen_type);
+        }
+        let known = KNOWN_APPS
+            .iter()
+            .find(|(_, apps)| apps.iter().any(|name| is_app(&app, name)));
+        if let Some((screen_type, _)) = known {
+            return Some(*screen_type);
+        }
+
+        let (screen_type, confidence) = classify_visual(frame.visual.as_ref()?);
+        (confidence >= self.config.min_visual_confidence).then_some(screen_type)
+    }
+
+    /// Label every frame of a segment
+    pub fn label(&self, frames: &mut [FrameMetadata]) {
+        for frame in frames {
+            frame.screen_type = self.classify(frame);
+        }
+    }
+}
+
+/// Whether an app name is `name` or starts with it as a whole word, e.g.
+/// "code - insiders" but not "codeblocks"
+fn is_app(app: &str, name: &str) -> bool {
+    app.strip_prefix(name)
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with(|c: char| !c.is_alphanumeric()))
+}
+
+/// Best guess from colors and edges, with a rough confidence
+fn classify_visual(visual: &VisualFeatures) -> (ScreenType, f32) {
+    let Some(dominant) = visual.dominant_colors.first() else {
+        return (ScreenType::Other, 0.0);
+    };
+    let [r, g, b] = dominant.rgb;
+    let luma = (r as f32 * 0.299 + g as f32 * 0.587 + b as f32 * 0.114) / 255.0;
+    let text = visual.ui_text_density;
+
+    // Terminals: one dark background, text in many blocks, few other colors
+    if luma < 0.25 && dominant.share > 60.0 && text > 25.0 {
+        return (ScreenType::Terminal, 0.7);
+    }
+    // Editors: a dark or light theme with dense text and several syntax colors
+    if dominant.share > 40.0 && text > 40.0 && visual.dominant_colors.len() >= 4 {
+        return (ScreenType::Ide, 0.6);
+    }
+    // Web pages: mostly white with moderate text
+    if luma > 0.9 && dominant.share > 50.0 && text > 5.0 {
+        return (ScreenType::Browser, 0.6);
+    }
+    // Camera video: no dominant color and few sharp edges
+    if dominant.share < 15.0 && visual.edge_density < 3.0 && text < 10.0 {
+        return (ScreenType::VideoCall, 0.6);
+    }
+    (ScreenType::Other, 0.3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_features::DominantColor;
+
+    fn frame(app_name: &str, win_title: &str, visual: Option<VisualFeatures>) -> FrameMetadata {
+        FrameMetadata {
+            ts_ns: 0,
+            monitor_id: 0,
+            segment_id: "segment".to_string(),
+            path: "/frames/frame_0.png".to_string(),
+            phash16: 0,
+            entropy: 0.0,
+            app_name: app_name.to_string(),
+            win_title: win_title.to_string(),
+            width: 1920,
+            height: 1080,
+            duplicate_of: None,
+            ocr_density: None,
+            visual,
+            screen_type: None,
+        }
+    }
+
+    #[test]
+    fn test_classifies_from_app_title_and_look() {
+        let config = ScreenClassifierConfig {
+            apps: [("Hyper Backup".to_string(), ScreenType::Other)].into(),
+            ..ScreenClassifierConfig::default()
+        };
+        let classifier = ScreenClassifier::new(config);
+
+        assert_eq!(classifier.classify(&frame("Code", "main.rs — crate", None)), Some(ScreenType::Ide));
+        assert_eq!(classifier.classify(&frame("iTerm2", "zsh", None)), Some(ScreenType::Terminal));
+        // Calls in a browser tab are calls, configured apps beat built-in prefixes
+        assert_eq!(classifier.classify(&frame("Google Chrome", "Meet - meet.google.com", None)), Some(ScreenType::VideoCall));
+        assert_eq!(classifier.classify(&frame("Hyper Backup", "Tasks", None)), Some(ScreenType::Other));
+        assert_eq!(classifier.classify(&frame("CodeBlocks", "", None)), None);
+
+        let terminal_look = VisualFeatures {
+            dominant_colors: vec![DominantColor { rgb: [30, 30, 30], share: 75.0 }, DominantColor { rgb: [200, 200, 200], share: 25.0 }],
+            edge_density: 28.0,
+            ui_text_density: 80.0,
+        };
+        assert_eq!(classifier.classify(&frame("Unknown", "", Some(terminal_look))), Some(ScreenType::Terminal));
+        let blank_look = VisualFeatures {
+            dominant_colors: vec![DominantColor { rgb: [128, 128, 128], share: 100.0 }],
+            edge_density: 0.0,
+            ui_text_density: 0.0,
+        };
+        assert_eq!(classifier.classify(&frame("Unknown", "", Some(blank_look))), None);
+        assert_eq!(ScreenType::parse("video_call"), Some(ScreenType::VideoCall));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use chrono::{DateTime, Utc};
+use image::{DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Frames whose unshifted profiles differ by less than this (luma levels) are treated as static
+const MIN_FRAME_DIFFERENCE: f32 = 0.5;
+
+/// Scroll detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrollDetectionConfig {
+    pub enabled: bool,
+    /// Number of strips each row/column profile is split into; more strips tell
+    /// apart rows with the same mean brightness
+    pub strips: u32,
+    /// Largest shift searched, as a fraction of the frame height or width
+    pub max_shift_ratio: f32,
+    /// Shifts smaller than this many pixels are ignored
+    pub min_shift_px: u32,
+    /// Fraction by which the best shift must reduce the unshifted profile error
+    pub min_improvement: f32,
+    /// Largest mean profile error (luma levels) accepted for a match
+    pub max_match_error: f32,
+}
+
+impl Default for ScrollDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strips: 8,
+            max_shift_ratio: 0.5,
+            min_shift_px: 4,
+            min_improvement: 0.6,
+            max_match_error: 6.0,
+        }
+    }
+}
+
+/// Direction the view scrolled in (content moves the opposite way)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScrollDirection::Up => "up",
+            ScrollDirection::Down => "down",
+            ScrollDirection::Left => "left",
+            ScrollDirection::Right => "right",
+        }
+    }
+}
+
+/// Content translation found between two frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrollMotion {
+    pub direction: ScrollDirection,
+    /// Horizontal scroll offset in pixels; positive when scrolling right
+    pub dx: i32,
+    /// Vertical scroll offset in pixels; positive when scrolling down
+    pub dy: i32,
+    pub confidence: f32,
+}
+
+impl ScrollMotion {
+    pub fn magnitude(&self) -> u32 {
+        self.dx.unsigned_abs().max(self.dy.unsigned_abs())
+    }
+}
+
+/// Detects scrolling between consecutive keyframes by row/column profile matching.
+///
+/// Each frame is reduced to per-row (for vertical scroll) and per-column (for
+/// horizontal scroll) luma profiles split into strips; the shift that best aligns
+/// the previous profile with the current one is the scroll offset.
+pub struct ScrollDetector {
+    config: ScrollDetectionConfig,
+}
+
+impl ScrollDetector {
+    pub fn new(config: ScrollDetectionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Scroll between two frames, if the change between them is a translation
+    pub fn detect(&self, previous: &DynamicImage, current: &DynamicImage) -> Option<ScrollMotion> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let previous = previous.to_luma8();
+        let current = current.to_luma8();
+        if previous.dimensions() != current.dimensions() {
+            return None;
+        }
+
+        let vertical = self.best_shift(&row_profiles(&previous, self.config.strips), &row_profiles(&current, self.config.strips));
+        let horizontal = self.best_shift(&column_profiles(&previous, self.config.strips), &column_profiles(&current, self.config.strips));
+
+        let motion = match (vertical, horizontal) {
+            (Some(v), Some(h)) if h.1 > v.1 => Self::horizontal_motion(h),
+            (Some(v), _) => Self::vertical_motion(v),
+            (None, Some(h)) => Self::horizontal_motion(h),
+            (None, None) => return None,
+        };
+        debug!("Detected scroll {:?} of {}px", motion.direction, motion.magnitude());
+        Some(motion)
+    }
+
+    /// Load two keyframes from disk and detect scrolling between them
+    pub fn detect_between(&self, previous_path: &str, current_path: &str) -> Result<Option<ScrollMotion>> {
+        let previous = image::open(previous_path).map_err(IndexerError::Image)?;
+        let current = image::open(current_path).map_err(IndexerError::Image)?;
+        Ok(self.detect(&previous, &current))
+    }
+
+    /// Navigation event describing a scroll seen in `frame_id`
+    pub fn to_event(&self, motion: &ScrollMotion, frame_id: &str, previous_frame_id: &str, timestamp: DateTime<Utc>) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("navigation_type".to_string(), "scroll".to_string());
+        metadata.insert("scroll_direction".to_string(), motion.direction.as_str().to_string());
+        metadata.insert("scroll_dx".to_string(), motion.dx.to_string());
+        metadata.insert("scroll_dy".to_string(), motion.dy.to_string());
+        metadata.insert("scroll_magnitude".to_string(), motion.magnitude().to_string());
+
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: EventType::Navigation,
+            target: "scroll".to_string(),
+            value_from: None,
+            value_to: Some(format!("{} {}px", motion.direction.as_str(), motion.magnitude())),
+            confidence: motion.confidence,
+            evidence_frames: vec![previous_frame_id.to_string(), frame_id.to_string()],
+            metadata,
+        }
+    }
+
+    /// Shift aligning `previous` with `current` and its confidence.
+    ///
+    /// A positive shift means content moved towards the start (up or left),
+    /// i.e. `current[i] ≈ previous[i + shift]`.
+    fn best_shift(&self, previous: &[Vec<f32>], current: &[Vec<f32>]) -> Option<(i32, f32)> {
+        let length = current.len() as i32;
+        let max_shift = ((length as f32) * self.config.max_shift_ratio.clamp(0.0, 0.9)) as i32;
+        if max_shift < self.config.min_shift_px as i32 {
+            return None;
+        }
+
+        let unshifted = profile_error(previous, current, 0);
+        if unshifted < MIN_FRAME_DIFFERENCE {
+            return None;
+        }
+
+        let (shift, error) = (-max_shift..=max_shift)
+            .filter(|shift| *shift != 0)
+            .map(|shift| (shift, profile_error(previous, current, shift)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let improvement = 1.0 - error / unshifted;
+        if shift.unsigned_abs() < self.config.min_shift_px
+            || error > self.config.max_match_error
+            || improvement < self.config.min_improvement
+        {
+            return None;
+        }
+
+        Some((shift, improvement.clamp(0.0, 1.0)))
+    }
+
+    fn vertical_motion((shift, confidence): (i32, f32)) -> ScrollMotion {
+        ScrollMotion {
+            direction: if shift > 0 { ScrollDirection::Down } else { ScrollDirection::Up },
+            dx: 0,
+            dy: shift,
+            confidence,
+        }
+    }
+
+    fn horizontal_motion((shift, confidence): (i32, f32)) -> ScrollMotion {
+        ScrollMotion {
+            direction: if shift > 0 { ScrollDirection::Right } else { ScrollDirection::Left },
+            dx: shift,
+            dy: 0,
+            confidence,
+        }
+    }
+}
+
+/// Mean absolute difference between `current[i]` and `previous[i + shift]` over the overlap
+fn profile_error(previous: &[Vec<f32>], current: &[Vec<f32>], shift: i32) -> f32 {
+    let length = current.len() as i32;
+    let start = (-shift).max(0);
+    let end = (length - shift).min(length);
+    if end <= start {
+        return f32::MAX;
+    }
+
+    let mut total = 0.0;
+    let mut count = 0usize;
+    for i in start..end {
+        let (cur, prev) = (&current[i as usize], &previous[(i + shift) as usize]);
+        for (a, b) in cur.iter().zip(prev) {
+            total += (a - b).abs();
+            count += 1;
+        }
+    }
+    if count == 0 { f32::MAX } else { total / count as f32 }
+}
+
+/// Mean luma of each row, split into `strips` horizontal segments
+fn row_profiles(image: &GrayImage, strips: u32) -> Vec<Vec<f32>> {
+    let (width, height) = image.dimensions();
+    let strips = strips.clamp(1, width.max(1));
+    (0..height)
+        .map(|y| {
+            (0..strips)
+                .map(|s| {
+                    let (x0, x1) = (s * width / strips, (s + 1) * width / strips);
+                    let sum: u32 = (x0..x1).map(|x| image.get_pixel(x, y)[0] as u32).sum();
+                    sum as f32 / (x1 - x0).max(1) as f32
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Mean luma of each column, split into `strips` vertical segments
+fn column_profiles(image: &GrayImage, strips: u32) -> Vec<Vec<f32>> {
+    let (width, height) = image.dimensions();
+    let strips = strips.clamp(1, height.max(1));
+    (0..width)
+        .map(|x| {
+            (0..strips)
+                .map(|s| {
+                    let (y0, y1) = (s * height / strips, (s + 1) * height / strips);
+                    let sum: u32 = (y0..y1).map(|y| image.get_pixel(x, y)[0] as u32).sum();
+                    sum as f32 / (y1 - y0).max(1) as f32
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    /// A tall "document" with irregular stripes, viewed through a 200x120 window at `offset`
+    fn viewport(offset_y: u32) -> DynamicImage {
+        let document = |y: u32, x: u32| -> u8 {
+            let line = y / 7;
+            if (line * 37 + x / 23) % 5 == 0 || (line * 13) % 7 == 3 { 30 } else { 220 }
+        };
+        DynamicImage::ImageLuma8(GrayImage::from_fn(200, 120, |x, y| Luma([document(y + offset_y, x)])))
+    }
+
+    #[test]
+    fn test_vertical_scroll_detected() {
+        let detector = ScrollDetector::new(ScrollDetectionConfig::default());
+
+        let motion = detector.detect(&viewport(10), &viewport(35)).unwrap();
+        assert_eq!(motion.direction, ScrollDirection::Down);
+        assert_eq!(motion.dy, 25);
+        assert!(motion.confidence > 0.6);
+
+        let motion = detector.detect(&viewport(35), &viewport(10)).unwrap();
+        assert_eq!(motion.direction, ScrollDirection::Up);
+        assert_eq!(motion.magnitude(), 25);
+
+        // Identical frames and unrelated content are not scrolls
+        assert!(detector.detect(&viewport(10), &viewport(10)).is_none());
+        let inverted = DynamicImage::ImageLuma8(GrayImage::from_fn(200, 120, |x, y| Luma([if (x / 10 + y / 10) % 2 == 0 { 0 } else { 255 }])));
+        assert!(detector.detect(&viewport(10), &inverted).is_none());
+
+        let event = detector.to_event(&motion, "frame_2", "frame_1", Utc::now());
+        assert_eq!(event.event_type, EventType::Navigation);
+        assert_eq!(event.metadata.get("scroll_direction").map(String::as_str), Some("up"));
+        assert_eq!(event.evidence_frames, vec!["frame_1".to_string(), "frame_2".to_string()]);
+    }
+}
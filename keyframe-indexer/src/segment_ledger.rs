@@ -0,0 +1,278 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Ledger handle shared between the file watcher and the processing loop
+pub type SharedSegmentLedger = Arc<Mutex<SegmentLedger>>;
+
+/// Operation recorded in the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerOp {
+    Pending,
+    Ack,
+}
+
+/// Single append-only ledger record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerRecord {
+    pub op: LedgerOp,
+    pub path: PathBuf,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Durable ledger of video segments that were detected but not yet processed.
+///
+/// Segments are appended as `pending` before they are handed to the processing
+/// channel and marked `ack` once processing completes, so work queued at the
+/// time of a restart is replayed instead of lost.
+pub struct SegmentLedger {
+    path: PathBuf,
+    pending: HashMap<PathBuf, DateTime<Utc>>,
+    record_count: usize,
+    compaction_threshold: usize,
+}
+
+impl SegmentLedger {
+    /// Open (or create) a ledger file and replay its records
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut pending = HashMap::new();
+        let mut record_count = 0;
+        let mut needs_repair = false;
+
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for (line_no, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                // A torn final write after a crash must not make the ledger unreadable
+                let record: LedgerRecord = match serde_json::from_str(&line) {
+                    Ok(record) => record,
+                    Err(e) => {
+                        warn!("Skipping malformed ledger record at line {}: {}", line_no + 1, e);
+                        needs_repair = true;
+                        continue;
+                    }
+                };
+
+                record_count += 1;
+                match record.op {
+                    LedgerOp::Pending => {
+                        pending.entry(record.path).or_insert(record.recorded_at);
+                    }
+                    LedgerOp::Ack => {
+                        pending.remove(&record.path);
+                    }
+                }
+            }
+        }
+
+        info!("Opened segment ledger {} with {} pending segments", path.display(), pending.len());
+
+        let mut ledger = Self {
+            path,
+            pending,
+            record_count,
+            compaction_threshold: 1000,
+        };
+
+        // Rewrite the file so new records are not appended onto a partial line
+        if needs_repair {
+            ledger.compact()?;
+        }
+
+        Ok(ledger)
+    }
+
+    /// Open a ledger wrapped for sharing across tasks
+    pub fn open_shared<P: AsRef<Path>>(path: P) -> Result<SharedSegmentLedger> {
+        Ok(Arc::new(Mutex::new(Self::open(path)?)))
+    }
+
+    /// Record a segment as pending. Returns false if it is already pending.
+    pub fn record_pending(&mut self, segment: &Path) -> Result<bool> {
+        if self.pending.contains_key(segment) {
+            debug!("Segment already pending in ledger: {}", segment.display());
+            return Ok(false);
+        }
+
+        let recorded_at = Utc::now();
+        self.append(&LedgerRecord {
+            op: LedgerOp::Pending,
+            path: segment.to_path_buf(),
+            recorded_at,
+        })?;
+        self.pending.insert(segment.to_path_buf(), recorded_at);
+
+        Ok(true)
+    }
+
+    /// Acknowledge that a segment has been fully processed
+    pub fn ack(&mut self, segment: &Path) -> Result<()> {
+        if self.pending.remove(segment).is_none() {
+            debug!("Ack for segment not pending in ledger: {}", segment.display());
+            return Ok(());
+        }
+
+        self.append(&LedgerRecord {
+            op: LedgerOp::Ack,
+            path: segment.to_path_buf(),
+            recorded_at: Utc::now(),
+        })?;
+
+        if self.record_count >= self.compaction_threshold
+            && self.record_count > self.pending.len() * 2
+        {
+            self.compact()?;
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a segment is awaiting processing
+    pub fn is_pending(&self, segment: &Path) -> bool {
+        self.pending.contains_key(segment)
+    }
+
+    /// Pending segments in the order they were first recorded
+    pub fn pending_segments(&self) -> Vec<PathBuf> {
+        let mut entries: Vec<(&PathBuf, &DateTime<Utc>)> = self.pending.iter().collect();
+        entries.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
+        entries.into_iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Number of segments awaiting processing
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// When the longest-waiting pending segment was recorded
+    pub fn oldest_pending_at(&self) -> Option<DateTime<Utc>> {
+        self.pending.values().min().copied()
+    }
+
+    /// Rewrite the ledger so it only contains the currently pending segments
+    pub fn compact(&mut self) -> Result<()> {
+        let temp_path = self.path.with_extension("jsonl.tmp");
+
+        {
+            let mut file = File::create(&temp_path)?;
+            for segment in self.pending_segments() {
+                let record = LedgerRecord {
+                    op: LedgerOp::Pending,
+                    recorded_at: self.pending[&segment],
+                    path: segment,
+                };
+                writeln!(file, "{}", serde_json::to_string(&record)?)?;
+            }
+            file.sync_all()?;
+        }
+
+        std::fs::rename(&temp_path, &self.path)?;
+        self.record_count = self.pending.len();
+
+        debug!("Compacted segment ledger to {} records", self.record_count);
+        Ok(())
+    }
+
+    /// Set the record count after which acknowledged entries are compacted away
+    pub fn set_compaction_threshold(&mut self, threshold: usize) {
+        self.compaction_threshold = threshold.max(1);
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    // MARK: - Private Helper Methods
+
+    fn append(&mut self, record: &LedgerRecord) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", line)?;
+        file.sync_data()?;
+
+        self.record_count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pending_survives_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let ledger_path = temp_dir.path().join("pending_segments.jsonl");
+
+        {
+            let mut ledger = SegmentLedger::open(&ledger_path).unwrap();
+            assert!(ledger.record_pending(Path::new("/videos/a.mp4")).unwrap());
+            assert!(ledger.record_pending(Path::new("/videos/b.mp4")).unwrap());
+            ledger.ack(Path::new("/videos/a.mp4")).unwrap();
+        }
+
+        let ledger = SegmentLedger::open(&ledger_path).unwrap();
+        assert_eq!(ledger.pending_segments(), vec![PathBuf::from("/videos/b.mp4")]);
+    }
+
+    #[test]
+    fn test_duplicate_pending_is_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut ledger = SegmentLedger::open(temp_dir.path().join("ledger.jsonl")).unwrap();
+
+        assert!(ledger.record_pending(Path::new("/videos/a.mp4")).unwrap());
+        assert!(!ledger.record_pending(Path::new("/videos/a.mp4")).unwrap());
+        assert_eq!(ledger.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_compaction_and_malformed_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let ledger_path = temp_dir.path().join("ledger.jsonl");
+
+        let mut ledger = SegmentLedger::open(&ledger_path).unwrap();
+        ledger.set_compaction_threshold(4);
+        for name in ["a.mp4", "b.mp4", "c.mp4"] {
+            ledger.record_pending(Path::new(name)).unwrap();
+        }
+        ledger.ack(Path::new("a.mp4")).unwrap();
+        ledger.ack(Path::new("b.mp4")).unwrap();
+
+        let contents = std::fs::read_to_string(&ledger_path).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+
+        // Simulate a torn write at the end of the file
+        let mut file = OpenOptions::new().append(true).open(&ledger_path).unwrap();
+        write!(file, "{{\"op\":\"pend").unwrap();
+        drop(file);
+
+        let ledger = SegmentLedger::open(&ledger_path).unwrap();
+        assert!(ledger.is_pending(Path::new("c.mp4")));
+        assert_eq!(ledger.pending_count(), 1);
+    }
+}
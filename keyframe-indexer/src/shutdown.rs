@@ -0,0 +1,105 @@
+use crate::error::Result;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Set once the process is asked to stop with Ctrl+C (SIGINT) or SIGTERM.
+///
+/// Handlers are installed when the listener is created, so a signal that
+/// arrives while a segment is being processed is not lost; the service sees
+/// it once the segment is done. Clones observe the same request.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    requested: watch::Receiver<bool>,
+}
+
+impl ShutdownSignal {
+    /// Install the signal handlers; must be called within a Tokio runtime.
+    /// A second signal exits at once, without flushing.
+    pub fn listen() -> Result<Self> {
+        let (sender, signal) = Self::channel();
+        let mut signals = Signals::install()?;
+        tokio::spawn(async move {
+            info!("Received {}; finishing in-flight work before exiting", signals.next().await);
+            let _ = sender.send(true);
+            warn!("Received {} again; exiting without flushing", signals.next().await);
+            std::process::exit(130);
+        });
+        Ok(signal)
+    }
+
+    /// A signal requested through the returned sender instead of by the OS,
+    /// e.g. by an app embedding the service
+    pub fn channel() -> (watch::Sender<bool>, Self) {
+        let (sender, requested) = watch::channel(false);
+        (sender, Self { requested })
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Wait until shutdown is requested
+    pub async fn requested(&mut self) {
+        // Without a sender no request can come anymore
+        if self.requested.wait_for(|requested| *requested).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+struct Signals {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    fn install() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn install() -> Result<Self> {
+        Ok(Self {})
+    }
+
+    /// Name of the next signal received
+    async fn next(&mut self) -> &'static str {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.interrupt.recv() => "SIGINT",
+                _ = self.terminate.recv() => "SIGTERM",
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            "Ctrl+C"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_request_is_seen_by_clones() {
+        let (sender, mut signal) = ShutdownSignal::channel();
+        let observer = signal.clone();
+        assert!(!observer.is_requested());
+        assert!(tokio::time::timeout(Duration::from_millis(20), signal.requested()).await.is_err());
+
+        sender.send(true).unwrap();
+        signal.requested().await;
+        assert!(observer.is_requested());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::enum_codes::EnumCode;
+use crate::error::Result;
+use crate::event_detector::DetectedEvent;
+use crate::metadata_collector::FrameMetadata;
+use crate::ocr_data::OCRResult;
+use crate::output_sink::OutputSink;
+use crate::visual_features::palette_to_text;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS frames (
+    id INTEGER PRIMARY KEY,
+    ts_ns INTEGER NOT NULL,
+    monitor_id INTEGER NOT NULL,
+    segment_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    phash16 INTEGER NOT NULL,
+    entropy REAL NOT NULL,
+    app_name TEXT NOT NULL,
+    win_title TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    duplicate_of TEXT,
+    dominant_colors TEXT,
+    edge_density REAL,
+    ui_text_density REAL,
+    screen_type TEXT
+);
+CREATE INDEX IF NOT EXISTS frames_segment ON frames (segment_id, ts_ns);
+CREATE INDEX IF NOT EXISTS frames_app ON frames (app_name);
+CREATE INDEX IF NOT EXISTS frames_path ON frames (path);
+CREATE INDEX IF NOT EXISTS frames_screen_type ON frames (screen_type, ts_ns);
+
+CREATE TABLE IF NOT EXISTS ocr (
+    id INTEGER PRIMARY KEY,
+    frame_id TEXT NOT NULL,
+    text TEXT NOT NULL,
+    language TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    width REAL NOT NULL,
+    height REAL NOT NULL,
+    processor TEXT NOT NULL,
+    processed_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ocr_frame ON ocr (frame_id);
+CREATE VIRTUAL TABLE IF NOT EXISTS ocr_fts USING fts5 (text, content = 'ocr', content_rowid = 'id');
+
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    type TEXT NOT NULL,
+    target TEXT NOT NULL,
+    value_from TEXT,
+    value_to TEXT,
+    confidence REAL NOT NULL,
+    evidence_frames TEXT NOT NULL,
+    metadata TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp);
+CREATE INDEX IF NOT EXISTS events_type ON events (type, timestamp);
+";
+
+/// Writes frames, OCR results and events into one SQLite database.
+///
+/// OCR text is indexed with FTS5 in `ocr_fts`, so a session can be searched
+/// with plain SQL, e.g. `SELECT ocr.* FROM ocr_fts JOIN ocr ON ocr.id = ocr_fts.rowid
+/// WHERE ocr_fts MATCH 'invoice'`. Timestamps are RFC 3339 text, evidence frames
+/// are separated by `;`, dominant colors are `#rrggbb:share` separated by `;`
+/// and event metadata is JSON, as in CSV exports.
+pub struct SqliteSink {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl SqliteSink {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(&path)?;
+        connection.execute_batch("PRAGMA journal_mode = WAL;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { path, connection })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+impl OutputSink for SqliteSink {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn write_frames(&mut self, frames: &[FrameMetadata]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO frames (ts_ns, monitor_id, segment_id, path, phash16, entropy, app_name, win_title, width, height, duplicate_of, dominant_colors, edge_density, ui_text_density, screen_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            )?;
+            for frame in frames {
+                insert.execute(params![
+                    frame.ts_ns,
+                    frame.monitor_id,
+                    frame.segment_id,
+                    frame.path,
+                    frame.phash16,
+                    frame.entropy,
+                    frame.app_name,
+                    frame.win_title,
+                    frame.width,
+                    frame.height,
+                    frame.duplicate_of,
+                    frame.visual.as_ref().map(|v| palette_to_text(&v.dominant_colors)),
+                    frame.visual.as_ref().map(|v| v.edge_density),
+                    frame.visual.as_ref().map(|v| v.ui_text_density),
+                    frame.screen_type.map(|t| t.as_str()),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        debug!("Wrote {} frames to {}", frames.len(), self.path.display());
+        Ok(())
+    }
+
+    fn write_ocr(&mut self, results: &[OCRResult]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO ocr (frame_id, text, language, confidence, x, y, width, height, processor, processed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            let mut index = transaction.prepare_cached("INSERT INTO ocr_fts (rowid, text) VALUES (?1, ?2)")?;
+            for result in results {
+                insert.execute(params![
+                    result.frame_id,
+                    result.text,
+                    result.language,
+                    result.confidence,
+                    result.roi.x,
+                    result.roi.y,
+                    result.roi.width,
+                    result.roi.height,
+                    result.processor,
+                    result.processed_at.to_rfc3339(),
+                ])?;
+                index.execute(params![transaction.last_insert_rowid(), result.text])?;
+            }
+        }
+        transaction.commit()?;
+        debug!("Wrote {} OCR results to {}", results.len(), self.path.display());
+        Ok(())
+    }
+
+    fn write_events(&mut self, events: &[DetectedEvent]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            // Reprocessing a segment yields the same event ids again
+            let mut insert = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO events (id, timestamp, type, target, value_from, value_to, confidence, evidence_frames, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for event in events {
+                insert.execute(params![
+                    event.id,
+                    event.timestamp.to_rfc3339(),
+                    event.event_type.name(),
+                    event.target,
+                    event.value_from,
+                    event.value_to,
+                    event.confidence,
+                    event.evidence_frames.join(";"),
+                    serde_json::to_string(&event.metadata)?,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        debug!("Wrote {} events to {}", events.len(), self.path.display());
+        Ok(())
+    }
+
+    /// Move the write-ahead log into the database file, so the file alone is complete
+    fn finish(&mut self) -> Result<()> {
+        self.connection.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use crate::ocr_data::BoundingBox;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sqlite_sink_indexes_ocr_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut sink = SqliteSink::open(temp_dir.path().join("session.db")).unwrap();
+
+        let ocr = |text: &str| OCRResult {
+            frame_id: "frame_1.png".to_string(),
+            roi: BoundingBox::new(10.0, 20.0, 300.0, 24.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.95,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        };
+        sink.write_ocr(&[ocr("Invoice 4711 overdue"), ocr("Weekly status report")]).unwrap();
+
+        let event = DetectedEvent {
+            id: "event-1".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ErrorDisplay,
+            target: "login_form".to_string(),
+            value_from: None,
+            value_to: Some("Invalid password".to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1.png".to_string()],
+            metadata: HashMap::new(),
+        };
+        sink.write_events(&[event.clone()]).unwrap();
+        sink.write_events(&[event]).unwrap();
+
+        let connection = sink.connection();
+        let matched: String = connection
+            .query_row(
+                "SELECT ocr.text FROM ocr_fts JOIN ocr ON ocr.id = ocr_fts.rowid WHERE ocr_fts MATCH 'invoice'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matched, "Invoice 4711 overdue");
+        let events: i64 = connection.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0)).unwrap();
+        assert_eq!(events, 1);
+    }
+}
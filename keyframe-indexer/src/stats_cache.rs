@@ -0,0 +1,339 @@
+use crate::error::Result;
+use crate::layout_compat;
+use arrow::array::{Array, Float32Array};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+
+/// Manifest kept next to the Parquet files; the leading underscore keeps it
+/// out of every listing of data files
+pub const STATS_MANIFEST_FILE: &str = "_stats_manifest.json";
+
+/// Row count, confidence sum and value counts of one Parquet file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileStats {
+    pub rows: u64,
+    pub size_bytes: u64,
+    /// Modification time when the stats were taken; a rewritten file is recounted
+    pub modified_ns: i64,
+    pub confidence_sum: f64,
+    /// Rows per value of each categorical column
+    pub distributions: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl FileStats {
+    /// Stats of the batches of a file; `columns` are counted by value
+    pub fn from_batches<'a>(batches: impl IntoIterator<Item = &'a RecordBatch>, columns: &[&str]) -> Result<Self> {
+        let mut stats = Self::default();
+        for batch in batches {
+            stats.add_batch(batch, columns)?;
+        }
+        Ok(stats)
+    }
+
+    /// Count one more batch of the same file
+    pub fn add_batch(&mut self, batch: &RecordBatch, columns: &[&str]) -> Result<()> {
+        self.rows += batch.num_rows() as u64;
+        if let Some(confidence) = batch
+            .column_by_name("confidence")
+            .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
+        {
+            self.confidence_sum += confidence.iter().flatten().map(f64::from).sum::<f64>();
+        }
+        for column in columns {
+            let Some(array) = batch.column_by_name(column) else {
+                continue;
+            };
+            let formatter = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())?;
+            let counts = self.distributions.entry(column.to_string()).or_default();
+            for row in (0..array.len()).filter(|row| array.is_valid(*row)) {
+                *counts.entry(formatter.value(row).to_string()).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a file's stats; only the confidence and counted columns are decoded
+    fn read(path: &Path, columns: &[&str]) -> Result<Self> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+        let wanted: Vec<usize> = std::iter::once("confidence")
+            .chain(columns.iter().copied())
+            .filter_map(|name| builder.schema().index_of(name).ok())
+            .collect();
+        let mask = ProjectionMask::roots(builder.parquet_schema(), wanted);
+        let batches = builder.with_projection(mask).build()?.collect::<std::result::Result<Vec<_>, _>>()?;
+        Self::from_batches(&batches, columns)
+    }
+
+    pub fn average_confidence(&self) -> f32 {
+        if self.rows == 0 {
+            0.0
+        } else {
+            (self.confidence_sum / self.rows as f64) as f32
+        }
+    }
+
+    /// Value counts of `column`, empty if it was never counted
+    pub fn counts(&self, column: &str) -> impl Iterator<Item = (&str, u64)> {
+        self.distributions.get(column).into_iter().flatten().map(|(value, count)| (value.as_str(), *count))
+    }
+
+    /// Stats of a file on disk, stamped with its size and modification
+    /// time; unreadable files count only their size
+    fn read_stamped(path: &Path, columns: &[&str]) -> Result<Self> {
+        let (size_bytes, modified_ns) = stamp(path)?;
+        debug!("Reading statistics of {}", path.display());
+        let mut stats = Self::read(path, columns).unwrap_or_else(|e| {
+            // E.g. encrypted files written before the manifest existed
+            warn!("Counting only the size of {}: {}", path.display(), e);
+            Self::default()
+        });
+        (stats.size_bytes, stats.modified_ns) = (size_bytes, modified_ns);
+        Ok(stats)
+    }
+
+    fn merge(&mut self, other: &FileStats) {
+        self.rows += other.rows;
+        self.size_bytes += other.size_bytes;
+        self.confidence_sum += other.confidence_sum;
+        for (column, counts) in &other.distributions {
+            let merged = self.distributions.entry(column.clone()).or_default();
+            for (value, count) in counts {
+                *merged.entry(value.clone()).or_default() += count;
+            }
+        }
+    }
+
+    /// Take the stats of a file out of an aggregate
+    fn subtract(&mut self, other: &FileStats) {
+        self.rows = self.rows.saturating_sub(other.rows);
+        self.size_bytes = self.size_bytes.saturating_sub(other.size_bytes);
+        self.confidence_sum = (self.confidence_sum - other.confidence_sum).max(0.0);
+        for (column, counts) in &other.distributions {
+            let Some(merged) = self.distributions.get_mut(column) else {
+                continue;
+            };
+            for (value, count) in counts {
+                match merged.get(value).map_or(0, |total| total.saturating_sub(*count)) {
+                    0 => merged.remove(value),
+                    remaining => merged.insert(value.clone(), remaining),
+                };
+            }
+        }
+    }
+}
+
+/// Per-file statistics of a Parquet output directory, with their aggregate.
+///
+/// Writers record each file's stats when they flush it and erasure
+/// invalidates the files it rewrites or deletes, so the aggregate is kept up
+/// to date without listing the directory. Invalidated files still on disk
+/// are read once on the next refresh. A manifest that was never checked
+/// against the directory, e.g. one written before the aggregate was kept, is
+/// rebuilt from a full listing once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsManifest {
+    /// Stats by file path relative to the directory
+    files: BTreeMap<String, FileStats>,
+    /// Stats of every file added together
+    #[serde(default)]
+    totals: FileStats,
+    /// Files changed outside the writers, read again on the next refresh
+    #[serde(default)]
+    pending: BTreeSet<String>,
+    /// Whether the files were checked against a directory listing
+    #[serde(default)]
+    listed: bool,
+}
+
+impl StatsManifest {
+    /// The stored manifest of `dir`; missing or unreadable manifests start empty
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(STATS_MANIFEST_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Rebuilding unreadable statistics manifest {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let temp = dir.join(format!("{}.tmp", STATS_MANIFEST_FILE));
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, dir.join(STATS_MANIFEST_FILE))?;
+        Ok(())
+    }
+
+    /// Store the stats of a file just written to `dir`
+    pub fn record(dir: &Path, file: &Path, mut stats: FileStats) -> Result<()> {
+        let mut manifest = Self::load(dir);
+        (stats.size_bytes, stats.modified_ns) = stamp(file)?;
+        let key = relative_key(dir, file);
+        manifest.pending.remove(&key);
+        manifest.insert(key, stats);
+        manifest.save(dir)
+    }
+
+    /// Forget the stats of a file in `dir` that was rewritten or deleted
+    /// outside the writers; a file still there is read on the next refresh
+    pub fn invalidate(dir: &Path, file: &Path) -> Result<()> {
+        let mut manifest = Self::load(dir);
+        let key = relative_key(dir, file);
+        if let Some(stats) = manifest.files.remove(&key) {
+            manifest.totals.subtract(&stats);
+        }
+        manifest.pending.insert(key);
+        manifest.save(dir)
+    }
+
+    /// The manifest of `dir` with its invalidated files read again, stored
+    /// if anything changed; only a manifest never checked against the
+    /// directory lists it
+    pub fn refresh(dir: &Path, columns: &[&str]) -> Result<Self> {
+        let mut manifest = Self::load(dir);
+        let changed = if !manifest.listed {
+            manifest.relist(dir, columns)?;
+            true
+        } else if !manifest.pending.is_empty() {
+            for key in std::mem::take(&mut manifest.pending) {
+                let file = dir.join(&key);
+                if file.is_file() {
+                    let stats = FileStats::read_stamped(&file, columns)?;
+                    manifest.insert(key, stats);
+                }
+            }
+            true
+        } else {
+            false
+        };
+
+        if changed {
+            if let Err(e) = manifest.save(dir) {
+                warn!("Failed to store statistics manifest in {}: {}", dir.display(), e);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Rebuild the files and aggregate from a listing of `dir`, keeping the
+    /// stats of files whose size and modification time are unchanged
+    fn relist(&mut self, dir: &Path, columns: &[&str]) -> Result<()> {
+        let stored = std::mem::take(&mut self.files);
+        self.totals = FileStats::default();
+        self.pending.clear();
+
+        for file in layout_compat::parquet_files(dir)? {
+            let key = relative_key(dir, &file);
+            let (size_bytes, modified_ns) = stamp(&file)?;
+            let cached = stored
+                .get(&key)
+                .filter(|stats| stats.size_bytes == size_bytes && stats.modified_ns == modified_ns);
+            let stats = match cached {
+                Some(stats) => stats.clone(),
+                None => FileStats::read_stamped(&file, columns)?,
+            };
+            self.insert(key, stats);
+        }
+        self.listed = true;
+        Ok(())
+    }
+
+    /// Add or replace the stats of a file, keeping the aggregate in step
+    fn insert(&mut self, key: String, stats: FileStats) {
+        self.totals.merge(&stats);
+        if let Some(previous) = self.files.insert(key, stats) {
+            self.totals.subtract(&previous);
+        }
+    }
+
+    /// Stats of every file added together
+    pub fn totals(&self) -> FileStats {
+        self.totals.clone()
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+fn stamp(file: &Path) -> Result<(u64, i64)> {
+    let metadata = std::fs::metadata(file)?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as i64);
+    Ok((metadata.len(), modified_ns))
+}
+
+fn relative_key(dir: &Path, file: &Path) -> String {
+    file.strip_prefix(dir).unwrap_or(file).to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    fn write(path: &Path, languages: &[&str]) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("language", DataType::Utf8, false),
+            Field::new("confidence", DataType::Float32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(languages.to_vec())),
+                Arc::new(Float32Array::from(vec![0.5; languages.len()])),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        batch
+    }
+
+    #[test]
+    fn test_manifest_follows_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("ocr_1.parquet");
+        let batch = write(&first, &["en", "en", "de"]);
+        StatsManifest::record(dir.path(), &first, FileStats::from_batches([&batch], &["language"]).unwrap()).unwrap();
+
+        // A file written without recording is read from disk
+        let second = dir.path().join("ocr_2.parquet");
+        write(&second, &["fr"]);
+        let totals = StatsManifest::refresh(dir.path(), &["language"]).unwrap().totals();
+        assert_eq!(totals.rows, 4);
+        assert!((totals.average_confidence() - 0.5).abs() < 1e-6);
+        assert_eq!(totals.counts("language").collect::<Vec<_>>(), vec![("de", 1), ("en", 2), ("fr", 1)]);
+
+        // Once listed, files not recorded or invalidated aren't looked for
+        let third = dir.path().join("ocr_3.parquet");
+        write(&third, &["fr"]);
+        assert_eq!(StatsManifest::refresh(dir.path(), &["language"]).unwrap().totals().rows, 4);
+
+        // Erasure deletes one file and rewrites another
+        std::fs::remove_file(&first).unwrap();
+        StatsManifest::invalidate(dir.path(), &first).unwrap();
+        write(&second, &["fr", "it"]);
+        StatsManifest::invalidate(dir.path(), &second).unwrap();
+        let manifest = StatsManifest::refresh(dir.path(), &["language"]).unwrap();
+        assert_eq!((manifest.file_count(), manifest.totals().rows), (1, 2));
+        assert_eq!(manifest.totals().counts("language").collect::<Vec<_>>(), vec![("fr", 1), ("it", 1)]);
+        assert_eq!(StatsManifest::load(dir.path()).totals().rows, 2);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::keyframe_codec::{self, KeyframeStorageConfig};
+use crate::system_probe::SystemProbe;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+const MIB: u64 = 1024 * 1024;
+
+/// Disk space thresholds and the per-session storage budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageMonitorConfig {
+    /// Off by default; it runs `df` and may throttle or pause processing
+    pub enabled: bool,
+    /// Below this much free space, keyframes are extracted at `reduced_fps`
+    pub reduce_rate_below_mb: u64,
+    /// Below this much free space, keyframes are kept as thumbnails only
+    pub thumbnails_below_mb: u64,
+    /// Below this much free space, segments are left queued until space is freed
+    pub pause_below_mb: u64,
+    /// Most MiB written in one session; unlimited when unset
+    pub session_budget_mb: Option<u64>,
+    /// Share of the budget used at which the rate is reduced
+    pub budget_reduce_rate_at: f64,
+    /// Share of the budget used at which keyframes are kept as thumbnails only;
+    /// processing pauses once the budget is used up
+    pub budget_thumbnails_at: f64,
+    pub reduced_fps: f32,
+    /// Width keyframes are scaled down to when kept as thumbnails
+    pub thumbnail_width: u32,
+    /// Free space is checked at most this often
+    pub check_interval_secs: u64,
+}
+
+impl Default for StorageMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            reduce_rate_below_mb: 10 * 1024,
+            thumbnails_below_mb: 5 * 1024,
+            pause_below_mb: 1024,
+            session_budget_mb: None,
+            budget_reduce_rate_at: 0.8,
+            budget_thumbnails_at: 0.9,
+            reduced_fps: 0.5,
+            thumbnail_width: 320,
+            check_interval_secs: 30,
+        }
+    }
+}
+
+impl StorageMonitorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.pause_below_mb <= self.thumbnails_below_mb && self.thumbnails_below_mb <= self.reduce_rate_below_mb) {
+            return Err(IndexerError::Config(
+                "storage_monitor needs pause_below_mb <= thumbnails_below_mb <= reduce_rate_below_mb".to_string(),
+            ));
+        }
+        let fraction = |f: f64| f > 0.0 && f <= 1.0;
+        if !fraction(self.budget_reduce_rate_at)
+            || !fraction(self.budget_thumbnails_at)
+            || self.budget_thumbnails_at < self.budget_reduce_rate_at
+        {
+            return Err(IndexerError::Config(
+                "storage_monitor needs 0 < budget_reduce_rate_at <= budget_thumbnails_at <= 1".to_string(),
+            ));
+        }
+        if self.session_budget_mb == Some(0) || self.reduced_fps <= 0.0 || self.thumbnail_width == 0 {
+            return Err(IndexerError::Config(
+                "storage_monitor session_budget_mb, reduced_fps and thumbnail_width must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How far processing is cut back to save space, mildest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageLevel {
+    Normal,
+    ReducedRate,
+    ThumbnailsOnly,
+    Paused,
+}
+
+impl StorageLevel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::ReducedRate => "reduced_rate",
+            Self::ThumbnailsOnly => "thumbnails_only",
+            Self::Paused => "paused",
+        }
+    }
+}
+
+/// A move from one storage level to another
+#[derive(Debug, Clone)]
+pub struct StorageChange {
+    pub from: StorageLevel,
+    pub to: StorageLevel,
+    /// Free space when the change was made, if it could be read
+    pub free_bytes: Option<u64>,
+    pub session_bytes: u64,
+}
+
+impl StorageChange {
+    pub fn to_event(&self, timestamp: DateTime<Utc>) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        if let Some(free) = self.free_bytes {
+            metadata.insert("free_mb".to_string(), (free / MIB).to_string());
+        }
+        metadata.insert("session_mb".to_string(), (self.session_bytes / MIB).to_string());
+
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: EventType::StorageThrottle,
+            target: "storage".to_string(),
+            value_from: Some(self.from.name().to_string()),
+            value_to: Some(self.to.name().to_string()),
+            confidence: 1.0,
+            evidence_frames: Vec::new(),
+            metadata,
+        }
+    }
+}
+
+impl std::fmt::Display for StorageChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {} (", self.from.name(), self.to.name())?;
+        match self.free_bytes {
+            Some(free) => write!(f, "{} MiB free", free / MIB)?,
+            None => write!(f, "free space unknown")?,
+        }
+        write!(f, ", {} MiB written this session)", self.session_bytes / MIB)
+    }
+}
+
+/// Tracks free disk space and what the session has written, and picks the
+/// storage level processing should run at
+pub struct StorageMonitor {
+    config: StorageMonitorConfig,
+    dir: PathBuf,
+    probe: SystemProbe,
+    level: StorageLevel,
+    session_bytes: u64,
+    free_bytes: Option<u64>,
+    last_checked: Option<Instant>,
+}
+
+impl StorageMonitor {
+    /// Monitor the filesystem holding `dir`
+    pub fn new(config: StorageMonitorConfig, dir: impl Into<PathBuf>, probe: SystemProbe) -> Self {
+        Self {
+            config,
+            dir: dir.into(),
+            probe,
+            level: StorageLevel::Normal,
+            session_bytes: 0,
+            free_bytes: None,
+            last_checked: None,
+        }
+    }
+
+    pub fn update_config(&mut self, config: StorageMonitorConfig) {
+        self.config = config;
+        // Apply new thresholds on the next check
+        self.last_checked = None;
+    }
+
+    pub fn level(&self) -> StorageLevel {
+        self.level
+    }
+
+    pub fn config(&self) -> &StorageMonitorConfig {
+        &self.config
+    }
+
+    /// Count bytes written this session against the budget
+    pub fn record_written(&mut self, bytes: u64) {
+        self.session_bytes += bytes;
+    }
+
+    /// Re-read free space when due and update the level; returns the change, if any
+    pub async fn check(&mut self) -> Option<StorageChange> {
+        let due = self
+            .last_checked
+            .map_or(true, |at| at.elapsed() >= Duration::from_secs(self.config.check_interval_secs));
+        if due {
+            self.last_checked = Some(Instant::now());
+            match free_space(&self.probe, &self.dir).await {
+                Ok(free) => self.free_bytes = Some(free),
+                // Keep the last reading; a failing `df` should not stop processing
+                Err(e) => warn!("Failed to read free disk space for {}: {}", self.dir.display(), e),
+            }
+        }
+
+        let level = self.level_for(self.free_bytes, self.session_bytes);
+        if level == self.level {
+            return None;
+        }
+        let change = StorageChange {
+            from: self.level,
+            to: level,
+            free_bytes: self.free_bytes,
+            session_bytes: self.session_bytes,
+        };
+        self.level = level;
+        Some(change)
+    }
+
+    /// Level for the given free space and session usage; the stricter of the two limits wins
+    pub fn level_for(&self, free_bytes: Option<u64>, session_bytes: u64) -> StorageLevel {
+        let by_disk = match free_bytes {
+            Some(free) if free < self.config.pause_below_mb * MIB => StorageLevel::Paused,
+            Some(free) if free < self.config.thumbnails_below_mb * MIB => StorageLevel::ThumbnailsOnly,
+            Some(free) if free < self.config.reduce_rate_below_mb * MIB => StorageLevel::ReducedRate,
+            _ => StorageLevel::Normal,
+        };
+        let by_budget = match self.config.session_budget_mb {
+            Some(budget) => {
+                let used = session_bytes as f64 / (budget * MIB) as f64;
+                if used >= 1.0 {
+                    StorageLevel::Paused
+                } else if used >= self.config.budget_thumbnails_at {
+                    StorageLevel::ThumbnailsOnly
+                } else if used >= self.config.budget_reduce_rate_at {
+                    StorageLevel::ReducedRate
+                } else {
+                    StorageLevel::Normal
+                }
+            }
+            None => StorageLevel::Normal,
+        };
+        by_disk.max(by_budget)
+    }
+}
+
+/// Free bytes on the filesystem holding `dir`, from POSIX `df`
+pub async fn free_space(probe: &SystemProbe, dir: &Path) -> Result<u64> {
+    let dir = dir.to_string_lossy();
+    let output = probe.run("df", "df", &["-Pk", &dir]).await?;
+    parse_df(&output).ok_or_else(|| IndexerError::Probe(format!("Unexpected df output for {}", dir)))
+}
+
+/// Available bytes from `df -Pk` output
+pub fn parse_df(output: &str) -> Option<u64> {
+    // Filesystem, 1024-blocks, Used, Available, Capacity, Mounted on
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    let available_kb: u64 = fields.get(3)?.parse().ok()?;
+    Some(available_kb * 1024)
+}
+
+/// Scale a saved keyframe down to `width` pixels wide, keeping its aspect ratio
+/// and re-encoding it with the keyframe codec
+pub fn shrink_to_thumbnail(path: &Path, width: u32, storage: &KeyframeStorageConfig) -> Result<()> {
+    let image = image::open(path)?;
+    if image.width() <= width {
+        return Ok(());
+    }
+    let height = (image.height() as u64 * width as u64 / image.width() as u64).max(1) as u32;
+    keyframe_codec::save_keyframe(&image.thumbnail(width, height), path, storage)?;
+    debug!("Kept {} as a {}x{} thumbnail", path.display(), width, height);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_follow_free_space_and_budget() {
+        let config = StorageMonitorConfig { session_budget_mb: Some(100), ..StorageMonitorConfig::default() };
+        let monitor = StorageMonitor::new(config, ".", SystemProbe::default());
+        let gb = |n: u64| Some(n * 1024 * MIB);
+
+        assert_eq!(monitor.level_for(gb(50), 0), StorageLevel::Normal);
+        assert_eq!(monitor.level_for(gb(8), 0), StorageLevel::ReducedRate);
+        assert_eq!(monitor.level_for(gb(2), 0), StorageLevel::ThumbnailsOnly);
+        assert_eq!(monitor.level_for(Some(512 * MIB), 0), StorageLevel::Paused);
+        // Unknown free space leaves only the budget
+        assert_eq!(monitor.level_for(None, 85 * MIB), StorageLevel::ReducedRate);
+        assert_eq!(monitor.level_for(gb(50), 95 * MIB), StorageLevel::ThumbnailsOnly);
+        assert_eq!(monitor.level_for(gb(8), 100 * MIB), StorageLevel::Paused);
+
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/disk3s5 971350180 402343452 569006728 42% /System/Volumes/Data\n";
+        assert_eq!(parse_df(df), Some(569006728 * 1024));
+        assert_eq!(parse_df("df: /missing: No such file or directory"), None);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+/// Metadata keys naming the application an event happened in
+const APP_METADATA_KEYS: [&str; 6] = ["app_name", "current_app", "to_app", "bundle_id", "current_bundle_id", "to_bundle_id"];
+
+/// Mutes matching events from alerts and reports; stored events are not touched.
+/// Empty fields match anything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuppressionRule {
+    /// Unique rule name
+    pub name: String,
+    /// Event types that match
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    /// Application name or bundle id, compared case-insensitively
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Regex the event target or message (`value_to`) must match
+    #[serde(default)]
+    pub message_pattern: Option<String>,
+    /// The rule stops applying at this time
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Why the rule exists, for whoever reviews it later
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl SuppressionRule {
+    pub fn validate(&self) -> Result<()> {
+        CompiledRule::compile(self.clone(), false).map(|_| ())
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    rule: SuppressionRule,
+    message_regex: Option<Regex>,
+    /// Rules from the config file are re-read on start and not written to the store
+    from_config: bool,
+}
+
+impl CompiledRule {
+    fn compile(rule: SuppressionRule, from_config: bool) -> Result<Self> {
+        if rule.name.trim().is_empty() {
+            return Err(IndexerError::Config("Suppression rule name must not be empty".to_string()));
+        }
+
+        let message_regex = rule.message_pattern
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    IndexerError::Config(format!("Suppression '{}' has invalid pattern '{}': {}", rule.name, pattern, e))
+                })
+            })
+            .transpose()?;
+
+        Ok(Self { rule, message_regex, from_config })
+    }
+
+    fn matches(&self, event: &DetectedEvent, now: DateTime<Utc>) -> bool {
+        if self.rule.is_expired(now) {
+            return false;
+        }
+
+        if !self.rule.event_types.is_empty() && !self.rule.event_types.contains(&event.event_type) {
+            return false;
+        }
+
+        if let Some(app) = &self.rule.app {
+            let in_app = APP_METADATA_KEYS
+                .iter()
+                .filter_map(|key| event.metadata.get(*key))
+                .any(|value| value.eq_ignore_ascii_case(app));
+            if !in_app {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.message_regex {
+            let message_matches = regex.is_match(&event.target)
+                || event.value_to.as_deref().is_some_and(|message| regex.is_match(message));
+            if !message_matches {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Shared set of suppression rules.
+///
+/// Cloning shares the same rules, so alerting, reporting and the control API
+/// see changes immediately. Rules added or removed at runtime are saved to
+/// the store file when one is attached; rules from the config file are not.
+#[derive(Debug, Clone, Default)]
+pub struct SuppressionList {
+    rules: Arc<RwLock<Vec<CompiledRule>>>,
+    store_path: Option<Arc<PathBuf>>,
+}
+
+impl SuppressionList {
+    /// Rules from the config file
+    pub fn new(rules: Vec<SuppressionRule>) -> Result<Self> {
+        let mut compiled: Vec<CompiledRule> = Vec::new();
+        for rule in rules {
+            let rule = CompiledRule::compile(rule, true)?;
+            if compiled.iter().any(|r| r.rule.name == rule.rule.name) {
+                return Err(IndexerError::Config(format!("Duplicate suppression rule '{}'", rule.rule.name)));
+            }
+            compiled.push(rule);
+        }
+
+        Ok(Self {
+            rules: Arc::new(RwLock::new(compiled)),
+            store_path: None,
+        })
+    }
+
+    /// Load and keep runtime-managed rules in `path`; they replace config rules of the same name
+    pub fn with_store(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            let stored: Vec<SuppressionRule> = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            info!("Loaded {} suppression rules from {}", stored.len(), path.display());
+            for rule in stored {
+                self.upsert(CompiledRule::compile(rule, false)?);
+            }
+        }
+        self.store_path = Some(Arc::new(path));
+        Ok(self)
+    }
+
+    /// Replace the rules from the config file, e.g. after it is reloaded;
+    /// runtime-managed rules are kept and still win over config rules of the same name
+    pub fn replace_config_rules(&self, rules: Vec<SuppressionRule>) -> Result<()> {
+        let replacement = Self::new(rules)?.write(std::mem::take);
+        self.write(|rules| {
+            rules.retain(|r| !r.from_config);
+            for rule in replacement {
+                if !rules.iter().any(|r| r.rule.name == rule.rule.name) {
+                    rules.push(rule);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Add or replace a rule by name
+    pub fn add(&self, rule: SuppressionRule) -> Result<()> {
+        let compiled = CompiledRule::compile(rule, false)?;
+        info!("Suppression rule '{}' added", compiled.rule.name);
+        self.upsert(compiled);
+        self.save()
+    }
+
+    /// Remove a rule; returns whether it existed
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let removed = self.write(|rules| {
+            let before = rules.len();
+            rules.retain(|r| r.rule.name != name);
+            rules.len() != before
+        });
+        if removed {
+            info!("Suppression rule '{}' removed", name);
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Drop expired rules; returns how many were removed
+    pub fn prune_expired(&self, now: DateTime<Utc>) -> Result<usize> {
+        let pruned = self.write(|rules| {
+            let before = rules.len();
+            rules.retain(|r| !r.rule.is_expired(now));
+            before - rules.len()
+        });
+        if pruned > 0 {
+            self.save()?;
+        }
+        Ok(pruned)
+    }
+
+    pub fn rules(&self) -> Vec<SuppressionRule> {
+        self.read(|rules| rules.iter().map(|r| r.rule.clone()).collect())
+    }
+
+    /// Name of the first active rule muting `event`
+    pub fn matching_rule(&self, event: &DetectedEvent, now: DateTime<Utc>) -> Option<String> {
+        self.read(|rules| rules.iter().find(|r| r.matches(event, now)).map(|r| r.rule.name.clone()))
+    }
+
+    pub fn is_suppressed(&self, event: &DetectedEvent) -> bool {
+        self.matching_rule(event, Utc::now()).is_some()
+    }
+
+    /// Events not muted by any active rule
+    pub fn filter_events(&self, events: &[DetectedEvent]) -> Vec<DetectedEvent> {
+        let now = Utc::now();
+        self.read(|rules| {
+            events
+                .iter()
+                .filter(|event| match rules.iter().find(|r| r.matches(event, now)) {
+                    Some(rule) => {
+                        debug!("Event {} suppressed by '{}'", event.id, rule.rule.name);
+                        false
+                    }
+                    None => true,
+                })
+                .cloned()
+                .collect()
+        })
+    }
+
+    // MARK: - Private Helper Methods
+
+    fn upsert(&self, rule: CompiledRule) {
+        self.write(|rules| match rules.iter_mut().find(|r| r.rule.name == rule.rule.name) {
+            Some(existing) => *existing = rule,
+            None => rules.push(rule),
+        });
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.store_path else {
+            return Ok(());
+        };
+        let managed: Vec<SuppressionRule> = self.read(|rules| {
+            rules.iter().filter(|r| !r.from_config).map(|r| r.rule.clone()).collect()
+        });
+        std::fs::write(path.as_path(), serde_json::to_string_pretty(&managed)?)?;
+        Ok(())
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Vec<CompiledRule>) -> T) -> T {
+        match self.rules.read() {
+            Ok(rules) => f(&rules),
+            Err(poisoned) => f(&poisoned.into_inner()),
+        }
+    }
+
+    fn write<T>(&self, f: impl FnOnce(&mut Vec<CompiledRule>) -> T) -> T {
+        match self.rules.write() {
+            Ok(mut rules) => f(&mut rules),
+            Err(poisoned) => f(&mut poisoned.into_inner()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn modal(message: &str, app: &str) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        metadata.insert("app_name".to_string(), app.to_string());
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::ModalAppearance,
+            target: "info_dialog_info".to_string(),
+            value_from: None,
+            value_to: Some(message.to_string()),
+            confidence: 0.9,
+            evidence_frames: vec!["frame_1".to_string()],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_rules_mute_until_expiry_and_persist() {
+        let list = SuppressionList::new(vec![SuppressionRule {
+            name: "updates".to_string(),
+            event_types: vec![EventType::ModalAppearance],
+            app: Some("slack".to_string()),
+            message_pattern: Some("(?i)update available".to_string()),
+            expires_at: None,
+            reason: Some("Nagging updater".to_string()),
+        }]).unwrap();
+
+        let nag = modal("An Update Available for Slack", "Slack");
+        let real = modal("Disk almost full", "Slack");
+        assert!(list.is_suppressed(&nag));
+        assert!(!list.is_suppressed(&real));
+        assert!(!list.is_suppressed(&modal("Update available", "Mail")));
+        let kept = list.filter_events(&[nag.clone(), real.clone()]);
+        assert_eq!(kept.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec![real.id.as_str()]);
+
+        // A runtime rule with an expiry
+        let temp_dir = TempDir::new().unwrap();
+        let store = temp_dir.path().join("suppressions.json");
+        let list = list.with_store(&store).unwrap();
+        let expires_at = Utc::now() + chrono::Duration::hours(1);
+        list.add(SuppressionRule {
+            name: "disk".to_string(),
+            event_types: Vec::new(),
+            app: None,
+            message_pattern: Some("Disk almost full".to_string()),
+            expires_at: Some(expires_at),
+            reason: None,
+        }).unwrap();
+        assert_eq!(list.matching_rule(&real, Utc::now()), Some("disk".to_string()));
+        assert_eq!(list.matching_rule(&real, expires_at), None);
+
+        // Only the runtime rule is stored, and it is reloaded
+        let reloaded = SuppressionList::new(Vec::new()).unwrap().with_store(&store).unwrap();
+        assert_eq!(reloaded.rules().len(), 1);
+        assert_eq!(list.prune_expired(expires_at).unwrap(), 1);
+        assert!(list.remove("updates").unwrap());
+        assert!(list.rules().is_empty());
+
+        assert!(SuppressionList::new(vec![SuppressionRule {
+            name: "bad".to_string(),
+            event_types: Vec::new(),
+            app: None,
+            message_pattern: Some("(".to_string()),
+            expires_at: None,
+            reason: None,
+        }]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::{IndexerError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// Limits for AppleScript and other system probes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemProbeConfig {
+    /// A probe still running after this long is killed
+    pub timeout_ms: u64,
+    /// Extra attempts after a failed or timed-out probe
+    pub retries: u32,
+    pub retry_delay_ms: u64,
+    /// Consecutive failed probes of one target after which it is skipped
+    pub failure_threshold: u32,
+    /// How long a skipped target is left alone before it is tried again
+    pub cooldown_ms: u64,
+}
+
+impl Default for SystemProbeConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 2_000,
+            retries: 1,
+            retry_delay_ms: 100,
+            failure_threshold: 3,
+            cooldown_ms: 30_000,
+        }
+    }
+}
+
+impl SystemProbeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_ms == 0 || self.failure_threshold == 0 {
+            return Err(IndexerError::Config(
+                "system_probe timeout_ms and failure_threshold must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Runs system commands without blocking the runtime.
+///
+/// Each probe is killed after `timeout_ms` and retried. Failures are counted
+/// per target app; once a target fails `failure_threshold` probes in a row its
+/// circuit opens and probes of it fail immediately for `cooldown_ms`, after
+/// which one probe is let through. Clones share their circuits.
+#[derive(Debug, Clone)]
+pub struct SystemProbe {
+    config: SystemProbeConfig,
+    breakers: Arc<Mutex<HashMap<String, Breaker>>>,
+}
+
+impl Default for SystemProbe {
+    fn default() -> Self {
+        Self::new(SystemProbeConfig::default())
+    }
+}
+
+impl SystemProbe {
+    pub fn new(config: SystemProbeConfig) -> Self {
+        Self { config, breakers: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Run an AppleScript that talks to `target`, returning its trimmed output
+    pub async fn osascript(&self, target: &str, script: &str) -> Result<String> {
+        self.run(target, "osascript", &["-e", script]).await
+    }
+
+    /// Run a JavaScript for Automation script that talks to `target`
+    pub async fn jxa(&self, target: &str, script: &str) -> Result<String> {
+        self.run(target, "osascript", &["-l", "JavaScript", "-e", script]).await
+    }
+
+    /// Run `program` with `args` on behalf of `target`
+    pub async fn run(&self, target: &str, program: &str, args: &[&str]) -> Result<String> {
+        if self.is_open(target) {
+            return Err(IndexerError::Probe(format!("{} is not responding; skipping probe", target)));
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.run_once(program, args).await {
+                Ok(output) => {
+                    self.record(target, true);
+                    return Ok(output);
+                }
+                Err(e) if attempt < self.config.retries => {
+                    debug!("Probe of {} failed, retrying: {}", target, e);
+                    attempt += 1;
+                    tokio::time::sleep(Duration::from_millis(self.config.retry_delay_ms)).await;
+                }
+                Err(e) => {
+                    self.record(target, false);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Whether probes of `target` are currently skipped
+    pub fn is_open(&self, target: &str) -> bool {
+        let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        breakers
+            .get(target)
+            .and_then(|b| b.open_until)
+            .map_or(false, |until| Instant::now() < until)
+    }
+
+    async fn run_once(&self, program: &str, args: &[&str]) -> Result<String> {
+        let output = Command::new(program).args(args).kill_on_drop(true).output();
+        let output = tokio::time::timeout(Duration::from_millis(self.config.timeout_ms), output)
+            .await
+            .map_err(|_| IndexerError::Probe(format!("{} timed out after {}ms", program, self.config.timeout_ms)))?
+            .map_err(|e| IndexerError::Probe(format!("Failed to run {}: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(IndexerError::Probe(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn record(&self, target: &str, success: bool) {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = breakers.entry(target.to_string()).or_default();
+        if success {
+            *breaker = Breaker::default();
+            return;
+        }
+        breaker.consecutive_failures += 1;
+        // A failed probe after the cooldown reopens the circuit at once
+        if breaker.consecutive_failures >= self.config.failure_threshold {
+            if breaker.open_until.is_none() {
+                warn!(
+                    "{} failed {} probes in a row; skipping it for {}s",
+                    target,
+                    breaker.consecutive_failures,
+                    self.config.cooldown_ms / 1000
+                );
+            }
+            breaker.open_until = Some(Instant::now() + Duration::from_millis(self.config.cooldown_ms));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_timeouts_open_the_circuit() {
+        let probe = SystemProbe::new(SystemProbeConfig {
+            timeout_ms: 50,
+            retries: 1,
+            retry_delay_ms: 1,
+            failure_threshold: 2,
+            cooldown_ms: 60_000,
+        });
+
+        assert_eq!(probe.run("echo", "echo", &["hello"]).await.unwrap(), "hello");
+
+        let started = Instant::now();
+        assert!(probe.run("Hung App", "sleep", &["5"]).await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(!probe.is_open("Hung App"));
+        assert!(probe.run("Hung App", "sleep", &["5"]).await.is_err());
+        assert!(probe.is_open("Hung App"));
+
+        // Skipped without running, and other targets are unaffected
+        let started = Instant::now();
+        let skipped = probe.clone().run("Hung App", "echo", &["late"]).await.unwrap_err();
+        assert!(skipped.to_string().contains("not responding"));
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert!(probe.run("echo", "echo", &["still fine"]).await.is_ok());
+    }
+}
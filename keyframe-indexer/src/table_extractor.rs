@@ -0,0 +1,429 @@
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::ocr_data::{BoundingBox, OCRResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+/// Configuration for finding tables in a frame's OCR layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableExtractorConfig {
+    /// Report tables and their changes as `TableChange` events
+    pub enabled: bool,
+    /// Fewest rows, header included, for text to count as a table
+    pub min_rows: usize,
+    /// Fewest columns for text to count as a table; label/value forms have two
+    pub min_columns: usize,
+    /// Largest gap between rows of one table, in row heights
+    pub max_row_gap: f32,
+    /// Text boxes closer than this (px) horizontally are in the same column
+    pub column_gap_px: f32,
+}
+
+impl Default for TableExtractorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_rows: 3,
+            min_columns: 3,
+            max_row_gap: 1.5,
+            column_gap_px: 8.0,
+        }
+    }
+}
+
+impl TableExtractorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_rows < 2 || self.min_columns < 2 {
+            return Err(IndexerError::Config("tables need at least 2 rows and 2 columns".to_string()));
+        }
+        if self.max_row_gap <= 0.0 || self.column_gap_px < 0.0 {
+            return Err(IndexerError::Config("table max_row_gap must be greater than 0 and column_gap_px not negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Table found in one frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedTable {
+    pub bounds: BoundingBox,
+    /// First row, when it has no digits
+    pub header: Option<Vec<String>>,
+    /// Cell text of each row, one entry per column; empty cells are ""
+    pub rows: Vec<Vec<String>>,
+    /// Mean OCR confidence of the cells
+    pub confidence: f32,
+}
+
+impl ExtractedTable {
+    pub fn column_count(&self) -> usize {
+        self.header
+            .iter()
+            .chain(&self.rows)
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Cell whose text changed between snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellChange {
+    /// Row in the newer snapshot
+    pub row: usize,
+    pub column: usize,
+    pub from: String,
+    pub to: String,
+}
+
+/// What changed in a table between two frames
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub added_rows: Vec<Vec<String>>,
+    pub removed_rows: Vec<Vec<String>>,
+    pub changed_cells: Vec<CellChange>,
+}
+
+impl TableDiff {
+    /// Rows are matched by their first cell when it is unique in both tables,
+    /// so inserted or re-sorted rows aren't taken for edits; otherwise by position
+    pub fn compute(old: &ExtractedTable, new: &ExtractedTable) -> Self {
+        let mut diff = Self::default();
+        let pairs: Vec<(Option<usize>, Option<usize>)> = match (row_keys(old), row_keys(new)) {
+            (Some(old_keys), Some(new_keys)) => {
+                let mut pairs: Vec<_> = new
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .map(|(new_index, row)| (old_keys.get(row[0].as_str()).copied(), Some(new_index)))
+                    .collect();
+                let mut removed: Vec<usize> = old_keys
+                    .iter()
+                    .filter(|(key, _)| !new_keys.contains_key(*key))
+                    .map(|(_, old_index)| *old_index)
+                    .collect();
+                removed.sort_unstable();
+                pairs.extend(removed.into_iter().map(|old_index| (Some(old_index), None)));
+                pairs
+            }
+            _ => (0..old.rows.len().max(new.rows.len()))
+                .map(|index| ((index < old.rows.len()).then_some(index), (index < new.rows.len()).then_some(index)))
+                .collect(),
+        };
+
+        for pair in pairs {
+            match pair {
+                (Some(old_index), Some(new_index)) => {
+                    let (old_row, new_row) = (&old.rows[old_index], &new.rows[new_index]);
+                    for column in 0..old_row.len().max(new_row.len()) {
+                        let from = old_row.get(column).map(String::as_str).unwrap_or("");
+                        let to = new_row.get(column).map(String::as_str).unwrap_or("");
+                        if from != to {
+                            diff.changed_cells.push(CellChange { row: new_index, column, from: from.to_string(), to: to.to_string() });
+                        }
+                    }
+                }
+                (None, Some(new_index)) => diff.added_rows.push(new.rows[new_index].clone()),
+                (Some(old_index), None) => diff.removed_rows.push(old.rows[old_index].clone()),
+                (None, None) => {}
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_rows.is_empty() && self.removed_rows.is_empty() && self.changed_cells.is_empty()
+    }
+}
+
+/// Row index by first cell, when every row has a distinct non-empty one
+fn row_keys(table: &ExtractedTable) -> Option<HashMap<&str, usize>> {
+    let mut keys = HashMap::new();
+    for (index, row) in table.rows.iter().enumerate() {
+        let key = row.first().map(String::as_str).filter(|key| !key.is_empty())?;
+        if keys.insert(key, index).is_some() {
+            return None;
+        }
+    }
+    Some(keys)
+}
+
+/// A table as seen in one frame, with what changed since the frame it was last seen in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSnapshot {
+    /// Id kept while the table stays on screen
+    pub table_id: String,
+    pub frame_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub table: ExtractedTable,
+    /// `None` when the table first appears
+    pub diff: Option<TableDiff>,
+}
+
+impl TableSnapshot {
+    pub fn to_event(&self) -> DetectedEvent {
+        let mut metadata = HashMap::new();
+        self.table.bounds.insert_metadata(&mut metadata);
+        metadata.insert("table_rows".to_string(), self.table.rows.len().to_string());
+        metadata.insert("table_columns".to_string(), self.table.column_count().to_string());
+        let snapshot = serde_json::json!({ "header": self.table.header, "rows": self.table.rows });
+        metadata.insert("table_snapshot".to_string(), snapshot.to_string());
+        let description = match &self.diff {
+            None => format!("table with {} rows", self.table.rows.len()),
+            Some(diff) => {
+                metadata.insert("rows_added".to_string(), diff.added_rows.len().to_string());
+                metadata.insert("rows_removed".to_string(), diff.removed_rows.len().to_string());
+                metadata.insert("cells_changed".to_string(), diff.changed_cells.len().to_string());
+                if let Ok(json) = serde_json::to_string(diff) {
+                    metadata.insert("table_diff".to_string(), json);
+                }
+                format!(
+                    "{} rows added, {} removed, {} cells changed",
+                    diff.added_rows.len(), diff.removed_rows.len(), diff.changed_cells.len()
+                )
+            }
+        };
+
+        DetectedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: self.timestamp,
+            event_type: EventType::TableChange,
+            target: self.table_id.clone(),
+            value_from: None,
+            value_to: Some(description),
+            confidence: self.table.confidence,
+            evidence_frames: vec![self.frame_id.clone()],
+            metadata,
+        }
+    }
+}
+
+/// Finds tables in each frame and follows them across frames.
+///
+/// Text boxes are grouped into rows by their vertical centers, and consecutive
+/// rows with several boxes form a candidate table. Its columns are the bands of
+/// the x axis that its boxes cover, separated by whitespace running through
+/// every row; left-, right- and center-aligned columns all leave such gaps,
+/// while lines of running text overlap into one band.
+#[derive(Debug, Clone)]
+pub struct TableTracker {
+    config: TableExtractorConfig,
+    /// Tables of the previous frame, with their ids
+    previous: Vec<(String, ExtractedTable)>,
+}
+
+impl TableTracker {
+    pub fn new(config: TableExtractorConfig) -> Self {
+        Self { config, previous: Vec::new() }
+    }
+
+    /// Snapshots of the frame's tables that are new or changed since the previous frame
+    pub fn process_frame(&mut self, frame_id: &str, timestamp: DateTime<Utc>, regions: &[&OCRResult]) -> Vec<TableSnapshot> {
+        let tables = extract_tables(regions, &self.config);
+        let mut matched = HashSet::new();
+        let mut current = Vec::with_capacity(tables.len());
+        let mut snapshots = Vec::new();
+
+        for table in tables {
+            let previous = self
+                .previous
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !matched.contains(index))
+                .map(|(index, (id, previous))| {
+                    let same_header = table.header.is_some() && table.header == previous.header;
+                    let overlap = table.bounds.iou(&previous.bounds);
+                    (index, id, previous, if same_header { 1.0 + overlap } else { overlap })
+                })
+                .filter(|(_, _, _, score)| *score >= 0.3)
+                .max_by(|a, b| a.3.total_cmp(&b.3));
+
+            let (table_id, diff) = match previous {
+                Some((index, id, previous, _)) => {
+                    matched.insert(index);
+                    (id.clone(), Some(TableDiff::compute(previous, &table)))
+                }
+                None => (uuid::Uuid::new_v4().to_string(), None),
+            };
+            if diff.as_ref().map_or(true, |diff| !diff.is_empty()) {
+                debug!("Table {} in frame {}: {} rows", table_id, frame_id, table.rows.len());
+                snapshots.push(TableSnapshot {
+                    table_id: table_id.clone(),
+                    frame_id: frame_id.to_string(),
+                    timestamp,
+                    table: table.clone(),
+                    diff,
+                });
+            }
+            current.push((table_id, table));
+        }
+
+        self.previous = current;
+        snapshots
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+    }
+}
+
+/// Tables laid out by a frame's text boxes, top to bottom
+pub fn extract_tables(regions: &[&OCRResult], config: &TableExtractorConfig) -> Vec<ExtractedTable> {
+    let rows = group_rows(regions);
+    let mut tables = Vec::new();
+    let mut run: Vec<&Vec<&OCRResult>> = Vec::new();
+
+    for row in rows.iter().chain(std::iter::once(&Vec::new())) {
+        let continues = row.len() >= 2
+            && run.last().map_or(true, |last| {
+                let (last_bounds, bounds) = (row_bounds(last), row_bounds(row));
+                bounds.y - last_bounds.bottom() <= config.max_row_gap * last_bounds.height.max(bounds.height)
+            });
+        if !continues {
+            if run.len() >= config.min_rows {
+                tables.extend(build_table(&run, config));
+            }
+            run.clear();
+        }
+        if row.len() >= 2 {
+            run.push(row);
+        }
+    }
+    tables
+}
+
+/// Boxes grouped into rows by vertical center, each row left to right
+fn group_rows<'a>(regions: &[&'a OCRResult]) -> Vec<Vec<&'a OCRResult>> {
+    let mut sorted = regions.to_vec();
+    sorted.sort_by(|a, b| a.roi.center().1.total_cmp(&b.roi.center().1));
+
+    let mut rows: Vec<Vec<&OCRResult>> = Vec::new();
+    for region in sorted {
+        let same_row = rows.last().map_or(false, |row| {
+            let first = &row[0].roi;
+            (region.roi.center().1 - first.center().1).abs() <= first.height.max(region.roi.height) / 2.0
+        });
+        match rows.last_mut() {
+            Some(row) if same_row => row.push(region),
+            _ => rows.push(vec![region]),
+        }
+    }
+    for row in &mut rows {
+        row.sort_by(|a, b| a.roi.x.total_cmp(&b.roi.x));
+    }
+    rows
+}
+
+fn row_bounds(row: &[&OCRResult]) -> BoundingBox {
+    BoundingBox::enclosing(row.iter().map(|region| &region.roi)).unwrap_or_else(|| BoundingBox::new(0.0, 0.0, 0.0, 0.0))
+}
+
+fn build_table(rows: &[&Vec<&OCRResult>], config: &TableExtractorConfig) -> Option<ExtractedTable> {
+    // Column bands: the x extents of all boxes, merged where they overlap or nearly touch
+    let mut extents: Vec<(f32, f32)> = rows.iter().flat_map(|row| row.iter().map(|r| (r.roi.x, r.roi.right()))).collect();
+    extents.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut bands: Vec<(f32, f32)> = Vec::new();
+    for (start, end) in extents {
+        match bands.last_mut() {
+            Some(band) if start <= band.1 + config.column_gap_px => band.1 = band.1.max(end),
+            _ => bands.push((start, end)),
+        }
+    }
+    if bands.len() < config.min_columns {
+        return None;
+    }
+
+    let mut cells: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut texts = vec![Vec::new(); bands.len()];
+        for region in row.iter() {
+            if let Some(column) = bands.iter().position(|band| region.roi.x >= band.0 && region.roi.x <= band.1) {
+                texts[column].push(region.text.trim());
+            }
+        }
+        cells.push(texts.into_iter().map(|parts| parts.join(" ")).collect());
+    }
+
+    let header = cells
+        .first()
+        .filter(|row| row.iter().all(|cell| !cell.chars().any(|c| c.is_ascii_digit())))
+        .cloned();
+    let body = cells.split_off(if header.is_some() { 1 } else { 0 });
+
+    let boxes: Vec<&OCRResult> = rows.iter().flat_map(|row| row.iter().copied()).collect();
+    let bounds = BoundingBox::enclosing(boxes.iter().map(|region| &region.roi))?;
+    let confidence = boxes.iter().map(|region| region.confidence).sum::<f32>() / boxes.len() as f32;
+    Some(ExtractedTable { bounds, header, rows: body, confidence })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(text: &str, x: f32, y: f32) -> OCRResult {
+        OCRResult {
+            frame_id: "frame".to_string(),
+            roi: BoundingBox::new(x, y, 80.0, 18.0),
+            text: text.to_string(),
+            language: "en-US".to_string(),
+            confidence: 0.9,
+            processed_at: Utc::now(),
+            processor: "vision".to_string(),
+        }
+    }
+
+    fn sheet(rows: &[[&str; 3]]) -> Vec<OCRResult> {
+        let mut regions = vec![cell("Invoice totals", 10.0, 0.0)];
+        for (index, row) in rows.iter().enumerate() {
+            let y = 40.0 + index as f32 * 24.0;
+            for (column, text) in row.iter().enumerate() {
+                if !text.is_empty() {
+                    regions.push(cell(text, 10.0 + column as f32 * 120.0, y));
+                }
+            }
+        }
+        regions.push(cell("Text below the table, far enough down", 10.0, 400.0));
+        regions
+    }
+
+    #[test]
+    fn test_tables_are_extracted_and_diffed() {
+        let config = TableExtractorConfig { enabled: true, ..TableExtractorConfig::default() };
+        let mut tracker = TableTracker::new(config);
+        let at = Utc::now();
+        let first = sheet(&[["Vendor", "Amount", "Due"], ["ACME", "120.00", "2024-05-01"], ["Globex", "80.00", ""]]);
+        let regions: Vec<&OCRResult> = first.iter().collect();
+
+        let snapshots = tracker.process_frame("frame_1", at, &regions);
+        assert_eq!(snapshots.len(), 1);
+        let table = &snapshots[0].table;
+        assert_eq!(table.header, Some(vec!["Vendor".to_string(), "Amount".to_string(), "Due".to_string()]));
+        assert_eq!(table.rows, vec![vec!["ACME", "120.00", "2024-05-01"], vec!["Globex", "80.00", ""]]);
+        assert!(snapshots[0].diff.is_none());
+
+        // The same frame again changes nothing
+        assert!(tracker.process_frame("frame_2", at, &regions).is_empty());
+
+        // A row is inserted above an edited one
+        let second = sheet(&[["Vendor", "Amount", "Due"], ["Initech", "45.00", ""], ["ACME", "125.00", "2024-05-01"], ["Globex", "80.00", ""]]);
+        let regions: Vec<&OCRResult> = second.iter().collect();
+        let snapshots = tracker.process_frame("frame_3", at, &regions);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].table_id, tracker.previous[0].0);
+        let diff = snapshots[0].diff.as_ref().unwrap();
+        assert_eq!(diff.added_rows, vec![vec!["Initech", "45.00", ""]]);
+        assert!(diff.removed_rows.is_empty());
+        assert_eq!(diff.changed_cells, vec![CellChange { row: 1, column: 1, from: "120.00".to_string(), to: "125.00".to_string() }]);
+        let event = snapshots[0].to_event();
+        assert_eq!(event.event_type, EventType::TableChange);
+        assert_eq!(event.metadata.get("cells_changed").map(String::as_str), Some("1"));
+
+        // Running text is not a table
+        let prose: Vec<OCRResult> = (0..4).map(|line| cell("Lorem ipsum dolor sit amet", 10.0, line as f32 * 24.0)).collect();
+        let regions: Vec<&OCRResult> = prose.iter().collect();
+        assert!(extract_tables(&regions, &TableExtractorConfig::default()).is_empty());
+    }
+}
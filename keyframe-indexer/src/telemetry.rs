@@ -0,0 +1,100 @@
+use crate::error::{IndexerError, Result};
+use crate::logging::{LogFormat, LoggingConfig, RateLimitLayer, RollingFileWriter};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace as sdktrace, Resource};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Standard OpenTelemetry variable used when no endpoint is given
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Flushes exported spans when dropped; keep it alive until the process exits
+pub struct TelemetryGuard {
+    exporting: bool,
+    /// Lines dropped by the per-stage rate limits
+    suppressed: Arc<AtomicU64>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        let suppressed = self.suppressed.load(Ordering::Relaxed);
+        if suppressed > 0 {
+            tracing::info!("{} log lines were dropped by per-stage rate limits", suppressed);
+        }
+        if self.exporting {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Install the global tracing subscriber.
+///
+/// Logs go to stdout, or stderr for commands that print results, and to the
+/// configured log file. With an OTLP endpoint (gRPC, e.g.
+/// `http://localhost:4317`), the pipeline's stage spans (`segment`, `extract`,
+/// `scene_detect`, `write_outputs`, `ocr_write`, `event_detect`, `correlate`)
+/// are exported too, with their `segment_id` and `frame_id` attributes.
+pub fn init(otlp_endpoint: Option<&str>, logs_to_stderr: bool, logging: &LoggingConfig) -> Result<TelemetryGuard> {
+    let level = logging.level_filter()?;
+    let console = if logs_to_stderr { BoxMakeWriter::new(std::io::stderr) } else { BoxMakeWriter::new(std::io::stdout) };
+    let logs = output_layer(logging.format, console, true);
+    let file_logs = match &logging.file {
+        Some(path) => {
+            let writer = RollingFileWriter::open(Path::new(path), logging.max_file_bytes, logging.max_files)?;
+            Some(output_layer(logging.format, BoxMakeWriter::new(move || writer.clone()), false))
+        }
+        None => None,
+    };
+    let rate_limit = (logging.max_lines_per_stage > 0)
+        .then(|| RateLimitLayer::new(logging.max_lines_per_stage, Duration::from_millis(logging.rate_window_ms)));
+    let suppressed = rate_limit.as_ref().map(RateLimitLayer::suppressed).unwrap_or_default();
+
+    let tracer = match otlp_endpoint {
+        Some(endpoint) => {
+            let provider = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+                .with_trace_config(
+                    sdktrace::Config::default()
+                        .with_resource(Resource::new(vec![KeyValue::new("service.name", "keyframe-indexer")])),
+                )
+                .install_batch(runtime::Tokio)
+                .map_err(|e| IndexerError::Config(format!("Failed to start OTLP exporter for {}: {}", endpoint, e)))?;
+            let tracer = provider.tracer("keyframe-indexer");
+            opentelemetry::global::set_tracer_provider(provider);
+            Some(tracer)
+        }
+        None => None,
+    };
+    let exporting = tracer.is_some();
+
+    tracing_subscriber::registry()
+        .with(rate_limit)
+        .with(logs)
+        .with(file_logs)
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
+        .with(level)
+        .init();
+    Ok(TelemetryGuard { exporting, suppressed })
+}
+
+fn output_layer<S>(format: LogFormat, writer: BoxMakeWriter, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().flatten_event(true).with_span_list(true).boxed(),
+    }
+}
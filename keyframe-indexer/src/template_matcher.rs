@@ -0,0 +1,413 @@
+use crate::error::{IndexerError, Result};
+use crate::error_modal_detector::{ErrorModalEvent, ErrorModalType, PatternMatch, SeverityLevel};
+use crate::ocr_data::BoundingBox;
+use chrono::{DateTime, Utc};
+use image::imageops::FilterType;
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Metadata key with the name of the template a dialog matched
+pub const TEMPLATE_KEY: &str = "template";
+
+/// A screenshot of a known dialog to look for in keyframes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogTemplate {
+    /// Name reported on matching events, e.g. "bank_transfer_failed"
+    pub name: String,
+    /// Cropped screenshot of the dialog
+    pub image_path: PathBuf,
+    #[serde(default = "default_template_type")]
+    pub event_type: ErrorModalType,
+    #[serde(default = "default_template_severity")]
+    pub severity: SeverityLevel,
+    /// Message reported on matching events; defaults to the template name
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Overrides the configured minimum match score for this template
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Width of the screen the template was captured from, so it can be
+    /// rescaled for keyframes recorded at other resolutions
+    #[serde(default)]
+    pub capture_width: Option<u32>,
+}
+
+fn default_template_type() -> ErrorModalType {
+    ErrorModalType::ApplicationError
+}
+
+fn default_template_severity() -> SeverityLevel {
+    SeverityLevel::High
+}
+
+/// Configuration for matching keyframes against dialog templates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplateMatchingConfig {
+    pub enabled: bool,
+    pub templates: Vec<DialogTemplate>,
+    /// Minimum normalized cross-correlation (0-1) for a match
+    pub min_score: f32,
+    /// Keyframes are downscaled to this width before searching
+    pub search_width: u32,
+}
+
+impl Default for TemplateMatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            templates: Vec::new(),
+            min_score: 0.85,
+            search_width: 320,
+        }
+    }
+}
+
+impl TemplateMatchingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.min_score) {
+            return Err(IndexerError::Config(
+                "template_matching.min_score must be between 0 and 1".to_string()
+            ));
+        }
+        if self.search_width == 0 {
+            return Err(IndexerError::Config(
+                "template_matching.search_width must be greater than 0".to_string()
+            ));
+        }
+        for template in &self.templates {
+            if template.min_score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+                return Err(IndexerError::Config(format!(
+                    "template_matching: min_score of template '{}' must be between 0 and 1",
+                    template.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a template was found in a keyframe
+#[derive(Debug, Clone)]
+pub struct TemplateMatch {
+    pub name: String,
+    /// Normalized cross-correlation between template and keyframe region (0-1)
+    pub score: f32,
+    /// Matched region in keyframe pixels
+    pub roi: BoundingBox,
+}
+
+struct LoadedTemplate {
+    template: DialogTemplate,
+    image: GrayImage,
+}
+
+/// Finds registered dialog screenshots in keyframes.
+///
+/// Matching compares pixels rather than text, so a known dialog is reported
+/// with the template's name even when OCR misreads it.
+pub struct TemplateMatcher {
+    config: TemplateMatchingConfig,
+    templates: Vec<LoadedTemplate>,
+}
+
+impl TemplateMatcher {
+    /// Create a matcher and load the configured templates
+    pub fn new(config: TemplateMatchingConfig) -> Result<Self> {
+        config.validate()?;
+        let mut matcher = Self {
+            templates: Vec::with_capacity(config.templates.len()),
+            config: TemplateMatchingConfig { templates: Vec::new(), ..config.clone() },
+        };
+        if config.enabled {
+            for template in config.templates {
+                matcher.register(template)?;
+            }
+        }
+        Ok(matcher)
+    }
+
+    /// Load a template's screenshot and start matching against it
+    pub fn register(&mut self, template: DialogTemplate) -> Result<()> {
+        if !template.image_path.exists() {
+            return Err(IndexerError::Config(format!(
+                "Template '{}' image not found: {}",
+                template.name,
+                template.image_path.display()
+            )));
+        }
+        let image = image::open(&template.image_path)?.to_luma8();
+        self.register_image(template, image);
+        Ok(())
+    }
+
+    /// Start matching against an already loaded screenshot
+    pub fn register_image(&mut self, template: DialogTemplate, image: GrayImage) {
+        debug!("Registered dialog template '{}' ({}x{})", template.name, image.width(), image.height());
+        self.templates.retain(|loaded| loaded.template.name != template.name);
+        self.templates.push(LoadedTemplate { template, image });
+    }
+
+    pub fn template_count(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Match a keyframe on disk, reporting each template found as an error/modal event
+    pub fn match_frame(&self, frame_id: &str, timestamp: DateTime<Utc>) -> Result<Vec<ErrorModalEvent>> {
+        if self.templates.is_empty() {
+            return Ok(Vec::new());
+        }
+        let frame = image::open(frame_id)?.to_luma8();
+        Ok(self
+            .match_image(&frame)
+            .into_iter()
+            .map(|found| self.to_event(found, frame_id, timestamp))
+            .collect())
+    }
+
+    /// Best match of every template whose score reaches its threshold
+    pub fn match_image(&self, frame: &GrayImage) -> Vec<TemplateMatch> {
+        if self.templates.is_empty() || frame.width() == 0 || frame.height() == 0 {
+            return Vec::new();
+        }
+
+        // Search at reduced resolution; never upscale small keyframes
+        let scale = (self.config.search_width as f32 / frame.width() as f32).min(1.0);
+        let search = downscale(frame, scale);
+        let integral = IntegralImage::new(&search);
+
+        let mut matches = Vec::new();
+        for loaded in &self.templates {
+            let capture_scale = loaded
+                .template
+                .capture_width
+                .map(|width| frame.width() as f32 / width.max(1) as f32)
+                .unwrap_or(1.0);
+            let template = downscale(&loaded.image, scale * capture_scale);
+            let Some((x, y, score)) = best_match(&search, &integral, &template) else {
+                continue;
+            };
+
+            let min_score = loaded.template.min_score.unwrap_or(self.config.min_score);
+            debug!("Template '{}' best score {:.3} at ({}, {})", loaded.template.name, score, x, y);
+            if score < min_score {
+                continue;
+            }
+            matches.push(TemplateMatch {
+                name: loaded.template.name.clone(),
+                score,
+                roi: BoundingBox::new(
+                    x as f32 / scale,
+                    y as f32 / scale,
+                    template.width() as f32 / scale,
+                    template.height() as f32 / scale,
+                ),
+            });
+        }
+        matches
+    }
+
+    // MARK: - Private Helper Methods
+
+    fn to_event(&self, found: TemplateMatch, frame_id: &str, timestamp: DateTime<Utc>) -> ErrorModalEvent {
+        let template = self
+            .templates
+            .iter()
+            .map(|loaded| &loaded.template)
+            .find(|template| template.name == found.name)
+            .expect("matches only come from registered templates");
+
+        let mut metadata = HashMap::new();
+        metadata.insert("detection_method".to_string(), "template_match".to_string());
+        metadata.insert(TEMPLATE_KEY.to_string(), found.name.clone());
+        metadata.insert("match_score".to_string(), format!("{:.3}", found.score));
+
+        ErrorModalEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp,
+            event_type: template.event_type.clone(),
+            severity: template.severity.clone(),
+            title: found.name.clone(),
+            message: template.message.clone().unwrap_or_else(|| found.name.clone()),
+            confidence: found.score,
+            frame_id: frame_id.to_string(),
+            roi: found.roi,
+            metadata,
+            pattern_matches: vec![PatternMatch {
+                pattern_type: "template".to_string(),
+                matched_text: found.name.clone(),
+                confidence_weight: found.score,
+                description: format!("Matched dialog template '{}'", found.name),
+            }],
+            layout_analysis: None,
+        }
+    }
+}
+
+fn downscale(image: &GrayImage, scale: f32) -> GrayImage {
+    if (scale - 1.0).abs() < f32::EPSILON {
+        return image.clone();
+    }
+    let width = ((image.width() as f32 * scale).round() as u32).max(1);
+    let height = ((image.height() as f32 * scale).round() as u32).max(1);
+    image::imageops::resize(image, width, height, FilterType::Triangle)
+}
+
+/// Running sums of pixel values and their squares, for window statistics in constant time
+struct IntegralImage {
+    width: usize,
+    sum: Vec<f64>,
+    sum_sq: Vec<f64>,
+}
+
+impl IntegralImage {
+    fn new(image: &GrayImage) -> Self {
+        let width = image.width() as usize + 1;
+        let height = image.height() as usize + 1;
+        let mut sum = vec![0.0; width * height];
+        let mut sum_sq = vec![0.0; width * height];
+        for y in 1..height {
+            let mut row = 0.0;
+            let mut row_sq = 0.0;
+            for x in 1..width {
+                let value = image.get_pixel(x as u32 - 1, y as u32 - 1)[0] as f64;
+                row += value;
+                row_sq += value * value;
+                sum[y * width + x] = sum[(y - 1) * width + x] + row;
+                sum_sq[y * width + x] = sum_sq[(y - 1) * width + x] + row_sq;
+            }
+        }
+        Self { width, sum, sum_sq }
+    }
+
+    fn window(&self, table: &[f64], x: u32, y: u32, width: u32, height: u32) -> f64 {
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = (x0 + width as usize, y0 + height as usize);
+        table[y1 * self.width + x1] - table[y0 * self.width + x1] - table[y1 * self.width + x0] + table[y0 * self.width + x0]
+    }
+}
+
+/// Position and score of the best normalized cross-correlation of `template` in `frame`.
+///
+/// Positions are sampled every other pixel and the best one refined, which
+/// keeps the search cheap enough to run on every keyframe.
+fn best_match(frame: &GrayImage, integral: &IntegralImage, template: &GrayImage) -> Option<(u32, u32, f32)> {
+    let (tw, th) = template.dimensions();
+    if tw < 4 || th < 4 || tw > frame.width() || th > frame.height() {
+        return None;
+    }
+
+    let n = (tw * th) as f64;
+    let mean = template.pixels().map(|p| p[0] as f64).sum::<f64>() / n;
+    let centered: Vec<f64> = template.pixels().map(|p| p[0] as f64 - mean).collect();
+    let template_norm = centered.iter().map(|v| v * v).sum::<f64>().sqrt();
+    // A flat template correlates equally with every flat region
+    if template_norm < 1e-6 {
+        return None;
+    }
+
+    let score_at = |x: u32, y: u32| -> f64 {
+        let window_sum = integral.window(&integral.sum, x, y, tw, th);
+        let window_sq = integral.window(&integral.sum_sq, x, y, tw, th);
+        let window_var = window_sq - window_sum * window_sum / n;
+        if window_var <= 1e-6 {
+            return 0.0;
+        }
+        let mut cross = 0.0;
+        for ty in 0..th {
+            let row = (ty * tw) as usize;
+            for tx in 0..tw {
+                cross += centered[row + tx as usize] * frame.get_pixel(x + tx, y + ty)[0] as f64;
+            }
+        }
+        cross / (template_norm * window_var.sqrt())
+    };
+
+    let max_x = frame.width() - tw;
+    let max_y = frame.height() - th;
+    let mut best = (0, 0, f64::MIN);
+    for y in (0..=max_y).step_by(2) {
+        for x in (0..=max_x).step_by(2) {
+            let score = score_at(x, y);
+            if score > best.2 {
+                best = (x, y, score);
+            }
+        }
+    }
+
+    let (coarse_x, coarse_y, _) = best;
+    for y in coarse_y.saturating_sub(1)..=(coarse_y + 1).min(max_y) {
+        for x in coarse_x.saturating_sub(1)..=(coarse_x + 1).min(max_x) {
+            let score = score_at(x, y);
+            if score > best.2 {
+                best = (x, y, score);
+            }
+        }
+    }
+
+    Some((best.0, best.1, best.2.clamp(0.0, 1.0) as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+    use tempfile::TempDir;
+
+    /// Gradient desktop, optionally with a striped dialog at (240, 120)
+    fn screen(with_dialog: bool) -> GrayImage {
+        GrayImage::from_fn(640, 400, |x, y| {
+            if with_dialog && (240..400).contains(&x) && (120..220).contains(&y) {
+                let line = (y - 120) / 6 % 2 == 1 && (256..(256 + (y % 5) * 24)).contains(&x);
+                Luma([if line { 30 } else { 240 }])
+            } else {
+                Luma([(60 + x / 8 + y / 10) as u8])
+            }
+        })
+    }
+
+    #[test]
+    fn test_registered_dialog_is_found_and_reported() {
+        let dir = TempDir::new().unwrap();
+        let template_path = dir.path().join("transfer_failed.png");
+        image::imageops::crop_imm(&screen(true), 240, 120, 160, 100)
+            .to_image()
+            .save(&template_path)
+            .unwrap();
+        let frame_path = dir.path().join("frame.png");
+        screen(true).save(&frame_path).unwrap();
+
+        let matcher = TemplateMatcher::new(TemplateMatchingConfig {
+            templates: vec![DialogTemplate {
+                name: "transfer_failed".to_string(),
+                image_path: template_path,
+                event_type: ErrorModalType::ApplicationError,
+                severity: SeverityLevel::Critical,
+                message: Some("Transfer could not be completed".to_string()),
+                min_score: None,
+                capture_width: None,
+            }],
+            ..TemplateMatchingConfig::default()
+        })
+        .unwrap();
+        assert_eq!(matcher.template_count(), 1);
+
+        let events = matcher.match_frame(&frame_path.to_string_lossy(), Utc::now()).unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.title, "transfer_failed");
+        assert_eq!(event.metadata[TEMPLATE_KEY], "transfer_failed");
+        assert_eq!(event.severity, SeverityLevel::Critical);
+        assert!(event.confidence > 0.95);
+        assert!((event.roi.x - 240.0).abs() <= 4.0 && (event.roi.y - 120.0).abs() <= 4.0);
+
+        assert!(matcher.match_image(&screen(false)).is_empty());
+    }
+}
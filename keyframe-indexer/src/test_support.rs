@@ -0,0 +1,564 @@
+//! Synthetic screen recordings with known events, for end-to-end accuracy
+//! tests of scene and event detection.
+//!
+//! A [`ScreenScript`] lists what happens on screen: windows opening, typing,
+//! dialogs and scrolling. Rendering it gives one image per frame, the text on
+//! each frame as OCR would read it, and the events and scene changes a
+//! detector should find. Grey bars stand in for glyphs, as no font is bundled.
+
+use crate::error::{IndexerError, Result};
+use crate::event_detector::EventType;
+use crate::keyframe_extractor::Keyframe;
+use crate::ocr_data::{BoundingBox, OCRResult};
+use chrono::{DateTime, Utc};
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use uuid::Uuid;
+
+const TEXT_COLOUR: Rgb<u8> = Rgb([50, 50, 50]);
+const ERROR_COLOUR: Rgb<u8> = Rgb([200, 40, 40]);
+
+/// Something that happens on the synthetic screen
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScreenAction {
+    /// Bring up a window in place of the one on screen
+    OpenWindow { app: String, title: String },
+    /// Type into a labeled field, one character per frame
+    Type { field: String, text: String },
+    /// Show a dialog over the window; error dialogs have a red banner
+    ShowDialog { title: String, message: String, error: bool },
+    DismissDialog,
+    /// Scroll the window's text by this many lines, one line per frame
+    Scroll { lines: usize },
+    /// Frames where nothing changes
+    Idle { frames: usize },
+}
+
+/// An event a detector should report, at the frame where it shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroundTruthEvent {
+    pub frame_index: usize,
+    pub event_type: EventType,
+    pub target: String,
+    pub value: Option<String>,
+}
+
+/// One rendered frame
+#[derive(Debug, Clone)]
+pub struct SyntheticFrame {
+    /// Offset from the start of the recording
+    pub timestamp_ns: i64,
+    pub image: RgbImage,
+    /// Text on screen with its region, as OCR would read it
+    pub text: Vec<(String, BoundingBox)>,
+    pub app_name: String,
+    pub win_title: String,
+}
+
+/// A scripted sequence of screen actions, built up step by step
+#[derive(Debug, Clone)]
+pub struct ScreenScript {
+    width: u32,
+    height: u32,
+    fps: f32,
+    actions: Vec<ScreenAction>,
+}
+
+impl ScreenScript {
+    /// An empty script at one frame per second; the first action should open a window
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, fps: 1.0, actions: Vec::new() }
+    }
+
+    pub fn fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn open_window(self, app: &str, title: &str) -> Self {
+        self.then(ScreenAction::OpenWindow { app: app.to_string(), title: title.to_string() })
+    }
+
+    pub fn type_into(self, field: &str, text: &str) -> Self {
+        self.then(ScreenAction::Type { field: field.to_string(), text: text.to_string() })
+    }
+
+    pub fn show_dialog(self, title: &str, message: &str, error: bool) -> Self {
+        self.then(ScreenAction::ShowDialog { title: title.to_string(), message: message.to_string(), error })
+    }
+
+    pub fn dismiss_dialog(self) -> Self {
+        self.then(ScreenAction::DismissDialog)
+    }
+
+    pub fn scroll(self, lines: usize) -> Self {
+        self.then(ScreenAction::Scroll { lines })
+    }
+
+    pub fn idle(self, frames: usize) -> Self {
+        self.then(ScreenAction::Idle { frames })
+    }
+
+    pub fn then(mut self, action: ScreenAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Render every frame with its ground truth
+    pub fn render(&self) -> Result<SyntheticRecording> {
+        if self.width < 320 || self.height < 200 || self.fps <= 0.0 {
+            return Err(IndexerError::Config("synthetic screens must be at least 320x200 at a positive fps".to_string()));
+        }
+        if !matches!(self.actions.first(), Some(ScreenAction::OpenWindow { .. })) {
+            return Err(IndexerError::Config("a screen script must start by opening a window".to_string()));
+        }
+
+        let mut recording = SyntheticRecording {
+            width: self.width,
+            height: self.height,
+            fps: self.fps,
+            frames: Vec::new(),
+            events: Vec::new(),
+            scene_changes: Vec::new(),
+        };
+        let mut screen = Screen::default();
+        for action in &self.actions {
+            match action {
+                ScreenAction::OpenWindow { app, title } => {
+                    if !screen.app.is_empty() {
+                        recording.truth(EventType::Navigation, "window", Some(title.as_str()));
+                    }
+                    screen = Screen { app: app.clone(), title: title.clone(), ..Screen::default() };
+                    recording.scene_changes.push(recording.frames.len());
+                    recording.push(self.draw(&screen), &screen);
+                }
+                ScreenAction::Type { field, text } => {
+                    let slot = match screen.fields.iter().position(|(label, _)| label == field) {
+                        Some(slot) => slot,
+                        None => {
+                            screen.fields.push((field.clone(), String::new()));
+                            screen.fields.len() - 1
+                        }
+                    };
+                    for character in text.chars() {
+                        screen.fields[slot].1.push(character);
+                        let value = screen.fields[slot].1.clone();
+                        recording.truth(EventType::FieldChange, field, Some(value.as_str()));
+                        recording.push(self.draw(&screen), &screen);
+                    }
+                }
+                ScreenAction::ShowDialog { title, message, error } => {
+                    screen.dialog = Some((title.clone(), message.clone(), *error));
+                    let event_type = if *error { EventType::ErrorDisplay } else { EventType::ModalAppearance };
+                    recording.truth(event_type, title, Some(message.as_str()));
+                    recording.scene_changes.push(recording.frames.len());
+                    recording.push(self.draw(&screen), &screen);
+                }
+                ScreenAction::DismissDialog => {
+                    if screen.dialog.take().is_some() {
+                        recording.scene_changes.push(recording.frames.len());
+                    }
+                    recording.push(self.draw(&screen), &screen);
+                }
+                ScreenAction::Scroll { lines } => {
+                    for _ in 0..*lines {
+                        screen.scroll += 1;
+                        recording.push(self.draw(&screen), &screen);
+                    }
+                }
+                ScreenAction::Idle { frames } => {
+                    for _ in 0..*frames {
+                        recording.push(self.draw(&screen), &screen);
+                    }
+                }
+            }
+        }
+        Ok(recording)
+    }
+
+    /// The screen as an image, with its text regions
+    fn draw(&self, screen: &Screen) -> (RgbImage, Vec<(String, BoundingBox)>) {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let mut image = RgbImage::from_pixel(self.width, self.height, app_background(&screen.app));
+        let mut text = Vec::new();
+        let at = |x: f32, y: f32, width: f32, height: f32| BoundingBox::new(x * w, y * h, width * w, height * h);
+
+        fill(&mut image, &at(0.0, 0.0, 1.0, 0.07), Rgb([210, 210, 215]));
+        put_text(&mut image, &mut text, &screen.title, at(0.02, 0.015, 0.6, 0.04), TEXT_COLOUR);
+
+        for (slot, (label, value)) in screen.fields.iter().enumerate() {
+            let y = 0.12 + slot as f32 * 0.08;
+            put_text(&mut image, &mut text, label, at(0.05, y, 0.2, 0.04), TEXT_COLOUR);
+            fill(&mut image, &at(0.3, y - 0.005, 0.4, 0.05), Rgb([255, 255, 255]));
+            put_text(&mut image, &mut text, value, at(0.31, y, 0.38, 0.04), TEXT_COLOUR);
+        }
+
+        // Body text fills the rest of the window and moves up as it scrolls
+        let mut y = 0.12 + screen.fields.len() as f32 * 0.08 + 0.04;
+        let mut line = screen.scroll;
+        while y + 0.03 < 0.97 {
+            let content = format!("{} line {} {}", screen.title, line + 1, "lorem ipsum ".repeat(line % 4 + 1));
+            put_text(&mut image, &mut text, content.trim_end(), at(0.05, y, 0.9, 0.03), Rgb([90, 90, 90]));
+            y += 0.05;
+            line += 1;
+        }
+
+        if let Some((title, message, error)) = &screen.dialog {
+            // A dialog hides what is behind it, text included
+            let panel = at(0.3, 0.35, 0.4, 0.25);
+            text.retain(|(_, roi)| roi.iou(&panel) == 0.0);
+            fill(&mut image, &at(0.295, 0.345, 0.41, 0.26), Rgb([120, 120, 120]));
+            fill(&mut image, &panel, Rgb([250, 250, 250]));
+            if *error {
+                fill(&mut image, &at(0.3, 0.35, 0.4, 0.06), Rgb([250, 220, 220]));
+            }
+            let colour = if *error { ERROR_COLOUR } else { TEXT_COLOUR };
+            put_text(&mut image, &mut text, title, at(0.32, 0.36, 0.36, 0.04), colour);
+            put_text(&mut image, &mut text, message, at(0.32, 0.44, 0.36, 0.04), TEXT_COLOUR);
+            put_text(&mut image, &mut text, "OK", at(0.6, 0.53, 0.08, 0.04), TEXT_COLOUR);
+        }
+        (image, text)
+    }
+}
+
+/// Window state while rendering
+#[derive(Debug, Clone, Default)]
+struct Screen {
+    app: String,
+    title: String,
+    fields: Vec<(String, String)>,
+    scroll: usize,
+    /// Title, message and whether it is an error
+    dialog: Option<(String, String, bool)>,
+}
+
+/// Rendered frames of a script with what detectors should find in them
+#[derive(Debug, Clone)]
+pub struct SyntheticRecording {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub frames: Vec<SyntheticFrame>,
+    pub events: Vec<GroundTruthEvent>,
+    /// Frames that differ substantially from the one before: new windows and
+    /// dialogs appearing or going
+    pub scene_changes: Vec<usize>,
+}
+
+impl SyntheticRecording {
+    fn truth(&mut self, event_type: EventType, target: &str, value: Option<&str>) {
+        self.events.push(GroundTruthEvent {
+            frame_index: self.frames.len(),
+            event_type,
+            target: target.to_string(),
+            value: value.map(str::to_string),
+        });
+    }
+
+    fn push(&mut self, (image, text): (RgbImage, Vec<(String, BoundingBox)>), screen: &Screen) {
+        let timestamp_ns = (self.frames.len() as f64 * 1e9 / self.fps as f64) as i64;
+        self.frames.push(SyntheticFrame {
+            timestamp_ns,
+            image,
+            text,
+            app_name: screen.app.clone(),
+            win_title: screen.title.clone(),
+        });
+    }
+
+    /// Save every frame as a PNG keyframe of one segment under `dir`, with
+    /// the OCR results of each
+    pub fn write_keyframes(&self, dir: &Path, segment_id: &str, start: DateTime<Utc>) -> Result<SyntheticSegment> {
+        std::fs::create_dir_all(dir)?;
+        let mut segment = SyntheticSegment { start, keyframes: Vec::new(), ocr: Vec::new() };
+        for (index, frame) in self.frames.iter().enumerate() {
+            let path = dir.join(format!("frame_{}_{:05}.png", segment_id, index));
+            frame.image.save(&path)?;
+            let frame_path = path.to_string_lossy().to_string();
+            let processed_at = start + chrono::Duration::nanoseconds(frame.timestamp_ns);
+            segment.ocr.push(
+                frame
+                    .text
+                    .iter()
+                    .map(|(text, roi)| OCRResult {
+                        frame_id: frame_path.clone(),
+                        roi: roi.clone(),
+                        text: text.clone(),
+                        language: "en-US".to_string(),
+                        confidence: 0.95,
+                        processed_at,
+                        processor: "synthetic".to_string(),
+                    })
+                    .collect(),
+            );
+            segment.keyframes.push(Keyframe {
+                id: Uuid::new_v4(),
+                timestamp_ns: frame.timestamp_ns,
+                segment_id: segment_id.to_string(),
+                frame_path,
+                width: self.width,
+                height: self.height,
+                format: "RGB".to_string(),
+                source_fps: Some(self.fps),
+                display_id: None,
+            });
+        }
+        Ok(segment)
+    }
+
+    /// Encode the frames as an H.264 video segment with the `ffmpeg` command,
+    /// for tests of keyframe extraction; needs even frame dimensions
+    pub fn write_video(&self, path: &Path) -> Result<()> {
+        if self.width % 2 != 0 || self.height % 2 != 0 {
+            return Err(IndexerError::Config("video frames need an even width and height".to_string()));
+        }
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{}x{}", self.width, self.height), "-r", &self.fps.to_string(), "-i", "-"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to start ffmpeg: {}", e)))?;
+        if let Some(mut stdin) = ffmpeg.stdin.take() {
+            for frame in &self.frames {
+                stdin.write_all(frame.image.as_raw())?;
+            }
+        }
+        let status = ffmpeg.wait()?;
+        if !status.success() {
+            return Err(std::io::Error::other(format!("ffmpeg failed to encode {}: {}", path.display(), status)).into());
+        }
+        Ok(())
+    }
+}
+
+/// Saved keyframes of a recording, ready for the detectors
+#[derive(Debug, Clone)]
+pub struct SyntheticSegment {
+    pub start: DateTime<Utc>,
+    pub keyframes: Vec<Keyframe>,
+    /// OCR results of each keyframe, in the same order
+    pub ocr: Vec<Vec<OCRResult>>,
+}
+
+impl SyntheticSegment {
+    pub fn timestamp(&self, index: usize) -> DateTime<Utc> {
+        self.start + chrono::Duration::nanoseconds(self.keyframes[index].timestamp_ns)
+    }
+
+    /// Index of the keyframe with this path, as detectors use it as the frame id
+    pub fn frame_index(&self, frame_id: &str) -> Option<usize> {
+        self.keyframes.iter().position(|keyframe| keyframe.frame_path == frame_id)
+    }
+}
+
+/// Detections matched against ground truth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Accuracy {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+impl Accuracy {
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            return 0.0;
+        }
+        2.0 * precision * recall / (precision + recall)
+    }
+}
+
+/// Score detected `(frame index, type)` pairs against the ground truth; a
+/// detection matches an unmatched true event of its type at most
+/// `tolerance_frames` away. Only types present in `types` are scored.
+pub fn score_events(
+    truth: &[GroundTruthEvent],
+    detected: &[(usize, EventType)],
+    types: &[EventType],
+    tolerance_frames: usize,
+) -> Accuracy {
+    let truth: Vec<(usize, EventType)> = truth
+        .iter()
+        .filter(|event| types.contains(&event.event_type))
+        .map(|event| (event.frame_index, event.event_type.clone()))
+        .collect();
+    let detected: Vec<(usize, EventType)> = detected.iter().filter(|(_, t)| types.contains(t)).cloned().collect();
+    match_frames(&truth, &detected, tolerance_frames)
+}
+
+/// Score detected scene change frames against the true ones
+pub fn score_scene_changes(truth: &[usize], detected: &[usize], tolerance_frames: usize) -> Accuracy {
+    let label = |frames: &[usize]| frames.iter().map(|frame| (*frame, ())).collect::<Vec<_>>();
+    match_frames(&label(truth), &label(detected), tolerance_frames)
+}
+
+fn match_frames<T: PartialEq>(truth: &[(usize, T)], detected: &[(usize, T)], tolerance_frames: usize) -> Accuracy {
+    let mut matched = vec![false; truth.len()];
+    let mut accuracy = Accuracy::default();
+    for (frame, kind) in detected {
+        let hit = truth.iter().enumerate().position(|(index, (true_frame, true_kind))| {
+            !matched[index] && true_kind == kind && true_frame.abs_diff(*frame) <= tolerance_frames
+        });
+        match hit {
+            Some(index) => {
+                matched[index] = true;
+                accuracy.true_positives += 1;
+            }
+            None => accuracy.false_positives += 1,
+        }
+    }
+    accuracy.false_negatives = matched.iter().filter(|matched| !**matched).count();
+    accuracy
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        return 1.0;
+    }
+    numerator as f64 / denominator as f64
+}
+
+/// A light background per app, so window switches change the whole screen
+fn app_background(app: &str) -> Rgb<u8> {
+    let hash = app.bytes().fold(7u32, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u32));
+    Rgb([200 + (hash % 50) as u8, 200 + (hash / 50 % 50) as u8, 200 + (hash / 2500 % 50) as u8])
+}
+
+fn fill(image: &mut RgbImage, roi: &BoundingBox, colour: Rgb<u8>) {
+    let x1 = ((roi.x + roi.width) as u32).min(image.width());
+    let y1 = ((roi.y + roi.height) as u32).min(image.height());
+    for y in roi.y.max(0.0) as u32..y1 {
+        for x in roi.x.max(0.0) as u32..x1 {
+            image.put_pixel(x, y, colour);
+        }
+    }
+}
+
+/// Draw a text line as a bar as long as the text, with gaps between words,
+/// and record its region
+fn put_text(image: &mut RgbImage, text: &mut Vec<(String, BoundingBox)>, content: &str, area: BoundingBox, colour: Rgb<u8>) {
+    if content.is_empty() {
+        return;
+    }
+    let char_width = area.height * 0.5;
+    let width = (content.chars().count() as f32 * char_width).min(area.width);
+    let mut x = area.x;
+    for word in content.split(' ') {
+        let word_width = word.chars().count() as f32 * char_width;
+        if x + word_width > area.x + width {
+            break;
+        }
+        fill(image, &BoundingBox::new(x, area.y, word_width, area.height), colour);
+        x += word_width + char_width;
+    }
+    text.push((content.to_string(), BoundingBox::new(area.x, area.y, width, area.height)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SceneDetectionConfig;
+    use crate::event_detector::EventDetector;
+    use crate::scene_detector::SceneDetector;
+
+    fn script() -> ScreenScript {
+        ScreenScript::new(640, 400)
+            .open_window("Safari", "Sign in - Example Portal")
+            .type_into("Username", "jdoe")
+            .show_dialog("Error", "Invalid password", true)
+            .dismiss_dialog()
+            .scroll(2)
+            .open_window("Numbers", "Q3 Invoices.numbers")
+            .idle(1)
+    }
+
+    #[test]
+    fn test_script_renders_frames_with_ground_truth() {
+        let recording = script().render().unwrap();
+        assert_eq!(recording.frames.len(), 1 + 4 + 1 + 1 + 2 + 1 + 1);
+        assert_eq!(recording.scene_changes, vec![0, 5, 6, 9]);
+        assert_eq!(recording.frames[9].app_name, "Numbers");
+        assert_eq!(recording.frames[2].timestamp_ns, 2_000_000_000);
+
+        let typed: Vec<_> = recording.events.iter().filter(|e| e.event_type == EventType::FieldChange).collect();
+        assert_eq!(typed.len(), 4);
+        assert_eq!((typed[3].frame_index, typed[3].value.as_deref()), (4, Some("jdoe")));
+        let error = recording.events.iter().find(|e| e.event_type == EventType::ErrorDisplay).unwrap();
+        assert_eq!(error.frame_index, 5);
+        assert!(recording.frames[5].text.iter().any(|(text, _)| text == "Invalid password"));
+        assert!(!recording.frames[6].text.iter().any(|(text, _)| text == "Invalid password"));
+        // Scrolling moves the body text
+        assert_ne!(recording.frames[7].text, recording.frames[8].text);
+        assert_ne!(recording.frames[7].image, recording.frames[8].image);
+        assert!(script().type_into("Password", "x").render().is_ok());
+        assert!(ScreenScript::new(640, 400).idle(1).render().is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let segment = recording.write_keyframes(dir.path(), "synthetic_monitor0_0001", Utc::now()).unwrap();
+        assert_eq!(segment.keyframes.len(), recording.frames.len());
+        assert!(Path::new(&segment.keyframes[5].frame_path).exists());
+        assert_eq!(segment.frame_index(&segment.ocr[5][0].frame_id), Some(5));
+
+        let detected = [(4, EventType::FieldChange), (5, EventType::ErrorDisplay), (8, EventType::ErrorDisplay)];
+        let accuracy = score_events(&recording.events, &detected, &[EventType::ErrorDisplay, EventType::FieldChange], 0);
+        assert_eq!(accuracy, Accuracy { true_positives: 2, false_positives: 1, false_negatives: 3 });
+        assert_eq!(score_scene_changes(&recording.scene_changes, &[1, 9], 1).recall(), 0.5);
+    }
+
+    #[test]
+    fn test_detectors_find_scripted_scene_changes_and_errors() {
+        let recording = ScreenScript::new(640, 400)
+            .open_window("Safari", "Sign in - Example Portal")
+            .type_into("Username", "jdoe")
+            .show_dialog("Error", "Invalid password", true)
+            .idle(1)
+            .dismiss_dialog()
+            .open_window("Numbers", "Q3 Invoices.numbers")
+            .scroll(3)
+            .show_dialog("Error", "Invalid cell reference", true)
+            .dismiss_dialog()
+            .open_window("Mail", "Inbox")
+            .idle(2)
+            .render()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let segment = recording.write_keyframes(dir.path(), "synthetic_monitor0_0001", Utc::now()).unwrap();
+
+        // The first frame has nothing before it to change from
+        let scene_detector = SceneDetector::new(SceneDetectionConfig::default()).unwrap();
+        let analysis = scene_detector.analyze_keyframes(&segment.keyframes).unwrap();
+        let detected: Vec<usize> = analysis.scene_changes.iter().map(|change| change.frame_index).collect();
+        let scenes = score_scene_changes(&recording.scene_changes[1..], &detected, 1);
+        assert!(scenes.recall() >= 0.5 && scenes.precision() >= 0.5, "scene changes: {:?}", scenes);
+
+        let mut event_detector = EventDetector::new().unwrap();
+        let mut detected = Vec::new();
+        for (index, keyframe) in segment.keyframes.iter().enumerate() {
+            let events = event_detector
+                .analyze_frame(&keyframe.frame_path, &segment.ocr[index], segment.timestamp(index), 640.0, 400.0)
+                .unwrap();
+            detected.extend(events.into_iter().map(|event| (index, event.event_type)));
+        }
+        for event in event_detector.finish() {
+            if let Some(index) = event.evidence_frames.iter().find_map(|frame| segment.frame_index(frame)) {
+                detected.push((index, event.event_type));
+            }
+        }
+        let errors = score_events(&recording.events, &detected, &[EventType::ErrorDisplay], 1);
+        assert!(errors.recall() >= 0.5, "error dialogs: {:?}", errors);
+    }
+}
@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Beyond this many LCS cells the changed middle is reported as one replacement
+const MAX_LCS_CELLS: usize = 250_000;
+
+/// Unit the diff is computed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffGranularity {
+    /// Individual characters
+    #[default]
+    Character,
+    /// Words and the whitespace between them
+    Token,
+}
+
+impl DiffGranularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffGranularity::Character => "character",
+            DiffGranularity::Token => "token",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "character" => Some(DiffGranularity::Character),
+            "token" => Some(DiffGranularity::Token),
+            _ => None,
+        }
+    }
+}
+
+/// One step of the edit script turning the old value into the new one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+    Replace { from: String, to: String },
+}
+
+/// Overall shape of a field change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Unchanged,
+    /// Text added at the end, e.g. typing into a field
+    Append,
+    /// Text added at the start
+    Prepend,
+    /// Text added in the middle
+    Insert,
+    /// Text removed from the middle or start
+    Delete,
+    /// Text removed from the end, e.g. backspacing
+    Truncate,
+    /// One contiguous span replaced
+    Replace,
+    /// Several separate spans changed
+    Edit,
+    /// Little or nothing of the old value kept
+    ReplaceAll,
+    /// Value emptied
+    Clear,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Unchanged => "unchanged",
+            ChangeKind::Append => "append",
+            ChangeKind::Prepend => "prepend",
+            ChangeKind::Insert => "insert",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Truncate => "truncate",
+            ChangeKind::Replace => "replace",
+            ChangeKind::Edit => "edit",
+            ChangeKind::ReplaceAll => "replace_all",
+            ChangeKind::Clear => "clear",
+        }
+    }
+}
+
+/// Insertions, deletions and replacements between two field values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextDiff {
+    pub granularity: DiffGranularity,
+    pub kind: ChangeKind,
+    pub ops: Vec<DiffOp>,
+    /// Characters present in the new value but not the old one
+    pub inserted_chars: usize,
+    /// Characters of the old value that were removed
+    pub deleted_chars: usize,
+}
+
+impl TextDiff {
+    pub fn compute(from: &str, to: &str, granularity: DiffGranularity) -> Self {
+        let old_units = split_units(from, granularity);
+        let new_units = split_units(to, granularity);
+        let ops = diff_units(&old_units, &new_units);
+
+        let mut inserted_chars = 0;
+        let mut deleted_chars = 0;
+        for op in &ops {
+            match op {
+                DiffOp::Equal(_) => {}
+                DiffOp::Insert(text) => inserted_chars += text.chars().count(),
+                DiffOp::Delete(text) => deleted_chars += text.chars().count(),
+                DiffOp::Replace { from, to } => {
+                    deleted_chars += from.chars().count();
+                    inserted_chars += to.chars().count();
+                }
+            }
+        }
+
+        let kind = classify(&ops, from, to);
+        Self { granularity, kind, ops, inserted_chars, deleted_chars }
+    }
+
+    /// Text added by the change, concatenated across all inserting ops
+    pub fn inserted_text(&self) -> String {
+        self.ops
+            .iter()
+            .filter_map(|op| match op {
+                DiffOp::Insert(text) | DiffOp::Replace { to: text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Short human-readable description, e.g. "appended 3 characters"
+    pub fn summary(&self) -> String {
+        match self.kind {
+            ChangeKind::Unchanged => "unchanged".to_string(),
+            ChangeKind::Append => format!("appended {}", characters(self.inserted_chars)),
+            ChangeKind::Prepend => format!("prepended {}", characters(self.inserted_chars)),
+            ChangeKind::Insert => format!("inserted {}", characters(self.inserted_chars)),
+            ChangeKind::Delete => format!("deleted {}", characters(self.deleted_chars)),
+            ChangeKind::Truncate => format!("removed {} from the end", characters(self.deleted_chars)),
+            ChangeKind::Replace => {
+                format!("replaced {} with {}", characters(self.deleted_chars), characters(self.inserted_chars))
+            }
+            ChangeKind::Edit => {
+                format!("edited {} (+{}/-{})", characters(self.inserted_chars + self.deleted_chars), self.inserted_chars, self.deleted_chars)
+            }
+            ChangeKind::ReplaceAll => "replaced entire value".to_string(),
+            ChangeKind::Clear => "cleared value".to_string(),
+        }
+    }
+
+    /// Add the diff to event metadata under `diff_*` keys
+    pub fn write_metadata(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert("diff_kind".to_string(), self.kind.as_str().to_string());
+        metadata.insert("diff_granularity".to_string(), self.granularity.as_str().to_string());
+        metadata.insert("diff_summary".to_string(), self.summary());
+        metadata.insert("diff_inserted_chars".to_string(), self.inserted_chars.to_string());
+        metadata.insert("diff_deleted_chars".to_string(), self.deleted_chars.to_string());
+        if let Ok(ops) = serde_json::to_string(&self.ops) {
+            metadata.insert("diff_ops".to_string(), ops);
+        }
+    }
+}
+
+fn characters(count: usize) -> String {
+    if count == 1 {
+        "1 character".to_string()
+    } else {
+        format!("{} characters", count)
+    }
+}
+
+/// Split text into diff units; concatenating the units gives back the text
+fn split_units(text: &str, granularity: DiffGranularity) -> Vec<&str> {
+    match granularity {
+        DiffGranularity::Character => text.char_indices().map(|(i, c)| &text[i..i + c.len_utf8()]).collect(),
+        DiffGranularity::Token => {
+            let mut units = Vec::new();
+            let mut start = 0;
+            let mut in_whitespace = None;
+            for (i, c) in text.char_indices() {
+                let whitespace = c.is_whitespace();
+                if in_whitespace.is_some_and(|w| w != whitespace) {
+                    units.push(&text[start..i]);
+                    start = i;
+                }
+                in_whitespace = Some(whitespace);
+            }
+            if start < text.len() {
+                units.push(&text[start..]);
+            }
+            units
+        }
+    }
+}
+
+/// Edit script over units: common prefix and suffix are trimmed, the middle is
+/// aligned with an LCS, and adjacent deletes and inserts become replacements
+fn diff_units(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // Per-unit steps, merged into ops at the end
+    let mut steps: Vec<(Step, &str)> = old[..prefix].iter().map(|u| (Step::Equal, *u)).collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_LCS_CELLS {
+        steps.extend(old_mid.iter().map(|u| (Step::Delete, *u)));
+        steps.extend(new_mid.iter().map(|u| (Step::Insert, *u)));
+    } else {
+        steps.extend(lcs_steps(old_mid, new_mid));
+    }
+
+    steps.extend(old[old.len() - suffix..].iter().map(|u| (Step::Equal, *u)));
+    merge_steps(steps)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Equal,
+    Insert,
+    Delete,
+}
+
+fn lcs_steps<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Step, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j] = LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            steps.push((Step::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            steps.push((Step::Delete, old[i]));
+            i += 1;
+        } else {
+            steps.push((Step::Insert, new[j]));
+            j += 1;
+        }
+    }
+    steps.extend(old[i..].iter().map(|u| (Step::Delete, *u)));
+    steps.extend(new[j..].iter().map(|u| (Step::Insert, *u)));
+    steps
+}
+
+fn merge_steps(steps: Vec<(Step, &str)>) -> Vec<DiffOp> {
+    let mut ops = Vec::new();
+    let mut deleted = String::new();
+    let mut inserted = String::new();
+
+    let flush = |ops: &mut Vec<DiffOp>, deleted: &mut String, inserted: &mut String| {
+        match (deleted.is_empty(), inserted.is_empty()) {
+            (true, true) => {}
+            (false, true) => ops.push(DiffOp::Delete(std::mem::take(deleted))),
+            (true, false) => ops.push(DiffOp::Insert(std::mem::take(inserted))),
+            (false, false) => ops.push(DiffOp::Replace {
+                from: std::mem::take(deleted),
+                to: std::mem::take(inserted),
+            }),
+        }
+    };
+
+    for (step, unit) in steps {
+        match step {
+            Step::Delete => deleted.push_str(unit),
+            Step::Insert => inserted.push_str(unit),
+            Step::Equal => {
+                flush(&mut ops, &mut deleted, &mut inserted);
+                match ops.last_mut() {
+                    Some(DiffOp::Equal(text)) => text.push_str(unit),
+                    _ => ops.push(DiffOp::Equal(unit.to_string())),
+                }
+            }
+        }
+    }
+    flush(&mut ops, &mut deleted, &mut inserted);
+    ops
+}
+
+fn classify(ops: &[DiffOp], from: &str, to: &str) -> ChangeKind {
+    if from == to {
+        return ChangeKind::Unchanged;
+    }
+    if to.is_empty() {
+        return ChangeKind::Clear;
+    }
+    if from.is_empty() {
+        return ChangeKind::Append;
+    }
+
+    // Most of the old value gone: scattered single-character matches are not an edit
+    let kept_chars: usize = ops
+        .iter()
+        .filter_map(|op| match op {
+            DiffOp::Equal(text) => Some(text.chars().count()),
+            _ => None,
+        })
+        .sum();
+    if kept_chars * 2 < from.chars().count() {
+        return ChangeKind::ReplaceAll;
+    }
+
+    let changes: Vec<(usize, &DiffOp)> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(_)))
+        .collect();
+
+    match changes.as_slice() {
+        [(index, op)] => {
+            let at_start = *index == 0;
+            let at_end = *index == ops.len() - 1;
+            match op {
+                DiffOp::Insert(_) if at_end => ChangeKind::Append,
+                DiffOp::Insert(_) if at_start => ChangeKind::Prepend,
+                DiffOp::Insert(_) => ChangeKind::Insert,
+                DiffOp::Delete(_) if at_end => ChangeKind::Truncate,
+                DiffOp::Delete(_) => ChangeKind::Delete,
+                _ => ChangeKind::Replace,
+            }
+        }
+        _ => ChangeKind::Edit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_kinds() {
+        let diff = TextDiff::compute("john@exam", "john@example.com", DiffGranularity::Character);
+        assert_eq!(diff.kind, ChangeKind::Append);
+        assert_eq!(diff.inserted_chars, 7);
+        assert_eq!(diff.inserted_text(), "ple.com");
+        assert_eq!(diff.summary(), "appended 7 characters");
+
+        let diff = TextDiff::compute("Invoice 1042", "Invoice 10", DiffGranularity::Character);
+        assert_eq!(diff.kind, ChangeKind::Truncate);
+        assert_eq!(diff.deleted_chars, 2);
+
+        let diff = TextDiff::compute("hello", "world", DiffGranularity::Character);
+        assert_eq!(diff.kind, ChangeKind::ReplaceAll);
+        assert_eq!(diff.summary(), "replaced entire value");
+
+        let diff = TextDiff::compute("Dear John Smith,", "Dear Jane Smith,", DiffGranularity::Token);
+        assert_eq!(diff.kind, ChangeKind::Replace);
+        assert_eq!(
+            diff.ops,
+            vec![
+                DiffOp::Equal("Dear ".to_string()),
+                DiffOp::Replace { from: "John".to_string(), to: "Jane".to_string() },
+                DiffOp::Equal(" Smith,".to_string()),
+            ]
+        );
+        assert_eq!(diff.summary(), "replaced 4 characters with 4 characters");
+
+        let diff = TextDiff::compute("a quick fox", "a very quick brown fox", DiffGranularity::Token);
+        assert_eq!(diff.kind, ChangeKind::Edit);
+        assert_eq!(diff.deleted_chars, 0);
+
+        assert_eq!(TextDiff::compute("abc", "", DiffGranularity::Character).kind, ChangeKind::Clear);
+        assert_eq!(TextDiff::compute("abc", "abc", DiffGranularity::Token).kind, ChangeKind::Unchanged);
+    }
+}
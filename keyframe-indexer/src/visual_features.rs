@@ -0,0 +1,193 @@
+use image::{DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Luma step between neighbouring pixels that counts as a sharp edge
+const EDGE_STEP: i16 = 64;
+/// Side of the blocks text density is estimated over
+const TEXT_BLOCK: u32 = 16;
+/// Share of edge pixels at which a block looks like text
+const TEXT_EDGE_FRACTION: f32 = 0.08;
+/// Colors kept in the palette
+const PALETTE_SIZE: usize = 5;
+/// Pixels sampled for the palette, at most
+const PALETTE_SAMPLES: u64 = 65_536;
+
+/// A palette color and how much of the frame it covers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DominantColor {
+    pub rgb: [u8; 3],
+    /// Percentage (0-100) of the frame in this color, after quantization
+    pub share: f32,
+}
+
+impl DominantColor {
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.rgb[0], self.rgb[1], self.rgb[2])
+    }
+
+    /// Parse `#rrggbb` back into a color
+    pub fn from_hex(hex: &str, share: f32) -> Option<Self> {
+        let digits = hex.strip_prefix('#')?;
+        if digits.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+        Some(Self { rgb: [channel(0)?, channel(2)?, channel(4)?], share })
+    }
+}
+
+/// Palette as text for CSV and SQLite, e.g. `#1e1e1e:75;#c8c8c8:25`
+pub fn palette_to_text(colors: &[DominantColor]) -> String {
+    colors.iter().map(|c| format!("{}:{}", c.hex(), c.share)).collect::<Vec<_>>().join(";")
+}
+
+/// Parse a palette written by [`palette_to_text`], skipping malformed colors
+pub fn palette_from_text(text: &str) -> Vec<DominantColor> {
+    text.split(';')
+        .filter_map(|color| {
+            let (hex, share) = color.split_once(':')?;
+            DominantColor::from_hex(hex, share.parse().ok()?)
+        })
+        .collect()
+}
+
+/// Cheap appearance features of a keyframe for telling screen types apart,
+/// e.g. a dark terminal from a white browser page or a busy IDE
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VisualFeatures {
+    /// Most common colors, most common first
+    pub dominant_colors: Vec<DominantColor>,
+    /// Percentage (0-100) of pixels on a sharp luma edge
+    pub edge_density: f32,
+    /// Percentage (0-100) of the frame whose edge pattern looks like text,
+    /// estimated without OCR
+    pub ui_text_density: f32,
+}
+
+impl VisualFeatures {
+    pub fn from_image(image: &DynamicImage) -> Self {
+        let gray = image.to_luma8();
+        let pixels = (gray.width() as u64 * gray.height() as u64).max(1);
+        let edges = edge_map(&gray).iter().filter(|edge| **edge).count() as u64;
+        let blocks = text_like_blocks(&gray, TEXT_BLOCK, TEXT_EDGE_FRACTION);
+        let text_blocks = blocks.iter().filter(|text| **text).count();
+
+        Self {
+            dominant_colors: dominant_colors(image),
+            edge_density: edges as f32 / pixels as f32 * 100.0,
+            ui_text_density: text_blocks as f32 / blocks.len().max(1) as f32 * 100.0,
+        }
+    }
+}
+
+/// Pixels whose right or lower neighbour differs by a sharp luma step, row by row
+pub(crate) fn edge_map(gray: &GrayImage) -> Vec<bool> {
+    let (width, height) = gray.dimensions();
+    let mut edges = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let here = gray.get_pixel(x, y)[0] as i16;
+            let right = if x + 1 < width { gray.get_pixel(x + 1, y)[0] as i16 } else { here };
+            let below = if y + 1 < height { gray.get_pixel(x, y + 1)[0] as i16 } else { here };
+            edges.push((here - right).abs() > EDGE_STEP || (here - below).abs() > EDGE_STEP);
+        }
+    }
+    edges
+}
+
+/// Whether each `block`-sized block, row by row, has at least `edge_fraction`
+/// of its pixels on a sharp edge, the way glyphs do
+pub(crate) fn text_like_blocks(gray: &GrayImage, block: u32, edge_fraction: f32) -> Vec<bool> {
+    let (width, height) = gray.dimensions();
+    let cols = width.div_ceil(block);
+    let rows = height.div_ceil(block);
+    let mut counts = vec![0u32; (cols * rows) as usize];
+    for (index, edge) in edge_map(gray).into_iter().enumerate() {
+        if edge {
+            let (x, y) = (index as u32 % width, index as u32 / width);
+            counts[((y / block) * cols + x / block) as usize] += 1;
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .map(|(index, count)| {
+            let (col, row) = (index as u32 % cols, index as u32 / cols);
+            let block_width = (width - col * block).min(block);
+            let block_height = (height - row * block).min(block);
+            *count as f32 / (block_width * block_height).max(1) as f32 >= edge_fraction
+        })
+        .collect()
+}
+
+/// Palette from a sample of pixels quantized to 4 bits per channel; each
+/// color is the mean of the pixels in its bucket
+fn dominant_colors(image: &DynamicImage) -> Vec<DominantColor> {
+    let rgb = image.to_rgb8();
+    let pixels = rgb.width() as u64 * rgb.height() as u64;
+    if pixels == 0 {
+        return Vec::new();
+    }
+    let stride = ((pixels / PALETTE_SAMPLES) as f64).sqrt().max(1.0) as u32;
+
+    let mut buckets: HashMap<u16, (u32, [u64; 3])> = HashMap::new();
+    let mut sampled = 0u32;
+    for y in (0..rgb.height()).step_by(stride as usize) {
+        for x in (0..rgb.width()).step_by(stride as usize) {
+            let [r, g, b] = rgb.get_pixel(x, y).0;
+            let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+            let bucket = buckets.entry(key).or_insert((0, [0; 3]));
+            bucket.0 += 1;
+            for (sum, value) in bucket.1.iter_mut().zip([r, g, b]) {
+                *sum += value as u64;
+            }
+            sampled += 1;
+        }
+    }
+
+    let mut palette: Vec<(u16, (u32, [u64; 3]))> = buckets.into_iter().collect();
+    // Ties go to the lower bucket so the palette is stable
+    palette.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(&b.0)));
+    palette
+        .into_iter()
+        .take(PALETTE_SIZE)
+        .map(|(_, (count, sums))| DominantColor {
+            rgb: sums.map(|sum| (sum / count as u64) as u8),
+            share: count as f32 / sampled as f32 * 100.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_and_blank_page_differ() {
+        // Dark terminal with rows of light "glyphs"
+        let terminal = DynamicImage::ImageRgb8(image::RgbImage::from_fn(160, 96, |x, y| {
+            if y % 12 < 8 && x % 4 < 2 && x < 120 { image::Rgb([200, 200, 200]) } else { image::Rgb([30, 30, 30]) }
+        }));
+        let blank = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(160, 96, image::Rgb([255, 255, 255])));
+
+        let terminal = VisualFeatures::from_image(&terminal);
+        let blank = VisualFeatures::from_image(&blank);
+
+        assert_eq!(terminal.dominant_colors[0].hex(), "#1e1e1e");
+        assert_eq!(terminal.dominant_colors[1].rgb, [200, 200, 200]);
+        let total: f32 = terminal.dominant_colors.iter().map(|c| c.share).sum();
+        assert!((total - 100.0).abs() < 0.01);
+        assert!(terminal.edge_density > 20.0);
+        // The glyph area is 120 of 160 columns wide
+        assert!((terminal.ui_text_density - 80.0).abs() < 5.0);
+
+        assert_eq!(blank.dominant_colors.len(), 1);
+        assert_eq!(blank.dominant_colors[0].share, 100.0);
+        assert_eq!((blank.edge_density, blank.ui_text_density), (0.0, 0.0));
+        assert_eq!(DominantColor::from_hex("#1e1e1e", 50.0), Some(DominantColor { rgb: [30, 30, 30], share: 50.0 }));
+        assert_eq!(palette_to_text(&terminal.dominant_colors[..1]), format!("#1e1e1e:{}", terminal.dominant_colors[0].share));
+        assert_eq!(palette_from_text(&palette_to_text(&terminal.dominant_colors)), terminal.dominant_colors);
+        assert!(palette_from_text("").is_empty());
+    }
+}
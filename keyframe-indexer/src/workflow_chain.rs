@@ -0,0 +1,651 @@
+use crate::error::Result;
+use crate::event_correlator::{CorrelationConfig, CorrelationEvent, CorrelationEventType, CorrelationResult, EventCorrelator};
+use crate::event_detector::DetectedEvent;
+use crate::layout_compat::{self, CompatibleTable};
+use arrow::array::{
+    Array, Float32Array, ListArray, ListBuilder, StringArray, StringBuilder, TimestampNanosecondArray,
+    TimestampNanosecondBuilder, UInt32Array, UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Duration, Utc};
+use datafusion::prelude::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Configuration for stitching pairwise correlations into chains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowChainConfig {
+    /// Build chains from the events the service publishes
+    pub enabled: bool,
+    /// Directory of the chain Parquet files; defaults to `<output_dir>/workflow_chains`
+    pub dir: Option<String>,
+    /// Fewest steps a chain needs; shorter groups stay plain correlations
+    pub min_steps: usize,
+    /// A chain is complete once no step has been added for this long (ms)
+    pub max_step_gap_ms: i64,
+    /// Correlations below this confidence do not link steps
+    pub min_link_confidence: f32,
+}
+
+impl Default for WorkflowChainConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            min_steps: 3,
+            max_step_gap_ms: 10_000,
+            min_link_confidence: 0.6,
+        }
+    }
+}
+
+/// Role of one event in a workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowStepKind {
+    Click,
+    FieldChange,
+    Submit,
+    Navigation,
+    Scroll,
+    Modal,
+    Error,
+}
+
+impl WorkflowStepKind {
+    /// Cursor movement links steps but is not a step itself
+    pub fn from_event_type(event_type: &CorrelationEventType) -> Option<Self> {
+        match event_type {
+            CorrelationEventType::CursorMovement => None,
+            CorrelationEventType::CursorClick => Some(Self::Click),
+            CorrelationEventType::FieldChange => Some(Self::FieldChange),
+            CorrelationEventType::FormSubmission => Some(Self::Submit),
+            CorrelationEventType::WindowChange
+            | CorrelationEventType::TabChange
+            | CorrelationEventType::FocusChange
+            | CorrelationEventType::ScreenChange => Some(Self::Navigation),
+            CorrelationEventType::Scroll => Some(Self::Scroll),
+            CorrelationEventType::ModalAppearance => Some(Self::Modal),
+            CorrelationEventType::ErrorDisplay => Some(Self::Error),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Click => "click",
+            Self::FieldChange => "field_change",
+            Self::Submit => "submit",
+            Self::Navigation => "navigation",
+            Self::Scroll => "scroll",
+            Self::Modal => "modal",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// How a workflow ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowOutcome {
+    /// Submitted and moved on without an error
+    Success,
+    /// An error was shown
+    Error,
+    /// Not submitted, or nothing followed the submission
+    Incomplete,
+}
+
+impl WorkflowOutcome {
+    fn of(steps: &[WorkflowStep]) -> Self {
+        if steps.iter().any(|s| s.kind == WorkflowStepKind::Error) {
+            return Self::Error;
+        }
+        match steps.iter().position(|s| s.kind == WorkflowStepKind::Submit) {
+            Some(submit) if steps[submit + 1..]
+                .iter()
+                .any(|s| matches!(s.kind, WorkflowStepKind::Navigation | WorkflowStepKind::Modal)) => Self::Success,
+            _ => Self::Incomplete,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Incomplete => "incomplete",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "success" => Self::Success,
+            "error" => Self::Error,
+            _ => Self::Incomplete,
+        }
+    }
+}
+
+/// One event in a workflow chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    pub event_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: WorkflowStepKind,
+    pub frame_id: String,
+    pub confidence: f32,
+}
+
+/// Multi-step workflow stitched together from pairwise correlations,
+/// e.g. click → field change → submit → navigation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowChain {
+    pub chain_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Steps in time order
+    pub steps: Vec<WorkflowStep>,
+    pub outcome: WorkflowOutcome,
+    /// Mean confidence of the links joining the steps
+    pub confidence: f32,
+    /// Correlations the chain was built from
+    pub correlation_ids: Vec<String>,
+}
+
+impl WorkflowChain {
+    pub fn duration_ms(&self) -> i64 {
+        (self.end - self.start).num_milliseconds()
+    }
+
+    pub fn step_kinds(&self) -> Vec<WorkflowStepKind> {
+        self.steps.iter().map(|s| s.kind).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChainLink {
+    correlation_id: String,
+    confidence: f32,
+}
+
+/// Stitches pairwise correlations into workflow chains.
+///
+/// Events joined by correlations form one chain; a chain is emitted once it
+/// has been idle for `max_step_gap_ms`, since later correlations could still
+/// extend it before then.
+pub struct WorkflowChainBuilder {
+    config: WorkflowChainConfig,
+    events: HashMap<String, CorrelationEvent>,
+    /// Links keyed by (earlier, later) event id
+    links: HashMap<(String, String), ChainLink>,
+}
+
+impl WorkflowChainBuilder {
+    pub fn new(config: WorkflowChainConfig) -> Self {
+        Self {
+            config,
+            events: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    /// Add correlations found among `events`, usually the correlator's recent events.
+    ///
+    /// A pair linked by several correlations keeps the first correlation id
+    /// and the highest confidence.
+    pub fn add_correlations<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a CorrelationEvent>,
+        correlations: &[CorrelationResult],
+    ) {
+        let known: HashMap<&str, &CorrelationEvent> = events.into_iter().map(|e| (e.id.as_str(), e)).collect();
+
+        for correlation in correlations {
+            if correlation.confidence < self.config.min_link_confidence {
+                continue;
+            }
+            for pair in correlation.correlated_events.windows(2) {
+                let (Some(first), Some(second)) = (known.get(pair[0].as_str()), known.get(pair[1].as_str())) else {
+                    continue;
+                };
+                if first.id == second.id {
+                    continue;
+                }
+                let (earlier, later) = if first.timestamp <= second.timestamp { (first, second) } else { (second, first) };
+
+                self.events.entry(earlier.id.clone()).or_insert_with(|| (*earlier).clone());
+                self.events.entry(later.id.clone()).or_insert_with(|| (*later).clone());
+
+                let link = self.links
+                    .entry((earlier.id.clone(), later.id.clone()))
+                    .or_insert_with(|| ChainLink {
+                        correlation_id: correlation.correlation_id.clone(),
+                        confidence: correlation.confidence,
+                    });
+                link.confidence = link.confidence.max(correlation.confidence);
+            }
+        }
+    }
+
+    /// Emit chains idle since before `now - max_step_gap_ms`
+    pub fn take_completed(&mut self, now: DateTime<Utc>) -> Vec<WorkflowChain> {
+        self.take_chains(Some(now - Duration::milliseconds(self.config.max_step_gap_ms)))
+    }
+
+    /// Emit every pending chain, e.g. at the end of a session
+    pub fn flush(&mut self) -> Vec<WorkflowChain> {
+        self.take_chains(None)
+    }
+
+    /// Events waiting to be emitted as part of a chain
+    pub fn pending_events(&self) -> usize {
+        self.events.len()
+    }
+
+    fn take_chains(&mut self, idle_before: Option<DateTime<Utc>>) -> Vec<WorkflowChain> {
+        let mut chains = Vec::new();
+
+        for component in self.components() {
+            let last = component.iter().filter_map(|id| self.events.get(id)).map(|e| e.timestamp).max();
+            if matches!((idle_before, last), (Some(cutoff), Some(last)) if last >= cutoff) {
+                continue;
+            }
+
+            let members: BTreeSet<&String> = component.iter().collect();
+            let links: Vec<ChainLink> = self.links
+                .iter()
+                .filter(|((from, _), _)| members.contains(from))
+                .map(|(_, link)| link.clone())
+                .collect();
+            let events: Vec<CorrelationEvent> = component.iter().filter_map(|id| self.events.get(id).cloned()).collect();
+
+            self.links.retain(|(from, _), _| !members.contains(from));
+            for id in &component {
+                self.events.remove(id);
+            }
+
+            if let Some(chain) = self.build_chain(events, &links) {
+                debug!("Built {}-step workflow chain ending in {}", chain.steps.len(), chain.outcome.as_str());
+                chains.push(chain);
+            }
+        }
+
+        chains.sort_by_key(|c| c.start);
+        chains
+    }
+
+    fn build_chain(&self, events: Vec<CorrelationEvent>, links: &[ChainLink]) -> Option<WorkflowChain> {
+        let mut steps: Vec<WorkflowStep> = events
+            .into_iter()
+            .filter_map(|event| {
+                let kind = WorkflowStepKind::from_event_type(&event.event_type)?;
+                Some(WorkflowStep {
+                    event_id: event.id,
+                    timestamp: event.timestamp,
+                    kind,
+                    frame_id: event.frame_id,
+                    confidence: event.confidence,
+                })
+            })
+            .collect();
+        if steps.len() < self.config.min_steps.max(2) || links.is_empty() {
+            return None;
+        }
+        steps.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.event_id.cmp(&b.event_id)));
+
+        let correlation_ids: BTreeSet<String> = links.iter().map(|l| l.correlation_id.clone()).collect();
+        Some(WorkflowChain {
+            chain_id: uuid::Uuid::new_v4().to_string(),
+            start: steps[0].timestamp,
+            end: steps[steps.len() - 1].timestamp,
+            outcome: WorkflowOutcome::of(&steps),
+            confidence: links.iter().map(|l| l.confidence).sum::<f32>() / links.len() as f32,
+            correlation_ids: correlation_ids.into_iter().collect(),
+            steps,
+        })
+    }
+
+    /// Groups of event ids connected by links
+    fn components(&self) -> Vec<Vec<String>> {
+        let ids: Vec<&String> = self.events.keys().collect();
+        let index: HashMap<&String, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut parent: Vec<usize> = (0..ids.len()).collect();
+
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for (from, to) in self.links.keys() {
+            if let (Some(&a), Some(&b)) = (index.get(from), index.get(to)) {
+                let (root_a, root_b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[root_a] = root_b;
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+        for (i, id) in ids.iter().enumerate() {
+            groups.entry(root(&mut parent, i)).or_default().push((*id).clone());
+        }
+        groups.into_values().collect()
+    }
+}
+
+/// Parquet output for workflow chains, one row per chain
+pub struct WorkflowChainParquetWriter {
+    output_dir: PathBuf,
+    schema: Arc<Schema>,
+    batch_size: usize,
+    current_batch: Vec<WorkflowChain>,
+}
+
+impl WorkflowChainParquetWriter {
+    pub fn new(output_dir: &str) -> Result<Self> {
+        let output_path = PathBuf::from(output_dir);
+        std::fs::create_dir_all(&output_path)?;
+
+        let string_list = || DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("chain_id", DataType::Utf8, false),
+            Field::new("start_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("end_ns", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+            Field::new("duration_ms", DataType::UInt64, false),
+            Field::new("step_count", DataType::UInt32, false),
+            Field::new("step_kinds", string_list(), false),
+            Field::new("event_ids", string_list(), false),
+            Field::new("outcome", DataType::Utf8, false),
+            Field::new("confidence", DataType::Float32, false),
+            Field::new("correlation_ids", string_list(), false),
+            Field::new("steps", DataType::Utf8, false), // JSON-encoded steps
+        ]));
+
+        Ok(Self {
+            output_dir: output_path,
+            schema,
+            batch_size: 200,
+            current_batch: Vec::new(),
+        })
+    }
+
+    pub async fn write_chains(&mut self, chains: &[WorkflowChain]) -> Result<()> {
+        self.current_batch.extend_from_slice(chains);
+        if self.current_batch.len() >= self.batch_size {
+            self.flush_batch().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered chains to disk
+    pub async fn flush_batch(&mut self) -> Result<()> {
+        if self.current_batch.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%f");
+        let file_path = self.output_dir.join(format!("workflow_chains_{}.parquet", timestamp));
+
+        let record_batch = self.create_record_batch(&self.current_batch)?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        let mut writer = ArrowWriter::try_new(File::create(&file_path)?, self.schema.clone(), Some(props))?;
+        writer.write(&record_batch)?;
+        writer.close()?;
+
+        info!("Wrote {} workflow chains to {}", self.current_batch.len(), file_path.display());
+        self.current_batch.clear();
+        Ok(())
+    }
+
+    pub async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch().await
+    }
+
+    /// Chains with the given outcome, oldest first
+    pub async fn query_by_outcome(&self, outcome: WorkflowOutcome) -> Result<Vec<WorkflowChain>> {
+        self.run_query(&format!(
+            "SELECT * FROM workflow_chains WHERE outcome = '{}' ORDER BY start_ns ASC",
+            outcome.as_str()
+        )).await
+    }
+
+    /// Chains that started within a time range, oldest first
+    pub async fn query_by_time_range(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<WorkflowChain>> {
+        self.run_query(&format!(
+            "SELECT * FROM workflow_chains WHERE start_ns >= {} AND start_ns <= {} ORDER BY start_ns ASC",
+            start_time.timestamp_nanos_opt().unwrap_or(0),
+            end_time.timestamp_nanos_opt().unwrap_or(0)
+        )).await
+    }
+
+    /// Register the stored chains as the `workflow_chains` table
+    pub async fn register_chains(&self, ctx: &SessionContext) -> Result<()> {
+        CompatibleTable::new("workflow_chains", self.schema.clone())
+            .register(ctx, &self.output_dir)
+            .await
+    }
+
+    pub fn get_output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    // MARK: - Private Helper Methods
+
+    async fn run_query(&self, sql: &str) -> Result<Vec<WorkflowChain>> {
+        if layout_compat::parquet_files(&self.output_dir)?.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = SessionContext::new();
+        self.register_chains(&ctx).await?;
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let mut chains = Vec::new();
+        for batch in &batches {
+            chains.extend(workflow_chains_from_batch(batch)?);
+        }
+        Ok(chains)
+    }
+
+    fn create_record_batch(&self, chains: &[WorkflowChain]) -> Result<RecordBatch> {
+        let mut start_builder = TimestampNanosecondBuilder::new();
+        let mut end_builder = TimestampNanosecondBuilder::new();
+        let mut kinds_builder = ListBuilder::new(StringBuilder::new());
+        let mut event_ids_builder = ListBuilder::new(StringBuilder::new());
+        let mut correlation_ids_builder = ListBuilder::new(StringBuilder::new());
+        let mut steps_json = Vec::with_capacity(chains.len());
+
+        for chain in chains {
+            start_builder.append_value(chain.start.timestamp_nanos_opt().unwrap_or(0));
+            end_builder.append_value(chain.end.timestamp_nanos_opt().unwrap_or(0));
+            for step in &chain.steps {
+                kinds_builder.values().append_value(step.kind.as_str());
+                event_ids_builder.values().append_value(&step.event_id);
+            }
+            kinds_builder.append(true);
+            event_ids_builder.append(true);
+            for id in &chain.correlation_ids {
+                correlation_ids_builder.values().append_value(id);
+            }
+            correlation_ids_builder.append(true);
+            steps_json.push(serde_json::to_string(&chain.steps)?);
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(StringArray::from(chains.iter().map(|c| c.chain_id.as_str()).collect::<Vec<_>>())),
+                Arc::new(start_builder.finish()),
+                Arc::new(end_builder.finish()),
+                Arc::new(UInt64Array::from(chains.iter().map(|c| c.duration_ms().max(0) as u64).collect::<Vec<_>>())),
+                Arc::new(UInt32Array::from(chains.iter().map(|c| c.steps.len() as u32).collect::<Vec<_>>())),
+                Arc::new(kinds_builder.finish()),
+                Arc::new(event_ids_builder.finish()),
+                Arc::new(StringArray::from(chains.iter().map(|c| c.outcome.as_str()).collect::<Vec<_>>())),
+                Arc::new(Float32Array::from(chains.iter().map(|c| c.confidence).collect::<Vec<_>>())),
+                Arc::new(correlation_ids_builder.finish()),
+                Arc::new(StringArray::from(steps_json)),
+            ],
+        )?;
+
+        Ok(record_batch)
+    }
+}
+
+/// Workflow chains of a batch read back from a chains file; a batch with
+/// unexpected columns is an error rather than a panic
+pub fn workflow_chains_from_batch(batch: &RecordBatch) -> Result<Vec<WorkflowChain>> {
+    let mut chains = Vec::with_capacity(batch.num_rows());
+
+    let ids = layout_compat::column::<StringArray>(batch, 0)?;
+    let starts = layout_compat::column::<TimestampNanosecondArray>(batch, 1)?;
+    let ends = layout_compat::column::<TimestampNanosecondArray>(batch, 2)?;
+    let outcomes = layout_compat::column::<StringArray>(batch, 7)?;
+    let confidences = layout_compat::column::<Float32Array>(batch, 8)?;
+    let correlation_ids = layout_compat::column::<ListArray>(batch, 9)?;
+    let steps = layout_compat::column::<StringArray>(batch, 10)?;
+
+    for i in 0..batch.num_rows() {
+        let chain_steps = serde_json::from_str(steps.value(i)).unwrap_or_else(|e| {
+            warn!("Failed to parse steps of workflow chain {}: {}", ids.value(i), e);
+            Vec::new()
+        });
+        let correlation_values = correlation_ids.value(i);
+        let chain_correlations = correlation_values
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .map(|values| values.iter().flatten().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        chains.push(WorkflowChain {
+            chain_id: ids.value(i).to_string(),
+            start: DateTime::from_timestamp_nanos(starts.value(i)),
+            end: DateTime::from_timestamp_nanos(ends.value(i)),
+            steps: chain_steps,
+            outcome: WorkflowOutcome::from_name(outcomes.value(i)),
+            confidence: confidences.value(i),
+            correlation_ids: chain_correlations,
+        });
+    }
+
+    Ok(chains)
+}
+
+/// Chains in stored events, correlated the way the service does while
+/// publishing them; for sessions recorded without `workflow_chains` enabled
+pub fn chains_from_events(
+    events: &[DetectedEvent],
+    correlation: CorrelationConfig,
+    config: WorkflowChainConfig,
+) -> Result<Vec<WorkflowChain>> {
+    let mut ordered: Vec<&DetectedEvent> = events.iter().collect();
+    ordered.sort_by_key(|e| e.timestamp);
+
+    let mut correlator = EventCorrelator::with_config(correlation);
+    let mut builder = WorkflowChainBuilder::new(config);
+    let mut chains = Vec::new();
+    for event in ordered {
+        correlator.add_detected_event(event);
+        let correlations = correlator.analyze_correlations(event.timestamp)?;
+        builder.add_correlations(correlator.get_recent_events(), &correlations);
+        chains.extend(builder.take_completed(event.timestamp));
+    }
+    chains.extend(builder.flush());
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_detector::EventType;
+    use tempfile::TempDir;
+
+    fn detected(id: &str, event_type: EventType, at: DateTime<Utc>) -> DetectedEvent {
+        DetectedEvent {
+            id: id.to_string(),
+            timestamp: at,
+            event_type,
+            target: id.to_string(),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: vec![format!("frame_{}", id)],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_form_workflow_becomes_one_chain() {
+        let start = Utc::now();
+        let at = |ms: i64| start + Duration::milliseconds(ms);
+
+        let mut correlator = EventCorrelator::new();
+        correlator.add_detected_event(&detected("name", EventType::FieldChange, at(100)));
+        correlator.add_detected_event(&detected("email", EventType::FieldChange, at(600)));
+        correlator.add_detected_event(&detected("submit", EventType::FormSubmission, at(1000)));
+        correlator.add_detected_event(&detected("next_page", EventType::Navigation, at(1400)));
+        let correlations = correlator.analyze_correlations(at(1500)).unwrap();
+
+        let mut builder = WorkflowChainBuilder::new(WorkflowChainConfig::default());
+        builder.add_correlations(correlator.get_recent_events(), &correlations);
+        // Still within the idle gap, so the chain could grow
+        assert!(builder.take_completed(at(2000)).is_empty());
+
+        let chains = builder.take_completed(at(12_000));
+        assert_eq!(chains.len(), 1);
+        assert_eq!(builder.pending_events(), 0);
+        let chain = &chains[0];
+        assert_eq!(chain.step_kinds(), vec![
+            WorkflowStepKind::FieldChange,
+            WorkflowStepKind::FieldChange,
+            WorkflowStepKind::Submit,
+            WorkflowStepKind::Navigation,
+        ]);
+        assert_eq!(chain.outcome, WorkflowOutcome::Success);
+        assert_eq!(chain.duration_ms(), 1300);
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = WorkflowChainParquetWriter::new(temp_dir.path().to_str().unwrap()).unwrap();
+        writer.write_chains(&chains).await.unwrap();
+        writer.finalize().await.unwrap();
+
+        let stored = writer.query_by_outcome(WorkflowOutcome::Success).await.unwrap();
+        assert_eq!(stored, chains);
+        assert!(writer.query_by_outcome(WorkflowOutcome::Error).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chains_from_stored_events() {
+        let start = Utc::now();
+        let at = |ms: i64| start + Duration::milliseconds(ms);
+        let events = vec![
+            detected("next_page", EventType::Navigation, at(1400)),
+            detected("name", EventType::FieldChange, at(100)),
+            detected("email", EventType::FieldChange, at(600)),
+            detected("submit", EventType::FormSubmission, at(1000)),
+        ];
+
+        let chains = chains_from_events(&events, CorrelationConfig::default(), WorkflowChainConfig::default()).unwrap();
+        assert_eq!(chains.len(), 1);
+        assert!(chains[0].step_kinds().contains(&WorkflowStepKind::Submit));
+    }
+}
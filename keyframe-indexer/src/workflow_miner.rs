@@ -0,0 +1,363 @@
+use crate::enum_codes::EnumCode;
+use crate::error::{IndexerError, Result};
+use crate::event_detector::{DetectedEvent, EventType};
+use crate::focus_summary::switched_to_app;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{info, warn};
+
+/// Longest sequence mined, whatever `max_steps` says; the search grows
+/// exponentially with the sequence length
+const MAX_STEPS_LIMIT: usize = 20;
+
+/// Settings for mining repeated action sequences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowMinerConfig {
+    /// Events further apart than this start a new session
+    pub session_gap_minutes: i64,
+    /// Fewest sessions a sequence must occur in
+    pub min_support: usize,
+    /// Fewest steps in a reported sequence
+    pub min_steps: usize,
+    /// Most steps in a sequence, at most 20; bounds the search
+    pub max_steps: usize,
+    /// Most sequences kept while mining; the search stops once this many are found
+    pub max_patterns: usize,
+    /// Events below this confidence are ignored
+    pub min_event_confidence: f32,
+    /// Most procedures returned, best first
+    pub max_procedures: usize,
+    /// Example occurrences kept per procedure
+    pub examples_per_procedure: usize,
+}
+
+impl Default for WorkflowMinerConfig {
+    fn default() -> Self {
+        Self {
+            session_gap_minutes: 30,
+            min_support: 3,
+            min_steps: 3,
+            max_steps: 8,
+            max_patterns: 10_000,
+            min_event_confidence: 0.5,
+            max_procedures: 20,
+            examples_per_procedure: 3,
+        }
+    }
+}
+
+impl WorkflowMinerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.session_gap_minutes <= 0 {
+            return Err(IndexerError::Config("workflow_miner session_gap_minutes must be positive".to_string()));
+        }
+        if self.min_support < 2 {
+            return Err(IndexerError::Config("workflow_miner min_support must be at least 2".to_string()));
+        }
+        if self.min_steps < 2 || self.max_steps < self.min_steps {
+            return Err(IndexerError::Config(
+                "workflow_miner needs 2 <= min_steps <= max_steps".to_string(),
+            ));
+        }
+        if self.max_steps > MAX_STEPS_LIMIT {
+            return Err(IndexerError::Config(format!(
+                "workflow_miner max_steps must be at most {}",
+                MAX_STEPS_LIMIT
+            )));
+        }
+        if self.max_patterns == 0 {
+            return Err(IndexerError::Config("workflow_miner max_patterns must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// One user action, independent of when it happened
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ActionStep {
+    /// Event type name, e.g. `field_change`
+    pub event_type: String,
+    /// Field, button or, for navigation, the app switched to
+    pub target: String,
+}
+
+impl ActionStep {
+    /// Action an event stands for; events that are not user actions have none
+    pub fn of(event: &DetectedEvent) -> Option<Self> {
+        let target = match event.event_type {
+            EventType::TriggerExecution | EventType::CaptureChange | EventType::StorageThrottle => return None,
+            // Text on screen, not something the user did
+            EventType::UrlDisplay | EventType::EmailDisplay | EventType::FilePathDisplay => return None,
+            EventType::CodeDiagnostic | EventType::BuildFailure | EventType::ProgressCompleted => return None,
+            EventType::Navigation => switched_to_app(event).unwrap_or_else(|| event.target.clone()),
+            _ => event.subject().to_string(),
+        };
+        Some(Self { event_type: event.event_type.name().to_string(), target })
+    }
+}
+
+impl std::fmt::Display for ActionStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.event_type, self.target)
+    }
+}
+
+/// Where a procedure was carried out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcedureExample {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Evidence frame of each step, in step order
+    pub frame_ids: Vec<String>,
+}
+
+/// A repeated action sequence: a candidate standard operating procedure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcedureCandidate {
+    pub steps: Vec<ActionStep>,
+    /// Number of sessions the steps occur in, in this order
+    pub support: usize,
+    pub examples: Vec<ProcedureExample>,
+}
+
+/// Finds frequently repeated multi-step action sequences across sessions
+/// with PrefixSpan. Steps of a sequence need not be adjacent, only in order;
+/// only closed sequences (not contained in a longer one with the same
+/// support) are reported.
+pub struct WorkflowMiner {
+    config: WorkflowMinerConfig,
+}
+
+impl WorkflowMiner {
+    pub fn new(config: WorkflowMinerConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { config })
+    }
+
+    /// Split events into sessions at gaps longer than `session_gap_minutes`
+    pub fn sessions<'a>(&self, events: &'a [DetectedEvent]) -> Vec<Vec<&'a DetectedEvent>> {
+        let mut events: Vec<&DetectedEvent> = events
+            .iter()
+            .filter(|e| e.confidence >= self.config.min_event_confidence)
+            .collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let gap = Duration::minutes(self.config.session_gap_minutes);
+        let mut sessions: Vec<Vec<&DetectedEvent>> = Vec::new();
+        for event in events {
+            match sessions.last_mut() {
+                Some(session) if event.timestamp - session[session.len() - 1].timestamp <= gap => session.push(event),
+                _ => sessions.push(vec![event]),
+            }
+        }
+        sessions
+    }
+
+    /// Mine procedures from events spanning any number of sessions, most support first
+    pub fn mine(&self, events: &[DetectedEvent]) -> Vec<ProcedureCandidate> {
+        let sessions: Vec<Vec<(&DetectedEvent, ActionStep)>> = self
+            .sessions(events)
+            .into_iter()
+            .map(|session| session.into_iter().filter_map(|e| ActionStep::of(e).map(|step| (e, step))).collect())
+            .collect();
+
+        // Intern steps so sequences are compact and items order deterministically
+        let mut vocabulary: BTreeMap<ActionStep, u32> = BTreeMap::new();
+        for (_, step) in sessions.iter().flatten() {
+            let next = vocabulary.len() as u32;
+            vocabulary.entry(step.clone()).or_insert(next);
+        }
+        let steps_by_id: HashMap<u32, &ActionStep> = vocabulary.iter().map(|(step, id)| (*id, step)).collect();
+        let sequences: Vec<Vec<u32>> = sessions
+            .iter()
+            .map(|session| session.iter().map(|(_, step)| vocabulary[step]).collect())
+            .collect();
+
+        let projected: Vec<(usize, usize)> = (0..sequences.len()).map(|s| (s, 0)).collect();
+        let mut patterns = Vec::new();
+        self.grow(&sequences, &mut Vec::new(), &projected, &mut patterns);
+        if patterns.len() >= self.config.max_patterns {
+            warn!("Stopped mining at {} sequences; raise workflow_miner.max_patterns to search further", patterns.len());
+        }
+        let mut patterns = closed(patterns);
+        patterns.sort_by(|a, b| {
+            (b.1.len() * b.0.len())
+                .cmp(&(a.1.len() * a.0.len()))
+                .then_with(|| b.0.len().cmp(&a.0.len()))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        patterns.truncate(self.config.max_procedures);
+
+        let procedures: Vec<ProcedureCandidate> = patterns
+            .into_iter()
+            .map(|(items, supporting)| ProcedureCandidate {
+                steps: items.iter().map(|id| steps_by_id[id].clone()).collect(),
+                support: supporting.len(),
+                examples: supporting
+                    .iter()
+                    .take(self.config.examples_per_procedure)
+                    .filter_map(|&s| example(&sessions[s], &sequences[s], &items))
+                    .collect(),
+            })
+            .collect();
+        info!("Mined {} procedures from {} sessions", procedures.len(), sequences.len());
+        procedures
+    }
+
+    /// Record `prefix` and extend it by every item frequent in its projected
+    /// database. `projected` holds, per supporting sequence, where the rest of
+    /// it starts.
+    fn grow(
+        &self,
+        sequences: &[Vec<u32>],
+        prefix: &mut Vec<u32>,
+        projected: &[(usize, usize)],
+        patterns: &mut Vec<(Vec<u32>, Vec<usize>)>,
+    ) {
+        if patterns.len() >= self.config.max_patterns {
+            return;
+        }
+        let mut extensions: BTreeMap<u32, Vec<(usize, usize)>> = BTreeMap::new();
+        if prefix.len() < self.config.max_steps {
+            for &(s, start) in projected {
+                let mut seen = HashSet::new();
+                for (offset, &item) in sequences[s][start..].iter().enumerate() {
+                    if seen.insert(item) {
+                        extensions.entry(item).or_default().push((s, start + offset + 1));
+                    }
+                }
+            }
+        }
+
+        // A prefix every supporting session continues with the same item is
+        // not closed; the longer sequence is recorded instead
+        let extends_fully = extensions.values().any(|next| next.len() == projected.len());
+        if prefix.len() >= self.config.min_steps && !extends_fully {
+            patterns.push((prefix.clone(), projected.iter().map(|(s, _)| *s).collect()));
+        }
+
+        for (item, next) in extensions {
+            if next.len() < self.config.min_support {
+                continue;
+            }
+            prefix.push(item);
+            self.grow(sequences, prefix, &next, patterns);
+            prefix.pop();
+        }
+    }
+}
+
+/// Drop patterns contained in a longer pattern found in the same sessions.
+/// Forward extensions are ruled out while mining, so only patterns sharing
+/// their supporting sessions are compared.
+fn closed(patterns: Vec<(Vec<u32>, Vec<usize>)>) -> Vec<(Vec<u32>, Vec<usize>)> {
+    let mut by_support: HashMap<&[usize], Vec<&[u32]>> = HashMap::new();
+    for (items, supporting) in &patterns {
+        by_support.entry(supporting.as_slice()).or_default().push(items.as_slice());
+    }
+    patterns
+        .iter()
+        .filter(|(items, supporting)| {
+            !by_support[supporting.as_slice()]
+                .iter()
+                .any(|other| other.len() > items.len() && is_subsequence(items, other))
+        })
+        .cloned()
+        .collect()
+}
+
+fn is_subsequence(needle: &[u32], haystack: &[u32]) -> bool {
+    let mut rest = haystack.iter();
+    needle.iter().all(|item| rest.any(|other| other == item))
+}
+
+/// Earliest occurrence of `items` in a session
+fn example(session: &[(&DetectedEvent, ActionStep)], sequence: &[u32], items: &[u32]) -> Option<ProcedureExample> {
+    let mut positions = Vec::with_capacity(items.len());
+    let mut from = 0;
+    for item in items {
+        let position = from + sequence[from..].iter().position(|other| other == item)?;
+        positions.push(position);
+        from = position + 1;
+    }
+    let events: Vec<&DetectedEvent> = positions.iter().map(|&p| session[p].0).collect();
+    Some(ProcedureExample {
+        start: events.first()?.timestamp,
+        end: events.last()?.timestamp,
+        frame_ids: events.iter().filter_map(|e| e.evidence_frames.first().cloned()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn event(session: i64, second: i64, event_type: EventType, target: &str) -> DetectedEvent {
+        DetectedEvent {
+            id: format!("event_{}_{}", session, second),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap() + Duration::hours(session) + Duration::seconds(second),
+            event_type,
+            target: target.to_string(),
+            value_from: None,
+            value_to: None,
+            confidence: 0.9,
+            evidence_frames: vec![format!("frame_{}_{}", session, second)],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_mines_repeated_procedure_across_sessions() {
+        let mut events = Vec::new();
+        for session in 0..4 {
+            events.push(event(session, 0, EventType::FieldChange, "vendor"));
+            // Noise that differs between sessions
+            events.push(event(session, 5, EventType::FieldChange, &format!("note_{}", session)));
+            events.push(event(session, 10, EventType::FieldChange, "amount"));
+            events.push(event(session, 20, EventType::FormSubmission, "approve"));
+        }
+        // A session that stops before submitting
+        events.push(event(5, 0, EventType::FieldChange, "vendor"));
+        events.push(event(5, 10, EventType::FieldChange, "amount"));
+
+        let miner = WorkflowMiner::new(WorkflowMinerConfig::default()).unwrap();
+        assert_eq!(miner.sessions(&events).len(), 5);
+
+        let procedures = miner.mine(&events);
+        assert_eq!(procedures.len(), 1);
+        let procedure = &procedures[0];
+        let steps: Vec<String> = procedure.steps.iter().map(|s| s.to_string()).collect();
+        assert_eq!(steps, ["field_change vendor", "field_change amount", "form_submission approve"]);
+        assert_eq!(procedure.support, 4);
+        assert_eq!(procedure.examples.len(), 3);
+        assert_eq!(procedure.examples[0].frame_ids, ["frame_0_0", "frame_0_10", "frame_0_20"]);
+    }
+
+    #[test]
+    fn test_pattern_cap_bounds_the_search() {
+        // The same twelve distinct steps in five sessions hold thousands of subsequences
+        let mut events = Vec::new();
+        for session in 0..5 {
+            for step in 0..12 {
+                events.push(event(session, step, EventType::FieldChange, &format!("field_{}", step)));
+            }
+        }
+
+        let config = WorkflowMinerConfig {
+            max_steps: 12,
+            max_patterns: 50,
+            max_procedures: 1_000,
+            ..WorkflowMinerConfig::default()
+        };
+        let procedures = WorkflowMiner::new(config).unwrap().mine(&events);
+        assert!(!procedures.is_empty());
+        assert!(procedures.len() <= 50);
+
+        let too_long = WorkflowMinerConfig { max_steps: 40, ..WorkflowMinerConfig::default() };
+        assert!(WorkflowMiner::new(too_long).is_err());
+    }
+}
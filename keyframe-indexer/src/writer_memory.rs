@@ -0,0 +1,246 @@
+use crate::error::{IndexerError, Result};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Directory under a writer's output directory holding spilled records; the
+/// leading underscore keeps it out of every listing of data files
+pub const SPILL_DIR: &str = "_spill";
+
+/// Memory limits of the Parquet writers' buffers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriterMemoryConfig {
+    /// Bytes all writers sharing a budget may buffer before they spill to disk
+    pub max_buffered_bytes: usize,
+    /// Buffered and spilled bytes at which a writer flushes a file, whatever
+    /// its row count
+    pub flush_threshold_bytes: usize,
+}
+
+impl Default for WriterMemoryConfig {
+    fn default() -> Self {
+        Self {
+            max_buffered_bytes: 256 * 1024 * 1024,
+            flush_threshold_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl WriterMemoryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_buffered_bytes == 0 {
+            return Err(IndexerError::Config("max_buffered_bytes must be greater than 0".to_string()));
+        }
+        if self.flush_threshold_bytes == 0 {
+            return Err(IndexerError::Config("flush_threshold_bytes must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    limit: usize,
+    flush_threshold: usize,
+    used: AtomicUsize,
+}
+
+/// Byte budget shared by the writers it is handed to; clones share it
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    state: Arc<BudgetState>,
+}
+
+impl MemoryBudget {
+    pub fn new(config: &WriterMemoryConfig) -> Self {
+        Self {
+            state: Arc::new(BudgetState {
+                limit: config.max_buffered_bytes,
+                flush_threshold: config.flush_threshold_bytes,
+                used: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Bytes currently buffered by all writers on the budget
+    pub fn used(&self) -> usize {
+        self.state.used.load(Ordering::Relaxed)
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.used() > self.state.limit
+    }
+
+    pub fn flush_threshold_bytes(&self) -> usize {
+        self.state.flush_threshold
+    }
+
+    /// An empty reservation against this budget
+    pub fn reservation(&self) -> MemoryReservation {
+        MemoryReservation { budget: self.clone(), bytes: 0 }
+    }
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self::new(&WriterMemoryConfig::default())
+    }
+}
+
+/// Bytes one writer holds against a budget, given back when cleared or dropped
+#[derive(Debug)]
+pub struct MemoryReservation {
+    budget: MemoryBudget,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    pub fn grow(&mut self, bytes: usize) {
+        self.bytes += bytes;
+        self.budget.state.used.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn clear(&mut self) {
+        self.budget.state.used.fetch_sub(self.bytes, Ordering::Relaxed);
+        self.bytes = 0;
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Record batches moved out of memory until the writer's next flush
+#[derive(Debug)]
+pub struct SpillBuffer {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    rows: usize,
+    bytes: usize,
+}
+
+impl SpillBuffer {
+    /// Spill into `<output_dir>/_spill`, created on the first spill
+    pub fn new(output_dir: &Path) -> Self {
+        Self { dir: output_dir.join(SPILL_DIR), files: Vec::new(), rows: 0, bytes: 0 }
+    }
+
+    /// Write `batch` to a spill file; `bytes` is what it held in memory
+    pub fn spill(&mut self, batch: &RecordBatch, bytes: usize) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("spill_{}.arrow", uuid::Uuid::new_v4()));
+        let mut writer = FileWriter::try_new(File::create(&path)?, &batch.schema())?;
+        writer.write(batch)?;
+        writer.finish()?;
+
+        debug!("Spilled {} rows ({} bytes) to {}", batch.num_rows(), bytes, path.display());
+        self.files.push(path);
+        self.rows += batch.num_rows();
+        self.bytes += bytes;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// In-memory size of the spilled records
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Hand every spilled batch to `f` in spill order, removing each file
+    /// once read; only one spill file is in memory at a time
+    pub fn drain(&mut self, mut f: impl FnMut(RecordBatch) -> Result<()>) -> Result<()> {
+        for path in std::mem::take(&mut self.files) {
+            for batch in FileReader::try_new(File::open(&path)?, None)? {
+                f(batch?)?;
+            }
+            std::fs::remove_file(&path)?;
+        }
+        self.rows = 0;
+        self.bytes = 0;
+        Ok(())
+    }
+}
+
+impl Drop for SpillBuffer {
+    fn drop(&mut self) {
+        for path in &self.files {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("Failed to remove spill file {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_compat;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    #[test]
+    fn test_budget_and_spill() {
+        let budget = MemoryBudget::new(&WriterMemoryConfig { max_buffered_bytes: 100, flush_threshold_bytes: 50 });
+        let mut first = budget.reservation();
+        let mut second = budget.reservation();
+        first.grow(60);
+        second.grow(60);
+        assert!(budget.is_exceeded());
+        first.clear();
+        assert_eq!(budget.used(), 60);
+        drop(second);
+        assert_eq!(budget.used(), 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("text", DataType::Utf8, false),
+        ]));
+        let batch = |ids: Vec<i64>| {
+            let texts: Vec<String> = ids.iter().map(|id| format!("row {}", id)).collect();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(ids)), Arc::new(StringArray::from(texts))])
+                .unwrap()
+        };
+
+        let mut spill = SpillBuffer::new(dir.path());
+        spill.spill(&batch(vec![1, 2]), 40).unwrap();
+        spill.spill(&batch(vec![3]), 20).unwrap();
+        assert_eq!((spill.rows(), spill.bytes()), (3, 60));
+
+        let mut ids = Vec::new();
+        spill
+            .drain(|batch| {
+                let column = layout_compat::column::<Int64Array>(&batch, 0)?;
+                ids.extend(column.values().iter().copied());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(spill.is_empty());
+        assert_eq!(std::fs::read_dir(dir.path().join(SPILL_DIR)).unwrap().count(), 0);
+    }
+}
@@ -1,8 +1,12 @@
-use keyframe_indexer::{IndexerService, IndexerConfig};
+use keyframe_indexer::{
+    BoundingBox, CorrelationParquetWriter, CorrelationType, DeadLetterStore, ErrorRecoveryConfig, FailedItemKind,
+    IndexerService, IndexerConfig, OCRParquetWriter, OCRResult, OutputStep,
+};
 use keyframe_indexer::scene_detector::{SceneDetector, SceneChangeType};
 use keyframe_indexer::keyframe_extractor::{KeyframeExtractor, Keyframe};
 use keyframe_indexer::config::SceneDetectionConfig;
 use tempfile::TempDir;
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::Path;
 use tokio::time::{timeout, Duration};
@@ -228,6 +232,88 @@ async fn test_corrupted_file_handling() {
     // 4. Check that error logging is appropriate
 }
 
+#[tokio::test]
+async fn test_retry_failed_reprocesses_dead_letters() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = IndexerConfig {
+        output_dir: temp_dir.path().join("output").to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let screenshot = temp_dir.path().join("screenshot.png");
+    save_test_image(&create_gradient_image(320, 240, true), &screenshot).unwrap();
+    let watched = temp_dir.path().join("watched.mp4");
+    fs::write(&watched, b"this is not a valid video file").unwrap();
+    let gone = temp_dir.path().join("gone.png");
+    
+    {
+        let mut store = DeadLetterStore::open(config.dead_letter_dir()).unwrap();
+        let error = anyhow::anyhow!("disk full");
+        store.record_failure(&screenshot, FailedItemKind::Frame, &error, BTreeSet::new()).unwrap();
+        let written = BTreeSet::from([OutputStep::FrameRows, OutputStep::SinkFrames(0)]);
+        store.record_failure(&watched, FailedItemKind::WatchedSegment, &error, written).unwrap();
+        store.record_failure(&gone, FailedItemKind::Frame, &error, BTreeSet::new()).unwrap();
+    }
+    
+    let mut service = IndexerService::new(config.clone()).unwrap();
+    let summary = service.retry_failed(None, None).await.unwrap();
+    assert_eq!((summary.retried, summary.recovered, summary.still_failing), (2, 1, 1));
+    assert_eq!((summary.missing, summary.skipped), (1, 0));
+    
+    // The watched segment failed again through the watch path, keeping what it had written
+    let store = DeadLetterStore::open(config.dead_letter_dir()).unwrap();
+    assert_eq!(store.len(), 1);
+    let entry = store.get(&watched).unwrap();
+    assert_eq!((entry.kind, entry.retry_count), (FailedItemKind::WatchedSegment, 1));
+    assert!(entry.committed.contains(&OutputStep::SinkFrames(0)));
+    
+    let summary = service.retry_failed(None, Some(1)).await.unwrap();
+    assert_eq!((summary.retried, summary.skipped), (0, 1));
+}
+
+#[tokio::test]
+async fn test_published_correlations_are_stored() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = IndexerConfig {
+        output_dir: temp_dir.path().join("output").to_string_lossy().to_string(),
+        error_recovery: ErrorRecoveryConfig { enabled: true, ..Default::default() },
+        ..Default::default()
+    };
+    let screenshots = temp_dir.path().join("screenshots");
+    fs::create_dir_all(&screenshots).unwrap();
+    save_test_image(&create_gradient_image(400, 300, true), &screenshots.join("checkout.png")).unwrap();
+    
+    // A submitted form answered by an error, read from the same screenshot
+    let text = |text: &str, y: f32| OCRResult {
+        frame_id: "checkout".to_string(),
+        roi: BoundingBox::new(100.0, y, 160.0, 20.0),
+        text: text.to_string(),
+        language: "en-US".to_string(),
+        confidence: 0.95,
+        processed_at: chrono::Utc::now(),
+        processor: "vision".to_string(),
+    };
+    let mut ocr_store = OCRParquetWriter::new(&temp_dir.path().join("ocr").to_string_lossy()).unwrap();
+    ocr_store
+        .write_ocr_results(&[text("Submit order", 100.0), text("Error: payment declined", 160.0)])
+        .await
+        .unwrap();
+    ocr_store.flush_batch().await.unwrap();
+    
+    let mut service = IndexerService::new(config.clone()).unwrap();
+    let summary = service.analyze_directory(&screenshots, Some(&ocr_store)).await.unwrap();
+    assert_eq!(summary.images_analyzed, 1);
+    service.shutdown().await.unwrap();
+    
+    let stored = CorrelationParquetWriter::new(&config.correlations_dir().to_string_lossy())
+        .unwrap()
+        .query_by_confidence(0.0)
+        .await
+        .unwrap();
+    assert!(!stored.is_empty());
+    assert!(stored.iter().all(|correlation| correlation.correlated_events.len() == 2));
+    assert!(stored.iter().any(|correlation| correlation.correlation_type == CorrelationType::InteractionWorkflow));
+}
+
 // Helper functions for creating test images
 fn create_solid_color_image(width: u32, height: u32, color: [u8; 3]) -> DynamicImage {
     let img: RgbImage = ImageBuffer::from_fn(width, height, |_, _| {
@@ -288,6 +374,7 @@ async fn test_scene_detection_accuracy_with_known_samples() {
         ssim_threshold: 0.8,
         phash_distance_threshold: 10,
         entropy_threshold: 0.1,
+        ..Default::default()
     };
     let detector = SceneDetector::new(config).unwrap();
     
@@ -345,6 +432,8 @@ async fn create_fade_sequence(temp_dir: &TempDir, prefix: &str) -> Vec<Keyframe>
             width: 128,
             height: 128,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -369,6 +458,8 @@ async fn create_cut_sequence(temp_dir: &TempDir, prefix: &str) -> Vec<Keyframe>
             width: 128,
             height: 128,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -405,6 +496,8 @@ async fn create_motion_sequence(temp_dir: &TempDir, prefix: &str) -> Vec<Keyfram
             width: 128,
             height: 128,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -429,6 +522,8 @@ async fn create_static_sequence(temp_dir: &TempDir, prefix: &str) -> Vec<Keyfram
             width: 128,
             height: 128,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -457,6 +552,8 @@ async fn test_scene_detection_performance_requirements() {
             width: 64,
             height: 64,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -518,6 +615,8 @@ async fn create_similar_frames(temp_dir: &TempDir, prefix: &str) -> Vec<Keyframe
             width: 64,
             height: 64,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -542,6 +641,8 @@ async fn create_high_contrast_frames(temp_dir: &TempDir, prefix: &str) -> Vec<Ke
             width: 64,
             height: 64,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     
@@ -565,6 +666,8 @@ async fn create_noisy_frames(temp_dir: &TempDir, prefix: &str) -> Vec<Keyframe>
             width: 64,
             height: 64,
             format: "RGB24".to_string(),
+            source_fps: None,
+            display_id: None,
         });
     }
     